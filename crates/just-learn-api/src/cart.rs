@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+/// Guest carts are identified by this header, set on the response that
+/// created the cart; send it back with later cart requests and on login.
+pub const CART_TOKEN_HEADER: &str = "X-Cart-Token";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddCartItemReq {
+    pub product_id: i64,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateCartItemReq {
+    pub quantity: i32,
+}
+
+/// Enter a coupon code; it must exist and be usable now.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApplyCouponReq {
+    pub code: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CartLineResp {
+    pub product_id: i64,
+    pub name: String,
+    pub quantity: i32,
+    /// The product's current price.
+    pub unit_price: f64,
+    /// The price changed since the shopper last saw the cart.
+    pub price_changed: bool,
+    /// Active and in stock, not counting what other carts reserved;
+    /// available lines are reserved for this cart a while, the others do
+    /// not count towards the total.
+    pub available: bool,
+}
+
+/// A promotion that took something off the cart.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppliedPromotionResp {
+    pub promotion_id: i64,
+    pub name: String,
+    pub code: Option<String>,
+    /// `percentage`, `fixed`, `buy_x_get_y` or `free_shipping`.
+    pub kind: String,
+    /// Taken off the subtotal; free shipping shows in `shipping` instead.
+    pub discount: f64,
+    /// What it did, e.g. `10% off category 2`.
+    pub detail: String,
+}
+
+/// `total = subtotal - discount + shipping`, over the available lines.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CartResp {
+    pub lines: Vec<CartLineResp>,
+    pub subtotal: f64,
+    pub discount: f64,
+    pub shipping: f64,
+    pub total: f64,
+    /// The coupon code entered, upper case.
+    pub coupon: Option<String>,
+    /// In the order they were applied.
+    pub promotions: Vec<AppliedPromotionResp>,
+    /// Why the coupon takes nothing off, when it does not; checking out
+    /// fails until it is removed.
+    pub coupon_note: Option<String>,
+}
+
+impl CartResp {
+    /// What an empty cart looks like.
+    pub fn empty() -> Self {
+        Self {
+            lines: Vec::new(),
+            subtotal: 0.0,
+            discount: 0.0,
+            shipping: 0.0,
+            total: 0.0,
+            coupon: None,
+            promotions: Vec::new(),
+            coupon_note: None,
+        }
+    }
+}
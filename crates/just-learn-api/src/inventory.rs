@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+fn adjustment() -> String {
+    "adjustment".into()
+}
+
+/// Book stock received or corrected by hand; admin only.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdjustStockReq {
+    /// `receipt` or `adjustment` (the default).
+    #[serde(default = "adjustment")]
+    pub kind: String,
+    /// Signed; receipts only add.
+    pub quantity: i32,
+    pub reason: String,
+    /// The default warehouse if `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warehouse_id: Option<i64>,
+}
+
+/// Move stock between warehouses; admin only.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferStockReq {
+    pub from_warehouse_id: i64,
+    pub to_warehouse_id: i64,
+    pub quantity: i32,
+    pub reason: String,
+}
+
+/// A point on the globe, in degrees.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// Add a warehouse; admin only.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWarehouseReq {
+    pub name: String,
+    /// Lower ships first; defaults to 0.
+    #[serde(default)]
+    pub priority: i32,
+    /// Needed for nearest-warehouse allocation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WarehouseResp {
+    pub id: i64,
+    pub name: String,
+    pub priority: i32,
+    pub location: Option<Location>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StockMovementResp {
+    pub id: i64,
+    pub product_id: i64,
+    pub warehouse_id: i64,
+    /// `receipt`, `sale`, `return`, `adjustment`, `reservation` or
+    /// `transfer`.
+    pub kind: String,
+    /// Signed: positive added to the stock, negative took from it.
+    pub quantity: i32,
+    pub reason: Option<String>,
+    /// Who booked it; `None` for the system or once their account is deleted.
+    pub actor_id: Option<i64>,
+    /// The order behind a sale or return.
+    pub order_id: Option<i64>,
+    /// RFC 3339, UTC.
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StockDiscrepancyResp {
+    pub product_id: i64,
+    pub name: String,
+    pub stock: i32,
+    /// What the product's movements add up to.
+    pub ledger: i64,
+    /// `stock - ledger`.
+    pub difference: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReconciliationResp {
+    pub products_checked: usize,
+    /// Empty when every stock matches its ledger.
+    pub discrepancies: Vec<StockDiscrepancyResp>,
+}
+
+/// Set a product's reorder threshold; admin only.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StockThresholdReq {
+    /// `null` clears it.
+    pub threshold: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StockThresholdResp {
+    pub product_id: i64,
+    pub threshold: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LowStockProductResp {
+    pub product_id: i64,
+    pub name: String,
+    pub stock: i32,
+    pub threshold: i32,
+}
+
+/// One category of the low-stock report.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LowStockCategoryResp {
+    pub category_id: i64,
+    pub category: String,
+    pub products: Vec<LowStockProductResp>,
+}
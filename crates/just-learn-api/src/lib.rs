@@ -0,0 +1,15 @@
+//! Wire types of the rust-just-learn REST API. The server serializes them
+//! and `just-learn-client` deserializes them, so both stay in step.
+
+pub mod cart;
+pub mod category;
+pub mod inventory;
+pub mod notification;
+pub mod order;
+pub mod page;
+pub mod payment;
+pub mod problem;
+pub mod product;
+pub mod promotion;
+pub mod purchasing;
+pub mod user;
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// `?unread=true` lists only the notifications nobody has read yet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct NotificationQuery {
+    #[serde(default)]
+    pub unread: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationResp {
+    pub id: i64,
+    /// What it is about, e.g. `low_stock`.
+    pub kind: String,
+    pub message: String,
+    pub product_id: Option<i64>,
+    /// RFC 3339, UTC.
+    pub created_at: String,
+    /// RFC 3339, UTC; `None` until marked read.
+    pub read_at: Option<String>,
+}
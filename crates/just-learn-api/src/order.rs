@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+use crate::inventory::Location;
+
+/// Optional body of a checkout.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CheckoutReq {
+    /// Where the order goes, for nearest-warehouse allocation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ship_to: Option<Location>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderLineResp {
+    pub product_id: i64,
+    /// Name and price as they were at checkout.
+    pub name: String,
+    pub quantity: i32,
+    pub unit_price: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderResp {
+    pub id: i64,
+    pub lines: Vec<OrderLineResp>,
+    /// Taken off by promotions.
+    pub discount: f64,
+    pub shipping: f64,
+    /// What the lines cost, less `discount`, plus `shipping`.
+    pub total: f64,
+    /// `pending`, `paid`, `fulfilled`, `shipped`, `delivered`, `cancelled`
+    /// or `refunded`.
+    pub status: String,
+    /// RFC 3339, UTC.
+    pub created_at: String,
+}
+
+/// Move an order to another status; admin only. `paid` and `refunded` are
+/// rejected, those follow the order's payment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateOrderStatusReq {
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusChangeResp {
+    /// `None` for the checkout that placed the order.
+    pub from: Option<String>,
+    pub to: String,
+    /// Who made the change; `None` once their account is deleted.
+    pub actor_id: Option<i64>,
+    pub reason: Option<String>,
+    /// RFC 3339, UTC.
+    pub changed_at: String,
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// `?limit=&offset=` on list routes; without them the whole list is
+/// returned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PageQuery {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+}
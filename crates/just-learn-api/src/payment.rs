@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaymentAttemptResp {
+    /// `authorize`, `capture`, `refund` or `webhook`.
+    pub operation: String,
+    pub succeeded: bool,
+    /// Decline reason or error of a failed attempt; the event type of a
+    /// webhook.
+    pub detail: Option<String>,
+    /// RFC 3339, UTC.
+    pub created_at: String,
+}
+
+/// One try at paying for an order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaymentResp {
+    pub id: i64,
+    pub order_id: i64,
+    /// The payment provider, e.g. `mock`.
+    pub provider: String,
+    /// The provider's id of the payment, once authorized.
+    pub provider_ref: Option<String>,
+    pub amount: f64,
+    /// `pending`, `authorized`, `captured`, `failed` or `refunded`.
+    pub status: String,
+    /// Oldest first.
+    pub attempts: Vec<PaymentAttemptResp>,
+    /// RFC 3339, UTC.
+    pub created_at: String,
+    /// RFC 3339, UTC.
+    pub updated_at: String,
+}
+
+/// Refund an order's payment; admin only.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RefundOrderReq {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateProductReq {
+    pub name: String,
+    pub description: Option<String>,
+    pub price: f64,
+    pub stock: i32,
+    pub category_id: i64,
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateProductResp {
+    pub id: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProductResp {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub price: f64,
+    /// On hand.
+    pub stock: i32,
+    /// Held by shoppers' carts for a while.
+    pub reserved: i32,
+    /// Left to sell: `stock` less `reserved`.
+    pub available: i32,
+    pub category_id: i64,
+    pub active: bool,
+    /// Where the stock is, with `?locations=true`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locations: Option<Vec<StockLocationResp>>,
+}
+
+/// How much of a product one warehouse holds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StockLocationResp {
+    pub warehouse_id: i64,
+    pub warehouse: String,
+    pub stock: i32,
+}
+
+/// `?locations=true` on the product routes adds each product's stock by
+/// warehouse.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProductQuery {
+    #[serde(default)]
+    pub locations: bool,
+}
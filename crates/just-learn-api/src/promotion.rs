@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+/// A promotion to create, or to replace one with; admin only.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PromotionReq {
+    pub name: String,
+    /// The coupon code that unlocks it, in any case; without one it applies
+    /// to every cart.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// `percentage`, `fixed`, `buy_x_get_y` or `free_shipping`.
+    pub kind: String,
+    /// Percent off for `percentage`, money off for `fixed`.
+    #[serde(default)]
+    pub value: f64,
+    /// For `buy_x_get_y`: of every `buy_quantity + get_quantity` units of a
+    /// product, `get_quantity` are free.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buy_quantity: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub get_quantity: Option<i32>,
+    /// Only lines of this category, or this product, count; at most one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category_id: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_id: Option<i64>,
+    /// RFC 3339; valid from `starts_at` up to, not including, `ends_at`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub starts_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ends_at: Option<String>,
+    /// Checkouts it may be used in, in all and per customer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_uses: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_uses_per_user: Option<i32>,
+    /// `true` if `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromotionResp {
+    pub id: i64,
+    pub name: String,
+    /// Upper case.
+    pub code: Option<String>,
+    pub kind: String,
+    pub value: f64,
+    pub buy_quantity: Option<i32>,
+    pub get_quantity: Option<i32>,
+    pub category_id: Option<i64>,
+    pub product_id: Option<i64>,
+    /// RFC 3339, UTC.
+    pub starts_at: Option<String>,
+    pub ends_at: Option<String>,
+    pub max_uses: Option<i32>,
+    pub max_uses_per_user: Option<i32>,
+    pub active: bool,
+    /// Checkouts it was used in so far.
+    pub uses: i64,
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+/// Add a supplier; admin only.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateSupplierReq {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SupplierResp {
+    pub id: i64,
+    pub name: String,
+    pub email: Option<String>,
+}
+
+/// What a supplier sells a product as; admin only.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupplierProductReq {
+    /// The supplier's own article number; unique per supplier.
+    pub sku: String,
+    pub cost_price: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SupplierProductResp {
+    pub supplier_id: i64,
+    pub product_id: i64,
+    pub sku: String,
+    pub cost_price: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurchaseOrderLineReq {
+    pub product_id: i64,
+    pub quantity: i32,
+    /// The supplier's cost price if `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit_cost: Option<f64>,
+}
+
+/// Draw up a purchase order; admin only. It starts as a draft.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePurchaseOrderReq {
+    pub supplier_id: i64,
+    /// One per product.
+    pub lines: Vec<PurchaseOrderLineReq>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PurchaseOrderLineResp {
+    pub product_id: i64,
+    pub quantity: i32,
+    /// How much of `quantity` has arrived so far.
+    pub received: i32,
+    pub unit_cost: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PurchaseOrderResp {
+    pub id: i64,
+    pub supplier_id: i64,
+    /// `draft`, `sent`, `partially_received` or `received`.
+    pub status: String,
+    /// By product id.
+    pub lines: Vec<PurchaseOrderLineResp>,
+    pub total: f64,
+    /// Who drew it up; `None` once their account is deleted.
+    pub created_by: Option<i64>,
+    /// RFC 3339, UTC.
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ReceivedLineReq {
+    pub product_id: i64,
+    pub quantity: i32,
+}
+
+/// Book goods that arrived against a sent purchase order; admin only.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceivePurchaseOrderReq {
+    pub lines: Vec<ReceivedLineReq>,
+    /// The default warehouse if `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warehouse_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProductCostResp {
+    pub product_id: i64,
+    /// Weighted over everything received; `None` before the first receipt.
+    pub average_cost: Option<f64>,
+}
@@ -0,0 +1,71 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CreateUserReq {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LoginReq {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateUserResp {
+    pub id: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LoginResp {
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeakResp {
+    pub speak: String,
+    pub shout: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserResp {
+    pub id: i64,
+    pub username: String,
+    pub active: bool,
+    pub greet: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UpdateUserReq {
+    pub username: String,
+    pub password: String,
+}
+
+// passwords and tokens must never reach logs
+
+impl fmt::Debug for CreateUserReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CreateUserReq").field("username", &self.username).finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for LoginReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoginReq").field("username", &self.username).finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for LoginResp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoginResp").finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for UpdateUserReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UpdateUserReq").field("username", &self.username).finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,62 @@
+use just_learn_api::problem::ProblemDetails;
+use reqwest::StatusCode;
+
+/// Errors of [`Client`](crate::Client) calls. Error responses are decoded
+/// from their `application/problem+json` body, so the server's `detail` and
+/// `request_id` are at hand.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// 400 or 422: the request was rejected as invalid.
+    #[error("{0}")]
+    BadRequest(Box<ProblemDetails>),
+    /// 401: missing, invalid or expired credentials.
+    #[error("{0}")]
+    Unauthorized(Box<ProblemDetails>),
+    #[error("{0}")]
+    NotFound(Box<ProblemDetails>),
+    #[error("{0}")]
+    Conflict(Box<ProblemDetails>),
+    /// 5xx, after retries for idempotent calls.
+    #[error("{0}")]
+    Server(Box<ProblemDetails>),
+    /// Any other error status.
+    #[error("{0}")]
+    Other(Box<ProblemDetails>),
+    /// The request could not be sent or the response not read.
+    #[error("request failed: {0}")]
+    Transport(#[from] reqwest::Error),
+    #[error("invalid base url: {0}")]
+    InvalidUrl(String),
+}
+
+impl Error {
+    pub(crate) fn from_problem(problem: ProblemDetails) -> Self {
+        let status = problem.status;
+        let problem = Box::new(problem);
+        match StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR) {
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => Error::BadRequest(problem),
+            StatusCode::UNAUTHORIZED => Error::Unauthorized(problem),
+            StatusCode::NOT_FOUND => Error::NotFound(problem),
+            StatusCode::CONFLICT => Error::Conflict(problem),
+            status if status.is_server_error() => Error::Server(problem),
+            _ => Error::Other(problem),
+        }
+    }
+
+    /// The server's problem details, if it answered with an error status.
+    pub fn problem(&self) -> Option<&ProblemDetails> {
+        match self {
+            Error::BadRequest(p)
+            | Error::Unauthorized(p)
+            | Error::NotFound(p)
+            | Error::Conflict(p)
+            | Error::Server(p)
+            | Error::Other(p) => Some(p),
+            Error::Transport(_) | Error::InvalidUrl(_) => None,
+        }
+    }
+
+    pub fn status(&self) -> Option<u16> {
+        self.problem().map(|p| p.status)
+    }
+}
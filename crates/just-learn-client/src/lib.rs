@@ -0,0 +1,603 @@
+//! Typed async client for the rust-just-learn REST API, built on the
+//! server's own request and response types ([`api`]).
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), just_learn_client::Error> {
+//! use just_learn_client::{api::category::CreateCategoryReq, Client};
+//!
+//! let client = Client::builder("http://localhost:3555")
+//!     .credentials("alice", "secret") // logs in on first use, again when the token expires
+//!     .build()?;
+//! let created = client.create_category(&CreateCategoryReq { name: "Books".into() }).await?;
+//! let categories = client.get_all_categories().try_collect().await?;
+//! # Ok(()) }
+//! ```
+//!
+//! Only `POST /users` takes an API key (`X-Api-Key`); every other route
+//! except `/health` and `/login` needs a login; the cart calls act on the
+//! logged-in user's cart (guest carts are for browsers). Idempotent calls (`GET`,
+//! `PUT`, `DELETE`) are retried on connection errors and on 429, 502, 503
+//! and 504 responses.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::{Method, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use just_learn_api::cart::{AddCartItemReq, ApplyCouponReq, CartResp, UpdateCartItemReq};
+use just_learn_api::category::{CategoryResp, CreateCategoryReq, CreateCategoryResp, UpdateCategoryReq};
+use just_learn_api::inventory::{
+    AdjustStockReq, CreateWarehouseReq, LowStockCategoryResp, ReconciliationResp, StockMovementResp, StockThresholdReq,
+    StockThresholdResp, TransferStockReq, WarehouseResp,
+};
+use just_learn_api::notification::NotificationResp;
+use just_learn_api::order::{CheckoutReq, OrderResp, StatusChangeResp, UpdateOrderStatusReq};
+use just_learn_api::page::PageQuery;
+use just_learn_api::payment::{PaymentResp, RefundOrderReq};
+use just_learn_api::problem::ProblemDetails;
+use just_learn_api::product::{CreateProductReq, CreateProductResp, ProductResp};
+use just_learn_api::promotion::{PromotionReq, PromotionResp};
+use just_learn_api::purchasing::{
+    CreatePurchaseOrderReq, CreateSupplierReq, ProductCostResp, PurchaseOrderResp, ReceivePurchaseOrderReq, SupplierProductReq,
+    SupplierProductResp, SupplierResp,
+};
+use just_learn_api::user::{CreateUserReq, CreateUserResp, LoginReq, LoginResp, SpeakResp, UpdateUserReq, UserResp};
+
+mod error;
+mod pager;
+#[cfg(test)]
+mod tests;
+
+pub use error::Error;
+pub use just_learn_api as api;
+pub use pager::Pager;
+
+/// How a route authenticates.
+#[derive(Clone, Copy, PartialEq)]
+enum Auth {
+    None,
+    ApiKey,
+    Bearer,
+}
+
+/// Configures a [`Client`].
+pub struct ClientBuilder {
+    base_url: String,
+    credentials: Option<(String, String)>,
+    token: Option<String>,
+    api_key: Option<String>,
+    http: Option<reqwest::Client>,
+    max_retries: u32,
+    retry_backoff: Duration,
+}
+
+impl ClientBuilder {
+    /// Log in with these on first use and whenever the token is rejected.
+    pub fn credentials(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.credentials = Some((username.into(), password.into()));
+        self
+    }
+
+    /// A bearer token from an earlier login. With
+    /// [`credentials`](Self::credentials) too, it is replaced once it expires.
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Sent as `X-Api-Key` to the routes that accept one.
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Use this `reqwest` client, e.g. with custom timeouts or proxies.
+    pub fn http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = Some(http);
+        self
+    }
+
+    /// Retries of idempotent calls; 3 by default.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Wait before the first retry, doubled for each next one; 200ms by
+    /// default.
+    pub fn retry_backoff(mut self, backoff: Duration) -> Self {
+        self.retry_backoff = backoff;
+        self
+    }
+
+    pub fn build(self) -> Result<Client, Error> {
+        let base_url = self.base_url.trim_end_matches('/').to_string();
+        reqwest::Url::parse(&base_url).map_err(|e| Error::InvalidUrl(format!("{base_url}: {e}")))?;
+        let http = match self.http {
+            Some(http) => http,
+            None => reqwest::Client::builder().timeout(Duration::from_secs(30)).build()?,
+        };
+        Ok(Client {
+            inner: Arc::new(Inner {
+                http,
+                base_url,
+                credentials: self.credentials,
+                api_key: self.api_key,
+                token: Mutex::new(self.token),
+                max_retries: self.max_retries,
+                retry_backoff: self.retry_backoff,
+            }),
+        })
+    }
+}
+
+struct Inner {
+    http: reqwest::Client,
+    base_url: String,
+    credentials: Option<(String, String)>,
+    api_key: Option<String>,
+    /// Held across a login so concurrent calls wait for one login.
+    token: Mutex<Option<String>>,
+    max_retries: u32,
+    retry_backoff: Duration,
+}
+
+/// Cheap to clone; clones share the login.
+#[derive(Clone)]
+pub struct Client {
+    inner: Arc<Inner>,
+}
+
+impl Client {
+    /// `base_url` is the server root, e.g. `http://localhost:3555`.
+    pub fn builder(base_url: impl Into<String>) -> ClientBuilder {
+        ClientBuilder {
+            base_url: base_url.into(),
+            credentials: None,
+            token: None,
+            api_key: None,
+            http: None,
+            max_retries: 3,
+            retry_backoff: Duration::from_millis(200),
+        }
+    }
+
+    pub async fn health(&self) -> Result<String, Error> {
+        let response = self.execute(Method::GET, "/health", None, None::<&()>, Auth::None).await?;
+        Ok(response.text().await?)
+    }
+
+    /// Log in and use the token for later calls; returns it.
+    pub async fn login(&self, username: &str, password: &str) -> Result<String, Error> {
+        let mut token = self.inner.token.lock().await;
+        let fresh = self.fetch_token(username, password).await?;
+        *token = Some(fresh.clone());
+        Ok(fresh)
+    }
+
+    /// Needs an API key.
+    pub async fn create_user(&self, req: &CreateUserReq) -> Result<CreateUserResp, Error> {
+        self.json(Method::POST, "/users", Some(req), Auth::ApiKey).await
+    }
+
+    pub fn get_all_users(&self) -> Pager<UserResp> {
+        Pager::new(self.clone(), "/users".into())
+    }
+
+    pub async fn get_user(&self, id: i64) -> Result<UserResp, Error> {
+        self.json(Method::GET, &format!("/users/{id}"), None::<&()>, Auth::Bearer).await
+    }
+
+    pub async fn update_user(&self, id: i64, req: &UpdateUserReq) -> Result<UserResp, Error> {
+        self.json(Method::PUT, &format!("/users/{id}"), Some(req), Auth::Bearer).await
+    }
+
+    pub async fn delete_user(&self, id: i64) -> Result<(), Error> {
+        self.execute(Method::DELETE, &format!("/users/{id}"), None, None::<&()>, Auth::Bearer).await?;
+        Ok(())
+    }
+
+    pub async fn user_speak(&self, id: i64) -> Result<SpeakResp, Error> {
+        self.json(Method::GET, &format!("/users/{id}/speak"), None::<&()>, Auth::Bearer).await
+    }
+
+    pub async fn create_category(&self, req: &CreateCategoryReq) -> Result<CreateCategoryResp, Error> {
+        self.json(Method::POST, "/categories", Some(req), Auth::Bearer).await
+    }
+
+    pub fn get_all_categories(&self) -> Pager<CategoryResp> {
+        Pager::new(self.clone(), "/categories".into())
+    }
+
+    pub async fn get_category(&self, id: i64) -> Result<CategoryResp, Error> {
+        self.json(Method::GET, &format!("/categories/{id}"), None::<&()>, Auth::Bearer).await
+    }
+
+    pub async fn update_category(&self, id: i64, req: &UpdateCategoryReq) -> Result<CategoryResp, Error> {
+        self.json(Method::PUT, &format!("/categories/{id}"), Some(req), Auth::Bearer).await
+    }
+
+    pub async fn delete_category(&self, id: i64) -> Result<(), Error> {
+        self.execute(Method::DELETE, &format!("/categories/{id}"), None, None::<&()>, Auth::Bearer).await?;
+        Ok(())
+    }
+
+    pub async fn create_product(&self, req: &CreateProductReq) -> Result<CreateProductResp, Error> {
+        self.json(Method::POST, "/products", Some(req), Auth::Bearer).await
+    }
+
+    pub async fn get_product(&self, id: i64) -> Result<ProductResp, Error> {
+        self.json(Method::GET, &format!("/products/{id}"), None::<&()>, Auth::Bearer).await
+    }
+
+    /// The product with its stock by warehouse in `locations`.
+    pub async fn get_product_with_locations(&self, id: i64) -> Result<ProductResp, Error> {
+        self.json(Method::GET, &format!("/products/{id}?locations=true"), None::<&()>, Auth::Bearer).await
+    }
+
+    pub fn get_products_by_category(&self, category_id: i64) -> Pager<ProductResp> {
+        Pager::new(self.clone(), format!("/products/categories/{category_id}"))
+    }
+
+    /// Admin only; taking more than is in stock is an [`Error::BadRequest`].
+    pub async fn adjust_stock(&self, product_id: i64, req: &AdjustStockReq) -> Result<StockMovementResp, Error> {
+        self.json(Method::POST, &format!("/admin/products/{product_id}/stock"), Some(req), Auth::Bearer).await
+    }
+
+    /// Admin only; the movement out of the source warehouse, then the one
+    /// into the destination.
+    pub async fn transfer_stock(&self, product_id: i64, req: &TransferStockReq) -> Result<Vec<StockMovementResp>, Error> {
+        self.json(Method::POST, &format!("/admin/products/{product_id}/stock/transfers"), Some(req), Auth::Bearer).await
+    }
+
+    /// Admin only.
+    pub async fn create_warehouse(&self, req: &CreateWarehouseReq) -> Result<WarehouseResp, Error> {
+        self.json(Method::POST, "/admin/warehouses", Some(req), Auth::Bearer).await
+    }
+
+    /// Admin only; by priority, the default warehouse first.
+    pub async fn get_warehouses(&self) -> Result<Vec<WarehouseResp>, Error> {
+        self.json(Method::GET, "/admin/warehouses", None::<&()>, Auth::Bearer).await
+    }
+
+    /// Admin only; oldest first.
+    pub async fn get_stock_movements(&self, product_id: i64) -> Result<Vec<StockMovementResp>, Error> {
+        self.json(Method::GET, &format!("/admin/products/{product_id}/stock/movements"), None::<&()>, Auth::Bearer).await
+    }
+
+    /// Admin only; products whose stock differs from their ledger.
+    pub async fn get_stock_reconciliation(&self) -> Result<ReconciliationResp, Error> {
+        self.json(Method::GET, "/admin/inventory/reconciliation", None::<&()>, Auth::Bearer).await
+    }
+
+    /// Admin only; `None` clears the product's reorder threshold.
+    pub async fn set_stock_threshold(&self, product_id: i64, threshold: Option<i32>) -> Result<StockThresholdResp, Error> {
+        let req = StockThresholdReq { threshold };
+        self.json(Method::PUT, &format!("/admin/products/{product_id}/stock/threshold"), Some(&req), Auth::Bearer).await
+    }
+
+    /// Admin only; products below their reorder threshold by category.
+    pub async fn get_low_stock_report(&self) -> Result<Vec<LowStockCategoryResp>, Error> {
+        self.json(Method::GET, "/reports/low-stock", None::<&()>, Auth::Bearer).await
+    }
+
+    /// Admin only; newest first, only the unread ones if `unread_only`.
+    pub async fn get_notifications(&self, unread_only: bool) -> Result<Vec<NotificationResp>, Error> {
+        self.json(Method::GET, &format!("/admin/notifications?unread={unread_only}"), None::<&()>, Auth::Bearer).await
+    }
+
+    /// Admin only.
+    pub async fn mark_notification_read(&self, id: i64) -> Result<(), Error> {
+        self.execute(Method::POST, &format!("/admin/notifications/{id}/read"), None, None::<&()>, Auth::Bearer).await?;
+        Ok(())
+    }
+
+    /// Admin only.
+    pub async fn create_supplier(&self, req: &CreateSupplierReq) -> Result<SupplierResp, Error> {
+        self.json(Method::POST, "/admin/suppliers", Some(req), Auth::Bearer).await
+    }
+
+    /// Admin only.
+    pub async fn get_suppliers(&self) -> Result<Vec<SupplierResp>, Error> {
+        self.json(Method::GET, "/admin/suppliers", None::<&()>, Auth::Bearer).await
+    }
+
+    /// Admin only; adds or replaces what the supplier sells the product as.
+    pub async fn set_supplier_product(
+        &self,
+        supplier_id: i64,
+        product_id: i64,
+        req: &SupplierProductReq,
+    ) -> Result<SupplierProductResp, Error> {
+        self.json(Method::PUT, &format!("/admin/suppliers/{supplier_id}/products/{product_id}"), Some(req), Auth::Bearer).await
+    }
+
+    /// Admin only.
+    pub async fn get_supplier_products(&self, supplier_id: i64) -> Result<Vec<SupplierProductResp>, Error> {
+        self.json(Method::GET, &format!("/admin/suppliers/{supplier_id}/products"), None::<&()>, Auth::Bearer).await
+    }
+
+    /// Admin only; the order starts as a draft.
+    pub async fn create_purchase_order(&self, req: &CreatePurchaseOrderReq) -> Result<PurchaseOrderResp, Error> {
+        self.json(Method::POST, "/admin/purchase-orders", Some(req), Auth::Bearer).await
+    }
+
+    /// Admin only; newest first.
+    pub async fn get_purchase_orders(&self) -> Result<Vec<PurchaseOrderResp>, Error> {
+        self.json(Method::GET, "/admin/purchase-orders", None::<&()>, Auth::Bearer).await
+    }
+
+    /// Admin only.
+    pub async fn get_purchase_order(&self, id: i64) -> Result<PurchaseOrderResp, Error> {
+        self.json(Method::GET, &format!("/admin/purchase-orders/{id}"), None::<&()>, Auth::Bearer).await
+    }
+
+    /// Admin only.
+    pub async fn send_purchase_order(&self, id: i64) -> Result<PurchaseOrderResp, Error> {
+        self.json(Method::POST, &format!("/admin/purchase-orders/{id}/send"), None::<&()>, Auth::Bearer).await
+    }
+
+    /// Admin only; the order as it stands after the receipt.
+    pub async fn receive_purchase_order(&self, id: i64, req: &ReceivePurchaseOrderReq) -> Result<PurchaseOrderResp, Error> {
+        self.json(Method::POST, &format!("/admin/purchase-orders/{id}/receipts"), Some(req), Auth::Bearer).await
+    }
+
+    /// Admin only.
+    pub async fn get_product_cost(&self, product_id: i64) -> Result<ProductCostResp, Error> {
+        self.json(Method::GET, &format!("/admin/products/{product_id}/cost"), None::<&()>, Auth::Bearer).await
+    }
+
+    /// Admin only.
+    pub async fn create_promotion(&self, req: &PromotionReq) -> Result<PromotionResp, Error> {
+        self.json(Method::POST, "/admin/promotions", Some(req), Auth::Bearer).await
+    }
+
+    /// Admin only; by id.
+    pub async fn get_promotions(&self) -> Result<Vec<PromotionResp>, Error> {
+        self.json(Method::GET, "/admin/promotions", None::<&()>, Auth::Bearer).await
+    }
+
+    /// Admin only.
+    pub async fn get_promotion(&self, id: i64) -> Result<PromotionResp, Error> {
+        self.json(Method::GET, &format!("/admin/promotions/{id}"), None::<&()>, Auth::Bearer).await
+    }
+
+    /// Admin only; replaces every setting.
+    pub async fn update_promotion(&self, id: i64, req: &PromotionReq) -> Result<PromotionResp, Error> {
+        self.json(Method::PUT, &format!("/admin/promotions/{id}"), Some(req), Auth::Bearer).await
+    }
+
+    /// Admin only.
+    pub async fn delete_promotion(&self, id: i64) -> Result<(), Error> {
+        self.execute(Method::DELETE, &format!("/admin/promotions/{id}"), None, None::<&()>, Auth::Bearer).await?;
+        Ok(())
+    }
+
+    pub fn get_all_products(&self) -> Pager<ProductResp> {
+        Pager::new(self.clone(), "/products".into())
+    }
+
+    pub async fn get_cart(&self) -> Result<CartResp, Error> {
+        self.json(Method::GET, "/cart", None::<&()>, Auth::Bearer).await
+    }
+
+    pub async fn add_cart_item(&self, req: &AddCartItemReq) -> Result<CartResp, Error> {
+        self.json(Method::POST, "/cart/items", Some(req), Auth::Bearer).await
+    }
+
+    pub async fn update_cart_item(&self, product_id: i64, req: &UpdateCartItemReq) -> Result<CartResp, Error> {
+        self.json(Method::PUT, &format!("/cart/items/{product_id}"), Some(req), Auth::Bearer).await
+    }
+
+    pub async fn remove_cart_item(&self, product_id: i64) -> Result<(), Error> {
+        self.execute(Method::DELETE, &format!("/cart/items/{product_id}"), None, None::<&()>, Auth::Bearer).await?;
+        Ok(())
+    }
+
+    /// Enter a coupon code, replacing the cart's earlier one.
+    pub async fn apply_coupon(&self, req: &ApplyCouponReq) -> Result<CartResp, Error> {
+        self.json(Method::PUT, "/cart/coupon", Some(req), Auth::Bearer).await
+    }
+
+    pub async fn remove_coupon(&self) -> Result<CartResp, Error> {
+        self.json(Method::DELETE, "/cart/coupon", None::<&()>, Auth::Bearer).await
+    }
+
+    pub async fn clear_cart(&self) -> Result<(), Error> {
+        self.execute(Method::DELETE, "/cart", None, None::<&()>, Auth::Bearer).await?;
+        Ok(())
+    }
+
+    /// Order what is in the cart; never retried.
+    pub async fn checkout(&self) -> Result<OrderResp, Error> {
+        self.json(Method::POST, "/checkout", None::<&()>, Auth::Bearer).await
+    }
+
+    /// Like [`Client::checkout`], shipping to `req.ship_to`.
+    pub async fn checkout_with(&self, req: &CheckoutReq) -> Result<OrderResp, Error> {
+        self.json(Method::POST, "/checkout", Some(req), Auth::Bearer).await
+    }
+
+    /// The logged-in user's orders, newest first.
+    pub fn get_orders(&self) -> Pager<OrderResp> {
+        Pager::new(self.clone(), "/orders".into())
+    }
+
+    pub async fn get_order(&self, id: i64) -> Result<OrderResp, Error> {
+        self.json(Method::GET, &format!("/orders/{id}"), None::<&()>, Auth::Bearer).await
+    }
+
+    /// Admin only; an illegal move is an [`Error::Conflict`].
+    pub async fn update_order_status(&self, id: i64, req: &UpdateOrderStatusReq) -> Result<OrderResp, Error> {
+        self.json(Method::POST, &format!("/admin/orders/{id}/status"), Some(req), Auth::Bearer).await
+    }
+
+    /// Admin only; oldest first.
+    pub async fn get_order_history(&self, id: i64) -> Result<Vec<StatusChangeResp>, Error> {
+        self.json(Method::GET, &format!("/admin/orders/{id}/history"), None::<&()>, Auth::Bearer).await
+    }
+
+    /// Pay for the logged-in user's pending order. A declined payment is an
+    /// [`Error::Other`] with status 402; paying again is a new try.
+    pub async fn pay_order(&self, id: i64) -> Result<PaymentResp, Error> {
+        self.json(Method::POST, &format!("/orders/{id}/pay"), None::<&()>, Auth::Bearer).await
+    }
+
+    /// Admin only; newest first.
+    pub async fn get_order_payments(&self, id: i64) -> Result<Vec<PaymentResp>, Error> {
+        self.json(Method::GET, &format!("/admin/orders/{id}/payments"), None::<&()>, Auth::Bearer).await
+    }
+
+    /// Admin only; refunds the captured payment and marks the order refunded.
+    pub async fn refund_order(&self, id: i64, req: &RefundOrderReq) -> Result<PaymentResp, Error> {
+        self.json(Method::POST, &format!("/admin/orders/{id}/refund"), Some(req), Auth::Bearer).await
+    }
+
+    pub(crate) async fn get_page<T: DeserializeOwned>(&self, path: &str, query: &PageQuery) -> Result<Vec<T>, Error> {
+        let response = self.execute(Method::GET, path, Some(query), None::<&()>, Auth::Bearer).await?;
+        Ok(response.json().await?)
+    }
+
+    async fn json<B, T>(&self, method: Method, path: &str, body: Option<&B>, auth: Auth) -> Result<T, Error>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let response = self.execute(method, path, None, body, auth).await?;
+        Ok(response.json().await?)
+    }
+
+    /// Send with the route's credentials; a rejected token is replaced by
+    /// logging in again, once. Error statuses become [`Error`]s.
+    async fn execute<B: Serialize + ?Sized>(
+        &self,
+        method: Method,
+        path: &str,
+        query: Option<&PageQuery>,
+        body: Option<&B>,
+        auth: Auth,
+    ) -> Result<Response, Error> {
+        let mut token = match auth {
+            Auth::Bearer => self.current_token().await?,
+            Auth::None | Auth::ApiKey => None,
+        };
+        let mut refreshed = false;
+        loop {
+            let response = self.send(method.clone(), path, query, body, auth, token.as_deref()).await?;
+            if response.status() == StatusCode::UNAUTHORIZED
+                && auth == Auth::Bearer
+                && self.inner.credentials.is_some()
+                && !refreshed
+            {
+                token = self.refresh_token(token).await?;
+                refreshed = true;
+                continue;
+            }
+            return check(response).await;
+        }
+    }
+
+    /// One request, retried while idempotent and failing transiently.
+    async fn send<B: Serialize + ?Sized>(
+        &self,
+        method: Method,
+        path: &str,
+        query: Option<&PageQuery>,
+        body: Option<&B>,
+        auth: Auth,
+        token: Option<&str>,
+    ) -> Result<Response, Error> {
+        let idempotent = matches!(method, Method::GET | Method::PUT | Method::DELETE);
+        let mut attempt = 0;
+        loop {
+            let mut request = self.inner.http.request(method.clone(), format!("{}{path}", self.inner.base_url));
+            if let Some(query) = query {
+                request = request.query(query);
+            }
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+            match auth {
+                Auth::None => {}
+                Auth::ApiKey => {
+                    if let Some(api_key) = &self.inner.api_key {
+                        request = request.header("X-Api-Key", api_key);
+                    }
+                }
+                Auth::Bearer => {
+                    if let Some(token) = token {
+                        request = request.bearer_auth(token);
+                    }
+                }
+            }
+
+            let result = request.send().await;
+            let transient = match &result {
+                Ok(response) => matches!(
+                    response.status(),
+                    StatusCode::TOO_MANY_REQUESTS
+                        | StatusCode::BAD_GATEWAY
+                        | StatusCode::SERVICE_UNAVAILABLE
+                        | StatusCode::GATEWAY_TIMEOUT
+                ),
+                Err(e) => e.is_connect() || e.is_timeout(),
+            };
+            if !(idempotent && transient && attempt < self.inner.max_retries) {
+                return Ok(result?);
+            }
+            tokio::time::sleep(self.inner.retry_backoff * 2u32.pow(attempt.min(10))).await;
+            attempt += 1;
+        }
+    }
+
+    /// The stored token, logging in first if there is none yet.
+    async fn current_token(&self) -> Result<Option<String>, Error> {
+        let mut token = self.inner.token.lock().await;
+        if token.is_none()
+            && let Some((username, password)) = &self.inner.credentials
+        {
+            *token = Some(self.fetch_token(username, password).await?);
+        }
+        Ok(token.clone())
+    }
+
+    /// Log in again unless another call already replaced `stale`.
+    async fn refresh_token(&self, stale: Option<String>) -> Result<Option<String>, Error> {
+        let mut token = self.inner.token.lock().await;
+        if *token == stale
+            && let Some((username, password)) = &self.inner.credentials
+        {
+            *token = Some(self.fetch_token(username, password).await?);
+        }
+        Ok(token.clone())
+    }
+
+    async fn fetch_token(&self, username: &str, password: &str) -> Result<String, Error> {
+        let req = LoginReq { username: username.to_string(), password: password.to_string() };
+        let response = self.send(Method::POST, "/login", None, Some(&req), Auth::None, None).await?;
+        let login: LoginResp = check(response).await?.json().await?;
+        Ok(login.token)
+    }
+}
+
+/// Pass successes through; decode error statuses into an [`Error`].
+async fn check(response: Response) -> Result<Response, Error> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await?;
+    let problem = serde_json::from_str::<ProblemDetails>(&body).unwrap_or_else(|_| ProblemDetails {
+        // not problem+json, e.g. a body the server's extractors rejected
+        kind: "about:blank".into(),
+        title: status.canonical_reason().unwrap_or("Error").into(),
+        status: status.as_u16(),
+        detail: Some(body).filter(|b| !b.is_empty()),
+        request_id: None,
+        trace_id: None,
+    });
+    Err(Error::from_problem(problem))
+}
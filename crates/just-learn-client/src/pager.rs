@@ -0,0 +1,75 @@
+use std::collections::VecDeque;
+
+use just_learn_api::page::PageQuery;
+use serde::de::DeserializeOwned;
+
+use crate::{Client, Error};
+
+/// Walks a list route page by page with `?limit=&offset=`.
+///
+/// ```no_run
+/// # async fn run(client: just_learn_client::Client) -> Result<(), just_learn_client::Error> {
+/// let mut products = client.get_all_products().page_size(50);
+/// while let Some(product) = products.next().await {
+///     println!("{}", product?.name);
+/// }
+/// # Ok(()) }
+/// ```
+pub struct Pager<T> {
+    client: Client,
+    path: String,
+    page_size: usize,
+    offset: usize,
+    buffer: VecDeque<T>,
+    done: bool,
+}
+
+impl<T: DeserializeOwned> Pager<T> {
+    pub(crate) fn new(client: Client, path: String) -> Self {
+        Self { client, path, page_size: 100, offset: 0, buffer: VecDeque::new(), done: false }
+    }
+
+    /// Items per request; 100 by default.
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// The next page, or `None` past the last one.
+    pub async fn next_page(&mut self) -> Result<Option<Vec<T>>, Error> {
+        if self.done {
+            return Ok(None);
+        }
+        let query = PageQuery { limit: Some(self.page_size), offset: Some(self.offset) };
+        let page: Vec<T> = self.client.get_page(&self.path, &query).await?;
+        self.offset += page.len();
+        // a short page is the last one
+        self.done = page.len() < self.page_size;
+        Ok(if page.is_empty() { None } else { Some(page) })
+    }
+
+    /// The next item, fetching the next page when needed. After an error
+    /// the pager is exhausted.
+    pub async fn next(&mut self) -> Option<Result<T, Error>> {
+        if self.buffer.is_empty() {
+            match self.next_page().await {
+                Ok(Some(page)) => self.buffer.extend(page),
+                Ok(None) => return None,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        self.buffer.pop_front().map(Ok)
+    }
+
+    /// Every remaining item.
+    pub async fn try_collect(mut self) -> Result<Vec<T>, Error> {
+        let mut items: Vec<T> = self.buffer.drain(..).collect();
+        while let Some(page) = self.next_page().await? {
+            items.extend(page);
+        }
+        Ok(items)
+    }
+}
@@ -0,0 +1,281 @@
+//! The client against the real router, served in-process on a loopback
+//! port over in-memory repositories.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Once};
+use std::time::Duration;
+
+use axum::extract::Request;
+use axum::http::StatusCode;
+use axum::middleware::{self, Next};
+use axum::response::IntoResponse;
+use rust_just_learn::{App, AppBuilder};
+
+use crate::api::cart::{AddCartItemReq, ApplyCouponReq, UpdateCartItemReq};
+use crate::api::category::{CreateCategoryReq, UpdateCategoryReq};
+use crate::api::inventory::{AdjustStockReq, CreateWarehouseReq, TransferStockReq};
+use crate::api::order::UpdateOrderStatusReq;
+use crate::api::payment::RefundOrderReq;
+use crate::api::product::CreateProductReq;
+use crate::api::promotion::PromotionReq;
+use crate::api::purchasing::{CreatePurchaseOrderReq, CreateSupplierReq};
+use crate::api::user::{CreateUserReq, UpdateUserReq};
+use crate::{Client, ClientBuilder, Error};
+
+const API_KEY: &str = "test-api-key";
+
+fn init_env() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        // SAFETY: runs once before any test reads these, and every test
+        // expects the same values
+        unsafe {
+            std::env::set_var("API_KEY", API_KEY);
+            std::env::set_var("JWT_SECRET", "test-jwt-secret");
+        }
+    });
+}
+
+/// Serve the app built from `configure` and return its base URL.
+async fn serve(configure: impl FnOnce(AppBuilder) -> AppBuilder) -> String {
+    init_env();
+    let app = configure(App::builder()).build().unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(axum::serve(listener, app.router()).into_future());
+    format!("http://{address}")
+}
+
+/// A server with user `alice` and a client logging in as her.
+async fn alice(configure: impl FnOnce(AppBuilder) -> AppBuilder) -> (String, ClientBuilder) {
+    let base_url = serve(configure).await;
+    let admin = Client::builder(&base_url).api_key(API_KEY).build().unwrap();
+    admin.create_user(&user("alice", "secret")).await.unwrap();
+    let builder = Client::builder(&base_url).credentials("alice", "secret");
+    (base_url, builder)
+}
+
+fn user(username: &str, password: &str) -> CreateUserReq {
+    CreateUserReq { username: username.into(), password: password.into() }
+}
+
+fn product(name: &str, category_id: i64) -> CreateProductReq {
+    CreateProductReq {
+        name: name.into(),
+        description: None,
+        price: 9.99,
+        stock: 3,
+        category_id,
+        active: true,
+    }
+}
+
+#[tokio::test]
+async fn api_key_creates_users_and_credentials_log_in() {
+    let base_url = serve(|builder| builder).await;
+    let keyed = Client::builder(&base_url).api_key(API_KEY).build().unwrap();
+    assert_eq!(keyed.health().await.unwrap(), "I'm alive!");
+    let created = keyed.create_user(&user("alice", "secret")).await.unwrap();
+
+    // an API key does not open the routes that need a login
+    let err = keyed.get_user(created.id).await.unwrap_err();
+    assert!(matches!(err, Error::Unauthorized(_)), "{err:?}");
+    assert_eq!(err.problem().unwrap().detail.as_deref(), Some("missing authorization header"));
+
+    let wrong_key = Client::builder(&base_url).api_key("nope").build().unwrap();
+    let err = wrong_key.create_user(&user("bob", "secret")).await.unwrap_err();
+    assert!(matches!(err, Error::Unauthorized(_)), "{err:?}");
+
+    let client = Client::builder(&base_url).credentials("alice", "secret").build().unwrap();
+    let alice = client.get_user(created.id).await.unwrap();
+    assert_eq!(alice.username, "alice");
+
+    let err = Client::builder(&base_url).build().unwrap().login("alice", "wrong").await.unwrap_err();
+    assert_eq!(err.status(), Some(401));
+}
+
+#[tokio::test]
+async fn every_route_round_trips() {
+    let (_, builder) = alice(|builder| builder).await;
+    let client = builder.build().unwrap();
+
+    let books = client.create_category(&CreateCategoryReq { name: "Books".into() }).await.unwrap().id;
+    let renamed = client.update_category(books, &UpdateCategoryReq { name: "Novels".into() }).await.unwrap();
+    assert_eq!(renamed.name, "Novels");
+    assert_eq!(client.get_category(books).await.unwrap(), renamed);
+
+    let id = client.create_product(&product("Dune", books)).await.unwrap().id;
+    let dune = client.get_product(id).await.unwrap();
+    assert_eq!((dune.name.as_str(), dune.category_id), ("Dune", books));
+    assert_eq!(client.get_products_by_category(books).try_collect().await.unwrap(), vec![dune.clone()]);
+    assert_eq!(client.get_all_products().try_collect().await.unwrap(), vec![dune]);
+
+    let cart = client.add_cart_item(&AddCartItemReq { product_id: id, quantity: 1 }).await.unwrap();
+    assert_eq!((cart.lines.len(), cart.total), (1, 9.99));
+    let cart = client.update_cart_item(id, &UpdateCartItemReq { quantity: 2 }).await.unwrap();
+    assert_eq!(cart.lines[0].quantity, 2);
+    assert_eq!(client.get_cart().await.unwrap(), cart);
+    let coupon = ApplyCouponReq { code: "nope".into() };
+    assert_eq!(client.apply_coupon(&coupon).await.unwrap_err().status(), Some(400));
+    assert_eq!(client.remove_coupon().await.unwrap(), cart);
+    client.remove_cart_item(id).await.unwrap();
+    assert!(matches!(client.remove_cart_item(id).await, Err(Error::NotFound(_))));
+    client.clear_cart().await.unwrap();
+    assert!(client.get_cart().await.unwrap().lines.is_empty());
+
+    client.add_cart_item(&AddCartItemReq { product_id: id, quantity: 2 }).await.unwrap();
+    let order = client.checkout().await.unwrap();
+    assert_eq!((order.lines[0].name.as_str(), order.total), ("Dune", 19.98));
+    assert_eq!(client.get_product(id).await.unwrap().stock, 1);
+    assert_eq!(client.get_orders().try_collect().await.unwrap(), vec![order.clone()]);
+    assert_eq!(client.get_order(order.id).await.unwrap(), order);
+    assert_eq!(order.status, "pending");
+    // alice is no admin
+    let req = UpdateOrderStatusReq { status: "fulfilled".into(), reason: None };
+    assert_eq!(client.update_order_status(order.id, &req).await.unwrap_err().status(), Some(403));
+    assert_eq!(client.get_order_history(order.id).await.unwrap_err().status(), Some(403));
+
+    let payment = client.pay_order(order.id).await.unwrap();
+    assert_eq!((payment.order_id, payment.amount, payment.status.as_str()), (order.id, 19.98, "captured"));
+    assert_eq!(client.get_order(order.id).await.unwrap().status, "paid");
+    assert_eq!(client.pay_order(order.id).await.unwrap_err().status(), Some(409));
+    assert_eq!(client.get_order_payments(order.id).await.unwrap_err().status(), Some(403));
+    assert_eq!(client.refund_order(order.id, &RefundOrderReq::default()).await.unwrap_err().status(), Some(403));
+    let req = AdjustStockReq { kind: "receipt".into(), quantity: 5, reason: "delivery".into(), warehouse_id: None };
+    assert_eq!(client.adjust_stock(id, &req).await.unwrap_err().status(), Some(403));
+    let req = TransferStockReq { from_warehouse_id: 1, to_warehouse_id: 2, quantity: 1, reason: "rebalance".into() };
+    assert_eq!(client.transfer_stock(id, &req).await.unwrap_err().status(), Some(403));
+    let req = CreateWarehouseReq { name: "east".into(), priority: 1, location: None };
+    assert_eq!(client.create_warehouse(&req).await.unwrap_err().status(), Some(403));
+    assert_eq!(client.get_warehouses().await.unwrap_err().status(), Some(403));
+    let located = client.get_product_with_locations(id).await.unwrap();
+    assert_eq!(located.locations.unwrap()[0].stock, 1);
+    assert_eq!(client.get_stock_movements(id).await.unwrap_err().status(), Some(403));
+    assert_eq!(client.get_stock_reconciliation().await.unwrap_err().status(), Some(403));
+    assert_eq!(client.set_stock_threshold(id, Some(5)).await.unwrap_err().status(), Some(403));
+    assert_eq!(client.get_low_stock_report().await.unwrap_err().status(), Some(403));
+    assert_eq!(client.get_notifications(true).await.unwrap_err().status(), Some(403));
+    assert_eq!(client.mark_notification_read(1).await.unwrap_err().status(), Some(403));
+    let supplier = CreateSupplierReq { name: "Acme".into(), email: None };
+    assert_eq!(client.create_supplier(&supplier).await.unwrap_err().status(), Some(403));
+    assert_eq!(client.get_suppliers().await.unwrap_err().status(), Some(403));
+    let purchase = CreatePurchaseOrderReq { supplier_id: 1, lines: vec![] };
+    assert_eq!(client.create_purchase_order(&purchase).await.unwrap_err().status(), Some(403));
+    assert_eq!(client.get_purchase_orders().await.unwrap_err().status(), Some(403));
+    assert_eq!(client.get_product_cost(id).await.unwrap_err().status(), Some(403));
+    let promotion = PromotionReq { name: "Sale".into(), kind: "percentage".into(), value: 10.0, ..Default::default() };
+    assert_eq!(client.create_promotion(&promotion).await.unwrap_err().status(), Some(403));
+    assert_eq!(client.get_promotions().await.unwrap_err().status(), Some(403));
+    assert_eq!(client.get_promotion(1).await.unwrap_err().status(), Some(403));
+    assert_eq!(client.update_promotion(1, &promotion).await.unwrap_err().status(), Some(403));
+    assert_eq!(client.delete_promotion(1).await.unwrap_err().status(), Some(403));
+
+    let users = client.get_all_users().try_collect().await.unwrap();
+    let alice = users[0].id;
+    assert_eq!(client.get_user(alice).await.unwrap().greet, "Hello alice");
+    assert_eq!(client.user_speak(alice).await.unwrap().shout, "HELLO ALICE");
+    let updated = client
+        .update_user(alice, &UpdateUserReq { username: "alice2".into(), password: "secret2".into() })
+        .await
+        .unwrap();
+    assert_eq!(updated.username, "alice2");
+
+    let other = client.create_category(&CreateCategoryReq { name: "Empty".into() }).await.unwrap().id;
+    client.delete_category(other).await.unwrap();
+    assert!(matches!(client.get_category(other).await, Err(Error::NotFound(_))));
+    client.delete_user(alice).await.unwrap();
+}
+
+#[tokio::test]
+async fn pagers_walk_every_page() {
+    let (_, builder) = alice(|builder| builder).await;
+    let client = builder.build().unwrap();
+    for name in ["a", "b", "c", "d", "e"] {
+        client.create_category(&CreateCategoryReq { name: name.into() }).await.unwrap();
+    }
+
+    let mut pages = client.get_all_categories().page_size(2);
+    let mut sizes = Vec::new();
+    while let Some(page) = pages.next_page().await.unwrap() {
+        sizes.push(page.len());
+    }
+    assert_eq!(sizes, [2, 2, 1]);
+
+    let mut categories = client.get_all_categories().page_size(2);
+    let mut names = Vec::new();
+    while let Some(category) = categories.next().await {
+        names.push(category.unwrap().name);
+    }
+    assert_eq!(names, ["a", "b", "c", "d", "e"]);
+    assert_eq!(client.get_all_categories().page_size(5).try_collect().await.unwrap().len(), 5);
+}
+
+#[tokio::test]
+async fn problem_json_maps_to_typed_errors() {
+    let (_, builder) = alice(|builder| builder).await;
+    let client = builder.build().unwrap();
+
+    let err = client.get_category(99).await.unwrap_err();
+    let Error::NotFound(problem) = &err else { panic!("expected NotFound, got {err:?}") };
+    assert_eq!(problem.status, 404);
+    assert!(problem.request_id.is_some());
+    assert!(err.to_string().starts_with("404 Not Found"), "{err}");
+
+    let err = client.create_product(&product("", 1)).await.unwrap_err();
+    assert!(matches!(err, Error::BadRequest(_)), "{err:?}");
+    assert_eq!(err.problem().unwrap().detail.as_deref(), Some("name is required"));
+
+    let err = client.create_category(&CreateCategoryReq { name: "".into() }).await.unwrap_err();
+    assert_eq!(err.status(), Some(400));
+}
+
+#[tokio::test]
+async fn rejected_tokens_are_refreshed_by_logging_in_again() {
+    let (base_url, builder) = alice(|builder| builder).await;
+    let client = builder.token("expired-or-revoked").build().unwrap();
+    assert_eq!(client.get_all_users().try_collect().await.unwrap().len(), 1);
+
+    // without credentials there is nothing to refresh with
+    let stale = Client::builder(&base_url).token("expired-or-revoked").build().unwrap();
+    assert!(matches!(stale.get_user(1).await, Err(Error::Unauthorized(_))));
+
+    let manual = Client::builder(&base_url).build().unwrap();
+    manual.login("alice", "secret").await.unwrap();
+    assert_eq!(manual.get_user(1).await.unwrap().username, "alice");
+}
+
+#[tokio::test]
+async fn only_idempotent_calls_are_retried() {
+    // answer 503 to the next `failures` requests
+    let failures = Arc::new(AtomicUsize::new(0));
+    let layer_failures = failures.clone();
+    let flaky = move |request: Request, next: Next| {
+        let failures = layer_failures.clone();
+        async move {
+            let fail = failures.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1)).is_ok();
+            if fail { StatusCode::SERVICE_UNAVAILABLE.into_response() } else { next.run(request).await }
+        }
+    };
+    let (_, builder) = alice(|builder| builder.layer(middleware::from_fn(flaky))).await;
+    let client = builder.max_retries(2).retry_backoff(Duration::from_millis(1)).build().unwrap();
+    // log in first; POST /login is not retried
+    client.get_user(1).await.unwrap();
+
+    failures.store(2, Ordering::SeqCst);
+    assert_eq!(client.get_all_users().try_collect().await.unwrap().len(), 1);
+
+    failures.store(3, Ordering::SeqCst);
+    let err = client.get_user(1).await.unwrap_err();
+    assert!(matches!(err, Error::Server(_)), "{err:?}");
+    assert_eq!(failures.load(Ordering::SeqCst), 0);
+
+    failures.store(1, Ordering::SeqCst);
+    let err = client.create_category(&CreateCategoryReq { name: "Books".into() }).await.unwrap_err();
+    assert_eq!(err.status(), Some(503));
+    assert!(client.get_all_categories().try_collect().await.unwrap().is_empty());
+}
+
+#[test]
+fn invalid_base_urls_are_rejected() {
+    assert!(matches!(Client::builder("not a url").build(), Err(Error::InvalidUrl(_))));
+}
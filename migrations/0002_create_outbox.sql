@@ -0,0 +1,16 @@
+-- Transactional outbox: domain events written with the change that raised
+-- them, published afterwards by the relay (at-least-once, in id order).
+CREATE TABLE IF NOT EXISTS outbox (
+  id             BIGSERIAL PRIMARY KEY,
+  aggregate_type TEXT NOT NULL,
+  aggregate_id   BIGINT NOT NULL,
+  event_type     TEXT NOT NULL,
+  payload        JSONB NOT NULL,
+  trace_context  TEXT,
+  created_at     TIMESTAMPTZ NOT NULL DEFAULT now(),
+  published_at   TIMESTAMPTZ,
+  attempts       INT NOT NULL DEFAULT 0,
+  last_error     TEXT
+);
+
+CREATE INDEX IF NOT EXISTS outbox_pending_idx ON outbox (id) WHERE published_at IS NULL;
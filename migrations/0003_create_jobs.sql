@@ -0,0 +1,23 @@
+-- Background job queue. Workers claim due rows with FOR UPDATE SKIP LOCKED;
+-- a claim is a lease (locked_until), so jobs of a crashed worker run again.
+CREATE TABLE IF NOT EXISTS jobs (
+  id            BIGSERIAL PRIMARY KEY,
+  queue         TEXT NOT NULL,
+  kind          TEXT NOT NULL,
+  payload       JSONB NOT NULL,
+  status        TEXT NOT NULL DEFAULT 'scheduled'
+                CHECK (status IN ('scheduled', 'running', 'completed', 'dead')),
+  attempts      INT NOT NULL DEFAULT 0,
+  max_attempts  INT NOT NULL,
+  run_at        TIMESTAMPTZ NOT NULL DEFAULT now(),
+  locked_until  TIMESTAMPTZ,
+  unique_key    TEXT UNIQUE,
+  last_error    TEXT,
+  trace_context TEXT,
+  created_at    TIMESTAMPTZ NOT NULL DEFAULT now(),
+  finished_at   TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS jobs_due_idx ON jobs (queue, run_at) WHERE status = 'scheduled';
+CREATE INDEX IF NOT EXISTS jobs_leased_idx ON jobs (queue, locked_until) WHERE status = 'running';
+CREATE INDEX IF NOT EXISTS jobs_finished_idx ON jobs (finished_at) WHERE finished_at IS NOT NULL;
@@ -0,0 +1,13 @@
+-- Admin users (created with `just-learn-admin user create-admin`) and API
+-- keys for `X-Api-Key`. Only a SHA-256 of each key is stored; the prefix
+-- finds the row.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS admin BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS api_keys (
+  id         BIGSERIAL PRIMARY KEY,
+  name       TEXT NOT NULL,
+  prefix     TEXT NOT NULL UNIQUE,
+  key_hash   TEXT NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  revoked_at TIMESTAMPTZ
+);
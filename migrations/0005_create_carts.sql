@@ -0,0 +1,22 @@
+-- Shopping carts: one per user, or per guest token until the guest logs in
+-- and the cart is merged into theirs. Carts idle for longer than the
+-- configured timeout are deleted by the `prune_idle_carts` job.
+CREATE TABLE IF NOT EXISTS carts (
+  id          BIGSERIAL PRIMARY KEY,
+  user_id     BIGINT UNIQUE REFERENCES users(id) ON DELETE CASCADE,
+  guest_token TEXT UNIQUE,
+  updated_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+  CHECK ((user_id IS NULL) <> (guest_token IS NULL))
+);
+
+CREATE INDEX IF NOT EXISTS carts_updated_at_idx ON carts (updated_at);
+
+-- unit_price is the price the shopper last saw; reads re-price from products.
+CREATE TABLE IF NOT EXISTS cart_items (
+  id         BIGSERIAL PRIMARY KEY,
+  cart_id    BIGINT NOT NULL REFERENCES carts(id) ON DELETE CASCADE,
+  product_id BIGINT NOT NULL REFERENCES products(id),
+  quantity   INT NOT NULL CHECK (quantity > 0),
+  unit_price NUMERIC(10, 2) NOT NULL,
+  UNIQUE (cart_id, product_id)
+);
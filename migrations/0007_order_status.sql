@@ -0,0 +1,18 @@
+-- Order status, moved along by the state machine in domain::order, and the
+-- history of every move. Orders placed before this migration are pending.
+ALTER TABLE orders
+  ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'pending'
+  CHECK (status IN ('pending', 'paid', 'fulfilled', 'shipped', 'delivered', 'cancelled', 'refunded'));
+
+-- from_status is NULL for the checkout that created the order.
+CREATE TABLE IF NOT EXISTS order_status_changes (
+  id          BIGSERIAL PRIMARY KEY,
+  order_id    BIGINT NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+  from_status TEXT,
+  to_status   TEXT NOT NULL,
+  actor_id    BIGINT REFERENCES users(id) ON DELETE SET NULL,
+  reason      TEXT,
+  changed_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS order_status_changes_order_id_idx ON order_status_changes (order_id);
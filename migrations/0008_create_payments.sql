@@ -0,0 +1,34 @@
+-- Payments: one intent per try at paying an order, every call to the
+-- provider (and webhook from it) as an attempt, and the ids of the webhook
+-- events already handled, since providers deliver them more than once.
+CREATE TABLE IF NOT EXISTS payment_intents (
+  id           BIGSERIAL PRIMARY KEY,
+  order_id     BIGINT NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+  provider     TEXT NOT NULL,
+  provider_ref TEXT,
+  amount       NUMERIC(10, 2) NOT NULL,
+  status       TEXT NOT NULL CHECK (status IN ('pending', 'authorized', 'captured', 'failed', 'refunded')),
+  created_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+  UNIQUE (provider, provider_ref)
+);
+
+CREATE INDEX IF NOT EXISTS payment_intents_order_id_idx ON payment_intents (order_id);
+
+CREATE TABLE IF NOT EXISTS payment_attempts (
+  id         BIGSERIAL PRIMARY KEY,
+  intent_id  BIGINT NOT NULL REFERENCES payment_intents(id) ON DELETE CASCADE,
+  operation  TEXT NOT NULL CHECK (operation IN ('authorize', 'capture', 'refund', 'webhook')),
+  succeeded  BOOLEAN NOT NULL,
+  detail     TEXT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS payment_attempts_intent_id_idx ON payment_attempts (intent_id);
+
+CREATE TABLE IF NOT EXISTS payment_webhook_events (
+  provider    TEXT NOT NULL,
+  event_id    TEXT NOT NULL,
+  received_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  PRIMARY KEY (provider, event_id)
+);
@@ -0,0 +1,20 @@
+-- The stock ledger: products.stock is the running total of its movements
+-- and changes only together with a new one. Stock held before this
+-- migration is booked as an opening adjustment.
+CREATE TABLE IF NOT EXISTS stock_movements (
+  id         BIGSERIAL PRIMARY KEY,
+  product_id BIGINT NOT NULL REFERENCES products(id),
+  kind       TEXT NOT NULL CHECK (kind IN ('receipt', 'sale', 'return', 'adjustment', 'reservation')),
+  quantity   INT NOT NULL CHECK (quantity <> 0),
+  reason     TEXT,
+  actor_id   BIGINT REFERENCES users(id) ON DELETE SET NULL,
+  order_id   BIGINT REFERENCES orders(id) ON DELETE SET NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS stock_movements_product_id_idx ON stock_movements (product_id);
+
+INSERT INTO stock_movements (product_id, kind, quantity, reason)
+SELECT id, 'adjustment', stock, 'opening balance'
+FROM products
+WHERE stock <> 0;
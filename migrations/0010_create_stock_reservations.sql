@@ -0,0 +1,15 @@
+-- Stock held for a cart until expires_at. Reservations leave products.stock
+-- and the ledger alone: what is left to sell is the stock less the live
+-- reservations. Expired ones are swept by the `release_expired_reservations`
+-- job and ignored until then.
+CREATE TABLE IF NOT EXISTS stock_reservations (
+  id         BIGSERIAL PRIMARY KEY,
+  cart_id    BIGINT NOT NULL REFERENCES carts(id) ON DELETE CASCADE,
+  product_id BIGINT NOT NULL REFERENCES products(id),
+  quantity   INT NOT NULL CHECK (quantity > 0),
+  expires_at TIMESTAMPTZ NOT NULL,
+  UNIQUE (cart_id, product_id)
+);
+
+CREATE INDEX IF NOT EXISTS stock_reservations_product_id_idx ON stock_reservations (product_id, expires_at);
+CREATE INDEX IF NOT EXISTS stock_reservations_expires_at_idx ON stock_reservations (expires_at);
@@ -0,0 +1,38 @@
+-- Stock per warehouse. products.stock stays the total over all warehouses
+-- and every ledger movement names the warehouse it happened in. Existing
+-- stock and movements are put in a `main` warehouse.
+CREATE TABLE IF NOT EXISTS warehouses (
+  id        BIGSERIAL PRIMARY KEY,
+  name      TEXT NOT NULL UNIQUE,
+  priority  INT NOT NULL DEFAULT 0,
+  latitude  DOUBLE PRECISION,
+  longitude DOUBLE PRECISION,
+  CHECK ((latitude IS NULL) = (longitude IS NULL))
+);
+
+INSERT INTO warehouses (name) VALUES ('main') ON CONFLICT (name) DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS warehouse_stock (
+  warehouse_id BIGINT NOT NULL REFERENCES warehouses(id),
+  product_id   BIGINT NOT NULL REFERENCES products(id),
+  stock        INT NOT NULL CHECK (stock >= 0),
+  PRIMARY KEY (warehouse_id, product_id)
+);
+
+CREATE INDEX IF NOT EXISTS warehouse_stock_product_id_idx ON warehouse_stock (product_id);
+
+INSERT INTO warehouse_stock (warehouse_id, product_id, stock)
+SELECT w.id, p.id, p.stock
+FROM products p, warehouses w
+WHERE w.name = 'main' AND p.stock > 0
+ON CONFLICT DO NOTHING;
+
+ALTER TABLE stock_movements ADD COLUMN IF NOT EXISTS warehouse_id BIGINT REFERENCES warehouses(id);
+UPDATE stock_movements SET warehouse_id = (SELECT id FROM warehouses WHERE name = 'main') WHERE warehouse_id IS NULL;
+ALTER TABLE stock_movements ALTER COLUMN warehouse_id SET NOT NULL;
+
+ALTER TABLE stock_movements DROP CONSTRAINT IF EXISTS stock_movements_kind_check;
+ALTER TABLE stock_movements ADD CONSTRAINT stock_movements_kind_check
+  CHECK (kind IN ('receipt', 'sale', 'return', 'adjustment', 'reservation', 'transfer'));
+
+CREATE INDEX IF NOT EXISTS stock_movements_order_id_idx ON stock_movements (order_id);
@@ -0,0 +1,19 @@
+-- Reorder thresholds: a product is low on stock while products.stock is
+-- below its threshold. Products without a row are never low.
+CREATE TABLE IF NOT EXISTS stock_thresholds (
+  product_id BIGINT PRIMARY KEY REFERENCES products(id),
+  threshold  INT NOT NULL CHECK (threshold >= 0)
+);
+
+-- The in-app notification list shown to admins, e.g. a product falling
+-- below its threshold. read_at is NULL until someone marks it read.
+CREATE TABLE IF NOT EXISTS notifications (
+  id         BIGSERIAL PRIMARY KEY,
+  kind       TEXT NOT NULL,
+  message    TEXT NOT NULL,
+  product_id BIGINT REFERENCES products(id),
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  read_at    TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS notifications_unread_idx ON notifications (id) WHERE read_at IS NULL;
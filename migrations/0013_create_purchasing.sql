@@ -0,0 +1,44 @@
+-- Suppliers, what they supply at which SKU and cost, and the purchase
+-- orders placed with them. Receiving an order books stock receipts in the
+-- ledger and moves the product's weighted average cost.
+CREATE TABLE IF NOT EXISTS suppliers (
+  id    BIGSERIAL PRIMARY KEY,
+  name  TEXT NOT NULL UNIQUE,
+  email TEXT
+);
+
+CREATE TABLE IF NOT EXISTS supplier_products (
+  supplier_id BIGINT NOT NULL REFERENCES suppliers(id),
+  product_id  BIGINT NOT NULL REFERENCES products(id),
+  sku         TEXT NOT NULL,
+  cost_price  NUMERIC(10, 2) NOT NULL CHECK (cost_price >= 0),
+  PRIMARY KEY (supplier_id, product_id),
+  UNIQUE (supplier_id, sku)
+);
+
+CREATE INDEX IF NOT EXISTS supplier_products_product_id_idx ON supplier_products (product_id);
+
+CREATE TABLE IF NOT EXISTS purchase_orders (
+  id          BIGSERIAL PRIMARY KEY,
+  supplier_id BIGINT NOT NULL REFERENCES suppliers(id),
+  status      TEXT NOT NULL DEFAULT 'draft'
+              CHECK (status IN ('draft', 'sent', 'partially_received', 'received')),
+  created_by  BIGINT REFERENCES users(id) ON DELETE SET NULL,
+  created_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS purchase_order_lines (
+  purchase_order_id BIGINT NOT NULL REFERENCES purchase_orders(id) ON DELETE CASCADE,
+  product_id        BIGINT NOT NULL REFERENCES products(id),
+  quantity          INT NOT NULL CHECK (quantity > 0),
+  received          INT NOT NULL DEFAULT 0 CHECK (received >= 0 AND received <= quantity),
+  unit_cost         NUMERIC(10, 2) NOT NULL CHECK (unit_cost >= 0),
+  PRIMARY KEY (purchase_order_id, product_id)
+);
+
+-- Weighted average cost of the stock on hand, unrounded so that repeated
+-- receipts do not drift. No row until the product is first received.
+CREATE TABLE IF NOT EXISTS product_costs (
+  product_id   BIGINT PRIMARY KEY REFERENCES products(id),
+  average_cost DOUBLE PRECISION NOT NULL CHECK (average_cost >= 0)
+);
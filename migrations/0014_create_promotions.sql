@@ -0,0 +1,40 @@
+-- Promotions: percentage or fixed discounts, buy-X-get-Y offers and free
+-- shipping, either applied to every cart or unlocked by a coupon code, and
+-- every use of one at checkout. Codes are stored upper case.
+CREATE TABLE IF NOT EXISTS promotions (
+  id                BIGSERIAL PRIMARY KEY,
+  name              TEXT NOT NULL,
+  code              TEXT UNIQUE,
+  kind              TEXT NOT NULL
+                    CHECK (kind IN ('percentage', 'fixed', 'buy_x_get_y', 'free_shipping')),
+  value             NUMERIC(10, 2) NOT NULL DEFAULT 0 CHECK (value >= 0),
+  buy_quantity      INT CHECK (buy_quantity > 0),
+  get_quantity      INT CHECK (get_quantity > 0),
+  category_id       BIGINT REFERENCES categories(id) ON DELETE CASCADE,
+  product_id        BIGINT REFERENCES products(id) ON DELETE CASCADE,
+  starts_at         TIMESTAMPTZ,
+  ends_at           TIMESTAMPTZ,
+  max_uses          INT CHECK (max_uses > 0),
+  max_uses_per_user INT CHECK (max_uses_per_user > 0),
+  active            BOOLEAN NOT NULL DEFAULT TRUE,
+  CHECK (category_id IS NULL OR product_id IS NULL)
+);
+
+CREATE TABLE IF NOT EXISTS promotion_redemptions (
+  id           BIGSERIAL PRIMARY KEY,
+  promotion_id BIGINT NOT NULL REFERENCES promotions(id) ON DELETE CASCADE,
+  user_id      BIGINT REFERENCES users(id) ON DELETE SET NULL,
+  order_id     BIGINT NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+  discount     NUMERIC(10, 2) NOT NULL,
+  created_at   TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS promotion_redemptions_promotion_id_idx ON promotion_redemptions (promotion_id, user_id);
+
+-- The code the shopper entered; checked again on every read of the cart.
+ALTER TABLE carts ADD COLUMN IF NOT EXISTS coupon_code TEXT;
+
+-- total = sum of the lines - discount + shipping
+ALTER TABLE orders
+  ADD COLUMN IF NOT EXISTS discount NUMERIC(10, 2) NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS shipping NUMERIC(10, 2) NOT NULL DEFAULT 0;
@@ -0,0 +1,9 @@
+-- Relay retries: a message that failed waits until next_attempt_at, backing
+-- off, and is given up on (dead_at) after too many attempts. Neither blocks
+-- the rest of the outbox.
+ALTER TABLE outbox
+  ADD COLUMN IF NOT EXISTS next_attempt_at TIMESTAMPTZ,
+  ADD COLUMN IF NOT EXISTS dead_at TIMESTAMPTZ;
+
+DROP INDEX IF EXISTS outbox_pending_idx;
+CREATE INDEX IF NOT EXISTS outbox_pending_idx ON outbox (id) WHERE published_at IS NULL AND dead_at IS NULL;
@@ -0,0 +1,26 @@
+-- SQLite mirror of ../0001_create_tables.sql.
+-- AUTOINCREMENT: ids are never reused, like BIGSERIAL.
+-- price is NUMERIC(10, 2) stored as integer cents.
+
+CREATE TABLE IF NOT EXISTS users (
+  id       INTEGER PRIMARY KEY AUTOINCREMENT,
+  username TEXT NOT NULL,
+  password TEXT NOT NULL,
+  active   BOOLEAN NOT NULL DEFAULT TRUE
+);
+
+CREATE TABLE IF NOT EXISTS categories (
+  id     INTEGER PRIMARY KEY AUTOINCREMENT,
+  name   TEXT NOT NULL,
+  active BOOLEAN NOT NULL DEFAULT TRUE
+);
+
+CREATE TABLE IF NOT EXISTS products (
+  id          INTEGER PRIMARY KEY AUTOINCREMENT,
+  name        TEXT NOT NULL,
+  description TEXT,
+  price_cents INTEGER NOT NULL CHECK (price_cents BETWEEN -9999999999 AND 9999999999),
+  stock       INTEGER NOT NULL,
+  category_id INTEGER NOT NULL REFERENCES categories(id),
+  active      BOOLEAN NOT NULL DEFAULT TRUE
+);
@@ -0,0 +1,15 @@
+-- SQLite mirror of ../0002_create_outbox.sql; payload is JSON text.
+CREATE TABLE IF NOT EXISTS outbox (
+  id             INTEGER PRIMARY KEY AUTOINCREMENT,
+  aggregate_type TEXT NOT NULL,
+  aggregate_id   INTEGER NOT NULL,
+  event_type     TEXT NOT NULL,
+  payload        TEXT NOT NULL,
+  trace_context  TEXT,
+  created_at     TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  published_at   TEXT,
+  attempts       INTEGER NOT NULL DEFAULT 0,
+  last_error     TEXT
+);
+
+CREATE INDEX IF NOT EXISTS outbox_pending_idx ON outbox (id) WHERE published_at IS NULL;
@@ -0,0 +1,23 @@
+-- SQLite mirror of ../0003_create_jobs.sql; payload is JSON text and times
+-- are Unix milliseconds. Claims need no row locks: SQLite has one writer.
+CREATE TABLE IF NOT EXISTS jobs (
+  id            INTEGER PRIMARY KEY AUTOINCREMENT,
+  queue         TEXT NOT NULL,
+  kind          TEXT NOT NULL,
+  payload       TEXT NOT NULL,
+  status        TEXT NOT NULL DEFAULT 'scheduled'
+                CHECK (status IN ('scheduled', 'running', 'completed', 'dead')),
+  attempts      INTEGER NOT NULL DEFAULT 0,
+  max_attempts  INTEGER NOT NULL,
+  run_at        INTEGER NOT NULL,
+  locked_until  INTEGER,
+  unique_key    TEXT UNIQUE,
+  last_error    TEXT,
+  trace_context TEXT,
+  created_at    TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  finished_at   INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS jobs_due_idx ON jobs (queue, run_at) WHERE status = 'scheduled';
+CREATE INDEX IF NOT EXISTS jobs_leased_idx ON jobs (queue, locked_until) WHERE status = 'running';
+CREATE INDEX IF NOT EXISTS jobs_finished_idx ON jobs (finished_at) WHERE finished_at IS NOT NULL;
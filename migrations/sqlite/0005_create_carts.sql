@@ -0,0 +1,20 @@
+-- SQLite mirror of ../0005_create_carts.sql; updated_at is Unix
+-- milliseconds and unit_price is stored as integer cents.
+CREATE TABLE IF NOT EXISTS carts (
+  id          INTEGER PRIMARY KEY AUTOINCREMENT,
+  user_id     INTEGER UNIQUE REFERENCES users(id) ON DELETE CASCADE,
+  guest_token TEXT UNIQUE,
+  updated_at  INTEGER NOT NULL,
+  CHECK ((user_id IS NULL) <> (guest_token IS NULL))
+);
+
+CREATE INDEX IF NOT EXISTS carts_updated_at_idx ON carts (updated_at);
+
+CREATE TABLE IF NOT EXISTS cart_items (
+  id               INTEGER PRIMARY KEY AUTOINCREMENT,
+  cart_id          INTEGER NOT NULL REFERENCES carts(id) ON DELETE CASCADE,
+  product_id       INTEGER NOT NULL REFERENCES products(id),
+  quantity         INTEGER NOT NULL CHECK (quantity > 0),
+  unit_price_cents INTEGER NOT NULL CHECK (unit_price_cents BETWEEN -9999999999 AND 9999999999),
+  UNIQUE (cart_id, product_id)
+);
@@ -0,0 +1,19 @@
+-- SQLite mirror of ../0006_create_orders.sql; created_at is Unix
+-- milliseconds and prices are stored as integer cents.
+CREATE TABLE IF NOT EXISTS orders (
+  id          INTEGER PRIMARY KEY AUTOINCREMENT,
+  user_id     INTEGER REFERENCES users(id) ON DELETE SET NULL,
+  total_cents INTEGER NOT NULL CHECK (total_cents BETWEEN -9999999999 AND 9999999999),
+  created_at  INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS orders_user_id_idx ON orders (user_id);
+
+CREATE TABLE IF NOT EXISTS order_items (
+  id               INTEGER PRIMARY KEY AUTOINCREMENT,
+  order_id         INTEGER NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+  product_id       INTEGER NOT NULL REFERENCES products(id),
+  name             TEXT NOT NULL,
+  quantity         INTEGER NOT NULL CHECK (quantity > 0),
+  unit_price_cents INTEGER NOT NULL CHECK (unit_price_cents BETWEEN -9999999999 AND 9999999999)
+);
@@ -0,0 +1,17 @@
+-- SQLite mirror of ../0007_order_status.sql; changed_at is Unix
+-- milliseconds.
+ALTER TABLE orders
+  ADD COLUMN status TEXT NOT NULL DEFAULT 'pending'
+  CHECK (status IN ('pending', 'paid', 'fulfilled', 'shipped', 'delivered', 'cancelled', 'refunded'));
+
+CREATE TABLE IF NOT EXISTS order_status_changes (
+  id          INTEGER PRIMARY KEY AUTOINCREMENT,
+  order_id    INTEGER NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+  from_status TEXT,
+  to_status   TEXT NOT NULL,
+  actor_id    INTEGER REFERENCES users(id) ON DELETE SET NULL,
+  reason      TEXT,
+  changed_at  INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS order_status_changes_order_id_idx ON order_status_changes (order_id);
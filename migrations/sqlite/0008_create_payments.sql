@@ -0,0 +1,33 @@
+-- SQLite mirror of ../0008_create_payments.sql; timestamps are Unix
+-- milliseconds and amounts are stored as integer cents.
+CREATE TABLE IF NOT EXISTS payment_intents (
+  id           INTEGER PRIMARY KEY AUTOINCREMENT,
+  order_id     INTEGER NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+  provider     TEXT NOT NULL,
+  provider_ref TEXT,
+  amount_cents INTEGER NOT NULL CHECK (amount_cents BETWEEN -9999999999 AND 9999999999),
+  status       TEXT NOT NULL CHECK (status IN ('pending', 'authorized', 'captured', 'failed', 'refunded')),
+  created_at   INTEGER NOT NULL,
+  updated_at   INTEGER NOT NULL,
+  UNIQUE (provider, provider_ref)
+);
+
+CREATE INDEX IF NOT EXISTS payment_intents_order_id_idx ON payment_intents (order_id);
+
+CREATE TABLE IF NOT EXISTS payment_attempts (
+  id         INTEGER PRIMARY KEY AUTOINCREMENT,
+  intent_id  INTEGER NOT NULL REFERENCES payment_intents(id) ON DELETE CASCADE,
+  operation  TEXT NOT NULL CHECK (operation IN ('authorize', 'capture', 'refund', 'webhook')),
+  succeeded  INTEGER NOT NULL,
+  detail     TEXT,
+  created_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS payment_attempts_intent_id_idx ON payment_attempts (intent_id);
+
+CREATE TABLE IF NOT EXISTS payment_webhook_events (
+  provider    TEXT NOT NULL,
+  event_id    TEXT NOT NULL,
+  received_at INTEGER NOT NULL,
+  PRIMARY KEY (provider, event_id)
+);
@@ -0,0 +1,19 @@
+-- SQLite mirror of ../0009_create_stock_movements.sql; created_at is Unix
+-- milliseconds.
+CREATE TABLE IF NOT EXISTS stock_movements (
+  id         INTEGER PRIMARY KEY AUTOINCREMENT,
+  product_id INTEGER NOT NULL REFERENCES products(id),
+  kind       TEXT NOT NULL CHECK (kind IN ('receipt', 'sale', 'return', 'adjustment', 'reservation')),
+  quantity   INTEGER NOT NULL CHECK (quantity <> 0),
+  reason     TEXT,
+  actor_id   INTEGER REFERENCES users(id) ON DELETE SET NULL,
+  order_id   INTEGER REFERENCES orders(id) ON DELETE SET NULL,
+  created_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS stock_movements_product_id_idx ON stock_movements (product_id);
+
+INSERT INTO stock_movements (product_id, kind, quantity, reason, created_at)
+SELECT id, 'adjustment', stock, 'opening balance', CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)
+FROM products
+WHERE stock <> 0;
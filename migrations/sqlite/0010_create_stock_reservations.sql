@@ -0,0 +1,13 @@
+-- SQLite mirror of ../0010_create_stock_reservations.sql; expires_at is Unix
+-- milliseconds.
+CREATE TABLE IF NOT EXISTS stock_reservations (
+  id         INTEGER PRIMARY KEY AUTOINCREMENT,
+  cart_id    INTEGER NOT NULL REFERENCES carts(id) ON DELETE CASCADE,
+  product_id INTEGER NOT NULL REFERENCES products(id),
+  quantity   INTEGER NOT NULL CHECK (quantity > 0),
+  expires_at INTEGER NOT NULL,
+  UNIQUE (cart_id, product_id)
+);
+
+CREATE INDEX IF NOT EXISTS stock_reservations_product_id_idx ON stock_reservations (product_id, expires_at);
+CREATE INDEX IF NOT EXISTS stock_reservations_expires_at_idx ON stock_reservations (expires_at);
@@ -0,0 +1,51 @@
+-- SQLite mirror of ../0011_create_warehouses.sql. SQLite cannot add a
+-- NOT NULL reference or change a CHECK in place, so stock_movements is
+-- rebuilt with its new column.
+CREATE TABLE IF NOT EXISTS warehouses (
+  id        INTEGER PRIMARY KEY AUTOINCREMENT,
+  name      TEXT NOT NULL UNIQUE,
+  priority  INTEGER NOT NULL DEFAULT 0,
+  latitude  REAL,
+  longitude REAL,
+  CHECK ((latitude IS NULL) = (longitude IS NULL))
+);
+
+INSERT INTO warehouses (name) VALUES ('main') ON CONFLICT (name) DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS warehouse_stock (
+  warehouse_id INTEGER NOT NULL REFERENCES warehouses(id),
+  product_id   INTEGER NOT NULL REFERENCES products(id),
+  stock        INTEGER NOT NULL CHECK (stock >= 0),
+  PRIMARY KEY (warehouse_id, product_id)
+);
+
+CREATE INDEX IF NOT EXISTS warehouse_stock_product_id_idx ON warehouse_stock (product_id);
+
+INSERT INTO warehouse_stock (warehouse_id, product_id, stock)
+SELECT w.id, p.id, p.stock
+FROM products p, warehouses w
+WHERE w.name = 'main' AND p.stock > 0
+ON CONFLICT DO NOTHING;
+
+CREATE TABLE stock_movements_new (
+  id           INTEGER PRIMARY KEY AUTOINCREMENT,
+  product_id   INTEGER NOT NULL REFERENCES products(id),
+  warehouse_id INTEGER NOT NULL REFERENCES warehouses(id),
+  kind         TEXT NOT NULL CHECK (kind IN ('receipt', 'sale', 'return', 'adjustment', 'reservation', 'transfer')),
+  quantity     INTEGER NOT NULL CHECK (quantity <> 0),
+  reason       TEXT,
+  actor_id     INTEGER REFERENCES users(id) ON DELETE SET NULL,
+  order_id     INTEGER REFERENCES orders(id) ON DELETE SET NULL,
+  created_at   INTEGER NOT NULL
+);
+
+INSERT INTO stock_movements_new (id, product_id, warehouse_id, kind, quantity, reason, actor_id, order_id, created_at)
+SELECT m.id, m.product_id, w.id, m.kind, m.quantity, m.reason, m.actor_id, m.order_id, m.created_at
+FROM stock_movements m, warehouses w
+WHERE w.name = 'main';
+
+DROP TABLE stock_movements;
+ALTER TABLE stock_movements_new RENAME TO stock_movements;
+
+CREATE INDEX IF NOT EXISTS stock_movements_product_id_idx ON stock_movements (product_id);
+CREATE INDEX IF NOT EXISTS stock_movements_order_id_idx ON stock_movements (order_id);
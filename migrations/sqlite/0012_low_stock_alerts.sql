@@ -0,0 +1,17 @@
+-- SQLite mirror of ../0012_low_stock_alerts.sql; timestamps are Unix
+-- milliseconds.
+CREATE TABLE IF NOT EXISTS stock_thresholds (
+  product_id INTEGER PRIMARY KEY REFERENCES products(id),
+  threshold  INTEGER NOT NULL CHECK (threshold >= 0)
+);
+
+CREATE TABLE IF NOT EXISTS notifications (
+  id         INTEGER PRIMARY KEY AUTOINCREMENT,
+  kind       TEXT NOT NULL,
+  message    TEXT NOT NULL,
+  product_id INTEGER REFERENCES products(id),
+  created_at INTEGER NOT NULL,
+  read_at    INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS notifications_unread_idx ON notifications (id) WHERE read_at IS NULL;
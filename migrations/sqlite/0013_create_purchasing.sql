@@ -0,0 +1,41 @@
+-- SQLite mirror of ../0013_create_purchasing.sql; money is integer cents
+-- and created_at Unix milliseconds.
+CREATE TABLE IF NOT EXISTS suppliers (
+  id    INTEGER PRIMARY KEY AUTOINCREMENT,
+  name  TEXT NOT NULL UNIQUE,
+  email TEXT
+);
+
+CREATE TABLE IF NOT EXISTS supplier_products (
+  supplier_id      INTEGER NOT NULL REFERENCES suppliers(id),
+  product_id       INTEGER NOT NULL REFERENCES products(id),
+  sku              TEXT NOT NULL,
+  cost_price_cents INTEGER NOT NULL CHECK (cost_price_cents >= 0),
+  PRIMARY KEY (supplier_id, product_id),
+  UNIQUE (supplier_id, sku)
+);
+
+CREATE INDEX IF NOT EXISTS supplier_products_product_id_idx ON supplier_products (product_id);
+
+CREATE TABLE IF NOT EXISTS purchase_orders (
+  id          INTEGER PRIMARY KEY AUTOINCREMENT,
+  supplier_id INTEGER NOT NULL REFERENCES suppliers(id),
+  status      TEXT NOT NULL DEFAULT 'draft'
+              CHECK (status IN ('draft', 'sent', 'partially_received', 'received')),
+  created_by  INTEGER REFERENCES users(id) ON DELETE SET NULL,
+  created_at  INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS purchase_order_lines (
+  purchase_order_id INTEGER NOT NULL REFERENCES purchase_orders(id) ON DELETE CASCADE,
+  product_id        INTEGER NOT NULL REFERENCES products(id),
+  quantity          INTEGER NOT NULL CHECK (quantity > 0),
+  received          INTEGER NOT NULL DEFAULT 0 CHECK (received >= 0 AND received <= quantity),
+  unit_cost_cents   INTEGER NOT NULL CHECK (unit_cost_cents >= 0),
+  PRIMARY KEY (purchase_order_id, product_id)
+);
+
+CREATE TABLE IF NOT EXISTS product_costs (
+  product_id   INTEGER PRIMARY KEY REFERENCES products(id),
+  average_cost REAL NOT NULL CHECK (average_cost >= 0)
+);
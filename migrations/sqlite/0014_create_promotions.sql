@@ -0,0 +1,36 @@
+-- SQLite mirror of ../0014_create_promotions.sql; money is integer cents
+-- and timestamps Unix milliseconds.
+CREATE TABLE IF NOT EXISTS promotions (
+  id                INTEGER PRIMARY KEY AUTOINCREMENT,
+  name              TEXT NOT NULL,
+  code              TEXT UNIQUE,
+  kind              TEXT NOT NULL
+                    CHECK (kind IN ('percentage', 'fixed', 'buy_x_get_y', 'free_shipping')),
+  value_cents       INTEGER NOT NULL DEFAULT 0 CHECK (value_cents >= 0),
+  buy_quantity      INTEGER CHECK (buy_quantity > 0),
+  get_quantity      INTEGER CHECK (get_quantity > 0),
+  category_id       INTEGER REFERENCES categories(id) ON DELETE CASCADE,
+  product_id        INTEGER REFERENCES products(id) ON DELETE CASCADE,
+  starts_at         INTEGER,
+  ends_at           INTEGER,
+  max_uses          INTEGER CHECK (max_uses > 0),
+  max_uses_per_user INTEGER CHECK (max_uses_per_user > 0),
+  active            BOOLEAN NOT NULL DEFAULT TRUE,
+  CHECK (category_id IS NULL OR product_id IS NULL)
+);
+
+CREATE TABLE IF NOT EXISTS promotion_redemptions (
+  id             INTEGER PRIMARY KEY AUTOINCREMENT,
+  promotion_id   INTEGER NOT NULL REFERENCES promotions(id) ON DELETE CASCADE,
+  user_id        INTEGER REFERENCES users(id) ON DELETE SET NULL,
+  order_id       INTEGER NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+  discount_cents INTEGER NOT NULL,
+  created_at     INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS promotion_redemptions_promotion_id_idx ON promotion_redemptions (promotion_id, user_id);
+
+ALTER TABLE carts ADD COLUMN coupon_code TEXT;
+
+ALTER TABLE orders ADD COLUMN discount_cents INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE orders ADD COLUMN shipping_cents INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,7 @@
+-- SQLite mirror of ../0015_outbox_retries.sql; next_attempt_at is Unix
+-- milliseconds from the application clock.
+ALTER TABLE outbox ADD COLUMN next_attempt_at INTEGER;
+ALTER TABLE outbox ADD COLUMN dead_at TEXT;
+
+DROP INDEX IF EXISTS outbox_pending_idx;
+CREATE INDEX IF NOT EXISTS outbox_pending_idx ON outbox (id) WHERE published_at IS NULL AND dead_at IS NULL;
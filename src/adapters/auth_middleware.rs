@@ -1,36 +1,50 @@
 use async_trait::async_trait;
 use axum::{
-    extract::FromRequestParts,
+    extract::{FromRef, FromRequestParts},
     http::{request::Parts, StatusCode},
     response::IntoResponse,
 };
 
+use crate::adapters::problem::Problem;
+use crate::domain::cart::CartOwner;
+use crate::domain::DomainError;
+pub use just_learn_api::cart::CART_TOKEN_HEADER;
 use crate::infra::jwt::{verify_token, Claims};
+use crate::usecases::api_key_service::ApiKeyService;
+use crate::usecases::user_service::UserService;
 
+/// A valid `X-Api-Key`: the shared `API_KEY`, if set, or a key issued with
+/// `just-learn-admin api-key issue`.
 pub struct ApiKey;
 
 #[async_trait]
 impl<S> FromRequestParts<S> for ApiKey
 where
     S: Send + Sync,
+    ApiKeyService: FromRef<S>,
 {
     type Rejection = axum::response::Response;
 
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        let expected = std::env::var("API_KEY")
-            .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "API_KEY not set").into_response())?;
-
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         let provided = parts
             .headers
             .get("X-Api-Key")
             .and_then(|v| v.to_str().ok())
-            .ok_or_else(|| (StatusCode::UNAUTHORIZED, "missing X-Api-Key header").into_response())?;
+            .ok_or_else(|| {
+                Problem::new(StatusCode::UNAUTHORIZED).with_detail("missing X-Api-Key header").into_response()
+            })?;
 
-        if provided != expected {
-            return Err((StatusCode::UNAUTHORIZED, "invalid api key").into_response());
+        if std::env::var("API_KEY").is_ok_and(|shared| shared == provided) {
+            return Ok(ApiKey);
+        }
+        match ApiKeyService::from_ref(state).verify(provided).await {
+            Ok(true) => Ok(ApiKey),
+            Ok(false) => Err(Problem::new(StatusCode::UNAUTHORIZED).with_detail("invalid api key").into_response()),
+            Err(e) => {
+                tracing::error!(error = %e, "api key lookup failed");
+                Err(Problem::new(StatusCode::INTERNAL_SERVER_ERROR).into_response())
+            }
         }
-
-        Ok(ApiKey)
     }
 }
 
@@ -48,11 +62,69 @@ where
             .and_then(|v| v.to_str().ok())
             .and_then(|v| v.strip_prefix("Bearer "))
             .ok_or_else(|| {
-                (StatusCode::UNAUTHORIZED, "missing authorization header").into_response()
+                Problem::new(StatusCode::UNAUTHORIZED).with_detail("missing authorization header").into_response()
             })?;
 
-        verify_token(token).map_err(|_| {
-            (StatusCode::UNAUTHORIZED, "invalid or expired token").into_response()
-        })
+        let claims = verify_token(token).map_err(|_| {
+            Problem::new(StatusCode::UNAUTHORIZED).with_detail("invalid or expired token").into_response()
+        })?;
+
+        // Handlers run inside the TraceLayer request span
+        tracing::Span::current().record("enduser.id", claims.sub);
+        Ok(claims)
+    }
+}
+
+/// A valid bearer token of an admin user, e.g. the one made with
+/// `just-learn-admin user create-admin`; 403 for everybody else.
+pub struct Admin(pub Claims);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Admin
+where
+    S: Send + Sync,
+    UserService: FromRef<S>,
+{
+    type Rejection = axum::response::Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let claims = Claims::from_request_parts(parts, state).await?;
+        match UserService::from_ref(state).get_user(claims.sub).await {
+            Ok(user) if user.admin => Ok(Admin(claims)),
+            Ok(_) | Err(DomainError::NotFound) => {
+                Err(Problem::new(StatusCode::FORBIDDEN).with_detail("admin only").into_response())
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "admin lookup failed");
+                Err(Problem::new(StatusCode::INTERNAL_SERVER_ERROR).into_response())
+            }
+        }
+    }
+}
+
+/// Whose cart a request is about: the signed-in user's if there is an
+/// `Authorization` header (which must then be valid), else the guest's from
+/// `X-Cart-Token`, else nobody's yet.
+pub struct Shopper(pub Option<CartOwner>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Shopper
+where
+    S: Send + Sync,
+{
+    type Rejection = axum::response::Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if parts.headers.contains_key("Authorization") {
+            let claims = Claims::from_request_parts(parts, state).await?;
+            return Ok(Shopper(Some(CartOwner::User(claims.sub))));
+        }
+        let guest = parts
+            .headers
+            .get(CART_TOKEN_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .filter(|token| !token.is_empty())
+            .map(|token| CartOwner::Guest(token.to_string()));
+        Ok(Shopper(guest))
     }
 }
@@ -0,0 +1,45 @@
+pub use just_learn_api::cart::{AddCartItemReq, AppliedPromotionResp, ApplyCouponReq, CartLineResp, CartResp, UpdateCartItemReq};
+
+use crate::domain::cart::{PricedCart, PricedLine};
+use crate::domain::promotion::AppliedPromotion;
+
+impl From<PricedLine> for CartLineResp {
+    fn from(l: PricedLine) -> Self {
+        Self {
+            product_id: l.product_id,
+            name: l.name,
+            quantity: l.quantity,
+            unit_price: l.unit_price,
+            price_changed: l.price_changed,
+            available: l.available,
+        }
+    }
+}
+
+impl From<AppliedPromotion> for AppliedPromotionResp {
+    fn from(p: AppliedPromotion) -> Self {
+        Self {
+            promotion_id: p.promotion_id,
+            name: p.name,
+            code: p.code,
+            kind: p.kind.to_string(),
+            discount: p.discount,
+            detail: p.detail,
+        }
+    }
+}
+
+impl From<PricedCart> for CartResp {
+    fn from(c: PricedCart) -> Self {
+        Self {
+            lines: c.lines.into_iter().map(CartLineResp::from).collect(),
+            subtotal: c.pricing.subtotal,
+            discount: c.pricing.discount,
+            shipping: c.pricing.shipping,
+            total: c.pricing.total,
+            coupon: c.coupon,
+            promotions: c.pricing.promotions.into_iter().map(AppliedPromotionResp::from).collect(),
+            coupon_note: c.pricing.coupon_note,
+        }
+    }
+}
@@ -0,0 +1,73 @@
+pub use just_learn_api::inventory::{
+    AdjustStockReq, CreateWarehouseReq, Location, LowStockCategoryResp, LowStockProductResp, ReconciliationResp,
+    StockDiscrepancyResp, StockMovementResp, StockThresholdReq, StockThresholdResp, TransferStockReq, WarehouseResp,
+};
+
+use crate::domain::inventory::{LowStockProduct, StockLevel, StockMovement};
+use crate::domain::warehouse::{GeoPoint, Warehouse};
+use crate::usecases::inventory_service::{LowStockCategory, Reconciliation};
+
+impl From<GeoPoint> for Location {
+    fn from(p: GeoPoint) -> Self {
+        Self { latitude: p.latitude, longitude: p.longitude }
+    }
+}
+
+impl From<Location> for GeoPoint {
+    fn from(l: Location) -> Self {
+        Self { latitude: l.latitude, longitude: l.longitude }
+    }
+}
+
+impl From<Warehouse> for WarehouseResp {
+    fn from(w: Warehouse) -> Self {
+        Self { id: w.id, name: w.name, priority: w.priority, location: w.location.map(Location::from) }
+    }
+}
+
+impl From<StockMovement> for StockMovementResp {
+    fn from(m: StockMovement) -> Self {
+        Self {
+            id: m.id,
+            product_id: m.product_id,
+            warehouse_id: m.warehouse_id,
+            kind: m.kind.to_string(),
+            quantity: m.quantity,
+            reason: m.reason,
+            actor_id: m.actor_id,
+            order_id: m.order_id,
+            created_at: m.created_at.to_rfc3339(),
+        }
+    }
+}
+
+impl From<StockLevel> for StockDiscrepancyResp {
+    fn from(l: StockLevel) -> Self {
+        Self { difference: l.difference(), product_id: l.product_id, name: l.name, stock: l.stock, ledger: l.ledger }
+    }
+}
+
+impl From<Reconciliation> for ReconciliationResp {
+    fn from(r: Reconciliation) -> Self {
+        Self {
+            products_checked: r.products_checked,
+            discrepancies: r.discrepancies.into_iter().map(StockDiscrepancyResp::from).collect(),
+        }
+    }
+}
+
+impl From<LowStockProduct> for LowStockProductResp {
+    fn from(p: LowStockProduct) -> Self {
+        Self { product_id: p.product_id, name: p.name, stock: p.stock, threshold: p.threshold }
+    }
+}
+
+impl From<LowStockCategory> for LowStockCategoryResp {
+    fn from(c: LowStockCategory) -> Self {
+        Self {
+            category_id: c.category_id,
+            category: c.category,
+            products: c.products.into_iter().map(LowStockProductResp::from).collect(),
+        }
+    }
+}
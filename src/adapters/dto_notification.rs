@@ -0,0 +1,16 @@
+pub use just_learn_api::notification::{NotificationQuery, NotificationResp};
+
+use crate::domain::notification::Notification;
+
+impl From<Notification> for NotificationResp {
+    fn from(n: Notification) -> Self {
+        Self {
+            id: n.id,
+            kind: n.kind,
+            message: n.message,
+            product_id: n.product_id,
+            created_at: n.created_at.to_rfc3339(),
+            read_at: n.read_at.map(|t| t.to_rfc3339()),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+pub use just_learn_api::order::{CheckoutReq, OrderLineResp, OrderResp, StatusChangeResp, UpdateOrderStatusReq};
+
+use crate::domain::order::{Order, OrderLine, StatusChange};
+
+impl From<OrderLine> for OrderLineResp {
+    fn from(l: OrderLine) -> Self {
+        Self { product_id: l.product_id, name: l.name, quantity: l.quantity, unit_price: l.unit_price }
+    }
+}
+
+impl From<Order> for OrderResp {
+    fn from(o: Order) -> Self {
+        Self {
+            id: o.id,
+            lines: o.lines.into_iter().map(OrderLineResp::from).collect(),
+            discount: o.discount,
+            shipping: o.shipping,
+            total: o.total,
+            status: o.status.to_string(),
+            created_at: o.created_at.to_rfc3339(),
+        }
+    }
+}
+
+impl From<StatusChange> for StatusChangeResp {
+    fn from(c: StatusChange) -> Self {
+        Self {
+            from: c.from.map(|s| s.to_string()),
+            to: c.to.to_string(),
+            actor_id: c.actor_id,
+            reason: c.reason,
+            changed_at: c.changed_at.to_rfc3339(),
+        }
+    }
+}
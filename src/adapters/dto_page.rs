@@ -0,0 +1,12 @@
+pub use just_learn_api::page::PageQuery;
+
+use crate::domain::page::Page;
+
+impl From<PageQuery> for Page {
+    fn from(q: PageQuery) -> Self {
+        Self {
+            limit: q.limit.map(|limit| i64::try_from(limit).unwrap_or(i64::MAX)),
+            offset: q.offset.map_or(0, |offset| i64::try_from(offset).unwrap_or(i64::MAX)),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+pub use just_learn_api::payment::{PaymentAttemptResp, PaymentResp, RefundOrderReq};
+
+use crate::domain::payment::{Payment, PaymentAttempt};
+
+impl From<PaymentAttempt> for PaymentAttemptResp {
+    fn from(a: PaymentAttempt) -> Self {
+        Self {
+            operation: a.operation.to_string(),
+            succeeded: a.succeeded,
+            detail: a.detail,
+            created_at: a.created_at.to_rfc3339(),
+        }
+    }
+}
+
+impl From<Payment> for PaymentResp {
+    fn from(p: Payment) -> Self {
+        let intent = p.intent;
+        Self {
+            id: intent.id,
+            order_id: intent.order_id,
+            provider: intent.provider,
+            provider_ref: intent.provider_ref,
+            amount: intent.amount,
+            status: intent.status.to_string(),
+            attempts: p.attempts.into_iter().map(PaymentAttemptResp::from).collect(),
+            created_at: intent.created_at.to_rfc3339(),
+            updated_at: intent.updated_at.to_rfc3339(),
+        }
+    }
+}
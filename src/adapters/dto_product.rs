@@ -1,27 +1,29 @@
-use serde::{Deserialize, Serialize};
+pub use just_learn_api::product::{CreateProductReq, CreateProductResp, ProductQuery, ProductResp, StockLocationResp};
 
-#[derive(Serialize, Deserialize)]
-pub struct CreateProductReq {
-    pub name: String,
-    pub description: Option<String>,
-    pub price: f64,
-    pub stock: i32,
-    pub category_id: i64,
-    pub active: bool,
-}
+use crate::domain::reservation::ProductAvailability;
+use crate::domain::warehouse::WarehouseStock;
 
-#[derive(Debug, Serialize)]
-pub struct CreateProductResp {
-    pub id: i64,
+impl From<WarehouseStock> for StockLocationResp {
+    fn from(s: WarehouseStock) -> Self {
+        Self { warehouse_id: s.warehouse_id, warehouse: s.warehouse, stock: s.stock }
+    }
 }
 
-#[derive(Debug, Serialize)]
-pub struct ProductResp {
-    pub id: i64,
-    pub name: String,
-    pub description: Option<String>,
-    pub price: f64,
-    pub stock: i32,
-    pub category_id: i64,
-    pub active: bool,
-}
\ No newline at end of file
+impl From<ProductAvailability> for ProductResp {
+    fn from(a: ProductAvailability) -> Self {
+        let available = a.available();
+        let p = a.product;
+        Self {
+            id: p.id,
+            name: p.name,
+            description: p.description,
+            price: p.price,
+            stock: p.stock,
+            reserved: a.reserved,
+            available,
+            category_id: p.category_id,
+            active: p.active,
+            locations: a.locations.map(|l| l.into_iter().map(StockLocationResp::from).collect()),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+pub use just_learn_api::promotion::{PromotionReq, PromotionResp};
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::promotion::Promotion;
+use crate::usecases::promotion_service::PromotionUsage;
+
+fn parse_time(field: &str, value: Option<String>) -> Result<Option<DateTime<Utc>>, String> {
+    value
+        .map(|v| {
+            DateTime::parse_from_rfc3339(&v)
+                .map(|t| t.with_timezone(&Utc))
+                .map_err(|_| format!("{field} must be an RFC 3339 timestamp"))
+        })
+        .transpose()
+}
+
+/// A promotion without an id yet; the kind and timestamps must parse.
+impl TryFrom<PromotionReq> for Promotion {
+    type Error = String;
+
+    fn try_from(r: PromotionReq) -> Result<Self, Self::Error> {
+        Ok(Promotion {
+            id: 0,
+            name: r.name,
+            code: r.code,
+            kind: r.kind.parse()?,
+            value: r.value,
+            buy_quantity: r.buy_quantity,
+            get_quantity: r.get_quantity,
+            category_id: r.category_id,
+            product_id: r.product_id,
+            starts_at: parse_time("starts_at", r.starts_at)?,
+            ends_at: parse_time("ends_at", r.ends_at)?,
+            max_uses: r.max_uses,
+            max_uses_per_user: r.max_uses_per_user,
+            active: r.active.unwrap_or(true),
+        })
+    }
+}
+
+impl From<PromotionUsage> for PromotionResp {
+    fn from(u: PromotionUsage) -> Self {
+        let p = u.promotion;
+        Self {
+            id: p.id,
+            name: p.name,
+            code: p.code,
+            kind: p.kind.to_string(),
+            value: p.value,
+            buy_quantity: p.buy_quantity,
+            get_quantity: p.get_quantity,
+            category_id: p.category_id,
+            product_id: p.product_id,
+            starts_at: p.starts_at.map(|t| t.to_rfc3339()),
+            ends_at: p.ends_at.map(|t| t.to_rfc3339()),
+            max_uses: p.max_uses,
+            max_uses_per_user: p.max_uses_per_user,
+            active: p.active,
+            uses: u.uses,
+        }
+    }
+}
@@ -0,0 +1,54 @@
+pub use just_learn_api::purchasing::{
+    CreatePurchaseOrderReq, CreateSupplierReq, ProductCostResp, PurchaseOrderLineReq, PurchaseOrderLineResp, PurchaseOrderResp,
+    ReceivePurchaseOrderReq, ReceivedLineReq, SupplierProductReq, SupplierProductResp, SupplierResp,
+};
+
+use crate::domain::purchasing::{PurchaseOrder, Supplier, SupplierProduct};
+use crate::usecases::purchasing_service::{NewPurchaseOrderLine, ReceivedLine};
+
+impl From<Supplier> for SupplierResp {
+    fn from(s: Supplier) -> Self {
+        Self { id: s.id, name: s.name, email: s.email }
+    }
+}
+
+impl From<SupplierProduct> for SupplierProductResp {
+    fn from(l: SupplierProduct) -> Self {
+        Self { supplier_id: l.supplier_id, product_id: l.product_id, sku: l.sku, cost_price: l.cost_price }
+    }
+}
+
+impl From<PurchaseOrder> for PurchaseOrderResp {
+    fn from(o: PurchaseOrder) -> Self {
+        Self {
+            id: o.id,
+            supplier_id: o.supplier_id,
+            status: o.status.to_string(),
+            total: o.total(),
+            lines: o
+                .lines
+                .into_iter()
+                .map(|l| PurchaseOrderLineResp {
+                    product_id: l.product_id,
+                    quantity: l.quantity,
+                    received: l.received,
+                    unit_cost: l.unit_cost,
+                })
+                .collect(),
+            created_by: o.created_by,
+            created_at: o.created_at.to_rfc3339(),
+        }
+    }
+}
+
+impl From<PurchaseOrderLineReq> for NewPurchaseOrderLine {
+    fn from(l: PurchaseOrderLineReq) -> Self {
+        Self { product_id: l.product_id, quantity: l.quantity, unit_cost: l.unit_cost }
+    }
+}
+
+impl From<ReceivedLineReq> for ReceivedLine {
+    fn from(l: ReceivedLineReq) -> Self {
+        Self { product_id: l.product_id, quantity: l.quantity }
+    }
+}
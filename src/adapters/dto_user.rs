@@ -1,43 +1,9 @@
-use serde::{Deserialize, Serialize};
+pub use just_learn_api::user::{
+    CreateUserReq, CreateUserResp, LoginReq, LoginResp, SpeakResp, UpdateUserReq, UserResp,
+};
 
 use crate::domain::user::User;
 
-#[derive(Serialize, Deserialize)]
-pub struct CreateUserReq {
-    pub username: String,
-    pub password: String,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct LoginReq {
-    pub username: String,
-    pub password: String,
-}
-
-#[derive(Debug, Serialize)]
-pub struct CreateUserResp {
-    pub id: i64,
-}
-
-#[derive(Debug, Serialize)]
-pub struct LoginResp {
-    pub token: String,
-}
-
-#[derive(Debug, Serialize)]
-pub struct SpeakResp {
-    pub speak: String,
-    pub shout: String,
-}
-
-#[derive(Debug, Serialize)]
-pub struct UserResp {
-    pub id: i64,
-    pub username: String,
-    pub active: bool,
-    pub greet: String,
-}
-
 impl From<User> for UserResp {
     fn from(u: User) -> Self {
         Self {
@@ -48,9 +14,3 @@ impl From<User> for UserResp {
         }
     }
 }
-
-#[derive(Serialize, Deserialize)]
-pub struct UpdateUserReq {
-    pub username: String,
-    pub password: String,
-}
@@ -1,5 +1,15 @@
 pub mod auth_middleware;
+pub mod problem;
+pub mod request_id;
 pub mod dto_user;
+pub mod dto_cart;
 pub mod dto_category;
+pub mod dto_inventory;
+pub mod dto_notification;
+pub mod dto_order;
 pub mod dto_product;
+pub mod dto_promotion;
+pub mod dto_purchasing;
+pub mod dto_page;
+pub mod dto_payment;
 pub mod restapi;
\ No newline at end of file
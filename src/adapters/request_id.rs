@@ -0,0 +1,34 @@
+use axum::{
+    extract::Request,
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+
+use crate::infra::request_context::{self, RequestId};
+
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Accept `X-Request-Id` from the caller or generate a ULID, then:
+/// - record it as `request.id` on the request span (and so on every log line under it)
+/// - make it available to error mapping through the request context
+/// - echo it back in the response header
+///
+/// Must run inside the `TraceLayer` span.
+pub async fn request_id(mut request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(RequestId::parse)
+        .unwrap_or_else(RequestId::generate);
+
+    tracing::Span::current().record("request.id", id.as_str());
+    request.extensions_mut().insert(id.clone());
+
+    let mut response = request_context::scope(id.clone(), next.run(request)).await;
+    if let Ok(v) = HeaderValue::from_str(id.as_str()) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, v);
+    }
+    response
+}
@@ -0,0 +1,131 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderValue, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{
+    adapters::auth_middleware::{Shopper, CART_TOKEN_HEADER},
+    adapters::dto_cart::{AddCartItemReq, ApplyCouponReq, CartResp, UpdateCartItemReq},
+    domain::cart::{CartOwner, PricedCart},
+    domain::DomainError,
+};
+
+use super::AppState;
+
+/// The cart as JSON; a guest's token is echoed in `X-Cart-Token`.
+fn cart_response(cart: PricedCart) -> axum::response::Response {
+    let guest_token = match &cart.owner {
+        CartOwner::Guest(token) => HeaderValue::from_str(token).ok(),
+        CartOwner::User(_) => None,
+    };
+    let mut response = (StatusCode::OK, Json(CartResp::from(cart))).into_response();
+    if let Some(token) = guest_token {
+        response.headers_mut().insert(CART_TOKEN_HEADER, token);
+    }
+    response
+}
+
+pub async fn get_cart(Shopper(owner): Shopper, State(state): State<AppState>) -> axum::response::Response {
+    let Some(owner) = owner else {
+        return (StatusCode::OK, Json(CartResp::empty())).into_response();
+    };
+    match state.cart_service.get(owner).await {
+        Ok(cart) => {
+            tracing::info!(lines = cart.lines.len(), total = cart.pricing.total, "fetched cart");
+            cart_response(cart)
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn add_cart_item(
+    Shopper(owner): Shopper,
+    State(state): State<AppState>,
+    Json(req): Json<AddCartItemReq>,
+) -> axum::response::Response {
+    match state.cart_service.add_item(owner, req.product_id, req.quantity).await {
+        Ok(cart) => {
+            tracing::info!(product_id = req.product_id, quantity = req.quantity, "added to cart");
+            cart_response(cart)
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn update_cart_item(
+    Shopper(owner): Shopper,
+    State(state): State<AppState>,
+    Path(product_id): Path<i64>,
+    Json(req): Json<UpdateCartItemReq>,
+) -> axum::response::Response {
+    let Some(owner) = owner else {
+        return super::map_error(DomainError::NotFound);
+    };
+    match state.cart_service.set_quantity(owner, product_id, req.quantity).await {
+        Ok(cart) => {
+            tracing::info!(product_id, quantity = req.quantity, "updated cart line");
+            cart_response(cart)
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn remove_cart_item(
+    Shopper(owner): Shopper,
+    State(state): State<AppState>,
+    Path(product_id): Path<i64>,
+) -> axum::response::Response {
+    let Some(owner) = owner else {
+        return super::map_error(DomainError::NotFound);
+    };
+    match state.cart_service.remove_item(owner, product_id).await {
+        Ok(()) => {
+            tracing::info!(product_id, "removed cart line");
+            (StatusCode::NO_CONTENT, Json(())).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+/// The cart priced with the coupon, or why it takes nothing off.
+pub async fn apply_coupon(
+    Shopper(owner): Shopper,
+    State(state): State<AppState>,
+    Json(req): Json<ApplyCouponReq>,
+) -> axum::response::Response {
+    let Some(owner) = owner else {
+        return super::map_error(DomainError::NotFound);
+    };
+    match state.cart_service.apply_coupon(owner, req.code).await {
+        Ok(cart) => {
+            tracing::info!(coupon = ?cart.coupon, discount = cart.pricing.discount, "applied coupon");
+            cart_response(cart)
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn remove_coupon(Shopper(owner): Shopper, State(state): State<AppState>) -> axum::response::Response {
+    let Some(owner) = owner else {
+        return super::map_error(DomainError::NotFound);
+    };
+    match state.cart_service.remove_coupon(owner).await {
+        Ok(cart) => {
+            tracing::info!("removed coupon");
+            cart_response(cart)
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn clear_cart(Shopper(owner): Shopper, State(state): State<AppState>) -> axum::response::Response {
+    if let Some(owner) = owner
+        && let Err(e) = state.cart_service.clear(owner).await
+    {
+        return super::map_error(e);
+    }
+    tracing::info!("cleared cart");
+    (StatusCode::NO_CONTENT, Json(())).into_response()
+}
@@ -1,12 +1,14 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
 
 use crate::{
+    adapters::dto_page::PageQuery,
     adapters::dto_category::{CategoryResp, CreateCategoryReq, CreateCategoryResp, UpdateCategoryReq},
+    domain::DomainError,
     infra::jwt::Claims,
 };
 
@@ -26,8 +28,12 @@ pub async fn create_category(
     }
 }
 
-pub async fn get_all_categories(_claims: Claims, State(state): State<AppState>) -> axum::response::Response {
-    match state.category_service.get_all_categories().await {
+pub async fn get_all_categories(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Query(page): Query<PageQuery>,
+) -> axum::response::Response {
+    match state.category_service.get_all_categories(page.into()).await {
         Ok(categories) => {
             tracing::info!(
                 count = categories.len(),
@@ -52,7 +58,7 @@ pub async fn get_category(_claims: Claims, State(state): State<AppState>, Path(i
         },
         Ok(None) => {
             tracing::warn!(category_id = id, "category not found");
-            (StatusCode::NOT_FOUND, "not found").into_response()
+            super::map_error(DomainError::NotFound)
         }
         Err(e) => super::map_error(e),
     }
@@ -0,0 +1,134 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{
+    adapters::auth_middleware::Admin,
+    adapters::dto_inventory::{
+        AdjustStockReq, CreateWarehouseReq, LowStockCategoryResp, ReconciliationResp, StockMovementResp, StockThresholdReq,
+        StockThresholdResp, TransferStockReq, WarehouseResp,
+    },
+    domain::{inventory::MovementKind, DomainError},
+};
+
+use super::AppState;
+
+pub async fn adjust_stock(
+    Admin(claims): Admin,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(req): Json<AdjustStockReq>,
+) -> axum::response::Response {
+    let kind = match req.kind.parse::<MovementKind>() {
+        Ok(kind) => kind,
+        Err(msg) => return super::map_error(DomainError::Validation(msg)),
+    };
+    match state.inventory_service.adjust(id, req.warehouse_id, kind, req.quantity, req.reason, claims.sub).await {
+        Ok(movement) => {
+            tracing::info!(
+                product_id = id,
+                warehouse_id = movement.warehouse_id,
+                kind = %movement.kind,
+                quantity = movement.quantity,
+                "stock adjusted"
+            );
+            (StatusCode::CREATED, Json(StockMovementResp::from(movement))).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+/// Both movements, out of the source warehouse first.
+pub async fn transfer_stock(
+    Admin(claims): Admin,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(req): Json<TransferStockReq>,
+) -> axum::response::Response {
+    let (from, to, quantity) = (req.from_warehouse_id, req.to_warehouse_id, req.quantity);
+    match state.inventory_service.transfer(id, from, to, quantity, req.reason, claims.sub).await {
+        Ok(movements) => {
+            tracing::info!(product_id = id, from, to, quantity, "stock transferred");
+            let resp: Vec<StockMovementResp> = movements.into_iter().map(StockMovementResp::from).collect();
+            (StatusCode::CREATED, Json(resp)).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn create_warehouse(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Json(req): Json<CreateWarehouseReq>,
+) -> axum::response::Response {
+    let location = req.location.map(Into::into);
+    match state.inventory_service.create_warehouse(req.name, req.priority, location).await {
+        Ok(warehouse) => {
+            tracing::info!(warehouse_id = warehouse.id, name = %warehouse.name, "warehouse created");
+            (StatusCode::CREATED, Json(WarehouseResp::from(warehouse))).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn get_warehouses(_admin: Admin, State(state): State<AppState>) -> axum::response::Response {
+    match state.inventory_service.warehouses().await {
+        Ok(warehouses) => {
+            tracing::info!(warehouse_count = warehouses.len(), "fetched warehouses");
+            let resp: Vec<WarehouseResp> = warehouses.into_iter().map(WarehouseResp::from).collect();
+            (StatusCode::OK, Json(resp)).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn get_stock_movements(_admin: Admin, State(state): State<AppState>, Path(id): Path<i64>) -> axum::response::Response {
+    match state.inventory_service.movements(id).await {
+        Ok(movements) => {
+            tracing::info!(product_id = id, movement_count = movements.len(), "fetched stock movements");
+            let resp: Vec<StockMovementResp> = movements.into_iter().map(StockMovementResp::from).collect();
+            (StatusCode::OK, Json(resp)).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn get_reconciliation(_admin: Admin, State(state): State<AppState>) -> axum::response::Response {
+    match state.inventory_service.reconcile().await {
+        Ok(report) => {
+            tracing::info!(products = report.products_checked, discrepancies = report.discrepancies.len(), "reconciled stock");
+            (StatusCode::OK, Json(ReconciliationResp::from(report))).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn set_stock_threshold(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(req): Json<StockThresholdReq>,
+) -> axum::response::Response {
+    match state.inventory_service.set_threshold(id, req.threshold).await {
+        Ok(()) => {
+            tracing::info!(product_id = id, threshold = ?req.threshold, "stock threshold set");
+            (StatusCode::OK, Json(StockThresholdResp { product_id: id, threshold: req.threshold })).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+/// Products below their reorder threshold, grouped by category.
+pub async fn get_low_stock_report(_admin: Admin, State(state): State<AppState>) -> axum::response::Response {
+    match state.inventory_service.low_stock().await {
+        Ok(report) => {
+            tracing::info!(categories = report.len(), "fetched low stock report");
+            let resp: Vec<LowStockCategoryResp> = report.into_iter().map(LowStockCategoryResp::from).collect();
+            (StatusCode::OK, Json(resp)).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
@@ -1,5 +1,7 @@
 use axum::{
+    extract::FromRef,
     http::StatusCode,
+    middleware,
     response::IntoResponse,
     routing::{get, post, put, delete},
     Router,
@@ -7,23 +9,64 @@ use axum::{
 use tower_http::trace::TraceLayer;
 
 use crate::{
+    adapters::{problem::Problem, request_id::request_id},
     domain::DomainError,
-    infra::http_trace::{OtelOnResponse, record_http_request},
-    usecases::{category_service::CategoryService, user_service::UserService, product_service::ProductService},
+    infra::http_trace::{trace_response_headers, track_http_metrics, OtelMakeSpan, OtelOnResponse},
+    usecases::{
+        api_key_service::ApiKeyService, cart_service::CartService, category_service::CategoryService,
+        inventory_service::InventoryService, notification_service::NotificationService, order_service::OrderService,
+        payment_service::PaymentService, product_service::ProductService, promotion_service::PromotionService,
+        purchasing_service::PurchasingService, user_service::UserService,
+    },
 };
 
 mod user;
 mod category;
 mod product;
+mod inventory;
+mod cart;
+mod order;
+mod payment;
+mod notification;
+mod promotion;
+mod purchasing;
+#[cfg(test)]
+mod tests;
 
 #[derive(Clone)]
 pub struct AppState {
     pub user_service: UserService,
     pub category_service: CategoryService,
     pub product_service: ProductService,
+    pub inventory_service: InventoryService,
+    pub api_key_service: ApiKeyService,
+    pub cart_service: CartService,
+    pub order_service: OrderService,
+    pub payment_service: PaymentService,
+    pub notification_service: NotificationService,
+    pub purchasing_service: PurchasingService,
+    pub promotion_service: PromotionService,
+}
+
+impl FromRef<AppState> for ApiKeyService {
+    fn from_ref(state: &AppState) -> Self {
+        state.api_key_service.clone()
+    }
+}
+
+impl FromRef<AppState> for UserService {
+    fn from_ref(state: &AppState) -> Self {
+        state.user_service.clone()
+    }
 }
 
 pub fn router(state: AppState) -> Router {
+    with_observability(routes().with_state(state))
+}
+
+/// The API's routes, before state and the observability layers are added;
+/// [`App::builder`](crate::app::App::builder) merges extra routes in here.
+pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/health", get(health))
         .route("/login", post(user::login_user))
@@ -42,19 +85,63 @@ pub fn router(state: AppState) -> Router {
         .route("/products/:id", get(product::get_product))
         .route("/products/categories/:id", get(product::get_products_by_category))
         .route("/products", get(product::get_all_products))
-        .with_state(state)
+        .route("/admin/products/:id/stock", post(inventory::adjust_stock))
+        .route("/admin/products/:id/stock/transfers", post(inventory::transfer_stock))
+        .route("/admin/products/:id/stock/movements", get(inventory::get_stock_movements))
+        .route("/admin/warehouses", post(inventory::create_warehouse))
+        .route("/admin/warehouses", get(inventory::get_warehouses))
+        .route("/admin/inventory/reconciliation", get(inventory::get_reconciliation))
+        .route("/admin/products/:id/stock/threshold", put(inventory::set_stock_threshold))
+        .route("/reports/low-stock", get(inventory::get_low_stock_report))
+        .route("/admin/notifications", get(notification::get_notifications))
+        .route("/admin/notifications/:id/read", post(notification::mark_notification_read))
+        .route("/admin/suppliers", post(purchasing::create_supplier))
+        .route("/admin/suppliers", get(purchasing::get_suppliers))
+        .route("/admin/suppliers/:id/products", get(purchasing::get_supplier_products))
+        .route("/admin/suppliers/:id/products/:product_id", put(purchasing::set_supplier_product))
+        .route("/admin/purchase-orders", post(purchasing::create_purchase_order))
+        .route("/admin/purchase-orders", get(purchasing::get_purchase_orders))
+        .route("/admin/purchase-orders/:id", get(purchasing::get_purchase_order))
+        .route("/admin/purchase-orders/:id/send", post(purchasing::send_purchase_order))
+        .route("/admin/purchase-orders/:id/receipts", post(purchasing::receive_purchase_order))
+        .route("/admin/products/:id/cost", get(purchasing::get_product_cost))
+        .route("/admin/promotions", post(promotion::create_promotion))
+        .route("/admin/promotions", get(promotion::get_promotions))
+        .route("/admin/promotions/:id", get(promotion::get_promotion))
+        .route("/admin/promotions/:id", put(promotion::update_promotion))
+        .route("/admin/promotions/:id", delete(promotion::delete_promotion))
+        .route("/cart", get(cart::get_cart))
+        .route("/cart", delete(cart::clear_cart))
+        .route("/cart/items", post(cart::add_cart_item))
+        .route("/cart/items/:product_id", put(cart::update_cart_item))
+        .route("/cart/items/:product_id", delete(cart::remove_cart_item))
+        .route("/cart/coupon", put(cart::apply_coupon))
+        .route("/cart/coupon", delete(cart::remove_coupon))
+        .route("/checkout", post(order::checkout))
+        .route("/orders", get(order::get_orders))
+        .route("/orders/:id", get(order::get_order))
+        .route("/admin/orders/:id/status", post(order::update_order_status))
+        .route("/admin/orders/:id/history", get(order::get_order_history))
+        .route("/orders/:id/pay", post(payment::pay_order))
+        .route("/payments/webhook", post(payment::payment_webhook))
+        .route("/admin/orders/:id/payments", get(payment::get_order_payments))
+        .route("/admin/orders/:id/refund", post(payment::refund_order))
+}
+
+/// Request ids, tracing and HTTP metrics around every route.
+pub fn with_observability(router: Router) -> Router {
+    router
+        .layer(middleware::from_fn(track_http_metrics))
+        .layer(middleware::from_fn(trace_response_headers))
+        .layer(middleware::from_fn(request_id))
         .layer(
             TraceLayer::new_for_http()
-                .make_span_with(
-                    tower_http::trace::DefaultMakeSpan::new()
-                        .level(tracing::Level::INFO),
-                )
+                .make_span_with(OtelMakeSpan)
                 .on_response(OtelOnResponse),
         )
 }
 
 async fn health() -> &'static str {
-    record_http_request("GET", "/health", 200, 0.0);
     "I'm alive!"
 }
 
@@ -62,19 +149,31 @@ fn map_error(e: DomainError) -> axum::response::Response {
     match e {
         DomainError::Validation(msg) => {
             tracing::warn!(error = %msg, "validation error");
-            (StatusCode::BAD_REQUEST, msg).into_response()
+            Problem::new(StatusCode::BAD_REQUEST).with_detail(msg).into_response()
         },
         DomainError::NotFound => {
             tracing::warn!(error = "not found", "not found");
-            (StatusCode::NOT_FOUND, "not found").into_response()
+            Problem::new(StatusCode::NOT_FOUND).into_response()
         },
         DomainError::Unauthorized => {
             tracing::warn!(error = "unauthorized", "unauthorized");
-            (StatusCode::UNAUTHORIZED, "unauthorized").into_response()
+            Problem::new(StatusCode::UNAUTHORIZED).into_response()
+        },
+        DomainError::Conflict(msg) => {
+            tracing::warn!(error = %msg, "conflict");
+            Problem::new(StatusCode::CONFLICT).with_detail(msg).into_response()
+        },
+        DomainError::IllegalTransition(e) => {
+            tracing::warn!(error = %e, "illegal transition");
+            Problem::new(StatusCode::CONFLICT).with_detail(e.to_string()).into_response()
+        },
+        DomainError::PaymentDeclined(reason) => {
+            tracing::warn!(error = %reason, "payment declined");
+            Problem::new(StatusCode::PAYMENT_REQUIRED).with_detail(format!("payment declined: {reason}")).into_response()
         },
         DomainError::Unexpected(msg) => {
             tracing::warn!(error = %msg, "unexpected error");
-            (StatusCode::INTERNAL_SERVER_ERROR, msg).into_response()
+            Problem::new(StatusCode::INTERNAL_SERVER_ERROR).with_detail(msg).into_response()
         },
     }
 }
@@ -0,0 +1,39 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::adapters::{
+    auth_middleware::Admin,
+    dto_notification::{NotificationQuery, NotificationResp},
+};
+
+use super::AppState;
+
+/// Newest first.
+pub async fn get_notifications(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Query(query): Query<NotificationQuery>,
+) -> axum::response::Response {
+    match state.notification_service.list(query.unread).await {
+        Ok(notifications) => {
+            tracing::info!(notification_count = notifications.len(), unread = query.unread, "fetched notifications");
+            let resp: Vec<NotificationResp> = notifications.into_iter().map(NotificationResp::from).collect();
+            (StatusCode::OK, Json(resp)).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn mark_notification_read(_admin: Admin, State(state): State<AppState>, Path(id): Path<i64>) -> axum::response::Response {
+    match state.notification_service.mark_read(id).await {
+        Ok(()) => {
+            tracing::info!(notification_id = id, "notification read");
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
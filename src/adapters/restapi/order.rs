@@ -0,0 +1,90 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{
+    adapters::auth_middleware::Admin,
+    adapters::dto_order::{CheckoutReq, OrderResp, StatusChangeResp, UpdateOrderStatusReq},
+    adapters::dto_page::PageQuery,
+    domain::{order::OrderStatus, warehouse::GeoPoint, DomainError},
+    infra::jwt::Claims,
+};
+
+use super::AppState;
+
+/// The body is optional; without `ship_to` stock is allocated by
+/// warehouse priority.
+pub async fn checkout(
+    claims: Claims,
+    State(state): State<AppState>,
+    req: Option<Json<CheckoutReq>>,
+) -> axum::response::Response {
+    let ship_to = match req.and_then(|Json(req)| req.ship_to) {
+        Some(l) => match GeoPoint::new(l.latitude, l.longitude) {
+            Some(point) => Some(point),
+            None => return super::map_error(DomainError::Validation("invalid ship_to location".into())),
+        },
+        None => None,
+    };
+    match state.order_service.checkout(claims.sub, ship_to).await {
+        Ok(order) => {
+            tracing::info!(order_id = order.id, lines = order.lines.len(), total = order.total, "order placed");
+            (StatusCode::CREATED, Json(OrderResp::from(order))).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn get_orders(claims: Claims, State(state): State<AppState>, Query(page): Query<PageQuery>) -> axum::response::Response {
+    match state.order_service.get_by_user_id(claims.sub, page.into()).await {
+        Ok(orders) => {
+            tracing::info!(order_count = orders.len(), "fetched orders");
+            let resp: Vec<OrderResp> = orders.into_iter().map(OrderResp::from).collect();
+            (StatusCode::OK, Json(resp)).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn get_order(claims: Claims, State(state): State<AppState>, Path(id): Path<i64>) -> axum::response::Response {
+    match state.order_service.get(claims.sub, id).await {
+        Ok(order) => {
+            tracing::info!(order_id = order.id, "fetched order");
+            (StatusCode::OK, Json(OrderResp::from(order))).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn update_order_status(
+    Admin(claims): Admin,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateOrderStatusReq>,
+) -> axum::response::Response {
+    let status = match req.status.parse::<OrderStatus>() {
+        Ok(status) => status,
+        Err(msg) => return super::map_error(DomainError::Validation(msg)),
+    };
+    match state.order_service.transition(id, status, claims.sub, req.reason).await {
+        Ok(order) => {
+            tracing::info!(order_id = order.id, status = %order.status, "order status changed");
+            (StatusCode::OK, Json(OrderResp::from(order))).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn get_order_history(_admin: Admin, State(state): State<AppState>, Path(id): Path<i64>) -> axum::response::Response {
+    match state.order_service.history(id).await {
+        Ok(history) => {
+            tracing::info!(order_id = id, changes = history.len(), "fetched order history");
+            let resp: Vec<StatusChangeResp> = history.into_iter().map(StatusChangeResp::from).collect();
+            (StatusCode::OK, Json(resp)).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
@@ -0,0 +1,61 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{
+    adapters::auth_middleware::Admin,
+    adapters::dto_payment::{PaymentResp, RefundOrderReq},
+    infra::jwt::Claims,
+};
+
+use super::AppState;
+
+pub async fn pay_order(claims: Claims, State(state): State<AppState>, Path(id): Path<i64>) -> axum::response::Response {
+    match state.payment_service.pay(claims.sub, id).await {
+        Ok(payment) => {
+            tracing::info!(order_id = id, payment_id = payment.intent.id, amount = payment.intent.amount, "order paid");
+            (StatusCode::OK, Json(PaymentResp::from(payment))).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+/// Notifications from the payment provider; authenticated by the
+/// `X-Signature` over the body, not a token.
+pub async fn payment_webhook(State(state): State<AppState>, headers: HeaderMap, body: Bytes) -> axum::response::Response {
+    let signature = headers.get("X-Signature").and_then(|v| v.to_str().ok()).unwrap_or_default();
+    match state.payment_service.handle_webhook(signature, &body).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn get_order_payments(_admin: Admin, State(state): State<AppState>, Path(id): Path<i64>) -> axum::response::Response {
+    match state.payment_service.payments(id).await {
+        Ok(payments) => {
+            tracing::info!(order_id = id, payment_count = payments.len(), "fetched payments");
+            let resp: Vec<PaymentResp> = payments.into_iter().map(PaymentResp::from).collect();
+            (StatusCode::OK, Json(resp)).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn refund_order(
+    Admin(claims): Admin,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(req): Json<RefundOrderReq>,
+) -> axum::response::Response {
+    match state.payment_service.refund(id, claims.sub, req.reason).await {
+        Ok(payment) => {
+            tracing::info!(order_id = id, payment_id = payment.intent.id, "order refunded");
+            (StatusCode::OK, Json(PaymentResp::from(payment))).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
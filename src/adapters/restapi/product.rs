@@ -1,13 +1,14 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::{IntoResponse},
     Json,
 };
 
 use crate::{
-    adapters::dto_product::{CreateProductReq, CreateProductResp, ProductResp},
-    domain::product::Product,
+    adapters::dto_page::PageQuery,
+    adapters::dto_product::{CreateProductReq, CreateProductResp, ProductQuery, ProductResp},
+    domain::{product::Product, DomainError},
     infra::jwt::Claims,
 };
 
@@ -37,62 +38,68 @@ pub async fn create_product(
     }
 }
 
-pub async fn get_product(_claims: Claims, State(state): State<AppState>, Path(id): Path<i64>) -> axum::response::Response {
+pub async fn get_product(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(query): Query<ProductQuery>,
+) -> axum::response::Response {
     match state.product_service.get_by_product_id(id).await {
         Ok(Some(p)) => {
             tracing::info!(product_id = p.id, name = %p.name, active = p.active, "fetched product");
-            let resp = ProductResp {
-                id: p.id,
-                name: p.name,
-                description: p.description,
-                price: p.price,
-                stock: p.stock,
-                category_id: p.category_id,
-                active: p.active,
-            };
-            (StatusCode::OK, Json(resp)).into_response()
+            match state.product_service.with_availability(vec![p], query.locations).await {
+                Ok(mut products) => (StatusCode::OK, Json(ProductResp::from(products.remove(0)))).into_response(),
+                Err(e) => super::map_error(e),
+            }
         },
         Ok(None) => {
-            (StatusCode::NOT_FOUND, "Product not found").into_response()
+            super::map_error(DomainError::NotFound)
         },
         Err(e) => super::map_error(e),
     }
 }
 
-pub async fn get_products_by_category(_claims: Claims, State(state): State<AppState>, Path(category_id): Path<i64>) -> axum::response::Response {
-    match state.product_service.get_by_category_id(category_id).await {
+pub async fn get_products_by_category(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(category_id): Path<i64>,
+    Query(page): Query<PageQuery>,
+    Query(query): Query<ProductQuery>,
+) -> axum::response::Response {
+    match state.product_service.get_by_category_id(category_id, page.into()).await {
         Ok(products) => {
             tracing::info!(category_id = category_id, product_count = products.len(), "fetched products by category {}", category_id);
-            let resp: Vec<ProductResp> = products.into_iter().map(|p| ProductResp {
-                id: p.id,
-                name: p.name,
-                description: p.description,
-                price: p.price,
-                stock: p.stock,
-                category_id: p.category_id,
-                active: p.active,
-            }).collect();
-            (StatusCode::OK, Json(resp)).into_response()
+            products_page(&state, query, products).await
         },
         Err(e) => super::map_error(e),
     }
 }
 
-pub async fn get_all_products(_claims: Claims, State(state): State<AppState>) -> axum::response::Response {
-    match state.product_service.get_all_products().await {
+pub async fn get_all_products(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Query(page): Query<PageQuery>,
+    Query(query): Query<ProductQuery>,
+) -> axum::response::Response {
+    match state.product_service.get_all_products(page.into()).await {
         Ok(products) => {
             tracing::info!(product_count = products.len(), "fetched all products {:#?}", &products[..products.len().min(5)]);
-            let resp: Vec<ProductResp> = products.into_iter().map(|p| ProductResp {
-                id: p.id,
-                name: p.name,
-                description: p.description,
-                price: p.price,
-                stock: p.stock,
-                category_id: p.category_id,
-                active: p.active,
-            }).collect();
-            (StatusCode::OK, Json(resp)).into_response()
+            products_page(&state, query, products).await
         },
         Err(e) => super::map_error(e),
     }
-}
\ No newline at end of file
+}
+/// A page of `products`, with their reservations.
+async fn products_page(
+    state: &AppState,
+    query: ProductQuery,
+    products: Vec<Product>,
+) -> axum::response::Response {
+    match state.product_service.with_availability(products, query.locations).await {
+        Ok(products) => {
+            let resp: Vec<ProductResp> = products.into_iter().map(ProductResp::from).collect();
+            (StatusCode::OK, Json(resp)).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
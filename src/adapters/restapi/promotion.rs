@@ -0,0 +1,84 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::adapters::{
+    auth_middleware::Admin,
+    dto_promotion::{PromotionReq, PromotionResp},
+};
+use crate::domain::promotion::Promotion;
+use crate::domain::DomainError;
+
+use super::AppState;
+
+pub async fn create_promotion(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Json(req): Json<PromotionReq>,
+) -> axum::response::Response {
+    let promotion = match Promotion::try_from(req) {
+        Ok(promotion) => promotion,
+        Err(msg) => return super::map_error(DomainError::Validation(msg)),
+    };
+    match state.promotion_service.create(promotion).await {
+        Ok(created) => {
+            tracing::info!(promotion_id = created.promotion.id, kind = %created.promotion.kind, "promotion created");
+            (StatusCode::CREATED, Json(PromotionResp::from(created))).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+/// By id.
+pub async fn get_promotions(_admin: Admin, State(state): State<AppState>) -> axum::response::Response {
+    match state.promotion_service.list().await {
+        Ok(promotions) => {
+            tracing::info!(promotion_count = promotions.len(), "fetched promotions");
+            let resp: Vec<PromotionResp> = promotions.into_iter().map(PromotionResp::from).collect();
+            (StatusCode::OK, Json(resp)).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn get_promotion(_admin: Admin, State(state): State<AppState>, Path(id): Path<i64>) -> axum::response::Response {
+    match state.promotion_service.get(id).await {
+        Ok(promotion) => {
+            tracing::info!(promotion_id = id, "fetched promotion");
+            (StatusCode::OK, Json(PromotionResp::from(promotion))).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn update_promotion(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(req): Json<PromotionReq>,
+) -> axum::response::Response {
+    let promotion = match Promotion::try_from(req) {
+        Ok(promotion) => Promotion { id, ..promotion },
+        Err(msg) => return super::map_error(DomainError::Validation(msg)),
+    };
+    match state.promotion_service.update(promotion).await {
+        Ok(updated) => {
+            tracing::info!(promotion_id = id, "promotion updated");
+            (StatusCode::OK, Json(PromotionResp::from(updated))).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn delete_promotion(_admin: Admin, State(state): State<AppState>, Path(id): Path<i64>) -> axum::response::Response {
+    match state.promotion_service.delete(id).await {
+        Ok(()) => {
+            tracing::info!(promotion_id = id, "promotion deleted");
+            (StatusCode::NO_CONTENT, Json(())).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
@@ -0,0 +1,142 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::adapters::{
+    auth_middleware::Admin,
+    dto_purchasing::{
+        CreatePurchaseOrderReq, CreateSupplierReq, ProductCostResp, PurchaseOrderResp, ReceivePurchaseOrderReq, SupplierProductReq,
+        SupplierProductResp, SupplierResp,
+    },
+};
+
+use super::AppState;
+
+pub async fn create_supplier(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Json(req): Json<CreateSupplierReq>,
+) -> axum::response::Response {
+    match state.purchasing_service.create_supplier(req.name, req.email).await {
+        Ok(supplier) => {
+            tracing::info!(supplier_id = supplier.id, name = %supplier.name, "supplier created");
+            (StatusCode::CREATED, Json(SupplierResp::from(supplier))).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn get_suppliers(_admin: Admin, State(state): State<AppState>) -> axum::response::Response {
+    match state.purchasing_service.suppliers().await {
+        Ok(suppliers) => {
+            tracing::info!(supplier_count = suppliers.len(), "fetched suppliers");
+            let resp: Vec<SupplierResp> = suppliers.into_iter().map(SupplierResp::from).collect();
+            (StatusCode::OK, Json(resp)).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn set_supplier_product(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path((id, product_id)): Path<(i64, i64)>,
+    Json(req): Json<SupplierProductReq>,
+) -> axum::response::Response {
+    match state.purchasing_service.set_supplier_product(id, product_id, req.sku, req.cost_price).await {
+        Ok(link) => {
+            tracing::info!(supplier_id = id, product_id, sku = %link.sku, "supplier product set");
+            (StatusCode::OK, Json(SupplierProductResp::from(link))).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+/// By product id.
+pub async fn get_supplier_products(_admin: Admin, State(state): State<AppState>, Path(id): Path<i64>) -> axum::response::Response {
+    match state.purchasing_service.supplier_products(id).await {
+        Ok(links) => {
+            tracing::info!(supplier_id = id, product_count = links.len(), "fetched supplier products");
+            let resp: Vec<SupplierProductResp> = links.into_iter().map(SupplierProductResp::from).collect();
+            (StatusCode::OK, Json(resp)).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn create_purchase_order(
+    Admin(claims): Admin,
+    State(state): State<AppState>,
+    Json(req): Json<CreatePurchaseOrderReq>,
+) -> axum::response::Response {
+    let lines = req.lines.into_iter().map(Into::into).collect();
+    match state.purchasing_service.create_order(req.supplier_id, lines, claims.sub).await {
+        Ok(order) => {
+            tracing::info!(purchase_order_id = order.id, supplier_id = order.supplier_id, "purchase order created");
+            (StatusCode::CREATED, Json(PurchaseOrderResp::from(order))).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+/// Newest first.
+pub async fn get_purchase_orders(_admin: Admin, State(state): State<AppState>) -> axum::response::Response {
+    match state.purchasing_service.orders().await {
+        Ok(orders) => {
+            tracing::info!(purchase_order_count = orders.len(), "fetched purchase orders");
+            let resp: Vec<PurchaseOrderResp> = orders.into_iter().map(PurchaseOrderResp::from).collect();
+            (StatusCode::OK, Json(resp)).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn get_purchase_order(_admin: Admin, State(state): State<AppState>, Path(id): Path<i64>) -> axum::response::Response {
+    match state.purchasing_service.order(id).await {
+        Ok(order) => {
+            tracing::info!(purchase_order_id = id, "fetched purchase order");
+            (StatusCode::OK, Json(PurchaseOrderResp::from(order))).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn send_purchase_order(_admin: Admin, State(state): State<AppState>, Path(id): Path<i64>) -> axum::response::Response {
+    match state.purchasing_service.send(id).await {
+        Ok(order) => {
+            tracing::info!(purchase_order_id = id, "purchase order sent");
+            (StatusCode::OK, Json(PurchaseOrderResp::from(order))).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+/// The order as it stands after the receipt.
+pub async fn receive_purchase_order(
+    Admin(claims): Admin,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(req): Json<ReceivePurchaseOrderReq>,
+) -> axum::response::Response {
+    let lines = req.lines.into_iter().map(Into::into).collect();
+    match state.purchasing_service.receive(id, req.warehouse_id, lines, claims.sub).await {
+        Ok(order) => {
+            tracing::info!(purchase_order_id = id, status = %order.status, "purchase order received");
+            (StatusCode::OK, Json(PurchaseOrderResp::from(order))).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
+
+pub async fn get_product_cost(_admin: Admin, State(state): State<AppState>, Path(id): Path<i64>) -> axum::response::Response {
+    match state.purchasing_service.average_cost(id).await {
+        Ok(average_cost) => {
+            tracing::info!(product_id = id, "fetched product cost");
+            (StatusCode::OK, Json(ProductCostResp { product_id: id, average_cost })).into_response()
+        }
+        Err(e) => super::map_error(e),
+    }
+}
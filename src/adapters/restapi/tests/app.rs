@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::extract::State;
+use axum::http::{HeaderValue, Method, StatusCode};
+use axum::response::Response;
+use axum::routing::get;
+use axum::{middleware, Json, Router};
+use serde_json::{json, Value};
+
+use crate::adapters::restapi::AppState;
+use crate::app::App;
+use crate::domain::page::Page;
+use crate::domain::product::{Product, ProductRepository};
+use crate::domain::DomainError;
+use crate::infra::jwt::Claims;
+use crate::infra::repository::backend::Repositories;
+use crate::usecases::housekeeping::PruneFinishedJobs;
+
+use super::{assert_problem, TestApp};
+
+/// Products served from elsewhere, e.g. another service's catalog.
+struct FixedProducts(Vec<Product>);
+
+#[async_trait]
+impl ProductRepository for FixedProducts {
+    async fn create(&self, _product: Product) -> Result<i64, DomainError> {
+        Err(DomainError::Unexpected("read-only".into()))
+    }
+
+    async fn get_by_product_id(&self, id: i64) -> Result<Option<Product>, DomainError> {
+        Ok(self.0.iter().find(|p| p.id == id).cloned())
+    }
+
+    async fn get_by_category_id(&self, category_id: i64, page: Page) -> Result<Vec<Product>, DomainError> {
+        Ok(page.apply(self.0.iter().filter(|p| p.category_id == category_id).cloned()))
+    }
+
+    async fn get_all_products(&self, page: Page) -> Result<Vec<Product>, DomainError> {
+        Ok(page.apply(self.0.iter().cloned()))
+    }
+}
+
+async fn whoami(claims: Claims, State(state): State<AppState>) -> Json<Value> {
+    let users = state.user_service.get_all_users(Page::ALL).await.unwrap();
+    Json(json!({ "username": claims.username, "users": users.len() }))
+}
+
+async fn tag(mut response: Response) -> Response {
+    response.headers_mut().insert("x-served-by", HeaderValue::from_static("embedded"));
+    response
+}
+
+#[tokio::test]
+async fn builder_merges_extra_routes_with_state_and_auth() {
+    let app = TestApp::with(TestApp::repositories(), |builder| {
+        builder.routes(Router::new().route("/whoami", get(whoami)))
+    });
+    let token = app.token().await;
+
+    let response = app.get("/whoami", &token).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json(), json!({ "username": "alice", "users": 1 }));
+    assert!(response.headers.contains_key("x-request-id"));
+
+    assert_problem(&app.request(Method::GET, "/whoami", None, None).await, StatusCode::UNAUTHORIZED);
+    // the API's own routes are still there
+    assert_eq!(app.get("/users", &token).await.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn builder_layers_wrap_every_route() {
+    let app = TestApp::with(TestApp::repositories(), |builder| {
+        builder.layer(middleware::map_response(tag))
+    });
+
+    let response = app.request(Method::GET, "/health", None, None).await;
+    assert_eq!(response.headers["x-served-by"], "embedded");
+    let response = app.request(Method::GET, "/users/1", None, None).await;
+    assert_eq!(response.headers["x-served-by"], "embedded");
+    assert_problem(&response, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn builder_uses_custom_repositories() {
+    let fixed = FixedProducts(vec![Product {
+        id: 7,
+        name: "Imported".into(),
+        description: None,
+        price: 9.5,
+        stock: 1,
+        category_id: 3,
+        active: true,
+    }]);
+    let repositories = Repositories { products: Arc::new(fixed), ..TestApp::repositories() };
+    let app = TestApp::with(repositories, |builder| builder);
+    let token = app.token().await;
+
+    let response = app.get("/products/7", &token).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json()["name"], "Imported");
+    assert_eq!(app.get("/products/categories/3", &token).await.json().as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn builder_rejects_invalid_schedules() {
+    let app = App::builder().schedule("bad", "every day", PruneFinishedJobs { retention_days: 1 }).build();
+    assert!(matches!(app, Err(DomainError::Validation(_))));
+}
@@ -0,0 +1,284 @@
+use axum::body::Body;
+use axum::http::{header, Method, Request, StatusCode};
+use serde_json::{json, Value};
+
+use crate::domain::cart::{CartLine, CartOwner};
+
+use super::{assert_problem, TestApp, TestResponse};
+
+/// `GET /cart` without anything in it.
+fn empty_cart() -> Value {
+    json!({
+        "lines": [],
+        "subtotal": 0.0,
+        "discount": 0.0,
+        "shipping": 0.0,
+        "total": 0.0,
+        "coupon": null,
+        "promotions": [],
+        "coupon_note": null,
+    })
+}
+
+/// `POST /products` body with `stock` of 3.
+fn product(name: &str, price: f64, active: bool) -> Value {
+    json!({ "name": name, "description": null, "price": price, "stock": 3, "category_id": 1, "active": active })
+}
+
+/// Products 1 (Rust Book, 40.00), 2 (Chess, 15.00) and 3 (inactive).
+async fn app_with_products() -> (TestApp, String) {
+    app_with_products_on(TestApp::new()).await
+}
+
+async fn app_with_products_on(app: TestApp) -> (TestApp, String) {
+    let token = app.token().await;
+    app.post("/categories", &token, json!({ "name": "Books" })).await;
+    app.post("/products", &token, product("Rust Book", 39.999, true)).await;
+    app.post("/products", &token, product("Chess", 15.0, true)).await;
+    app.post("/products", &token, product("Retired", 1.0, false)).await;
+    (app, token)
+}
+
+/// A cart request as a guest holding `cart_token`, if any.
+async fn as_guest(app: &TestApp, method: Method, uri: &str, cart_token: Option<&str>, body: Option<Value>) -> TestResponse {
+    let mut builder = Request::builder().method(method).uri(uri);
+    if let Some(cart_token) = cart_token {
+        builder = builder.header("X-Cart-Token", cart_token);
+    }
+    let request = match body {
+        Some(body) => builder.header(header::CONTENT_TYPE, "application/json").body(Body::from(body.to_string())),
+        None => builder.body(Body::empty()),
+    };
+    app.send(request.unwrap()).await
+}
+
+fn cart_token(response: &TestResponse) -> String {
+    response.headers["x-cart-token"].to_str().unwrap().to_string()
+}
+
+fn quantities(response: &TestResponse) -> Vec<(i64, i64)> {
+    response.json()["lines"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|l| (l["product_id"].as_i64().unwrap(), l["quantity"].as_i64().unwrap()))
+        .collect()
+}
+
+#[tokio::test]
+async fn guest_cart_is_created_on_first_add() {
+    let (app, _) = app_with_products().await;
+
+    let response = as_guest(&app, Method::GET, "/cart", None, None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json(), empty_cart());
+    assert!(!response.headers.contains_key("x-cart-token"));
+
+    let body = json!({ "product_id": 1, "quantity": 2 });
+    let response = as_guest(&app, Method::POST, "/cart/items", None, Some(body)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let token = cart_token(&response);
+    assert_eq!(token.len(), 32);
+
+    let body = json!({ "product_id": 2, "quantity": 1 });
+    as_guest(&app, Method::POST, "/cart/items", Some(&token), Some(body)).await;
+    let body = json!({ "product_id": 1, "quantity": 1 });
+    as_guest(&app, Method::POST, "/cart/items", Some(&token), Some(body)).await;
+
+    let response = as_guest(&app, Method::GET, "/cart", Some(&token), None).await;
+    assert_eq!(cart_token(&response), token);
+    assert_eq!(
+        response.json(),
+        json!({
+            "lines": [
+                { "product_id": 1, "name": "Rust Book", "quantity": 3, "unit_price": 40.0, "price_changed": false, "available": true },
+                { "product_id": 2, "name": "Chess", "quantity": 1, "unit_price": 15.0, "price_changed": false, "available": true },
+            ],
+            "subtotal": 135.0,
+            "discount": 0.0,
+            "shipping": 0.0,
+            "total": 135.0,
+            "coupon": null,
+            "promotions": [],
+            "coupon_note": null,
+        })
+    );
+
+    // an unknown token is not adopted: the guest gets a fresh cart
+    let body = json!({ "product_id": 2, "quantity": 1 });
+    let response = as_guest(&app, Method::POST, "/cart/items", Some("made-up"), Some(body)).await;
+    assert_ne!(cart_token(&response), "made-up");
+}
+
+#[tokio::test]
+async fn cart_checks_stock_and_availability() {
+    let (app, token) = app_with_products().await;
+    let add = |product_id: i64, quantity: i32| app.post("/cart/items", &token, json!({ "product_id": product_id, "quantity": quantity }));
+
+    assert_eq!(add(1, 3).await.status, StatusCode::OK);
+    let response = add(1, 1).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "only 3 of product 1 in stock");
+
+    let response = add(3, 1).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "product 3 is not available");
+
+    let response = add(99, 1).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "product 99 does not exist");
+
+    assert_problem(&add(2, 0).await, StatusCode::BAD_REQUEST);
+    assert_eq!(quantities(&app.get("/cart", &token).await), [(1, 3)]);
+}
+
+#[tokio::test]
+async fn update_and_remove_cart_lines() {
+    let (app, token) = app_with_products().await;
+    app.post("/cart/items", &token, json!({ "product_id": 1, "quantity": 1 })).await;
+    app.post("/cart/items", &token, json!({ "product_id": 2, "quantity": 1 })).await;
+
+    let response = app.put("/cart/items/2", &token, json!({ "quantity": 3 })).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(quantities(&response), [(1, 1), (2, 3)]);
+    assert!(!response.headers.contains_key("x-cart-token"));
+
+    assert_problem(&app.put("/cart/items/2", &token, json!({ "quantity": 4 })).await, StatusCode::BAD_REQUEST);
+    assert_problem(&app.put("/cart/items/3", &token, json!({ "quantity": 1 })).await, StatusCode::NOT_FOUND);
+
+    assert_eq!(app.delete("/cart/items/1", &token).await.status, StatusCode::NO_CONTENT);
+    assert_problem(&app.delete("/cart/items/1", &token).await, StatusCode::NOT_FOUND);
+    assert_eq!(quantities(&app.get("/cart", &token).await), [(2, 3)]);
+
+    assert_eq!(app.delete("/cart", &token).await.status, StatusCode::NO_CONTENT);
+    assert_eq!(app.get("/cart", &token).await.json(), empty_cart());
+    assert_eq!(app.delete("/cart", &token).await.status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn cart_lines_are_repriced() {
+    let repositories = TestApp::repositories();
+    let carts = repositories.carts.clone();
+    let (app, token) = app_with_products_on(TestApp::with(repositories, |b| b)).await;
+    app.post("/cart/items", &token, json!({ "product_id": 1, "quantity": 2 })).await;
+
+    // priced at 35.00 when the shopper last looked
+    let cart = carts.find(&CartOwner::User(1)).await.unwrap().unwrap();
+    carts.set_line(cart.id, CartLine { product_id: 1, quantity: 2, unit_price: 35.0 }).await.unwrap();
+
+    let response = app.get("/cart", &token).await;
+    assert_eq!(response.json()["lines"][0]["unit_price"], 40.0);
+    assert_eq!(response.json()["lines"][0]["price_changed"], true);
+    assert_eq!(response.json()["total"], 80.0);
+    // reported once
+    assert_eq!(app.get("/cart", &token).await.json()["lines"][0]["price_changed"], false);
+}
+
+/// `(stock, reserved, available)` of the product.
+async fn availability(app: &TestApp, token: &str, product_id: i64) -> (i64, i64, i64) {
+    let product = app.get(&format!("/products/{product_id}"), token).await.json();
+    let field = |name: &str| product[name].as_i64().unwrap();
+    (field("stock"), field("reserved"), field("available"))
+}
+
+#[tokio::test]
+async fn carts_reserve_their_lines() {
+    let (app, token) = app_with_products().await;
+    app.post("/cart/items", &token, json!({ "product_id": 1, "quantity": 2 })).await;
+    assert_eq!(availability(&app, &token, 1).await, (3, 2, 1));
+
+    // other shoppers only get what is left
+    let body = json!({ "product_id": 1, "quantity": 2 });
+    let response = as_guest(&app, Method::POST, "/cart/items", None, Some(body)).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "only 1 of product 1 in stock");
+    let body = json!({ "product_id": 1, "quantity": 1 });
+    let guest = cart_token(&as_guest(&app, Method::POST, "/cart/items", None, Some(body)).await);
+    assert_eq!(availability(&app, &token, 1).await, (3, 3, 0));
+    let listed = app.get("/products", &token).await.json();
+    assert_eq!((listed[0]["reserved"].clone(), listed[1]["reserved"].clone()), (json!(3), json!(0)));
+
+    // a cart's own reservation is its to use
+    let response = app.put("/cart/items/1", &token, json!({ "quantity": 3 })).await;
+    assert_eq!(response.json()["detail"], "only 2 of product 1 in stock");
+    assert_eq!(app.get("/cart", &token).await.json()["lines"][0]["available"], true);
+
+    assert_eq!(app.delete("/cart/items/1", &token).await.status, StatusCode::NO_CONTENT);
+    assert_eq!(availability(&app, &token, 1).await, (3, 1, 2));
+    as_guest(&app, Method::DELETE, "/cart", Some(&guest), None).await;
+    assert_eq!(availability(&app, &token, 1).await, (3, 0, 3));
+}
+
+#[tokio::test]
+async fn lapsed_reservations_free_the_stock() {
+    let app = TestApp::with(TestApp::repositories(), |builder| builder.reservation_minutes(0));
+    let (app, token) = app_with_products_on(app).await;
+    app.post("/cart/items", &token, json!({ "product_id": 1, "quantity": 2 })).await;
+    assert_eq!(availability(&app, &token, 1).await, (3, 0, 3));
+
+    let body = json!({ "product_id": 1, "quantity": 3 });
+    let response = as_guest(&app, Method::POST, "/cart/items", None, Some(body)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let guest = cart_token(&response);
+
+    // the first checkout gets the stock, the other cart is left short
+    assert_eq!(app.post("/checkout", &token, Value::Null).await.status, StatusCode::CREATED);
+    let response = as_guest(&app, Method::GET, "/cart", Some(&guest), None).await;
+    assert_eq!(response.json()["lines"][0]["available"], false);
+}
+
+#[tokio::test]
+async fn guest_cart_merges_into_user_cart_on_login() {
+    // reservations expire at once, so the guest can add the books alice holds
+    let app = TestApp::with(TestApp::repositories(), |builder| builder.reservation_minutes(0));
+    let (app, token) = app_with_products_on(app).await;
+    app.post("/cart/items", &token, json!({ "product_id": 1, "quantity": 2 })).await;
+
+    let body = json!({ "product_id": 1, "quantity": 2 });
+    let guest = cart_token(&as_guest(&app, Method::POST, "/cart/items", None, Some(body)).await);
+    let body = json!({ "product_id": 2, "quantity": 1 });
+    as_guest(&app, Method::POST, "/cart/items", Some(&guest), Some(body)).await;
+
+    let login = Request::post("/login")
+        .header("X-Cart-Token", &guest)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(json!({ "username": "alice", "password": "secret" }).to_string()))
+        .unwrap();
+    let response = app.send(login).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    // 2 + 2 capped at the 3 in stock
+    assert_eq!(quantities(&app.get("/cart", &token).await), [(1, 3), (2, 1)]);
+    let response = as_guest(&app, Method::GET, "/cart", Some(&guest), None).await;
+    assert_eq!(response.json()["lines"], json!([]));
+
+    // logging in again with the spent token changes nothing
+    let login = Request::post("/login")
+        .header("X-Cart-Token", &guest)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(json!({ "username": "alice", "password": "secret" }).to_string()))
+        .unwrap();
+    assert_eq!(app.send(login).await.status, StatusCode::OK);
+    assert_eq!(quantities(&app.get("/cart", &token).await), [(1, 3), (2, 1)]);
+}
+
+#[tokio::test]
+async fn idle_carts_expire() {
+    let app = TestApp::with(TestApp::repositories(), |builder| builder.cart_idle_days(0));
+    let (app, token) = app_with_products_on(app).await;
+
+    let response = app.post("/cart/items", &token, json!({ "product_id": 1, "quantity": 1 })).await;
+    assert_eq!(quantities(&response), [(1, 1)]);
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    assert_eq!(app.get("/cart", &token).await.json()["lines"], json!([]));
+}
+
+#[tokio::test]
+async fn cart_rejects_invalid_tokens() {
+    let app = TestApp::new();
+    for (method, uri) in [(Method::GET, "/cart"), (Method::POST, "/cart/items"), (Method::DELETE, "/cart/items/1")] {
+        let response = app.request(method, uri, Some("not-a-jwt"), None).await;
+        assert_problem(&response, StatusCode::UNAUTHORIZED);
+    }
+    assert_problem(&as_guest(&app, Method::PUT, "/cart/items/1", Some("unknown"), Some(json!({ "quantity": 1 }))).await, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,131 @@
+use axum::http::StatusCode;
+use serde_json::json;
+
+use super::{assert_problem, TestApp};
+
+#[tokio::test]
+async fn create_and_get_category() {
+    let app = TestApp::new();
+    let token = app.token().await;
+
+    let response = app.post("/categories", &token, json!({ "name": "Books" })).await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    assert_eq!(response.json(), json!({ "id": 1 }));
+
+    let response = app.get("/categories/1", &token).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json(), json!({ "id": 1, "name": "Books", "active": true }));
+
+    assert_problem(&app.get("/categories/99", &token).await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn create_category_requires_name() {
+    let app = TestApp::new();
+    let token = app.token().await;
+
+    let response = app.post("/categories", &token, json!({ "name": " " })).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "name is required");
+}
+
+#[tokio::test]
+async fn list_categories() {
+    let app = TestApp::new();
+    let token = app.token().await;
+    app.post("/categories", &token, json!({ "name": "Books" })).await;
+    app.post("/categories", &token, json!({ "name": "Games" })).await;
+
+    let response = app.get("/categories", &token).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.json(),
+        json!([
+            { "id": 1, "name": "Books", "active": true },
+            { "id": 2, "name": "Games", "active": true },
+        ])
+    );
+}
+
+#[tokio::test]
+async fn list_categories_by_page() {
+    let app = TestApp::new();
+    let token = app.token().await;
+    for name in ["a", "b", "c"] {
+        app.post("/categories", &token, json!({ "name": name })).await;
+    }
+
+    let names = |response: super::TestResponse| -> Vec<String> {
+        let body = response.json();
+        body.as_array().unwrap().iter().map(|c| c["name"].as_str().unwrap().to_string()).collect()
+    };
+    assert_eq!(names(app.get("/categories?limit=2", &token).await), ["a", "b"]);
+    assert_eq!(names(app.get("/categories?limit=2&offset=2", &token).await), ["c"]);
+    assert!(names(app.get("/categories?offset=5", &token).await).is_empty());
+
+    let response = app.get("/categories?limit=-1", &token).await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn update_category() {
+    let app = TestApp::new();
+    let token = app.token().await;
+    app.post("/categories", &token, json!({ "name": "Books" })).await;
+
+    let response = app.put("/categories/1", &token, json!({ "name": "E-books" })).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json()["name"], "E-books");
+
+    assert_problem(&app.put("/categories/99", &token, json!({ "name": "x" })).await, StatusCode::NOT_FOUND);
+    assert_problem(&app.put("/categories/1", &token, json!({ "name": "" })).await, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn delete_category() {
+    let app = TestApp::new();
+    let token = app.token().await;
+    app.post("/categories", &token, json!({ "name": "Books" })).await;
+
+    assert_eq!(app.delete("/categories/1", &token).await.status, StatusCode::NO_CONTENT);
+    assert_problem(&app.get("/categories/1", &token).await, StatusCode::NOT_FOUND);
+    assert_problem(&app.delete("/categories/1", &token).await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn delete_category_with_products_fails() {
+    let app = TestApp::new();
+    let token = app.token().await;
+    app.post("/categories", &token, json!({ "name": "Books" })).await;
+    let product = json!({
+        "name": "Rust Book", "description": null, "price": 39.9,
+        "stock": 3, "category_id": 1, "active": true,
+    });
+    assert_eq!(app.post("/products", &token, product).await.status, StatusCode::CREATED);
+
+    assert_problem(&app.delete("/categories/1", &token).await, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(app.get("/categories/1", &token).await.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn category_changes_raise_events() {
+    let app = TestApp::new();
+    let token = app.token().await;
+
+    app.post("/categories", &token, json!({ "name": "Books" })).await;
+    app.put("/categories/1", &token, json!({ "name": "Books" })).await;
+    app.put("/categories/1", &token, json!({ "name": "E-books" })).await;
+    app.delete("/categories/1", &token).await;
+    // failed changes raise nothing
+    app.put("/categories/1", &token, json!({ "name": "x" })).await;
+
+    let events: Vec<_> = app.events().await.into_iter().filter(|(t, _)| t.starts_with("Category")).collect();
+    assert_eq!(
+        events,
+        [
+            ("CategoryCreated".to_string(), json!({ "id": 1, "name": "Books" })),
+            ("CategoryRenamed".to_string(), json!({ "id": 1, "old_name": "Books", "new_name": "E-books" })),
+            ("CategoryDeleted".to_string(), json!({ "id": 1 })),
+        ]
+    );
+}
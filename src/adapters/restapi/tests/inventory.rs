@@ -0,0 +1,316 @@
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use crate::domain::product::Product;
+use crate::domain::warehouse::AllocationStrategy;
+
+use super::order::{admin_with_order, login_as, stock};
+use super::{assert_problem, TestApp};
+
+/// `(kind, quantity, reason, actor_id, order_id)` of product 1's movements.
+async fn movements(app: &TestApp, token: &str) -> Vec<(String, i64, Value, Value, Value)> {
+    let response = app.get("/admin/products/1/stock/movements", token).await;
+    assert_eq!(response.status, StatusCode::OK);
+    response
+        .json()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| {
+            let kind = m["kind"].as_str().unwrap().to_string();
+            (kind, m["quantity"].as_i64().unwrap(), m["reason"].clone(), m["actor_id"].clone(), m["order_id"].clone())
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn orders_book_sales_and_returns() {
+    let (app, admin) = admin_with_order(|builder| builder).await;
+    let response = app.post("/admin/orders/1/status", &admin, json!({ "status": "cancelled" })).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    assert_eq!(
+        movements(&app, &admin).await,
+        [
+            ("receipt".into(), 3, json!("initial stock"), Value::Null, Value::Null),
+            ("sale".into(), -2, json!("order 1"), json!(1), json!(1)),
+            ("return".into(), 2, json!("order 1 cancelled"), json!(1), json!(1)),
+        ]
+    );
+    assert_eq!(stock(&app, &admin, 1).await, 3);
+}
+
+#[tokio::test]
+async fn admins_post_receipts_and_adjustments() {
+    let (app, admin) = admin_with_order(|builder| builder).await;
+    assert_eq!(stock(&app, &admin, 1).await, 1);
+
+    let response = app
+        .post("/admin/products/1/stock", &admin, json!({ "kind": "receipt", "quantity": 5, "reason": "delivery 17" }))
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    let movement = response.json();
+    assert_eq!((movement["kind"].clone(), movement["quantity"].clone()), (json!("receipt"), json!(5)));
+    assert_eq!((movement["actor_id"].clone(), movement["reason"].clone()), (json!(1), json!("delivery 17")));
+    assert_eq!(stock(&app, &admin, 1).await, 6);
+
+    // adjustments by default, and never below zero
+    let response = app.post("/admin/products/1/stock", &admin, json!({ "quantity": -1, "reason": "damaged" })).await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    assert_eq!(response.json()["kind"], "adjustment");
+    let response = app.post("/admin/products/1/stock", &admin, json!({ "quantity": -6, "reason": "lost" })).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "only 5 of product 1 in stock at main");
+    assert_eq!(stock(&app, &admin, 1).await, 5);
+
+    for (body, detail) in [
+        (json!({ "kind": "sale", "quantity": -1, "reason": "x" }), "sale movements are recorded by orders"),
+        (json!({ "kind": "receipt", "quantity": -1, "reason": "x" }), "invalid receipt quantity -1"),
+        (json!({ "quantity": 0, "reason": "x" }), "invalid adjustment quantity 0"),
+        (json!({ "quantity": 1, "reason": " " }), "reason is required"),
+        (json!({ "kind": "gift", "quantity": 1, "reason": "x" }), "invalid stock movement kind \"gift\""),
+    ] {
+        let response = app.post("/admin/products/1/stock", &admin, body).await;
+        assert_problem(&response, StatusCode::BAD_REQUEST);
+        assert_eq!(response.json()["detail"], detail);
+    }
+    let response = app.post("/admin/products/42/stock", &admin, json!({ "quantity": 1, "reason": "x" })).await;
+    assert_problem(&response, StatusCode::NOT_FOUND);
+    assert_problem(&app.get("/admin/products/42/stock/movements", &admin).await, StatusCode::NOT_FOUND);
+
+    let adjusted: Vec<_> = app.events().await.into_iter().filter(|(kind, _)| kind == "StockAdjusted").map(|(_, e)| e).collect();
+    assert_eq!(
+        adjusted,
+        [
+            json!({ "product_id": 1, "warehouse_id": 1, "kind": "receipt", "quantity": 5, "stock": 6 }),
+            json!({ "product_id": 1, "warehouse_id": 1, "kind": "adjustment", "quantity": -1, "stock": 5 }),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn reconciliation_reports_stock_off_the_ledger() {
+    let repositories = TestApp::repositories();
+    let (users, products) = (repositories.users.clone(), repositories.products.clone());
+    let app = TestApp::with(repositories, |builder| builder);
+    let admin = app.token().await;
+    users.set_admin(1, true).await.unwrap();
+    app.post("/categories", &admin, json!({ "name": "Books" })).await;
+    let product = json!({ "name": "Rust Book", "description": null, "price": 40.0, "stock": 3, "category_id": 1, "active": true });
+    app.post("/products", &admin, product).await;
+
+    let response = app.get("/admin/inventory/reconciliation", &admin).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json(), json!({ "products_checked": 1, "discrepancies": [] }));
+
+    // written straight to the table, past the ledger
+    let chess = Product {
+        id: 0,
+        name: "Chess".into(),
+        description: None,
+        price: 15.0,
+        stock: 7,
+        category_id: 1,
+        active: true,
+    };
+    products.create(chess).await.unwrap();
+    let response = app.get("/admin/inventory/reconciliation", &admin).await;
+    assert_eq!(
+        response.json(),
+        json!({
+            "products_checked": 2,
+            "discrepancies": [{ "product_id": 2, "name": "Chess", "stock": 7, "ledger": 0, "difference": 7 }],
+        })
+    );
+}
+
+/// Product 1's stock by warehouse, as `(warehouse_id, stock)`.
+async fn locations(app: &TestApp, token: &str) -> Vec<(i64, i64)> {
+    let product = app.get("/products/1?locations=true", token).await.json();
+    product["locations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|l| (l["warehouse_id"].as_i64().unwrap(), l["stock"].as_i64().unwrap()))
+        .collect()
+}
+
+#[tokio::test]
+async fn warehouses_keep_their_own_stock() {
+    let (app, admin) = admin_with_order(|builder| builder).await;
+    let response = app.post("/admin/warehouses", &admin, json!({ "name": "east", "priority": 1 })).await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    assert_eq!(response.json(), json!({ "id": 2, "name": "east", "priority": 1, "location": null }));
+    let warehouses = app.get("/admin/warehouses", &admin).await.json();
+    let names: Vec<_> = warehouses.as_array().unwrap().iter().map(|w| w["name"].clone()).collect();
+    assert_eq!(names, [json!("main"), json!("east")]);
+
+    let receipt = json!({ "kind": "receipt", "quantity": 4, "reason": "delivery", "warehouse_id": 2 });
+    let response = app.post("/admin/products/1/stock", &admin, receipt).await;
+    assert_eq!(response.json()["warehouse_id"], 2);
+    assert_eq!(locations(&app, &admin).await, [(1, 1), (2, 4)]);
+    assert_eq!(stock(&app, &admin, 1).await, 5);
+    // without asking, no locations
+    assert!(app.get("/products/1", &admin).await.json().get("locations").is_none());
+
+    let transfer = |from: i64, to: i64, quantity: i32| {
+        json!({ "from_warehouse_id": from, "to_warehouse_id": to, "quantity": quantity, "reason": "rebalance" })
+    };
+    let response = app.post("/admin/products/1/stock/transfers", &admin, transfer(2, 1, 3)).await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    let moved: Vec<_> =
+        response.json().as_array().unwrap().iter().map(|m| (m["warehouse_id"].clone(), m["quantity"].clone())).collect();
+    assert_eq!(moved, [(json!(2), json!(-3)), (json!(1), json!(3))]);
+    assert_eq!(locations(&app, &admin).await, [(1, 4), (2, 1)]);
+    assert_eq!(stock(&app, &admin, 1).await, 5);
+    let events = app.events().await;
+    let transferred: Vec<_> = events.iter().filter(|(kind, _)| kind == "StockTransferred").map(|(_, e)| e).collect();
+    assert_eq!(transferred, [&json!({ "product_id": 1, "from_warehouse_id": 2, "to_warehouse_id": 1, "quantity": 3 })]);
+
+    let transfers = "/admin/products/1/stock/transfers";
+    let far_north = json!({ "name": "north", "location": { "latitude": 91.0, "longitude": 0.0 } });
+    for (uri, body, detail) in [
+        (transfers, transfer(2, 1, 2), "only 1 of product 1 in stock at east"),
+        (transfers, transfer(1, 1, 1), "cannot transfer to the same warehouse"),
+        (transfers, transfer(1, 42, 1), "no warehouse 42"),
+        (transfers, transfer(1, 2, 0), "invalid transfer quantity 0"),
+        ("/admin/products/1/stock", json!({ "quantity": 1, "reason": "x", "warehouse_id": 42 }), "no warehouse 42"),
+        ("/admin/warehouses", json!({ "name": "east" }), "warehouse \"east\" already exists"),
+        ("/admin/warehouses", far_north, "latitude must be within [-90, 90] and longitude within [-180, 180]"),
+    ] {
+        let response = app.post(uri, &admin, body).await;
+        assert_problem(&response, StatusCode::BAD_REQUEST);
+        assert_eq!(response.json()["detail"], detail);
+    }
+    assert_eq!(locations(&app, &admin).await, [(1, 4), (2, 1)]);
+}
+
+#[tokio::test]
+async fn checkout_ships_from_the_nearest_warehouse() {
+    let (app, admin) = admin_with_order(|builder| builder.allocation_strategy(AllocationStrategy::Nearest)).await;
+    // order 1 took 2 of the 3 in main, which has no location
+    for (name, latitude, longitude) in [("paris", 48.86, 2.35), ("berlin", 52.52, 13.40)] {
+        let warehouse = json!({ "name": name, "priority": 1, "location": { "latitude": latitude, "longitude": longitude } });
+        let id = app.post("/admin/warehouses", &admin, warehouse).await.json()["id"].clone();
+        let receipt = json!({ "kind": "receipt", "quantity": 2, "reason": "delivery", "warehouse_id": id });
+        app.post("/admin/products/1/stock", &admin, receipt).await;
+    }
+    assert_eq!(locations(&app, &admin).await, [(1, 1), (2, 2), (3, 2)]);
+
+    // near Berlin: all of it from there
+    app.post("/cart/items", &admin, json!({ "product_id": 1, "quantity": 2 })).await;
+    let ship_to = json!({ "ship_to": { "latitude": 52.0, "longitude": 13.0 } });
+    assert_eq!(app.post("/checkout", &admin, ship_to).await.status, StatusCode::CREATED);
+    assert_eq!(locations(&app, &admin).await, [(1, 1), (2, 2)]);
+
+    // near Paris, more than Paris holds: Paris first, the rest from main
+    app.post("/cart/items", &admin, json!({ "product_id": 1, "quantity": 3 })).await;
+    let ship_to = json!({ "ship_to": { "latitude": 48.0, "longitude": 2.0 } });
+    assert_eq!(app.post("/checkout", &admin, ship_to).await.status, StatusCode::CREATED);
+    assert!(locations(&app, &admin).await.is_empty());
+
+    // cancelling puts it back where it came from
+    let response = app.post("/admin/orders/3/status", &admin, json!({ "status": "cancelled" })).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(locations(&app, &admin).await, [(1, 1), (2, 2)]);
+
+    let response = app.post("/checkout", &admin, json!({ "ship_to": { "latitude": 0.0, "longitude": 200.0 } })).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "invalid ship_to location");
+}
+
+#[tokio::test]
+async fn falling_below_the_threshold_notifies_once() {
+    let (app, admin) = admin_with_order(|builder| builder).await;
+    let response = app.put("/admin/products/1/stock/threshold", &admin, json!({ "threshold": 2 })).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json(), json!({ "product_id": 1, "threshold": 2 }));
+    app.put("/admin/products/2/stock/threshold", &admin, json!({ "threshold": 2 })).await;
+    // already below when set: reported, but nobody is notified
+    assert_eq!(app.get("/admin/notifications", &admin).await.json(), json!([]));
+
+    // 3 -> 1 crosses the threshold, 1 -> 0 is below already
+    app.post("/cart/items", &admin, json!({ "product_id": 2, "quantity": 2 })).await;
+    assert_eq!(app.post("/checkout", &admin, Value::Null).await.status, StatusCode::CREATED);
+    let response = app.post("/admin/products/2/stock", &admin, json!({ "quantity": -1, "reason": "damaged" })).await;
+    assert_eq!(response.status, StatusCode::CREATED);
+
+    let low: Vec<_> = app.events().await.into_iter().filter(|(kind, _)| kind == "LowStock").map(|(_, e)| e).collect();
+    assert_eq!(low, [json!({ "product_id": 2, "stock": 1, "threshold": 2 })]);
+    let response = app.get("/admin/notifications?unread=true", &admin).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let notifications = response.json();
+    assert_eq!(notifications.as_array().unwrap().len(), 1);
+    let notification = &notifications[0];
+    assert_eq!((notification["kind"].clone(), notification["product_id"].clone()), (json!("low_stock"), json!(2)));
+    assert_eq!(notification["message"], "Chess is low on stock: 1 left, reorder threshold 2");
+    assert_eq!(notification["read_at"], Value::Null);
+
+    let response = app.post("/admin/notifications/1/read", &admin, Value::Null).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+    assert_eq!(app.get("/admin/notifications?unread=true", &admin).await.json(), json!([]));
+    assert!(app.get("/admin/notifications", &admin).await.json()[0]["read_at"].is_string());
+    assert_problem(&app.post("/admin/notifications/42/read", &admin, Value::Null).await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn low_stock_report_groups_by_category() {
+    let (app, admin) = admin_with_order(|builder| builder).await;
+    assert_eq!(app.get("/reports/low-stock", &admin).await.json(), json!([]));
+    app.post("/categories", &admin, json!({ "name": "Art" })).await;
+    let paint = json!({ "name": "Paint", "description": null, "price": 5.0, "stock": 1, "category_id": 2, "active": true });
+    app.post("/products", &admin, paint).await;
+    for (id, threshold) in [(1, 2), (2, 3), (3, 5)] {
+        let response = app.put(&format!("/admin/products/{id}/stock/threshold"), &admin, json!({ "threshold": threshold })).await;
+        assert_eq!(response.status, StatusCode::OK);
+    }
+
+    let response = app.get("/reports/low-stock", &admin).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.json(),
+        json!([
+            {
+                "category_id": 2,
+                "category": "Art",
+                "products": [{ "product_id": 3, "name": "Paint", "stock": 1, "threshold": 5 }],
+            },
+            {
+                "category_id": 1,
+                "category": "Books",
+                "products": [{ "product_id": 1, "name": "Rust Book", "stock": 1, "threshold": 2 }],
+            },
+        ])
+    );
+
+    let response = app.put("/admin/products/3/stock/threshold", &admin, json!({ "threshold": null })).await;
+    assert_eq!(response.json(), json!({ "product_id": 3, "threshold": null }));
+    assert_eq!(app.get("/reports/low-stock", &admin).await.json().as_array().unwrap().len(), 1);
+
+    let response = app.put("/admin/products/1/stock/threshold", &admin, json!({ "threshold": -1 })).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "threshold cannot be negative");
+    let response = app.put("/admin/products/42/stock/threshold", &admin, json!({ "threshold": 1 })).await;
+    assert_problem(&response, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn inventory_is_admin_only() {
+    let (app, _admin) = admin_with_order(|builder| builder).await;
+    let bob = login_as(&app, "bob").await;
+
+    let response = app.post("/admin/products/1/stock", &bob, json!({ "quantity": 1, "reason": "x" })).await;
+    assert_problem(&response, StatusCode::FORBIDDEN);
+    assert_problem(&app.get("/admin/products/1/stock/movements", &bob).await, StatusCode::FORBIDDEN);
+    assert_problem(&app.get("/admin/inventory/reconciliation", &bob).await, StatusCode::FORBIDDEN);
+    let transfer = json!({ "from_warehouse_id": 1, "to_warehouse_id": 1, "quantity": 1, "reason": "x" });
+    assert_problem(&app.post("/admin/products/1/stock/transfers", &bob, transfer).await, StatusCode::FORBIDDEN);
+    assert_problem(&app.post("/admin/warehouses", &bob, json!({ "name": "x" })).await, StatusCode::FORBIDDEN);
+    assert_problem(&app.get("/admin/warehouses", &bob).await, StatusCode::FORBIDDEN);
+    let threshold = json!({ "threshold": 5 });
+    assert_problem(&app.put("/admin/products/1/stock/threshold", &bob, threshold).await, StatusCode::FORBIDDEN);
+    assert_problem(&app.get("/reports/low-stock", &bob).await, StatusCode::FORBIDDEN);
+    assert_problem(&app.get("/admin/notifications", &bob).await, StatusCode::FORBIDDEN);
+    assert_problem(&app.post("/admin/notifications/1/read", &bob, Value::Null).await, StatusCode::FORBIDDEN);
+    assert_eq!(stock(&app, &bob, 1).await, 1);
+}
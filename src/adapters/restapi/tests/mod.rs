@@ -0,0 +1,276 @@
+//! HTTP tests: the real router over in-memory repositories, driven with
+//! `tower::ServiceExt::oneshot` — no database or listener needed.
+
+use std::sync::{Arc, Once};
+
+use axum::{
+    body::{Body, Bytes},
+    http::{header, HeaderMap, Method, Request, StatusCode},
+    Router,
+};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use tower::ServiceExt;
+
+use crate::app::{App, AppBuilder};
+use crate::domain::uow::UnitOfWork;
+use crate::infra::repository::backend::{Backend, Repositories};
+use crate::infra::repository::memory::{InMemoryJobQueue, InMemoryStore};
+use crate::usecases::api_key_service::ApiKeyService;
+
+mod app;
+mod cart;
+mod category;
+mod inventory;
+mod order;
+mod payment;
+mod product;
+mod promotion;
+mod purchasing;
+mod trace;
+mod user;
+
+pub const API_KEY: &str = "test-api-key";
+
+fn init_env() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        // SAFETY: runs once before any test reads these, and every test
+        // expects the same values
+        unsafe {
+            std::env::set_var("API_KEY", API_KEY);
+            std::env::set_var("JWT_SECRET", "test-jwt-secret");
+        }
+    });
+}
+
+pub struct TestApp {
+    router: Router,
+    uow: Arc<dyn UnitOfWork>,
+    pub api_keys: ApiKeyService,
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl TestResponse {
+    pub fn json(&self) -> Value {
+        serde_json::from_slice(&self.body).expect("response body is not JSON")
+    }
+
+    pub fn content_type(&self) -> &str {
+        self.headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+    }
+}
+
+impl TestApp {
+    pub fn new() -> Self {
+        Self::with(Self::repositories(), |builder| builder)
+    }
+
+    /// Fresh in-memory repositories, to customize before [`TestApp::with`].
+    pub fn repositories() -> Repositories {
+        Backend::Memory(InMemoryStore::new(), InMemoryJobQueue::new()).repositories()
+    }
+
+    /// The app built on `repositories` after `configure` ran on the builder.
+    pub fn with(repositories: Repositories, configure: impl FnOnce(AppBuilder) -> AppBuilder) -> Self {
+        init_env();
+        let uow = repositories.uow.clone();
+        let app = configure(App::builder().repositories(repositories)).build().unwrap();
+        Self { router: app.router(), uow, api_keys: app.state().api_key_service.clone() }
+    }
+
+    /// `(event_type, payload)` of every unpublished outbox event, oldest first.
+    pub async fn events(&self) -> Vec<(String, Value)> {
+        self.uow
+            .transaction(|tx| Box::pin(async move { tx.outbox.pending(1000).await }))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|m| (m.event_type, m.payload))
+            .collect()
+    }
+
+    pub async fn send(&self, request: Request<Body>) -> TestResponse {
+        let response = self.router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        TestResponse { status, headers, body }
+    }
+
+    /// Request with optional bearer token and JSON body.
+    pub async fn request(
+        &self,
+        method: Method,
+        uri: &str,
+        token: Option<&str>,
+        body: Option<Value>,
+    ) -> TestResponse {
+        let mut builder = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let request = match body {
+            Some(body) => builder
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => builder.body(Body::empty()),
+        };
+        self.send(request.unwrap()).await
+    }
+
+    pub async fn get(&self, uri: &str, token: &str) -> TestResponse {
+        self.request(Method::GET, uri, Some(token), None).await
+    }
+
+    pub async fn post(&self, uri: &str, token: &str, body: Value) -> TestResponse {
+        self.request(Method::POST, uri, Some(token), Some(body)).await
+    }
+
+    pub async fn put(&self, uri: &str, token: &str, body: Value) -> TestResponse {
+        self.request(Method::PUT, uri, Some(token), Some(body)).await
+    }
+
+    pub async fn delete(&self, uri: &str, token: &str) -> TestResponse {
+        self.request(Method::DELETE, uri, Some(token), None).await
+    }
+
+    pub async fn create_user(&self, username: &str, password: &str) -> TestResponse {
+        self.create_user_with_key(API_KEY, username, password).await
+    }
+
+    pub async fn create_user_with_key(&self, api_key: &str, username: &str, password: &str) -> TestResponse {
+        let request = Request::post("/users")
+            .header("X-Api-Key", api_key)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                serde_json::json!({ "username": username, "password": password }).to_string(),
+            ))
+            .unwrap();
+        self.send(request).await
+    }
+
+    pub async fn login(&self, username: &str, password: &str) -> TestResponse {
+        self.request(
+            Method::POST,
+            "/login",
+            None,
+            Some(serde_json::json!({ "username": username, "password": password })),
+        )
+        .await
+    }
+
+    /// Create a user and return a bearer token for it.
+    pub async fn token(&self) -> String {
+        assert_eq!(self.create_user("alice", "secret").await.status, StatusCode::CREATED);
+        let response = self.login("alice", "secret").await;
+        assert_eq!(response.status, StatusCode::OK);
+        response.json()["token"].as_str().unwrap().to_string()
+    }
+}
+
+pub fn assert_problem(response: &TestResponse, status: StatusCode) {
+    assert_eq!(response.status, status);
+    assert_eq!(response.content_type(), "application/problem+json");
+    let body = response.json();
+    assert_eq!(body["status"], status.as_u16());
+    assert!(body["request_id"].is_string());
+}
+
+#[tokio::test]
+async fn health_is_public() {
+    let app = TestApp::new();
+    let response = app.request(Method::GET, "/health", None, None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(&response.body[..], b"I'm alive!");
+}
+
+#[tokio::test]
+async fn request_id_is_echoed_and_generated() {
+    let app = TestApp::new();
+
+    let request = Request::get("/users/1")
+        .header("X-Request-Id", "ticket-42")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.send(request).await;
+    assert_eq!(response.headers["x-request-id"], "ticket-42");
+    assert_problem(&response, StatusCode::UNAUTHORIZED);
+    assert_eq!(response.json()["request_id"], "ticket-42");
+
+    let response = app.request(Method::GET, "/health", None, None).await;
+    let generated = response.headers["x-request-id"].to_str().unwrap();
+    assert_eq!(generated.len(), 26, "expected a ULID, got {generated}");
+}
+
+#[tokio::test]
+async fn protected_routes_reject_missing_and_invalid_tokens() {
+    let app = TestApp::new();
+    let routes = [
+        (Method::GET, "/users"),
+        (Method::GET, "/users/1"),
+        (Method::PUT, "/users/1"),
+        (Method::DELETE, "/users/1"),
+        (Method::GET, "/users/1/speak"),
+        (Method::POST, "/categories"),
+        (Method::GET, "/categories"),
+        (Method::GET, "/categories/1"),
+        (Method::PUT, "/categories/1"),
+        (Method::DELETE, "/categories/1"),
+        (Method::POST, "/products"),
+        (Method::GET, "/products"),
+        (Method::GET, "/products/1"),
+        (Method::GET, "/products/categories/1"),
+        (Method::POST, "/checkout"),
+        (Method::GET, "/orders"),
+        (Method::GET, "/orders/1"),
+        (Method::POST, "/admin/orders/1/status"),
+        (Method::GET, "/admin/orders/1/history"),
+        (Method::POST, "/orders/1/pay"),
+        (Method::GET, "/admin/orders/1/payments"),
+        (Method::POST, "/admin/orders/1/refund"),
+        (Method::POST, "/admin/products/1/stock"),
+        (Method::POST, "/admin/products/1/stock/transfers"),
+        (Method::GET, "/admin/products/1/stock/movements"),
+        (Method::POST, "/admin/warehouses"),
+        (Method::GET, "/admin/warehouses"),
+        (Method::GET, "/admin/inventory/reconciliation"),
+        (Method::PUT, "/admin/products/1/stock/threshold"),
+        (Method::GET, "/reports/low-stock"),
+        (Method::GET, "/admin/notifications"),
+        (Method::POST, "/admin/notifications/1/read"),
+        (Method::POST, "/admin/suppliers"),
+        (Method::GET, "/admin/suppliers"),
+        (Method::PUT, "/admin/suppliers/1/products/1"),
+        (Method::GET, "/admin/suppliers/1/products"),
+        (Method::POST, "/admin/purchase-orders"),
+        (Method::GET, "/admin/purchase-orders"),
+        (Method::GET, "/admin/purchase-orders/1"),
+        (Method::POST, "/admin/purchase-orders/1/send"),
+        (Method::POST, "/admin/purchase-orders/1/receipts"),
+        (Method::GET, "/admin/products/1/cost"),
+        (Method::POST, "/admin/promotions"),
+        (Method::GET, "/admin/promotions"),
+        (Method::GET, "/admin/promotions/1"),
+        (Method::PUT, "/admin/promotions/1"),
+        (Method::DELETE, "/admin/promotions/1"),
+    ];
+
+    for (method, uri) in routes {
+        let response = app.request(method.clone(), uri, None, None).await;
+        assert_problem(&response, StatusCode::UNAUTHORIZED);
+        assert_eq!(response.json()["detail"], "missing authorization header", "{method} {uri}");
+
+        let response = app.request(method.clone(), uri, Some("not-a-jwt"), None).await;
+        assert_problem(&response, StatusCode::UNAUTHORIZED);
+        assert_eq!(response.json()["detail"], "invalid or expired token", "{method} {uri}");
+    }
+}
@@ -0,0 +1,217 @@
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use crate::app::AppBuilder;
+
+use super::{assert_problem, TestApp};
+
+/// Products 1 (Rust Book, 40.00) and 2 (Chess, 15.00), 3 of each in stock,
+/// and a token for alice.
+async fn app_with_products() -> (TestApp, String) {
+    let app = TestApp::new();
+    let token = app.token().await;
+    add_products(&app, &token).await;
+    (app, token)
+}
+
+/// [`app_with_products`] where alice (user 1) is an admin and has placed
+/// order 1 for two Rust Books.
+async fn app_with_order_and_admin() -> (TestApp, String) {
+    admin_with_order(|builder| builder).await
+}
+
+/// [`app_with_order_and_admin`], built with `configure`.
+pub(super) async fn admin_with_order(configure: impl FnOnce(AppBuilder) -> AppBuilder) -> (TestApp, String) {
+    let repositories = TestApp::repositories();
+    let users = repositories.users.clone();
+    let app = TestApp::with(repositories, configure);
+    let token = app.token().await;
+    users.set_admin(1, true).await.unwrap();
+    add_products(&app, &token).await;
+    app.post("/cart/items", &token, json!({ "product_id": 1, "quantity": 2 })).await;
+    assert_eq!(app.post("/checkout", &token, Value::Null).await.status, StatusCode::CREATED);
+    (app, token)
+}
+
+async fn add_products(app: &TestApp, token: &str) {
+    app.post("/categories", token, json!({ "name": "Books" })).await;
+    for (name, price) in [("Rust Book", 39.999), ("Chess", 15.0)] {
+        let product = json!({ "name": name, "description": null, "price": price, "stock": 3, "category_id": 1, "active": true });
+        app.post("/products", token, product).await;
+    }
+}
+
+pub(super) async fn login_as(app: &TestApp, username: &str) -> String {
+    assert_eq!(app.create_user(username, "secret").await.status, StatusCode::CREATED);
+    app.login(username, "secret").await.json()["token"].as_str().unwrap().to_string()
+}
+
+pub(super) async fn stock(app: &TestApp, token: &str, product_id: i64) -> i64 {
+    app.get(&format!("/products/{product_id}"), token).await.json()["stock"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn checkout_places_order_takes_stock_and_empties_cart() {
+    let (app, token) = app_with_products().await;
+    app.post("/cart/items", &token, json!({ "product_id": 1, "quantity": 2 })).await;
+    app.post("/cart/items", &token, json!({ "product_id": 2, "quantity": 1 })).await;
+
+    let response = app.post("/checkout", &token, Value::Null).await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    let order = response.json();
+    assert_eq!(order["id"], 1);
+    assert_eq!(
+        order["lines"],
+        json!([
+            { "product_id": 1, "name": "Rust Book", "quantity": 2, "unit_price": 40.0 },
+            { "product_id": 2, "name": "Chess", "quantity": 1, "unit_price": 15.0 },
+        ])
+    );
+    assert_eq!(order["total"], 95.0);
+    assert!(chrono::DateTime::parse_from_rfc3339(order["created_at"].as_str().unwrap()).is_ok());
+
+    assert_eq!((stock(&app, &token, 1).await, stock(&app, &token, 2).await), (1, 2));
+    assert_eq!(app.get("/cart", &token).await.json()["lines"], json!([]));
+    let events: Vec<_> = app.events().await.into_iter().filter(|(t, _)| t == "OrderPlaced").collect();
+    assert_eq!(events, [("OrderPlaced".to_string(), json!({ "id": 1, "user_id": 1, "total": 95.0 }))]);
+
+    assert_eq!(app.get("/orders", &token).await.json(), json!([order]));
+    assert_eq!(app.get("/orders/1", &token).await.json(), order);
+
+    // nothing left to order
+    let response = app.post("/checkout", &token, Value::Null).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "cart is empty");
+}
+
+#[tokio::test]
+async fn checkout_fails_whole_when_stock_ran_out() {
+    // reservations expire at once, so bob can buy the book alice's cart holds
+    let app = TestApp::with(TestApp::repositories(), |builder| builder.reservation_minutes(0));
+    let alice = app.token().await;
+    add_products(&app, &alice).await;
+    let bob = login_as(&app, "bob").await;
+    app.post("/cart/items", &alice, json!({ "product_id": 2, "quantity": 1 })).await;
+    app.post("/cart/items", &alice, json!({ "product_id": 1, "quantity": 3 })).await;
+    app.post("/cart/items", &bob, json!({ "product_id": 1, "quantity": 1 })).await;
+    assert_eq!(app.post("/checkout", &bob, Value::Null).await.status, StatusCode::CREATED);
+
+    let response = app.post("/checkout", &alice, Value::Null).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "only 2 of product 1 in stock");
+    // no stock taken for the line that did fit, cart left for the shopper to fix
+    assert_eq!((stock(&app, &alice, 1).await, stock(&app, &alice, 2).await), (2, 3));
+    assert_eq!(app.get("/cart", &alice).await.json()["lines"].as_array().unwrap().len(), 2);
+    assert_eq!(app.get("/orders", &alice).await.json(), json!([]));
+}
+
+#[tokio::test]
+async fn orders_are_only_visible_to_their_user() {
+    let (app, alice) = app_with_products().await;
+    let bob = login_as(&app, "bob").await;
+    app.post("/cart/items", &alice, json!({ "product_id": 1, "quantity": 1 })).await;
+    app.post("/checkout", &alice, Value::Null).await;
+
+    assert_problem(&app.get("/orders/1", &bob).await, StatusCode::NOT_FOUND);
+    assert_problem(&app.get("/orders/2", &alice).await, StatusCode::NOT_FOUND);
+    assert_eq!(app.get("/orders", &bob).await.json(), json!([]));
+}
+
+#[tokio::test]
+async fn admin_moves_order_through_its_statuses() {
+    let (app, admin) = app_with_order_and_admin().await;
+    assert_eq!(app.get("/orders/1", &admin).await.json()["status"], "pending");
+
+    assert_eq!(app.post("/orders/1/pay", &admin, Value::Null).await.status, StatusCode::OK);
+    for (status, reason) in [("fulfilled", Value::Null), ("shipped", json!("DHL 0042"))] {
+        let response = app.post("/admin/orders/1/status", &admin, json!({ "status": status, "reason": reason })).await;
+        assert_eq!(response.status, StatusCode::OK, "{status}");
+        assert_eq!(response.json()["status"], status);
+    }
+    assert_eq!(app.get("/orders/1", &admin).await.json()["status"], "shipped");
+
+    let history = app.get("/admin/orders/1/history", &admin).await.json();
+    let steps: Vec<_> = history
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| (c["from"].clone(), c["to"].clone(), c["actor_id"].clone(), c["reason"].clone()))
+        .collect();
+    assert_eq!(
+        steps,
+        [
+            (Value::Null, json!("pending"), json!(1), Value::Null),
+            (json!("pending"), json!("paid"), json!(1), json!("payment captured")),
+            (json!("paid"), json!("fulfilled"), json!(1), Value::Null),
+            (json!("fulfilled"), json!("shipped"), json!(1), json!("DHL 0042")),
+        ]
+    );
+    assert!(chrono::DateTime::parse_from_rfc3339(history[0]["changed_at"].as_str().unwrap()).is_ok());
+
+    let events: Vec<_> = app.events().await.into_iter().filter(|(t, _)| t == "OrderStatusChanged").collect();
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[2].1, json!({ "id": 1, "from": "fulfilled", "to": "shipped" }));
+    // shipped goods stay sold
+    assert_eq!(stock(&app, &admin, 1).await, 1);
+}
+
+#[tokio::test]
+async fn illegal_transitions_are_rejected() {
+    let (app, admin) = app_with_order_and_admin().await;
+
+    let response = app.post("/admin/orders/1/status", &admin, json!({ "status": "shipped" })).await;
+    assert_problem(&response, StatusCode::CONFLICT);
+    assert_eq!(response.json()["detail"], "an order cannot go from pending to shipped");
+
+    let response = app.post("/admin/orders/1/status", &admin, json!({ "status": "lost" })).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "invalid order status \"lost\"");
+
+    assert_problem(&app.post("/admin/orders/2/status", &admin, json!({ "status": "cancelled" })).await, StatusCode::NOT_FOUND);
+    assert_problem(&app.get("/admin/orders/2/history", &admin).await, StatusCode::NOT_FOUND);
+
+    // paid and refunded follow the payment
+    let response = app.post("/admin/orders/1/status", &admin, json!({ "status": "paid" })).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "an order becomes paid by paying for it");
+    let response = app.post("/admin/orders/1/status", &admin, json!({ "status": "refunded" })).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "an order is refunded by refunding its payment");
+    assert_eq!(app.get("/orders/1", &admin).await.json()["status"], "pending");
+
+    // a cancelled order is done with
+    app.post("/admin/orders/1/status", &admin, json!({ "status": "cancelled" })).await;
+    let response = app.post("/admin/orders/1/status", &admin, json!({ "status": "fulfilled" })).await;
+    assert_problem(&response, StatusCode::CONFLICT);
+    assert_eq!(response.json()["detail"], "an order cannot go from cancelled to fulfilled");
+    assert_eq!(app.get("/admin/orders/1/history", &admin).await.json().as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn cancelling_and_early_refunds_restock() {
+    let (app, admin) = app_with_order_and_admin().await;
+    assert_eq!(stock(&app, &admin, 1).await, 1);
+    let response = app.post("/admin/orders/1/status", &admin, json!({ "status": "cancelled", "reason": "changed mind" })).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(stock(&app, &admin, 1).await, 3);
+
+    // refunded after payment: back on the shelf as well
+    app.post("/cart/items", &admin, json!({ "product_id": 1, "quantity": 1 })).await;
+    app.post("/checkout", &admin, Value::Null).await;
+    assert_eq!(stock(&app, &admin, 1).await, 2);
+    app.post("/orders/2/pay", &admin, Value::Null).await;
+    assert_eq!(app.post("/admin/orders/2/refund", &admin, json!({})).await.status, StatusCode::OK);
+    assert_eq!(stock(&app, &admin, 1).await, 3);
+}
+
+#[tokio::test]
+async fn order_status_is_admin_only() {
+    let (app, admin) = app_with_order_and_admin().await;
+    let bob = login_as(&app, "bob").await;
+
+    let response = app.post("/admin/orders/1/status", &bob, json!({ "status": "cancelled" })).await;
+    assert_problem(&response, StatusCode::FORBIDDEN);
+    assert_eq!(response.json()["detail"], "admin only");
+    assert_problem(&app.get("/admin/orders/1/history", &bob).await, StatusCode::FORBIDDEN);
+    assert_eq!(app.get("/orders/1", &admin).await.json()["status"], "pending");
+}
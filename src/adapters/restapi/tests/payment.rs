@@ -0,0 +1,261 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use serde_json::{json, Value};
+
+use crate::domain::payment::{Authorization, PaymentProvider, WebhookEvent, WebhookKind};
+use crate::domain::DomainError;
+use crate::infra::payment::MockPaymentProvider;
+
+use super::order::{admin_with_order, login_as, stock};
+use super::{assert_problem, TestApp, TestResponse};
+
+/// The mock, except that the answer to a capture is lost; the capture is
+/// then only learned of from the webhook.
+struct CaptureTimesOut(MockPaymentProvider);
+
+#[async_trait]
+impl PaymentProvider for CaptureTimesOut {
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    async fn authorize(&self, reference: &str, amount_cents: i64) -> Result<Authorization, DomainError> {
+        self.0.authorize(reference, amount_cents).await
+    }
+
+    async fn capture(&self, provider_ref: &str, amount_cents: i64) -> Result<(), DomainError> {
+        self.0.capture(provider_ref, amount_cents).await?;
+        Err(DomainError::Unexpected("payment provider request failed: timed out".into()))
+    }
+
+    async fn refund(&self, provider_ref: &str, amount_cents: i64) -> Result<(), DomainError> {
+        self.0.refund(provider_ref, amount_cents).await
+    }
+
+    fn verify_webhook(&self, signature: &str, body: &[u8]) -> Result<WebhookEvent, DomainError> {
+        self.0.verify_webhook(signature, body)
+    }
+}
+
+/// [`CaptureTimesOut`] that cannot refund either.
+struct RefundFails(CaptureTimesOut);
+
+#[async_trait]
+impl PaymentProvider for RefundFails {
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    async fn authorize(&self, reference: &str, amount_cents: i64) -> Result<Authorization, DomainError> {
+        self.0.authorize(reference, amount_cents).await
+    }
+
+    async fn capture(&self, provider_ref: &str, amount_cents: i64) -> Result<(), DomainError> {
+        self.0.capture(provider_ref, amount_cents).await
+    }
+
+    async fn refund(&self, _provider_ref: &str, _amount_cents: i64) -> Result<(), DomainError> {
+        Err(DomainError::Unexpected("payment provider request failed: connection refused".into()))
+    }
+
+    fn verify_webhook(&self, signature: &str, body: &[u8]) -> Result<WebhookEvent, DomainError> {
+        self.0.verify_webhook(signature, body)
+    }
+}
+
+fn mock() -> MockPaymentProvider {
+    MockPaymentProvider::new("whsec-test")
+}
+
+/// Admin alice with order 1 (two Rust Books, 80.00), paid with `provider`.
+async fn app_paying_with(provider: impl PaymentProvider + 'static) -> (TestApp, String) {
+    let provider: Arc<dyn PaymentProvider> = Arc::new(provider);
+    admin_with_order(|builder| builder.payment_provider(provider)).await
+}
+
+async fn webhook(app: &TestApp, (signature, body): (String, Vec<u8>)) -> TestResponse {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/payments/webhook")
+        .header("X-Signature", signature)
+        .body(Body::from(body))
+        .unwrap();
+    app.send(request).await
+}
+
+fn events_of(events: &[(String, Value)], event_type: &str) -> Vec<Value> {
+    events.iter().filter(|(t, _)| t == event_type).map(|(_, payload)| payload.clone()).collect()
+}
+
+fn operations(payment: &Value) -> Vec<(String, bool)> {
+    payment["attempts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| (a["operation"].as_str().unwrap().to_string(), a["succeeded"].as_bool().unwrap()))
+        .collect()
+}
+
+#[tokio::test]
+async fn paying_captures_and_marks_the_order_paid() {
+    let mock = mock();
+    let (app, alice) = app_paying_with(mock.clone()).await;
+
+    let response = app.post("/orders/1/pay", &alice, Value::Null).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let payment = response.json();
+    assert_eq!(
+        (&payment["id"], &payment["order_id"], &payment["provider"], &payment["provider_ref"], &payment["amount"], &payment["status"]),
+        (&json!(1), &json!(1), &json!("mock"), &json!("mock_intent-1"), &json!(80.0), &json!("captured"))
+    );
+    assert_eq!(operations(&payment), [("authorize".into(), true), ("capture".into(), true)]);
+    assert_eq!(app.get("/orders/1", &alice).await.json()["status"], "paid");
+    let history = app.get("/admin/orders/1/history", &alice).await.json();
+    assert_eq!(history[1]["to"], "paid");
+    assert_eq!((&history[1]["actor_id"], &history[1]["reason"]), (&json!(1), &json!("payment captured")));
+
+    // the provider's webhook for the same capture changes nothing
+    assert_eq!(webhook(&app, mock.webhook("evt_1", WebhookKind::Captured, "mock_intent-1")).await.status, StatusCode::NO_CONTENT);
+    let events = app.events().await;
+    assert_eq!(events_of(&events, "PaymentCaptured"), [json!({ "id": 1, "order_id": 1, "amount": 80.0 })]);
+    assert_eq!(events_of(&events, "OrderStatusChanged"), [json!({ "id": 1, "from": "pending", "to": "paid" })]);
+
+    let response = app.post("/orders/1/pay", &alice, Value::Null).await;
+    assert_problem(&response, StatusCode::CONFLICT);
+    assert_eq!(response.json()["detail"], "an order cannot go from paid to paid");
+    let bob = login_as(&app, "bob").await;
+    assert_problem(&app.post("/orders/1/pay", &bob, Value::Null).await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn declined_payments_leave_the_order_pending() {
+    let (app, alice) = app_paying_with(mock().declining("insufficient funds")).await;
+
+    let response = app.post("/orders/1/pay", &alice, Value::Null).await;
+    assert_problem(&response, StatusCode::PAYMENT_REQUIRED);
+    assert_eq!(response.json()["detail"], "payment declined: insufficient funds");
+    assert_eq!(app.get("/orders/1", &alice).await.json()["status"], "pending");
+
+    // paying again is a new try
+    app.post("/orders/1/pay", &alice, Value::Null).await;
+    let payments = app.get("/admin/orders/1/payments", &alice).await.json();
+    let payments = payments.as_array().unwrap();
+    assert_eq!(payments.iter().map(|p| p["id"].as_i64().unwrap()).collect::<Vec<_>>(), [2, 1]);
+    assert_eq!(payments[1]["status"], "failed");
+    assert_eq!(payments[1]["attempts"][0]["detail"], "insufficient funds");
+    let failed = events_of(&app.events().await, "PaymentFailed");
+    assert_eq!(failed[0], json!({ "id": 1, "order_id": 1, "reason": "insufficient funds" }));
+}
+
+#[tokio::test]
+async fn webhooks_complete_payments_once() {
+    let mock = mock();
+    let (app, alice) = app_paying_with(CaptureTimesOut(mock.clone())).await;
+    let response = app.post("/orders/1/pay", &alice, Value::Null).await;
+    assert_problem(&response, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(app.get("/orders/1", &alice).await.json()["status"], "pending");
+
+    let captured = mock.webhook("evt_1", WebhookKind::Captured, "mock_intent-1");
+    assert_eq!(webhook(&app, captured.clone()).await.status, StatusCode::NO_CONTENT);
+    assert_eq!(webhook(&app, captured).await.status, StatusCode::NO_CONTENT);
+    assert_eq!(app.get("/orders/1", &alice).await.json()["status"], "paid");
+
+    let payment = &app.get("/admin/orders/1/payments", &alice).await.json()[0];
+    assert_eq!(payment["status"], "captured");
+    assert_eq!(
+        operations(payment),
+        [("authorize".into(), true), ("capture".into(), false), ("webhook".into(), true)]
+    );
+    assert_eq!(payment["attempts"][1]["detail"], "unexpected error: payment provider request failed: timed out");
+    let history = app.get("/admin/orders/1/history", &alice).await.json();
+    assert_eq!((&history[1]["to"], &history[1]["actor_id"]), (&json!("paid"), &Value::Null));
+    let events = app.events().await;
+    assert_eq!(events_of(&events, "PaymentCaptured").len(), 1);
+    assert_eq!(events_of(&events, "OrderStatusChanged").len(), 1);
+
+    // forged or foreign webhooks
+    let (_, body) = mock.webhook("evt_2", WebhookKind::Refunded, "mock_intent-1");
+    assert_problem(&webhook(&app, ("sha256=00".into(), body)).await, StatusCode::UNAUTHORIZED);
+    let unknown = mock.webhook("evt_3", WebhookKind::Captured, "mock_intent-42");
+    assert_eq!(webhook(&app, unknown).await.status, StatusCode::NO_CONTENT);
+    assert_eq!(app.get("/orders/1", &alice).await.json()["status"], "paid");
+}
+
+#[tokio::test]
+async fn captures_for_cancelled_orders_are_refunded() {
+    let mock = mock();
+    let (app, alice) = app_paying_with(CaptureTimesOut(mock.clone())).await;
+    assert_problem(&app.post("/orders/1/pay", &alice, Value::Null).await, StatusCode::INTERNAL_SERVER_ERROR);
+    let response = app.post("/admin/orders/1/status", &alice, json!({ "status": "cancelled" })).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(stock(&app, &alice, 1).await, 3);
+
+    // the capture went through after all
+    assert_eq!(webhook(&app, mock.webhook("evt_1", WebhookKind::Captured, "mock_intent-1")).await.status, StatusCode::NO_CONTENT);
+    assert_eq!(app.get("/orders/1", &alice).await.json()["status"], "cancelled");
+    let payment = &app.get("/admin/orders/1/payments", &alice).await.json()[0];
+    assert_eq!(payment["status"], "refunded");
+    assert_eq!(
+        operations(payment),
+        [("authorize".into(), true), ("capture".into(), false), ("webhook".into(), true), ("refund".into(), true)]
+    );
+    let events = app.events().await;
+    assert_eq!(events_of(&events, "PaymentRefunded"), [json!({ "id": 1, "order_id": 1, "amount": 80.0 })]);
+    assert!(events_of(&events, "PaymentNeedsAttention").is_empty());
+    // nothing sold twice or put back twice
+    assert_eq!(stock(&app, &alice, 1).await, 3);
+    assert_eq!(app.get("/admin/orders/1/history", &alice).await.json().as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn stranded_captures_that_cannot_be_refunded_need_attention() {
+    let mock = mock();
+    let (app, alice) = app_paying_with(RefundFails(CaptureTimesOut(mock.clone()))).await;
+    app.post("/orders/1/pay", &alice, Value::Null).await;
+    app.post("/admin/orders/1/status", &alice, json!({ "status": "cancelled" })).await;
+
+    assert_eq!(webhook(&app, mock.webhook("evt_1", WebhookKind::Captured, "mock_intent-1")).await.status, StatusCode::NO_CONTENT);
+    let payment = &app.get("/admin/orders/1/payments", &alice).await.json()[0];
+    assert_eq!(payment["status"], "captured");
+    assert_eq!(operations(payment).last().unwrap(), &("refund".to_string(), false));
+    let attention = events_of(&app.events().await, "PaymentNeedsAttention");
+    assert_eq!(
+        attention,
+        [json!({
+            "id": 1,
+            "order_id": 1,
+            "reason": "captured for cancelled order 1, refund failed: unexpected error: payment provider request failed: connection refused"
+        })]
+    );
+}
+
+#[tokio::test]
+async fn admins_refund_paid_orders() {
+    let (app, alice) = app_paying_with(mock()).await;
+    let bob = login_as(&app, "bob").await;
+
+    // nothing paid yet
+    let response = app.post("/admin/orders/1/refund", &alice, json!({})).await;
+    assert_problem(&response, StatusCode::CONFLICT);
+    assert_eq!(response.json()["detail"], "an order cannot go from pending to refunded");
+
+    app.post("/orders/1/pay", &alice, Value::Null).await;
+    assert_eq!(stock(&app, &alice, 1).await, 1);
+    assert_problem(&app.post("/admin/orders/1/refund", &bob, json!({})).await, StatusCode::FORBIDDEN);
+    assert_problem(&app.get("/admin/orders/1/payments", &bob).await, StatusCode::FORBIDDEN);
+
+    let response = app.post("/admin/orders/1/refund", &alice, json!({ "reason": "damaged" })).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let payment = response.json();
+    assert_eq!(payment["status"], "refunded");
+    assert_eq!(operations(&payment).last().unwrap(), &("refund".to_string(), true));
+    assert_eq!(app.get("/orders/1", &alice).await.json()["status"], "refunded");
+    assert_eq!(app.get("/admin/orders/1/history", &alice).await.json()[2]["reason"], "damaged");
+    // refunded before it shipped
+    assert_eq!(stock(&app, &alice, 1).await, 3);
+    let refunded = events_of(&app.events().await, "PaymentRefunded");
+    assert_eq!(refunded, [json!({ "id": 1, "order_id": 1, "amount": 80.0 })]);
+}
@@ -0,0 +1,104 @@
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use super::{assert_problem, TestApp};
+
+fn product(name: &str, price: f64, category_id: i64) -> Value {
+    json!({
+        "name": name,
+        "description": format!("{name} description"),
+        "price": price,
+        "stock": 10,
+        "category_id": category_id,
+        "active": true,
+    })
+}
+
+async fn app_with_categories() -> (TestApp, String) {
+    let app = TestApp::new();
+    let token = app.token().await;
+    app.post("/categories", &token, json!({ "name": "Books" })).await;
+    app.post("/categories", &token, json!({ "name": "Games" })).await;
+    (app, token)
+}
+
+#[tokio::test]
+async fn create_and_get_product() {
+    let (app, token) = app_with_categories().await;
+
+    let response = app.post("/products", &token, product("Rust Book", 39.999, 1)).await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    assert_eq!(response.json(), json!({ "id": 1 }));
+
+    let response = app.get("/products/1", &token).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.json(),
+        json!({
+            "id": 1,
+            "name": "Rust Book",
+            "description": "Rust Book description",
+            // NUMERIC(10, 2)
+            "price": 40.0,
+            "stock": 10,
+            "reserved": 0,
+            "available": 10,
+            "category_id": 1,
+            "active": true,
+        })
+    );
+
+    assert_problem(&app.get("/products/99", &token).await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn create_product_validates_input() {
+    let (app, token) = app_with_categories().await;
+
+    let response = app.post("/products", &token, product("", 1.0, 1)).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "name is required");
+
+    // products.category_id REFERENCES categories(id)
+    let response = app.post("/products", &token, product("Orphan", 1.0, 99)).await;
+    assert_problem(&response, StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[tokio::test]
+async fn list_products_and_filter_by_category() {
+    let (app, token) = app_with_categories().await;
+    app.post("/products", &token, product("Rust Book", 39.9, 1)).await;
+    app.post("/products", &token, product("Chess", 15.0, 2)).await;
+    app.post("/products", &token, product("Go Book", 29.9, 1)).await;
+
+    let response = app.get("/products", &token).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let names: Vec<_> = response.json().as_array().unwrap().iter().map(|p| p["name"].clone()).collect();
+    assert_eq!(names, vec!["Rust Book", "Chess", "Go Book"]);
+
+    let response = app.get("/products/categories/1", &token).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let ids: Vec<_> = response.json().as_array().unwrap().iter().map(|p| p["id"].clone()).collect();
+    assert_eq!(ids, vec![1, 3]);
+
+    let response = app.get("/products/categories/99", &token).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json(), json!([]));
+}
+
+#[tokio::test]
+async fn create_product_raises_event_with_stored_price() {
+    let (app, token) = app_with_categories().await;
+
+    app.post("/products", &token, product("Rust Book", 39.999, 1)).await;
+    app.post("/products", &token, product("Orphan", 1.0, 99)).await;
+
+    let events: Vec<_> = app.events().await.into_iter().filter(|(t, _)| t.starts_with("Product")).collect();
+    assert_eq!(
+        events,
+        [(
+            "ProductCreated".to_string(),
+            json!({ "id": 1, "name": "Rust Book", "price": 40.0, "stock": 10, "category_id": 1 }),
+        )]
+    );
+}
@@ -0,0 +1,245 @@
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+
+use super::order::{admin_with_order, login_as, stock};
+use super::{assert_problem, TestApp};
+
+/// [`admin_with_order`] with a shipping fee of 5, plus category 2 (Games)
+/// with product 3 (Go, 20.00, 10 in stock), and a token for bob.
+async fn shop() -> (TestApp, String, String) {
+    let (app, admin) = admin_with_order(|builder| builder.shipping_fee(5.0)).await;
+    app.post("/categories", &admin, json!({ "name": "Games" })).await;
+    let go = json!({ "name": "Go", "description": null, "price": 20.0, "stock": 10, "category_id": 2, "active": true });
+    app.post("/products", &admin, go).await;
+    let bob = login_as(&app, "bob").await;
+    (app, admin, bob)
+}
+
+async fn create(app: &TestApp, admin: &str, body: Value) -> Value {
+    let response = app.post("/admin/promotions", admin, body).await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    response.json()
+}
+
+async fn add(app: &TestApp, token: &str, product_id: i64, quantity: i32) {
+    let response = app.post("/cart/items", token, json!({ "product_id": product_id, "quantity": quantity })).await;
+    assert_eq!(response.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn promotions_are_managed_by_admins() {
+    let (app, admin, bob) = shop().await;
+
+    let body = json!({
+        "name": "Spring",
+        "code": " spring10 ",
+        "kind": "percentage",
+        "value": 10,
+        "category_id": 2,
+        "max_uses": 100,
+        "max_uses_per_user": 1,
+    });
+    let created = create(&app, &admin, body).await;
+    assert_eq!(
+        created,
+        json!({
+            "id": 1,
+            "name": "Spring",
+            "code": "SPRING10",
+            "kind": "percentage",
+            "value": 10.0,
+            "buy_quantity": null,
+            "get_quantity": null,
+            "category_id": 2,
+            "product_id": null,
+            "starts_at": null,
+            "ends_at": null,
+            "max_uses": 100,
+            "max_uses_per_user": 1,
+            "active": true,
+            "uses": 0,
+        })
+    );
+    assert_eq!(app.get("/admin/promotions", &admin).await.json(), json!([created]));
+    assert_eq!(app.get("/admin/promotions/1", &admin).await.json(), created);
+
+    let body = json!({ "name": "Spring", "code": "SPRING10", "kind": "fixed", "value": 2.5, "starts_at": "2030-01-01T00:00:00Z", "active": false });
+    let response = app.put("/admin/promotions/1", &admin, body).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let updated = response.json();
+    assert_eq!((updated["kind"].clone(), updated["value"].clone()), (json!("fixed"), json!(2.5)));
+    assert_eq!((updated["category_id"].clone(), updated["max_uses"].clone()), (Value::Null, Value::Null));
+    assert_eq!((updated["starts_at"].clone(), updated["active"].clone()), (json!("2030-01-01T00:00:00+00:00"), json!(false)));
+    assert_eq!(app.get("/admin/promotions/1", &admin).await.json(), updated);
+    let body = json!({ "name": "Nothing", "kind": "free_shipping" });
+    assert_problem(&app.put("/admin/promotions/42", &admin, body).await, StatusCode::NOT_FOUND);
+
+    let invalid = [
+        (json!({ "name": "x", "code": "Spring10", "kind": "fixed", "value": 1 }), "coupon SPRING10 already exists"),
+        (json!({ "name": "x", "kind": "bogus" }), "invalid promotion kind \"bogus\""),
+        (json!({ "name": " ", "kind": "free_shipping" }), "name is required"),
+        (json!({ "name": "x", "kind": "percentage", "value": 150 }), "a percentage must be greater than 0 and at most 100"),
+        (json!({ "name": "x", "kind": "buy_x_get_y", "buy_quantity": 2 }), "buy_x_get_y needs a positive buy_quantity and get_quantity"),
+        (json!({ "name": "x", "kind": "free_shipping", "category_id": 42 }), "no category 42"),
+        (json!({ "name": "x", "kind": "free_shipping", "product_id": 42 }), "no product 42"),
+        (json!({ "name": "x", "kind": "free_shipping", "starts_at": "tomorrow" }), "starts_at must be an RFC 3339 timestamp"),
+        (
+            json!({ "name": "x", "kind": "free_shipping", "starts_at": "2030-01-02T00:00:00Z", "ends_at": "2030-01-01T00:00:00Z" }),
+            "ends_at must be after starts_at",
+        ),
+        (json!({ "name": "x", "kind": "free_shipping", "max_uses_per_user": 0 }), "usage limits must be positive"),
+    ];
+    for (body, detail) in invalid {
+        let response = app.post("/admin/promotions", &admin, body).await;
+        assert_problem(&response, StatusCode::BAD_REQUEST);
+        assert_eq!(response.json()["detail"], detail);
+    }
+
+    assert_problem(&app.get("/admin/promotions", &bob).await, StatusCode::FORBIDDEN);
+    let body = json!({ "name": "Mine", "kind": "percentage", "value": 100 });
+    assert_problem(&app.post("/admin/promotions", &bob, body).await, StatusCode::FORBIDDEN);
+
+    assert_eq!(app.delete("/admin/promotions/1", &admin).await.status, StatusCode::NO_CONTENT);
+    assert_problem(&app.get("/admin/promotions/1", &admin).await, StatusCode::NOT_FOUND);
+    assert_problem(&app.delete("/admin/promotions/1", &admin).await, StatusCode::NOT_FOUND);
+    assert_eq!(app.get("/admin/promotions", &admin).await.json(), json!([]));
+}
+
+#[tokio::test]
+async fn discounts_apply_to_the_eligible_lines_and_are_explained() {
+    let (app, admin, bob) = shop().await;
+    create(&app, &admin, json!({ "name": "Games week", "kind": "percentage", "value": 10, "category_id": 2 })).await;
+    create(&app, &admin, json!({ "name": "Five off chess", "code": "CHESS5", "kind": "fixed", "value": 5, "product_id": 2 })).await;
+    add(&app, &bob, 2, 1).await;
+    add(&app, &bob, 3, 1).await;
+
+    let games_week = json!({
+        "promotion_id": 1,
+        "name": "Games week",
+        "code": null,
+        "kind": "percentage",
+        "discount": 2.0,
+        "detail": "10% off category 2",
+    });
+    let cart = app.get("/cart", &bob).await.json();
+    assert_eq!((cart["subtotal"].clone(), cart["discount"].clone()), (json!(35.0), json!(2.0)));
+    assert_eq!((cart["shipping"].clone(), cart["total"].clone()), (json!(5.0), json!(38.0)));
+    assert_eq!(cart["promotions"], json!([games_week]));
+    assert_eq!((cart["coupon"].clone(), cart["coupon_note"].clone()), (Value::Null, Value::Null));
+
+    let response = app.put("/cart/coupon", &bob, json!({ "code": "chess5" })).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let cart = response.json();
+    assert_eq!(cart["coupon"], "CHESS5");
+    assert_eq!((cart["discount"].clone(), cart["total"].clone()), (json!(7.0), json!(33.0)));
+    assert_eq!(
+        cart["promotions"],
+        json!([
+            games_week,
+            { "promotion_id": 2, "name": "Five off chess", "code": "CHESS5", "kind": "fixed", "discount": 5.0, "detail": "5.00 off product 2" },
+        ])
+    );
+    assert_eq!(app.get("/cart", &bob).await.json(), cart);
+
+    // without the chess the coupon takes nothing off, and says why
+    assert_eq!(app.delete("/cart/items/2", &bob).await.status, StatusCode::NO_CONTENT);
+    let cart = app.get("/cart", &bob).await.json();
+    assert_eq!(cart["coupon"], "CHESS5");
+    assert_eq!(cart["coupon_note"], "coupon CHESS5 does not apply to anything in the cart");
+    assert_eq!((cart["discount"].clone(), cart["total"].clone()), (json!(2.0), json!(23.0)));
+    let response = app.post("/checkout", &bob, Value::Null).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "coupon CHESS5 does not apply to anything in the cart");
+
+    let cart = app.delete("/cart/coupon", &bob).await.json();
+    assert_eq!((cart["coupon"].clone(), cart["coupon_note"].clone()), (Value::Null, Value::Null));
+    let response = app.post("/checkout", &bob, Value::Null).await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    let order = response.json();
+    assert_eq!((order["discount"].clone(), order["shipping"].clone(), order["total"].clone()), (json!(2.0), json!(5.0), json!(23.0)));
+    let events: Vec<_> = app.events().await.into_iter().filter(|(t, _)| t == "OrderPlaced").collect();
+    assert_eq!(events[1], ("OrderPlaced".to_string(), json!({ "id": 2, "user_id": 2, "total": 23.0 })));
+    assert_eq!(app.get("/admin/promotions/1", &admin).await.json()["uses"], 1);
+    assert_eq!(app.get("/admin/promotions/2", &admin).await.json()["uses"], 0);
+}
+
+#[tokio::test]
+async fn buy_x_get_y_and_free_shipping() {
+    let (app, admin, bob) = shop().await;
+    let bogo = json!({ "name": "Chess 3 for 2", "kind": "buy_x_get_y", "buy_quantity": 2, "get_quantity": 1, "product_id": 2 });
+    create(&app, &admin, bogo).await;
+    create(&app, &admin, json!({ "name": "Free shipping", "code": "SHIPFREE", "kind": "free_shipping" })).await;
+
+    // two are not enough
+    add(&app, &bob, 2, 2).await;
+    let cart = app.get("/cart", &bob).await.json();
+    assert_eq!((cart["subtotal"].clone(), cart["discount"].clone(), cart["total"].clone()), (json!(30.0), json!(0.0), json!(35.0)));
+    assert_eq!(cart["promotions"], json!([]));
+
+    let cart = app.put("/cart/items/2", &bob, json!({ "quantity": 3 })).await.json();
+    assert_eq!((cart["subtotal"].clone(), cart["discount"].clone(), cart["total"].clone()), (json!(45.0), json!(15.0), json!(35.0)));
+    assert_eq!(cart["promotions"][0]["detail"], "buy 2 get 1 free on product 2");
+
+    let cart = app.put("/cart/coupon", &bob, json!({ "code": "SHIPFREE" })).await.json();
+    assert_eq!((cart["shipping"].clone(), cart["total"].clone()), (json!(0.0), json!(30.0)));
+    assert_eq!(
+        cart["promotions"][1],
+        json!({ "promotion_id": 2, "name": "Free shipping", "code": "SHIPFREE", "kind": "free_shipping", "discount": 0.0, "detail": "free shipping" })
+    );
+
+    let order = app.post("/checkout", &bob, Value::Null).await.json();
+    assert_eq!((order["discount"].clone(), order["shipping"].clone(), order["total"].clone()), (json!(15.0), json!(0.0), json!(30.0)));
+    assert_eq!(stock(&app, &bob, 2).await, 0);
+    assert_eq!(app.get("/admin/promotions/2", &admin).await.json()["uses"], 1);
+}
+
+#[tokio::test]
+async fn coupons_respect_validity_windows_and_usage_limits() {
+    let (app, admin, bob) = shop().await;
+    let tomorrow = (Utc::now() + Duration::days(1)).to_rfc3339();
+    let yesterday = (Utc::now() - Duration::days(1)).to_rfc3339();
+    create(&app, &admin, json!({ "name": "Later", "code": "LATER", "kind": "free_shipping", "starts_at": tomorrow })).await;
+    create(&app, &admin, json!({ "name": "Old", "code": "OLD", "kind": "free_shipping", "ends_at": yesterday })).await;
+    create(&app, &admin, json!({ "name": "Off", "code": "OFF", "kind": "free_shipping", "active": false })).await;
+    let once = json!({ "name": "Once", "code": "ONCE", "kind": "percentage", "value": 10, "max_uses": 2, "max_uses_per_user": 1 });
+    create(&app, &admin, once).await;
+
+    // no cart yet
+    assert_problem(&app.put("/cart/coupon", &bob, json!({ "code": "ONCE" })).await, StatusCode::NOT_FOUND);
+    add(&app, &bob, 3, 1).await;
+    let unusable = [
+        ("later", "coupon LATER is not valid yet"),
+        ("old", "coupon OLD has expired"),
+        ("off", "coupon OFF is not active"),
+        ("nope", "coupon NOPE does not exist"),
+        (" ", "code is required"),
+    ];
+    for (code, detail) in unusable {
+        let response = app.put("/cart/coupon", &bob, json!({ "code": code })).await;
+        assert_problem(&response, StatusCode::BAD_REQUEST);
+        assert_eq!(response.json()["detail"], detail);
+    }
+
+    assert_eq!(app.put("/cart/coupon", &bob, json!({ "code": "ONCE" })).await.json()["discount"], 2.0);
+    assert_eq!(app.post("/checkout", &bob, Value::Null).await.json()["total"], 23.0);
+    add(&app, &bob, 3, 1).await;
+    let response = app.put("/cart/coupon", &bob, json!({ "code": "ONCE" })).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "coupon ONCE can be used 1 time(s) per customer");
+
+    let carol = login_as(&app, "carol").await;
+    add(&app, &carol, 3, 1).await;
+    assert_eq!(app.put("/cart/coupon", &carol, json!({ "code": "ONCE" })).await.status, StatusCode::OK);
+    // another customer using it up meanwhile leaves a note
+    let dave = login_as(&app, "dave").await;
+    add(&app, &dave, 3, 1).await;
+    assert_eq!(app.put("/cart/coupon", &dave, json!({ "code": "ONCE" })).await.status, StatusCode::OK);
+    assert_eq!(app.post("/checkout", &carol, Value::Null).await.status, StatusCode::CREATED);
+    let cart = app.get("/cart", &dave).await.json();
+    assert_eq!((cart["coupon_note"].clone(), cart["discount"].clone()), (json!("coupon ONCE has been used up"), json!(0.0)));
+    let response = app.post("/checkout", &dave, Value::Null).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "coupon ONCE has been used up");
+    assert_eq!(app.get("/admin/promotions/4", &admin).await.json()["uses"], 2);
+}
@@ -0,0 +1,195 @@
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use super::order::{admin_with_order, login_as, stock};
+use super::{assert_problem, TestApp};
+
+/// Supplier 1, Acme, selling the Rust Book at 20 and Chess at 8.
+async fn acme(app: &TestApp, admin: &str) {
+    let response = app.post("/admin/suppliers", admin, json!({ "name": "Acme", "email": "orders@acme.test" })).await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    assert_eq!(response.json(), json!({ "id": 1, "name": "Acme", "email": "orders@acme.test" }));
+    for (product_id, sku, cost_price) in [(1, "RB-1", 20.0), (2, "CH-1", 8.0)] {
+        let uri = format!("/admin/suppliers/1/products/{product_id}");
+        let response = app.put(&uri, admin, json!({ "sku": sku, "cost_price": cost_price })).await;
+        assert_eq!(response.status, StatusCode::OK);
+    }
+}
+
+async fn average_cost(app: &TestApp, admin: &str, product_id: i64) -> Value {
+    let response = app.get(&format!("/admin/products/{product_id}/cost"), admin).await;
+    assert_eq!(response.status, StatusCode::OK);
+    response.json()["average_cost"].clone()
+}
+
+#[tokio::test]
+async fn suppliers_list_their_products() {
+    let (app, admin) = admin_with_order(|builder| builder).await;
+    acme(&app, &admin).await;
+
+    assert_eq!(app.get("/admin/suppliers", &admin).await.json(), json!([{ "id": 1, "name": "Acme", "email": "orders@acme.test" }]));
+    let response = app.put("/admin/suppliers/1/products/2", &admin, json!({ "sku": "CH-2", "cost_price": 7.499 })).await;
+    assert_eq!(response.json(), json!({ "supplier_id": 1, "product_id": 2, "sku": "CH-2", "cost_price": 7.5 }));
+    let response = app.get("/admin/suppliers/1/products", &admin).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.json(),
+        json!([
+            { "supplier_id": 1, "product_id": 1, "sku": "RB-1", "cost_price": 20.0 },
+            { "supplier_id": 1, "product_id": 2, "sku": "CH-2", "cost_price": 7.5 },
+        ])
+    );
+
+    let response = app.post("/admin/suppliers", &admin, json!({ "name": " Acme " })).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "supplier \"Acme\" already exists");
+    let taken = json!({ "sku": "RB-1", "cost_price": 1.0 });
+    assert_problem(&app.put("/admin/suppliers/1/products/2", &admin, taken).await, StatusCode::BAD_REQUEST);
+    let negative = json!({ "sku": "X", "cost_price": -1.0 });
+    assert_problem(&app.put("/admin/suppliers/1/products/2", &admin, negative).await, StatusCode::BAD_REQUEST);
+    let link = json!({ "sku": "X", "cost_price": 1.0 });
+    assert_problem(&app.put("/admin/suppliers/1/products/42", &admin, link.clone()).await, StatusCode::BAD_REQUEST);
+    assert_problem(&app.put("/admin/suppliers/42/products/1", &admin, link).await, StatusCode::NOT_FOUND);
+    assert_problem(&app.get("/admin/suppliers/42/products", &admin).await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn receiving_posts_stock_and_weighs_the_average_cost() {
+    let (app, admin) = admin_with_order(|builder| builder).await;
+    acme(&app, &admin).await;
+
+    let lines = json!([{ "product_id": 2, "quantity": 2, "unit_cost": 9.0 }, { "product_id": 1, "quantity": 4 }]);
+    let response = app.post("/admin/purchase-orders", &admin, json!({ "supplier_id": 1, "lines": lines })).await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    let order = response.json();
+    assert_eq!((order["id"].clone(), order["status"].clone(), order["created_by"].clone()), (json!(1), json!("draft"), json!(1)));
+    assert_eq!(
+        order["lines"],
+        json!([
+            { "product_id": 1, "quantity": 4, "received": 0, "unit_cost": 20.0 },
+            { "product_id": 2, "quantity": 2, "received": 0, "unit_cost": 9.0 },
+        ])
+    );
+    assert_eq!(order["total"], 98.0);
+
+    // nothing can arrive before the order went out
+    let receipt = json!({ "lines": [{ "product_id": 1, "quantity": 3 }] });
+    let response = app.post("/admin/purchase-orders/1/receipts", &admin, receipt.clone()).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "a draft purchase order cannot be received");
+    assert_eq!(app.post("/admin/purchase-orders/1/send", &admin, Value::Null).await.json()["status"], "sent");
+
+    let response = app.post("/admin/purchase-orders/1/receipts", &admin, receipt).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json()["status"], "partially_received");
+    assert_eq!(response.json()["lines"][0]["received"], 3);
+    assert_eq!(stock(&app, &admin, 1).await, 4);
+    assert_eq!(average_cost(&app, &admin, 1).await, json!(20.0));
+
+    let rest = json!({ "lines": [{ "product_id": 1, "quantity": 1 }, { "product_id": 2, "quantity": 2 }] });
+    let response = app.post("/admin/purchase-orders/1/receipts", &admin, rest).await;
+    assert_eq!(response.json()["status"], "received");
+    assert_eq!((stock(&app, &admin, 1).await, stock(&app, &admin, 2).await), (5, 5));
+    // Chess had 3 in stock at no known cost: only the receipt counts
+    assert_eq!(average_cost(&app, &admin, 2).await, json!(9.0));
+
+    // 5 at 20 and 5 at 26
+    let lines = json!([{ "product_id": 1, "quantity": 5, "unit_cost": 26.0 }]);
+    app.post("/admin/purchase-orders", &admin, json!({ "supplier_id": 1, "lines": lines })).await;
+    app.post("/admin/purchase-orders/2/send", &admin, Value::Null).await;
+    let receipt = json!({ "lines": [{ "product_id": 1, "quantity": 5 }], "warehouse_id": 1 });
+    assert_eq!(app.post("/admin/purchase-orders/2/receipts", &admin, receipt).await.status, StatusCode::OK);
+    assert_eq!(average_cost(&app, &admin, 1).await, json!(23.0));
+    assert_eq!(stock(&app, &admin, 1).await, 10);
+
+    let movements = app.get("/admin/products/1/stock/movements", &admin).await.json();
+    let receipts: Vec<_> = movements.as_array().unwrap().iter().filter(|m| m["kind"] == "receipt").collect();
+    let receipts: Vec<_> = receipts.iter().map(|m| (m["quantity"].clone(), m["reason"].clone(), m["actor_id"].clone())).collect();
+    assert_eq!(
+        receipts,
+        [
+            (json!(3), json!("initial stock"), Value::Null),
+            (json!(3), json!("purchase order 1"), json!(1)),
+            (json!(1), json!("purchase order 1"), json!(1)),
+            (json!(5), json!("purchase order 2"), json!(1)),
+        ]
+    );
+    let changes: Vec<_> = app.events().await.into_iter().filter(|(t, _)| t == "PurchaseOrderStatusChanged").map(|(_, e)| e).collect();
+    assert_eq!(
+        changes,
+        [
+            json!({ "id": 1, "from": "draft", "to": "sent" }),
+            json!({ "id": 1, "from": "sent", "to": "partially_received" }),
+            json!({ "id": 1, "from": "partially_received", "to": "received" }),
+            json!({ "id": 2, "from": "draft", "to": "sent" }),
+            json!({ "id": 2, "from": "sent", "to": "received" }),
+        ]
+    );
+    let ids: Vec<_> = app.get("/admin/purchase-orders", &admin).await.json().as_array().unwrap().iter().map(|o| o["id"].clone()).collect();
+    assert_eq!(ids, [json!(2), json!(1)]);
+}
+
+#[tokio::test]
+async fn purchase_orders_reject_what_the_supplier_does_not_sell() {
+    let (app, admin) = admin_with_order(|builder| builder).await;
+    acme(&app, &admin).await;
+    app.post("/products", &admin, json!({ "name": "Go", "description": null, "price": 5.0, "stock": 0, "category_id": 1, "active": true }))
+        .await;
+
+    for (supplier_id, lines, detail) in [
+        (1, json!([]), "a purchase order needs at least one line"),
+        (1, json!([{ "product_id": 3, "quantity": 1 }]), "supplier 1 does not sell product 3"),
+        (1, json!([{ "product_id": 1, "quantity": 0 }]), "invalid quantity 0 for product 1"),
+        (1, json!([{ "product_id": 1, "quantity": 1 }, { "product_id": 1, "quantity": 2 }]), "product 1 is on the order twice"),
+        (42, json!([{ "product_id": 1, "quantity": 1 }]), "no supplier 42"),
+    ] {
+        let response = app.post("/admin/purchase-orders", &admin, json!({ "supplier_id": supplier_id, "lines": lines })).await;
+        assert_problem(&response, StatusCode::BAD_REQUEST);
+        assert_eq!(response.json()["detail"], detail);
+    }
+
+    let lines = json!([{ "product_id": 1, "quantity": 2 }]);
+    app.post("/admin/purchase-orders", &admin, json!({ "supplier_id": 1, "lines": lines })).await;
+    app.post("/admin/purchase-orders/1/send", &admin, Value::Null).await;
+    let response = app.post("/admin/purchase-orders/1/send", &admin, Value::Null).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "a purchase order cannot go from sent to sent");
+    for (lines, detail) in [
+        (json!([{ "product_id": 1, "quantity": 3 }]), "only 2 of product 1 outstanding on purchase order 1"),
+        (json!([{ "product_id": 2, "quantity": 1 }]), "product 2 is not on purchase order 1"),
+    ] {
+        let response = app.post("/admin/purchase-orders/1/receipts", &admin, json!({ "lines": lines })).await;
+        assert_problem(&response, StatusCode::BAD_REQUEST);
+        assert_eq!(response.json()["detail"], detail);
+    }
+    let elsewhere = json!({ "lines": [{ "product_id": 1, "quantity": 1 }], "warehouse_id": 42 });
+    assert_problem(&app.post("/admin/purchase-orders/1/receipts", &admin, elsewhere).await, StatusCode::BAD_REQUEST);
+    assert_eq!(stock(&app, &admin, 1).await, 1);
+    assert_eq!(average_cost(&app, &admin, 1).await, Value::Null);
+
+    assert_problem(&app.get("/admin/purchase-orders/42", &admin).await, StatusCode::NOT_FOUND);
+    assert_problem(&app.post("/admin/purchase-orders/42/send", &admin, Value::Null).await, StatusCode::NOT_FOUND);
+    assert_problem(&app.get("/admin/products/42/cost", &admin).await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn purchasing_is_admin_only() {
+    let (app, admin) = admin_with_order(|builder| builder).await;
+    acme(&app, &admin).await;
+    let bob = login_as(&app, "bob").await;
+
+    assert_problem(&app.post("/admin/suppliers", &bob, json!({ "name": "Initech" })).await, StatusCode::FORBIDDEN);
+    assert_problem(&app.get("/admin/suppliers", &bob).await, StatusCode::FORBIDDEN);
+    let link = json!({ "sku": "X", "cost_price": 1.0 });
+    assert_problem(&app.put("/admin/suppliers/1/products/1", &bob, link).await, StatusCode::FORBIDDEN);
+    assert_problem(&app.get("/admin/suppliers/1/products", &bob).await, StatusCode::FORBIDDEN);
+    let order = json!({ "supplier_id": 1, "lines": [{ "product_id": 1, "quantity": 1 }] });
+    assert_problem(&app.post("/admin/purchase-orders", &bob, order).await, StatusCode::FORBIDDEN);
+    assert_problem(&app.get("/admin/purchase-orders", &bob).await, StatusCode::FORBIDDEN);
+    assert_problem(&app.get("/admin/purchase-orders/1", &bob).await, StatusCode::FORBIDDEN);
+    assert_problem(&app.post("/admin/purchase-orders/1/send", &bob, Value::Null).await, StatusCode::FORBIDDEN);
+    let receipt = json!({ "lines": [{ "product_id": 1, "quantity": 1 }] });
+    assert_problem(&app.post("/admin/purchase-orders/1/receipts", &bob, receipt).await, StatusCode::FORBIDDEN);
+    assert_problem(&app.get("/admin/products/1/cost", &bob).await, StatusCode::FORBIDDEN);
+    assert!(app.get("/admin/purchase-orders", &admin).await.json().as_array().unwrap().is_empty());
+}
@@ -0,0 +1,76 @@
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use opentelemetry::global;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use tracing::subscriber::DefaultGuard;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::Registry;
+
+use super::{TestApp, TestResponse};
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const PARENT_ID: &str = "00f067aa0ba902b7";
+
+/// Request spans backed by OTel on this thread, with W3C propagation as
+/// `init_telemetry` sets it up.
+fn traced() -> DefaultGuard {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    let tracer = SdkTracerProvider::builder().build().tracer("test");
+    tracing::subscriber::set_default(Registry::default().with(tracing_opentelemetry::layer().with_tracer(tracer)))
+}
+
+async fn health(app: &TestApp, traceparent: Option<&str>) -> TestResponse {
+    let mut request = Request::get("/health");
+    if let Some(traceparent) = traceparent {
+        request = request.header("traceparent", traceparent);
+    }
+    let response = app.send(request.body(Body::empty()).unwrap()).await;
+    assert_eq!(response.status, StatusCode::OK);
+    response
+}
+
+/// `(trace id, span id, flags)` of the `traceresponse` header, checking that
+/// `X-Trace-Id` names the same trace.
+fn trace_response(response: &TestResponse) -> (String, String, String) {
+    let header = |name: &str| response.headers[name].to_str().unwrap().to_string();
+    let traceresponse = header("traceresponse");
+    let parts: Vec<_> = traceresponse.split('-').collect();
+    let [version, trace_id, span_id, flags] = parts[..] else { panic!("traceresponse {traceresponse}") };
+    assert_eq!(version, "00");
+    assert_eq!(header("X-Trace-Id"), trace_id);
+    (trace_id.to_string(), span_id.to_string(), flags.to_string())
+}
+
+#[tokio::test]
+async fn requests_continue_the_callers_trace() {
+    let _guard = traced();
+    let app = TestApp::new();
+
+    let response = health(&app, Some(&format!("00-{TRACE_ID}-{PARENT_ID}-01"))).await;
+    let (trace_id, span_id, flags) = trace_response(&response);
+    assert_eq!((trace_id.as_str(), flags.as_str()), (TRACE_ID, "01"));
+    // the server span is a child of the caller's
+    assert_ne!(span_id, PARENT_ID);
+    assert_eq!(span_id.len(), 16);
+}
+
+#[tokio::test]
+async fn requests_without_a_valid_traceparent_start_a_trace() {
+    let _guard = traced();
+    let app = TestApp::new();
+
+    let (first, _, _) = trace_response(&health(&app, None).await);
+    let (second, _, _) = trace_response(&health(&app, None).await);
+    assert_eq!(first.len(), 32);
+    assert_ne!(first, second);
+
+    // malformed: ignored, not rejected
+    for traceparent in ["garbage", &format!("00-{TRACE_ID}-{PARENT_ID}"), &format!("00-{}-{PARENT_ID}-01", "0".repeat(32))] {
+        let (trace_id, _, _) = trace_response(&health(&app, Some(traceparent)).await);
+        assert_eq!(trace_id.len(), 32, "{traceparent}");
+        assert_ne!(trace_id, TRACE_ID, "{traceparent}");
+        assert_ne!(trace_id, "0".repeat(32), "{traceparent}");
+    }
+}
@@ -0,0 +1,168 @@
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use serde_json::json;
+
+use super::{assert_problem, TestApp};
+
+#[tokio::test]
+async fn create_user_requires_api_key() {
+    let app = TestApp::new();
+    let body = json!({ "username": "bob", "password": "pw" }).to_string();
+
+    let request = Request::post("/users")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.clone()))
+        .unwrap();
+    let response = app.send(request).await;
+    assert_problem(&response, StatusCode::UNAUTHORIZED);
+    assert_eq!(response.json()["detail"], "missing X-Api-Key header");
+
+    let request = Request::post("/users")
+        .header("X-Api-Key", "wrong")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    let response = app.send(request).await;
+    assert_problem(&response, StatusCode::UNAUTHORIZED);
+    assert_eq!(response.json()["detail"], "invalid api key");
+}
+
+#[tokio::test]
+async fn create_user_accepts_issued_api_keys_until_revoked() {
+    let app = TestApp::new();
+    let issued = app.api_keys.issue("provisioning".into()).await.unwrap();
+    assert!(issued.key.starts_with("jl_"));
+
+    let response = app.create_user_with_key(&issued.key, "bob", "pw").await;
+    assert_eq!(response.status, StatusCode::CREATED);
+
+    // right prefix, wrong secret
+    let (prefix, _) = issued.key.rsplit_once('_').unwrap();
+    let forged = format!("{prefix}_{}", "0".repeat(48));
+    assert_problem(&app.create_user_with_key(&forged, "eve", "pw").await, StatusCode::UNAUTHORIZED);
+
+    app.api_keys.revoke(issued.id).await.unwrap();
+    let response = app.create_user_with_key(&issued.key, "carol", "pw").await;
+    assert_problem(&response, StatusCode::UNAUTHORIZED);
+    assert_eq!(response.json()["detail"], "invalid api key");
+}
+
+#[tokio::test]
+async fn create_user_validates_input() {
+    let app = TestApp::new();
+
+    let response = app.create_user("bob", "pw").await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    assert_eq!(response.json()["id"], 1);
+
+    let response = app.create_user("bob", "other").await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "username already exists");
+
+    let response = app.create_user("  ", "pw").await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "username is required");
+
+    let response = app.create_user("carol", "").await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+    assert_eq!(response.json()["detail"], "password is required");
+}
+
+#[tokio::test]
+async fn login_returns_token_only_for_valid_credentials() {
+    let app = TestApp::new();
+    app.create_user("bob", "pw").await;
+
+    let response = app.login("bob", "pw").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.json()["token"].as_str().unwrap().starts_with("eyJ"));
+
+    assert_problem(&app.login("bob", "wrong").await, StatusCode::UNAUTHORIZED);
+    assert_problem(&app.login("nobody", "pw").await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn list_and_get_users_never_expose_passwords() {
+    let app = TestApp::new();
+    let token = app.token().await;
+    app.create_user("bob", "pw").await;
+
+    let response = app.get("/users", &token).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let users = response.json();
+    assert_eq!(users.as_array().unwrap().len(), 2);
+    assert_eq!(users[1]["username"], "bob");
+    assert!(users[1].get("password").is_none());
+
+    let response = app.get("/users/2", &token).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.json(),
+        json!({ "id": 2, "username": "bob", "active": true, "greet": "Hello bob" })
+    );
+
+    assert_problem(&app.get("/users/99", &token).await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn update_user_changes_username_and_password() {
+    let app = TestApp::new();
+    let token = app.token().await;
+    app.create_user("bob", "pw").await;
+
+    let response = app.put("/users/2", &token, json!({ "username": "robert", "password": "new" })).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json()["username"], "robert");
+
+    assert_eq!(app.login("robert", "new").await.status, StatusCode::OK);
+    assert_problem(&app.login("robert", "pw").await, StatusCode::UNAUTHORIZED);
+
+    let response = app.put("/users/99", &token, json!({ "username": "x", "password": "y" })).await;
+    assert_problem(&response, StatusCode::NOT_FOUND);
+
+    let response = app.put("/users/2", &token, json!({ "username": "", "password": "y" })).await;
+    assert_problem(&response, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn delete_user() {
+    let app = TestApp::new();
+    let token = app.token().await;
+    app.create_user("bob", "pw").await;
+
+    assert_eq!(app.delete("/users/2", &token).await.status, StatusCode::NO_CONTENT);
+    assert_problem(&app.get("/users/2", &token).await, StatusCode::NOT_FOUND);
+    assert_problem(&app.delete("/users/2", &token).await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn user_speak() {
+    let app = TestApp::new();
+    let token = app.token().await;
+
+    let response = app.get("/users/1/speak", &token).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json(), json!({ "speak": "Hello alice", "shout": "HELLO ALICE" }));
+
+    assert_problem(&app.get("/users/99/speak", &token).await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn user_changes_raise_events_without_passwords() {
+    let app = TestApp::new();
+    let token = app.token().await;
+
+    app.put("/users/1", &token, json!({ "username": "alicia", "password": "new" })).await;
+    app.delete("/users/1", &token).await;
+
+    assert_eq!(
+        app.events().await,
+        [
+            ("UserCreated".to_string(), json!({ "id": 1, "username": "alice" })),
+            ("UserUpdated".to_string(), json!({ "id": 1, "username": "alicia" })),
+            ("UserDeleted".to_string(), json!({ "id": 1 })),
+        ]
+    );
+}
@@ -1,12 +1,13 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
     Json,
 };
 
 use crate::{
-    adapters::auth_middleware::ApiKey,
+    adapters::auth_middleware::{ApiKey, CART_TOKEN_HEADER},
+    adapters::dto_page::PageQuery,
     adapters::dto_user::{CreateUserReq, CreateUserResp, LoginReq, LoginResp, SpeakResp, UpdateUserReq, UserResp},
     domain::user::Speak,
     infra::jwt::Claims,
@@ -21,17 +22,21 @@ pub async fn create_user(
 ) -> axum::response::Response {
     match state.user_service.create_user(req.username.clone(), req.password).await {
         Ok(id) => {
-            tracing::info!(user_id = id, username = %req.username, "user created: {:#?}", req.username);
+            tracing::info!(user_id = id, username = %req.username, "user created");
             (StatusCode::CREATED, Json(CreateUserResp { id })).into_response()
         }
         Err(e) => super::map_error(e),
     }
 }
 
-pub async fn get_all_users(_claims: Claims, State(state): State<AppState>) -> axum::response::Response {
-    match state.user_service.get_all_users().await {
+pub async fn get_all_users(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Query(page): Query<PageQuery>,
+) -> axum::response::Response {
+    match state.user_service.get_all_users(page.into()).await {
         Ok(users) => {
-            tracing::info!(count = users.len(), "fetched users: {:#?}", &users[..users.len().min(5)]);
+            tracing::info!(count = users.len(), "fetched users");
             let resp: Vec<UserResp> = users.into_iter().map(UserResp::from).collect();
             (StatusCode::OK, Json(resp)).into_response()
         },
@@ -42,7 +47,7 @@ pub async fn get_all_users(_claims: Claims, State(state): State<AppState>) -> ax
 pub async fn get_user(_claims: Claims, State(state): State<AppState>, Path(id): Path<i64>) -> axum::response::Response {
     match state.user_service.get_user(id).await {
         Ok(u) => {
-            tracing::info!(user_id = u.id, username = %u.username, active = u.active, "fetched user {:#?}", u);
+            tracing::info!(user_id = u.id, username = %u.username, active = u.active, "fetched user");
             (StatusCode::OK, Json(UserResp::from(u))).into_response()
         },
         Err(e) => super::map_error(e),
@@ -57,7 +62,7 @@ pub async fn update_user(
 ) -> axum::response::Response {
     match state.user_service.update_user(id, req.username, req.password).await {
         Ok(u) => {
-            tracing::info!(user_id = u.id, username = %u.username, active = u.active, "updated user {:#?}", u);
+            tracing::info!(user_id = u.id, username = %u.username, active = u.active, "updated user");
             (StatusCode::OK, Json(UserResp::from(u))).into_response()
         },
         Err(e) => super::map_error(e),
@@ -95,15 +100,23 @@ pub async fn delete_user(
     }
 }
 
+/// With an `X-Cart-Token` header, the guest's cart is merged into the user's.
 pub async fn login_user(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(req): Json<LoginReq>,
 ) -> axum::response::Response {
     let username = req.username.clone();
     match state.user_service.login(req.username, req.password).await {
-        Ok(token) => {
+        Ok(session) => {
             tracing::info!(username = %username, "user logged in");
-            (StatusCode::OK, Json(LoginResp { token })).into_response()
+            if let Some(guest_token) = headers.get(CART_TOKEN_HEADER).and_then(|v| v.to_str().ok()) {
+                // the login itself succeeded; the guest can re-add the items
+                if let Err(e) = state.cart_service.merge_guest_cart(guest_token.to_string(), session.user_id).await {
+                    tracing::warn!(error = %e, user_id = session.user_id, "guest cart not merged");
+                }
+            }
+            (StatusCode::OK, Json(LoginResp { token: session.token })).into_response()
         }
         Err(e) => super::map_error(e),
     }
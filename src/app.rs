@@ -0,0 +1,424 @@
+//! [`App::builder`] wires repositories into the services, the REST router
+//! and the background tasks (outbox relay, job workers, cron schedules).
+//! The `rust-just-learn` binary is one configuration of it; other services
+//! can embed the router, bring their own repositories, or add routes,
+//! layers and jobs.
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::Request;
+use axum::response::IntoResponse;
+use axum::routing::Route;
+use axum::Router;
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tower::{Layer, Service};
+
+use crate::adapters::restapi::{routes, with_observability, AppState};
+use crate::domain::cache::CacheInvalidator;
+use crate::domain::event::EventSink;
+use crate::domain::job::JobPayload;
+use crate::domain::payment::PaymentProvider;
+use crate::domain::warehouse::AllocationStrategy;
+use crate::domain::DomainError;
+use crate::infra::events::InProcessBus;
+use crate::infra::payment::MockPaymentProvider;
+use crate::infra::repository::backend::{Backend, Repositories};
+use crate::infra::repository::cache_sync::PgCacheSync;
+use crate::infra::repository::cached::RepositoryCache;
+use crate::infra::repository::memory::{InMemoryJobQueue, InMemoryStore};
+use crate::usecases::api_key_service::ApiKeyService;
+use crate::usecases::cart_service::CartService;
+use crate::usecases::category_service::CategoryService;
+use crate::usecases::housekeeping::{
+    PruneFinishedJobs, PruneFinishedJobsHandler, PruneIdleCarts, PruneIdleCartsHandler, ReleaseExpiredReservations,
+    ReleaseExpiredReservationsHandler,
+};
+use crate::usecases::job_scheduler::JobScheduler;
+use crate::usecases::job_worker::{JobHandler, JobWorker};
+use crate::usecases::inventory_service::InventoryService;
+use crate::usecases::notification_service::NotificationService;
+use crate::usecases::order_service::OrderService;
+use crate::usecases::outbox_relay::OutboxRelay;
+use crate::usecases::payment_service::PaymentService;
+use crate::usecases::promotion_service::PromotionService;
+use crate::usecases::purchasing_service::PurchasingService;
+use crate::usecases::product_service::ProductService;
+use crate::usecases::user_service::UserService;
+
+type Layering = Box<dyn FnOnce(Router) -> Router + Send>;
+type Registration = Box<dyn FnOnce(JobWorker) -> JobWorker + Send>;
+type Scheduling = Box<dyn FnOnce(JobScheduler) -> Result<JobScheduler, DomainError> + Send>;
+
+/// Configures an [`App`]; every setting has the server's default.
+pub struct AppBuilder {
+    repositories: Option<Repositories>,
+    cache: Option<RepositoryCache>,
+    cache_sync: Option<PgCacheSync>,
+    routes: Router<AppState>,
+    layers: Vec<Layering>,
+    event_bus: Option<InProcessBus>,
+    outbox_sinks: Option<Vec<Arc<dyn EventSink>>>,
+    outbox_poll_interval: Duration,
+    outbox_batch_size: i64,
+    job_queues: Vec<(String, usize)>,
+    job_poll_interval: Duration,
+    job_retention_days: i64,
+    cart_idle_days: i64,
+    reservation_minutes: i64,
+    allocation_strategy: AllocationStrategy,
+    shipping_fee: f64,
+    payment_provider: Option<Arc<dyn PaymentProvider>>,
+    job_handlers: Vec<Registration>,
+    schedules: Vec<Scheduling>,
+}
+
+impl AppBuilder {
+    /// Repositories to build the services on, e.g.
+    /// [`Backend::repositories`], with any field swapped for a custom
+    /// implementation. Defaults to a fresh in-memory store.
+    pub fn repositories(mut self, repositories: Repositories) -> Self {
+        self.repositories = Some(repositories);
+        self
+    }
+
+    /// Cache category and product reads in `cache`; the services invalidate
+    /// it on every write.
+    pub fn cache(mut self, cache: RepositoryCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Share invalidations of the [`cache`](Self::cache) with the other
+    /// replicas on the same Postgres database.
+    pub fn cache_sync(mut self, sync: PgCacheSync) -> Self {
+        self.cache_sync = Some(sync);
+        self
+    }
+
+    /// Extra routes, merged with the API's; they share [`AppState`] and the
+    /// extractors, and get the same request ids, tracing and metrics.
+    pub fn routes(mut self, routes: Router<AppState>) -> Self {
+        self.routes = self.routes.merge(routes);
+        self
+    }
+
+    /// Wrap every route in `layer`, inside the observability layers. Layers
+    /// apply in the order added, the last one outermost.
+    pub fn layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + 'static,
+        L::Service: Service<Request> + Clone + Send + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.layers.push(Box::new(move |router| router.layer(layer)));
+        self
+    }
+
+    /// Bus the relay publishes to when it is a sink; [`App::event_bus`]
+    /// subscribes to it. Defaults to one buffering 1024 events.
+    pub fn event_bus(mut self, bus: InProcessBus) -> Self {
+        self.event_bus = Some(bus);
+        self
+    }
+
+    /// Where the outbox relay publishes events; defaults to the event bus only.
+    pub fn outbox_sinks(mut self, sinks: Vec<Arc<dyn EventSink>>) -> Self {
+        self.outbox_sinks = Some(sinks);
+        self
+    }
+
+    pub fn outbox_poll_interval(mut self, interval: Duration) -> Self {
+        self.outbox_poll_interval = interval;
+        self
+    }
+
+    pub fn outbox_batch_size(mut self, batch_size: i64) -> Self {
+        self.outbox_batch_size = batch_size;
+        self
+    }
+
+    /// `(queue, concurrency)` pairs to work; empty runs no workers.
+    /// Defaults to `default` with 4 slots.
+    pub fn job_queues(mut self, queues: Vec<(String, usize)>) -> Self {
+        self.job_queues = queues;
+        self
+    }
+
+    pub fn job_poll_interval(mut self, interval: Duration) -> Self {
+        self.job_poll_interval = interval;
+        self
+    }
+
+    /// Finished jobs are pruned daily at 03:00 UTC after this many days.
+    pub fn job_retention_days(mut self, days: i64) -> Self {
+        self.job_retention_days = days;
+        self
+    }
+
+    /// Carts nobody wrote to for this many days are gone; they are pruned
+    /// daily at 03:30 UTC.
+    pub fn cart_idle_days(mut self, days: i64) -> Self {
+        self.cart_idle_days = days;
+        self
+    }
+
+    /// Carts hold their products for this many minutes after they were
+    /// last read or written; expired holds are swept every minute.
+    pub fn reservation_minutes(mut self, minutes: i64) -> Self {
+        self.reservation_minutes = minutes;
+        self
+    }
+
+    /// Which warehouses checkouts take stock from first; by priority by
+    /// default.
+    pub fn allocation_strategy(mut self, strategy: AllocationStrategy) -> Self {
+        self.allocation_strategy = strategy;
+        self
+    }
+
+    /// Flat shipping fee on every order, unless a free-shipping promotion
+    /// applies; none by default.
+    pub fn shipping_fee(mut self, fee: f64) -> Self {
+        self.shipping_fee = fee;
+        self
+    }
+
+    /// Where orders are paid, e.g. [`provider_from_env`](crate::infra::payment::provider_from_env).
+    /// Defaults to a [`MockPaymentProvider`] with a random webhook secret.
+    pub fn payment_provider(mut self, provider: Arc<dyn PaymentProvider>) -> Self {
+        self.payment_provider = Some(provider);
+        self
+    }
+
+    /// Run jobs of `H::Job` with `handler`.
+    pub fn job_handler<H: JobHandler>(mut self, handler: H) -> Self {
+        self.job_handlers.push(Box::new(move |worker| worker.register(handler)));
+        self
+    }
+
+    /// Enqueue `job` on a cron `expression`, see [`JobScheduler::add`].
+    pub fn schedule<J: JobPayload>(mut self, name: &str, expression: &str, job: J) -> Self {
+        let (name, expression) = (name.to_string(), expression.to_string());
+        self.schedules.push(Box::new(move |scheduler| scheduler.add(&name, &expression, &job)));
+        self
+    }
+
+    /// Fails on an invalid cron expression.
+    pub fn build(self) -> Result<App, DomainError> {
+        let mut repos = self.repositories.unwrap_or_else(|| {
+            Backend::Memory(InMemoryStore::new(), InMemoryJobQueue::new()).repositories()
+        });
+        let mut invalidator: Option<Arc<dyn CacheInvalidator>> = None;
+        if let Some(cache) = &self.cache {
+            repos.categories = Arc::new(cache.categories(repos.categories));
+            repos.products = Arc::new(cache.products(repos.products));
+            invalidator = Some(match &self.cache_sync {
+                Some(sync) => Arc::new(sync.clone()),
+                None => Arc::new(cache.clone()),
+            });
+        }
+
+        let mut category_service = CategoryService::new(repos.categories.clone(), repos.uow.clone());
+        let mut product_service = ProductService::new(
+            repos.products.clone(),
+            repos.reservations.clone(),
+            repos.warehouses.clone(),
+            repos.uow.clone(),
+        );
+        let mut inventory_service =
+            InventoryService::new(repos.inventory.clone(), repos.products.clone(), repos.warehouses.clone(), repos.uow.clone());
+        let cart_idle_timeout = chrono::Duration::days(self.cart_idle_days);
+        let mut order_service =
+            OrderService::new(repos.orders.clone(), repos.uow.clone())
+                .with_cart_idle_timeout(cart_idle_timeout)
+                .with_allocation_strategy(self.allocation_strategy)
+                .with_shipping_fee(self.shipping_fee);
+        let provider = self
+            .payment_provider
+            .unwrap_or_else(|| Arc::new(MockPaymentProvider::new(ulid::Ulid::new().to_string())));
+        let mut payment_service = PaymentService::new(repos.payments.clone(), repos.uow.clone(), provider);
+        let mut purchasing_service = PurchasingService::new(
+            repos.purchasing.clone(),
+            repos.inventory.clone(),
+            repos.products.clone(),
+            repos.uow.clone(),
+        );
+        if let Some(invalidator) = invalidator {
+            category_service = category_service.with_cache_invalidator(invalidator.clone());
+            product_service = product_service.with_cache_invalidator(invalidator.clone());
+            inventory_service = inventory_service.with_cache_invalidator(invalidator.clone());
+            order_service = order_service.with_cache_invalidator(invalidator.clone());
+            payment_service = payment_service.with_cache_invalidator(invalidator.clone());
+            purchasing_service = purchasing_service.with_cache_invalidator(invalidator);
+        }
+        let state = AppState {
+            user_service: UserService::new(repos.users.clone(), repos.uow.clone()),
+            category_service,
+            product_service,
+            inventory_service,
+            api_key_service: ApiKeyService::new(repos.api_keys.clone()),
+            cart_service: CartService::new(repos.uow.clone())
+                .with_idle_timeout(cart_idle_timeout)
+                .with_reservation_ttl(chrono::Duration::minutes(self.reservation_minutes))
+                .with_shipping_fee(self.shipping_fee),
+            order_service,
+            payment_service,
+            notification_service: NotificationService::new(repos.notifications.clone()),
+            purchasing_service,
+            promotion_service: PromotionService::new(repos.promotions.clone(), repos.uow.clone()),
+        };
+        let mut router = self.routes.with_state(state.clone());
+        for layer in self.layers {
+            router = layer(router);
+        }
+        let router = with_observability(router);
+
+        let event_bus = self.event_bus.unwrap_or_else(|| InProcessBus::new(1024));
+        let sinks = self.outbox_sinks.unwrap_or_else(|| vec![Arc::new(event_bus.clone())]);
+        let relay = OutboxRelay::new(repos.uow.clone(), sinks).with_batch_size(self.outbox_batch_size);
+
+        let mut worker = JobWorker::new(repos.jobs.clone())
+            .register(PruneFinishedJobsHandler::new(repos.jobs.clone()))
+            .register(PruneIdleCartsHandler::new(repos.carts.clone()))
+            .register(ReleaseExpiredReservationsHandler::new(repos.reservations.clone()));
+        for register in self.job_handlers {
+            worker = register(worker);
+        }
+        let mut scheduler = JobScheduler::new(repos.jobs.clone()).add(
+            "prune_finished_jobs",
+            "0 0 3 * * *",
+            &PruneFinishedJobs { retention_days: self.job_retention_days },
+        )?
+        .add("prune_idle_carts", "0 30 3 * * *", &PruneIdleCarts { idle_days: self.cart_idle_days })?
+        .add("release_expired_reservations", "0 * * * * *", &ReleaseExpiredReservations {})?;
+        for schedule in self.schedules {
+            scheduler = schedule(scheduler)?;
+        }
+
+        Ok(App {
+            state,
+            router,
+            event_bus,
+            relay,
+            outbox_poll_interval: self.outbox_poll_interval,
+            worker,
+            job_queues: self.job_queues,
+            job_poll_interval: self.job_poll_interval,
+            scheduler,
+            cache_sync: self.cache.and(self.cache_sync),
+        })
+    }
+}
+
+/// The services, router and background tasks of one configuration.
+pub struct App {
+    state: AppState,
+    router: Router,
+    event_bus: InProcessBus,
+    relay: OutboxRelay,
+    outbox_poll_interval: Duration,
+    worker: JobWorker,
+    job_queues: Vec<(String, usize)>,
+    job_poll_interval: Duration,
+    scheduler: JobScheduler,
+    cache_sync: Option<PgCacheSync>,
+}
+
+impl App {
+    pub fn builder() -> AppBuilder {
+        AppBuilder {
+            repositories: None,
+            cache: None,
+            cache_sync: None,
+            routes: routes(),
+            layers: Vec::new(),
+            event_bus: None,
+            outbox_sinks: None,
+            outbox_poll_interval: Duration::from_secs(1),
+            outbox_batch_size: 100,
+            job_queues: vec![("default".to_string(), 4)],
+            job_poll_interval: Duration::from_secs(1),
+            job_retention_days: 7,
+            cart_idle_days: 30,
+            reservation_minutes: 15,
+            allocation_strategy: AllocationStrategy::Priority,
+            shipping_fee: 0.0,
+            payment_provider: None,
+            job_handlers: Vec::new(),
+            schedules: Vec::new(),
+        }
+    }
+
+    pub fn state(&self) -> &AppState {
+        &self.state
+    }
+
+    /// The complete router, to serve or to nest in another one. Background
+    /// tasks are not started; see [`App::spawn_background`].
+    pub fn router(&self) -> Router {
+        self.router.clone()
+    }
+
+    pub fn event_bus(&self) -> &InProcessBus {
+        &self.event_bus
+    }
+
+    /// Start the outbox relay, job scheduler, job workers and the cache
+    /// sync, if any.
+    pub fn spawn_background(&self) -> Background {
+        let (stop, stopped) = watch::channel(false);
+        let until_stopped = || {
+            let mut stopped = stopped.clone();
+            async move {
+                let _ = stopped.wait_for(|stop| *stop).await;
+            }
+        };
+
+        let mut tasks = vec![
+            ("outbox relay", tokio::spawn(self.relay.clone().run(self.outbox_poll_interval, until_stopped()))),
+            ("job scheduler", tokio::spawn(self.scheduler.clone().run(until_stopped()))),
+            (
+                "job workers",
+                tokio::spawn(self.worker.clone().run(self.job_queues.clone(), self.job_poll_interval, until_stopped())),
+            ),
+        ];
+        if let Some(sync) = &self.cache_sync {
+            tasks.push(("cache sync", tokio::spawn(sync.clone().run(until_stopped()))));
+        }
+        Background { stop, tasks }
+    }
+
+    /// Serve the router and run the background tasks until `shutdown`
+    /// resolves, then finish in-flight requests and jobs.
+    pub async fn serve(self, listener: TcpListener, shutdown: impl Future<Output = ()> + Send + 'static) -> std::io::Result<()> {
+        let background = self.spawn_background();
+        let result = axum::serve(listener, self.router).with_graceful_shutdown(shutdown).await;
+        background.shutdown().await;
+        result
+    }
+}
+
+/// Handle on the tasks started by [`App::spawn_background`].
+pub struct Background {
+    stop: watch::Sender<bool>,
+    tasks: Vec<(&'static str, JoinHandle<()>)>,
+}
+
+impl Background {
+    /// Stop the tasks and wait for them; running jobs are let finish.
+    pub async fn shutdown(self) {
+        let _ = self.stop.send(true);
+        for (name, task) in self.tasks {
+            if let Err(e) = task.await {
+                tracing::error!("{name} task failed: {e}");
+            }
+        }
+    }
+}
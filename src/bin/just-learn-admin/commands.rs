@@ -0,0 +1,210 @@
+use std::env;
+use std::error::Error;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, Read};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
+
+use rust_just_learn::domain::cache::{CacheInvalidator, NoCache};
+use rust_just_learn::domain::page::Page;
+use rust_just_learn::infra::repository::backend::{Backend, Repositories};
+use rust_just_learn::infra::repository::cache_sync::PgCacheSync;
+use rust_just_learn::infra::repository::cached::RepositoryCache;
+use rust_just_learn::usecases::api_key_service::ApiKeyService;
+use rust_just_learn::usecases::catalog_service::{Catalog, CatalogService};
+use rust_just_learn::usecases::category_service::CategoryService;
+use rust_just_learn::usecases::product_service::ProductService;
+use rust_just_learn::usecases::user_service::UserService;
+
+use crate::{config, ApiKeyCommand, CatalogCommand, Command, ConfigCommand, MigrateCommand, Report, UserCommand};
+
+type CommandResult = Result<Report, Box<dyn Error>>;
+
+pub async fn run(command: Command) -> CommandResult {
+    if let Command::Config(ConfigCommand::Check) = command {
+        // must report a bad DATABASE_URL rather than fail on it
+        return Ok(config::check().await);
+    }
+
+    let backend = connect().await?;
+    if let Backend::Memory(..) = backend {
+        return Err("DATABASE_URL is memory://, there is nothing to administer".into());
+    }
+    let repos = backend.repositories();
+    let result = match command {
+        Command::User(command) => user(command, &repos).await,
+        Command::Migrate(command) => migrate(command, &backend).await,
+        Command::ApiKey(command) => api_key(command, &repos).await,
+        Command::Catalog(command) => catalog(command, &backend, &repos).await,
+        Command::Config(ConfigCommand::Check) => unreachable!("handled above"),
+    };
+    backend.close().await;
+    result
+}
+
+pub async fn connect() -> Result<Backend, Box<dyn Error>> {
+    let url = env::var("DATABASE_URL").map_err(|_| "DATABASE_URL must be set")?;
+    Ok(Backend::connect(&url, 2).await?)
+}
+
+async fn user(command: UserCommand, repos: &Repositories) -> CommandResult {
+    let users = UserService::new(repos.users.clone(), repos.uow.clone());
+    match command {
+        UserCommand::CreateAdmin { username, password } => {
+            let id = users.create_admin(username.clone(), password_or_stdin(password)?).await?;
+            Ok(Report::new(
+                json!({ "id": id, "username": username, "admin": true }),
+                format!("created admin {username} (id {id})"),
+            ))
+        }
+        UserCommand::ResetPassword { username, password } => {
+            let user = users.reset_password(username, password_or_stdin(password)?).await?;
+            Ok(Report::new(
+                json!({ "id": user.id, "username": user.username }),
+                format!("password reset for {} (id {})", user.username, user.id),
+            ))
+        }
+        UserCommand::List => {
+            let all = users.get_all_users(Page::ALL).await?;
+            let mut text = format!("{:<6} {:<24} {:<6} {}\n", "ID", "USERNAME", "ADMIN", "ACTIVE");
+            for u in &all {
+                writeln!(text, "{:<6} {:<24} {:<6} {}", u.id, u.username, u.admin, u.active)?;
+            }
+            let json = all
+                .iter()
+                .map(|u| json!({ "id": u.id, "username": u.username, "admin": u.admin, "active": u.active }))
+                .collect();
+            Ok(Report::new(json, text))
+        }
+    }
+}
+
+/// `--password` if given, else the first line of stdin, so it stays out of
+/// shell history.
+fn password_or_stdin(password: Option<String>) -> Result<String, Box<dyn Error>> {
+    if let Some(password) = password {
+        return Ok(password);
+    }
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+async fn migrate(command: MigrateCommand, backend: &Backend) -> CommandResult {
+    if let MigrateCommand::Run = command {
+        backend.migrate().await?;
+    }
+    let status = backend.migration_status().await?;
+
+    let mut text = String::new();
+    for m in &status {
+        let state = match (m.applied, m.checksum_mismatch) {
+            (true, false) => "applied",
+            (true, true) => "CHANGED since applied",
+            (false, _) => "pending",
+        };
+        writeln!(text, "{:>4}  {:<40} {state}", m.version, m.description)?;
+    }
+    let pending = status.iter().filter(|m| !m.applied).count();
+    writeln!(text, "{} migrations, {pending} pending ({})", status.len(), backend.kind())?;
+
+    Ok(Report::new(json!({ "backend": backend.kind(), "migrations": status }), text))
+}
+
+async fn api_key(command: ApiKeyCommand, repos: &Repositories) -> CommandResult {
+    let keys = ApiKeyService::new(repos.api_keys.clone());
+    match command {
+        ApiKeyCommand::Issue { name } => {
+            let issued = keys.issue(name).await?;
+            Ok(Report::new(
+                json!({ "id": issued.id, "name": issued.name, "key": issued.key }),
+                format!(
+                    "issued key {} ({}); it will not be shown again:\n{}",
+                    issued.id, issued.name, issued.key
+                ),
+            ))
+        }
+        ApiKeyCommand::List => {
+            let all = keys.list().await?;
+            let mut text = format!("{:<6} {:<20} {:<12} {:<26} {}\n", "ID", "NAME", "PREFIX", "CREATED", "REVOKED");
+            for k in &all {
+                let revoked = k.revoked_at.map(|t| t.to_rfc3339()).unwrap_or_else(|| "-".into());
+                writeln!(text, "{:<6} {:<20} {:<12} {:<26} {revoked}", k.id, k.name, k.prefix, k.created_at.to_rfc3339())?;
+            }
+            let json = all
+                .iter()
+                .map(|k| {
+                    json!({
+                        "id": k.id,
+                        "name": k.name,
+                        "prefix": k.prefix,
+                        "created_at": k.created_at.to_rfc3339(),
+                        "revoked_at": k.revoked_at.map(|t| t.to_rfc3339()),
+                    })
+                })
+                .collect();
+            Ok(Report::new(json, text))
+        }
+        ApiKeyCommand::Revoke { id } => {
+            keys.revoke(id).await?;
+            Ok(Report::new(json!({ "id": id, "revoked": true }), format!("revoked key {id}")))
+        }
+    }
+}
+
+async fn catalog(command: CatalogCommand, backend: &Backend, repos: &Repositories) -> CommandResult {
+    // tell running servers to drop what they cached of the imported catalog
+    let invalidator: Arc<dyn CacheInvalidator> = match backend {
+        Backend::Postgres(pool) => Arc::new(PgCacheSync::new(pool.clone(), RepositoryCache::new(0, Duration::ZERO))),
+        _ => Arc::new(NoCache),
+    };
+    let catalog = CatalogService::new(
+        CategoryService::new(repos.categories.clone(), repos.uow.clone()).with_cache_invalidator(invalidator.clone()),
+        ProductService::new(repos.products.clone(), repos.reservations.clone(), repos.warehouses.clone(), repos.uow.clone())
+            .with_cache_invalidator(invalidator),
+    );
+    match command {
+        CatalogCommand::Export { output } => {
+            let exported = catalog.export().await?;
+            let document = serde_json::to_string_pretty(&exported)?;
+            let Some(path) = output else {
+                // the export itself is the output
+                return Ok(Report::new(serde_json::to_value(&exported)?, document));
+            };
+            std::fs::write(&path, document + "\n")?;
+            let products: usize = exported.categories.iter().map(|c| c.products.len()).sum();
+            Ok(Report::new(
+                json!({ "file": path, "categories": exported.categories.len(), "products": products }),
+                format!(
+                    "exported {} categories and {products} products to {}",
+                    exported.categories.len(),
+                    path.display()
+                ),
+            ))
+        }
+        CatalogCommand::Import { file } => {
+            let summary = catalog.import(read_catalog(&file)?).await?;
+            Ok(Report::new(
+                serde_json::to_value(&summary)?,
+                format!(
+                    "created {} categories and {} products, skipped {} existing products",
+                    summary.categories_created, summary.products_created, summary.products_skipped
+                ),
+            ))
+        }
+    }
+}
+
+fn read_catalog(file: &Path) -> Result<Catalog, Box<dyn Error>> {
+    let document = if file == Path::new("-") {
+        let mut document = String::new();
+        io::stdin().read_to_string(&mut document)?;
+        document
+    } else {
+        std::fs::read_to_string(file)?
+    };
+    serde_json::from_str(&document).map_err(|e| format!("{}: {e}", file.display()).into())
+}
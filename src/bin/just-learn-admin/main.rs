@@ -0,0 +1,140 @@
+//! `just-learn-admin`: operational tasks on the same database and services
+//! as the server. Reads `DATABASE_URL` (and `.env`) like the server does.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use dotenvy::dotenv;
+use serde_json::Value;
+
+mod commands;
+mod config;
+
+#[derive(Parser)]
+#[command(name = "just-learn-admin", version, about = "Operational tasks for rust-just-learn")]
+struct Cli {
+    /// Print JSON instead of text, for scripts
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage users
+    #[command(subcommand)]
+    User(UserCommand),
+    /// Apply or inspect database migrations
+    #[command(subcommand)]
+    Migrate(MigrateCommand),
+    /// Manage keys accepted in `X-Api-Key`
+    #[command(subcommand)]
+    ApiKey(ApiKeyCommand),
+    /// Move categories and products in and out as JSON
+    #[command(subcommand)]
+    Catalog(CatalogCommand),
+    /// Inspect configuration
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Subcommand)]
+enum UserCommand {
+    /// Create the first admin user; refused once an admin exists
+    CreateAdmin {
+        username: String,
+        /// Read from the first line of stdin when omitted
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Set a new password for a user
+    ResetPassword {
+        username: String,
+        /// Read from the first line of stdin when omitted
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// List users
+    List,
+}
+
+#[derive(Subcommand)]
+enum MigrateCommand {
+    /// Apply pending migrations
+    Run,
+    /// Show applied and pending migrations
+    Status,
+}
+
+#[derive(Subcommand)]
+enum ApiKeyCommand {
+    /// Issue a key; it is printed once and cannot be shown again
+    Issue { name: String },
+    /// List keys (without the secret part)
+    List,
+    /// Revoke a key by id
+    Revoke { id: i64 },
+}
+
+#[derive(Subcommand)]
+enum CatalogCommand {
+    /// Write all categories and products as JSON
+    Export {
+        /// File to write instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Create the categories and products of a JSON export that are missing
+    Import {
+        /// Export file, or `-` for stdin
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Check settings, database connectivity and migrations
+    Check,
+}
+
+/// A command's result in both forms; only one is printed.
+pub struct Report {
+    pub json: Value,
+    pub text: String,
+    /// Exit non-zero even though the command ran, e.g. failed checks.
+    pub failed: bool,
+}
+
+impl Report {
+    pub fn new(json: Value, text: impl Into<String>) -> Self {
+        Self { json, text: text.into(), failed: false }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    dotenv().ok();
+    let cli = Cli::parse();
+
+    match commands::run(cli.command).await {
+        Ok(report) => {
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&report.json).unwrap_or_default());
+            } else if !report.text.is_empty() {
+                println!("{}", report.text.trim_end());
+            }
+            if report.failed { ExitCode::FAILURE } else { ExitCode::SUCCESS }
+        }
+        Err(e) => {
+            if cli.json {
+                println!("{}", serde_json::json!({ "error": e.to_string() }));
+            } else {
+                eprintln!("error: {e}");
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! Server settings from the environment, read once at startup by the
+//! `rust-just-learn` binary and checked by `just-learn-admin config check`.
+//!
+//! `DATABASE_URL`, `MAX_CONNECTION` and `LISTEN_PORT` are required; every
+//! other variable is optional and falls back to the [`AppBuilder`] default
+//! when unset or empty. A value that is set but does not parse, or is out of
+//! range, is an error, never a silent default. Outbox sinks, the payment
+//! provider, telemetry and log redaction read their own variables.
+
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::domain::warehouse::AllocationStrategy;
+use crate::usecases::job_worker::parse_queues;
+use crate::AppBuilder;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub database_url: String,
+    /// `MAX_CONNECTION`: size of the database pool.
+    pub max_connections: u32,
+    pub listen_port: u16,
+    /// `JOB_QUEUES`, `queue:concurrency` pairs; default `default:4`.
+    pub job_queues: Vec<(String, usize)>,
+    /// `OUTBOX_POLL_MS`
+    pub outbox_poll_interval: Option<Duration>,
+    /// `OUTBOX_BATCH_SIZE`
+    pub outbox_batch_size: Option<i64>,
+    /// `JOB_POLL_MS`
+    pub job_poll_interval: Option<Duration>,
+    /// `JOB_RETENTION_DAYS`
+    pub job_retention_days: Option<i64>,
+    /// `CART_IDLE_DAYS`
+    pub cart_idle_days: Option<i64>,
+    /// `RESERVATION_MINUTES`
+    pub reservation_minutes: Option<i64>,
+    /// `SHIPPING_FEE`
+    pub shipping_fee: Option<f64>,
+    /// `ALLOCATION_STRATEGY`: `priority` or `nearest`.
+    pub allocation_strategy: Option<AllocationStrategy>,
+    /// Size and time to live of the category and product cache, from
+    /// `CACHE_MAX_ENTRIES` (default 1000) and `CACHE_TTL_SECS` (default 60);
+    /// `None` when either is 0.
+    pub cache: Option<(usize, Duration)>,
+}
+
+/// One variable that is missing or does not hold a valid value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidVar {
+    pub name: &'static str,
+    pub problem: String,
+}
+
+/// Every [`InvalidVar`] found, in the order the variables are read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError(pub Vec<InvalidVar>);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, var) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{} {}", var.name, var.problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_vars(|name| env::var(name).ok())
+    }
+
+    /// Read the settings from `var`, which returns a variable's value.
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        let mut vars = Vars { var: &var, invalid: Vec::new() };
+
+        let database_url = vars.required("DATABASE_URL", |v| Ok(v.to_string()));
+        let max_connections = vars.required("MAX_CONNECTION", positive::<u32>);
+        let listen_port = vars.required("LISTEN_PORT", parsed::<u16>);
+        let job_queues = vars.optional("JOB_QUEUES", parse_queues).unwrap_or_else(|| vec![("default".into(), 4)]);
+        let outbox_poll_interval = vars.optional("OUTBOX_POLL_MS", positive::<u64>).map(Duration::from_millis);
+        let outbox_batch_size = vars.optional("OUTBOX_BATCH_SIZE", positive::<i64>);
+        let job_poll_interval = vars.optional("JOB_POLL_MS", positive::<u64>).map(Duration::from_millis);
+        let job_retention_days = vars.optional("JOB_RETENTION_DAYS", positive::<i64>);
+        let cart_idle_days = vars.optional("CART_IDLE_DAYS", positive::<i64>);
+        let reservation_minutes = vars.optional("RESERVATION_MINUTES", positive::<i64>);
+        let shipping_fee = vars.optional("SHIPPING_FEE", |v| {
+            let fee = parsed::<f64>(v)?;
+            if fee.is_finite() && fee >= 0.0 { Ok(fee) } else { Err("must be zero or more".into()) }
+        });
+        let allocation_strategy = vars.optional("ALLOCATION_STRATEGY", parsed::<AllocationStrategy>);
+        let cache_entries = vars.optional("CACHE_MAX_ENTRIES", parsed::<usize>).unwrap_or(1000);
+        let cache_ttl = vars.optional("CACHE_TTL_SECS", parsed::<u64>).unwrap_or(60);
+
+        if !vars.invalid.is_empty() {
+            return Err(ConfigError(vars.invalid));
+        }
+        // the required ones are all there when nothing was invalid
+        Ok(Config {
+            database_url: database_url.unwrap_or_default(),
+            max_connections: max_connections.unwrap_or_default(),
+            listen_port: listen_port.unwrap_or_default(),
+            job_queues,
+            outbox_poll_interval,
+            outbox_batch_size,
+            job_poll_interval,
+            job_retention_days,
+            cart_idle_days,
+            reservation_minutes,
+            shipping_fee,
+            allocation_strategy,
+            cache: (cache_entries > 0 && cache_ttl > 0).then(|| (cache_entries, Duration::from_secs(cache_ttl))),
+        })
+    }
+
+    /// `0.0.0.0:<LISTEN_PORT>`
+    pub fn listen_address(&self) -> String {
+        format!("0.0.0.0:{}", self.listen_port)
+    }
+
+    /// Apply the settings that have a builder counterpart; the repositories
+    /// and the cache depend on the backend and are left to the caller.
+    pub fn configure(&self, mut builder: AppBuilder) -> AppBuilder {
+        builder = builder.job_queues(self.job_queues.clone());
+        if let Some(interval) = self.outbox_poll_interval {
+            builder = builder.outbox_poll_interval(interval);
+        }
+        if let Some(size) = self.outbox_batch_size {
+            builder = builder.outbox_batch_size(size);
+        }
+        if let Some(interval) = self.job_poll_interval {
+            builder = builder.job_poll_interval(interval);
+        }
+        if let Some(days) = self.job_retention_days {
+            builder = builder.job_retention_days(days);
+        }
+        if let Some(days) = self.cart_idle_days {
+            builder = builder.cart_idle_days(days);
+        }
+        if let Some(minutes) = self.reservation_minutes {
+            builder = builder.reservation_minutes(minutes);
+        }
+        if let Some(fee) = self.shipping_fee {
+            builder = builder.shipping_fee(fee);
+        }
+        if let Some(strategy) = self.allocation_strategy {
+            builder = builder.allocation_strategy(strategy);
+        }
+        builder
+    }
+}
+
+/// Reads variables, collecting what is wrong with them.
+struct Vars<'a> {
+    var: &'a dyn Fn(&str) -> Option<String>,
+    invalid: Vec<InvalidVar>,
+}
+
+impl Vars<'_> {
+    fn optional<T>(&mut self, name: &'static str, parse: impl Fn(&str) -> Result<T, String>) -> Option<T> {
+        let value = (self.var)(name)?;
+        let value = value.trim();
+        if value.is_empty() {
+            return None;
+        }
+        match parse(value) {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                self.invalid.push(InvalidVar { name, problem: format!("is invalid ({value:?}): {e}") });
+                None
+            }
+        }
+    }
+
+    fn required<T>(&mut self, name: &'static str, parse: impl Fn(&str) -> Result<T, String>) -> Option<T> {
+        let before = self.invalid.len();
+        let parsed = self.optional(name, parse);
+        if parsed.is_none() && self.invalid.len() == before {
+            self.invalid.push(InvalidVar { name, problem: "must be set".into() });
+        }
+        parsed
+    }
+}
+
+fn parsed<T: FromStr>(value: &str) -> Result<T, String>
+where
+    T::Err: fmt::Display,
+{
+    value.parse().map_err(|e: T::Err| e.to_string())
+}
+
+fn positive<T: FromStr + PartialOrd + Default>(value: &str) -> Result<T, String>
+where
+    T::Err: fmt::Display,
+{
+    let n = parsed::<T>(value)?;
+    if n > T::default() { Ok(n) } else { Err("must be greater than 0".into()) }
+}
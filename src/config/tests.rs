@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::domain::warehouse::AllocationStrategy;
+
+use super::{Config, ConfigError, InvalidVar};
+
+const REQUIRED: [(&str, &str); 3] = [("DATABASE_URL", "memory://"), ("MAX_CONNECTION", "5"), ("LISTEN_PORT", "8080")];
+
+fn config(vars: &[(&str, &str)]) -> Result<Config, ConfigError> {
+    let vars: HashMap<String, String> = REQUIRED.iter().chain(vars).map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    Config::from_vars(|name| vars.get(name).cloned())
+}
+
+fn invalid(vars: &[(&str, &str)]) -> Vec<(&'static str, String)> {
+    config(vars).unwrap_err().0.into_iter().map(|InvalidVar { name, problem }| (name, problem)).collect()
+}
+
+#[test]
+fn defaults_leave_the_builder_settings_alone() {
+    let config = config(&[]).unwrap();
+    assert_eq!(config.database_url, "memory://");
+    assert_eq!((config.max_connections, config.listen_address()), (5, "0.0.0.0:8080".to_string()));
+    assert_eq!(config.job_queues, [("default".to_string(), 4)]);
+    assert_eq!((config.outbox_poll_interval, config.shipping_fee, config.allocation_strategy), (None, None, None));
+    assert_eq!(config.cache, Some((1000, Duration::from_secs(60))));
+}
+
+#[test]
+fn set_values_are_parsed() {
+    let config = config(&[
+        ("JOB_QUEUES", "default:2,imports:1"),
+        ("OUTBOX_POLL_MS", "250"),
+        ("OUTBOX_BATCH_SIZE", "50"),
+        ("JOB_POLL_MS", " 100 "),
+        ("JOB_RETENTION_DAYS", "3"),
+        ("CART_IDLE_DAYS", "7"),
+        ("RESERVATION_MINUTES", "20"),
+        ("SHIPPING_FEE", "4.95"),
+        ("ALLOCATION_STRATEGY", "nearest"),
+        ("CACHE_MAX_ENTRIES", "10"),
+        ("CACHE_TTL_SECS", "5"),
+    ])
+    .unwrap();
+    assert_eq!(config.job_queues, [("default".to_string(), 2), ("imports".to_string(), 1)]);
+    assert_eq!((config.outbox_poll_interval, config.outbox_batch_size), (Some(Duration::from_millis(250)), Some(50)));
+    assert_eq!((config.job_poll_interval, config.job_retention_days), (Some(Duration::from_millis(100)), Some(3)));
+    assert_eq!((config.cart_idle_days, config.reservation_minutes), (Some(7), Some(20)));
+    assert_eq!((config.shipping_fee, config.allocation_strategy), (Some(4.95), Some(AllocationStrategy::Nearest)));
+    assert_eq!(config.cache, Some((10, Duration::from_secs(5))));
+
+    assert_eq!(self::config(&[("CACHE_TTL_SECS", "0")]).unwrap().cache, None);
+    assert_eq!(self::config(&[("SHIPPING_FEE", "")]).unwrap().shipping_fee, None);
+}
+
+#[test]
+fn bad_values_are_errors_not_defaults() {
+    let problems = invalid(&[
+        ("OUTBOX_POLL_MS", "fast"),
+        ("CART_IDLE_DAYS", "0"),
+        ("RESERVATION_MINUTES", "-5"),
+        ("SHIPPING_FEE", "-1"),
+        ("ALLOCATION_STRATEGY", "random"),
+        ("CACHE_MAX_ENTRIES", "lots"),
+    ]);
+    let names: Vec<_> = problems.iter().map(|(name, _)| *name).collect();
+    assert_eq!(
+        names,
+        ["OUTBOX_POLL_MS", "CART_IDLE_DAYS", "RESERVATION_MINUTES", "SHIPPING_FEE", "ALLOCATION_STRATEGY", "CACHE_MAX_ENTRIES"]
+    );
+    assert_eq!(problems[0].1, r#"is invalid ("fast"): invalid digit found in string"#);
+    assert_eq!(problems[1].1, r#"is invalid ("0"): must be greater than 0"#);
+    assert_eq!(problems[3].1, r#"is invalid ("-1"): must be zero or more"#);
+    assert_eq!(problems[4].1, r#"is invalid ("random"): invalid allocation strategy "random""#);
+
+    assert_eq!(invalid(&[("JOB_QUEUES", "default:x")])[0].0, "JOB_QUEUES");
+}
+
+#[test]
+fn required_values_must_be_set() {
+    let error = Config::from_vars(|name| (name == "LISTEN_PORT").then(|| "http".to_string())).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        r#"DATABASE_URL must be set; MAX_CONNECTION must be set; LISTEN_PORT is invalid ("http"): invalid digit found in string"#
+    );
+}
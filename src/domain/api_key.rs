@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::domain::DomainError;
+
+/// An issued API key; the key itself is only shown once, at issue time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiKey {
+    pub id: i64,
+    pub name: String,
+    /// Public part of the key, used to look it up.
+    pub prefix: String,
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[async_trait]
+pub trait ApiKeyRepository: Send + Sync {
+    async fn create(&self, name: String, prefix: String, key_hash: String) -> Result<i64, DomainError>;
+    async fn get_all(&self) -> Result<Vec<ApiKey>, DomainError>;
+    /// Hash of the unrevoked key with `prefix`.
+    async fn active_hash(&self, prefix: &str) -> Result<Option<String>, DomainError>;
+    /// `false` when there is no such key or it was already revoked.
+    async fn revoke(&self, id: i64) -> Result<bool, DomainError>;
+}
@@ -0,0 +1,54 @@
+use std::fmt;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+
+/// A cached read made stale by a write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Invalidation {
+    /// The category and the category list.
+    Category(i64),
+    Product(i64),
+}
+
+/// `category:<id>` / `product:<id>`, the form sent between replicas.
+impl fmt::Display for Invalidation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Invalidation::Category(id) => write!(f, "category:{id}"),
+            Invalidation::Product(id) => write!(f, "product:{id}"),
+        }
+    }
+}
+
+impl FromStr for Invalidation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid cache invalidation {s:?}");
+        let (kind, id) = s.split_once(':').ok_or_else(invalid)?;
+        let id = id.parse().map_err(|_| invalid())?;
+        match kind {
+            "category" => Ok(Invalidation::Category(id)),
+            "product" => Ok(Invalidation::Product(id)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Told by the services about every committed write, so caches in front of
+/// the repositories drop what it changed.
+#[async_trait]
+pub trait CacheInvalidator: Send + Sync {
+    /// Failures are logged, not returned: the write already committed and
+    /// entries expire on their own.
+    async fn invalidate(&self, invalidation: Invalidation);
+}
+
+/// For services without a cache in front of their repositories.
+pub struct NoCache;
+
+#[async_trait]
+impl CacheInvalidator for NoCache {
+    async fn invalidate(&self, _invalidation: Invalidation) {}
+}
@@ -0,0 +1,88 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::domain::promotion::Pricing;
+use crate::domain::DomainError;
+
+/// Whose cart: a signed-in user's, or a guest's, found by the token handed
+/// out when the cart was created.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CartOwner {
+    User(i64),
+    Guest(String),
+}
+
+impl CartOwner {
+    /// The signed-in user, if it is one.
+    pub fn user_id(&self) -> Option<i64> {
+        match self {
+            CartOwner::User(id) => Some(*id),
+            CartOwner::Guest(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CartLine {
+    pub product_id: i64,
+    pub quantity: i32,
+    /// Price the shopper last saw; re-priced from the product on every read.
+    pub unit_price: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cart {
+    pub id: i64,
+    pub owner: CartOwner,
+    /// In the order they were first added.
+    pub lines: Vec<CartLine>,
+    /// The coupon code the shopper entered, upper case.
+    pub coupon: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Cart {
+    pub fn line(&self, product_id: i64) -> Option<&CartLine> {
+        self.lines.iter().find(|l| l.product_id == product_id)
+    }
+}
+
+/// A cart line priced from the current product.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PricedLine {
+    pub product_id: i64,
+    pub name: String,
+    pub quantity: i32,
+    pub unit_price: f64,
+    /// `unit_price` differs from what the shopper saw last time.
+    pub price_changed: bool,
+    /// The product is active and has `quantity` in stock.
+    pub available: bool,
+}
+
+/// What the shopper sees: every line re-priced, totalled over the
+/// available ones with the promotions that apply.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PricedCart {
+    pub owner: CartOwner,
+    pub lines: Vec<PricedLine>,
+    pub coupon: Option<String>,
+    pub pricing: Pricing,
+}
+
+/// Every write also counts as activity, pushing back the cart's expiry.
+#[async_trait]
+pub trait CartRepository: Send + Sync {
+    async fn find(&self, owner: &CartOwner) -> Result<Option<Cart>, DomainError>;
+    async fn create(&self, owner: &CartOwner) -> Result<i64, DomainError>;
+    /// Add the line, or replace the one for the same product in place.
+    async fn set_line(&self, cart_id: i64, line: CartLine) -> Result<(), DomainError>;
+    /// `false` when the cart has no line for `product_id`.
+    async fn remove_line(&self, cart_id: i64, product_id: i64) -> Result<bool, DomainError>;
+    /// Enter a coupon code, replacing any earlier one, or take it out with
+    /// `None`.
+    async fn set_coupon(&self, cart_id: i64, coupon: Option<String>) -> Result<(), DomainError>;
+    async fn delete(&self, cart_id: i64) -> Result<(), DomainError>;
+    /// Delete carts not written to since `before`; returns how many.
+    async fn delete_idle(&self, before: DateTime<Utc>) -> Result<u64, DomainError>;
+}
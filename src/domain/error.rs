@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::domain::order::IllegalTransition;
+
 #[derive(Debug, Error)]
 pub enum DomainError {
     #[error("validation error: {0}")]
@@ -8,6 +10,15 @@ pub enum DomainError {
     NotFound,
     #[error("unauthorized")]
     Unauthorized,
+    /// Concurrent update won (serialization failure, deadlock); safe to retry.
+    #[error("conflict: {0}")]
+    Conflict(String),
+    /// Not a retryable race: the order is in the wrong status for the move.
+    #[error(transparent)]
+    IllegalTransition(#[from] IllegalTransition),
+    /// The payment provider turned the payment down; paying again may work.
+    #[error("payment declined: {0}")]
+    PaymentDeclined(String),
     #[error("unexpected error: {0}")]
     Unexpected(String),
 }
@@ -0,0 +1,174 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::inventory::MovementKind;
+use crate::domain::order::OrderStatus;
+use crate::domain::product::Product;
+use crate::domain::purchasing::PurchaseOrderStatus;
+use crate::domain::DomainError;
+
+/// Something that happened to an aggregate, raised by the `usecases`
+/// services and stored in the outbox in the same transaction as the change.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum DomainEvent {
+    UserCreated { id: i64, username: String },
+    UserUpdated { id: i64, username: String },
+    UserDeleted { id: i64 },
+    CategoryCreated { id: i64, name: String },
+    CategoryRenamed { id: i64, old_name: String, new_name: String },
+    CategoryDeleted { id: i64 },
+    ProductCreated {
+        id: i64,
+        name: String,
+        price: f64,
+        stock: i32,
+        category_id: i64,
+    },
+    /// Stock received or corrected by hand; sales and returns are told by
+    /// the order events.
+    StockAdjusted { product_id: i64, warehouse_id: i64, kind: MovementKind, quantity: i32, stock: i32 },
+    /// Stock moved between warehouses; the product's total stays the same.
+    StockTransferred { product_id: i64, from_warehouse_id: i64, to_warehouse_id: i64, quantity: i32 },
+    /// Stock fell below the product's reorder threshold.
+    LowStock { product_id: i64, stock: i32, threshold: i32 },
+    OrderPlaced { id: i64, user_id: i64, total: f64 },
+    OrderStatusChanged { id: i64, from: OrderStatus, to: OrderStatus },
+    PaymentCaptured { id: i64, order_id: i64, amount: f64 },
+    PaymentFailed { id: i64, order_id: i64, reason: String },
+    PaymentRefunded { id: i64, order_id: i64, amount: f64 },
+    /// Money was captured for an order that can no longer be paid and
+    /// giving it back failed; somebody has to sort it out with the provider.
+    PaymentNeedsAttention { id: i64, order_id: i64, reason: String },
+    PurchaseOrderStatusChanged { id: i64, from: PurchaseOrderStatus, to: PurchaseOrderStatus },
+}
+
+impl DomainEvent {
+    pub fn product_created(product: &Product) -> Self {
+        DomainEvent::ProductCreated {
+            id: product.id,
+            name: product.name.clone(),
+            price: product.price,
+            stock: product.stock,
+            category_id: product.category_id,
+        }
+    }
+
+    /// Variant name, e.g. `CategoryRenamed`.
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            DomainEvent::UserCreated { .. } => "UserCreated",
+            DomainEvent::UserUpdated { .. } => "UserUpdated",
+            DomainEvent::UserDeleted { .. } => "UserDeleted",
+            DomainEvent::CategoryCreated { .. } => "CategoryCreated",
+            DomainEvent::CategoryRenamed { .. } => "CategoryRenamed",
+            DomainEvent::CategoryDeleted { .. } => "CategoryDeleted",
+            DomainEvent::ProductCreated { .. } => "ProductCreated",
+            DomainEvent::StockAdjusted { .. } => "StockAdjusted",
+            DomainEvent::StockTransferred { .. } => "StockTransferred",
+            DomainEvent::LowStock { .. } => "LowStock",
+            DomainEvent::OrderPlaced { .. } => "OrderPlaced",
+            DomainEvent::OrderStatusChanged { .. } => "OrderStatusChanged",
+            DomainEvent::PaymentCaptured { .. } => "PaymentCaptured",
+            DomainEvent::PaymentFailed { .. } => "PaymentFailed",
+            DomainEvent::PaymentRefunded { .. } => "PaymentRefunded",
+            DomainEvent::PaymentNeedsAttention { .. } => "PaymentNeedsAttention",
+            DomainEvent::PurchaseOrderStatusChanged { .. } => "PurchaseOrderStatusChanged",
+        }
+    }
+
+    /// Kind of aggregate the event belongs to; events are delivered in order
+    /// per (`aggregate_type`, `aggregate_id`).
+    pub fn aggregate_type(&self) -> &'static str {
+        match self {
+            DomainEvent::UserCreated { .. }
+            | DomainEvent::UserUpdated { .. }
+            | DomainEvent::UserDeleted { .. } => "user",
+            DomainEvent::CategoryCreated { .. }
+            | DomainEvent::CategoryRenamed { .. }
+            | DomainEvent::CategoryDeleted { .. } => "category",
+            DomainEvent::ProductCreated { .. }
+            | DomainEvent::StockAdjusted { .. }
+            | DomainEvent::StockTransferred { .. }
+            | DomainEvent::LowStock { .. } => "product",
+            DomainEvent::OrderPlaced { .. } | DomainEvent::OrderStatusChanged { .. } => "order",
+            DomainEvent::PaymentCaptured { .. }
+            | DomainEvent::PaymentFailed { .. }
+            | DomainEvent::PaymentRefunded { .. }
+            | DomainEvent::PaymentNeedsAttention { .. } => "payment",
+            DomainEvent::PurchaseOrderStatusChanged { .. } => "purchase_order",
+        }
+    }
+
+    pub fn aggregate_id(&self) -> i64 {
+        match self {
+            DomainEvent::UserCreated { id, .. }
+            | DomainEvent::UserUpdated { id, .. }
+            | DomainEvent::UserDeleted { id }
+            | DomainEvent::CategoryCreated { id, .. }
+            | DomainEvent::CategoryRenamed { id, .. }
+            | DomainEvent::CategoryDeleted { id }
+            | DomainEvent::ProductCreated { id, .. }
+            | DomainEvent::OrderPlaced { id, .. }
+            | DomainEvent::OrderStatusChanged { id, .. }
+            | DomainEvent::PaymentCaptured { id, .. }
+            | DomainEvent::PaymentFailed { id, .. }
+            | DomainEvent::PaymentRefunded { id, .. }
+            | DomainEvent::PaymentNeedsAttention { id, .. }
+            | DomainEvent::PurchaseOrderStatusChanged { id, .. } => *id,
+            DomainEvent::StockAdjusted { product_id, .. }
+            | DomainEvent::StockTransferred { product_id, .. }
+            | DomainEvent::LowStock { product_id, .. } => *product_id,
+        }
+    }
+
+    /// The event's fields, without the type tag.
+    pub fn payload(&self) -> Result<serde_json::Value, DomainError> {
+        let mut tagged = serde_json::to_value(self).map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        Ok(tagged.get_mut("data").map(serde_json::Value::take).unwrap_or_default())
+    }
+}
+
+/// A stored event waiting to be (or already) published.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OutboxMessage {
+    /// Increasing; doubles as the idempotency key for consumers.
+    pub id: i64,
+    pub aggregate_type: String,
+    pub aggregate_id: i64,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    /// W3C `traceparent` of the request that raised the event.
+    #[serde(skip)]
+    pub trace_context: Option<String>,
+    /// Failed deliveries so far.
+    #[serde(skip)]
+    pub attempts: i32,
+}
+
+#[async_trait]
+pub trait OutboxRepository: Send + Sync {
+    /// Store `event`; only meaningful inside a unit of work.
+    async fn append(&self, event: &DomainEvent) -> Result<i64, DomainError>;
+    /// Become the only relay until the transaction ends. `false` when another
+    /// relay already holds it.
+    async fn lock_relay(&self) -> Result<bool, DomainError>;
+    /// Oldest unpublished messages that are due first: not given up on, not
+    /// waiting for a retry, and not behind one of the same aggregate that is.
+    async fn pending(&self, limit: i64) -> Result<Vec<OutboxMessage>, DomainError>;
+    /// Keep messages `ids` out of [`pending`](Self::pending) until `until`,
+    /// like a retry, or let them back in when `None`.
+    async fn hold(&self, ids: &[i64], until: Option<DateTime<Utc>>) -> Result<(), DomainError>;
+    async fn mark_published(&self, id: i64) -> Result<(), DomainError>;
+    /// Try again from `retry_at`, or give up (dead letter) when `None`.
+    async fn mark_failed(&self, id: i64, error: String, retry_at: Option<DateTime<Utc>>) -> Result<(), DomainError>;
+}
+
+/// Destination for published events (in-process bus, webhook, broker).
+#[async_trait]
+pub trait EventSink: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// Must only return `Ok` once the sink has accepted the message.
+    async fn publish(&self, message: &OutboxMessage) -> Result<(), DomainError>;
+}
@@ -0,0 +1,144 @@
+use std::fmt;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::DomainError;
+
+/// Why a product's stock changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MovementKind {
+    /// Goods arrived.
+    Receipt,
+    /// Taken by a checkout.
+    Sale,
+    /// Put back by a cancelled or refunded order.
+    Return,
+    /// Correction after a count, damage, loss and the like.
+    Adjustment,
+    /// Held for a customer.
+    Reservation,
+    /// Moved between warehouses: one movement out of the source, one into
+    /// the destination.
+    Transfer,
+}
+
+impl MovementKind {
+    pub const ALL: [MovementKind; 6] = [
+        MovementKind::Receipt,
+        MovementKind::Sale,
+        MovementKind::Return,
+        MovementKind::Adjustment,
+        MovementKind::Reservation,
+        MovementKind::Transfer,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MovementKind::Receipt => "receipt",
+            MovementKind::Sale => "sale",
+            MovementKind::Return => "return",
+            MovementKind::Adjustment => "adjustment",
+            MovementKind::Reservation => "reservation",
+            MovementKind::Transfer => "transfer",
+        }
+    }
+
+    /// Whether an admin may post it by hand; transfers have their own
+    /// call and the others are recorded by orders.
+    pub fn is_manual(self) -> bool {
+        matches!(self, MovementKind::Receipt | MovementKind::Adjustment)
+    }
+}
+
+impl fmt::Display for MovementKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for MovementKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MovementKind::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| format!("invalid stock movement kind {s:?}"))
+    }
+}
+
+/// One line of the stock ledger. A product's stock is the sum of its
+/// movements' quantities, and its stock in a warehouse the sum of the
+/// movements there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StockMovement {
+    pub id: i64,
+    pub product_id: i64,
+    pub warehouse_id: i64,
+    pub kind: MovementKind,
+    /// Signed: positive adds to the stock, negative takes from it.
+    pub quantity: i32,
+    pub reason: Option<String>,
+    /// The user behind it; `None` for the system or once their account is
+    /// deleted.
+    pub actor_id: Option<i64>,
+    /// The order behind a sale or return.
+    pub order_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A product's stock next to what its ledger adds up to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StockLevel {
+    pub product_id: i64,
+    pub name: String,
+    pub stock: i32,
+    pub ledger: i64,
+}
+
+impl StockLevel {
+    pub fn difference(&self) -> i64 {
+        i64::from(self.stock) - self.ledger
+    }
+}
+
+/// A product whose stock is below its reorder threshold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LowStockProduct {
+    pub product_id: i64,
+    pub name: String,
+    pub category_id: i64,
+    pub category: String,
+    pub stock: i32,
+    pub threshold: i32,
+}
+
+#[async_trait]
+pub trait InventoryRepository: Send + Sync {
+    /// Apply `movement.quantity` to the product's stock in the warehouse and
+    /// in total, and append the movement to its ledger, unless that would
+    /// leave the warehouse's stock negative; `None` then, or when there is
+    /// no such product. `id` and `created_at` are assigned. The writes land
+    /// together inside a unit of work.
+    async fn record(&self, movement: StockMovement) -> Result<Option<StockMovement>, DomainError>;
+    /// Oldest first.
+    async fn movements(&self, product_id: i64) -> Result<Vec<StockMovement>, DomainError>;
+    /// The movements booked for the order, oldest first.
+    async fn order_movements(&self, order_id: i64) -> Result<Vec<StockMovement>, DomainError>;
+    /// Every product's stock and ledger total, by product id.
+    async fn stock_levels(&self) -> Result<Vec<StockLevel>, DomainError>;
+    /// Set the product's reorder threshold, or clear it with `None`. `false`
+    /// when there is no such product.
+    async fn set_threshold(&self, product_id: i64, threshold: Option<i32>) -> Result<bool, DomainError>;
+    async fn threshold(&self, product_id: i64) -> Result<Option<i32>, DomainError>;
+    /// Products below their threshold, by category name, then product id.
+    async fn low_stock(&self) -> Result<Vec<LowStockProduct>, DomainError>;
+    /// Weighted average cost of the product's stock; `None` until it is
+    /// first received against a purchase order.
+    async fn average_cost(&self, product_id: i64) -> Result<Option<f64>, DomainError>;
+    async fn set_average_cost(&self, product_id: i64, cost: f64) -> Result<(), DomainError>;
+}
@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::domain::DomainError;
+
+/// A typed background job; the value itself is the stored payload.
+pub trait JobPayload: Serialize + DeserializeOwned + Send + 'static {
+    /// Stable name stored with the job and used to find its handler.
+    const KIND: &'static str;
+    const QUEUE: &'static str = "default";
+    const MAX_ATTEMPTS: i32 = 5;
+}
+
+/// A job to enqueue, built from a [`JobPayload`] with [`NewJob::of`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewJob {
+    pub queue: String,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub run_at: DateTime<Utc>,
+    pub max_attempts: i32,
+    /// Enqueueing a second job with the same key is a no-op.
+    pub unique_key: Option<String>,
+}
+
+impl NewJob {
+    /// Due now, on `J::QUEUE`.
+    pub fn of<J: JobPayload>(job: &J) -> Result<Self, DomainError> {
+        Ok(NewJob {
+            queue: J::QUEUE.to_string(),
+            kind: J::KIND.to_string(),
+            payload: serde_json::to_value(job).map_err(|e| DomainError::Unexpected(e.to_string()))?,
+            run_at: Utc::now(),
+            max_attempts: J::MAX_ATTEMPTS,
+            unique_key: None,
+        })
+    }
+
+    pub fn run_at(mut self, run_at: DateTime<Utc>) -> Self {
+        self.run_at = run_at;
+        self
+    }
+
+    pub fn unique_key(mut self, key: impl Into<String>) -> Self {
+        self.unique_key = Some(key.into());
+        self
+    }
+}
+
+/// A job claimed by a worker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Job {
+    pub id: i64,
+    pub queue: String,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    /// Including this one; also fences `complete`/`fail` against a worker
+    /// whose lease expired and whose job was claimed again.
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: DateTime<Utc>,
+    /// W3C `traceparent` of the code that enqueued the job.
+    pub trace_context: Option<String>,
+}
+
+#[async_trait]
+pub trait JobQueue: Send + Sync {
+    /// `None` when a job with the same `unique_key` already exists.
+    async fn enqueue(&self, job: NewJob) -> Result<Option<i64>, DomainError>;
+    /// Lease up to `limit` due jobs of `queue` for `lease`, oldest `run_at`
+    /// first. Jobs whose lease ran out are due again.
+    async fn claim(&self, queue: &str, limit: i64, lease: Duration) -> Result<Vec<Job>, DomainError>;
+    async fn complete(&self, job: &Job) -> Result<(), DomainError>;
+    /// Run again at `retry_at`, or give up (`dead`) when `None`.
+    async fn fail(&self, job: &Job, error: String, retry_at: Option<DateTime<Utc>>) -> Result<(), DomainError>;
+    /// Delete completed and dead jobs that finished before `before`.
+    async fn prune_finished(&self, before: DateTime<Utc>) -> Result<u64, DomainError>;
+}
@@ -1,6 +1,23 @@
+pub mod api_key;
+pub mod cache;
+pub mod cart;
 pub mod error;
+pub mod event;
+pub mod inventory;
+pub mod job;
+pub mod notification;
+pub mod order;
+pub mod page;
+pub mod payment;
+pub mod reservation;
 pub mod user;
 pub mod category;
 pub mod product;
+pub mod promotion;
+pub mod purchasing;
+pub mod secret;
+pub mod uow;
+pub mod warehouse;
 
-pub use error::DomainError;
\ No newline at end of file
+pub use error::DomainError;
+pub use secret::Secret;
\ No newline at end of file
@@ -0,0 +1,28 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::domain::DomainError;
+
+/// An entry in the admins' in-app notification list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub id: i64,
+    /// What it is about, e.g. `low_stock`.
+    pub kind: String,
+    pub message: String,
+    pub product_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    /// `None` until someone marks it read.
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+#[async_trait]
+pub trait NotificationRepository: Send + Sync {
+    /// `id`, `created_at` and `read_at` are assigned.
+    async fn create(&self, notification: Notification) -> Result<Notification, DomainError>;
+    /// Newest first; only the unread ones if `unread_only`.
+    async fn list(&self, unread_only: bool) -> Result<Vec<Notification>, DomainError>;
+    /// `false` when there is no such notification; marking it again keeps
+    /// the first `read_at`.
+    async fn mark_read(&self, id: i64) -> Result<bool, DomainError>;
+}
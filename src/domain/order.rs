@@ -0,0 +1,163 @@
+use std::fmt;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{page::Page, DomainError};
+
+/// Where an order is in its life:
+///
+/// ```text
+/// pending ─> paid ─> fulfilled ─> shipped ─> delivered
+///    │        │          │                       │
+///    v        └──────────┴─────> refunded <──────┘
+/// cancelled
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderStatus {
+    Pending,
+    Paid,
+    Fulfilled,
+    Shipped,
+    Delivered,
+    Cancelled,
+    Refunded,
+}
+
+impl OrderStatus {
+    pub const ALL: [OrderStatus; 7] = [
+        OrderStatus::Pending,
+        OrderStatus::Paid,
+        OrderStatus::Fulfilled,
+        OrderStatus::Shipped,
+        OrderStatus::Delivered,
+        OrderStatus::Cancelled,
+        OrderStatus::Refunded,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OrderStatus::Pending => "pending",
+            OrderStatus::Paid => "paid",
+            OrderStatus::Fulfilled => "fulfilled",
+            OrderStatus::Shipped => "shipped",
+            OrderStatus::Delivered => "delivered",
+            OrderStatus::Cancelled => "cancelled",
+            OrderStatus::Refunded => "refunded",
+        }
+    }
+
+    pub fn can_become(self, to: OrderStatus) -> bool {
+        use OrderStatus::*;
+        matches!(
+            (self, to),
+            (Pending, Paid)
+                | (Pending, Cancelled)
+                | (Paid, Fulfilled)
+                | (Paid, Refunded)
+                | (Fulfilled, Shipped)
+                | (Fulfilled, Refunded)
+                | (Shipped, Delivered)
+                | (Delivered, Refunded)
+        )
+    }
+
+    /// The status after moving to `to`, if that is a legal move.
+    pub fn transition(self, to: OrderStatus) -> Result<OrderStatus, IllegalTransition> {
+        if self.can_become(to) {
+            Ok(to)
+        } else {
+            Err(IllegalTransition { from: self, to })
+        }
+    }
+
+    /// Whether moving from here to `to` puts the goods back on the shelf:
+    /// on cancellation, and on a refund before the order left the warehouse.
+    pub fn restocks(self, to: OrderStatus) -> bool {
+        match to {
+            OrderStatus::Cancelled => true,
+            OrderStatus::Refunded => matches!(self, OrderStatus::Paid | OrderStatus::Fulfilled),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OrderStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        OrderStatus::ALL
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| format!("invalid order status {s:?}"))
+    }
+}
+
+/// A move the order state machine does not allow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("an order cannot go from {from} to {to}")]
+pub struct IllegalTransition {
+    pub from: OrderStatus,
+    pub to: OrderStatus,
+}
+
+/// A product as it was sold: name and price are copied at checkout and never
+/// follow later catalog changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderLine {
+    pub product_id: i64,
+    pub name: String,
+    pub quantity: i32,
+    pub unit_price: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Order {
+    pub id: i64,
+    /// `None` once the user's account is deleted; the order is kept.
+    pub user_id: Option<i64>,
+    /// In cart order.
+    pub lines: Vec<OrderLine>,
+    /// Taken off by promotions.
+    pub discount: f64,
+    pub shipping: f64,
+    /// What the customer pays: the lines, less `discount`, plus `shipping`.
+    pub total: f64,
+    pub status: OrderStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One step of an order's history. The first one, from no status to
+/// `pending`, is the checkout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusChange {
+    pub from: Option<OrderStatus>,
+    pub to: OrderStatus,
+    /// The user who made the change; `None` once their account is deleted.
+    pub actor_id: Option<i64>,
+    pub reason: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[async_trait]
+pub trait OrderRepository: Send + Sync {
+    /// Store the order and its lines; `id` and `created_at` are assigned.
+    async fn create(&self, order: Order) -> Result<i64, DomainError>;
+    /// Set the status and append `change` to the history; `change.changed_at`
+    /// is assigned. Legality is the caller's business.
+    async fn record_status_change(&self, id: i64, change: StatusChange) -> Result<(), DomainError>;
+    /// Oldest first.
+    async fn status_history(&self, id: i64) -> Result<Vec<StatusChange>, DomainError>;
+    async fn get(&self, id: i64) -> Result<Option<Order>, DomainError>;
+    /// Newest first.
+    async fn get_by_user_id(&self, user_id: i64, page: Page) -> Result<Vec<Order>, DomainError>;
+}
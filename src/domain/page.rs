@@ -0,0 +1,20 @@
+/// A window onto a list query: at most `limit` rows after skipping
+/// `offset`. Repositories push it into their queries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Page {
+    pub limit: Option<i64>,
+    pub offset: i64,
+}
+
+impl Page {
+    /// Every row.
+    pub const ALL: Page = Page { limit: None, offset: 0 };
+
+    /// The page of `items` this selects, for backends that hold the
+    /// whole list anyway.
+    pub fn apply<T>(self, items: impl IntoIterator<Item = T>) -> Vec<T> {
+        let offset = usize::try_from(self.offset).unwrap_or(usize::MAX);
+        let limit = self.limit.map_or(usize::MAX, |limit| usize::try_from(limit).unwrap_or(0));
+        items.into_iter().skip(offset).take(limit).collect()
+    }
+}
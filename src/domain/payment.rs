@@ -0,0 +1,209 @@
+use std::fmt;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::domain::DomainError;
+
+/// Where a payment intent is:
+///
+/// ```text
+/// pending ─> authorized ─> captured ─> refunded
+///    │            │
+///    └────────────┴─> failed
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentStatus {
+    Pending,
+    Authorized,
+    Captured,
+    Failed,
+    Refunded,
+}
+
+impl PaymentStatus {
+    pub const ALL: [PaymentStatus; 5] = [
+        PaymentStatus::Pending,
+        PaymentStatus::Authorized,
+        PaymentStatus::Captured,
+        PaymentStatus::Failed,
+        PaymentStatus::Refunded,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PaymentStatus::Pending => "pending",
+            PaymentStatus::Authorized => "authorized",
+            PaymentStatus::Captured => "captured",
+            PaymentStatus::Failed => "failed",
+            PaymentStatus::Refunded => "refunded",
+        }
+    }
+}
+
+impl fmt::Display for PaymentStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PaymentStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PaymentStatus::ALL
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| format!("invalid payment status {s:?}"))
+    }
+}
+
+/// One try at paying for an order, with one provider.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentIntent {
+    pub id: i64,
+    pub order_id: i64,
+    /// [`PaymentProvider::name`] of the provider handling it.
+    pub provider: String,
+    /// The provider's id of the authorization, once there is one.
+    pub provider_ref: Option<String>,
+    pub amount: f64,
+    pub status: PaymentStatus,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PaymentIntent {
+    /// Sent to the provider so it can tell retries of the same intent apart
+    /// from new payments.
+    pub fn reference(&self) -> String {
+        format!("intent-{}", self.id)
+    }
+}
+
+/// A call to the provider, or a webhook from it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentOperation {
+    Authorize,
+    Capture,
+    Refund,
+    Webhook,
+}
+
+impl PaymentOperation {
+    pub const ALL: [PaymentOperation; 4] =
+        [PaymentOperation::Authorize, PaymentOperation::Capture, PaymentOperation::Refund, PaymentOperation::Webhook];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PaymentOperation::Authorize => "authorize",
+            PaymentOperation::Capture => "capture",
+            PaymentOperation::Refund => "refund",
+            PaymentOperation::Webhook => "webhook",
+        }
+    }
+}
+
+impl fmt::Display for PaymentOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PaymentOperation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PaymentOperation::ALL
+            .into_iter()
+            .find(|operation| operation.as_str() == s)
+            .ok_or_else(|| format!("invalid payment operation {s:?}"))
+    }
+}
+
+/// What came of one [`PaymentOperation`] on an intent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentAttempt {
+    pub operation: PaymentOperation,
+    pub succeeded: bool,
+    /// Decline reason or transport error of a failed attempt; the event type
+    /// of a webhook.
+    pub detail: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An intent with its attempts, oldest first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Payment {
+    pub intent: PaymentIntent,
+    pub attempts: Vec<PaymentAttempt>,
+}
+
+/// The provider's answer to an authorization.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Authorization {
+    Authorized { provider_ref: String },
+    Declined { reason: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookKind {
+    Captured,
+    Failed,
+    Refunded,
+}
+
+impl WebhookKind {
+    /// Event type on the wire, e.g. `payment.captured`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WebhookKind::Captured => "payment.captured",
+            WebhookKind::Failed => "payment.failed",
+            WebhookKind::Refunded => "payment.refunded",
+        }
+    }
+}
+
+/// A verified notification from the provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookEvent {
+    /// The provider's event id; providers send events more than once.
+    pub id: String,
+    pub kind: WebhookKind,
+    pub provider_ref: String,
+}
+
+/// A payment service provider. Amounts are in cents.
+#[async_trait]
+pub trait PaymentProvider: Send + Sync {
+    /// Stored with every intent, e.g. `mock`.
+    fn name(&self) -> &'static str;
+    /// Reserve `amount_cents` on the customer's payment method. Providers
+    /// treat a repeated `reference` as the same authorization.
+    async fn authorize(&self, reference: &str, amount_cents: i64) -> Result<Authorization, DomainError>;
+    async fn capture(&self, provider_ref: &str, amount_cents: i64) -> Result<(), DomainError>;
+    async fn refund(&self, provider_ref: &str, amount_cents: i64) -> Result<(), DomainError>;
+    /// Check the `signature` the provider sent along with `body` and parse
+    /// it; [`DomainError::Unauthorized`] if it does not match.
+    fn verify_webhook(&self, signature: &str, body: &[u8]) -> Result<WebhookEvent, DomainError>;
+}
+
+#[async_trait]
+pub trait PaymentRepository: Send + Sync {
+    /// Store the intent; `id`, `created_at` and `updated_at` are assigned.
+    async fn create_intent(&self, intent: PaymentIntent) -> Result<i64, DomainError>;
+    async fn get_intent(&self, id: i64) -> Result<Option<PaymentIntent>, DomainError>;
+    /// Newest first.
+    async fn get_intents_by_order_id(&self, order_id: i64) -> Result<Vec<PaymentIntent>, DomainError>;
+    async fn get_intent_by_provider_ref(&self, provider: &str, provider_ref: &str)
+    -> Result<Option<PaymentIntent>, DomainError>;
+    /// Set the status, and the provider's reference if given.
+    async fn update_intent(&self, id: i64, status: PaymentStatus, provider_ref: Option<String>) -> Result<(), DomainError>;
+    /// `attempt.created_at` is assigned.
+    async fn record_attempt(&self, intent_id: i64, attempt: PaymentAttempt) -> Result<(), DomainError>;
+    /// Oldest first.
+    async fn attempts(&self, intent_id: i64) -> Result<Vec<PaymentAttempt>, DomainError>;
+    /// Remember a webhook event; `false` if it was seen before.
+    async fn record_webhook_event(&self, provider: &str, event_id: &str) -> Result<bool, DomainError>;
+}
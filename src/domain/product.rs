@@ -1,13 +1,16 @@
 use async_trait::async_trait;
 
-use crate::domain::DomainError;
+use crate::domain::{page::Page, DomainError};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Product {
     pub id: i64,
     pub name: String,
     pub description: Option<String>,
     pub price: f64,
+    /// Kept in step with the stock ledger, see
+    /// [`InventoryRepository`](crate::domain::inventory::InventoryRepository);
+    /// the total over all warehouses.
     pub stock: i32,
     pub category_id: i64,
     pub active: bool,
@@ -15,8 +18,9 @@ pub struct Product {
 
 #[async_trait]
 pub trait ProductRepository: Send + Sync {
+    /// Starting stock is put in the default warehouse.
     async fn create(&self, product: Product) -> Result<i64, DomainError>;
     async fn get_by_product_id(&self, id: i64) -> Result<Option<Product>, DomainError>;
-    async fn get_by_category_id(&self, category_id: i64) -> Result<Vec<Product>, DomainError>;
-    async fn get_all_products(&self) -> Result<Vec<Product>, DomainError>;
+    async fn get_by_category_id(&self, category_id: i64, page: Page) -> Result<Vec<Product>, DomainError>;
+    async fn get_all_products(&self, page: Page) -> Result<Vec<Product>, DomainError>;
 }
\ No newline at end of file
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::domain::DomainError;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromotionKind {
+    /// `value` percent off the eligible lines.
+    Percentage,
+    /// `value` off the eligible lines, at most what they cost.
+    Fixed,
+    /// Of every `buy_quantity + get_quantity` units of an eligible product,
+    /// `get_quantity` are free.
+    BuyXGetY,
+    /// No shipping fee, if the cart holds anything eligible.
+    FreeShipping,
+}
+
+impl PromotionKind {
+    pub const ALL: [PromotionKind; 4] =
+        [PromotionKind::Percentage, PromotionKind::Fixed, PromotionKind::BuyXGetY, PromotionKind::FreeShipping];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PromotionKind::Percentage => "percentage",
+            PromotionKind::Fixed => "fixed",
+            PromotionKind::BuyXGetY => "buy_x_get_y",
+            PromotionKind::FreeShipping => "free_shipping",
+        }
+    }
+}
+
+impl fmt::Display for PromotionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PromotionKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PromotionKind::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| format!("invalid promotion kind {s:?}"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Promotion {
+    pub id: i64,
+    pub name: String,
+    /// The coupon code that unlocks it, upper case; without one it applies
+    /// to every cart.
+    pub code: Option<String>,
+    pub kind: PromotionKind,
+    /// Percent for [`PromotionKind::Percentage`], money for
+    /// [`PromotionKind::Fixed`], unused otherwise.
+    pub value: f64,
+    pub buy_quantity: Option<i32>,
+    pub get_quantity: Option<i32>,
+    /// Only lines of this category count; at most one of `category_id` and
+    /// `product_id` is set, neither means the whole cart.
+    pub category_id: Option<i64>,
+    pub product_id: Option<i64>,
+    /// Valid from `starts_at` up to, not including, `ends_at`.
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    /// Checkouts it may be used in, in all and per customer.
+    pub max_uses: Option<i32>,
+    pub max_uses_per_user: Option<i32>,
+    pub active: bool,
+}
+
+impl Promotion {
+    /// Whether the settings make sense for the kind.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name is required".into());
+        }
+        if self.code.as_deref().is_some_and(|c| c.trim().is_empty()) {
+            return Err("code cannot be empty".into());
+        }
+        match self.kind {
+            PromotionKind::Percentage if !(self.value > 0.0 && self.value <= 100.0) => {
+                return Err("a percentage must be greater than 0 and at most 100".into());
+            }
+            PromotionKind::Fixed if !(self.value > 0.0 && self.value.is_finite()) => {
+                return Err("a fixed discount must be positive".into());
+            }
+            PromotionKind::BuyXGetY if self.buy_quantity.is_none_or(|q| q <= 0) || self.get_quantity.is_none_or(|q| q <= 0) => {
+                return Err("buy_x_get_y needs a positive buy_quantity and get_quantity".into());
+            }
+            _ => {}
+        }
+        if self.category_id.is_some() && self.product_id.is_some() {
+            return Err("a promotion is for a category or a product, not both".into());
+        }
+        if let (Some(starts_at), Some(ends_at)) = (self.starts_at, self.ends_at)
+            && ends_at <= starts_at
+        {
+            return Err("ends_at must be after starts_at".into());
+        }
+        if self.max_uses.is_some_and(|n| n <= 0) || self.max_uses_per_user.is_some_and(|n| n <= 0) {
+            return Err("usage limits must be positive".into());
+        }
+        Ok(())
+    }
+
+    /// Why it cannot be used at `now`, given how often it was used in all
+    /// and, if known, by the customer; phrased to follow the coupon code.
+    pub fn unusable(&self, now: DateTime<Utc>, uses: i64, user_uses: Option<i64>) -> Option<String> {
+        if !self.active {
+            return Some("is not active".into());
+        }
+        if self.starts_at.is_some_and(|t| now < t) {
+            return Some("is not valid yet".into());
+        }
+        if self.ends_at.is_some_and(|t| now >= t) {
+            return Some("has expired".into());
+        }
+        if self.max_uses.is_some_and(|max| uses >= i64::from(max)) {
+            return Some("has been used up".into());
+        }
+        if let (Some(max), Some(user_uses)) = (self.max_uses_per_user, user_uses)
+            && user_uses >= i64::from(max)
+        {
+            return Some(format!("can be used {max} time(s) per customer"));
+        }
+        None
+    }
+
+    pub fn applies_to(&self, line: &PricingLine) -> bool {
+        self.category_id.is_none_or(|id| id == line.category_id) && self.product_id.is_none_or(|id| id == line.product_id)
+    }
+
+    fn scope(&self) -> String {
+        match (self.category_id, self.product_id) {
+            (Some(id), _) => format!("category {id}"),
+            (_, Some(id)) => format!("product {id}"),
+            _ => "everything".into(),
+        }
+    }
+}
+
+/// One use of a promotion, recorded at checkout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redemption {
+    pub promotion_id: i64,
+    pub user_id: Option<i64>,
+    pub order_id: i64,
+    pub discount: f64,
+}
+
+/// A cart line as the promotions see it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PricingLine {
+    pub product_id: i64,
+    pub category_id: i64,
+    pub quantity: i32,
+    pub unit_price: f64,
+}
+
+/// A promotion that took something off, and what.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppliedPromotion {
+    pub promotion_id: i64,
+    pub name: String,
+    pub code: Option<String>,
+    pub kind: PromotionKind,
+    /// Taken off the subtotal; 0 for free shipping, which shows in the
+    /// shipping instead.
+    pub discount: f64,
+    /// E.g. `10% off category 2`.
+    pub detail: String,
+}
+
+/// `total = subtotal - discount + shipping`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Pricing {
+    pub subtotal: f64,
+    pub discount: f64,
+    pub shipping: f64,
+    pub total: f64,
+    /// In the order they were applied.
+    pub promotions: Vec<AppliedPromotion>,
+    /// Why the cart's coupon takes nothing off, when it does not.
+    pub coupon_note: Option<String>,
+}
+
+/// Price `lines` with a shipping fee of `shipping`, applying `promotions`
+/// in order. Each discount is worked out on the lines' own prices; together
+/// they never take off more than the subtotal. The promotions that apply to
+/// nothing in the cart come back by id, with the reason.
+pub fn apply(promotions: &[Promotion], lines: &[PricingLine], shipping: f64) -> (Pricing, Vec<(i64, String)>) {
+    // prices are NUMERIC(10, 2), so whole cents
+    let cents = |amount: f64| (amount * 100.0).round() as i64;
+    let subtotal: i64 = lines.iter().map(|l| cents(l.unit_price) * i64::from(l.quantity)).sum();
+    let mut shipping = if lines.is_empty() { 0 } else { cents(shipping) };
+    let mut remaining = subtotal;
+    let mut applied = Vec::new();
+    let mut skipped = Vec::new();
+
+    for promotion in promotions {
+        let eligible: Vec<_> = lines.iter().filter(|l| promotion.applies_to(l)).collect();
+        if eligible.is_empty() {
+            skipped.push((promotion.id, "does not apply to anything in the cart".to_string()));
+            continue;
+        }
+        let eligible_cents: i64 = eligible.iter().map(|l| cents(l.unit_price) * i64::from(l.quantity)).sum();
+        let (discount, detail) = match promotion.kind {
+            PromotionKind::Percentage => {
+                let discount = (eligible_cents as f64 * promotion.value / 100.0).round() as i64;
+                (discount, format!("{}% off {}", promotion.value, promotion.scope()))
+            }
+            PromotionKind::Fixed => {
+                (cents(promotion.value).min(eligible_cents), format!("{:.2} off {}", promotion.value, promotion.scope()))
+            }
+            PromotionKind::BuyXGetY => {
+                let (buy, get) = (promotion.buy_quantity.unwrap_or(1), promotion.get_quantity.unwrap_or(1));
+                let discount: i64 = eligible
+                    .iter()
+                    .map(|l| i64::from(l.quantity / (buy + get) * get) * cents(l.unit_price))
+                    .sum();
+                if discount == 0 {
+                    skipped.push((promotion.id, format!("needs {} of a product in the cart", buy + get)));
+                    continue;
+                }
+                (discount, format!("buy {buy} get {get} free on {}", promotion.scope()))
+            }
+            PromotionKind::FreeShipping => {
+                shipping = 0;
+                (0, "free shipping".to_string())
+            }
+        };
+        let discount = discount.min(remaining);
+        remaining -= discount;
+        applied.push(AppliedPromotion {
+            promotion_id: promotion.id,
+            name: promotion.name.clone(),
+            code: promotion.code.clone(),
+            kind: promotion.kind,
+            discount: discount as f64 / 100.0,
+            detail,
+        });
+    }
+
+    let pricing = Pricing {
+        subtotal: subtotal as f64 / 100.0,
+        discount: (subtotal - remaining) as f64 / 100.0,
+        shipping: shipping as f64 / 100.0,
+        total: (remaining + shipping) as f64 / 100.0,
+        promotions: applied,
+        coupon_note: None,
+    };
+    (pricing, skipped)
+}
+
+#[async_trait]
+pub trait PromotionRepository: Send + Sync {
+    async fn create(&self, promotion: Promotion) -> Result<i64, DomainError>;
+    async fn get(&self, id: i64) -> Result<Option<Promotion>, DomainError>;
+    /// `code` exactly as stored, i.e. upper case.
+    async fn by_code(&self, code: &str) -> Result<Option<Promotion>, DomainError>;
+    /// By id.
+    async fn list(&self) -> Result<Vec<Promotion>, DomainError>;
+    /// `false` when there is no such promotion.
+    async fn update(&self, promotion: Promotion) -> Result<bool, DomainError>;
+    /// With its redemptions; `false` when there is no such promotion.
+    async fn delete(&self, id: i64) -> Result<bool, DomainError>;
+    async fn redeem(&self, redemption: Redemption) -> Result<(), DomainError>;
+    /// How often the promotion was redeemed, by `user_id` only if given.
+    async fn uses(&self, promotion_id: i64, user_id: Option<i64>) -> Result<i64, DomainError>;
+    /// [`uses`](Self::uses) of every promotion redeemed at all, by id.
+    async fn uses_by_promotion(&self, user_id: Option<i64>) -> Result<HashMap<i64, i64>, DomainError>;
+}
@@ -0,0 +1,85 @@
+use super::{apply, PricingLine, Promotion, PromotionKind};
+
+fn promotion(id: i64, kind: PromotionKind, value: f64) -> Promotion {
+    Promotion {
+        id,
+        name: format!("promotion {id}"),
+        code: None,
+        kind,
+        value,
+        buy_quantity: None,
+        get_quantity: None,
+        category_id: None,
+        product_id: None,
+        starts_at: None,
+        ends_at: None,
+        max_uses: None,
+        max_uses_per_user: None,
+        active: true,
+    }
+}
+
+fn line(product_id: i64, category_id: i64, quantity: i32, unit_price: f64) -> PricingLine {
+    PricingLine { product_id, category_id, quantity, unit_price }
+}
+
+#[test]
+fn percentages_round_to_whole_cents() {
+    // 15% of 9.99 is 1.4985
+    let (pricing, skipped) = apply(&[promotion(1, PromotionKind::Percentage, 15.0)], &[line(1, 1, 3, 3.33)], 4.95);
+    assert!(skipped.is_empty());
+    assert_eq!((pricing.subtotal, pricing.discount, pricing.shipping, pricing.total), (9.99, 1.5, 4.95, 13.44));
+    assert_eq!(pricing.promotions[0].detail, "15% off everything");
+
+    // half a cent rounds up
+    let (pricing, _) = apply(&[promotion(1, PromotionKind::Percentage, 10.0)], &[line(1, 1, 1, 0.05)], 0.0);
+    assert_eq!(pricing.discount, 0.01);
+}
+
+#[test]
+fn fixed_discounts_stop_at_what_the_eligible_lines_cost() {
+    let fixed = Promotion { category_id: Some(2), ..promotion(1, PromotionKind::Fixed, 50.0) };
+    let lines = [line(1, 1, 1, 40.0), line(2, 2, 2, 15.0)];
+    let (pricing, _) = apply(&[fixed], &lines, 0.0);
+    assert_eq!((pricing.subtotal, pricing.discount, pricing.total), (70.0, 30.0, 40.0));
+    assert_eq!(pricing.promotions[0].detail, "50.00 off category 2");
+}
+
+#[test]
+fn buy_x_get_y_needs_enough_of_a_product() {
+    let three_for_two = Promotion { buy_quantity: Some(2), get_quantity: Some(1), ..promotion(7, PromotionKind::BuyXGetY, 0.0) };
+
+    let (pricing, skipped) = apply(std::slice::from_ref(&three_for_two), &[line(1, 1, 2, 15.0)], 0.0);
+    assert_eq!((pricing.discount, pricing.total), (0.0, 30.0));
+    assert!(pricing.promotions.is_empty());
+    assert_eq!(skipped, [(7, "needs 3 of a product in the cart".to_string())]);
+
+    // units of different products do not add up; five of one give one free
+    let (pricing, _) = apply(std::slice::from_ref(&three_for_two), &[line(1, 1, 2, 15.0), line(2, 1, 1, 15.0)], 0.0);
+    assert_eq!(pricing.discount, 0.0);
+    let (pricing, _) = apply(&[three_for_two], &[line(1, 1, 5, 15.0)], 0.0);
+    assert_eq!((pricing.discount, pricing.total), (15.0, 60.0));
+}
+
+#[test]
+fn stacked_discounts_never_exceed_the_subtotal() {
+    let promotions = [promotion(1, PromotionKind::Fixed, 30.0), promotion(2, PromotionKind::Percentage, 80.0)];
+    let (pricing, _) = apply(&promotions, &[line(1, 1, 1, 40.0)], 4.95);
+    // 80% of 40.00 is worked out on the prices, but only 10.00 is left
+    let discounts: Vec<_> = pricing.promotions.iter().map(|p| (p.promotion_id, p.discount)).collect();
+    assert_eq!(discounts, [(1, 30.0), (2, 10.0)]);
+    assert_eq!((pricing.subtotal, pricing.discount, pricing.shipping, pricing.total), (40.0, 40.0, 4.95, 4.95));
+}
+
+#[test]
+fn free_shipping_needs_something_in_the_cart() {
+    let free_shipping = promotion(3, PromotionKind::FreeShipping, 0.0);
+
+    let (pricing, skipped) = apply(std::slice::from_ref(&free_shipping), &[], 4.95);
+    assert_eq!(pricing, Default::default());
+    assert_eq!(skipped, [(3, "does not apply to anything in the cart".to_string())]);
+
+    let (pricing, _) = apply(&[free_shipping], &[line(1, 1, 1, 40.0)], 4.95);
+    assert_eq!((pricing.discount, pricing.shipping, pricing.total), (0.0, 0.0, 40.0));
+    assert_eq!((pricing.promotions[0].discount, pricing.promotions[0].detail.as_str()), (0.0, "free shipping"));
+}
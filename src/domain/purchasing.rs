@@ -0,0 +1,144 @@
+use std::fmt;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::DomainError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Supplier {
+    pub id: i64,
+    pub name: String,
+    pub email: Option<String>,
+}
+
+/// What a supplier sells us a product as, and for how much.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupplierProduct {
+    pub supplier_id: i64,
+    pub product_id: i64,
+    /// The supplier's own article number; unique per supplier.
+    pub sku: String,
+    pub cost_price: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PurchaseOrderStatus {
+    /// Being put together; nothing has gone to the supplier yet.
+    Draft,
+    Sent,
+    /// Some, not all, of it has arrived.
+    PartiallyReceived,
+    Received,
+}
+
+impl PurchaseOrderStatus {
+    pub const ALL: [PurchaseOrderStatus; 4] = [
+        PurchaseOrderStatus::Draft,
+        PurchaseOrderStatus::Sent,
+        PurchaseOrderStatus::PartiallyReceived,
+        PurchaseOrderStatus::Received,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PurchaseOrderStatus::Draft => "draft",
+            PurchaseOrderStatus::Sent => "sent",
+            PurchaseOrderStatus::PartiallyReceived => "partially_received",
+            PurchaseOrderStatus::Received => "received",
+        }
+    }
+
+    pub fn can_become(self, to: PurchaseOrderStatus) -> bool {
+        use PurchaseOrderStatus::*;
+        matches!(
+            (self, to),
+            (Draft, Sent) | (Sent, PartiallyReceived) | (Sent, Received) | (PartiallyReceived, Received)
+        )
+    }
+
+    /// Whether goods may be received against the order.
+    pub fn is_receivable(self) -> bool {
+        matches!(self, PurchaseOrderStatus::Sent | PurchaseOrderStatus::PartiallyReceived)
+    }
+}
+
+impl fmt::Display for PurchaseOrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PurchaseOrderStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PurchaseOrderStatus::ALL
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| format!("invalid purchase order status {s:?}"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PurchaseOrderLine {
+    pub product_id: i64,
+    pub quantity: i32,
+    /// How much of `quantity` has arrived so far.
+    pub received: i32,
+    pub unit_cost: f64,
+}
+
+impl PurchaseOrderLine {
+    pub fn outstanding(&self) -> i32 {
+        self.quantity - self.received
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PurchaseOrder {
+    pub id: i64,
+    pub supplier_id: i64,
+    pub status: PurchaseOrderStatus,
+    /// By product id, one line per product.
+    pub lines: Vec<PurchaseOrderLine>,
+    /// The admin who drew it up; `None` once their account is deleted.
+    pub created_by: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PurchaseOrder {
+    pub fn total(&self) -> f64 {
+        self.lines.iter().map(|l| f64::from(l.quantity) * l.unit_cost).sum()
+    }
+
+    pub fn is_fully_received(&self) -> bool {
+        self.lines.iter().all(|l| l.outstanding() == 0)
+    }
+}
+
+#[async_trait]
+pub trait PurchasingRepository: Send + Sync {
+    async fn create_supplier(&self, supplier: Supplier) -> Result<i64, DomainError>;
+    async fn supplier(&self, id: i64) -> Result<Option<Supplier>, DomainError>;
+    /// By id.
+    async fn suppliers(&self) -> Result<Vec<Supplier>, DomainError>;
+    /// Add or replace the supplier's terms for the product; the stored
+    /// link, with the cost price as the database rounded it.
+    async fn set_supplier_product(&self, link: SupplierProduct) -> Result<SupplierProduct, DomainError>;
+    /// By product id.
+    async fn supplier_products(&self, supplier_id: i64) -> Result<Vec<SupplierProduct>, DomainError>;
+    /// Store the order and its lines; `id` and `created_at` are assigned.
+    async fn create_order(&self, order: PurchaseOrder) -> Result<i64, DomainError>;
+    async fn order(&self, id: i64) -> Result<Option<PurchaseOrder>, DomainError>;
+    /// Newest first.
+    async fn orders(&self) -> Result<Vec<PurchaseOrder>, DomainError>;
+    /// Legality is the caller's business.
+    async fn set_status(&self, id: i64, status: PurchaseOrderStatus) -> Result<(), DomainError>;
+    /// Count `quantity` more of the product as received, unless that is more
+    /// than is outstanding; `false` then, or when the order has no such line.
+    async fn receive(&self, id: i64, product_id: i64, quantity: i32) -> Result<bool, DomainError>;
+}
@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::domain::product::Product;
+use crate::domain::warehouse::WarehouseStock;
+use crate::domain::DomainError;
+
+/// Stock held for a cart until `expires_at`. It does not touch the stock
+/// or its ledger, it only keeps other carts from counting on it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reservation {
+    pub id: i64,
+    pub cart_id: i64,
+    pub product_id: i64,
+    pub quantity: i32,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A product with the quantity live reservations hold of it.
+#[derive(Debug, Clone)]
+pub struct ProductAvailability {
+    pub product: Product,
+    pub reserved: i32,
+    /// Where the stock is, when asked for.
+    pub locations: Option<Vec<WarehouseStock>>,
+}
+
+impl ProductAvailability {
+    /// What is left to sell: on hand less reserved, never below zero.
+    pub fn available(&self) -> i32 {
+        (self.product.stock - self.reserved).max(0)
+    }
+}
+
+/// A cart holds at most one reservation per product. Deleting the cart
+/// releases its reservations.
+#[async_trait]
+pub trait ReservationRepository: Send + Sync {
+    /// Hold `quantity` of the product for the cart until `expires_at`, in
+    /// place of the cart's earlier reservation of it.
+    async fn hold(
+        &self,
+        cart_id: i64,
+        product_id: i64,
+        quantity: i32,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Reservation, DomainError>;
+    /// `false` when the cart holds none of the product.
+    async fn release(&self, cart_id: i64, product_id: i64) -> Result<bool, DomainError>;
+    /// Quantity held at `now` of each of `product_ids` that has any held,
+    /// leaving out `except_cart`'s reservations.
+    async fn reserved(
+        &self,
+        product_ids: &[i64],
+        except_cart: Option<i64>,
+        now: DateTime<Utc>,
+    ) -> Result<HashMap<i64, i32>, DomainError>;
+    /// Delete the reservations expired at `now`; returns how many.
+    async fn release_expired(&self, now: DateTime<Utc>) -> Result<u64, DomainError>;
+}
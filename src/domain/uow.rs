@@ -0,0 +1,88 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use crate::domain::cart::CartRepository;
+use crate::domain::category::CategoryRepository;
+use crate::domain::event::OutboxRepository;
+use crate::domain::inventory::InventoryRepository;
+use crate::domain::notification::NotificationRepository;
+use crate::domain::order::OrderRepository;
+use crate::domain::payment::PaymentRepository;
+use crate::domain::product::ProductRepository;
+use crate::domain::promotion::PromotionRepository;
+use crate::domain::purchasing::PurchasingRepository;
+use crate::domain::reservation::ReservationRepository;
+use crate::domain::user::UserRepository;
+use crate::domain::warehouse::WarehouseRepository;
+use crate::domain::DomainError;
+
+/// Repositories bound to one open transaction. Everything done through them
+/// commits or rolls back together.
+#[derive(Clone)]
+pub struct TxRepositories {
+    pub users: Arc<dyn UserRepository>,
+    pub categories: Arc<dyn CategoryRepository>,
+    pub products: Arc<dyn ProductRepository>,
+    /// The only way to change a product's stock.
+    pub inventory: Arc<dyn InventoryRepository>,
+    pub warehouses: Arc<dyn WarehouseRepository>,
+    pub carts: Arc<dyn CartRepository>,
+    pub reservations: Arc<dyn ReservationRepository>,
+    pub orders: Arc<dyn OrderRepository>,
+    pub payments: Arc<dyn PaymentRepository>,
+    pub notifications: Arc<dyn NotificationRepository>,
+    pub purchasing: Arc<dyn PurchasingRepository>,
+    pub promotions: Arc<dyn PromotionRepository>,
+    /// Append the events raised by the change here.
+    pub outbox: Arc<dyn OutboxRepository>,
+}
+
+pub type TxFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, DomainError>> + Send + 'a>>;
+
+/// Work run inside a transaction. `FnMut` because it is re-run from scratch
+/// when the transaction is retried.
+pub type TxWork<'w> = dyn for<'a> FnMut(&'a TxRepositories) -> TxFuture<'a, ()> + Send + 'w;
+
+#[async_trait]
+pub trait UnitOfWork: Send + Sync {
+    /// Open a transaction and run `work` with repositories scoped to it:
+    /// commit on `Ok`, roll back on `Err`. Serialization failures
+    /// ([`DomainError::Conflict`]) roll back and re-run `work` a few times
+    /// before giving up.
+    async fn run(&self, work: &mut TxWork<'_>) -> Result<(), DomainError>;
+}
+
+impl dyn UnitOfWork {
+    /// Typed wrapper over [`UnitOfWork::run`]:
+    ///
+    /// ```ignore
+    /// let id = uow.transaction(|tx| Box::pin(async move {
+    ///     let id = tx.categories.create("Books".into()).await?;
+    ///     tx.products.create(product(id)).await
+    /// })).await?;
+    /// ```
+    pub async fn transaction<T, F>(&self, mut work: F) -> Result<T, DomainError>
+    where
+        T: Send + 'static,
+        F: for<'a> FnMut(&'a TxRepositories) -> TxFuture<'a, T> + Send,
+    {
+        // only the last (committed) attempt's value survives
+        let output = Arc::new(Mutex::new(None));
+        self.run(&mut |tx| {
+            let fut = work(tx);
+            let output = output.clone();
+            Box::pin(async move {
+                let value = fut.await?;
+                *output.lock().unwrap_or_else(|e| e.into_inner()) = Some(value);
+                Ok(())
+            })
+        })
+        .await?;
+
+        let value = output.lock().unwrap_or_else(|e| e.into_inner()).take();
+        value.ok_or_else(|| DomainError::Unexpected("transaction committed without a result".into()))
+    }
+}
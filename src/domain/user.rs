@@ -1,13 +1,14 @@
 use async_trait::async_trait;
 
-use crate::domain::DomainError;
+use crate::domain::{page::Page, DomainError, Secret};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct User {
     pub id: i64,
     pub username: String,
-    pub password: String,
+    pub password: Secret<String>,
     pub active: bool,
+    pub admin: bool,
 }
 
 impl User {
@@ -39,7 +40,8 @@ pub trait UserRepository: Send + Sync {
     async fn create(&self, username: String, password: String) -> Result<i64, DomainError>;
     async fn get_by_id(&self, id: i64) -> Result<Option<User>, DomainError>;
     async fn get_by_username(&self, username: String) -> Result<Option<User>, DomainError>;
-    async fn get_all_users(&self) -> Result<Vec<User>, DomainError>;
+    async fn get_all_users(&self, page: Page) -> Result<Vec<User>, DomainError>;
     async fn update(&self, id: i64, username: String, password: String) -> Result<User, DomainError>;
     async fn delete(&self, id: i64) -> Result<(), DomainError>;
+    async fn set_admin(&self, id: i64, admin: bool) -> Result<(), DomainError>;
 }
@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+
+use crate::domain::DomainError;
+
+/// A point on the globe, in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl GeoPoint {
+    /// `None` unless both coordinates are in range.
+    pub fn new(latitude: f64, longitude: f64) -> Option<Self> {
+        ((-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude))
+            .then_some(GeoPoint { latitude, longitude })
+    }
+
+    /// Great-circle distance in kilometres.
+    pub fn distance_km(self, other: GeoPoint) -> f64 {
+        const EARTH_RADIUS_KM: f64 = 6371.0;
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.longitude - self.longitude).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+}
+
+/// A stock location. Stock is kept per warehouse; `products.stock` is the
+/// total over all of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warehouse {
+    pub id: i64,
+    pub name: String,
+    /// Lower ships first under [`AllocationStrategy::Priority`], and the
+    /// first by priority is the default for receipts and adjustments.
+    pub priority: i32,
+    pub location: Option<GeoPoint>,
+}
+
+/// How much of one product one warehouse holds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarehouseStock {
+    pub warehouse_id: i64,
+    pub warehouse: String,
+    pub product_id: i64,
+    pub stock: i32,
+}
+
+/// Which warehouses a checkout draws from first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocationStrategy {
+    /// By [`Warehouse::priority`].
+    Priority,
+    /// Closest to the shipping address; warehouses without a location, and
+    /// every warehouse when the address is unknown, go by priority.
+    Nearest,
+}
+
+impl AllocationStrategy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AllocationStrategy::Priority => "priority",
+            AllocationStrategy::Nearest => "nearest",
+        }
+    }
+
+    /// `warehouses` in the order to draw from.
+    pub fn rank(self, mut warehouses: Vec<Warehouse>, ship_to: Option<GeoPoint>) -> Vec<Warehouse> {
+        warehouses.sort_by_key(|w| (w.priority, w.id));
+        if let (AllocationStrategy::Nearest, Some(ship_to)) = (self, ship_to) {
+            // stable, so ties and unknown locations keep the priority order
+            warehouses.sort_by(|a, b| {
+                let distance = |w: &Warehouse| w.location.map_or(f64::INFINITY, |l| l.distance_km(ship_to));
+                distance(a).total_cmp(&distance(b))
+            });
+        }
+        warehouses
+    }
+}
+
+impl fmt::Display for AllocationStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AllocationStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "priority" => Ok(AllocationStrategy::Priority),
+            "nearest" => Ok(AllocationStrategy::Nearest),
+            _ => Err(format!("invalid allocation strategy {s:?}")),
+        }
+    }
+}
+
+/// `(warehouse_id, quantity)` to take `quantity` from, given the ranked
+/// warehouses and what each holds: the first that has it all, otherwise
+/// as much as possible from each in turn. `None` if they hold too little
+/// between them.
+pub fn allocate(ranked: &[Warehouse], stock: &HashMap<i64, i32>, quantity: i32) -> Option<Vec<(i64, i32)>> {
+    let held = |w: &Warehouse| stock.get(&w.id).copied().unwrap_or_default();
+    if let Some(whole) = ranked.iter().find(|w| held(w) >= quantity) {
+        return Some(vec![(whole.id, quantity)]);
+    }
+    let mut left = quantity;
+    let mut taken = Vec::new();
+    for warehouse in ranked {
+        let take = held(warehouse).min(left);
+        if take > 0 {
+            taken.push((warehouse.id, take));
+            left -= take;
+        }
+    }
+    (left == 0).then_some(taken)
+}
+
+#[async_trait]
+pub trait WarehouseRepository: Send + Sync {
+    /// `id` is assigned.
+    async fn create(&self, warehouse: Warehouse) -> Result<i64, DomainError>;
+    async fn get(&self, id: i64) -> Result<Option<Warehouse>, DomainError>;
+    /// By priority, then id.
+    async fn list(&self) -> Result<Vec<Warehouse>, DomainError>;
+    /// Every stock level of `product_ids` that is not zero, by product,
+    /// then warehouse priority.
+    async fn stock(&self, product_ids: &[i64]) -> Result<Vec<WarehouseStock>, DomainError>;
+}
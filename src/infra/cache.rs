@@ -0,0 +1,178 @@
+//! In-process LRU cache with a TTL, for read-through caching in front of
+//! repositories (see [`crate::infra::repository::cached`]).
+//!
+//! Metrics emitted, by `cache.name`:
+//! - `cache.hits`
+//! - `cache.misses`     (including expired entries)
+//! - `cache.evictions`  (by `cache.eviction.reason`: `capacity` or `expired`)
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+
+use opentelemetry::metrics::Counter;
+use opentelemetry::{global, KeyValue};
+
+struct CacheMetrics {
+    hits: Counter<u64>,
+    misses: Counter<u64>,
+    evictions: Counter<u64>,
+}
+
+impl CacheMetrics {
+    fn get() -> &'static CacheMetrics {
+        static METRICS: OnceLock<CacheMetrics> = OnceLock::new();
+        METRICS.get_or_init(|| {
+            let meter = global::meter("rust-just-learn");
+            CacheMetrics {
+                hits: meter
+                    .u64_counter("cache.hits")
+                    .with_description("Cache lookups answered from the cache")
+                    .with_unit("{lookup}")
+                    .build(),
+                misses: meter
+                    .u64_counter("cache.misses")
+                    .with_description("Cache lookups that went to the repository")
+                    .with_unit("{lookup}")
+                    .build(),
+                evictions: meter
+                    .u64_counter("cache.evictions")
+                    .with_description("Entries dropped to stay within size or because they expired")
+                    .with_unit("{entry}")
+                    .build(),
+            }
+        })
+    }
+}
+
+struct Entry<V> {
+    value: V,
+    expires_at: Instant,
+    used: u64,
+}
+
+struct State<K, V> {
+    entries: HashMap<K, Entry<V>>,
+    /// Last use -> key, oldest first.
+    recency: BTreeMap<u64, K>,
+    clock: u64,
+    generation: u64,
+}
+
+/// Holds at most `capacity` entries, each for at most `ttl`; the least
+/// recently used entry makes room for a new one.
+pub struct LruCache<K, V> {
+    name: &'static str,
+    capacity: usize,
+    ttl: Duration,
+    state: Mutex<State<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> LruCache<K, V> {
+    pub fn new(name: &'static str, capacity: usize, ttl: Duration) -> Self {
+        Self {
+            name,
+            capacity,
+            ttl,
+            state: Mutex::new(State {
+                entries: HashMap::new(),
+                recency: BTreeMap::new(),
+                clock: 0,
+                generation: 0,
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<K, V>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn attributes(&self) -> [KeyValue; 1] {
+        [KeyValue::new("cache.name", self.name)]
+    }
+
+    fn evicted(&self, reason: &'static str) {
+        CacheMetrics::get()
+            .evictions
+            .add(1, &[KeyValue::new("cache.name", self.name), KeyValue::new("cache.eviction.reason", reason)]);
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut state = self.lock();
+        let state = &mut *state;
+        let Some(entry) = state.entries.get_mut(key) else {
+            CacheMetrics::get().misses.add(1, &self.attributes());
+            return None;
+        };
+        if entry.expires_at <= Instant::now() {
+            state.recency.remove(&entry.used);
+            state.entries.remove(key);
+            self.evicted("expired");
+            CacheMetrics::get().misses.add(1, &self.attributes());
+            return None;
+        }
+
+        state.clock += 1;
+        state.recency.remove(&entry.used);
+        entry.used = state.clock;
+        state.recency.insert(state.clock, key.clone());
+        CacheMetrics::get().hits.add(1, &self.attributes());
+        Some(entry.value.clone())
+    }
+
+    /// Changes on every [`remove`](Self::remove) and [`clear`](Self::clear);
+    /// take it before reading what to [`insert`](Self::insert).
+    pub fn generation(&self) -> u64 {
+        self.lock().generation
+    }
+
+    /// Store `value` unless something was invalidated since `generation`
+    /// was taken: the value may have been read before that write.
+    pub fn insert(&self, generation: u64, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.lock();
+        if state.generation != generation {
+            return;
+        }
+
+        state.clock += 1;
+        let used = state.clock;
+        let entry = Entry { value, expires_at: Instant::now() + self.ttl, used };
+        if let Some(old) = state.entries.insert(key.clone(), entry) {
+            state.recency.remove(&old.used);
+        }
+        state.recency.insert(used, key);
+
+        while state.entries.len() > self.capacity {
+            let Some((_, oldest)) = state.recency.pop_first() else { break };
+            state.entries.remove(&oldest);
+            self.evicted("capacity");
+        }
+    }
+
+    pub fn remove(&self, key: &K) {
+        let mut state = self.lock();
+        state.generation += 1;
+        if let Some(entry) = state.entries.remove(key) {
+            state.recency.remove(&entry.used);
+        }
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.generation += 1;
+        state.entries.clear();
+        state.recency.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
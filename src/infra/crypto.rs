@@ -1,7 +1,11 @@
 use argon2::{
-    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    password_hash::{
+        rand_core::{OsRng, RngCore},
+        PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+    },
     Argon2,
 };
+use sha2::{Digest, Sha256};
 
 use crate::domain::DomainError;
 
@@ -28,3 +32,15 @@ pub async fn verify_password(password: String, hash: String) -> Result<bool, Dom
     .await
     .map_err(|e| DomainError::Unexpected(e.to_string()))?
 }
+
+/// `len` random bytes from the OS, hex encoded.
+pub fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// For high-entropy secrets such as API keys, where a slow hash buys nothing.
+pub fn sha256_hex(value: &str) -> String {
+    hex::encode(Sha256::digest(value.as_bytes()))
+}
@@ -0,0 +1,271 @@
+//! Database instrumentation for the Postgres and SQLite repositories.
+//! Attribute and metric names follow OTel Semantic Conventions:
+//! https://opentelemetry.io/docs/specs/semconv/database/database-spans/
+//! https://opentelemetry.io/docs/specs/semconv/database/database-metrics/
+//!
+//! Repository methods declare the span with `#[instrument]`:
+//!
+//! ```ignore
+//! #[instrument(skip(self), err, fields(
+//!     db.system = "postgresql",
+//!     db.operation.name = "SELECT",
+//!     db.collection.name = "categories",
+//!     db.query.text = Empty,
+//! ))]
+//! ```
+//!
+//! and run the query through [`pg_query!`] / [`pg_query_as!`] (SQLite:
+//! [`sqlite_query!`] / [`sqlite_query_as!`]) to record `db.query.text`,
+//! [`acquire`] (pool wait metrics) and [`DbTraceExt::traced`] (slow-query log).
+
+use std::env;
+use std::future::Future;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use opentelemetry::{global, metrics::Histogram, KeyValue};
+use sqlx::pool::PoolConnection;
+use sqlx::{Database, Pool, Postgres, Sqlite};
+
+use crate::domain::DomainError;
+
+#[cfg(test)]
+mod tests;
+
+/// Value of `db.client.connection.pool.name` for each backend.
+pub trait PoolName: Database {
+    const POOL_NAME: &'static str;
+}
+
+impl PoolName for Postgres {
+    const POOL_NAME: &'static str = "postgres";
+}
+
+impl PoolName for Sqlite {
+    const POOL_NAME: &'static str = "sqlite";
+}
+
+/// `sqlx::query!` that also records the sanitized SQL as `db.query.text`
+/// on the current span.
+macro_rules! pg_query {
+    ($sql:literal $(, $arg:expr)* $(,)?) => {{
+        $crate::infra::db_trace::record_query_text($sql);
+        sqlx::query!($sql $(, $arg)*)
+    }};
+}
+
+/// `sqlx::query_as!` that also records the sanitized SQL as `db.query.text`
+/// on the current span.
+macro_rules! pg_query_as {
+    ($out:path, $sql:literal $(, $arg:expr)* $(,)?) => {{
+        $crate::infra::db_trace::record_query_text($sql);
+        sqlx::query_as!($out, $sql $(, $arg)*)
+    }};
+}
+
+/// `sqlx::query` (runtime-checked, the compile-time macros only know the
+/// Postgres `DATABASE_URL`) that also records `db.query.text`.
+macro_rules! sqlite_query {
+    ($sql:expr) => {{
+        $crate::infra::db_trace::record_query_text($sql);
+        sqlx::query($sql)
+    }};
+}
+
+/// `sqlx::query_as` that also records `db.query.text`.
+macro_rules! sqlite_query_as {
+    ($out:ty, $sql:expr) => {{
+        $crate::infra::db_trace::record_query_text($sql);
+        sqlx::query_as::<_, $out>($sql)
+    }};
+}
+
+pub(crate) use {pg_query, pg_query_as, sqlite_query, sqlite_query_as};
+
+// ---- Query spans -----------------------------------------------------------
+
+/// Record `db.query.text` on the current span. Parameters are bound as `$n`
+/// placeholders, so only inline literals need scrubbing.
+pub fn record_query_text(sql: &str) {
+    tracing::Span::current().record("db.query.text", sanitize_query(sql).as_str());
+}
+
+/// Collapse whitespace and replace inline string / numeric literals with `?`;
+/// `$1` and `?1` bind placeholders are kept.
+pub fn sanitize_query(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    let mut prev: Option<char> = None;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                // skip to the closing quote ('' is an escaped quote)
+                while let Some(n) = chars.next() {
+                    if n == '\'' {
+                        if chars.peek() == Some(&'\'') {
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                }
+                out.push('?');
+                prev = Some('?');
+            }
+            c if c.is_ascii_digit()
+                && !prev.is_some_and(|p| p.is_alphanumeric() || p == '_' || p == '$' || p == '?') =>
+            {
+                while chars.peek().is_some_and(|n| n.is_ascii_digit() || *n == '.') {
+                    chars.next();
+                }
+                out.push('?');
+                prev = Some('?');
+            }
+            c if c.is_whitespace() => {
+                if prev.is_some_and(|p| p != ' ') {
+                    out.push(' ');
+                    prev = Some(' ');
+                }
+            }
+            c => {
+                out.push(c);
+                prev = Some(c);
+            }
+        }
+    }
+    out.trim_end().to_string()
+}
+
+/// Slow-query threshold from `DB_SLOW_QUERY_MS` (default 500ms).
+fn slow_query_threshold() -> Duration {
+    static THRESHOLD: OnceLock<Duration> = OnceLock::new();
+    *THRESHOLD.get_or_init(|| {
+        let ms = env::var("DB_SLOW_QUERY_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(500);
+        Duration::from_millis(ms)
+    })
+}
+
+pub trait DbTraceExt: Future + Sized {
+    /// Await the query and log the surrounding db span at warn level when it
+    /// takes longer than the slow-query threshold.
+    fn traced(self) -> impl Future<Output = Self::Output> {
+        async move {
+            let start = Instant::now();
+            let output = self.await;
+            let elapsed = start.elapsed();
+            let threshold = slow_query_threshold();
+            if elapsed >= threshold {
+                tracing::warn!(
+                    duration_ms = elapsed.as_millis() as u64,
+                    threshold_ms = threshold.as_millis() as u64,
+                    "slow query"
+                );
+            }
+            output
+        }
+    }
+}
+
+impl<F: Future> DbTraceExt for F {}
+
+// ---- Pool metrics ----------------------------------------------------------
+
+/// Number of tasks currently waiting in [`acquire`].
+static PENDING_ACQUIRES: AtomicI64 = AtomicI64::new(0);
+
+fn wait_time() -> &'static Histogram<f64> {
+    static WAIT_TIME: OnceLock<Histogram<f64>> = OnceLock::new();
+    WAIT_TIME.get_or_init(|| {
+        global::meter("rust-just-learn")
+            .f64_histogram("db.client.connection.wait_time")
+            .with_description("Time it took to obtain an open connection from the pool")
+            .with_unit("s")
+            .build()
+    })
+}
+
+/// Decrements [`PENDING_ACQUIRES`] even if the acquire future is dropped.
+struct PendingAcquire;
+
+impl PendingAcquire {
+    fn start() -> Self {
+        PENDING_ACQUIRES.fetch_add(1, Ordering::Relaxed);
+        PendingAcquire
+    }
+}
+
+impl Drop for PendingAcquire {
+    fn drop(&mut self) {
+        PENDING_ACQUIRES.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Take a connection from the pool, recording pending-acquire count and wait time.
+pub async fn acquire<DB: PoolName>(pool: &Pool<DB>) -> Result<PoolConnection<DB>, DomainError> {
+    let pending = PendingAcquire::start();
+    let start = Instant::now();
+    let conn = pool.acquire().await;
+    drop(pending);
+
+    wait_time().record(
+        start.elapsed().as_secs_f64(),
+        &[KeyValue::new("db.client.connection.pool.name", DB::POOL_NAME)],
+    );
+    conn.map_err(|e| DomainError::Unexpected(e.to_string()))
+}
+
+/// Register observable gauges for the connection pool.
+///
+/// Metrics emitted:
+/// - `db.client.connection.count`            (`db.client.connection.state` = idle | used)
+/// - `db.client.connection.max`
+/// - `db.client.connection.pending_requests`
+/// - `db.client.connection.wait_time`        (histogram, seconds — see [`acquire`])
+pub fn register_pool_metrics<DB: PoolName>(pool: &Pool<DB>) {
+    let meter = global::meter("rust-just-learn");
+    let pool_attr = KeyValue::new("db.client.connection.pool.name", DB::POOL_NAME);
+
+    let count_pool = pool.clone();
+    let count_attr = pool_attr.clone();
+    meter
+        .i64_observable_gauge("db.client.connection.count")
+        .with_description("Number of connections that are currently in the state described by the state attribute")
+        .with_unit("{connection}")
+        .with_callback(move |observer| {
+            let size = count_pool.size() as i64;
+            let idle = count_pool.num_idle() as i64;
+            observer.observe(
+                idle,
+                &[count_attr.clone(), KeyValue::new("db.client.connection.state", "idle")],
+            );
+            observer.observe(
+                size - idle,
+                &[count_attr.clone(), KeyValue::new("db.client.connection.state", "used")],
+            );
+        })
+        .build();
+
+    let max = pool.options().get_max_connections() as i64;
+    let max_attrs = [pool_attr.clone()];
+    meter
+        .i64_observable_gauge("db.client.connection.max")
+        .with_description("The maximum number of open connections allowed")
+        .with_unit("{connection}")
+        .with_callback(move |observer| observer.observe(max, &max_attrs))
+        .build();
+
+    let pending_attrs = [pool_attr];
+    meter
+        .i64_observable_gauge("db.client.connection.pending_requests")
+        .with_description("Number of pending requests for an open connection")
+        .with_unit("{request}")
+        .with_callback(move |observer| {
+            observer.observe(PENDING_ACQUIRES.load(Ordering::Relaxed), &pending_attrs)
+        })
+        .build();
+}
@@ -0,0 +1,33 @@
+use super::sanitize_query;
+
+#[test]
+fn strings_become_placeholders_including_escaped_quotes() {
+    assert_eq!(sanitize_query("SELECT * FROM users WHERE name = 'alice'"), "SELECT * FROM users WHERE name = ?");
+    assert_eq!(sanitize_query("WHERE name = 'O''Brien' AND x = 'a'"), "WHERE name = ? AND x = ?");
+    assert_eq!(sanitize_query("VALUES ('''', 'it''s ''quoted''')"), "VALUES (?, ?)");
+}
+
+#[test]
+fn numbers_become_placeholders_but_binds_stay() {
+    assert_eq!(sanitize_query("SELECT * FROM products WHERE id = 42"), "SELECT * FROM products WHERE id = ?");
+    assert_eq!(sanitize_query("WHERE id = $1 AND stock > $12"), "WHERE id = $1 AND stock > $12");
+    assert_eq!(sanitize_query("WHERE id = ?1 LIMIT 10"), "WHERE id = ?1 LIMIT ?");
+    assert_eq!(sanitize_query("LIMIT $2 OFFSET 20"), "LIMIT $2 OFFSET ?");
+}
+
+#[test]
+fn identifiers_with_digits_are_kept() {
+    assert_eq!(sanitize_query("SELECT t1.col2, t2.x FROM t1 JOIN t2 ON t1.id = t2.id"), "SELECT t1.col2, t2.x FROM t1 JOIN t2 ON t1.id = t2.id");
+    assert_eq!(sanitize_query("SELECT price_2024 FROM v2_orders"), "SELECT price_2024 FROM v2_orders");
+}
+
+#[test]
+fn negative_and_decimal_numbers_are_hidden() {
+    assert_eq!(sanitize_query("SET price = 12.50 WHERE stock > -3"), "SET price = ? WHERE stock > -?");
+    assert_eq!(sanitize_query("WHERE value BETWEEN -0.5 AND 100.25"), "WHERE value BETWEEN -? AND ?");
+}
+
+#[test]
+fn whitespace_is_collapsed() {
+    assert_eq!(sanitize_query("\n  SELECT id\n    FROM users\n   WHERE id = $1\n  "), "SELECT id FROM users WHERE id = $1");
+}
@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+use tokio::sync::broadcast;
+
+use crate::domain::event::{EventSink, OutboxMessage};
+use crate::domain::DomainError;
+
+/// In-process fan-out to [`InProcessBus::subscribe`]rs. Slow subscribers
+/// that fall more than `capacity` messages behind miss the oldest ones.
+#[derive(Clone)]
+pub struct InProcessBus {
+    sender: broadcast::Sender<OutboxMessage>,
+}
+
+impl InProcessBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<OutboxMessage> {
+        self.sender.subscribe()
+    }
+}
+
+#[async_trait]
+impl EventSink for InProcessBus {
+    fn name(&self) -> &'static str {
+        "bus"
+    }
+
+    async fn publish(&self, message: &OutboxMessage) -> Result<(), DomainError> {
+        // no subscribers is not a failure
+        let _ = self.sender.send(message.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+//! Event sinks for the outbox relay.
+//!
+//! Configuration:
+//! - `OUTBOX_SINKS`               — comma-separated: `bus`, `webhook`, `nats` (default `bus`)
+//! - `OUTBOX_WEBHOOK_URL`         — required for `webhook`
+//! - `OUTBOX_WEBHOOK_SECRET`      — optional; signs bodies (`X-Signature: sha256=<hex>`)
+//! - `OUTBOX_NATS_URL`            — required for `nats`, e.g. `nats://localhost:4222`
+//! - `OUTBOX_NATS_SUBJECT_PREFIX` — default `events`
+
+use std::env;
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+
+use crate::domain::event::{EventSink, OutboxMessage};
+
+pub mod bus;
+pub mod nats;
+pub mod webhook;
+
+pub use bus::InProcessBus;
+pub use nats::NatsSink;
+pub use webhook::WebhookSink;
+
+type ConfigError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Wire format shared by the webhook and NATS sinks.
+pub fn envelope(message: &OutboxMessage) -> Value {
+    json!({
+        "id": message.id,
+        "type": message.event_type,
+        "aggregate_type": message.aggregate_type,
+        "aggregate_id": message.aggregate_id,
+        "data": message.payload,
+    })
+}
+
+/// Sinks named in `OUTBOX_SINKS`; `bus` publishes to `bus`.
+pub fn sinks_from_env(bus: &InProcessBus) -> Result<Vec<Arc<dyn EventSink>>, ConfigError> {
+    let names = env::var("OUTBOX_SINKS").unwrap_or_else(|_| "bus".into());
+    let mut sinks: Vec<Arc<dyn EventSink>> = Vec::new();
+    for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        match name {
+            "bus" => sinks.push(Arc::new(bus.clone())),
+            "webhook" => {
+                let url = env::var("OUTBOX_WEBHOOK_URL").map_err(|_| "OUTBOX_WEBHOOK_URL must be set")?;
+                let secret = env::var("OUTBOX_WEBHOOK_SECRET").ok().filter(|s| !s.is_empty());
+                sinks.push(Arc::new(WebhookSink::new(url, secret)?));
+            }
+            "nats" => {
+                let url = env::var("OUTBOX_NATS_URL").map_err(|_| "OUTBOX_NATS_URL must be set")?;
+                let prefix = env::var("OUTBOX_NATS_SUBJECT_PREFIX").unwrap_or_else(|_| "events".into());
+                sinks.push(Arc::new(NatsSink::new(&url, prefix)));
+            }
+            other => return Err(format!("unknown outbox sink: {other}").into()),
+        }
+    }
+    Ok(sinks)
+}
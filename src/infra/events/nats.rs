@@ -0,0 +1,104 @@
+//! Minimal NATS publisher (core protocol over TCP), enough for a local
+//! `nats-server` standing in for a real broker:
+//! https://docs.nats.io/reference/reference-protocols/nats-protocol
+
+use std::io;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufStream};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+
+use crate::domain::event::{EventSink, OutboxMessage};
+use crate::domain::DomainError;
+
+use super::envelope;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Publishes to `<prefix>.<aggregate_type>.<event_type>`. Each publish ends
+/// with a PING and waits for the PONG, so `Ok` means the server has the
+/// message. The connection is reopened after any error.
+pub struct NatsSink {
+    address: String,
+    subject_prefix: String,
+    conn: Mutex<Option<BufStream<TcpStream>>>,
+}
+
+impl NatsSink {
+    /// `url` is `nats://host[:port]` (port defaults to 4222).
+    pub fn new(url: &str, subject_prefix: String) -> Self {
+        let host = url.strip_prefix("nats://").unwrap_or(url).trim_end_matches('/');
+        let address = if host.contains(':') { host.to_string() } else { format!("{host}:4222") };
+        Self { address, subject_prefix, conn: Mutex::new(None) }
+    }
+
+    async fn connect(&self) -> io::Result<BufStream<TcpStream>> {
+        let mut stream = BufStream::new(TcpStream::connect(&self.address).await?);
+        let mut info = String::new();
+        stream.read_line(&mut info).await?;
+        if !info.starts_with("INFO") {
+            return Err(io::Error::other(format!("unexpected greeting: {}", info.trim_end())));
+        }
+        stream
+            .write_all(br#"CONNECT {"verbose":false,"pedantic":false,"name":"rust-just-learn","lang":"rust"}"#)
+            .await?;
+        stream.write_all(b"\r\n").await?;
+        stream.flush().await?;
+        Ok(stream)
+    }
+
+    async fn send(stream: &mut BufStream<TcpStream>, subject: &str, payload: &[u8]) -> io::Result<()> {
+        stream.write_all(format!("PUB {subject} {}\r\n", payload.len()).as_bytes()).await?;
+        stream.write_all(payload).await?;
+        stream.write_all(b"\r\nPING\r\n").await?;
+        stream.flush().await?;
+
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if stream.read_line(&mut line).await? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            match line.trim_end() {
+                "PONG" => return Ok(()),
+                "PING" => {
+                    stream.write_all(b"PONG\r\n").await?;
+                    stream.flush().await?;
+                }
+                err if err.starts_with("-ERR") => return Err(io::Error::other(err.to_string())),
+                // +OK, INFO updates
+                _ => {}
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl EventSink for NatsSink {
+    fn name(&self) -> &'static str {
+        "nats"
+    }
+
+    async fn publish(&self, message: &OutboxMessage) -> Result<(), DomainError> {
+        let subject = format!("{}.{}.{}", self.subject_prefix, message.aggregate_type, message.event_type);
+        let payload = serde_json::to_vec(&envelope(message)).map_err(|e| DomainError::Unexpected(e.to_string()))?;
+
+        let mut conn = self.conn.lock().await;
+        let result = tokio::time::timeout(TIMEOUT, async {
+            if conn.is_none() {
+                *conn = Some(self.connect().await?);
+            }
+            let stream = conn.as_mut().expect("connected above");
+            Self::send(stream, &subject, &payload).await
+        })
+        .await
+        .unwrap_or_else(|_| Err(io::ErrorKind::TimedOut.into()));
+
+        result.map_err(|e| {
+            *conn = None;
+            DomainError::Unexpected(format!("nats publish failed: {e}"))
+        })
+    }
+}
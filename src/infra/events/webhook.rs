@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::http::{header, HeaderMap, HeaderValue};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::domain::event::{EventSink, OutboxMessage};
+use crate::domain::{DomainError, Secret};
+use crate::infra::propagation::inject_context;
+
+use super::envelope;
+
+/// POSTs each event as JSON. Any non-2xx response counts as a failure and
+/// the event is sent again later; receivers dedupe on `X-Event-Id`.
+pub struct WebhookSink {
+    client: reqwest::Client,
+    url: String,
+    secret: Option<Secret<String>>,
+}
+
+impl WebhookSink {
+    pub fn new(url: String, secret: Option<String>) -> Result<Self, reqwest::Error> {
+        let client = reqwest::Client::builder().timeout(Duration::from_secs(10)).build()?;
+        Ok(Self { client, url, secret: secret.map(Secret::from) })
+    }
+
+    fn signature(&self, body: &[u8]) -> Option<String> {
+        let secret = self.secret.clone()?.into_inner();
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).ok()?;
+        mac.update(body);
+        Some(format!("sha256={}", hex::encode(mac.finalize().into_bytes())))
+    }
+}
+
+#[async_trait]
+impl EventSink for WebhookSink {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    async fn publish(&self, message: &OutboxMessage) -> Result<(), DomainError> {
+        let body = serde_json::to_vec(&envelope(message)).map_err(|e| DomainError::Unexpected(e.to_string()))?;
+
+        let mut headers = HeaderMap::new();
+        inject_context(&mut headers);
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers.insert("X-Event-Id", HeaderValue::from(message.id));
+        if let Ok(event_type) = HeaderValue::from_str(&message.event_type) {
+            headers.insert("X-Event-Type", event_type);
+        }
+        if let Some(signature) = self.signature(&body).and_then(|s| HeaderValue::from_str(&s).ok()) {
+            headers.insert("X-Signature", signature);
+        }
+
+        let response = self
+            .client
+            .post(&self.url)
+            .headers(headers)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| DomainError::Unexpected(format!("webhook request failed: {e}")))?;
+        if !response.status().is_success() {
+            return Err(DomainError::Unexpected(format!("webhook returned {}", response.status())));
+        }
+        Ok(())
+    }
+}
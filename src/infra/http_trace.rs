@@ -1,21 +1,81 @@
 //! HTTP tracing & metrics middleware for Axum.
-//! Metric names follow OTel Semantic Conventions:
+//! Metric and attribute names follow OTel Semantic Conventions:
 //! https://opentelemetry.io/docs/specs/semconv/http/http-metrics/
+//! https://opentelemetry.io/docs/specs/semconv/http/http-spans/
 
-use opentelemetry::{global, trace::Status as OtelStatus, KeyValue};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use axum::{
+    body::HttpBody,
+    extract::{MatchedPath, Request},
+    http::{header, HeaderMap, HeaderValue, Uri},
+    middleware::Next,
+    response::Response,
+};
+use opentelemetry::{
+    global,
+    metrics::{Histogram, Meter, UpDownCounter},
+    trace::Status as OtelStatus,
+    KeyValue,
+};
+use tracing::field::Empty;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
-// ---- Span status + auto metrics -----------------------------------------
+use crate::infra::propagation::{current_trace_response, extract_context};
+
+#[cfg(test)]
+mod tests;
 
-/// Sets OTel span status and records standard HTTP server metrics.
+// ---- Span creation ----------------------------------------------------------
+
+/// Creates the per-request server span with standard HTTP attributes.
 ///
-/// Metrics emitted (per OTel Semantic Conventions):
-/// - `http.server.request.duration` (histogram, seconds)
-/// - `http.server.error.duration`   (histogram, seconds — 5xx only)
+/// `http.route` comes from axum's `MatchedPath`, so the span name is the
+/// route template (`GET /users/:id`) rather than the raw path.
+/// `enduser.id` is left empty here and recorded by the `Claims` extractor
+/// once the bearer token has been verified; `request.id` is recorded by the
+/// request-id middleware.
 ///
-/// Attributes:
-/// - `http.response.status_code`
-/// - `http.request.method`  (if accessible via span)
+/// An incoming `traceparent` / `tracestate` becomes the parent of the span, so
+/// calls from the gateway continue the caller's trace.
+#[derive(Clone)]
+pub struct OtelMakeSpan;
+
+impl<B> tower_http::trace::MakeSpan<B> for OtelMakeSpan {
+    fn make_span(&mut self, request: &Request<B>) -> tracing::Span {
+        let method = request.method().as_str();
+        let route = matched_route(request);
+        let span_name = match route {
+            Some(route) => format!("{method} {route}"),
+            None => method.to_string(),
+        };
+
+        let span = tracing::info_span!(
+            "HTTP request",
+            otel.name = %span_name,
+            otel.kind = "server",
+            http.request.method = %method,
+            http.route = route,
+            url.path = %request.uri().path(),
+            url.scheme = url_scheme(request.headers(), request.uri()),
+            server.address = header_str(request.headers(), header::HOST),
+            user_agent.original = header_str(request.headers(), header::USER_AGENT),
+            enduser.id = Empty,
+            request.id = Empty,
+            http.response.status_code = Empty,
+        );
+
+        if let Err(e) = span.set_parent(extract_context(request.headers())) {
+            tracing::debug!(error = %e, "failed to set remote parent on request span");
+        }
+        span
+    }
+}
+
+// ---- Span status -------------------------------------------------------------
+
+/// Sets OTel span status and records the response status code on the span.
 #[derive(Clone)]
 pub struct OtelOnResponse;
 
@@ -23,82 +83,194 @@ impl<B> tower_http::trace::OnResponse<B> for OtelOnResponse {
     fn on_response(
         self,
         response: &axum::http::Response<B>,
-        latency: std::time::Duration,
+        _latency: std::time::Duration,
         span: &tracing::Span,
     ) {
         let http_status = response.status();
         let status_code = http_status.as_u16();
-        // OTel standard: duration in SECONDS (not milliseconds)
-        let duration_secs = latency.as_secs_f64();
 
-        // --- OTel span status ---
+        span.record("http.response.status_code", status_code);
         if http_status.is_server_error() {
             span.set_status(OtelStatus::error(format!("HTTP {}", status_code)));
         } else {
             span.set_status(OtelStatus::Ok);
         }
+    }
+}
+
+// ---- Trace response headers ----------------------------------------------------
+
+/// Axum middleware adding `traceresponse` and `X-Trace-Id` to every response,
+/// so callers can look the request up in HyperDX.
+///
+/// Must run inside the `TraceLayer` span.
+pub async fn trace_response_headers(request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+
+    if let Some((trace_id, trace_response)) = current_trace_response() {
+        let headers = response.headers_mut();
+        if let Ok(v) = HeaderValue::from_str(&trace_response) {
+            headers.insert("traceresponse", v);
+        }
+        if let Ok(v) = HeaderValue::from_str(&trace_id) {
+            headers.insert("X-Trace-Id", v);
+        }
+    }
+    response
+}
 
-        // --- OTel standard attributes ---
-        let attrs = &[KeyValue::new("http.response.status_code", status_code as i64)];
-        let meter = global::meter("rust-just-learn");
+// ---- Metrics -------------------------------------------------------------------
 
-        // http.server.request.duration — REQUIRED by OTel spec
-        meter
-            .f64_histogram("http.server.request.duration")
-            .with_description("Duration of HTTP server requests")
-            .with_unit("s")
-            .build()
-            .record(duration_secs, attrs);
+/// HTTP server instruments, built once and shared by every request.
+///
+/// Metrics emitted (per OTel Semantic Conventions):
+/// - `http.server.request.duration`  (histogram, seconds)
+/// - `http.server.active_requests`   (up-down counter)
+/// - `http.server.request.body.size` (histogram, bytes)
+/// - `http.server.response.body.size` (histogram, bytes)
+/// - `http.server.error.duration`    (histogram, seconds — 5xx only, custom extension)
+struct HttpMetrics {
+    request_duration: Histogram<f64>,
+    error_duration: Histogram<f64>,
+    active_requests: UpDownCounter<i64>,
+    request_body_size: Histogram<u64>,
+    response_body_size: Histogram<u64>,
+}
 
-        // http.server.error.duration — 5xx only (custom extension)
-        if http_status.is_server_error() {
-            meter
+impl HttpMetrics {
+    fn get() -> &'static HttpMetrics {
+        static METRICS: OnceLock<HttpMetrics> = OnceLock::new();
+        METRICS.get_or_init(|| HttpMetrics::new(&global::meter("rust-just-learn")))
+    }
+
+    fn new(meter: &Meter) -> Self {
+        HttpMetrics {
+            request_duration: meter
+                .f64_histogram("http.server.request.duration")
+                .with_description("Duration of HTTP server requests")
+                .with_unit("s")
+                .build(),
+            error_duration: meter
                 .f64_histogram("http.server.error.duration")
                 .with_description("Duration of HTTP 5xx server error requests")
                 .with_unit("s")
-                .build()
-                .record(duration_secs, attrs);
-            // --- log event inside span ---
-            /*
-            tracing::error!(
-                "http.response.status_code" = status_code,
-                latency_ms = latency.as_millis(),
-                "HTTP {} in {}ms",
-                status_code,
-                latency.as_millis(),
-            );
-            */
-        } 
-        /*
-        else {
-            // --- log event inside span ---
-            tracing::info!(
-                "http.response.status_code" = status_code,
-                latency_ms = latency.as_millis(),
-                "HTTP {} in {}ms",
-                status_code,
-                latency.as_millis(),
-            );
+                .build(),
+            active_requests: meter
+                .i64_up_down_counter("http.server.active_requests")
+                .with_description("Number of active HTTP server requests")
+                .with_unit("{request}")
+                .build(),
+            request_body_size: meter
+                .u64_histogram("http.server.request.body.size")
+                .with_description("Size of HTTP server request bodies")
+                .with_unit("By")
+                .build(),
+            response_body_size: meter
+                .u64_histogram("http.server.response.body.size")
+                .with_description("Size of HTTP server response bodies")
+                .with_unit("By")
+                .build(),
         }
-        */
     }
 }
 
-// ---- Metrics helpers -------------------------------------------------------
+/// One request counted in `http.server.active_requests` until dropped, so a
+/// request whose future is dropped half way (client gone, timeout, shutdown)
+/// is counted out as well.
+struct ActiveRequest<'a> {
+    counter: &'a UpDownCounter<i64>,
+    attrs: [KeyValue; 2],
+}
+
+impl<'a> ActiveRequest<'a> {
+    fn start(counter: &'a UpDownCounter<i64>, attrs: [KeyValue; 2]) -> Self {
+        counter.add(1, &attrs);
+        ActiveRequest { counter, attrs }
+    }
+}
+
+impl Drop for ActiveRequest<'_> {
+    fn drop(&mut self) {
+        self.counter.add(-1, &self.attrs);
+    }
+}
+
+/// Axum middleware recording the HTTP server metrics above for every request,
+/// labelled with `http.route` from `MatchedPath`.
+///
+/// Must be added with `Router::layer` so it runs after routing.
+pub async fn track_http_metrics(request: Request, next: Next) -> Response {
+    track(HttpMetrics::get(), request, next).await
+}
+
+async fn track(metrics: &HttpMetrics, request: Request, next: Next) -> Response {
+    let method = request.method().as_str().to_string();
+    let scheme = url_scheme(request.headers(), request.uri());
+    let route = matched_route(&request).map(str::to_string);
+    let request_size = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+
+    let active_attrs = [
+        KeyValue::new("http.request.method", method.clone()),
+        KeyValue::new("url.scheme", scheme),
+    ];
+    let active = ActiveRequest::start(&metrics.active_requests, active_attrs);
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+    // OTel standard: duration in SECONDS (not milliseconds)
+    let duration_secs = start.elapsed().as_secs_f64();
+
+    drop(active);
 
-/// Manually record a request with full OTel-standard attributes.
-/// Use when you need finer-grained `http.route` labeling per handler.
-pub fn record_http_request(method: &str, route: &str, status: u16, duration_secs: f64) {
-    let meter = global::meter("rust-just-learn");
-    let attrs = &[
-        KeyValue::new("http.request.method", method.to_string()),
-        KeyValue::new("http.route", route.to_string()),
-        KeyValue::new("http.response.status_code", status as i64),
+    let status = response.status();
+    let mut attrs = vec![
+        KeyValue::new("http.request.method", method),
+        KeyValue::new("url.scheme", scheme),
+        KeyValue::new("http.response.status_code", status.as_u16() as i64),
     ];
-    meter
-        .f64_histogram("http.server.request.duration")
-        .with_description("Duration of HTTP server requests")
-        .with_unit("s")
-        .build()
-        .record(duration_secs, attrs);
+    if let Some(route) = route {
+        attrs.push(KeyValue::new("http.route", route));
+    }
+
+    metrics.request_duration.record(duration_secs, &attrs);
+    if status.is_server_error() {
+        metrics.error_duration.record(duration_secs, &attrs);
+    }
+    if let Some(size) = request_size {
+        metrics.request_body_size.record(size, &attrs);
+    }
+    if let Some(size) = response.body().size_hint().exact() {
+        metrics.response_body_size.record(size, &attrs);
+    }
+
+    response
+}
+
+// ---- Helpers -------------------------------------------------------------------
+
+fn matched_route<B>(request: &Request<B>) -> Option<&str> {
+    request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(MatchedPath::as_str)
+}
+
+/// Scheme as seen by the client: the request URI is usually origin-form on the
+/// server side, so fall back to `X-Forwarded-Proto` from the proxy.
+fn url_scheme(headers: &HeaderMap, uri: &Uri) -> &'static str {
+    let scheme = uri
+        .scheme_str()
+        .or_else(|| headers.get("X-Forwarded-Proto").and_then(|v| v.to_str().ok()));
+    match scheme {
+        Some(s) if s.eq_ignore_ascii_case("https") => "https",
+        _ => "http",
+    }
+}
+
+fn header_str(headers: &HeaderMap, name: header::HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
 }
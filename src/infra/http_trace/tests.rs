@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{middleware, Router};
+use opentelemetry::metrics::MeterProvider;
+use opentelemetry_sdk::metrics::data::{AggregatedMetrics, MetricData};
+use opentelemetry_sdk::metrics::{InMemoryMetricExporter, PeriodicReader, SdkMeterProvider};
+use tower::ServiceExt;
+use tower_http::trace::TraceLayer;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Registry;
+
+use super::{track, HttpMetrics, OtelMakeSpan, OtelOnResponse};
+
+/// [`HttpMetrics`] on a meter of their own, read back through an in-memory
+/// exporter.
+struct TestMetrics {
+    metrics: Arc<HttpMetrics>,
+    provider: SdkMeterProvider,
+    exporter: InMemoryMetricExporter,
+}
+
+impl TestMetrics {
+    fn new() -> Self {
+        let exporter = InMemoryMetricExporter::default();
+        let provider = SdkMeterProvider::builder().with_reader(PeriodicReader::builder(exporter.clone()).build()).build();
+        let metrics = Arc::new(HttpMetrics::new(&provider.meter("test")));
+        TestMetrics { metrics, provider, exporter }
+    }
+
+    /// `router` with the metrics middleware, as `with_observability` adds it.
+    fn layer(&self, router: Router) -> Router {
+        let metrics = self.metrics.clone();
+        router.layer(middleware::from_fn(move |request, next| {
+            let metrics = metrics.clone();
+            async move { track(&metrics, request, next).await }
+        }))
+    }
+
+    /// The data points of metric `name` as sorted `key=value` attributes and
+    /// the sum's value or the histogram's count.
+    fn points(&self, name: &str) -> Vec<(Vec<String>, i64)> {
+        self.exporter.reset();
+        self.provider.force_flush().unwrap();
+        let exported = self.exporter.get_finished_metrics().unwrap();
+        let attributes = |attrs: &mut dyn Iterator<Item = &opentelemetry::KeyValue>| {
+            let mut attrs: Vec<_> = attrs.map(|kv| format!("{}={}", kv.key, kv.value)).collect();
+            attrs.sort();
+            attrs
+        };
+        let mut points = Vec::new();
+        for metric in exported.iter().flat_map(|rm| rm.scope_metrics()).flat_map(|sm| sm.metrics()) {
+            if metric.name() != name {
+                continue;
+            }
+            match metric.data() {
+                AggregatedMetrics::I64(MetricData::Sum(sum)) => {
+                    points.extend(sum.data_points().map(|p| (attributes(&mut p.attributes()), p.value())));
+                }
+                AggregatedMetrics::F64(MetricData::Histogram(histogram)) => {
+                    points.extend(histogram.data_points().map(|p| (attributes(&mut p.attributes()), p.count() as i64)));
+                }
+                data => panic!("unexpected data for {name}: {data:?}"),
+            }
+        }
+        points.sort();
+        points
+    }
+}
+
+fn request(uri: &str) -> Request {
+    Request::get(uri).body(Body::empty()).unwrap()
+}
+
+fn routes() -> Router {
+    Router::new()
+        .route("/users/:id", get(|| async { "alice" }))
+        .route("/boom", get(|| async { (StatusCode::INTERNAL_SERVER_ERROR, "boom") }))
+}
+
+/// The fields of every request span, as recorded by the time it closes.
+#[derive(Clone, Default)]
+struct RequestSpans(Arc<Mutex<Vec<BTreeMap<String, String>>>>);
+
+struct SpanFields(BTreeMap<String, String>);
+
+impl Visit for SpanFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().to_string(), format!("{value:?}"));
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for RequestSpans {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().name() != "HTTP request" {
+            return;
+        }
+        let mut fields = SpanFields(BTreeMap::new());
+        attrs.record(&mut fields);
+        ctx.span(id).unwrap().extensions_mut().insert(fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(fields) = ctx.span(id).unwrap().extensions_mut().get_mut::<SpanFields>() {
+            values.record(fields);
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        if let Some(fields) = ctx.span(&id).unwrap().extensions_mut().remove::<SpanFields>() {
+            self.0.lock().unwrap().push(fields.0);
+        }
+    }
+}
+
+#[tokio::test]
+async fn spans_are_named_after_the_matched_route() {
+    let spans = RequestSpans::default();
+    let _guard = tracing::subscriber::set_default(Registry::default().with(spans.clone()));
+    let app = routes().layer(TraceLayer::new_for_http().make_span_with(OtelMakeSpan).on_response(OtelOnResponse));
+
+    assert_eq!(app.clone().oneshot(request("/users/42")).await.unwrap().status(), StatusCode::OK);
+    assert_eq!(app.oneshot(request("/nope")).await.unwrap().status(), StatusCode::NOT_FOUND);
+
+    let spans = spans.0.lock().unwrap().clone();
+    let field = |span: &BTreeMap<String, String>, name: &str| span.get(name).cloned();
+    assert_eq!(spans.len(), 2);
+    assert_eq!(field(&spans[0], "otel.name").as_deref(), Some("GET /users/:id"));
+    assert_eq!(field(&spans[0], "http.route").as_deref(), Some("/users/:id"));
+    assert_eq!(field(&spans[0], "url.path").as_deref(), Some("/users/42"));
+    assert_eq!(field(&spans[0], "http.response.status_code").as_deref(), Some("200"));
+    // no route matched: named after the method only, no http.route
+    assert_eq!(field(&spans[1], "otel.name").as_deref(), Some("GET"));
+    assert_eq!(field(&spans[1], "http.route"), None);
+    assert_eq!(field(&spans[1], "url.path").as_deref(), Some("/nope"));
+    assert_eq!(field(&spans[1], "http.response.status_code").as_deref(), Some("404"));
+}
+
+#[tokio::test]
+async fn request_metrics_carry_route_and_status() {
+    let metrics = TestMetrics::new();
+    let app = metrics.layer(routes());
+    for uri in ["/users/42", "/users/7", "/boom", "/nope"] {
+        app.clone().oneshot(request(uri)).await.unwrap();
+    }
+
+    let attrs = |extra: &[&str]| {
+        let mut attrs: Vec<_> = ["http.request.method=GET", "url.scheme=http"].iter().chain(extra).map(|a| a.to_string()).collect();
+        attrs.sort();
+        attrs
+    };
+    assert_eq!(
+        metrics.points("http.server.request.duration"),
+        [
+            (attrs(&["http.response.status_code=200", "http.route=/users/:id"]), 2),
+            (attrs(&["http.response.status_code=404"]), 1),
+            (attrs(&["http.response.status_code=500", "http.route=/boom"]), 1),
+        ]
+    );
+    assert_eq!(
+        metrics.points("http.server.error.duration"),
+        [(attrs(&["http.response.status_code=500", "http.route=/boom"]), 1)]
+    );
+    assert_eq!(metrics.points("http.server.active_requests"), [(attrs(&[]), 0)]);
+}
+
+#[tokio::test]
+async fn requests_dropped_half_way_are_no_longer_active() {
+    let metrics = TestMetrics::new();
+    let app = metrics.layer(Router::new().route("/slow", get(std::future::pending::<&'static str>)));
+
+    // the client gives up
+    assert!(tokio::time::timeout(Duration::from_millis(20), app.oneshot(request("/slow"))).await.is_err());
+    let active = metrics.points("http.server.active_requests");
+    assert_eq!(active, [(vec!["http.request.method=GET".to_string(), "url.scheme=http".to_string()], 0)]);
+    assert!(metrics.points("http.server.request.duration").is_empty());
+}
@@ -0,0 +1,110 @@
+//! Spans and metrics for background jobs, exported through the same OTel
+//! pipeline as the HTTP and database telemetry.
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use chrono::Utc;
+use opentelemetry::{
+    global,
+    metrics::{Histogram, UpDownCounter},
+    KeyValue,
+};
+use tracing::field::Empty;
+
+use crate::domain::job::Job;
+use crate::infra::propagation::set_parent_from_traceparent;
+
+/// Consumer span for one run of `job`, a child of the trace that enqueued it.
+/// `job.outcome` is recorded when the run ends.
+pub fn job_span(job: &Job) -> tracing::Span {
+    let span = tracing::info_span!(
+        "job",
+        otel.name = format!("process {}", job.kind),
+        otel.kind = "consumer",
+        messaging.operation.type = "process",
+        messaging.destination.name = %job.queue,
+        messaging.message.id = job.id,
+        job.kind = %job.kind,
+        job.attempt = job.attempts,
+        job.max_attempts = job.max_attempts,
+        job.outcome = Empty,
+    );
+    if let Some(traceparent) = &job.trace_context {
+        set_parent_from_traceparent(&span, traceparent);
+    }
+    span
+}
+
+/// Job instruments, built once.
+///
+/// Metrics emitted:
+/// - `job.duration`     (histogram, seconds; by queue, kind and outcome)
+/// - `job.lag`          (histogram, seconds from `run_at` to claim)
+/// - `job.active`       (up-down counter of running jobs per queue)
+struct JobMetrics {
+    duration: Histogram<f64>,
+    lag: Histogram<f64>,
+    active: UpDownCounter<i64>,
+}
+
+impl JobMetrics {
+    fn get() -> &'static JobMetrics {
+        static METRICS: OnceLock<JobMetrics> = OnceLock::new();
+        METRICS.get_or_init(|| {
+            let meter = global::meter("rust-just-learn");
+            JobMetrics {
+                duration: meter
+                    .f64_histogram("job.duration")
+                    .with_description("Duration of background job runs")
+                    .with_unit("s")
+                    .build(),
+                lag: meter
+                    .f64_histogram("job.lag")
+                    .with_description("Time between a job becoming due and a worker claiming it")
+                    .with_unit("s")
+                    .build(),
+                active: meter
+                    .i64_up_down_counter("job.active")
+                    .with_description("Number of background jobs currently running")
+                    .with_unit("{job}")
+                    .build(),
+            }
+        })
+    }
+}
+
+/// Counts a job as running until dropped; records its claim lag on start.
+pub struct ActiveJob {
+    queue: KeyValue,
+}
+
+impl ActiveJob {
+    pub fn start(job: &Job) -> Self {
+        let metrics = JobMetrics::get();
+        let queue = KeyValue::new("job.queue", job.queue.clone());
+        let lag = (Utc::now() - job.run_at).to_std().unwrap_or_default();
+        metrics.lag.record(lag.as_secs_f64(), std::slice::from_ref(&queue));
+        metrics.active.add(1, std::slice::from_ref(&queue));
+        ActiveJob { queue }
+    }
+
+    /// `outcome` is `completed`, `retried` or `dead`.
+    pub fn finish(self, kind: &str, outcome: &'static str, duration: Duration) {
+        tracing::Span::current().record("job.outcome", outcome);
+        JobMetrics::get().duration.record(
+            duration.as_secs_f64(),
+            &[
+                self.queue.clone(),
+                KeyValue::new("job.kind", kind.to_string()),
+                KeyValue::new("job.outcome", outcome),
+            ],
+        );
+    }
+}
+
+impl Drop for ActiveJob {
+    fn drop(&mut self) {
+        JobMetrics::get().active.add(-1, std::slice::from_ref(&self.queue));
+    }
+}
@@ -1,5 +1,13 @@
+pub mod cache;
 pub mod crypto;
+pub mod db_trace;
+pub mod events;
 pub mod jwt;
+pub mod payment;
 pub mod repository;
 pub mod telemetry;
-pub mod http_trace;
\ No newline at end of file
+pub mod http_trace;
+pub mod job_trace;
+pub mod propagation;
+pub mod redact;
+pub mod request_context;
\ No newline at end of file
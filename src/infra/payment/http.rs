@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::http::{header, HeaderMap, HeaderValue};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::payment::{Authorization, PaymentProvider, WebhookEvent};
+use crate::domain::{DomainError, Secret};
+use crate::infra::propagation::inject_context;
+
+use super::verify_webhook;
+
+#[derive(Serialize)]
+struct AuthorizeReq<'a> {
+    reference: &'a str,
+    amount: i64,
+    currency: &'a str,
+}
+
+#[derive(Deserialize)]
+struct AuthorizeResp {
+    id: String,
+    /// `authorized` or `declined`.
+    status: String,
+    decline_reason: Option<String>,
+}
+
+#[derive(Serialize)]
+struct AmountReq {
+    amount: i64,
+}
+
+/// Provider behind a JSON API:
+///
+/// - `POST {url}/authorizations` `{"reference", "amount", "currency"}` answers
+///   `{"id", "status": "authorized" | "declined", "decline_reason"}`
+/// - `POST {url}/authorizations/{id}/capture` `{"amount"}`
+/// - `POST {url}/authorizations/{id}/refund` `{"amount"}`
+///
+/// Requests carry `Authorization: Bearer <api key>` and an `Idempotency-Key`;
+/// any non-2xx answer is an error.
+pub struct HttpPaymentProvider {
+    client: reqwest::Client,
+    url: String,
+    api_key: Secret<String>,
+    webhook_secret: Secret<String>,
+    currency: String,
+}
+
+impl HttpPaymentProvider {
+    pub fn new(url: String, api_key: String, webhook_secret: String, currency: String) -> Result<Self, reqwest::Error> {
+        let client = reqwest::Client::builder().timeout(Duration::from_secs(10)).build()?;
+        Ok(Self {
+            client,
+            url: url.trim_end_matches('/').to_string(),
+            api_key: Secret::from(api_key),
+            webhook_secret: Secret::from(webhook_secret),
+            currency,
+        })
+    }
+
+    async fn post(&self, path: &str, idempotency_key: &str, body: &impl Serialize) -> Result<Vec<u8>, DomainError> {
+        let body = serde_json::to_vec(body).map_err(|e| DomainError::Unexpected(e.to_string()))?;
+
+        let mut headers = HeaderMap::new();
+        inject_context(&mut headers);
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let bearer = format!("Bearer {}", self.api_key.clone().into_inner());
+        if let Ok(value) = HeaderValue::from_str(&bearer) {
+            headers.insert(header::AUTHORIZATION, value);
+        }
+        if let Ok(value) = HeaderValue::from_str(idempotency_key) {
+            headers.insert("Idempotency-Key", value);
+        }
+
+        let response = self
+            .client
+            .post(format!("{}{path}", self.url))
+            .headers(headers)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| DomainError::Unexpected(format!("payment provider request failed: {e}")))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(DomainError::Unexpected(format!("payment provider returned {status}")));
+        }
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| DomainError::Unexpected(format!("payment provider request failed: {e}")))?;
+        Ok(body.to_vec())
+    }
+}
+
+#[async_trait]
+impl PaymentProvider for HttpPaymentProvider {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    async fn authorize(&self, reference: &str, amount_cents: i64) -> Result<Authorization, DomainError> {
+        let req = AuthorizeReq { reference, amount: amount_cents, currency: &self.currency };
+        let body = self.post("/authorizations", reference, &req).await?;
+        let resp: AuthorizeResp = serde_json::from_slice(&body)
+            .map_err(|e| DomainError::Unexpected(format!("invalid payment provider response: {e}")))?;
+        match resp.status.as_str() {
+            "authorized" => Ok(Authorization::Authorized { provider_ref: resp.id }),
+            "declined" => Ok(Authorization::Declined { reason: resp.decline_reason.unwrap_or_else(|| "declined".into()) }),
+            other => Err(DomainError::Unexpected(format!("payment provider returned status {other:?}"))),
+        }
+    }
+
+    async fn capture(&self, provider_ref: &str, amount_cents: i64) -> Result<(), DomainError> {
+        let path = format!("/authorizations/{provider_ref}/capture");
+        self.post(&path, &format!("capture-{provider_ref}"), &AmountReq { amount: amount_cents }).await?;
+        Ok(())
+    }
+
+    async fn refund(&self, provider_ref: &str, amount_cents: i64) -> Result<(), DomainError> {
+        let path = format!("/authorizations/{provider_ref}/refund");
+        self.post(&path, &format!("refund-{provider_ref}"), &AmountReq { amount: amount_cents }).await?;
+        Ok(())
+    }
+
+    fn verify_webhook(&self, signature: &str, body: &[u8]) -> Result<WebhookEvent, DomainError> {
+        verify_webhook(&self.webhook_secret.clone().into_inner(), signature, body)
+    }
+}
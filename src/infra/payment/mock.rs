@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use crate::domain::payment::{Authorization, PaymentProvider, WebhookEvent, WebhookKind};
+use crate::domain::{DomainError, Secret};
+
+use super::{sign, verify_webhook, webhook_body};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Authorized,
+    Captured,
+    Refunded,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct MockPayment {
+    amount_cents: i64,
+    state: State,
+}
+
+/// In-process provider for tests and demos. Deterministic: every
+/// authorization succeeds (or every one is declined, see
+/// [`MockPaymentProvider::declining`]) and the reference of `intent-1` is
+/// `mock_intent-1`. It keeps its payments in memory and rejects captures and
+/// refunds a real provider would reject.
+#[derive(Clone)]
+pub struct MockPaymentProvider {
+    secret: Secret<String>,
+    declining: Option<String>,
+    payments: Arc<Mutex<HashMap<String, MockPayment>>>,
+}
+
+impl MockPaymentProvider {
+    pub fn new(webhook_secret: impl Into<String>) -> Self {
+        Self { secret: Secret::from(webhook_secret.into()), declining: None, payments: Arc::default() }
+    }
+
+    /// Decline every authorization with `reason`.
+    pub fn declining(mut self, reason: impl Into<String>) -> Self {
+        self.declining = Some(reason.into());
+        self
+    }
+
+    /// `(signature, body)` of the webhook the provider would send for `event`.
+    pub fn webhook(&self, id: &str, kind: WebhookKind, provider_ref: &str) -> (String, Vec<u8>) {
+        let body = webhook_body(&WebhookEvent { id: id.into(), kind, provider_ref: provider_ref.into() });
+        (sign(&self.secret.clone().into_inner(), &body), body)
+    }
+
+    /// Move payment `provider_ref` from `from` to `to` for `amount_cents`;
+    /// already being in `to` with the same amount is fine, for retries.
+    fn advance(&self, provider_ref: &str, amount_cents: i64, from: State, to: State) -> Result<(), DomainError> {
+        let mut payments = self.payments.lock().unwrap_or_else(|e| e.into_inner());
+        let payment = payments
+            .get_mut(provider_ref)
+            .ok_or_else(|| DomainError::Unexpected(format!("payment provider: no payment {provider_ref}")))?;
+        if payment.amount_cents != amount_cents {
+            return Err(DomainError::Unexpected(format!(
+                "payment provider: payment {provider_ref} is for {} cents, not {amount_cents}",
+                payment.amount_cents
+            )));
+        }
+        match payment.state {
+            state if state == to => Ok(()),
+            state if state == from => {
+                payment.state = to;
+                Ok(())
+            }
+            state => Err(DomainError::Unexpected(format!("payment provider: payment {provider_ref} is {state:?}"))),
+        }
+    }
+}
+
+#[async_trait]
+impl PaymentProvider for MockPaymentProvider {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn authorize(&self, reference: &str, amount_cents: i64) -> Result<Authorization, DomainError> {
+        if let Some(reason) = &self.declining {
+            return Ok(Authorization::Declined { reason: reason.clone() });
+        }
+        let provider_ref = format!("mock_{reference}");
+        let mut payments = self.payments.lock().unwrap_or_else(|e| e.into_inner());
+        let payment = payments
+            .entry(provider_ref.clone())
+            .or_insert(MockPayment { amount_cents, state: State::Authorized });
+        if payment.amount_cents != amount_cents {
+            return Err(DomainError::Unexpected(format!("payment provider: reference {reference} reused for another amount")));
+        }
+        Ok(Authorization::Authorized { provider_ref })
+    }
+
+    async fn capture(&self, provider_ref: &str, amount_cents: i64) -> Result<(), DomainError> {
+        self.advance(provider_ref, amount_cents, State::Authorized, State::Captured)
+    }
+
+    async fn refund(&self, provider_ref: &str, amount_cents: i64) -> Result<(), DomainError> {
+        self.advance(provider_ref, amount_cents, State::Captured, State::Refunded)
+    }
+
+    fn verify_webhook(&self, signature: &str, body: &[u8]) -> Result<WebhookEvent, DomainError> {
+        verify_webhook(&self.secret.clone().into_inner(), signature, body)
+    }
+}
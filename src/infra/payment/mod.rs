@@ -0,0 +1,92 @@
+//! Payment providers.
+//!
+//! Configuration:
+//! - `PAYMENT_PROVIDER`       — `mock` (default) or `http`
+//! - `PAYMENT_WEBHOOK_SECRET` — checks webhook signatures (`X-Signature: sha256=<hex>`);
+//!   required for `http`, random for `mock` if unset
+//! - `PAYMENT_HTTP_URL`       — required for `http`, e.g. `https://payments.example.com/v1`
+//! - `PAYMENT_HTTP_API_KEY`   — required for `http`
+//! - `PAYMENT_CURRENCY`       — default `EUR`
+//!
+//! Both providers send webhooks as
+//! `{"id": "evt_1", "type": "payment.captured", "payment_id": "<provider ref>"}`
+//! with the HMAC-SHA256 of the body, keyed with the webhook secret, in
+//! `X-Signature`.
+
+use std::env;
+use std::sync::Arc;
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use ulid::Ulid;
+
+use crate::domain::payment::{PaymentProvider, WebhookEvent, WebhookKind};
+use crate::domain::DomainError;
+
+pub mod http;
+pub mod mock;
+#[cfg(test)]
+mod tests;
+
+pub use http::HttpPaymentProvider;
+pub use mock::MockPaymentProvider;
+
+type ConfigError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// A webhook on the wire.
+#[derive(Serialize, Deserialize)]
+struct WebhookBody {
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    payment_id: String,
+}
+
+/// `X-Signature` value for `body`.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// The body of `event` as a provider sends it.
+pub fn webhook_body(event: &WebhookEvent) -> Vec<u8> {
+    let body = WebhookBody { id: event.id.clone(), kind: event.kind.as_str().into(), payment_id: event.provider_ref.clone() };
+    serde_json::to_vec(&body).expect("a webhook body serializes")
+}
+
+/// Check `signature` in constant time and parse `body`.
+fn verify_webhook(secret: &str, signature: &str, body: &[u8]) -> Result<WebhookEvent, DomainError> {
+    let expected = signature
+        .strip_prefix("sha256=")
+        .and_then(|hex| hex::decode(hex).ok())
+        .ok_or(DomainError::Unauthorized)?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).map_err(|_| DomainError::Unauthorized)?;
+    mac.update(body);
+    mac.verify_slice(&expected).map_err(|_| DomainError::Unauthorized)?;
+
+    let body: WebhookBody =
+        serde_json::from_slice(body).map_err(|e| DomainError::Validation(format!("invalid webhook body: {e}")))?;
+    let kind = [WebhookKind::Captured, WebhookKind::Failed, WebhookKind::Refunded]
+        .into_iter()
+        .find(|k| k.as_str() == body.kind)
+        .ok_or_else(|| DomainError::Validation(format!("unknown webhook type {:?}", body.kind)))?;
+    Ok(WebhookEvent { id: body.id, kind, provider_ref: body.payment_id })
+}
+
+/// The provider named in `PAYMENT_PROVIDER`.
+pub fn provider_from_env() -> Result<Arc<dyn PaymentProvider>, ConfigError> {
+    let secret = env::var("PAYMENT_WEBHOOK_SECRET").ok().filter(|s| !s.is_empty());
+    match env::var("PAYMENT_PROVIDER").unwrap_or_else(|_| "mock".into()).as_str() {
+        "mock" => Ok(Arc::new(MockPaymentProvider::new(secret.unwrap_or_else(|| Ulid::new().to_string())))),
+        "http" => {
+            let url = env::var("PAYMENT_HTTP_URL").map_err(|_| "PAYMENT_HTTP_URL must be set")?;
+            let api_key = env::var("PAYMENT_HTTP_API_KEY").map_err(|_| "PAYMENT_HTTP_API_KEY must be set")?;
+            let secret = secret.ok_or("PAYMENT_WEBHOOK_SECRET must be set")?;
+            let currency = env::var("PAYMENT_CURRENCY").unwrap_or_else(|_| "EUR".into());
+            Ok(Arc::new(HttpPaymentProvider::new(url, api_key, secret, currency)?))
+        }
+        other => Err(format!("unknown payment provider: {other}").into()),
+    }
+}
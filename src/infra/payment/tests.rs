@@ -0,0 +1,170 @@
+//! Provider cases, run against the mock directly and against the HTTP
+//! provider talking to a stand-in server on a loopback port, which serves a
+//! mock over the provider's JSON API.
+
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+
+use crate::domain::payment::{Authorization, PaymentProvider, WebhookEvent, WebhookKind};
+use crate::domain::DomainError;
+
+use super::{HttpPaymentProvider, MockPaymentProvider};
+
+const WEBHOOK_SECRET: &str = "whsec-test";
+const API_KEY: &str = "sk-test";
+
+fn mock() -> MockPaymentProvider {
+    MockPaymentProvider::new(WEBHOOK_SECRET)
+}
+
+async fn with_mock(mock: MockPaymentProvider) -> Arc<dyn PaymentProvider> {
+    Arc::new(mock)
+}
+
+/// Serve `mock` on a loopback port and return a provider pointed at it.
+async fn with_http(mock: MockPaymentProvider) -> Arc<dyn PaymentProvider> {
+    let app = Router::new()
+        .route("/v1/authorizations", post(authorize))
+        .route("/v1/authorizations/:id/:action", post(capture_or_refund))
+        .with_state(mock);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(axum::serve(listener, app).into_future());
+    let url = format!("http://{address}/v1/");
+    Arc::new(HttpPaymentProvider::new(url, API_KEY.into(), WEBHOOK_SECRET.into(), "EUR".into()).unwrap())
+}
+
+/// What every stand-in route insists on.
+fn check_headers(headers: &HeaderMap) -> Result<(), StatusCode> {
+    if headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok()) != Some(&format!("Bearer {API_KEY}")) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    if !headers.contains_key("Idempotency-Key") {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(())
+}
+
+async fn authorize(State(mock): State<MockPaymentProvider>, headers: HeaderMap, Json(req): Json<Value>) -> Response {
+    if let Err(rejected) = check_headers(&headers) {
+        return rejected.into_response();
+    }
+    assert_eq!(req["currency"], "EUR");
+    let reference = req["reference"].as_str().unwrap();
+    match mock.authorize(reference, req["amount"].as_i64().unwrap()).await {
+        Ok(Authorization::Authorized { provider_ref }) => Json(json!({ "id": provider_ref, "status": "authorized" })).into_response(),
+        Ok(Authorization::Declined { reason }) => {
+            Json(json!({ "id": "declined", "status": "declined", "decline_reason": reason })).into_response()
+        }
+        Err(_) => StatusCode::UNPROCESSABLE_ENTITY.into_response(),
+    }
+}
+
+async fn capture_or_refund(
+    State(mock): State<MockPaymentProvider>,
+    Path((id, action)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(req): Json<Value>,
+) -> Response {
+    if let Err(rejected) = check_headers(&headers) {
+        return rejected.into_response();
+    }
+    let amount = req["amount"].as_i64().unwrap();
+    let result = match action.as_str() {
+        "capture" => mock.capture(&id, amount).await,
+        "refund" => mock.refund(&id, amount).await,
+        _ => return StatusCode::NOT_FOUND.into_response(),
+    };
+    match result {
+        Ok(()) => Json(json!({})).into_response(),
+        Err(_) => StatusCode::UNPROCESSABLE_ENTITY.into_response(),
+    }
+}
+
+async fn authorized(provider: &dyn PaymentProvider, reference: &str, amount_cents: i64) -> String {
+    match provider.authorize(reference, amount_cents).await.unwrap() {
+        Authorization::Authorized { provider_ref } => provider_ref,
+        declined => panic!("expected an authorization, got {declined:?}"),
+    }
+}
+
+async fn authorize_capture_refund(provider: Arc<dyn PaymentProvider>) {
+    let provider_ref = authorized(&*provider, "intent-1", 1999).await;
+    // the same reference is the same payment
+    assert_eq!(authorized(&*provider, "intent-1", 1999).await, provider_ref);
+    assert_ne!(authorized(&*provider, "intent-2", 1999).await, provider_ref);
+
+    provider.capture(&provider_ref, 1999).await.unwrap();
+    // retried after a lost answer
+    provider.capture(&provider_ref, 1999).await.unwrap();
+    provider.refund(&provider_ref, 1999).await.unwrap();
+    assert!(provider.capture(&provider_ref, 1999).await.is_err());
+}
+
+async fn capture_and_refund_are_checked(provider: Arc<dyn PaymentProvider>) {
+    assert!(provider.capture("nope", 100).await.is_err());
+    let provider_ref = authorized(&*provider, "intent-1", 100).await;
+    assert!(provider.refund(&provider_ref, 100).await.is_err(), "refund before capture");
+    assert!(provider.capture(&provider_ref, 101).await.is_err(), "more than authorized");
+    assert!(provider.authorize("intent-1", 200).await.is_err(), "reference reused for another amount");
+    provider.capture(&provider_ref, 100).await.unwrap();
+}
+
+async fn declines_are_answers(provider: Arc<dyn PaymentProvider>) {
+    let answer = provider.authorize("intent-1", 100).await.unwrap();
+    assert_eq!(answer, Authorization::Declined { reason: "insufficient funds".into() });
+}
+
+async fn webhooks_are_verified(provider: Arc<dyn PaymentProvider>) {
+    let (signature, body) = mock().webhook("evt_1", WebhookKind::Captured, "mock_intent-1");
+    let event = provider.verify_webhook(&signature, &body).unwrap();
+    assert_eq!(event, WebhookEvent { id: "evt_1".into(), kind: WebhookKind::Captured, provider_ref: "mock_intent-1".into() });
+
+    let tampered = String::from_utf8(body.clone()).unwrap().replace("captured", "refunded");
+    assert!(matches!(provider.verify_webhook(&signature, tampered.as_bytes()), Err(DomainError::Unauthorized)));
+    let (foreign, _) = MockPaymentProvider::new("other-secret").webhook("evt_1", WebhookKind::Captured, "mock_intent-1");
+    assert!(matches!(provider.verify_webhook(&foreign, &body), Err(DomainError::Unauthorized)));
+    assert!(matches!(provider.verify_webhook("not-a-signature", &body), Err(DomainError::Unauthorized)));
+
+    let body = br#"{"id": "evt_2", "type": "payment.disputed", "payment_id": "mock_intent-1"}"#;
+    let signature = super::sign(WEBHOOK_SECRET, body);
+    assert!(matches!(provider.verify_webhook(&signature, body), Err(DomainError::Validation(_))));
+}
+
+macro_rules! provider_tests {
+    ($with_provider:path) => {
+        #[tokio::test]
+        async fn authorize_capture_refund() {
+            super::authorize_capture_refund($with_provider(super::mock()).await).await;
+        }
+
+        #[tokio::test]
+        async fn capture_and_refund_are_checked() {
+            super::capture_and_refund_are_checked($with_provider(super::mock()).await).await;
+        }
+
+        #[tokio::test]
+        async fn declines_are_answers() {
+            super::declines_are_answers($with_provider(super::mock().declining("insufficient funds")).await).await;
+        }
+
+        #[tokio::test]
+        async fn webhooks_are_verified() {
+            super::webhooks_are_verified($with_provider(super::mock()).await).await;
+        }
+    };
+}
+
+mod mock_provider {
+    provider_tests!(super::with_mock);
+}
+
+mod http_provider {
+    provider_tests!(super::with_http);
+}
@@ -0,0 +1,69 @@
+//! W3C trace-context propagation helpers.
+//! Uses the global text-map propagator installed by `init_telemetry`
+//! (`traceparent` / `tracestate`):
+//! https://www.w3.org/TR/trace-context/
+
+use axum::http::HeaderMap;
+use opentelemetry::{global, trace::TraceContextExt, Context};
+use opentelemetry_http::{HeaderExtractor, HeaderInjector};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Extract the remote parent context from incoming request headers.
+pub fn extract_context(headers: &HeaderMap) -> Context {
+    global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)))
+}
+
+/// Inject the current span's context into outgoing request headers.
+/// Call this on every outbound HTTP request (webhooks, other services).
+pub fn inject_context(headers: &mut HeaderMap) {
+    let cx = tracing::Span::current().context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&cx, &mut HeaderInjector(headers))
+    });
+}
+
+/// The current span's `traceparent`, for work that continues the trace later
+/// (outbox messages).
+pub fn current_traceparent() -> Option<String> {
+    let mut headers = HeaderMap::new();
+    inject_context(&mut headers);
+    headers
+        .get("traceparent")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned)
+}
+
+/// Make `span` a child of the trace described by `traceparent`.
+pub fn set_parent_from_traceparent(span: &tracing::Span, traceparent: &str) {
+    let Ok(value) = traceparent.parse() else {
+        return;
+    };
+    let mut headers = HeaderMap::new();
+    headers.insert("traceparent", value);
+    if let Err(e) = span.set_parent(extract_context(&headers)) {
+        tracing::debug!(error = %e, "failed to set remote parent from traceparent");
+    }
+}
+
+/// `(trace_id, traceresponse)` for the current span, or `None` when the span
+/// is not sampled into a valid OTel trace.
+///
+/// `traceresponse` uses the same layout as `traceparent`:
+/// https://w3c.github.io/trace-context/#traceresponse-header
+pub fn current_trace_response() -> Option<(String, String)> {
+    let cx = tracing::Span::current().context();
+    let span = cx.span();
+    let span_context = span.span_context();
+    if !span_context.is_valid() {
+        return None;
+    }
+
+    let trace_id = span_context.trace_id().to_string();
+    let trace_response = format!(
+        "00-{}-{}-{:02x}",
+        trace_id,
+        span_context.span_id(),
+        span_context.trace_flags().to_u8()
+    );
+    Some((trace_id, trace_response))
+}
@@ -0,0 +1,376 @@
+//! Sensitive-data redaction for logs and traces.
+//!
+//! [`RedactionLayer`] wraps the fmt / OTel layers and rewrites event and span
+//! fields before they see them:
+//! - fields whose name is in the deny list are replaced by `[REDACTED]`
+//! - string values matching a secret pattern (JWTs, bearer tokens, API keys)
+//!   have the match replaced by `[REDACTED]`
+//!
+//! Configuration (comma-separated, added to the built-in defaults):
+//! - `LOG_REDACT_FIELDS`   — extra field names, e.g. `ssn,card_number`
+//! - `LOG_REDACT_PATTERNS` — extra regexes, separated by `;`
+
+use std::any::TypeId;
+use std::collections::HashSet;
+use std::env;
+use std::fmt;
+use std::sync::Arc;
+
+use regex::Regex;
+use tracing::field::{display, DisplayValue, Field, Value, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+#[cfg(test)]
+mod tests;
+
+const REDACTED: &str = "[REDACTED]";
+
+const DEFAULT_FIELDS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "access_token",
+    "refresh_token",
+    "authorization",
+    "api_key",
+    "apikey",
+    "x-api-key",
+    "cookie",
+    "set-cookie",
+];
+
+const DEFAULT_PATTERNS: &[&str] = &[
+    // JWT: header.payload.signature, base64url
+    r"eyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]*",
+    // Authorization: Bearer <token>
+    r"(?i)bearer\s+[A-Za-z0-9._~+/=-]+",
+    // api_key=..., X-Api-Key: ...
+    r"(?i)(x-)?api[_-]?key\s*[:=]\s*[^\s,;&]+",
+];
+
+/// Field names and value patterns to scrub.
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    fields: HashSet<String>,
+    patterns: Vec<Regex>,
+}
+
+impl Redactor {
+    pub fn new<F, P>(fields: F, patterns: P) -> Result<Self, regex::Error>
+    where
+        F: IntoIterator,
+        F::Item: AsRef<str>,
+        P: IntoIterator,
+        P::Item: AsRef<str>,
+    {
+        Ok(Self {
+            fields: fields
+                .into_iter()
+                .map(|f| f.as_ref().trim().to_ascii_lowercase())
+                .filter(|f| !f.is_empty())
+                .collect(),
+            patterns: patterns
+                .into_iter()
+                .map(|p| Regex::new(p.as_ref()))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Built-in defaults plus `LOG_REDACT_FIELDS` / `LOG_REDACT_PATTERNS`.
+    pub fn from_env() -> Result<Self, regex::Error> {
+        let extra_fields = env::var("LOG_REDACT_FIELDS").unwrap_or_default();
+        let extra_patterns = env::var("LOG_REDACT_PATTERNS").unwrap_or_default();
+        Self::with_extra(&extra_fields, &extra_patterns)
+    }
+
+    /// Built-in defaults plus comma-separated `extra_fields` and
+    /// `;`-separated `extra_patterns`.
+    fn with_extra(extra_fields: &str, extra_patterns: &str) -> Result<Self, regex::Error> {
+        let fields = DEFAULT_FIELDS
+            .iter()
+            .copied()
+            .chain(extra_fields.split(','));
+        let patterns = DEFAULT_PATTERNS
+            .iter()
+            .copied()
+            .chain(extra_patterns.split(';').map(str::trim).filter(|p| !p.is_empty()));
+        Self::new(fields, patterns)
+    }
+
+    /// Whether the whole value of `field` must be hidden. Dotted names match
+    /// on their last segment too (`user.password`).
+    pub fn is_sensitive_field(&self, field: &str) -> bool {
+        let field = field.to_ascii_lowercase();
+        if self.fields.contains(&field) {
+            return true;
+        }
+        field
+            .rsplit('.')
+            .next()
+            .is_some_and(|last| self.fields.contains(last))
+    }
+
+    /// Replace every pattern match in `value`; `None` when nothing matched.
+    pub fn scrub(&self, value: &str) -> Option<String> {
+        let mut scrubbed: Option<String> = None;
+        for pattern in &self.patterns {
+            let current = scrubbed.as_deref().unwrap_or(value);
+            if pattern.is_match(current) {
+                scrubbed = Some(pattern.replace_all(current, REDACTED).into_owned());
+            }
+        }
+        scrubbed
+    }
+}
+
+// ---- Layer -----------------------------------------------------------------
+
+/// Wraps `inner` so it only ever sees redacted event and span fields.
+pub struct RedactionLayer<L> {
+    inner: L,
+    redactor: Arc<Redactor>,
+}
+
+impl<L> RedactionLayer<L> {
+    pub fn new(inner: L, redactor: Redactor) -> Self {
+        Self { inner, redactor: Arc::new(redactor) }
+    }
+}
+
+impl<S, L> Layer<S> for RedactionLayer<L>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    L: Layer<S>,
+{
+    fn on_register_dispatch(&self, subscriber: &tracing::Dispatch) {
+        self.inner.on_register_dispatch(subscriber);
+    }
+
+    fn on_layer(&mut self, subscriber: &mut S) {
+        self.inner.on_layer(subscriber);
+    }
+
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> tracing::subscriber::Interest {
+        self.inner.register_callsite(metadata)
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, ctx: Context<'_, S>) -> bool {
+        self.inner.enabled(metadata, ctx)
+    }
+
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = RedactedFields::new(attrs.metadata().fields().len(), &self.redactor);
+        attrs.record(&mut fields);
+        if !fields.changed {
+            return self.inner.on_new_span(attrs, id, ctx);
+        }
+
+        let values = fields.values();
+        let value_set = attrs.metadata().fields().value_set_all(&values);
+        let redacted = if let Some(parent) = attrs.parent() {
+            Attributes::child_of(parent.clone(), attrs.metadata(), &value_set)
+        } else if attrs.is_root() {
+            Attributes::new_root(attrs.metadata(), &value_set)
+        } else {
+            Attributes::new(attrs.metadata(), &value_set)
+        };
+        self.inner.on_new_span(&redacted, id, ctx);
+    }
+
+    fn on_record(&self, span: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(metadata) = ctx.metadata(span) else {
+            return self.inner.on_record(span, values, ctx);
+        };
+        let mut fields = RedactedFields::new(metadata.fields().len(), &self.redactor);
+        values.record(&mut fields);
+        if !fields.changed {
+            return self.inner.on_record(span, values, ctx);
+        }
+
+        // fields missing from this record stay `None`
+        let values = fields.values();
+        let value_set = metadata.fields().value_set_all(&values);
+        self.inner.on_record(span, &Record::new(&value_set), ctx);
+    }
+
+    fn on_follows_from(&self, span: &Id, follows: &Id, ctx: Context<'_, S>) {
+        self.inner.on_follows_from(span, follows, ctx);
+    }
+
+    fn event_enabled(&self, event: &Event<'_>, ctx: Context<'_, S>) -> bool {
+        self.inner.event_enabled(event, ctx)
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = RedactedFields::new(event.metadata().fields().len(), &self.redactor);
+        event.record(&mut fields);
+        if !fields.changed {
+            return self.inner.on_event(event, ctx);
+        }
+
+        let values = fields.values();
+        let value_set = event.metadata().fields().value_set_all(&values);
+        let redacted = if let Some(parent) = event.parent() {
+            Event::new_child_of(parent.clone(), event.metadata(), &value_set)
+        } else if event.is_root() {
+            Event::new_child_of(None, event.metadata(), &value_set)
+        } else {
+            Event::new(event.metadata(), &value_set)
+        };
+        self.inner.on_event(&redacted, ctx);
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        self.inner.on_enter(id, ctx);
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        self.inner.on_exit(id, ctx);
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        self.inner.on_close(id, ctx);
+    }
+
+    fn on_id_change(&self, old: &Id, new: &Id, ctx: Context<'_, S>) {
+        self.inner.on_id_change(old, new, ctx);
+    }
+
+    unsafe fn downcast_raw(&self, id: TypeId) -> Option<*const ()> {
+        // Forward so `OpenTelemetrySpanExt` can still find the OTel layer.
+        if id == TypeId::of::<Self>() {
+            Some(self as *const Self as *const ())
+        } else {
+            unsafe { self.inner.downcast_raw(id) }
+        }
+    }
+}
+
+// ---- Field visitor ---------------------------------------------------------
+
+/// Owned copy of a recorded field value.
+enum OwnedValue {
+    Str(String),
+    Debug(DisplayValue<String>),
+    I64(i64),
+    U64(u64),
+    I128(i128),
+    U128(u128),
+    F64(f64),
+    Bool(bool),
+}
+
+impl OwnedValue {
+    fn as_value(&self) -> &dyn Value {
+        match self {
+            OwnedValue::Str(v) => v,
+            OwnedValue::Debug(v) => v,
+            OwnedValue::I64(v) => v,
+            OwnedValue::U64(v) => v,
+            OwnedValue::I128(v) => v,
+            OwnedValue::U128(v) => v,
+            OwnedValue::F64(v) => v,
+            OwnedValue::Bool(v) => v,
+        }
+    }
+}
+
+/// Copies every field, redacting as it goes, indexed by field position.
+struct RedactedFields<'a> {
+    redactor: &'a Redactor,
+    values: Vec<Option<OwnedValue>>,
+    changed: bool,
+}
+
+impl<'a> RedactedFields<'a> {
+    fn new(len: usize, redactor: &'a Redactor) -> Self {
+        Self {
+            redactor,
+            values: (0..len).map(|_| None).collect(),
+            changed: false,
+        }
+    }
+
+    /// One entry per field in the field set, `None` for unrecorded fields.
+    fn values(&self) -> Vec<Option<&dyn Value>> {
+        self.values
+            .iter()
+            .map(|v| v.as_ref().map(OwnedValue::as_value))
+            .collect()
+    }
+
+    fn set(&mut self, field: &Field, value: OwnedValue) {
+        if let Some(slot) = self.values.get_mut(field.index()) {
+            *slot = Some(value);
+        }
+    }
+
+    fn set_text(&mut self, field: &Field, text: String, debug: bool) {
+        let value = if self.redactor.is_sensitive_field(field.name()) {
+            self.changed = true;
+            REDACTED.to_string()
+        } else if let Some(scrubbed) = self.redactor.scrub(&text) {
+            self.changed = true;
+            scrubbed
+        } else {
+            text
+        };
+        let value = if debug {
+            OwnedValue::Debug(display(value))
+        } else {
+            OwnedValue::Str(value)
+        };
+        self.set(field, value);
+    }
+
+    fn set_plain(&mut self, field: &Field, value: OwnedValue) {
+        if self.redactor.is_sensitive_field(field.name()) {
+            self.changed = true;
+            self.set(field, OwnedValue::Str(REDACTED.to_string()));
+        } else {
+            self.set(field, value);
+        }
+    }
+}
+
+impl Visit for RedactedFields<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.set_text(field, value.to_string(), false);
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.set_text(field, format!("{value:?}"), true);
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.set_text(field, value.to_string(), true);
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.set_plain(field, OwnedValue::I64(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.set_plain(field, OwnedValue::U64(value));
+    }
+
+    fn record_i128(&mut self, field: &Field, value: i128) {
+        self.set_plain(field, OwnedValue::I128(value));
+    }
+
+    fn record_u128(&mut self, field: &Field, value: u128) {
+        self.set_plain(field, OwnedValue::U128(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.set_plain(field, OwnedValue::F64(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.set_plain(field, OwnedValue::Bool(value));
+    }
+}
@@ -0,0 +1,99 @@
+use async_trait::async_trait;
+use sqlx::{PgPool, Postgres};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::api_key::{ApiKey, ApiKeyRepository};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{pg_query, pg_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+
+#[derive(Clone)]
+pub struct PostgresApiKeyRepository {
+    db: DbHandle<Postgres>,
+}
+
+impl PostgresApiKeyRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for PostgresApiKeyRepository {
+    #[instrument(skip(self, key_hash), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "api_keys", db.query.text = Empty))]
+    async fn create(&self, name: String, prefix: String, key_hash: String) -> Result<i64, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query!(
+            r#"
+            INSERT INTO api_keys (name, prefix, key_hash)
+            VALUES ($1, $2, $3)
+            RETURNING id
+            "#,
+            name,
+            prefix,
+            key_hash
+        )
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.id)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "api_keys", db.query.text = Empty))]
+    async fn get_all(&self) -> Result<Vec<ApiKey>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = pg_query_as!(ApiKey,
+            r#"
+            SELECT id, name, prefix, created_at, revoked_at
+            FROM api_keys
+            ORDER BY id
+            "#
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(rows)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "api_keys", db.query.text = Empty))]
+    async fn active_hash(&self, prefix: &str) -> Result<Option<String>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query!(
+            r#"
+            SELECT key_hash
+            FROM api_keys
+            WHERE prefix = $1 AND revoked_at IS NULL
+            "#,
+            prefix
+        )
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.map(|r| r.key_hash))
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "UPDATE", db.collection.name = "api_keys", db.query.text = Empty))]
+    async fn revoke(&self, id: i64) -> Result<bool, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = pg_query!(
+            r#"
+            UPDATE api_keys
+            SET revoked_at = now()
+            WHERE id = $1 AND revoked_at IS NULL
+            "#,
+            id
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
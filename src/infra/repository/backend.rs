@@ -0,0 +1,222 @@
+//! Picks the repository implementations from the `DATABASE_URL` scheme:
+//! `memory:`, `sqlite:`, anything else is Postgres.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::Serialize;
+use sqlx::migrate::{Migrate, MigrateError, Migrator};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{PgPool, Pool, SqlitePool};
+
+use crate::domain::api_key::ApiKeyRepository;
+use crate::domain::cart::CartRepository;
+use crate::domain::category::CategoryRepository;
+use crate::domain::inventory::InventoryRepository;
+use crate::domain::job::JobQueue;
+use crate::domain::notification::NotificationRepository;
+use crate::domain::order::OrderRepository;
+use crate::domain::payment::PaymentRepository;
+use crate::domain::product::ProductRepository;
+use crate::domain::promotion::PromotionRepository;
+use crate::domain::purchasing::PurchasingRepository;
+use crate::domain::reservation::ReservationRepository;
+use crate::domain::uow::UnitOfWork;
+use crate::domain::user::UserRepository;
+use crate::domain::warehouse::WarehouseRepository;
+use crate::infra::repository::api_key::PostgresApiKeyRepository;
+use crate::infra::repository::cart::PostgresCartRepository;
+use crate::infra::repository::category::PostgresCategoryRepository;
+use crate::infra::repository::inventory::PostgresInventoryRepository;
+use crate::infra::repository::job::PostgresJobQueue;
+use crate::infra::repository::notification::PostgresNotificationRepository;
+use crate::infra::repository::memory::{
+    InMemoryApiKeyRepository, InMemoryCartRepository, InMemoryCategoryRepository, InMemoryInventoryRepository, InMemoryJobQueue,
+    InMemoryNotificationRepository, InMemoryOrderRepository, InMemoryPaymentRepository, InMemoryProductRepository, InMemoryPromotionRepository, InMemoryPurchasingRepository, InMemoryReservationRepository,
+    InMemoryStore,
+    InMemoryUnitOfWork, InMemoryUserRepository, InMemoryWarehouseRepository,
+};
+use crate::infra::repository::order::PostgresOrderRepository;
+use crate::infra::repository::payment::PostgresPaymentRepository;
+use crate::infra::repository::product::PostgresProductRepository;
+use crate::infra::repository::promotion::PostgresPromotionRepository;
+use crate::infra::repository::purchasing::PostgresPurchasingRepository;
+use crate::infra::repository::reservation::PostgresReservationRepository;
+use crate::infra::repository::sqlite::{
+    self, SqliteApiKeyRepository, SqliteCartRepository, SqliteCategoryRepository, SqliteInventoryRepository, SqliteJobQueue,
+    SqliteNotificationRepository, SqliteOrderRepository, SqlitePaymentRepository, SqliteProductRepository, SqlitePromotionRepository, SqlitePurchasingRepository, SqliteReservationRepository,
+    SqliteUnitOfWork,
+    SqliteUserRepository, SqliteWarehouseRepository,
+};
+use crate::infra::repository::uow::PostgresUnitOfWork;
+use crate::infra::repository::user::PostgresUserRepository;
+use crate::infra::repository::warehouse::PostgresWarehouseRepository;
+
+static POSTGRES_MIGRATOR: Migrator = sqlx::migrate!();
+
+/// Every repository of one backend, sharing its pool (or store).
+#[derive(Clone)]
+pub struct Repositories {
+    pub users: Arc<dyn UserRepository>,
+    pub categories: Arc<dyn CategoryRepository>,
+    pub products: Arc<dyn ProductRepository>,
+    pub inventory: Arc<dyn InventoryRepository>,
+    pub warehouses: Arc<dyn WarehouseRepository>,
+    pub uow: Arc<dyn UnitOfWork>,
+    pub jobs: Arc<dyn JobQueue>,
+    pub api_keys: Arc<dyn ApiKeyRepository>,
+    pub carts: Arc<dyn CartRepository>,
+    pub reservations: Arc<dyn ReservationRepository>,
+    pub orders: Arc<dyn OrderRepository>,
+    pub payments: Arc<dyn PaymentRepository>,
+    pub notifications: Arc<dyn NotificationRepository>,
+    pub purchasing: Arc<dyn PurchasingRepository>,
+    pub promotions: Arc<dyn PromotionRepository>,
+}
+
+/// A migration known to this build and whether the database has it.
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub applied: bool,
+    /// Applied, but the file changed since.
+    pub checksum_mismatch: bool,
+}
+
+#[derive(Clone)]
+pub enum Backend {
+    /// Demo mode: nothing survives a restart.
+    Memory(InMemoryStore, InMemoryJobQueue),
+    Sqlite(SqlitePool),
+    Postgres(PgPool),
+}
+
+impl Backend {
+    /// Open the database; migrations are left to [`Backend::migrate`].
+    pub async fn connect(url: &str, max_connections: u32) -> Result<Self, sqlx::Error> {
+        if url.starts_with("memory:") {
+            Ok(Backend::Memory(InMemoryStore::new(), InMemoryJobQueue::new()))
+        } else if url.starts_with("sqlite:") {
+            Ok(Backend::Sqlite(sqlite::open(url, max_connections).await?))
+        } else {
+            let pool = PgPoolOptions::new().max_connections(max_connections).connect(url).await?;
+            Ok(Backend::Postgres(pool))
+        }
+    }
+
+    /// `memory`, `sqlite` or `postgres`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Backend::Memory(..) => "memory",
+            Backend::Sqlite(_) => "sqlite",
+            Backend::Postgres(_) => "postgres",
+        }
+    }
+
+    pub fn repositories(&self) -> Repositories {
+        match self {
+            Backend::Memory(store, jobs) => Repositories {
+                users: Arc::new(InMemoryUserRepository::new(store.clone())),
+                categories: Arc::new(InMemoryCategoryRepository::new(store.clone())),
+                products: Arc::new(InMemoryProductRepository::new(store.clone())),
+                inventory: Arc::new(InMemoryInventoryRepository::new(store.clone())),
+                warehouses: Arc::new(InMemoryWarehouseRepository::new(store.clone())),
+                uow: Arc::new(InMemoryUnitOfWork::new(store.clone())),
+                jobs: Arc::new(jobs.clone()),
+                api_keys: Arc::new(InMemoryApiKeyRepository::new(store.clone())),
+                carts: Arc::new(InMemoryCartRepository::new(store.clone())),
+                reservations: Arc::new(InMemoryReservationRepository::new(store.clone())),
+                orders: Arc::new(InMemoryOrderRepository::new(store.clone())),
+                payments: Arc::new(InMemoryPaymentRepository::new(store.clone())),
+                notifications: Arc::new(InMemoryNotificationRepository::new(store.clone())),
+                purchasing: Arc::new(InMemoryPurchasingRepository::new(store.clone())),
+                promotions: Arc::new(InMemoryPromotionRepository::new(store.clone())),
+            },
+            Backend::Sqlite(pool) => Repositories {
+                users: Arc::new(SqliteUserRepository::new(pool.clone())),
+                categories: Arc::new(SqliteCategoryRepository::new(pool.clone())),
+                products: Arc::new(SqliteProductRepository::new(pool.clone())),
+                inventory: Arc::new(SqliteInventoryRepository::new(pool.clone())),
+                warehouses: Arc::new(SqliteWarehouseRepository::new(pool.clone())),
+                uow: Arc::new(SqliteUnitOfWork::new(pool.clone())),
+                jobs: Arc::new(SqliteJobQueue::new(pool.clone())),
+                api_keys: Arc::new(SqliteApiKeyRepository::new(pool.clone())),
+                carts: Arc::new(SqliteCartRepository::new(pool.clone())),
+                reservations: Arc::new(SqliteReservationRepository::new(pool.clone())),
+                orders: Arc::new(SqliteOrderRepository::new(pool.clone())),
+                payments: Arc::new(SqlitePaymentRepository::new(pool.clone())),
+                notifications: Arc::new(SqliteNotificationRepository::new(pool.clone())),
+                purchasing: Arc::new(SqlitePurchasingRepository::new(pool.clone())),
+                promotions: Arc::new(SqlitePromotionRepository::new(pool.clone())),
+            },
+            Backend::Postgres(pool) => Repositories {
+                users: Arc::new(PostgresUserRepository::new(pool.clone())),
+                categories: Arc::new(PostgresCategoryRepository::new(pool.clone())),
+                products: Arc::new(PostgresProductRepository::new(pool.clone())),
+                inventory: Arc::new(PostgresInventoryRepository::new(pool.clone())),
+                warehouses: Arc::new(PostgresWarehouseRepository::new(pool.clone())),
+                uow: Arc::new(PostgresUnitOfWork::new(pool.clone())),
+                jobs: Arc::new(PostgresJobQueue::new(pool.clone())),
+                api_keys: Arc::new(PostgresApiKeyRepository::new(pool.clone())),
+                carts: Arc::new(PostgresCartRepository::new(pool.clone())),
+                reservations: Arc::new(PostgresReservationRepository::new(pool.clone())),
+                orders: Arc::new(PostgresOrderRepository::new(pool.clone())),
+                payments: Arc::new(PostgresPaymentRepository::new(pool.clone())),
+                notifications: Arc::new(PostgresNotificationRepository::new(pool.clone())),
+                purchasing: Arc::new(PostgresPurchasingRepository::new(pool.clone())),
+                promotions: Arc::new(PostgresPromotionRepository::new(pool.clone())),
+            },
+        }
+    }
+
+    /// Apply pending migrations; the in-memory store needs none.
+    pub async fn migrate(&self) -> Result<(), MigrateError> {
+        match self {
+            Backend::Memory(..) => Ok(()),
+            Backend::Sqlite(pool) => sqlite::MIGRATOR.run(pool).await,
+            Backend::Postgres(pool) => POSTGRES_MIGRATOR.run(pool).await,
+        }
+    }
+
+    pub async fn migration_status(&self) -> Result<Vec<MigrationStatus>, MigrateError> {
+        match self {
+            Backend::Memory(..) => Ok(Vec::new()),
+            Backend::Sqlite(pool) => status(&sqlite::MIGRATOR, pool).await,
+            Backend::Postgres(pool) => status(&POSTGRES_MIGRATOR, pool).await,
+        }
+    }
+
+    pub async fn close(&self) {
+        match self {
+            Backend::Memory(..) => {}
+            Backend::Sqlite(pool) => pool.close().await,
+            Backend::Postgres(pool) => pool.close().await,
+        }
+    }
+}
+
+async fn status<DB>(migrator: &Migrator, pool: &Pool<DB>) -> Result<Vec<MigrationStatus>, MigrateError>
+where
+    DB: sqlx::Database,
+    DB::Connection: Migrate,
+{
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    let applied: HashMap<_, _> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|m| (m.version, m.checksum))
+        .collect();
+
+    Ok(migrator
+        .iter()
+        .map(|m| MigrationStatus {
+            version: m.version,
+            description: m.description.to_string(),
+            applied: applied.contains_key(&m.version),
+            checksum_mismatch: applied.get(&m.version).is_some_and(|c| *c != m.checksum),
+        })
+        .collect())
+}
@@ -0,0 +1,94 @@
+use std::future::Future;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use tracing::{field::Empty, instrument};
+
+use crate::domain::cache::{CacheInvalidator, Invalidation};
+use crate::infra::db_trace::{pg_query, DbTraceExt};
+use crate::infra::repository::cached::RepositoryCache;
+
+/// `LISTEN`/`NOTIFY` channel carrying [`Invalidation`]s between replicas.
+pub const CHANNEL: &str = "cache_invalidation";
+
+/// Keeps the [`RepositoryCache`]s of all replicas on one Postgres database
+/// in step: invalidations are applied locally and sent with `NOTIFY`, and
+/// [`PgCacheSync::run`] applies the ones other replicas send.
+#[derive(Clone)]
+pub struct PgCacheSync {
+    pool: PgPool,
+    cache: RepositoryCache,
+}
+
+impl PgCacheSync {
+    pub fn new(pool: PgPool, cache: RepositoryCache) -> Self {
+        Self { pool, cache }
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.query.text = Empty))]
+    async fn notify(&self, invalidation: Invalidation) -> Result<(), sqlx::Error> {
+        pg_query!("SELECT pg_notify($1, $2)", CHANNEL, invalidation.to_string())
+            .execute(&self.pool)
+            .traced()
+            .await?;
+        Ok(())
+    }
+
+    /// Apply invalidations from other replicas until `shutdown` resolves.
+    /// Whenever the connection drops, notifications may have been missed,
+    /// so the whole cache is cleared.
+    pub async fn run(self, shutdown: impl Future<Output = ()>) {
+        tokio::pin!(shutdown);
+        loop {
+            let mut listener = match self.listen().await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    tracing::warn!(error = %e, "cache invalidation listener failed to connect");
+                    tokio::select! {
+                        _ = &mut shutdown => return,
+                        _ = tokio::time::sleep(Duration::from_secs(5)) => continue,
+                    }
+                }
+            };
+            self.cache.clear();
+
+            loop {
+                let notification = tokio::select! {
+                    _ = &mut shutdown => return,
+                    notification = listener.try_recv() => notification,
+                };
+                match notification {
+                    Ok(Some(notification)) => match notification.payload().parse::<Invalidation>() {
+                        Ok(invalidation) => self.cache.invalidate_local(invalidation),
+                        Err(e) => tracing::warn!(error = %e, "ignoring cache invalidation"),
+                    },
+                    // reconnects on the next call
+                    Ok(None) => self.cache.clear(),
+                    Err(e) => {
+                        tracing::warn!(error = %e, "cache invalidation listener failed");
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn listen(&self) -> Result<PgListener, sqlx::Error> {
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener.listen(CHANNEL).await?;
+        Ok(listener)
+    }
+}
+
+#[async_trait]
+impl CacheInvalidator for PgCacheSync {
+    async fn invalidate(&self, invalidation: Invalidation) {
+        self.cache.invalidate_local(invalidation);
+        if let Err(e) = self.notify(invalidation).await {
+            // the other replicas catch up when their entries expire
+            tracing::warn!(error = %e, %invalidation, "cache invalidation not sent");
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Read-through caching in front of any [`CategoryRepository`] or
+//! [`ProductRepository`]. Categories (one by one and by page of the list) and
+//! products by id are cached; everything else goes straight through.
+//!
+//! Services write through a unit of work, not these decorators, so they
+//! report each committed write to a [`CacheInvalidator`]: the
+//! [`RepositoryCache`] itself on a single instance, or a
+//! [`PgCacheSync`](super::cache_sync::PgCacheSync) that tells the other
+//! replicas too.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::domain::cache::{CacheInvalidator, Invalidation};
+use crate::domain::category::{Category, CategoryRepository};
+use crate::domain::page::Page;
+use crate::domain::product::{Product, ProductRepository};
+use crate::domain::DomainError;
+use crate::infra::cache::LruCache;
+
+struct Caches {
+    categories: LruCache<i64, Category>,
+    category_list: LruCache<Page, Vec<Category>>,
+    products: LruCache<i64, Product>,
+}
+
+/// The caches shared by the decorators of one app; cheap to clone.
+#[derive(Clone)]
+pub struct RepositoryCache {
+    caches: Arc<Caches>,
+}
+
+impl RepositoryCache {
+    /// Each cache holds at most `capacity` entries for at most `ttl`.
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            caches: Arc::new(Caches {
+                categories: LruCache::new("categories", capacity, ttl),
+                category_list: LruCache::new("category_list", capacity, ttl),
+                products: LruCache::new("products", capacity, ttl),
+            }),
+        }
+    }
+
+    pub fn categories(&self, inner: Arc<dyn CategoryRepository>) -> CachedCategoryRepository {
+        CachedCategoryRepository { inner, cache: self.clone() }
+    }
+
+    pub fn products(&self, inner: Arc<dyn ProductRepository>) -> CachedProductRepository {
+        CachedProductRepository { inner, cache: self.clone() }
+    }
+
+    pub fn invalidate_local(&self, invalidation: Invalidation) {
+        match invalidation {
+            Invalidation::Category(id) => {
+                self.caches.categories.remove(&id);
+                self.caches.category_list.clear();
+            }
+            Invalidation::Product(id) => self.caches.products.remove(&id),
+        }
+    }
+
+    /// Drop everything, e.g. after missing invalidations from other replicas.
+    pub fn clear(&self) {
+        self.caches.categories.clear();
+        self.caches.category_list.clear();
+        self.caches.products.clear();
+    }
+}
+
+#[async_trait]
+impl CacheInvalidator for RepositoryCache {
+    async fn invalidate(&self, invalidation: Invalidation) {
+        self.invalidate_local(invalidation);
+    }
+}
+
+pub struct CachedCategoryRepository {
+    inner: Arc<dyn CategoryRepository>,
+    cache: RepositoryCache,
+}
+
+#[async_trait]
+impl CategoryRepository for CachedCategoryRepository {
+    async fn create(&self, name: String) -> Result<i64, DomainError> {
+        let id = self.inner.create(name).await?;
+        self.cache.invalidate_local(Invalidation::Category(id));
+        Ok(id)
+    }
+
+    async fn get_by_id(&self, id: i64) -> Result<Option<Category>, DomainError> {
+        let cache = &self.cache.caches.categories;
+        if let Some(category) = cache.get(&id) {
+            return Ok(Some(category));
+        }
+        let generation = cache.generation();
+        let category = self.inner.get_by_id(id).await?;
+        if let Some(category) = &category {
+            cache.insert(generation, id, category.clone());
+        }
+        Ok(category)
+    }
+
+    async fn get_all_categories(&self, page: Page) -> Result<Vec<Category>, DomainError> {
+        let cache = &self.cache.caches.category_list;
+        if let Some(categories) = cache.get(&page) {
+            return Ok(categories);
+        }
+        let generation = cache.generation();
+        let categories = self.inner.get_all_categories(page).await?;
+        cache.insert(generation, page, categories.clone());
+        Ok(categories)
+    }
+
+    async fn update(&self, id: i64, name: String) -> Result<Category, DomainError> {
+        let category = self.inner.update(id, name).await;
+        self.cache.invalidate_local(Invalidation::Category(id));
+        category
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), DomainError> {
+        let deleted = self.inner.delete(id).await;
+        self.cache.invalidate_local(Invalidation::Category(id));
+        deleted
+    }
+}
+
+pub struct CachedProductRepository {
+    inner: Arc<dyn ProductRepository>,
+    cache: RepositoryCache,
+}
+
+#[async_trait]
+impl ProductRepository for CachedProductRepository {
+    async fn create(&self, product: Product) -> Result<i64, DomainError> {
+        let id = self.inner.create(product).await?;
+        self.cache.invalidate_local(Invalidation::Product(id));
+        Ok(id)
+    }
+
+    async fn get_by_product_id(&self, id: i64) -> Result<Option<Product>, DomainError> {
+        let cache = &self.cache.caches.products;
+        if let Some(product) = cache.get(&id) {
+            return Ok(Some(product));
+        }
+        let generation = cache.generation();
+        let product = self.inner.get_by_product_id(id).await?;
+        if let Some(product) = &product {
+            cache.insert(generation, id, product.clone());
+        }
+        Ok(product)
+    }
+
+    async fn get_by_category_id(&self, category_id: i64, page: Page) -> Result<Vec<Product>, DomainError> {
+        self.inner.get_by_category_id(category_id, page).await
+    }
+
+    async fn get_all_products(&self, page: Page) -> Result<Vec<Product>, DomainError> {
+        self.inner.get_all_products(page).await
+    }
+}
@@ -0,0 +1,232 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::cart::{Cart, CartLine, CartOwner, CartRepository};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{pg_query, pg_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+
+/// `(user_id, guest_token)`; exactly one is set.
+pub(crate) fn owner_columns(owner: &CartOwner) -> (Option<i64>, Option<String>) {
+    match owner {
+        CartOwner::User(id) => (Some(*id), None),
+        CartOwner::Guest(token) => (None, Some(token.clone())),
+    }
+}
+
+pub(crate) fn owner_from_columns(user_id: Option<i64>, guest_token: Option<String>) -> Result<CartOwner, DomainError> {
+    match (user_id, guest_token) {
+        (Some(id), None) => Ok(CartOwner::User(id)),
+        (None, Some(token)) => Ok(CartOwner::Guest(token)),
+        _ => Err(DomainError::Unexpected("cart must have exactly one owner".into())),
+    }
+}
+
+#[derive(Clone)]
+pub struct PostgresCartRepository {
+    db: DbHandle<Postgres>,
+}
+
+impl PostgresCartRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Postgres>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl CartRepository for PostgresCartRepository {
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "carts", db.query.text = Empty))]
+    async fn find(&self, owner: &CartOwner) -> Result<Option<Cart>, DomainError> {
+        let (user_id, guest_token) = owner_columns(owner);
+        let mut conn = self.db.acquire().await?;
+        let Some(cart) = pg_query!(
+            r#"
+            SELECT id, user_id, guest_token, coupon_code, updated_at
+            FROM carts
+            WHERE user_id = $1 OR guest_token = $2
+            "#,
+            user_id,
+            guest_token
+        )
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        else {
+            return Ok(None);
+        };
+
+        let lines = pg_query_as!(
+            CartLine,
+            r#"
+            SELECT product_id, quantity, unit_price::float8 as "unit_price!"
+            FROM cart_items
+            WHERE cart_id = $1
+            ORDER BY id
+            "#,
+            cart.id
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(Some(Cart {
+            id: cart.id,
+            owner: owner_from_columns(cart.user_id, cart.guest_token)?,
+            lines,
+            coupon: cart.coupon_code,
+            updated_at: cart.updated_at,
+        }))
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "carts", db.query.text = Empty))]
+    async fn create(&self, owner: &CartOwner) -> Result<i64, DomainError> {
+        let (user_id, guest_token) = owner_columns(owner);
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query!(
+            r#"
+            INSERT INTO carts (user_id, guest_token)
+            VALUES ($1, $2)
+            RETURNING id
+            "#,
+            user_id,
+            guest_token
+        )
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.id)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "cart_items", db.query.text = Empty))]
+    async fn set_line(&self, cart_id: i64, line: CartLine) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query!(
+            r#"
+            INSERT INTO cart_items (cart_id, product_id, quantity, unit_price)
+            VALUES ($1, $2, $3, $4::float8)
+            ON CONFLICT (cart_id, product_id)
+            DO UPDATE SET quantity = EXCLUDED.quantity, unit_price = EXCLUDED.unit_price
+            "#,
+            cart_id,
+            line.product_id,
+            line.quantity,
+            line.unit_price
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        pg_query!(
+            r#"
+            UPDATE carts
+            SET updated_at = now()
+            WHERE id = $1
+            "#,
+            cart_id
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "DELETE", db.collection.name = "cart_items", db.query.text = Empty))]
+    async fn remove_line(&self, cart_id: i64, product_id: i64) -> Result<bool, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = pg_query!(
+            r#"
+            DELETE FROM cart_items
+            WHERE cart_id = $1 AND product_id = $2
+            "#,
+            cart_id,
+            product_id
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        pg_query!(
+            r#"
+            UPDATE carts
+            SET updated_at = now()
+            WHERE id = $1
+            "#,
+            cart_id
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(true)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "UPDATE", db.collection.name = "carts", db.query.text = Empty))]
+    async fn set_coupon(&self, cart_id: i64, coupon: Option<String>) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query!(
+            r#"
+            UPDATE carts
+            SET coupon_code = $2, updated_at = now()
+            WHERE id = $1
+            "#,
+            cart_id,
+            coupon
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "DELETE", db.collection.name = "carts", db.query.text = Empty))]
+    async fn delete(&self, cart_id: i64) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query!(
+            r#"
+            DELETE FROM carts
+            WHERE id = $1
+            "#,
+            cart_id
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "DELETE", db.collection.name = "carts", db.query.text = Empty))]
+    async fn delete_idle(&self, before: DateTime<Utc>) -> Result<u64, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = pg_query!(
+            r#"
+            DELETE FROM carts
+            WHERE updated_at < $1
+            "#,
+            before
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(result.rows_affected())
+    }
+}
@@ -1,26 +1,35 @@
 use async_trait::async_trait;
-use sqlx::PgPool;
-use tracing::instrument;
+use sqlx::{PgPool, Postgres};
+use tracing::{field::Empty, instrument};
 
+use crate::domain::page::Page;
 use crate::domain::category::{Category, CategoryRepository};
 use crate::domain::DomainError;
+use crate::infra::db_trace::{pg_query, pg_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
 
 #[derive(Clone)]
 pub struct PostgresCategoryRepository {
-    pool: PgPool,
+    db: DbHandle<Postgres>,
 }
 
 impl PostgresCategoryRepository {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Postgres>) -> Self {
+        Self { db }
     }
 }
 
 #[async_trait]
 impl CategoryRepository for PostgresCategoryRepository {
-    #[instrument(skip(self), err, fields(db = "postgres"))]
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "categories", db.query.text = Empty))]
     async fn create(&self, name: String) -> Result<i64, DomainError> {
-        let row = sqlx::query!(
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query!(
             r#"
             INSERT INTO categories (name)
             VALUES ($1)
@@ -28,16 +37,18 @@ impl CategoryRepository for PostgresCategoryRepository {
             "#,
             name
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *conn)
+        .traced()
         .await
-        .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        .map_err(db_error)?;
 
         Ok(row.id)
     }
 
-    #[instrument(skip(self), err, fields(db = "postgres"))]
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "categories", db.query.text = Empty))]
     async fn get_by_id(&self, id: i64) -> Result<Option<Category>, DomainError> {
-        let row = sqlx::query_as!(
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query_as!(
             Category,
             r#"
             SELECT id, name, active
@@ -46,32 +57,40 @@ impl CategoryRepository for PostgresCategoryRepository {
             "#,
             id
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *conn)
+        .traced()
         .await
-        .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        .map_err(db_error)?;
 
         Ok(row)
     }
 
-    #[instrument(skip(self), err, fields(db = "postgres"))]
-    async fn get_all_categories(&self) -> Result<Vec<Category>, DomainError> {
-        let rows = sqlx::query_as!(
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "categories", db.query.text = Empty))]
+    async fn get_all_categories(&self, page: Page) -> Result<Vec<Category>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = pg_query_as!(
             Category,
             r#"
             SELECT id, name, active
             FROM categories
-            "#
+            ORDER BY id
+            LIMIT $1 OFFSET $2
+            "#,
+            page.limit,
+            page.offset
         )
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *conn)
+        .traced()
         .await
-        .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        .map_err(db_error)?;
 
         Ok(rows)
     }
 
-    #[instrument(skip(self), err, fields(db = "postgres"))]
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "UPDATE", db.collection.name = "categories", db.query.text = Empty))]
     async fn update(&self, id: i64, name: String) -> Result<Category, DomainError> {
-        let row = sqlx::query_as!(
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query_as!(
             Category,
             r#"
             UPDATE categories
@@ -82,25 +101,28 @@ impl CategoryRepository for PostgresCategoryRepository {
             name,
             id
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *conn)
+        .traced()
         .await
-        .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        .map_err(db_error)?;
 
         Ok(row)
     }
 
-    #[instrument(skip(self), err, fields(db = "postgres"))]
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "DELETE", db.collection.name = "categories", db.query.text = Empty))]
     async fn delete(&self, id: i64) -> Result<(), DomainError> {
-        let _row = sqlx::query!(
+        let mut conn = self.db.acquire().await?;
+        let _row = pg_query!(
             r#"
             DELETE FROM categories
             WHERE id = $1
             "#,
             id
         )
-        .execute(&self.pool)
+        .execute(&mut *conn)
+        .traced()
         .await
-        .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        .map_err(db_error)?;
 
         Ok(())
     }
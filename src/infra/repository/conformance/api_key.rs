@@ -0,0 +1,32 @@
+use chrono::{TimeDelta, Utc};
+
+use super::Repos;
+
+pub async fn api_key_create_and_find_active(r: &Repos) {
+    let id = r.api_keys.create("ci".into(), "ab12cd34".into(), "hash-1".into()).await.unwrap();
+    r.api_keys.create("ops".into(), "ef56ab78".into(), "hash-2".into()).await.unwrap();
+
+    assert_eq!(r.api_keys.active_hash("ab12cd34").await.unwrap().as_deref(), Some("hash-1"));
+    assert_eq!(r.api_keys.active_hash("00000000").await.unwrap(), None);
+
+    let keys = r.api_keys.get_all().await.unwrap();
+    assert_eq!(keys.iter().map(|k| k.name.as_str()).collect::<Vec<_>>(), ["ci", "ops"]);
+    assert_eq!(keys[0].id, id);
+    assert_eq!(keys[0].prefix, "ab12cd34");
+    assert!((Utc::now() - keys[0].created_at).abs() < TimeDelta::minutes(1));
+    assert_eq!(keys[0].revoked_at, None);
+
+    // prefixes identify keys
+    assert!(r.api_keys.create("dup".into(), "ab12cd34".into(), "hash-3".into()).await.is_err());
+}
+
+pub async fn api_key_revoke(r: &Repos) {
+    let id = r.api_keys.create("ci".into(), "ab12cd34".into(), "hash-1".into()).await.unwrap();
+
+    assert!(r.api_keys.revoke(id).await.unwrap());
+    assert!(!r.api_keys.revoke(id).await.unwrap());
+    assert!(!r.api_keys.revoke(42).await.unwrap());
+
+    assert_eq!(r.api_keys.active_hash("ab12cd34").await.unwrap(), None);
+    assert!(r.api_keys.get_all().await.unwrap()[0].revoked_at.is_some());
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::domain::page::Page;
+use crate::domain::product::Product;
+use crate::infra::repository::cached::RepositoryCache;
+use crate::usecases::category_service::CategoryService;
+use crate::usecases::product_service::ProductService;
+
+use super::Repos;
+
+fn category_service(r: &Repos, cache: &RepositoryCache) -> CategoryService {
+    CategoryService::new(Arc::new(cache.categories(r.categories.clone())), r.uow.clone())
+        .with_cache_invalidator(Arc::new(cache.clone()))
+}
+
+pub async fn cache_serves_reads_until_service_write(r: &Repos) {
+    let cache = RepositoryCache::new(10, Duration::from_secs(60));
+    let categories = category_service(r, &cache);
+    let id = categories.create("Books".into()).await.unwrap();
+    assert_eq!(categories.get_by_id(id).await.unwrap().unwrap().name, "Books");
+    assert_eq!(categories.get_all_categories(Page::ALL).await.unwrap().len(), 1);
+
+    // behind the cache's back: still served from the cache
+    r.categories.update(id, "Comics".into()).await.unwrap();
+    assert_eq!(categories.get_by_id(id).await.unwrap().unwrap().name, "Books");
+    assert_eq!(categories.get_all_categories(Page::ALL).await.unwrap()[0].name, "Books");
+
+    categories.update(id, "E-books".into()).await.unwrap();
+    assert_eq!(categories.get_by_id(id).await.unwrap().unwrap().name, "E-books");
+    assert_eq!(categories.get_all_categories(Page::ALL).await.unwrap()[0].name, "E-books");
+
+    categories.create("Games".into()).await.unwrap();
+    assert_eq!(categories.get_all_categories(Page::ALL).await.unwrap().len(), 2);
+
+    categories.delete(id).await.unwrap();
+    assert!(categories.get_by_id(id).await.unwrap().is_none());
+    assert_eq!(categories.get_all_categories(Page::ALL).await.unwrap().len(), 1);
+}
+
+pub async fn cache_does_not_keep_misses(r: &Repos) {
+    let cache = RepositoryCache::new(10, Duration::from_secs(60));
+    let cached = Arc::new(cache.products(r.products.clone()));
+    let products = ProductService::new(cached, r.reservations.clone(), r.warehouses.clone(), r.uow.clone())
+        .with_cache_invalidator(Arc::new(cache.clone()));
+    let category_id = r.categories.create("Books".into()).await.unwrap();
+    assert!(products.get_by_product_id(1).await.unwrap().is_none());
+
+    let product = Product {
+        id: 0,
+        name: "Rust Book".into(),
+        description: None,
+        price: 10.0,
+        stock: 1,
+        category_id,
+        active: true,
+    };
+    // not through the service, so nothing is invalidated
+    let id = r.products.create(product).await.unwrap();
+    assert_eq!(products.get_by_product_id(id).await.unwrap().unwrap().name, "Rust Book");
+}
+
+pub async fn cache_evicts_least_recently_used(r: &Repos) {
+    let cache = RepositoryCache::new(2, Duration::from_secs(60));
+    let categories = category_service(r, &cache);
+    let mut ids = Vec::new();
+    for name in ["Books", "Games", "Music"] {
+        ids.push(categories.create(name.into()).await.unwrap());
+    }
+    for id in [ids[0], ids[1], ids[0], ids[2]] {
+        categories.get_by_id(id).await.unwrap();
+    }
+    for &id in &ids {
+        r.categories.update(id, "Renamed".into()).await.unwrap();
+    }
+
+    // ids[1] was least recently used when ids[2] came in
+    assert_eq!(categories.get_by_id(ids[0]).await.unwrap().unwrap().name, "Books");
+    assert_eq!(categories.get_by_id(ids[1]).await.unwrap().unwrap().name, "Renamed");
+}
+
+pub async fn cache_entries_expire(r: &Repos) {
+    let cache = RepositoryCache::new(10, Duration::from_millis(50));
+    let categories = category_service(r, &cache);
+    let id = categories.create("Books".into()).await.unwrap();
+    categories.get_by_id(id).await.unwrap();
+    r.categories.update(id, "E-books".into()).await.unwrap();
+    assert_eq!(categories.get_by_id(id).await.unwrap().unwrap().name, "Books");
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(categories.get_by_id(id).await.unwrap().unwrap().name, "E-books");
+}
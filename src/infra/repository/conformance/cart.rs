@@ -0,0 +1,128 @@
+use chrono::{TimeDelta, Utc};
+
+use crate::domain::cart::{CartLine, CartOwner};
+use crate::domain::product::Product;
+
+use super::Repos;
+
+/// A category with products `Rust Book` (10.00) and `Chess` (15.00).
+async fn catalog(r: &Repos) -> (i64, i64) {
+    let category_id = r.categories.create("Books".into()).await.unwrap();
+    let product = |name: &str, price: f64| Product {
+        id: 0,
+        name: name.into(),
+        description: None,
+        price,
+        stock: 5,
+        category_id,
+        active: true,
+    };
+    let book = r.products.create(product("Rust Book", 10.0)).await.unwrap();
+    let chess = r.products.create(product("Chess", 15.0)).await.unwrap();
+    (book, chess)
+}
+
+fn line(product_id: i64, quantity: i32, unit_price: f64) -> CartLine {
+    CartLine { product_id, quantity, unit_price }
+}
+
+pub async fn cart_create_and_find_by_owner(r: &Repos) {
+    let user_id = r.users.create("alice".into(), "pw".into()).await.unwrap();
+    let user = CartOwner::User(user_id);
+    let guest = CartOwner::Guest("guest-token".into());
+    assert!(r.carts.find(&user).await.unwrap().is_none());
+
+    let user_cart = r.carts.create(&user).await.unwrap();
+    let guest_cart = r.carts.create(&guest).await.unwrap();
+    assert_ne!(user_cart, guest_cart);
+
+    let cart = r.carts.find(&user).await.unwrap().unwrap();
+    assert_eq!(cart.id, user_cart);
+    assert_eq!(cart.owner, user);
+    assert!(cart.lines.is_empty());
+    assert!((Utc::now() - cart.updated_at).abs() < TimeDelta::minutes(1));
+    assert_eq!(r.carts.find(&guest).await.unwrap().unwrap().id, guest_cart);
+    assert!(r.carts.find(&CartOwner::Guest("other".into())).await.unwrap().is_none());
+
+    // one cart per owner; carts.user_id REFERENCES users(id)
+    assert!(r.carts.create(&user).await.is_err());
+    assert!(r.carts.create(&guest).await.is_err());
+    assert!(r.carts.create(&CartOwner::User(42)).await.is_err());
+}
+
+pub async fn cart_set_line_upserts_in_place(r: &Repos) {
+    let (book, chess) = catalog(r).await;
+    let owner = CartOwner::Guest("guest-token".into());
+    let id = r.carts.create(&owner).await.unwrap();
+
+    r.carts.set_line(id, line(chess, 1, 15.0)).await.unwrap();
+    r.carts.set_line(id, line(book, 2, 9.999)).await.unwrap();
+    r.carts.set_line(id, line(chess, 3, 14.0)).await.unwrap();
+
+    let cart = r.carts.find(&owner).await.unwrap().unwrap();
+    // NUMERIC(10, 2), first-added order
+    assert_eq!(cart.lines, [line(chess, 3, 14.0), line(book, 2, 10.0)]);
+
+    // cart_items.product_id REFERENCES products(id), quantity > 0
+    assert!(r.carts.set_line(id, line(42, 1, 1.0)).await.is_err());
+    assert!(r.carts.set_line(id, line(book, 0, 1.0)).await.is_err());
+}
+
+pub async fn cart_remove_line(r: &Repos) {
+    let (book, chess) = catalog(r).await;
+    let owner = CartOwner::Guest("guest-token".into());
+    let id = r.carts.create(&owner).await.unwrap();
+    r.carts.set_line(id, line(book, 1, 10.0)).await.unwrap();
+    r.carts.set_line(id, line(chess, 1, 15.0)).await.unwrap();
+
+    assert!(r.carts.remove_line(id, book).await.unwrap());
+    assert!(!r.carts.remove_line(id, book).await.unwrap());
+    assert!(!r.carts.remove_line(42, chess).await.unwrap());
+    assert_eq!(r.carts.find(&owner).await.unwrap().unwrap().lines, [line(chess, 1, 15.0)]);
+}
+
+pub async fn cart_coupon_is_kept_until_replaced(r: &Repos) {
+    let owner = CartOwner::Guest("guest-token".into());
+    let id = r.carts.create(&owner).await.unwrap();
+    assert_eq!(r.carts.find(&owner).await.unwrap().unwrap().coupon, None);
+
+    r.carts.set_coupon(id, Some("SPRING".into())).await.unwrap();
+    assert_eq!(r.carts.find(&owner).await.unwrap().unwrap().coupon.as_deref(), Some("SPRING"));
+    r.carts.set_coupon(id, Some("SUMMER".into())).await.unwrap();
+    assert_eq!(r.carts.find(&owner).await.unwrap().unwrap().coupon.as_deref(), Some("SUMMER"));
+    r.carts.set_coupon(id, None).await.unwrap();
+    assert_eq!(r.carts.find(&owner).await.unwrap().unwrap().coupon, None);
+    r.carts.set_coupon(42, Some("SPRING".into())).await.unwrap();
+}
+
+pub async fn cart_delete_removes_lines(r: &Repos) {
+    let (book, _) = catalog(r).await;
+    let owner = CartOwner::Guest("guest-token".into());
+    let id = r.carts.create(&owner).await.unwrap();
+    r.carts.set_line(id, line(book, 1, 10.0)).await.unwrap();
+
+    r.carts.delete(id).await.unwrap();
+    assert!(r.carts.find(&owner).await.unwrap().is_none());
+    r.carts.delete(id).await.unwrap();
+
+    // a new cart for the same owner starts empty
+    r.carts.create(&owner).await.unwrap();
+    assert!(r.carts.find(&owner).await.unwrap().unwrap().lines.is_empty());
+}
+
+pub async fn cart_deleted_with_its_user(r: &Repos) {
+    let user_id = r.users.create("alice".into(), "pw".into()).await.unwrap();
+    r.carts.create(&CartOwner::User(user_id)).await.unwrap();
+
+    r.users.delete(user_id).await.unwrap();
+    assert!(r.carts.find(&CartOwner::User(user_id)).await.unwrap().is_none());
+}
+
+pub async fn cart_delete_idle(r: &Repos) {
+    r.carts.create(&CartOwner::Guest("a".into())).await.unwrap();
+    r.carts.create(&CartOwner::Guest("b".into())).await.unwrap();
+
+    assert_eq!(r.carts.delete_idle(Utc::now() - TimeDelta::hours(1)).await.unwrap(), 0);
+    assert_eq!(r.carts.delete_idle(Utc::now() + TimeDelta::minutes(1)).await.unwrap(), 2);
+    assert!(r.carts.find(&CartOwner::Guest("a".into())).await.unwrap().is_none());
+}
@@ -0,0 +1,74 @@
+use crate::domain::page::Page;
+use crate::domain::product::Product;
+
+use super::Repos;
+
+pub async fn category_create_and_get(r: &Repos) {
+    let id = r.categories.create("Books".into()).await.unwrap();
+    assert_eq!(id, 1);
+
+    let category = r.categories.get_by_id(id).await.unwrap().unwrap();
+    assert_eq!(category.id, id);
+    assert_eq!(category.name, "Books");
+    assert!(category.active);
+
+    assert!(r.categories.get_by_id(42).await.unwrap().is_none());
+}
+
+pub async fn category_get_all_in_insert_order(r: &Repos) {
+    assert!(r.categories.get_all_categories(Page::ALL).await.unwrap().is_empty());
+    for name in ["Games", "Books"] {
+        r.categories.create(name.into()).await.unwrap();
+    }
+
+    let names: Vec<_> = r
+        .categories
+        .get_all_categories(Page::ALL)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|c| c.name)
+        .collect();
+    assert_eq!(names, ["Games", "Books"]);
+}
+
+pub async fn category_update(r: &Repos) {
+    let id = r.categories.create("Books".into()).await.unwrap();
+
+    let category = r.categories.update(id, "E-books".into()).await.unwrap();
+    assert_eq!(category.id, id);
+    assert_eq!(category.name, "E-books");
+    assert!(category.active);
+    assert_eq!(r.categories.get_by_id(id).await.unwrap().unwrap().name, "E-books");
+}
+
+pub async fn category_update_missing_fails(r: &Repos) {
+    assert!(r.categories.update(42, "x".into()).await.is_err());
+}
+
+pub async fn category_delete_is_idempotent(r: &Repos) {
+    let id = r.categories.create("Books".into()).await.unwrap();
+
+    r.categories.delete(id).await.unwrap();
+    assert!(r.categories.get_by_id(id).await.unwrap().is_none());
+    r.categories.delete(id).await.unwrap();
+}
+
+pub async fn category_delete_with_products_fails(r: &Repos) {
+    let id = r.categories.create("Books".into()).await.unwrap();
+    r.products
+        .create(Product {
+            id: 0,
+            name: "Rust Book".into(),
+            description: None,
+            price: 10.0,
+            stock: 1,
+            category_id: id,
+            active: true,
+        })
+        .await
+        .unwrap();
+
+    assert!(r.categories.delete(id).await.is_err());
+    assert!(r.categories.get_by_id(id).await.unwrap().is_some());
+}
@@ -0,0 +1,185 @@
+use chrono::{TimeDelta, Utc};
+
+use crate::domain::inventory::{LowStockProduct, MovementKind, StockLevel, StockMovement};
+use crate::domain::order::{Order, OrderLine, OrderStatus};
+use crate::domain::page::Page;
+use crate::domain::product::Product;
+
+use super::Repos;
+
+/// A category with product `name`, 5 in stock that the ledger knows nothing
+/// about.
+async fn product(r: &Repos, name: &str) -> i64 {
+    let category_id = match r.categories.get_all_categories(Page::ALL).await.unwrap().first() {
+        Some(category) => category.id,
+        None => r.categories.create("Books".into()).await.unwrap(),
+    };
+    r.products
+        .create(Product {
+            id: 0,
+            name: name.into(),
+            description: None,
+            price: 10.0,
+            stock: 5,
+            category_id,
+            active: true,
+        })
+        .await
+        .unwrap()
+}
+
+/// The `main` warehouse every database starts with.
+pub const MAIN: i64 = 1;
+
+fn movement(product_id: i64, kind: MovementKind, quantity: i32) -> StockMovement {
+    StockMovement {
+        id: 0,
+        product_id,
+        warehouse_id: MAIN,
+        kind,
+        quantity,
+        reason: None,
+        actor_id: None,
+        order_id: None,
+        created_at: Utc::now(),
+    }
+}
+
+async fn stock(r: &Repos, id: i64) -> i32 {
+    r.products.get_by_product_id(id).await.unwrap().unwrap().stock
+}
+
+pub async fn stock_movements_update_the_stock(r: &Repos) {
+    let book = product(r, "Rust Book").await;
+
+    let received = StockMovement { reason: Some("delivery 17".into()), ..movement(book, MovementKind::Receipt, 3) };
+    let stored = r.inventory.record(received).await.unwrap().unwrap();
+    assert_eq!(stored.id, 1);
+    assert_eq!((stored.product_id, stored.kind, stored.quantity), (book, MovementKind::Receipt, 3));
+    assert_eq!(stored.reason.as_deref(), Some("delivery 17"));
+    assert!((Utc::now() - stored.created_at).abs() < TimeDelta::minutes(1));
+    assert_eq!(stock(r, book).await, 8);
+
+    r.inventory.record(movement(book, MovementKind::Adjustment, -2)).await.unwrap().unwrap();
+    assert_eq!(stock(r, book).await, 6);
+
+    let movements = r.inventory.movements(book).await.unwrap();
+    assert_eq!(movements[0], stored);
+    let steps: Vec<_> = movements.iter().map(|m| (m.kind, m.quantity)).collect();
+    assert_eq!(steps, [(MovementKind::Receipt, 3), (MovementKind::Adjustment, -2)]);
+    assert!(r.inventory.movements(42).await.unwrap().is_empty());
+}
+
+pub async fn stock_never_goes_negative(r: &Repos) {
+    let book = product(r, "Rust Book").await;
+    let sale = |quantity: i32| movement(book, MovementKind::Sale, -quantity);
+
+    assert!(r.inventory.record(sale(3)).await.unwrap().is_some());
+    assert!(r.inventory.record(sale(3)).await.unwrap().is_none());
+    assert!(r.inventory.record(sale(2)).await.unwrap().is_some());
+    assert_eq!(stock(r, book).await, 0);
+    assert!(r.inventory.record(sale(1)).await.unwrap().is_none());
+    assert!(r.inventory.record(movement(42, MovementKind::Receipt, 1)).await.unwrap().is_none());
+    // refused movements leave no trace
+    assert_eq!(r.inventory.movements(book).await.unwrap().len(), 2);
+}
+
+pub async fn stock_movements_keep_actor_and_order(r: &Repos) {
+    let alice = r.users.create("alice".into(), "pw".into()).await.unwrap();
+    let book = product(r, "Rust Book").await;
+    let line = OrderLine { product_id: book, name: "Rust Book".into(), quantity: 1, unit_price: 10.0 };
+    let order_id = r
+        .orders
+        .create(Order {
+            id: 0,
+            user_id: Some(alice),
+            lines: vec![line],
+            discount: 0.0,
+            shipping: 0.0,
+            total: 10.0,
+            status: OrderStatus::Pending,
+            created_at: Utc::now(),
+        })
+        .await
+        .unwrap();
+
+    let sold = StockMovement { actor_id: Some(alice), order_id: Some(order_id), ..movement(book, MovementKind::Sale, -1) };
+    r.inventory.record(sold).await.unwrap().unwrap();
+    let stored = &r.inventory.movements(book).await.unwrap()[0];
+    assert_eq!((stored.actor_id, stored.order_id), (Some(alice), Some(order_id)));
+    assert_eq!(r.inventory.order_movements(order_id).await.unwrap(), vec![stored.clone()]);
+    assert!(r.inventory.order_movements(42).await.unwrap().is_empty());
+
+    // the ledger outlives the actor's account
+    r.users.delete(alice).await.unwrap();
+    assert_eq!(r.inventory.movements(book).await.unwrap()[0].actor_id, None);
+
+    // stock_movements.actor_id REFERENCES users(id), order_id REFERENCES orders(id)
+    let unknown_actor = StockMovement { actor_id: Some(42), ..movement(book, MovementKind::Receipt, 1) };
+    assert!(r.inventory.record(unknown_actor).await.is_err());
+    let unknown_order = StockMovement { order_id: Some(42), ..movement(book, MovementKind::Return, 1) };
+    assert!(r.inventory.record(unknown_order).await.is_err());
+}
+
+pub async fn stock_levels_compare_stock_with_ledger(r: &Repos) {
+    let book = product(r, "Rust Book").await;
+    let chess = product(r, "Chess").await;
+    r.inventory.record(movement(book, MovementKind::Receipt, 5)).await.unwrap().unwrap();
+    r.inventory.record(movement(book, MovementKind::Sale, -2)).await.unwrap().unwrap();
+
+    let levels = r.inventory.stock_levels().await.unwrap();
+    assert_eq!(
+        levels,
+        [
+            StockLevel { product_id: book, name: "Rust Book".into(), stock: 8, ledger: 3 },
+            StockLevel { product_id: chess, name: "Chess".into(), stock: 5, ledger: 0 },
+        ]
+    );
+    assert_eq!(levels[0].difference(), 5);
+}
+
+pub async fn stock_thresholds_list_low_stock_by_category(r: &Repos) {
+    let games = r.categories.create("Games".into()).await.unwrap();
+    let chess = product(r, "Chess").await;
+    let go = product(r, "Go").await;
+    let books = r.categories.create("Books".into()).await.unwrap();
+    let book = r
+        .products
+        .create(Product {
+            id: 0,
+            name: "Rust Book".into(),
+            description: None,
+            price: 10.0,
+            stock: 5,
+            category_id: books,
+            active: true,
+        })
+        .await
+        .unwrap();
+    let low = |product_id: i64, name: &str, category_id: i64, category: &str, stock: i32, threshold: i32| LowStockProduct {
+        product_id,
+        name: name.into(),
+        category_id,
+        category: category.into(),
+        stock,
+        threshold,
+    };
+
+    assert!(r.inventory.set_threshold(chess, Some(6)).await.unwrap());
+    assert!(r.inventory.set_threshold(go, Some(5)).await.unwrap());
+    assert!(r.inventory.set_threshold(book, Some(10)).await.unwrap());
+    assert!(!r.inventory.set_threshold(42, Some(1)).await.unwrap());
+    assert_eq!(r.inventory.threshold(chess).await.unwrap(), Some(6));
+    assert_eq!(r.inventory.threshold(42).await.unwrap(), None);
+    // below, not at: Go's 5 is enough
+    assert_eq!(
+        r.inventory.low_stock().await.unwrap(),
+        [low(book, "Rust Book", books, "Books", 5, 10), low(chess, "Chess", games, "Games", 5, 6)]
+    );
+
+    r.inventory.record(movement(go, MovementKind::Sale, -1)).await.unwrap().unwrap();
+    assert!(r.inventory.set_threshold(chess, None).await.unwrap());
+    assert_eq!(r.inventory.threshold(chess).await.unwrap(), None);
+    assert!(r.inventory.set_threshold(book, Some(3)).await.unwrap());
+    assert_eq!(r.inventory.low_stock().await.unwrap(), [low(go, "Go", games, "Games", 4, 5)]);
+}
@@ -0,0 +1,199 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::domain::job::{JobPayload, NewJob};
+use crate::domain::DomainError;
+use crate::usecases::housekeeping::PruneFinishedJobs;
+use crate::usecases::job_scheduler::JobScheduler;
+use crate::usecases::job_worker::{JobHandler, JobWorker};
+
+use super::Repos;
+
+const LEASE: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Greet {
+    name: String,
+}
+
+impl JobPayload for Greet {
+    const KIND: &'static str = "greet";
+    const MAX_ATTEMPTS: i32 = 2;
+}
+
+/// Records the jobs it ran; fails while `failing` is set.
+#[derive(Clone, Default)]
+struct GreetHandler {
+    greeted: Arc<Mutex<Vec<String>>>,
+    failing: bool,
+}
+
+#[async_trait]
+impl JobHandler for GreetHandler {
+    type Job = Greet;
+
+    async fn handle(&self, job: Greet) -> Result<(), DomainError> {
+        if self.failing {
+            return Err(DomainError::Unexpected("mailer down".into()));
+        }
+        self.greeted.lock().unwrap().push(job.name);
+        Ok(())
+    }
+}
+
+fn greet(name: &str, seconds_ago: i64) -> NewJob {
+    NewJob::of(&Greet { name: name.into() })
+        .unwrap()
+        .run_at(Utc::now() - TimeDelta::seconds(seconds_ago))
+}
+
+async fn claimed_names(r: &Repos, limit: i64, lease: Duration) -> Vec<String> {
+    r.jobs
+        .claim("default", limit, lease)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|j| j.payload["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+pub async fn job_claims_due_jobs_oldest_first(r: &Repos) {
+    r.jobs.enqueue(greet("b", 10)).await.unwrap();
+    r.jobs.enqueue(greet("a", 20)).await.unwrap();
+    r.jobs.enqueue(greet("c", 5)).await.unwrap();
+
+    let jobs = r.jobs.claim("default", 2, LEASE).await.unwrap();
+    assert_eq!(jobs.len(), 2);
+    assert_eq!(jobs[0].kind, "greet");
+    assert_eq!(jobs[0].payload, json!({ "name": "a" }));
+    assert_eq!(jobs[0].attempts, 1);
+    assert_eq!(jobs[0].max_attempts, 2);
+    assert_eq!(jobs[1].payload, json!({ "name": "b" }));
+
+    // claimed jobs are leased to their worker
+    assert_eq!(claimed_names(r, 10, LEASE).await, ["c"]);
+    assert!(claimed_names(r, 10, LEASE).await.is_empty());
+}
+
+pub async fn job_claim_skips_future_and_other_queues(r: &Repos) {
+    r.jobs.enqueue(greet("later", -3600)).await.unwrap();
+    let mut other = greet("elsewhere", 10);
+    other.queue = "imports".into();
+    r.jobs.enqueue(other).await.unwrap();
+
+    assert!(claimed_names(r, 10, LEASE).await.is_empty());
+    let jobs = r.jobs.claim("imports", 10, LEASE).await.unwrap();
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].queue, "imports");
+}
+
+pub async fn job_unique_key_dedupes(r: &Repos) {
+    let first = r.jobs.enqueue(greet("a", 0).unique_key("welcome:1")).await.unwrap();
+    let second = r.jobs.enqueue(greet("b", 0).unique_key("welcome:1")).await.unwrap();
+    let other = r.jobs.enqueue(greet("c", 0).unique_key("welcome:2")).await.unwrap();
+
+    assert!(first.is_some());
+    assert_eq!(second, None);
+    assert!(other.is_some() && other != first);
+    assert_eq!(claimed_names(r, 10, LEASE).await, ["a", "c"]);
+}
+
+pub async fn job_expired_lease_is_claimed_again(r: &Repos) {
+    r.jobs.enqueue(greet("a", 0)).await.unwrap();
+
+    let stale = r.jobs.claim("default", 1, Duration::ZERO).await.unwrap().remove(0);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let current = r.jobs.claim("default", 1, LEASE).await.unwrap().remove(0);
+    assert_eq!(current.id, stale.id);
+    assert_eq!(current.attempts, 2);
+
+    // the first worker lost its lease; its result must not count
+    r.jobs.fail(&stale, "too slow".into(), None).await.unwrap();
+    r.jobs.complete(&current).await.unwrap();
+    assert_eq!(r.jobs.prune_finished(Utc::now() + TimeDelta::seconds(1)).await.unwrap(), 1);
+}
+
+pub async fn job_failures_retry_then_die(r: &Repos) {
+    r.jobs.enqueue(greet("a", 0)).await.unwrap();
+
+    let job = r.jobs.claim("default", 1, LEASE).await.unwrap().remove(0);
+    r.jobs.fail(&job, "boom".into(), Some(Utc::now() + TimeDelta::seconds(3600))).await.unwrap();
+    assert!(claimed_names(r, 10, LEASE).await.is_empty());
+
+    r.jobs.enqueue(greet("b", 0)).await.unwrap();
+    let job = r.jobs.claim("default", 1, LEASE).await.unwrap().remove(0);
+    r.jobs.fail(&job, "boom".into(), Some(Utc::now() - TimeDelta::seconds(1))).await.unwrap();
+    let job = r.jobs.claim("default", 1, LEASE).await.unwrap().remove(0);
+    assert_eq!(job.payload, json!({ "name": "b" }));
+    assert_eq!(job.attempts, 2);
+
+    r.jobs.fail(&job, "boom".into(), None).await.unwrap();
+    assert!(claimed_names(r, 10, LEASE).await.is_empty());
+    assert_eq!(r.jobs.prune_finished(Utc::now() + TimeDelta::seconds(1)).await.unwrap(), 1);
+}
+
+pub async fn job_prune_keeps_recent_and_unfinished(r: &Repos) {
+    r.jobs.enqueue(greet("done", 0)).await.unwrap();
+    r.jobs.enqueue(greet("waiting", -3600)).await.unwrap();
+    let job = r.jobs.claim("default", 1, LEASE).await.unwrap().remove(0);
+    r.jobs.complete(&job).await.unwrap();
+
+    assert_eq!(r.jobs.prune_finished(Utc::now() - TimeDelta::seconds(3600)).await.unwrap(), 0);
+    assert_eq!(r.jobs.prune_finished(Utc::now() + TimeDelta::seconds(1)).await.unwrap(), 1);
+    assert_eq!(r.jobs.prune_finished(Utc::now() + TimeDelta::seconds(1)).await.unwrap(), 0);
+}
+
+pub async fn worker_runs_typed_handlers(r: &Repos) {
+    let handler = GreetHandler::default();
+    let worker = JobWorker::new(r.jobs.clone()).register(handler.clone());
+    r.jobs.enqueue(greet("a", 0)).await.unwrap();
+    r.jobs.enqueue(greet("b", 0)).await.unwrap();
+
+    assert_eq!(worker.run_once("default", 10).await.unwrap(), 2);
+    assert_eq!(*handler.greeted.lock().unwrap(), ["a", "b"]);
+    assert_eq!(worker.run_once("default", 10).await.unwrap(), 0);
+    assert_eq!(r.jobs.prune_finished(Utc::now() + TimeDelta::seconds(1)).await.unwrap(), 2);
+}
+
+pub async fn worker_retries_with_backoff_until_max_attempts(r: &Repos) {
+    let worker = JobWorker::new(r.jobs.clone()).register(GreetHandler { failing: true, ..Default::default() });
+    r.jobs.enqueue(greet("a", 0)).await.unwrap();
+
+    // first failure is retried later, not right away
+    assert_eq!(worker.run_once("default", 10).await.unwrap(), 1);
+    assert_eq!(worker.run_once("default", 10).await.unwrap(), 0);
+    assert_eq!(r.jobs.prune_finished(Utc::now() + TimeDelta::seconds(1)).await.unwrap(), 0);
+
+    // a kind no handler knows fails like a handler error
+    let mut unknown = greet("x", 0);
+    unknown.kind = "no_such_job".into();
+    unknown.max_attempts = 1;
+    r.jobs.enqueue(unknown).await.unwrap();
+    assert_eq!(worker.run_once("default", 10).await.unwrap(), 1);
+    assert_eq!(r.jobs.prune_finished(Utc::now() + TimeDelta::seconds(1)).await.unwrap(), 1);
+}
+
+pub async fn scheduler_enqueues_each_fire_time_once(r: &Repos) {
+    let scheduler = JobScheduler::new(r.jobs.clone())
+        .add("prune", "0 * * * * *", &PruneFinishedJobs { retention_days: 7 })
+        .unwrap();
+    let start = Utc::now() - TimeDelta::minutes(10);
+    let until = start + TimeDelta::minutes(3);
+
+    assert_eq!(scheduler.enqueue_due(start, until).await.unwrap(), 3);
+    // a second instance running the same schedule
+    assert_eq!(scheduler.enqueue_due(start, until).await.unwrap(), 0);
+
+    let jobs = r.jobs.claim("default", 10, LEASE).await.unwrap();
+    assert_eq!(jobs.len(), 3);
+    assert!(jobs.iter().all(|j| j.kind == "prune_finished_jobs" && j.run_at.timestamp() % 60 == 0));
+    assert_eq!(jobs[0].payload, json!({ "retention_days": 7 }));
+
+    let invalid = JobScheduler::new(r.jobs.clone()).add("bad", "every day", &PruneFinishedJobs { retention_days: 7 });
+    assert!(matches!(invalid, Err(DomainError::Validation(_))));
+}
@@ -0,0 +1,268 @@
+//! Repository conformance suite: every case is written once against the
+//! repository traits and instantiated for each backend, so the in-memory
+//! Postgres and SQLite implementations cannot drift apart unnoticed.
+//!
+//! Postgres cases need `TEST_DATABASE_URL` and are ignored unless asked
+//! for with `cargo test -- --include-ignored`.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::domain::api_key::ApiKeyRepository;
+use crate::domain::cart::CartRepository;
+use crate::domain::category::CategoryRepository;
+use crate::domain::inventory::InventoryRepository;
+use crate::domain::job::JobQueue;
+use crate::domain::notification::NotificationRepository;
+use crate::domain::order::OrderRepository;
+use crate::domain::payment::PaymentRepository;
+use crate::domain::product::ProductRepository;
+use crate::domain::promotion::PromotionRepository;
+use crate::domain::purchasing::PurchasingRepository;
+use crate::domain::reservation::ReservationRepository;
+use crate::domain::uow::UnitOfWork;
+use crate::domain::user::UserRepository;
+use crate::domain::warehouse::WarehouseRepository;
+use crate::infra::repository::memory::{
+    InMemoryApiKeyRepository, InMemoryCartRepository, InMemoryCategoryRepository, InMemoryInventoryRepository, InMemoryJobQueue,
+    InMemoryNotificationRepository, InMemoryOrderRepository, InMemoryPaymentRepository, InMemoryProductRepository, InMemoryPromotionRepository, InMemoryPurchasingRepository, InMemoryReservationRepository,
+    InMemoryStore,
+    InMemoryUnitOfWork, InMemoryUserRepository, InMemoryWarehouseRepository,
+};
+use crate::infra::repository::sqlite::{
+    self, SqliteApiKeyRepository, SqliteCartRepository, SqliteCategoryRepository, SqliteInventoryRepository, SqliteJobQueue,
+    SqliteNotificationRepository, SqliteOrderRepository, SqlitePaymentRepository, SqliteProductRepository, SqlitePromotionRepository, SqlitePurchasingRepository, SqliteReservationRepository,
+    SqliteUnitOfWork,
+    SqliteUserRepository, SqliteWarehouseRepository,
+};
+
+mod api_key;
+mod cache;
+mod cart;
+mod category;
+mod inventory;
+mod job;
+mod notification;
+mod order;
+mod outbox;
+mod payment;
+mod postgres;
+mod product;
+mod promotion;
+mod purchasing;
+mod reservation;
+mod uow;
+mod user;
+mod warehouse;
+
+use api_key::*;
+use cache::*;
+use cart::*;
+use category::*;
+use inventory::*;
+use job::*;
+use notification::*;
+use order::*;
+use outbox::*;
+use payment::*;
+use product::*;
+use promotion::*;
+use purchasing::*;
+use reservation::*;
+use uow::*;
+use user::*;
+use warehouse::*;
+
+/// One backend's repositories, all over the same fresh, empty database.
+pub struct Repos {
+    pub users: Arc<dyn UserRepository>,
+    pub categories: Arc<dyn CategoryRepository>,
+    pub products: Arc<dyn ProductRepository>,
+    pub inventory: Arc<dyn InventoryRepository>,
+    pub warehouses: Arc<dyn WarehouseRepository>,
+    pub uow: Arc<dyn UnitOfWork>,
+    pub jobs: Arc<dyn JobQueue>,
+    pub api_keys: Arc<dyn ApiKeyRepository>,
+    pub carts: Arc<dyn CartRepository>,
+    pub reservations: Arc<dyn ReservationRepository>,
+    pub orders: Arc<dyn OrderRepository>,
+    pub payments: Arc<dyn PaymentRepository>,
+    pub notifications: Arc<dyn NotificationRepository>,
+    pub purchasing: Arc<dyn PurchasingRepository>,
+    pub promotions: Arc<dyn PromotionRepository>,
+}
+
+async fn with_memory_repos<F, Fut>(test: F)
+where
+    F: FnOnce(Repos) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let store = InMemoryStore::new();
+    test(Repos {
+        users: Arc::new(InMemoryUserRepository::new(store.clone())),
+        categories: Arc::new(InMemoryCategoryRepository::new(store.clone())),
+        products: Arc::new(InMemoryProductRepository::new(store.clone())),
+        inventory: Arc::new(InMemoryInventoryRepository::new(store.clone())),
+        warehouses: Arc::new(InMemoryWarehouseRepository::new(store.clone())),
+        uow: Arc::new(InMemoryUnitOfWork::new(store.clone())),
+        jobs: Arc::new(InMemoryJobQueue::new()),
+        api_keys: Arc::new(InMemoryApiKeyRepository::new(store.clone())),
+        carts: Arc::new(InMemoryCartRepository::new(store.clone())),
+        reservations: Arc::new(InMemoryReservationRepository::new(store.clone())),
+        orders: Arc::new(InMemoryOrderRepository::new(store.clone())),
+        payments: Arc::new(InMemoryPaymentRepository::new(store.clone())),
+        notifications: Arc::new(InMemoryNotificationRepository::new(store.clone())),
+        purchasing: Arc::new(InMemoryPurchasingRepository::new(store.clone())),
+        promotions: Arc::new(InMemoryPromotionRepository::new(store)),
+    })
+    .await;
+}
+
+/// A fresh `sqlite::memory:` database per test, migrated like a file database.
+async fn with_sqlite_repos<F, Fut>(test: F)
+where
+    F: FnOnce(Repos) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let pool = sqlite::connect("sqlite::memory:", 2)
+        .await
+        .expect("Failed to open in-memory SQLite database");
+    test(Repos {
+        users: Arc::new(SqliteUserRepository::new(pool.clone())),
+        categories: Arc::new(SqliteCategoryRepository::new(pool.clone())),
+        products: Arc::new(SqliteProductRepository::new(pool.clone())),
+        inventory: Arc::new(SqliteInventoryRepository::new(pool.clone())),
+        warehouses: Arc::new(SqliteWarehouseRepository::new(pool.clone())),
+        uow: Arc::new(SqliteUnitOfWork::new(pool.clone())),
+        jobs: Arc::new(SqliteJobQueue::new(pool.clone())),
+        api_keys: Arc::new(SqliteApiKeyRepository::new(pool.clone())),
+        carts: Arc::new(SqliteCartRepository::new(pool.clone())),
+        reservations: Arc::new(SqliteReservationRepository::new(pool.clone())),
+        orders: Arc::new(SqliteOrderRepository::new(pool.clone())),
+        payments: Arc::new(SqlitePaymentRepository::new(pool.clone())),
+        notifications: Arc::new(SqliteNotificationRepository::new(pool.clone())),
+        purchasing: Arc::new(SqlitePurchasingRepository::new(pool.clone())),
+        promotions: Arc::new(SqlitePromotionRepository::new(pool.clone())),
+    })
+    .await;
+    pool.close().await;
+}
+
+/// Generate a `#[tokio::test]` per case, running it through `$with_repos`;
+/// leading attributes are put on every case.
+macro_rules! conformance_tests {
+    ($(#[$attr:meta])* $with_repos:path) => {
+        conformance_tests!(@cases [$(#[$attr])*] $with_repos;
+            user_create_assigns_sequential_ids,
+            user_create_stores_password_hash,
+            user_get_missing_returns_none,
+            user_get_by_username,
+            user_get_all_in_insert_order,
+            user_update_rehashes_password,
+            user_update_missing_fails,
+            user_delete_is_idempotent,
+            user_set_admin,
+            category_create_and_get,
+            category_get_all_in_insert_order,
+            category_update,
+            category_update_missing_fails,
+            category_delete_is_idempotent,
+            category_delete_with_products_fails,
+            product_create_and_get,
+            product_price_rounds_like_numeric,
+            product_price_overflow_fails,
+            product_requires_existing_category,
+            product_get_missing_returns_none,
+            product_get_by_category,
+            product_get_all_in_insert_order,
+            product_lists_are_paged,
+            stock_movements_update_the_stock,
+            stock_never_goes_negative,
+            stock_movements_keep_actor_and_order,
+            stock_levels_compare_stock_with_ledger,
+            stock_thresholds_list_low_stock_by_category,
+            notification_create_list_and_mark_read,
+            purchasing_suppliers_and_product_links,
+            purchasing_orders_receive_up_to_the_quantity,
+            promotion_create_get_update_and_delete,
+            promotion_codes_are_unique_and_references_checked,
+            promotion_uses_count_redemptions_per_user,
+            warehouse_create_and_list_by_priority,
+            warehouse_stock_moves_per_location,
+            reservation_hold_replaces_the_carts_earlier_one,
+            reservation_expired_ones_are_not_counted,
+            reservation_released_with_its_cart,
+            uow_commits_on_success,
+            uow_rolls_back_on_error,
+            uow_rolls_back_on_repository_error,
+            uow_retries_conflicts,
+            uow_gives_up_after_repeated_conflicts,
+            uow_serializes_check_then_insert,
+            uow_keeps_writes_made_outside_it,
+            outbox_append_stores_event,
+            outbox_rolls_back_with_the_change,
+            relay_publishes_in_order_once,
+            relay_publishes_in_batches,
+            relay_holds_back_aggregate_after_failure,
+            relay_requires_every_sink,
+            relay_backs_off_failing_messages,
+            relay_gives_up_after_max_attempts,
+            relay_publishes_outside_its_claiming_transaction,
+            job_claims_due_jobs_oldest_first,
+            job_claim_skips_future_and_other_queues,
+            job_unique_key_dedupes,
+            job_expired_lease_is_claimed_again,
+            job_failures_retry_then_die,
+            job_prune_keeps_recent_and_unfinished,
+            worker_runs_typed_handlers,
+            worker_retries_with_backoff_until_max_attempts,
+            scheduler_enqueues_each_fire_time_once,
+            api_key_create_and_find_active,
+            api_key_revoke,
+            cache_serves_reads_until_service_write,
+            cache_does_not_keep_misses,
+            cache_evicts_least_recently_used,
+            cache_entries_expire,
+            cart_create_and_find_by_owner,
+            cart_set_line_upserts_in_place,
+            cart_remove_line,
+            cart_coupon_is_kept_until_replaced,
+            cart_delete_removes_lines,
+            cart_deleted_with_its_user,
+            cart_delete_idle,
+            order_create_and_get,
+            order_get_by_user_newest_first,
+            order_get_by_user_pages_orders_not_lines,
+            order_kept_when_user_deleted,
+            order_status_changes_are_recorded,
+            checkout_never_oversells,
+            payment_intent_create_and_update,
+            payment_attempts_in_order,
+            payment_webhook_event_recorded_once,
+        );
+    };
+    (@cases $attrs:tt $with_repos:path; $($case:ident),* $(,)?) => {
+        $(
+            conformance_tests!(@case $attrs $with_repos; $case);
+        )*
+    };
+    (@case [$($attr:tt)*] $with_repos:path; $case:ident) => {
+        #[tokio::test]
+        $($attr)*
+        async fn $case() {
+            $with_repos(|repos| async move { super::$case(&repos).await }).await;
+        }
+    };
+}
+
+mod memory {
+    conformance_tests!(super::with_memory_repos);
+}
+
+mod sqlite_backend {
+    conformance_tests!(super::with_sqlite_repos);
+}
+
+mod pg {
+    conformance_tests!(#[ignore = "needs Postgres at TEST_DATABASE_URL"] super::postgres::with_postgres_repos);
+}
@@ -0,0 +1,39 @@
+use chrono::{TimeDelta, Utc};
+
+use crate::domain::notification::Notification;
+
+use super::Repos;
+
+fn notification(message: &str) -> Notification {
+    Notification {
+        id: 0,
+        kind: "low_stock".into(),
+        message: message.into(),
+        product_id: None,
+        created_at: Utc::now(),
+        read_at: None,
+    }
+}
+
+pub async fn notification_create_list_and_mark_read(r: &Repos) {
+    let first = r.notifications.create(notification("first")).await.unwrap();
+    assert_eq!((first.id, first.kind.as_str(), first.message.as_str()), (1, "low_stock", "first"));
+    assert!((Utc::now() - first.created_at).abs() < TimeDelta::minutes(1));
+    assert_eq!(first.read_at, None);
+    let second = r.notifications.create(notification("second")).await.unwrap();
+
+    assert_eq!(r.notifications.list(false).await.unwrap(), [second.clone(), first.clone()]);
+    assert!(r.notifications.mark_read(first.id).await.unwrap());
+    let read_at = r.notifications.list(false).await.unwrap()[1].read_at.unwrap();
+    assert!((Utc::now() - read_at).abs() < TimeDelta::minutes(1));
+    assert_eq!(r.notifications.list(true).await.unwrap(), [second]);
+
+    // marking it again keeps the first read_at
+    assert!(r.notifications.mark_read(first.id).await.unwrap());
+    assert_eq!(r.notifications.list(false).await.unwrap()[1].read_at, Some(read_at));
+    assert!(!r.notifications.mark_read(42).await.unwrap());
+
+    // notifications.product_id REFERENCES products(id)
+    let unknown = Notification { product_id: Some(42), ..notification("third") };
+    assert!(r.notifications.create(unknown).await.is_err());
+}
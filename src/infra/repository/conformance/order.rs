@@ -0,0 +1,161 @@
+use chrono::{TimeDelta, Utc};
+
+use crate::domain::cart::{CartLine, CartOwner};
+use crate::domain::order::{Order, OrderLine, OrderStatus, StatusChange};
+use crate::domain::page::Page;
+use crate::domain::product::Product;
+use crate::domain::DomainError;
+use crate::usecases::order_service::OrderService;
+
+use super::Repos;
+
+/// A category with product `Rust Book` at 10.00, 4 in stock.
+async fn book(r: &Repos) -> i64 {
+    let category_id = r.categories.create("Books".into()).await.unwrap();
+    r.products
+        .create(Product {
+            id: 0,
+            name: "Rust Book".into(),
+            description: None,
+            price: 10.0,
+            stock: 4,
+            category_id,
+            active: true,
+        })
+        .await
+        .unwrap()
+}
+
+fn line(product_id: i64, quantity: i32, unit_price: f64) -> OrderLine {
+    OrderLine { product_id, name: "Rust Book".into(), quantity, unit_price }
+}
+
+fn order(user_id: Option<i64>, lines: Vec<OrderLine>, total: f64) -> Order {
+    Order { id: 0, user_id, lines, discount: 0.0, shipping: 0.0, total, status: OrderStatus::Pending, created_at: Utc::now() }
+}
+
+fn change(from: Option<OrderStatus>, to: OrderStatus, actor_id: Option<i64>, reason: Option<&str>) -> StatusChange {
+    StatusChange { from, to, actor_id, reason: reason.map(Into::into), changed_at: Utc::now() }
+}
+
+pub async fn order_create_and_get(r: &Repos) {
+    let user_id = r.users.create("alice".into(), "pw".into()).await.unwrap();
+    let book = book(r).await;
+
+    let id = r
+        .orders
+        .create(order(Some(user_id), vec![line(book, 2, 9.999), line(book, 1, 5.0)], 24.999))
+        .await
+        .unwrap();
+    let stored = r.orders.get(id).await.unwrap().unwrap();
+    assert_eq!(stored.id, id);
+    assert_eq!(stored.user_id, Some(user_id));
+    // NUMERIC(10, 2), in the given order
+    assert_eq!(stored.lines, [line(book, 2, 10.0), line(book, 1, 5.0)]);
+    assert_eq!(stored.total, 25.0);
+    assert_eq!(stored.status, OrderStatus::Pending);
+    assert!((Utc::now() - stored.created_at).abs() < TimeDelta::minutes(1));
+    assert!(r.orders.get(42).await.unwrap().is_none());
+
+    let discounted = Order { discount: 4.999, shipping: 5.0, ..order(None, vec![line(book, 1, 10.0)], 10.0) };
+    let id = r.orders.create(discounted).await.unwrap();
+    let stored = r.orders.get(id).await.unwrap().unwrap();
+    assert_eq!((stored.discount, stored.shipping, stored.total), (5.0, 5.0, 10.0));
+
+    // orders.user_id REFERENCES users(id), order_items.product_id REFERENCES products(id)
+    assert!(r.orders.create(order(Some(42), vec![line(book, 1, 1.0)], 1.0)).await.is_err());
+    assert!(r.orders.create(order(Some(user_id), vec![line(42, 1, 1.0)], 1.0)).await.is_err());
+}
+
+pub async fn order_get_by_user_newest_first(r: &Repos) {
+    let alice = r.users.create("alice".into(), "pw".into()).await.unwrap();
+    let bob = r.users.create("bob".into(), "pw".into()).await.unwrap();
+    let book = book(r).await;
+    let first = r.orders.create(order(Some(alice), vec![line(book, 1, 10.0)], 10.0)).await.unwrap();
+    r.orders.create(order(Some(bob), vec![line(book, 1, 10.0)], 10.0)).await.unwrap();
+    let second = r.orders.create(order(Some(alice), vec![line(book, 2, 10.0)], 20.0)).await.unwrap();
+
+    let orders = r.orders.get_by_user_id(alice, Page::ALL).await.unwrap();
+    let ids: Vec<_> = orders.iter().map(|o| o.id).collect();
+    assert_eq!(ids, [second, first]);
+    assert_eq!(orders[0].lines, [line(book, 2, 10.0)]);
+    assert!(r.orders.get_by_user_id(42, Page::ALL).await.unwrap().is_empty());
+}
+
+pub async fn order_get_by_user_pages_orders_not_lines(r: &Repos) {
+    let alice = r.users.create("alice".into(), "pw".into()).await.unwrap();
+    let book = book(r).await;
+    let mut ids = Vec::new();
+    for _ in 0..3 {
+        let lines = vec![line(book, 1, 10.0), line(book, 2, 10.0)];
+        ids.push(r.orders.create(order(Some(alice), lines, 30.0)).await.unwrap());
+    }
+
+    let orders = r.orders.get_by_user_id(alice, Page { limit: Some(2), offset: 1 }).await.unwrap();
+    let paged: Vec<_> = orders.iter().map(|o| o.id).collect();
+    assert_eq!(paged, [ids[1], ids[0]]);
+    assert!(orders.iter().all(|o| o.lines.len() == 2));
+}
+
+pub async fn order_kept_when_user_deleted(r: &Repos) {
+    let user_id = r.users.create("alice".into(), "pw".into()).await.unwrap();
+    let book = book(r).await;
+    let id = r.orders.create(order(Some(user_id), vec![line(book, 1, 10.0)], 10.0)).await.unwrap();
+
+    r.users.delete(user_id).await.unwrap();
+    assert_eq!(r.orders.get(id).await.unwrap().unwrap().user_id, None);
+    assert!(r.orders.get_by_user_id(user_id, Page::ALL).await.unwrap().is_empty());
+}
+
+pub async fn order_status_changes_are_recorded(r: &Repos) {
+    let alice = r.users.create("alice".into(), "pw".into()).await.unwrap();
+    let admin = r.users.create("admin".into(), "pw".into()).await.unwrap();
+    let book = book(r).await;
+    let id = r.orders.create(order(Some(alice), vec![line(book, 1, 10.0)], 10.0)).await.unwrap();
+    assert!(r.orders.status_history(id).await.unwrap().is_empty());
+
+    r.orders.record_status_change(id, change(None, OrderStatus::Pending, Some(alice), None)).await.unwrap();
+    let paid = change(Some(OrderStatus::Pending), OrderStatus::Paid, Some(admin), Some("wire transfer"));
+    r.orders.record_status_change(id, paid).await.unwrap();
+    assert_eq!(r.orders.get(id).await.unwrap().unwrap().status, OrderStatus::Paid);
+
+    let history = r.orders.status_history(id).await.unwrap();
+    let steps: Vec<_> = history.iter().map(|c| (c.from, c.to, c.actor_id, c.reason.as_deref())).collect();
+    assert_eq!(
+        steps,
+        [
+            (None, OrderStatus::Pending, Some(alice), None),
+            (Some(OrderStatus::Pending), OrderStatus::Paid, Some(admin), Some("wire transfer")),
+        ]
+    );
+    assert!((Utc::now() - history[1].changed_at).abs() < TimeDelta::minutes(1));
+
+    // the history outlives the actor's account
+    r.users.delete(admin).await.unwrap();
+    assert_eq!(r.orders.status_history(id).await.unwrap()[1].actor_id, None);
+
+    // order_status_changes.order_id REFERENCES orders(id)
+    assert!(r.orders.record_status_change(42, change(None, OrderStatus::Paid, None, None)).await.is_err());
+}
+
+/// Two carts wanting 3 of the 4 in stock: one checkout wins, the other is
+/// told what is left, and the stock never goes negative.
+pub async fn checkout_never_oversells(r: &Repos) {
+    let book = book(r).await;
+    let mut users = Vec::new();
+    for name in ["alice", "bob"] {
+        let user_id = r.users.create(name.into(), "pw".into()).await.unwrap();
+        let cart = r.carts.create(&CartOwner::User(user_id)).await.unwrap();
+        r.carts.set_line(cart, CartLine { product_id: book, quantity: 3, unit_price: 10.0 }).await.unwrap();
+        users.push(user_id);
+    }
+    let orders = OrderService::new(r.orders.clone(), r.uow.clone());
+
+    let (a, b) = tokio::join!(orders.checkout(users[0], None), orders.checkout(users[1], None));
+    let (winner, loser) = if a.is_ok() { (a, b) } else { (b, a) };
+    let winner = winner.unwrap();
+    assert_eq!((winner.lines[0].quantity, winner.total), (3, 30.0));
+    let expected = format!("only 1 of product {book} in stock");
+    assert!(matches!(&loser, Err(DomainError::Validation(msg)) if *msg == expected), "{loser:?}");
+    assert_eq!(r.products.get_by_product_id(book).await.unwrap().unwrap().stock, 1);
+}
@@ -0,0 +1,243 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::domain::event::{DomainEvent, EventSink, OutboxMessage};
+use crate::domain::page::Page;
+use crate::domain::DomainError;
+use crate::usecases::outbox_relay::OutboxRelay;
+
+use super::Repos;
+
+/// Records what it was sent; fails the first delivery of chosen ids, or
+/// every one.
+#[derive(Default)]
+struct RecordingSink {
+    published: Mutex<Vec<(i64, String, i64)>>,
+    fail_once: Mutex<HashSet<i64>>,
+    fail_always: HashSet<i64>,
+}
+
+impl RecordingSink {
+    fn failing_once(ids: &[i64]) -> Self {
+        Self { fail_once: Mutex::new(ids.iter().copied().collect()), ..Default::default() }
+    }
+
+    fn failing_always(ids: &[i64]) -> Self {
+        Self { fail_always: ids.iter().copied().collect(), ..Default::default() }
+    }
+
+    fn ids(&self) -> Vec<i64> {
+        self.published.lock().unwrap().iter().map(|(id, _, _)| *id).collect()
+    }
+}
+
+#[async_trait]
+impl EventSink for RecordingSink {
+    fn name(&self) -> &'static str {
+        "recording"
+    }
+
+    async fn publish(&self, message: &OutboxMessage) -> Result<(), DomainError> {
+        if self.fail_once.lock().unwrap().remove(&message.id) || self.fail_always.contains(&message.id) {
+            return Err(DomainError::Unexpected("sink unavailable".into()));
+        }
+        self.published.lock().unwrap().push((
+            message.id,
+            message.aggregate_type.clone(),
+            message.aggregate_id,
+        ));
+        Ok(())
+    }
+}
+
+/// Runs another relay while publishing its first message, as a second
+/// instance polling at the same moment would.
+struct ConcurrentRelaySink {
+    sink: RecordingSink,
+    other: Mutex<Option<OutboxRelay>>,
+    other_published: Mutex<Vec<usize>>,
+}
+
+#[async_trait]
+impl EventSink for ConcurrentRelaySink {
+    fn name(&self) -> &'static str {
+        "concurrent"
+    }
+
+    async fn publish(&self, message: &OutboxMessage) -> Result<(), DomainError> {
+        let other = self.other.lock().unwrap().take();
+        if let Some(other) = other {
+            let published = other.run_once().await?;
+            self.other_published.lock().unwrap().push(published);
+        }
+        self.sink.publish(message).await
+    }
+}
+
+async fn append(r: &Repos, events: Vec<DomainEvent>) -> Vec<i64> {
+    r.uow
+        .transaction(move |tx| {
+            let events = events.clone();
+            Box::pin(async move {
+                let mut ids = Vec::new();
+                for event in &events {
+                    ids.push(tx.outbox.append(event).await?);
+                }
+                Ok(ids)
+            })
+        })
+        .await
+        .unwrap()
+}
+
+async fn pending(r: &Repos) -> Vec<OutboxMessage> {
+    r.uow
+        .transaction(|tx| Box::pin(async move { tx.outbox.pending(100).await }))
+        .await
+        .unwrap()
+}
+
+fn renamed(id: i64, new_name: &str) -> DomainEvent {
+    DomainEvent::CategoryRenamed { id, old_name: "old".into(), new_name: new_name.into() }
+}
+
+pub async fn outbox_append_stores_event(r: &Repos) {
+    let ids = append(r, vec![renamed(7, "Books"), DomainEvent::UserDeleted { id: 3 }]).await;
+
+    let messages = pending(r).await;
+    assert_eq!(messages.iter().map(|m| m.id).collect::<Vec<_>>(), ids);
+    assert_eq!(messages[0].aggregate_type, "category");
+    assert_eq!(messages[0].aggregate_id, 7);
+    assert_eq!(messages[0].event_type, "CategoryRenamed");
+    assert_eq!(messages[0].payload, json!({ "id": 7, "old_name": "old", "new_name": "Books" }));
+    assert_eq!(messages[1].aggregate_type, "user");
+    assert_eq!(messages[1].payload, json!({ "id": 3 }));
+}
+
+pub async fn outbox_rolls_back_with_the_change(r: &Repos) {
+    let result: Result<(), _> = r
+        .uow
+        .transaction(|tx| {
+            Box::pin(async move {
+                let id = tx.categories.create("Books".into()).await?;
+                tx.outbox.append(&DomainEvent::CategoryCreated { id, name: "Books".into() }).await?;
+                Err(DomainError::Validation("nope".into()))
+            })
+        })
+        .await;
+
+    assert!(result.is_err());
+    assert!(pending(r).await.is_empty());
+    assert!(r.categories.get_all_categories(Page::ALL).await.unwrap().is_empty());
+}
+
+pub async fn relay_publishes_in_order_once(r: &Repos) {
+    let ids = append(r, vec![renamed(1, "a"), renamed(2, "b"), renamed(1, "c")]).await;
+    let sink = Arc::new(RecordingSink::default());
+    let relay = OutboxRelay::new(r.uow.clone(), vec![sink.clone()]);
+
+    assert_eq!(relay.run_once().await.unwrap(), 3);
+    assert_eq!(sink.ids(), ids);
+    assert!(pending(r).await.is_empty());
+
+    assert_eq!(relay.run_once().await.unwrap(), 0);
+    assert_eq!(sink.ids(), ids);
+}
+
+pub async fn relay_publishes_in_batches(r: &Repos) {
+    let ids = append(r, (1..=5).map(|i| renamed(i, "x")).collect()).await;
+    let sink = Arc::new(RecordingSink::default());
+    let relay = OutboxRelay::new(r.uow.clone(), vec![sink.clone()]).with_batch_size(2);
+
+    assert_eq!(relay.run_once().await.unwrap(), 2);
+    assert_eq!(relay.run_once().await.unwrap(), 2);
+    assert_eq!(relay.run_once().await.unwrap(), 1);
+    assert_eq!(sink.ids(), ids);
+}
+
+pub async fn relay_holds_back_aggregate_after_failure(r: &Repos) {
+    let ids = append(r, vec![renamed(1, "a"), renamed(2, "b"), renamed(1, "c"), renamed(2, "d")]).await;
+    let sink = Arc::new(RecordingSink::failing_once(&[ids[0]]));
+    let relay = OutboxRelay::new(r.uow.clone(), vec![sink.clone()]).with_retries(10, Duration::ZERO);
+
+    // category 1 is stuck behind its failed first event; category 2 goes out
+    assert_eq!(relay.run_once().await.unwrap(), 2);
+    assert_eq!(sink.ids(), [ids[1], ids[3]]);
+    assert_eq!(pending(r).await.iter().map(|m| m.id).collect::<Vec<_>>(), [ids[0], ids[2]]);
+
+    assert_eq!(relay.run_once().await.unwrap(), 2);
+    assert_eq!(sink.ids(), [ids[1], ids[3], ids[0], ids[2]]);
+    assert!(pending(r).await.is_empty());
+}
+
+pub async fn relay_requires_every_sink(r: &Repos) {
+    let ids = append(r, vec![renamed(1, "a")]).await;
+    let ok = Arc::new(RecordingSink::default());
+    let flaky = Arc::new(RecordingSink::failing_once(&[ids[0]]));
+    let relay = OutboxRelay::new(r.uow.clone(), vec![ok.clone(), flaky.clone()]).with_retries(10, Duration::ZERO);
+
+    assert_eq!(relay.run_once().await.unwrap(), 0);
+    assert_eq!(relay.run_once().await.unwrap(), 1);
+    // at-least-once: the healthy sink sees the retry too
+    assert_eq!(ok.ids(), [ids[0], ids[0]]);
+    assert_eq!(flaky.ids(), [ids[0]]);
+}
+
+/// A full batch of failing messages must not stall the outbox: they wait
+/// for their retry, and so does the rest of their aggregate.
+pub async fn relay_backs_off_failing_messages(r: &Repos) {
+    let ids = append(r, vec![renamed(1, "a"), renamed(2, "b"), renamed(1, "c"), renamed(3, "d")]).await;
+    let sink = Arc::new(RecordingSink::failing_always(&[ids[0], ids[1]]));
+    let relay = OutboxRelay::new(r.uow.clone(), vec![sink.clone()]).with_batch_size(2);
+
+    assert_eq!(relay.run_once().await.unwrap(), 0);
+    assert_eq!(pending(r).await.iter().map(|m| m.id).collect::<Vec<_>>(), [ids[3]]);
+    assert_eq!(relay.run_once().await.unwrap(), 1);
+    assert_eq!(sink.ids(), [ids[3]]);
+    assert_eq!(relay.run_once().await.unwrap(), 0);
+}
+
+/// The batch is claimed and the claim committed before anything is sent: a
+/// relay running meanwhile gets the rest of the outbox, not the same batch.
+pub async fn relay_publishes_outside_its_claiming_transaction(r: &Repos) {
+    let ids = append(r, vec![renamed(1, "a"), renamed(2, "b")]).await;
+    let other_sink = Arc::new(RecordingSink::default());
+    let other = OutboxRelay::new(r.uow.clone(), vec![other_sink.clone()]);
+    let sink = Arc::new(ConcurrentRelaySink {
+        sink: RecordingSink::default(),
+        other: Mutex::new(Some(other)),
+        other_published: Mutex::new(Vec::new()),
+    });
+    let relay = OutboxRelay::new(r.uow.clone(), vec![sink.clone()]).with_batch_size(1);
+
+    assert_eq!(relay.run_once().await.unwrap(), 1);
+    assert_eq!(*sink.other_published.lock().unwrap(), [1]);
+    assert_eq!(sink.sink.ids(), [ids[0]]);
+    assert_eq!(other_sink.ids(), [ids[1]]);
+    assert!(pending(r).await.is_empty());
+    assert_eq!(relay.run_once().await.unwrap(), 0);
+}
+
+pub async fn relay_gives_up_after_max_attempts(r: &Repos) {
+    let ids = append(r, vec![renamed(1, "a"), renamed(1, "b")]).await;
+    let sink = Arc::new(RecordingSink::failing_always(&[ids[0]]));
+    let relay = OutboxRelay::new(r.uow.clone(), vec![sink.clone()]).with_retries(3, Duration::ZERO);
+
+    for attempt in 1..=3 {
+        assert_eq!(relay.run_once().await.unwrap(), 0, "attempt {attempt}");
+        let pending: Vec<_> = pending(r).await.iter().map(|m| (m.id, m.attempts)).collect();
+        if attempt < 3 {
+            assert_eq!(pending, [(ids[0], attempt), (ids[1], 0)]);
+        } else {
+            // dead-lettered: the rest of the aggregate goes on
+            assert_eq!(pending, [(ids[1], 0)]);
+        }
+    }
+    assert_eq!(relay.run_once().await.unwrap(), 1);
+    assert_eq!(sink.ids(), [ids[1]]);
+    assert!(pending(r).await.is_empty());
+}
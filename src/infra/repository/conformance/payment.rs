@@ -0,0 +1,104 @@
+use chrono::{TimeDelta, Utc};
+
+use crate::domain::order::{Order, OrderLine, OrderStatus};
+use crate::domain::payment::{PaymentAttempt, PaymentIntent, PaymentOperation, PaymentStatus};
+use crate::domain::product::Product;
+
+use super::Repos;
+
+/// Order 1 for one Rust Book at 19.99.
+async fn order(r: &Repos) -> i64 {
+    let category_id = r.categories.create("Books".into()).await.unwrap();
+    let product = Product {
+        id: 0,
+        name: "Rust Book".into(),
+        description: None,
+        price: 19.99,
+        stock: 4,
+        category_id,
+        active: true,
+    };
+    let product_id = r.products.create(product).await.unwrap();
+    let line = OrderLine { product_id, name: "Rust Book".into(), quantity: 1, unit_price: 19.99 };
+    let order = Order { id: 0, user_id: None, lines: vec![line], discount: 0.0, shipping: 0.0, total: 19.99, status: OrderStatus::Pending, created_at: Utc::now() };
+    r.orders.create(order).await.unwrap()
+}
+
+fn intent(order_id: i64, amount: f64) -> PaymentIntent {
+    PaymentIntent {
+        id: 0,
+        order_id,
+        provider: "mock".into(),
+        provider_ref: None,
+        amount,
+        status: PaymentStatus::Pending,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+fn attempt(operation: PaymentOperation, succeeded: bool, detail: Option<&str>) -> PaymentAttempt {
+    PaymentAttempt { operation, succeeded, detail: detail.map(Into::into), created_at: Utc::now() }
+}
+
+pub async fn payment_intent_create_and_update(r: &Repos) {
+    let order_id = order(r).await;
+    let id = r.payments.create_intent(intent(order_id, 19.999)).await.unwrap();
+    let stored = r.payments.get_intent(id).await.unwrap().unwrap();
+    assert_eq!((stored.id, stored.order_id, stored.provider.as_str()), (id, order_id, "mock"));
+    // NUMERIC(10, 2)
+    assert_eq!(stored.amount, 20.0);
+    assert_eq!((stored.status, stored.provider_ref), (PaymentStatus::Pending, None));
+    assert!((Utc::now() - stored.created_at).abs() < TimeDelta::minutes(1));
+    assert!(r.payments.get_intent(42).await.unwrap().is_none());
+
+    r.payments.update_intent(id, PaymentStatus::Authorized, Some("mock_intent-1".into())).await.unwrap();
+    // no reference keeps the one there is
+    r.payments.update_intent(id, PaymentStatus::Captured, None).await.unwrap();
+    let stored = r.payments.get_intent_by_provider_ref("mock", "mock_intent-1").await.unwrap().unwrap();
+    assert_eq!((stored.id, stored.status), (id, PaymentStatus::Captured));
+    assert!(stored.updated_at >= stored.created_at);
+    assert!(r.payments.get_intent_by_provider_ref("http", "mock_intent-1").await.unwrap().is_none());
+
+    let newer = r.payments.create_intent(intent(order_id, 19.99)).await.unwrap();
+    let ids: Vec<_> = r.payments.get_intents_by_order_id(order_id).await.unwrap().iter().map(|i| i.id).collect();
+    assert_eq!(ids, [newer, id]);
+    assert!(r.payments.get_intents_by_order_id(42).await.unwrap().is_empty());
+
+    // UNIQUE (provider, provider_ref)
+    let taken = r.payments.update_intent(newer, PaymentStatus::Authorized, Some("mock_intent-1".into())).await;
+    assert!(taken.is_err());
+    // payment_intents.order_id REFERENCES orders(id)
+    assert!(r.payments.create_intent(intent(42, 1.0)).await.is_err());
+}
+
+pub async fn payment_attempts_in_order(r: &Repos) {
+    let order_id = order(r).await;
+    let id = r.payments.create_intent(intent(order_id, 19.99)).await.unwrap();
+    assert!(r.payments.attempts(id).await.unwrap().is_empty());
+
+    r.payments.record_attempt(id, attempt(PaymentOperation::Authorize, false, Some("timed out"))).await.unwrap();
+    r.payments.record_attempt(id, attempt(PaymentOperation::Authorize, true, None)).await.unwrap();
+    r.payments.record_attempt(id, attempt(PaymentOperation::Webhook, true, Some("payment.captured"))).await.unwrap();
+    let attempts = r.payments.attempts(id).await.unwrap();
+    let steps: Vec<_> = attempts.iter().map(|a| (a.operation, a.succeeded, a.detail.as_deref())).collect();
+    assert_eq!(
+        steps,
+        [
+            (PaymentOperation::Authorize, false, Some("timed out")),
+            (PaymentOperation::Authorize, true, None),
+            (PaymentOperation::Webhook, true, Some("payment.captured")),
+        ]
+    );
+    assert!((Utc::now() - attempts[0].created_at).abs() < TimeDelta::minutes(1));
+
+    // payment_attempts.intent_id REFERENCES payment_intents(id)
+    assert!(r.payments.record_attempt(42, attempt(PaymentOperation::Capture, true, None)).await.is_err());
+}
+
+pub async fn payment_webhook_event_recorded_once(r: &Repos) {
+    assert!(r.payments.record_webhook_event("mock", "evt_1").await.unwrap());
+    assert!(!r.payments.record_webhook_event("mock", "evt_1").await.unwrap());
+    assert!(r.payments.record_webhook_event("mock", "evt_2").await.unwrap());
+    assert!(r.payments.record_webhook_event("http", "evt_1").await.unwrap());
+}
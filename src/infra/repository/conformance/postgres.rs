@@ -0,0 +1,159 @@
+use std::env;
+use std::future::Future;
+use std::sync::Arc;
+
+use std::time::Duration;
+
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{Executor, PgPool};
+use ulid::Ulid;
+
+use crate::domain::category::CategoryRepository;
+use crate::infra::repository::api_key::PostgresApiKeyRepository;
+use crate::infra::repository::cache_sync::PgCacheSync;
+use crate::infra::repository::cart::PostgresCartRepository;
+use crate::infra::repository::cached::RepositoryCache;
+use crate::infra::repository::category::PostgresCategoryRepository;
+use crate::infra::repository::inventory::PostgresInventoryRepository;
+use crate::infra::repository::job::PostgresJobQueue;
+use crate::infra::repository::notification::PostgresNotificationRepository;
+use crate::infra::repository::order::PostgresOrderRepository;
+use crate::infra::repository::payment::PostgresPaymentRepository;
+use crate::infra::repository::product::PostgresProductRepository;
+use crate::infra::repository::promotion::PostgresPromotionRepository;
+use crate::infra::repository::purchasing::PostgresPurchasingRepository;
+use crate::infra::repository::reservation::PostgresReservationRepository;
+use crate::infra::repository::uow::PostgresUnitOfWork;
+use crate::infra::repository::user::PostgresUserRepository;
+use crate::infra::repository::warehouse::PostgresWarehouseRepository;
+use crate::usecases::category_service::CategoryService;
+
+use super::Repos;
+
+/// Run `test` against Postgres repositories in a throwaway schema with all
+/// migrations applied. The schema is dropped afterwards, even if the test
+/// panics.
+pub async fn with_postgres_repos<F, Fut>(test: F)
+where
+    F: FnOnce(Repos) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    with_postgres_pool(|pool| {
+        test(Repos {
+            users: Arc::new(PostgresUserRepository::new(pool.clone())),
+            categories: Arc::new(PostgresCategoryRepository::new(pool.clone())),
+            products: Arc::new(PostgresProductRepository::new(pool.clone())),
+            inventory: Arc::new(PostgresInventoryRepository::new(pool.clone())),
+            warehouses: Arc::new(PostgresWarehouseRepository::new(pool.clone())),
+            uow: Arc::new(PostgresUnitOfWork::new(pool.clone())),
+            jobs: Arc::new(PostgresJobQueue::new(pool.clone())),
+            api_keys: Arc::new(PostgresApiKeyRepository::new(pool.clone())),
+            carts: Arc::new(PostgresCartRepository::new(pool.clone())),
+            reservations: Arc::new(PostgresReservationRepository::new(pool.clone())),
+            orders: Arc::new(PostgresOrderRepository::new(pool.clone())),
+            payments: Arc::new(PostgresPaymentRepository::new(pool.clone())),
+            notifications: Arc::new(PostgresNotificationRepository::new(pool.clone())),
+            purchasing: Arc::new(PostgresPurchasingRepository::new(pool.clone())),
+            promotions: Arc::new(PostgresPromotionRepository::new(pool)),
+        })
+    })
+    .await;
+}
+
+/// Like [`with_postgres_repos`], for cases that need the pool itself.
+/// Fails without `TEST_DATABASE_URL`: the cases are `#[ignore]`d, so
+/// running them at all means Postgres was asked for.
+pub async fn with_postgres_pool<F, Fut>(test: F)
+where
+    F: FnOnce(PgPool) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    dotenvy::dotenv().ok();
+    let url = env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set for Postgres conformance tests");
+
+    let schema = format!("test_{}", Ulid::new().to_string().to_lowercase());
+    let admin = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&url)
+        .await
+        .expect("Failed to connect to TEST_DATABASE_URL");
+    admin
+        .execute(format!("CREATE SCHEMA {schema}").as_str())
+        .await
+        .expect("Failed to create test schema");
+
+    let search_path = format!("SET search_path TO {schema}");
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .after_connect(move |conn, _meta| {
+            let search_path = search_path.clone();
+            Box::pin(async move {
+                conn.execute(search_path.as_str()).await?;
+                Ok(())
+            })
+        })
+        .connect(&url)
+        .await
+        .expect("Failed to connect to test schema");
+    sqlx::migrate!()
+        .run(&pool)
+        .await
+        .expect("Failed to apply migrations");
+
+    let result = tokio::spawn(test(pool.clone())).await;
+
+    pool.close().await;
+    admin
+        .execute(format!("DROP SCHEMA {schema} CASCADE").as_str())
+        .await
+        .expect("Failed to drop test schema");
+
+    if let Err(e) = result {
+        std::panic::resume_unwind(e.into_panic());
+    }
+}
+
+/// Two replicas on one database: a write through one drops the other's
+/// cached copy.
+#[tokio::test]
+#[ignore = "needs Postgres at TEST_DATABASE_URL"]
+async fn cache_sync_invalidates_other_replicas() {
+    with_postgres_pool(|pool| async move {
+        let categories: Arc<dyn CategoryRepository> = Arc::new(PostgresCategoryRepository::new(pool.clone()));
+        let uow = Arc::new(PostgresUnitOfWork::new(pool.clone()));
+        let replica = |cache: &RepositoryCache| {
+            let sync = PgCacheSync::new(pool.clone(), cache.clone());
+            let service = CategoryService::new(Arc::new(cache.categories(categories.clone())), uow.clone())
+                .with_cache_invalidator(Arc::new(sync.clone()));
+            (service, sync)
+        };
+        let (cache_a, cache_b) =
+            (RepositoryCache::new(10, Duration::from_secs(60)), RepositoryCache::new(10, Duration::from_secs(60)));
+        let (a, _) = replica(&cache_a);
+        let (b, sync_b) = replica(&cache_b);
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let listener = tokio::spawn(sync_b.run(async move {
+            let _ = stopped.await;
+        }));
+        // the listener clears the cache once it is connected
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let id = a.create("Books".into()).await.unwrap();
+        assert_eq!(b.get_by_id(id).await.unwrap().unwrap().name, "Books");
+
+        a.update(id, "E-books".into()).await.unwrap();
+        let mut name = String::new();
+        for _ in 0..50 {
+            name = b.get_by_id(id).await.unwrap().unwrap().name;
+            if name == "E-books" {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(name, "E-books");
+
+        let _ = stop.send(());
+        listener.await.unwrap();
+    })
+    .await;
+}
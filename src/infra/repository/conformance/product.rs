@@ -0,0 +1,124 @@
+use crate::domain::page::Page;
+use crate::domain::product::Product;
+
+use super::Repos;
+
+fn product(name: &str, price: f64, category_id: i64) -> Product {
+    Product {
+        id: 0,
+        name: name.into(),
+        description: Some(format!("{name} description")),
+        price,
+        stock: 5,
+        category_id,
+        active: true,
+    }
+}
+
+pub async fn product_create_and_get(r: &Repos) {
+    let category_id = r.categories.create("Books".into()).await.unwrap();
+    let id = r
+        .products
+        .create(Product { description: None, active: false, ..product("Rust Book", 39.9, category_id) })
+        .await
+        .unwrap();
+    assert_eq!(id, 1);
+
+    let stored = r.products.get_by_product_id(id).await.unwrap().unwrap();
+    assert_eq!(stored.id, id);
+    assert_eq!(stored.name, "Rust Book");
+    assert_eq!(stored.description, None);
+    assert_eq!(stored.price, 39.9);
+    assert_eq!(stored.stock, 5);
+    assert_eq!(stored.category_id, category_id);
+    assert!(!stored.active);
+}
+
+pub async fn product_price_rounds_like_numeric(r: &Repos) {
+    let category_id = r.categories.create("Books".into()).await.unwrap();
+    let cases = [
+        (12.345, 12.35),
+        (1.005, 1.01),
+        (2.675, 2.68),
+        (0.004, 0.0),
+        (0.005, 0.01),
+        (-1.005, -1.01),
+        (123456.785, 123456.79),
+        (99_999_999.99, 99_999_999.99),
+    ];
+
+    for (price, expected) in cases {
+        let id = r.products.create(product("p", price, category_id)).await.unwrap();
+        let stored = r.products.get_by_product_id(id).await.unwrap().unwrap();
+        assert_eq!(stored.price, expected, "price {price}");
+    }
+}
+
+pub async fn product_price_overflow_fails(r: &Repos) {
+    let category_id = r.categories.create("Books".into()).await.unwrap();
+    assert!(r.products.create(product("p", 100_000_000.0, category_id)).await.is_err());
+    assert!(r.products.create(product("p", 99_999_999.995, category_id)).await.is_err());
+    assert!(r.products.get_all_products(Page::ALL).await.unwrap().is_empty());
+}
+
+pub async fn product_requires_existing_category(r: &Repos) {
+    assert!(r.products.create(product("Orphan", 1.0, 42)).await.is_err());
+    assert!(r.products.get_all_products(Page::ALL).await.unwrap().is_empty());
+}
+
+pub async fn product_get_missing_returns_none(r: &Repos) {
+    assert!(r.products.get_by_product_id(42).await.unwrap().is_none());
+}
+
+pub async fn product_get_by_category(r: &Repos) {
+    let books = r.categories.create("Books".into()).await.unwrap();
+    let games = r.categories.create("Games".into()).await.unwrap();
+    let a = r.products.create(product("Rust Book", 39.9, books)).await.unwrap();
+    r.products.create(product("Chess", 15.0, games)).await.unwrap();
+    let b = r.products.create(product("Go Book", 29.9, books)).await.unwrap();
+
+    let ids: Vec<_> = r
+        .products
+        .get_by_category_id(books, Page::ALL)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|p| p.id)
+        .collect();
+    assert_eq!(ids, [a, b]);
+    assert!(r.products.get_by_category_id(42, Page::ALL).await.unwrap().is_empty());
+}
+
+pub async fn product_get_all_in_insert_order(r: &Repos) {
+    let books = r.categories.create("Books".into()).await.unwrap();
+    for name in ["c", "a", "b"] {
+        r.products.create(product(name, 1.0, books)).await.unwrap();
+    }
+
+    let names: Vec<_> = r
+        .products
+        .get_all_products(Page::ALL)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|p| p.name)
+        .collect();
+    assert_eq!(names, ["c", "a", "b"]);
+}
+
+pub async fn product_lists_are_paged(r: &Repos) {
+    let books = r.categories.create("Books".into()).await.unwrap();
+    let games = r.categories.create("Games".into()).await.unwrap();
+    for (name, category_id) in [("a", books), ("b", games), ("c", books), ("d", books), ("e", games)] {
+        r.products.create(product(name, 1.0, category_id)).await.unwrap();
+    }
+    let names = |products: Vec<Product>| products.into_iter().map(|p| p.name).collect::<Vec<_>>();
+
+    let page = Page { limit: Some(2), offset: 1 };
+    assert_eq!(names(r.products.get_all_products(page).await.unwrap()), ["b", "c"]);
+    assert_eq!(names(r.products.get_by_category_id(books, page).await.unwrap()), ["c", "d"]);
+    let rest = Page { limit: None, offset: 3 };
+    assert_eq!(names(r.products.get_all_products(rest).await.unwrap()), ["d", "e"]);
+    let past_the_end = Page { limit: Some(2), offset: 5 };
+    assert!(r.products.get_all_products(past_the_end).await.unwrap().is_empty());
+}
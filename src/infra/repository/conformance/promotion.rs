@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::order::{Order, OrderStatus};
+use crate::domain::product::Product;
+use crate::domain::promotion::{Promotion, PromotionKind, Redemption};
+
+use super::Repos;
+
+fn promotion(name: &str, code: Option<&str>) -> Promotion {
+    Promotion {
+        id: 0,
+        name: name.into(),
+        code: code.map(Into::into),
+        kind: PromotionKind::Percentage,
+        value: 10.0,
+        buy_quantity: None,
+        get_quantity: None,
+        category_id: None,
+        product_id: None,
+        starts_at: None,
+        ends_at: None,
+        max_uses: None,
+        max_uses_per_user: None,
+        active: true,
+    }
+}
+
+/// A category and a product in it.
+async fn catalog(r: &Repos) -> (i64, i64) {
+    let category_id = r.categories.create("Books".into()).await.unwrap();
+    let product = Product {
+        id: 0,
+        name: "Rust Book".into(),
+        description: None,
+        price: 10.0,
+        stock: 5,
+        category_id,
+        active: true,
+    };
+    (category_id, r.products.create(product).await.unwrap())
+}
+
+async fn order(r: &Repos, user_id: Option<i64>) -> i64 {
+    let order = Order {
+        id: 0,
+        user_id,
+        lines: Vec::new(),
+        discount: 0.0,
+        shipping: 0.0,
+        total: 0.0,
+        status: OrderStatus::Pending,
+        created_at: Utc::now(),
+    };
+    r.orders.create(order).await.unwrap()
+}
+
+fn redemption(promotion_id: i64, user_id: Option<i64>, order_id: i64) -> Redemption {
+    Redemption { promotion_id, user_id, order_id, discount: 1.0 }
+}
+
+pub async fn promotion_create_get_update_and_delete(r: &Repos) {
+    let (category_id, product_id) = catalog(r).await;
+    let starts_at = DateTime::from_timestamp(1_800_000_000, 0).unwrap();
+    let ends_at = DateTime::from_timestamp(1_900_000_000, 0).unwrap();
+    let created = Promotion {
+        value: 12.345,
+        category_id: Some(category_id),
+        starts_at: Some(starts_at),
+        ends_at: Some(ends_at),
+        max_uses: Some(100),
+        max_uses_per_user: Some(1),
+        ..promotion("Spring sale", Some("SPRING"))
+    };
+    let id = r.promotions.create(created.clone()).await.unwrap();
+    let stored = r.promotions.get(id).await.unwrap().unwrap();
+    // NUMERIC(10, 2)
+    assert_eq!(stored, Promotion { id, value: 12.35, ..created });
+    assert!(r.promotions.get(42).await.unwrap().is_none());
+
+    let bogo = Promotion {
+        kind: PromotionKind::BuyXGetY,
+        value: 0.0,
+        buy_quantity: Some(2),
+        get_quantity: Some(1),
+        product_id: Some(product_id),
+        active: false,
+        ..promotion("Buy 2 get 1", None)
+    };
+    let other = r.promotions.create(bogo.clone()).await.unwrap();
+    let ids: Vec<_> = r.promotions.list().await.unwrap().iter().map(|p| p.id).collect();
+    assert_eq!(ids, [id, other]);
+
+    let changed = Promotion { id, kind: PromotionKind::FreeShipping, value: 0.0, category_id: None, ..stored };
+    assert!(r.promotions.update(changed.clone()).await.unwrap());
+    assert_eq!(r.promotions.get(id).await.unwrap().unwrap(), changed);
+    assert!(!r.promotions.update(Promotion { id: 42, ..changed }).await.unwrap());
+
+    assert!(r.promotions.delete(id).await.unwrap());
+    assert!(!r.promotions.delete(id).await.unwrap());
+    assert_eq!(r.promotions.list().await.unwrap(), [Promotion { id: other, ..bogo }]);
+}
+
+pub async fn promotion_codes_are_unique_and_references_checked(r: &Repos) {
+    let (category_id, product_id) = catalog(r).await;
+    let spring = r.promotions.create(promotion("Spring", Some("SPRING"))).await.unwrap();
+    let summer = r.promotions.create(promotion("Summer", Some("SUMMER"))).await.unwrap();
+    // any number without a code
+    r.promotions.create(promotion("Auto", None)).await.unwrap();
+    r.promotions.create(promotion("Auto too", None)).await.unwrap();
+
+    assert_eq!(r.promotions.by_code("SPRING").await.unwrap().unwrap().id, spring);
+    assert!(r.promotions.by_code("spring").await.unwrap().is_none());
+    assert!(r.promotions.by_code("WINTER").await.unwrap().is_none());
+
+    // promotions.code UNIQUE
+    assert!(r.promotions.create(promotion("Again", Some("SPRING"))).await.is_err());
+    let taken = Promotion { id: summer, ..promotion("Summer", Some("SPRING")) };
+    assert!(r.promotions.update(taken).await.is_err());
+    assert_eq!(r.promotions.get(summer).await.unwrap().unwrap().code.as_deref(), Some("SUMMER"));
+
+    // REFERENCES categories(id), products(id); not both
+    assert!(r.promotions.create(Promotion { category_id: Some(42), ..promotion("x", None) }).await.is_err());
+    assert!(r.promotions.create(Promotion { product_id: Some(42), ..promotion("x", None) }).await.is_err());
+    let both = Promotion { category_id: Some(category_id), product_id: Some(product_id), ..promotion("x", None) };
+    assert!(r.promotions.create(both).await.is_err());
+    assert!(r.promotions.create(Promotion { max_uses: Some(0), ..promotion("x", None) }).await.is_err());
+}
+
+pub async fn promotion_uses_count_redemptions_per_user(r: &Repos) {
+    let alice = r.users.create("alice".into(), "pw".into()).await.unwrap();
+    let bob = r.users.create("bob".into(), "pw".into()).await.unwrap();
+    let spring = r.promotions.create(promotion("Spring", Some("SPRING"))).await.unwrap();
+    let summer = r.promotions.create(promotion("Summer", Some("SUMMER"))).await.unwrap();
+    let first = order(r, Some(alice)).await;
+    let second = order(r, Some(alice)).await;
+    let third = order(r, Some(bob)).await;
+
+    r.promotions.redeem(redemption(spring, Some(alice), first)).await.unwrap();
+    r.promotions.redeem(redemption(spring, Some(alice), second)).await.unwrap();
+    r.promotions.redeem(redemption(spring, Some(bob), third)).await.unwrap();
+    r.promotions.redeem(redemption(summer, Some(bob), third)).await.unwrap();
+    assert_eq!(r.promotions.uses(spring, None).await.unwrap(), 3);
+    assert_eq!(r.promotions.uses(spring, Some(alice)).await.unwrap(), 2);
+    assert_eq!(r.promotions.uses(spring, Some(bob)).await.unwrap(), 1);
+    assert_eq!(r.promotions.uses(summer, Some(alice)).await.unwrap(), 0);
+    assert_eq!(r.promotions.uses(42, None).await.unwrap(), 0);
+    let uses = r.promotions.uses_by_promotion(None).await.unwrap();
+    assert_eq!(uses, HashMap::from([(spring, 3), (summer, 1)]));
+    let uses = r.promotions.uses_by_promotion(Some(alice)).await.unwrap();
+    assert_eq!(uses, HashMap::from([(spring, 2)]));
+    assert!(r.promotions.uses_by_promotion(Some(42)).await.unwrap().is_empty());
+
+    // REFERENCES promotions(id), users(id), orders(id)
+    assert!(r.promotions.redeem(redemption(42, Some(alice), first)).await.is_err());
+    assert!(r.promotions.redeem(redemption(spring, Some(42), first)).await.is_err());
+    assert!(r.promotions.redeem(redemption(spring, Some(alice), 42)).await.is_err());
+
+    // user_id ON DELETE SET NULL: the use still counts towards the total
+    r.users.delete(alice).await.unwrap();
+    assert_eq!(r.promotions.uses(spring, None).await.unwrap(), 3);
+    assert_eq!(r.promotions.uses(spring, Some(alice)).await.unwrap(), 0);
+
+    // promotion_id ON DELETE CASCADE
+    r.promotions.delete(spring).await.unwrap();
+    assert_eq!(r.promotions.uses(spring, None).await.unwrap(), 0);
+    assert_eq!(r.promotions.uses(summer, None).await.unwrap(), 1);
+}
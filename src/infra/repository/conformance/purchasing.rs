@@ -0,0 +1,134 @@
+use chrono::{TimeDelta, Utc};
+
+use crate::domain::page::Page;
+use crate::domain::product::Product;
+use crate::domain::purchasing::{PurchaseOrder, PurchaseOrderLine, PurchaseOrderStatus, Supplier, SupplierProduct};
+
+use super::Repos;
+
+async fn product(r: &Repos, name: &str) -> i64 {
+    let category_id = match r.categories.get_all_categories(Page::ALL).await.unwrap().first() {
+        Some(category) => category.id,
+        None => r.categories.create("Books".into()).await.unwrap(),
+    };
+    r.products
+        .create(Product {
+            id: 0,
+            name: name.into(),
+            description: None,
+            price: 10.0,
+            stock: 0,
+            category_id,
+            active: true,
+        })
+        .await
+        .unwrap()
+}
+
+async fn supplier(r: &Repos, name: &str) -> i64 {
+    r.purchasing
+        .create_supplier(Supplier { id: 0, name: name.into(), email: Some(format!("{name}@example.com")) })
+        .await
+        .unwrap()
+}
+
+fn link(supplier_id: i64, product_id: i64, sku: &str, cost_price: f64) -> SupplierProduct {
+    SupplierProduct { supplier_id, product_id, sku: sku.into(), cost_price }
+}
+
+fn line(product_id: i64, quantity: i32, unit_cost: f64) -> PurchaseOrderLine {
+    PurchaseOrderLine { product_id, quantity, received: 0, unit_cost }
+}
+
+fn order(supplier_id: i64, lines: Vec<PurchaseOrderLine>) -> PurchaseOrder {
+    PurchaseOrder {
+        id: 0,
+        supplier_id,
+        status: PurchaseOrderStatus::Draft,
+        lines,
+        created_by: None,
+        created_at: Utc::now(),
+    }
+}
+
+pub async fn purchasing_suppliers_and_product_links(r: &Repos) {
+    let acme = supplier(r, "Acme").await;
+    let globex = supplier(r, "Globex").await;
+    assert_eq!((acme, globex), (1, 2));
+    let expected = Supplier { id: acme, name: "Acme".into(), email: Some("Acme@example.com".into()) };
+    assert_eq!(r.purchasing.supplier(acme).await.unwrap(), Some(expected.clone()));
+    assert_eq!(r.purchasing.supplier(42).await.unwrap(), None);
+    assert_eq!(r.purchasing.suppliers().await.unwrap()[0], expected);
+    assert_eq!(r.purchasing.suppliers().await.unwrap().len(), 2);
+    // suppliers.name is UNIQUE
+    assert!(r.purchasing.create_supplier(Supplier { id: 0, name: "Acme".into(), email: None }).await.is_err());
+
+    let chess = product(r, "Chess").await;
+    let go = product(r, "Go").await;
+    // cost prices round like NUMERIC(10, 2)
+    assert_eq!(r.purchasing.set_supplier_product(link(acme, go, "A-2", 3.456)).await.unwrap(), link(acme, go, "A-2", 3.46));
+    r.purchasing.set_supplier_product(link(acme, chess, "A-1", 5.0)).await.unwrap();
+    r.purchasing.set_supplier_product(link(globex, chess, "A-2", 6.0)).await.unwrap();
+    assert_eq!(
+        r.purchasing.supplier_products(acme).await.unwrap(),
+        [link(acme, chess, "A-1", 5.0), link(acme, go, "A-2", 3.46)]
+    );
+
+    // setting it again replaces the terms
+    r.purchasing.set_supplier_product(link(acme, chess, "A-9", 4.5)).await.unwrap();
+    assert_eq!(r.purchasing.supplier_products(acme).await.unwrap()[0], link(acme, chess, "A-9", 4.5));
+    // one sku per supplier, cost prices are not negative, both ends must exist
+    assert!(r.purchasing.set_supplier_product(link(acme, chess, "A-2", 4.5)).await.is_err());
+    assert!(r.purchasing.set_supplier_product(link(acme, chess, "A-1", -1.0)).await.is_err());
+    assert!(r.purchasing.set_supplier_product(link(42, chess, "A-1", 1.0)).await.is_err());
+    assert!(r.purchasing.set_supplier_product(link(acme, 42, "A-3", 1.0)).await.is_err());
+    assert!(r.purchasing.supplier_products(42).await.unwrap().is_empty());
+}
+
+pub async fn purchasing_orders_receive_up_to_the_quantity(r: &Repos) {
+    let acme = supplier(r, "Acme").await;
+    let chess = product(r, "Chess").await;
+    let go = product(r, "Go").await;
+
+    let first = r.purchasing.create_order(order(acme, vec![line(go, 5, 2.5), line(chess, 10, 4.999)])).await.unwrap();
+    let second = r.purchasing.create_order(order(acme, vec![line(go, 1, 2.5)])).await.unwrap();
+    assert_eq!((first, second), (1, 2));
+
+    let stored = r.purchasing.order(first).await.unwrap().unwrap();
+    assert!((Utc::now() - stored.created_at).abs() < TimeDelta::minutes(1));
+    assert_eq!(
+        stored,
+        PurchaseOrder { id: first, lines: vec![line(chess, 10, 5.0), line(go, 5, 2.5)], created_at: stored.created_at, ..order(acme, vec![]) }
+    );
+    assert_eq!(stored.total(), 62.5);
+    assert_eq!(r.purchasing.order(42).await.unwrap(), None);
+    let ids: Vec<i64> = r.purchasing.orders().await.unwrap().iter().map(|o| o.id).collect();
+    assert_eq!(ids, [second, first]);
+
+    r.purchasing.set_status(first, PurchaseOrderStatus::Sent).await.unwrap();
+    assert!(r.purchasing.receive(first, chess, 4).await.unwrap());
+    assert!(r.purchasing.receive(first, chess, 6).await.unwrap());
+    // more than is outstanding, or not on the order
+    assert!(!r.purchasing.receive(first, chess, 1).await.unwrap());
+    assert!(!r.purchasing.receive(first, go, 6).await.unwrap());
+    assert!(!r.purchasing.receive(second, chess, 1).await.unwrap());
+    assert!(!r.purchasing.receive(42, go, 1).await.unwrap());
+
+    let stored = r.purchasing.order(first).await.unwrap().unwrap();
+    assert_eq!(stored.status, PurchaseOrderStatus::Sent);
+    assert_eq!((stored.lines[0].received, stored.lines[1].received), (10, 0));
+    assert!(!stored.is_fully_received());
+
+    // orders need their supplier and products, and positive quantities
+    assert!(r.purchasing.create_order(order(42, vec![line(go, 1, 1.0)])).await.is_err());
+    assert!(r.purchasing.create_order(order(acme, vec![line(42, 1, 1.0)])).await.is_err());
+    assert!(r.purchasing.create_order(order(acme, vec![line(go, 0, 1.0)])).await.is_err());
+
+    // the weighted average cost lives with the inventory
+    assert_eq!(r.inventory.average_cost(go).await.unwrap(), None);
+    r.inventory.set_average_cost(go, 2.75).await.unwrap();
+    r.inventory.set_average_cost(chess, 5.0).await.unwrap();
+    r.inventory.set_average_cost(go, 3.125).await.unwrap();
+    assert_eq!(r.inventory.average_cost(go).await.unwrap(), Some(3.125));
+    assert!(r.inventory.set_average_cost(42, 1.0).await.is_err());
+}
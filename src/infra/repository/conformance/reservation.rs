@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use chrono::{TimeDelta, Utc};
+
+use crate::domain::cart::CartOwner;
+use crate::domain::product::Product;
+
+use super::Repos;
+
+/// Products `Rust Book` and `Chess`, 5 of each in stock, and two guest carts.
+async fn setup(r: &Repos) -> ((i64, i64), (i64, i64)) {
+    let category_id = r.categories.create("Books".into()).await.unwrap();
+    let product = |name: &str| Product {
+        id: 0,
+        name: name.into(),
+        description: None,
+        price: 10.0,
+        stock: 5,
+        category_id,
+        active: true,
+    };
+    let book = r.products.create(product("Rust Book")).await.unwrap();
+    let chess = r.products.create(product("Chess")).await.unwrap();
+    let first = r.carts.create(&CartOwner::Guest("first".into())).await.unwrap();
+    let second = r.carts.create(&CartOwner::Guest("second".into())).await.unwrap();
+    ((book, chess), (first, second))
+}
+
+pub async fn reservation_hold_replaces_the_carts_earlier_one(r: &Repos) {
+    let ((book, chess), (first, second)) = setup(r).await;
+    let soon = Utc::now() + TimeDelta::minutes(15);
+
+    let held = r.reservations.hold(first, book, 2, soon).await.unwrap();
+    assert_eq!((held.cart_id, held.product_id, held.quantity), (first, book, 2));
+    assert!((held.expires_at - soon).abs() < TimeDelta::seconds(1));
+    let again = r.reservations.hold(first, book, 3, soon).await.unwrap();
+    assert_eq!((again.id, again.quantity), (held.id, 3));
+    r.reservations.hold(second, book, 1, soon).await.unwrap();
+    r.reservations.hold(second, chess, 4, soon).await.unwrap();
+
+    let now = Utc::now();
+    let reserved = r.reservations.reserved(&[book, chess], None, now).await.unwrap();
+    assert_eq!(reserved, HashMap::from([(book, 4), (chess, 4)]));
+    let reserved = r.reservations.reserved(&[book, chess], Some(second), now).await.unwrap();
+    assert_eq!(reserved, HashMap::from([(book, 3)]));
+    assert_eq!(r.reservations.reserved(&[chess], Some(first), now).await.unwrap(), HashMap::from([(chess, 4)]));
+    assert!(r.reservations.reserved(&[], None, now).await.unwrap().is_empty());
+
+    // stock_reservations.cart_id REFERENCES carts(id), product_id REFERENCES products(id)
+    assert!(r.reservations.hold(42, book, 1, soon).await.is_err());
+    assert!(r.reservations.hold(first, 42, 1, soon).await.is_err());
+}
+
+pub async fn reservation_expired_ones_are_not_counted(r: &Repos) {
+    let ((book, _), (first, second)) = setup(r).await;
+    let now = Utc::now();
+    r.reservations.hold(first, book, 2, now - TimeDelta::minutes(1)).await.unwrap();
+    r.reservations.hold(second, book, 1, now + TimeDelta::minutes(15)).await.unwrap();
+
+    assert_eq!(r.reservations.reserved(&[book], None, now).await.unwrap(), HashMap::from([(book, 1)]));
+    let later = now + TimeDelta::minutes(20);
+    assert!(r.reservations.reserved(&[book], None, later).await.unwrap().is_empty());
+
+    assert_eq!(r.reservations.release_expired(now).await.unwrap(), 1);
+    assert_eq!(r.reservations.release_expired(now).await.unwrap(), 0);
+    assert!(!r.reservations.release(first, book).await.unwrap());
+    assert!(r.reservations.release(second, book).await.unwrap());
+}
+
+pub async fn reservation_released_with_its_cart(r: &Repos) {
+    let ((book, chess), (first, second)) = setup(r).await;
+    let alice = r.users.create("alice".into(), "pw".into()).await.unwrap();
+    let third = r.carts.create(&CartOwner::User(alice)).await.unwrap();
+    let soon = Utc::now() + TimeDelta::minutes(15);
+    for cart in [first, second, third] {
+        r.reservations.hold(cart, book, 1, soon).await.unwrap();
+    }
+    r.reservations.hold(first, chess, 1, soon).await.unwrap();
+
+    r.carts.delete(first).await.unwrap();
+    let reserved = r.reservations.reserved(&[book, chess], None, Utc::now()).await.unwrap();
+    assert_eq!(reserved, HashMap::from([(book, 2)]));
+    r.users.delete(alice).await.unwrap();
+    assert_eq!(r.reservations.reserved(&[book], None, Utc::now()).await.unwrap(), HashMap::from([(book, 1)]));
+    r.carts.delete_idle(Utc::now() + TimeDelta::minutes(1)).await.unwrap();
+    assert!(r.reservations.reserved(&[book], None, Utc::now()).await.unwrap().is_empty());
+}
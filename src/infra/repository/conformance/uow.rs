@@ -0,0 +1,183 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use crate::domain::page::Page;
+use crate::domain::DomainError;
+
+use super::Repos;
+
+pub async fn uow_commits_on_success(r: &Repos) {
+    let (user_id, category_id) = r
+        .uow
+        .transaction(|tx| {
+            Box::pin(async move {
+                let user_id = tx.users.create("alice".into(), "pw".into()).await?;
+                let category_id = tx.categories.create("Books".into()).await?;
+                Ok((user_id, category_id))
+            })
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(r.users.get_by_id(user_id).await.unwrap().unwrap().username, "alice");
+    assert_eq!(r.categories.get_by_id(category_id).await.unwrap().unwrap().name, "Books");
+}
+
+pub async fn uow_rolls_back_on_error(r: &Repos) {
+    let existing = r.categories.create("Games".into()).await.unwrap();
+
+    let result: Result<(), _> = r
+        .uow
+        .transaction(move |tx| {
+            Box::pin(async move {
+                tx.users.create("alice".into(), "pw".into()).await?;
+                tx.categories.create("Books".into()).await?;
+                tx.categories.update(existing, "Toys".into()).await?;
+                Err(DomainError::Validation("nope".into()))
+            })
+        })
+        .await;
+
+    assert!(matches!(result, Err(DomainError::Validation(msg)) if msg == "nope"));
+    assert!(r.users.get_all_users(Page::ALL).await.unwrap().is_empty());
+    let names: Vec<_> = r
+        .categories
+        .get_all_categories(Page::ALL)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|c| c.name)
+        .collect();
+    assert_eq!(names, ["Games"]);
+}
+
+pub async fn uow_rolls_back_on_repository_error(r: &Repos) {
+    let result = r
+        .uow
+        .transaction(|tx| {
+            Box::pin(async move {
+                tx.categories.create("Books".into()).await?;
+                // no such row: fails inside the transaction
+                tx.categories.update(42, "x".into()).await
+            })
+        })
+        .await;
+
+    assert!(result.is_err());
+    assert!(r.categories.get_all_categories(Page::ALL).await.unwrap().is_empty());
+}
+
+pub async fn uow_retries_conflicts(r: &Repos) {
+    let attempts = Arc::new(AtomicU32::new(0));
+
+    let counter = attempts.clone();
+    let id = r
+        .uow
+        .transaction(move |tx| {
+            let attempt = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            Box::pin(async move {
+                let id = tx.categories.create(format!("attempt {attempt}")).await?;
+                if attempt == 1 {
+                    return Err(DomainError::Conflict("could not serialize access".into()));
+                }
+                Ok(id)
+            })
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    let categories = r.categories.get_all_categories(Page::ALL).await.unwrap();
+    assert_eq!(categories.len(), 1);
+    assert_eq!(categories[0].id, id);
+    assert_eq!(categories[0].name, "attempt 2");
+}
+
+pub async fn uow_gives_up_after_repeated_conflicts(r: &Repos) {
+    let attempts = Arc::new(AtomicU32::new(0));
+
+    let counter = attempts.clone();
+    let result: Result<(), _> = r
+        .uow
+        .transaction(move |tx| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move {
+                tx.categories.create("Books".into()).await?;
+                Err(DomainError::Conflict("could not serialize access".into()))
+            })
+        })
+        .await;
+
+    assert!(matches!(result, Err(DomainError::Conflict(_))));
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    assert!(r.categories.get_all_categories(Page::ALL).await.unwrap().is_empty());
+}
+
+/// Plain repository writes made while a transaction is open stay once it
+/// commits; a row both changed ends up as the transaction left it (on a
+/// retry, where the backend reports a conflict).
+pub async fn uow_keeps_writes_made_outside_it(r: &Repos) {
+    let shared = r.categories.create("Books".into()).await.unwrap();
+    let attempts = Arc::new(AtomicU32::new(0));
+
+    let categories = r.categories.clone();
+    r.uow
+        .transaction(move |tx| {
+            let categories = categories.clone();
+            let first = attempts.fetch_add(1, Ordering::SeqCst) == 0;
+            Box::pin(async move {
+                if first {
+                    categories.create("Games".into()).await?;
+                    categories.update(shared, "Comics".into()).await?;
+                }
+                tx.users.create("alice".into(), "pw".into()).await?;
+                tx.categories.update(shared, "Novels".into()).await?;
+                Ok(())
+            })
+        })
+        .await
+        .unwrap();
+
+    let names: Vec<_> = r
+        .categories
+        .get_all_categories(Page::ALL)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|c| c.name)
+        .collect();
+    assert_eq!(names, ["Novels", "Games"]);
+    assert_eq!(r.users.get_all_users(Page::ALL).await.unwrap().len(), 1);
+}
+
+/// Two concurrent check-then-insert transactions for the same name: the
+/// loser must see the winner's row (after a retry, where the backend
+/// reports a conflict) instead of inserting a duplicate.
+pub async fn uow_serializes_check_then_insert(r: &Repos) {
+    let create = |name: &'static str| {
+        let uow = r.uow.clone();
+        async move {
+            uow.transaction(move |tx| {
+                Box::pin(async move {
+                    let taken = tx
+                        .categories
+                        .get_all_categories(Page::ALL)
+                        .await?
+                        .iter()
+                        .any(|c| c.name == name);
+                    if taken {
+                        return Err(DomainError::Validation("name taken".into()));
+                    }
+                    tx.categories.create(name.into()).await
+                })
+            })
+            .await
+        }
+    };
+
+    let (a, b) = tokio::join!(create("Books"), create("Books"));
+    let (winner, loser) = if a.is_ok() { (a, b) } else { (b, a) };
+    assert!(winner.is_ok(), "{winner:?}");
+    assert!(matches!(&loser, Err(DomainError::Validation(msg)) if msg == "name taken"), "{loser:?}");
+    assert_eq!(r.categories.get_all_categories(Page::ALL).await.unwrap().len(), 1);
+}
@@ -0,0 +1,98 @@
+use crate::domain::page::Page;
+use crate::infra::crypto::verify_password;
+
+use super::Repos;
+
+pub async fn user_create_assigns_sequential_ids(r: &Repos) {
+    assert_eq!(r.users.create("alice".into(), "pw".into()).await.unwrap(), 1);
+    assert_eq!(r.users.create("bob".into(), "pw".into()).await.unwrap(), 2);
+}
+
+pub async fn user_create_stores_password_hash(r: &Repos) {
+    let id = r.users.create("alice".into(), "secret".into()).await.unwrap();
+
+    let user = r.users.get_by_id(id).await.unwrap().unwrap();
+    assert_eq!(user.username, "alice");
+    assert!(user.active);
+    assert!(!user.admin);
+
+    let hash = user.password.into_inner();
+    assert_ne!(hash, "secret");
+    assert!(verify_password("secret".into(), hash.clone()).await.unwrap());
+    assert!(!verify_password("wrong".into(), hash).await.unwrap());
+}
+
+pub async fn user_get_missing_returns_none(r: &Repos) {
+    assert!(r.users.get_by_id(42).await.unwrap().is_none());
+    assert!(r.users.get_by_username("nobody".into()).await.unwrap().is_none());
+}
+
+pub async fn user_get_by_username(r: &Repos) {
+    r.users.create("alice".into(), "pw".into()).await.unwrap();
+    let id = r.users.create("bob".into(), "pw".into()).await.unwrap();
+
+    let user = r.users.get_by_username("bob".into()).await.unwrap().unwrap();
+    assert_eq!(user.id, id);
+    // exact, case-sensitive match
+    assert!(r.users.get_by_username("BOB".into()).await.unwrap().is_none());
+}
+
+pub async fn user_get_all_in_insert_order(r: &Repos) {
+    assert!(r.users.get_all_users(Page::ALL).await.unwrap().is_empty());
+    for name in ["carol", "alice", "bob"] {
+        r.users.create(name.into(), "pw".into()).await.unwrap();
+    }
+
+    let names: Vec<_> = r
+        .users
+        .get_all_users(Page::ALL)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|u| u.username)
+        .collect();
+    assert_eq!(names, ["carol", "alice", "bob"]);
+}
+
+pub async fn user_update_rehashes_password(r: &Repos) {
+    let id = r.users.create("alice".into(), "old".into()).await.unwrap();
+
+    let user = r.users.update(id, "alicia".into(), "new".into()).await.unwrap();
+    assert_eq!(user.id, id);
+    assert_eq!(user.username, "alicia");
+    assert!(user.active);
+    assert!(verify_password("new".into(), user.password.into_inner()).await.unwrap());
+
+    let stored = r.users.get_by_username("alicia".into()).await.unwrap().unwrap();
+    assert!(verify_password("new".into(), stored.password.into_inner()).await.unwrap());
+    assert!(r.users.get_by_username("alice".into()).await.unwrap().is_none());
+}
+
+pub async fn user_update_missing_fails(r: &Repos) {
+    assert!(r.users.update(42, "x".into(), "y".into()).await.is_err());
+}
+
+pub async fn user_delete_is_idempotent(r: &Repos) {
+    let id = r.users.create("alice".into(), "pw".into()).await.unwrap();
+
+    r.users.delete(id).await.unwrap();
+    assert!(r.users.get_by_id(id).await.unwrap().is_none());
+    r.users.delete(id).await.unwrap();
+
+    // ids are never reused
+    assert_eq!(r.users.create("bob".into(), "pw".into()).await.unwrap(), id + 1);
+}
+
+pub async fn user_set_admin(r: &Repos) {
+    let id = r.users.create("alice".into(), "pw".into()).await.unwrap();
+
+    r.users.set_admin(id, true).await.unwrap();
+    assert!(r.users.get_by_id(id).await.unwrap().unwrap().admin);
+    // a password change keeps the flag
+    let user = r.users.update(id, "alice".into(), "new".into()).await.unwrap();
+    assert!(user.admin);
+
+    r.users.set_admin(id, false).await.unwrap();
+    assert!(!r.users.get_by_username("alice".into()).await.unwrap().unwrap().admin);
+    r.users.set_admin(42, true).await.unwrap();
+}
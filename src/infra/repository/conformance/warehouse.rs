@@ -0,0 +1,86 @@
+use chrono::Utc;
+
+use crate::domain::inventory::{MovementKind, StockMovement};
+use crate::domain::product::Product;
+use crate::domain::warehouse::{GeoPoint, Warehouse, WarehouseStock};
+
+use super::inventory::MAIN;
+use super::Repos;
+
+fn warehouse(name: &str, priority: i32, location: Option<GeoPoint>) -> Warehouse {
+    Warehouse { id: 0, name: name.into(), priority, location }
+}
+
+fn movement(product_id: i64, warehouse_id: i64, kind: MovementKind, quantity: i32) -> StockMovement {
+    StockMovement {
+        id: 0,
+        product_id,
+        warehouse_id,
+        kind,
+        quantity,
+        reason: None,
+        actor_id: None,
+        order_id: None,
+        created_at: Utc::now(),
+    }
+}
+
+pub async fn warehouse_create_and_list_by_priority(r: &Repos) {
+    let main = r.warehouses.get(MAIN).await.unwrap().unwrap();
+    assert_eq!(main, Warehouse { id: MAIN, name: "main".into(), priority: 0, location: None });
+
+    let paris = GeoPoint::new(48.8566, 2.3522);
+    let east = r.warehouses.create(warehouse("east", 5, paris)).await.unwrap();
+    let west = r.warehouses.create(warehouse("west", -1, None)).await.unwrap();
+    assert_eq!(east, MAIN + 1);
+    assert_eq!(r.warehouses.get(east).await.unwrap().unwrap().location, paris);
+    assert!(r.warehouses.get(42).await.unwrap().is_none());
+
+    let ids: Vec<_> = r.warehouses.list().await.unwrap().iter().map(|w| w.id).collect();
+    assert_eq!(ids, [west, MAIN, east]);
+    // warehouses.name is UNIQUE
+    assert!(r.warehouses.create(warehouse("east", 0, None)).await.is_err());
+}
+
+pub async fn warehouse_stock_moves_per_location(r: &Repos) {
+    let category_id = r.categories.create("Books".into()).await.unwrap();
+    let product = |name: &str, stock: i32| Product {
+        id: 0,
+        name: name.into(),
+        description: None,
+        price: 10.0,
+        stock,
+        category_id,
+        active: true,
+    };
+    // starting stock lands in the default warehouse
+    let book = r.products.create(product("Rust Book", 5)).await.unwrap();
+    let chess = r.products.create(product("Chess", 0)).await.unwrap();
+    let east = r.warehouses.create(warehouse("east", 1, None)).await.unwrap();
+
+    r.inventory.record(movement(book, east, MovementKind::Receipt, 3)).await.unwrap().unwrap();
+    r.inventory.record(movement(chess, east, MovementKind::Receipt, 2)).await.unwrap().unwrap();
+    // the product has 8 in all, but only 3 in the east
+    assert!(r.inventory.record(movement(book, east, MovementKind::Sale, -4)).await.unwrap().is_none());
+    r.inventory.record(movement(book, MAIN, MovementKind::Transfer, -5)).await.unwrap().unwrap();
+    r.inventory.record(movement(book, east, MovementKind::Transfer, 5)).await.unwrap().unwrap();
+    assert_eq!(r.products.get_by_product_id(book).await.unwrap().unwrap().stock, 8);
+
+    let stock = |warehouse_id: i64, warehouse: &str, product_id: i64, stock: i32| WarehouseStock {
+        warehouse_id,
+        warehouse: warehouse.into(),
+        product_id,
+        stock,
+    };
+    // main ran empty and is left out
+    assert_eq!(
+        r.warehouses.stock(&[book, chess]).await.unwrap(),
+        [stock(east, "east", book, 8), stock(east, "east", chess, 2)]
+    );
+    assert_eq!(r.warehouses.stock(&[chess]).await.unwrap(), [stock(east, "east", chess, 2)]);
+    assert!(r.warehouses.stock(&[]).await.unwrap().is_empty());
+
+    // stock_movements.warehouse_id REFERENCES warehouses(id)
+    assert!(r.inventory.record(movement(book, 42, MovementKind::Receipt, 1)).await.is_err());
+    assert_eq!(r.products.get_by_product_id(book).await.unwrap().unwrap().stock, 8);
+}
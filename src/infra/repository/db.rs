@@ -0,0 +1,112 @@
+//! Connection source shared by the SQL repositories: the pool, or one open
+//! transaction handed out by a unit of work.
+
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::time::Duration;
+
+use sqlx::pool::PoolConnection;
+use sqlx::{Database, Pool, Transaction};
+use tokio::sync::{MappedMutexGuard, Mutex, MutexGuard};
+
+use crate::domain::uow::{TxFuture, TxWork};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{acquire, PoolName};
+
+/// Attempts per unit of work before a serialization failure is returned.
+const MAX_TX_ATTEMPTS: u32 = 3;
+
+/// Transaction shared by every repository of one unit of work; `None` once
+/// committed or rolled back.
+pub type SharedTx<DB> = Arc<Mutex<Option<Transaction<'static, DB>>>>;
+
+pub enum DbHandle<DB: Database> {
+    Pool(Pool<DB>),
+    Tx(SharedTx<DB>),
+}
+
+impl<DB: Database> Clone for DbHandle<DB> {
+    fn clone(&self) -> Self {
+        match self {
+            DbHandle::Pool(pool) => DbHandle::Pool(pool.clone()),
+            DbHandle::Tx(tx) => DbHandle::Tx(tx.clone()),
+        }
+    }
+}
+
+impl<DB: PoolName> DbHandle<DB> {
+    /// A pooled connection, or exclusive use of the transaction until the
+    /// guard is dropped.
+    pub async fn acquire(&self) -> Result<DbConn<'_, DB>, DomainError> {
+        match self {
+            DbHandle::Pool(pool) => Ok(DbConn::Pool(acquire(pool).await?)),
+            DbHandle::Tx(tx) => MutexGuard::try_map(tx.lock().await, Option::as_mut)
+                .map(DbConn::Tx)
+                .map_err(|_| DomainError::Unexpected("transaction already finished".into())),
+        }
+    }
+}
+
+pub enum DbConn<'a, DB: Database> {
+    Pool(PoolConnection<DB>),
+    Tx(MappedMutexGuard<'a, Transaction<'static, DB>>),
+}
+
+impl<DB: Database> Deref for DbConn<'_, DB> {
+    type Target = DB::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            DbConn::Pool(conn) => conn,
+            DbConn::Tx(tx) => tx,
+        }
+    }
+}
+
+impl<DB: Database> DerefMut for DbConn<'_, DB> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            DbConn::Pool(conn) => conn,
+            DbConn::Tx(tx) => tx,
+        }
+    }
+}
+
+/// Map a sqlx error, keeping serialization failures and deadlocks apart as
+/// [`DomainError::Conflict`]: Postgres 40001 / 40P01, SQLite BUSY (5, 517)
+/// and LOCKED (6, 262 — shared-cache table locks).
+pub fn db_error(e: sqlx::Error) -> DomainError {
+    let retryable = e
+        .as_database_error()
+        .and_then(|db| db.code())
+        .is_some_and(|code| matches!(code.as_ref(), "40001" | "40P01" | "5" | "517" | "6" | "262"));
+    if retryable {
+        DomainError::Conflict(e.to_string())
+    } else {
+        DomainError::Unexpected(e.to_string())
+    }
+}
+
+/// Pause before retry `attempt` (1-based) of a conflicted transaction.
+fn retry_backoff(attempt: u32) -> Duration {
+    Duration::from_millis(10 * 2u64.pow(attempt.saturating_sub(1)))
+}
+
+/// Run one transaction attempt at a time, starting over on
+/// [`DomainError::Conflict`] up to [`MAX_TX_ATTEMPTS`] times.
+pub async fn retry_on_conflict<'w, A>(work: &mut TxWork<'w>, mut attempt: A) -> Result<(), DomainError>
+where
+    A: for<'x> FnMut(&'x mut TxWork<'w>) -> TxFuture<'x, ()>,
+{
+    let mut n = 1;
+    loop {
+        match attempt(&mut *work).await {
+            Err(DomainError::Conflict(msg)) if n < MAX_TX_ATTEMPTS => {
+                tracing::warn!(attempt = n, error = %msg, "transaction conflict, retrying");
+                tokio::time::sleep(retry_backoff(n)).await;
+                n += 1;
+            }
+            result => return result,
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::inventory::{InventoryRepository, LowStockProduct, MovementKind, StockLevel, StockMovement};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{pg_query, pg_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+
+/// A kind as stored in `stock_movements.kind`.
+pub(crate) fn parse_kind(kind: &str) -> Result<MovementKind, DomainError> {
+    kind.parse().map_err(DomainError::Unexpected)
+}
+
+struct MovementRow {
+    id: i64,
+    product_id: i64,
+    warehouse_id: i64,
+    kind: String,
+    quantity: i32,
+    reason: Option<String>,
+    actor_id: Option<i64>,
+    order_id: Option<i64>,
+    created_at: DateTime<Utc>,
+}
+
+impl TryFrom<MovementRow> for StockMovement {
+    type Error = DomainError;
+
+    fn try_from(row: MovementRow) -> Result<Self, Self::Error> {
+        Ok(StockMovement {
+            id: row.id,
+            product_id: row.product_id,
+            warehouse_id: row.warehouse_id,
+            kind: parse_kind(&row.kind)?,
+            quantity: row.quantity,
+            reason: row.reason,
+            actor_id: row.actor_id,
+            order_id: row.order_id,
+            created_at: row.created_at,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct PostgresInventoryRepository {
+    db: DbHandle<Postgres>,
+}
+
+impl PostgresInventoryRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Postgres>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl InventoryRepository for PostgresInventoryRepository {
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "stock_movements", db.query.text = Empty))]
+    async fn record(&self, movement: StockMovement) -> Result<Option<StockMovement>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        // the product's first movement in the warehouse starts from zero
+        pg_query!(
+            r#"
+            INSERT INTO warehouse_stock (warehouse_id, product_id, stock)
+            SELECT $2, id, 0 FROM products WHERE id = $1
+            ON CONFLICT DO NOTHING
+            "#,
+            movement.product_id,
+            movement.warehouse_id
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        // one statement, so the stock never moves without its ledger line;
+        // the guard is part of the UPDATE, so concurrent movements cannot
+        // both pass it on the same stale stock
+        pg_query_as!(
+            MovementRow,
+            r#"
+            WITH located AS (
+                UPDATE warehouse_stock
+                SET stock = stock + $2
+                WHERE product_id = $1 AND warehouse_id = $7 AND stock + $2 >= 0
+                RETURNING product_id
+            ), moved AS (
+                UPDATE products
+                SET stock = stock + $2
+                WHERE id IN (SELECT product_id FROM located)
+                RETURNING id
+            )
+            INSERT INTO stock_movements (product_id, warehouse_id, kind, quantity, reason, actor_id, order_id)
+            SELECT id, $7, $3, $2, $4, $5, $6 FROM moved
+            RETURNING id, product_id, warehouse_id, kind, quantity, reason, actor_id, order_id, created_at
+            "#,
+            movement.product_id,
+            movement.quantity,
+            movement.kind.as_str(),
+            movement.reason,
+            movement.actor_id,
+            movement.order_id,
+            movement.warehouse_id
+        )
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        .map(StockMovement::try_from)
+        .transpose()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "stock_movements", db.query.text = Empty))]
+    async fn movements(&self, product_id: i64) -> Result<Vec<StockMovement>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query_as!(
+            MovementRow,
+            r#"
+            SELECT id, product_id, warehouse_id, kind, quantity, reason, actor_id, order_id, created_at
+            FROM stock_movements
+            WHERE product_id = $1
+            ORDER BY id
+            "#,
+            product_id
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(StockMovement::try_from)
+        .collect()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "stock_movements", db.query.text = Empty))]
+    async fn order_movements(&self, order_id: i64) -> Result<Vec<StockMovement>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query_as!(
+            MovementRow,
+            r#"
+            SELECT id, product_id, warehouse_id, kind, quantity, reason, actor_id, order_id, created_at
+            FROM stock_movements
+            WHERE order_id = $1
+            ORDER BY id
+            "#,
+            order_id
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(StockMovement::try_from)
+        .collect()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "stock_movements", db.query.text = Empty))]
+    async fn stock_levels(&self) -> Result<Vec<StockLevel>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let levels = pg_query_as!(
+            StockLevel,
+            r#"
+            SELECT p.id as product_id, p.name, p.stock, COALESCE(SUM(m.quantity), 0)::int8 as "ledger!"
+            FROM products p
+            LEFT JOIN stock_movements m ON m.product_id = p.id
+            GROUP BY p.id
+            ORDER BY p.id
+            "#
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(levels)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "UPSERT", db.collection.name = "stock_thresholds", db.query.text = Empty))]
+    async fn set_threshold(&self, product_id: i64, threshold: Option<i32>) -> Result<bool, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let exists = pg_query!("SELECT id FROM products WHERE id = $1", product_id)
+            .fetch_optional(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)?
+            .is_some();
+        if !exists {
+            return Ok(false);
+        }
+        match threshold {
+            Some(threshold) => pg_query!(
+                r#"
+                INSERT INTO stock_thresholds (product_id, threshold)
+                VALUES ($1, $2)
+                ON CONFLICT (product_id) DO UPDATE SET threshold = EXCLUDED.threshold
+                "#,
+                product_id,
+                threshold
+            )
+            .execute(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)?,
+            None => pg_query!("DELETE FROM stock_thresholds WHERE product_id = $1", product_id)
+                .execute(&mut *conn)
+                .traced()
+                .await
+                .map_err(db_error)?,
+        };
+        Ok(true)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "stock_thresholds", db.query.text = Empty))]
+    async fn threshold(&self, product_id: i64) -> Result<Option<i32>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query!("SELECT threshold FROM stock_thresholds WHERE product_id = $1", product_id)
+            .fetch_optional(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)?;
+        Ok(row.map(|r| r.threshold))
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "stock_thresholds", db.query.text = Empty))]
+    async fn low_stock(&self) -> Result<Vec<LowStockProduct>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query_as!(
+            LowStockProduct,
+            r#"
+            SELECT p.id AS product_id, p.name, p.category_id, c.name AS category, p.stock, t.threshold
+            FROM stock_thresholds t
+            JOIN products p ON p.id = t.product_id
+            JOIN categories c ON c.id = p.category_id
+            WHERE p.stock < t.threshold
+            ORDER BY c.name, c.id, p.id
+            "#
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "product_costs", db.query.text = Empty))]
+    async fn average_cost(&self, product_id: i64) -> Result<Option<f64>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query!("SELECT average_cost FROM product_costs WHERE product_id = $1", product_id)
+            .fetch_optional(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)?;
+        Ok(row.map(|r| r.average_cost))
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "UPSERT", db.collection.name = "product_costs", db.query.text = Empty))]
+    async fn set_average_cost(&self, product_id: i64, cost: f64) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query!(
+            r#"
+            INSERT INTO product_costs (product_id, average_cost)
+            VALUES ($1, $2)
+            ON CONFLICT (product_id) DO UPDATE SET average_cost = EXCLUDED.average_cost
+            "#,
+            product_id,
+            cost
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::job::{Job, JobQueue, NewJob};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{pg_query, pg_query_as, DbTraceExt};
+use crate::infra::propagation::current_traceparent;
+use crate::infra::repository::db::{db_error, DbHandle};
+
+/// `jobs` table queue; concurrent workers claim disjoint rows with
+/// `FOR UPDATE SKIP LOCKED`.
+#[derive(Clone)]
+pub struct PostgresJobQueue {
+    db: DbHandle<Postgres>,
+}
+
+impl PostgresJobQueue {
+    pub fn new(pool: PgPool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+}
+
+#[async_trait]
+impl JobQueue for PostgresJobQueue {
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "jobs", db.query.text = Empty))]
+    async fn enqueue(&self, job: NewJob) -> Result<Option<i64>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query!(
+            r#"
+            INSERT INTO jobs (queue, kind, payload, run_at, max_attempts, unique_key, trace_context)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (unique_key) DO NOTHING
+            RETURNING id
+            "#,
+            job.queue,
+            job.kind,
+            job.payload,
+            job.run_at,
+            job.max_attempts,
+            job.unique_key,
+            current_traceparent()
+        )
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.map(|r| r.id))
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "UPDATE", db.collection.name = "jobs", db.query.text = Empty))]
+    async fn claim(&self, queue: &str, limit: i64, lease: Duration) -> Result<Vec<Job>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let mut jobs = pg_query_as!(
+            Job,
+            r#"
+            UPDATE jobs
+            SET status = 'running', attempts = attempts + 1, locked_until = now() + make_interval(secs => $3)
+            WHERE id IN (
+                SELECT id FROM jobs
+                WHERE queue = $1
+                  AND ((status = 'scheduled' AND run_at <= now())
+                    OR (status = 'running' AND locked_until < now()))
+                ORDER BY run_at, id
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, queue, kind, payload, attempts, max_attempts, run_at, trace_context
+            "#,
+            queue,
+            limit,
+            lease.as_secs_f64()
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        // RETURNING comes back in update order
+        jobs.sort_by_key(|j| (j.run_at, j.id));
+        Ok(jobs)
+    }
+
+    #[instrument(skip(self, job), err, fields(job.id = job.id, db.system = "postgresql", db.operation.name = "UPDATE", db.collection.name = "jobs", db.query.text = Empty))]
+    async fn complete(&self, job: &Job) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query!(
+            r#"
+            UPDATE jobs
+            SET status = 'completed', locked_until = NULL, last_error = NULL, finished_at = now()
+            WHERE id = $1 AND status = 'running' AND attempts = $2
+            "#,
+            job.id,
+            job.attempts
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self, job), err, fields(job.id = job.id, db.system = "postgresql", db.operation.name = "UPDATE", db.collection.name = "jobs", db.query.text = Empty))]
+    async fn fail(&self, job: &Job, error: String, retry_at: Option<DateTime<Utc>>) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query!(
+            r#"
+            UPDATE jobs
+            SET status = CASE WHEN $4::timestamptz IS NULL THEN 'dead' ELSE 'scheduled' END,
+                run_at = COALESCE($4, run_at),
+                finished_at = CASE WHEN $4::timestamptz IS NULL THEN now() END,
+                locked_until = NULL,
+                last_error = $3
+            WHERE id = $1 AND status = 'running' AND attempts = $2
+            "#,
+            job.id,
+            job.attempts,
+            error,
+            retry_at
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "DELETE", db.collection.name = "jobs", db.query.text = Empty))]
+    async fn prune_finished(&self, before: DateTime<Utc>) -> Result<u64, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = pg_query!(
+            r#"
+            DELETE FROM jobs
+            WHERE finished_at < $1
+            "#,
+            before
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,67 @@
+use async_trait::async_trait;
+use chrono::Utc;
+
+use crate::domain::api_key::{ApiKey, ApiKeyRepository};
+use crate::domain::DomainError;
+
+use super::InMemoryStore;
+
+/// An `api_keys` row.
+#[derive(Clone, PartialEq)]
+pub(crate) struct ApiKeyEntry {
+    pub key: ApiKey,
+    pub key_hash: String,
+}
+
+#[derive(Clone)]
+pub struct InMemoryApiKeyRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryApiKeyRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for InMemoryApiKeyRepository {
+    async fn create(&self, name: String, prefix: String, key_hash: String) -> Result<i64, DomainError> {
+        let mut tables = self.store.lock();
+        if tables.api_keys.rows.values().any(|e| e.key.prefix == prefix) {
+            return Err(DomainError::Unexpected(
+                "error returned from database: duplicate key value violates unique constraint \"api_keys_prefix_key\"".into(),
+            ));
+        }
+        let id = tables.api_keys.next_id();
+        let key = ApiKey { id, name, prefix, created_at: Utc::now(), revoked_at: None };
+        tables.api_keys.rows.insert(id, ApiKeyEntry { key, key_hash });
+        Ok(id)
+    }
+
+    async fn get_all(&self) -> Result<Vec<ApiKey>, DomainError> {
+        Ok(self.store.lock().api_keys.rows.values().map(|e| e.key.clone()).collect())
+    }
+
+    async fn active_hash(&self, prefix: &str) -> Result<Option<String>, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .api_keys
+            .rows
+            .values()
+            .find(|e| e.key.prefix == prefix && e.key.revoked_at.is_none())
+            .map(|e| e.key_hash.clone()))
+    }
+
+    async fn revoke(&self, id: i64) -> Result<bool, DomainError> {
+        let mut tables = self.store.lock();
+        match tables.api_keys.rows.get_mut(&id) {
+            Some(entry) if entry.key.revoked_at.is_none() => {
+                entry.key.revoked_at = Some(Utc::now());
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::domain::cart::{Cart, CartLine, CartOwner, CartRepository};
+use crate::domain::DomainError;
+use crate::infra::repository::numeric::{cents_to_f64, to_numeric_10_2_cents};
+
+use super::{foreign_key_violation, InMemoryStore};
+
+#[derive(Clone)]
+pub struct InMemoryCartRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryCartRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl CartRepository for InMemoryCartRepository {
+    async fn find(&self, owner: &CartOwner) -> Result<Option<Cart>, DomainError> {
+        Ok(self.store.lock().carts.rows.values().find(|c| &c.owner == owner).cloned())
+    }
+
+    async fn create(&self, owner: &CartOwner) -> Result<i64, DomainError> {
+        let mut tables = self.store.lock();
+        if let CartOwner::User(user_id) = owner
+            && !tables.users.rows.contains_key(user_id)
+        {
+            return Err(foreign_key_violation("carts", "carts_user_id_fkey"));
+        }
+        if tables.carts.rows.values().any(|c| &c.owner == owner) {
+            let constraint = match owner {
+                CartOwner::User(_) => "carts_user_id_key",
+                CartOwner::Guest(_) => "carts_guest_token_key",
+            };
+            return Err(DomainError::Unexpected(format!(
+                "error returned from database: duplicate key value violates unique constraint \"{constraint}\""
+            )));
+        }
+        let id = tables.carts.next_id();
+        tables.carts.rows.insert(id, Cart { id, owner: owner.clone(), lines: Vec::new(), coupon: None, updated_at: Utc::now() });
+        Ok(id)
+    }
+
+    async fn set_line(&self, cart_id: i64, line: CartLine) -> Result<(), DomainError> {
+        let unit_price = cents_to_f64(to_numeric_10_2_cents(line.unit_price)?);
+        if line.quantity <= 0 {
+            return Err(DomainError::Unexpected(
+                "error returned from database: new row for relation \"cart_items\" violates check constraint \"cart_items_quantity_check\"".into(),
+            ));
+        }
+
+        let mut tables = self.store.lock();
+        if !tables.products.rows.contains_key(&line.product_id) {
+            return Err(foreign_key_violation("cart_items", "cart_items_product_id_fkey"));
+        }
+        let Some(cart) = tables.carts.rows.get_mut(&cart_id) else {
+            return Err(foreign_key_violation("cart_items", "cart_items_cart_id_fkey"));
+        };
+        let line = CartLine { unit_price, ..line };
+        match cart.lines.iter_mut().find(|l| l.product_id == line.product_id) {
+            Some(existing) => *existing = line,
+            None => cart.lines.push(line),
+        }
+        cart.updated_at = Utc::now();
+        Ok(())
+    }
+
+    async fn remove_line(&self, cart_id: i64, product_id: i64) -> Result<bool, DomainError> {
+        let mut tables = self.store.lock();
+        let Some(cart) = tables.carts.rows.get_mut(&cart_id) else {
+            return Ok(false);
+        };
+        let before = cart.lines.len();
+        cart.lines.retain(|l| l.product_id != product_id);
+        if cart.lines.len() == before {
+            return Ok(false);
+        }
+        cart.updated_at = Utc::now();
+        Ok(true)
+    }
+
+    async fn set_coupon(&self, cart_id: i64, coupon: Option<String>) -> Result<(), DomainError> {
+        if let Some(cart) = self.store.lock().carts.rows.get_mut(&cart_id) {
+            cart.coupon = coupon;
+            cart.updated_at = Utc::now();
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, cart_id: i64) -> Result<(), DomainError> {
+        self.store.lock().retain_carts(|c| c.id != cart_id);
+        Ok(())
+    }
+
+    async fn delete_idle(&self, before: DateTime<Utc>) -> Result<u64, DomainError> {
+        Ok(self.store.lock().retain_carts(|c| c.updated_at >= before))
+    }
+}
@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+
+use crate::domain::page::Page;
+use crate::domain::category::{Category, CategoryRepository};
+use crate::domain::DomainError;
+
+use super::{row_not_found, InMemoryStore};
+
+#[derive(Clone)]
+pub struct InMemoryCategoryRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryCategoryRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl CategoryRepository for InMemoryCategoryRepository {
+    async fn create(&self, name: String) -> Result<i64, DomainError> {
+        let mut tables = self.store.lock();
+        let id = tables.categories.next_id();
+        tables.categories.rows.insert(id, Category { id, name, active: true });
+        Ok(id)
+    }
+
+    async fn get_by_id(&self, id: i64) -> Result<Option<Category>, DomainError> {
+        Ok(self.store.lock().categories.rows.get(&id).cloned())
+    }
+
+    async fn get_all_categories(&self, page: Page) -> Result<Vec<Category>, DomainError> {
+        Ok(page.apply(self.store.lock().categories.rows.values().cloned()))
+    }
+
+    async fn update(&self, id: i64, name: String) -> Result<Category, DomainError> {
+        let mut tables = self.store.lock();
+        let category = tables.categories.rows.get_mut(&id).ok_or_else(row_not_found)?;
+        category.name = name;
+        Ok(category.clone())
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        // products.category_id REFERENCES categories(id)
+        if tables.products.rows.values().any(|p| p.category_id == id) {
+            return Err(DomainError::Unexpected(
+                "error returned from database: update or delete on table \"categories\" violates foreign key constraint \"products_category_id_fkey\" on table \"products\"".into(),
+            ));
+        }
+        tables.categories.rows.remove(&id);
+        // promotions.category_id REFERENCES categories(id) ON DELETE CASCADE
+        let promotions: Vec<i64> = tables.promotions.rows.values().filter(|p| p.category_id == Some(id)).map(|p| p.id).collect();
+        tables.promotions.rows.retain(|_, p| p.category_id != Some(id));
+        tables.promotion_redemptions.rows.retain(|_, r| !promotions.contains(&r.promotion_id));
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::Utc;
+
+use crate::domain::inventory::{InventoryRepository, LowStockProduct, StockLevel, StockMovement};
+use crate::domain::DomainError;
+
+use super::{foreign_key_violation, InMemoryStore};
+
+#[derive(Clone)]
+pub struct InMemoryInventoryRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryInventoryRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl InventoryRepository for InMemoryInventoryRepository {
+    async fn record(&self, movement: StockMovement) -> Result<Option<StockMovement>, DomainError> {
+        let mut tables = self.store.lock();
+        if movement.quantity == 0 {
+            return Err(DomainError::Unexpected(
+                "error returned from database: new row for relation \"stock_movements\" violates check constraint \"stock_movements_quantity_check\"".into(),
+            ));
+        }
+        if let Some(actor_id) = movement.actor_id
+            && !tables.users.rows.contains_key(&actor_id)
+        {
+            return Err(foreign_key_violation("stock_movements", "stock_movements_actor_id_fkey"));
+        }
+        if let Some(order_id) = movement.order_id
+            && !tables.orders.rows.contains_key(&order_id)
+        {
+            return Err(foreign_key_violation("stock_movements", "stock_movements_order_id_fkey"));
+        }
+        if !tables.products.rows.contains_key(&movement.product_id) {
+            return Ok(None);
+        }
+        if !tables.warehouses.rows.contains_key(&movement.warehouse_id) {
+            return Err(foreign_key_violation("warehouse_stock", "warehouse_stock_warehouse_id_fkey"));
+        }
+        let located = tables.warehouse_stock.entry((movement.warehouse_id, movement.product_id)).or_default();
+        if *located + movement.quantity < 0 {
+            return Ok(None);
+        }
+        *located += movement.quantity;
+        if let Some(product) = tables.products.rows.get_mut(&movement.product_id) {
+            product.stock += movement.quantity;
+        }
+        let id = tables.stock_movements.next_id();
+        let movement = StockMovement { id, created_at: Utc::now(), ..movement };
+        tables.stock_movements.rows.insert(id, movement.clone());
+        Ok(Some(movement))
+    }
+
+    async fn movements(&self, product_id: i64) -> Result<Vec<StockMovement>, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .stock_movements
+            .rows
+            .values()
+            .filter(|m| m.product_id == product_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn order_movements(&self, order_id: i64) -> Result<Vec<StockMovement>, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .stock_movements
+            .rows
+            .values()
+            .filter(|m| m.order_id == Some(order_id))
+            .cloned()
+            .collect())
+    }
+
+    async fn stock_levels(&self) -> Result<Vec<StockLevel>, DomainError> {
+        let tables = self.store.lock();
+        let mut ledger: HashMap<i64, i64> = HashMap::new();
+        for movement in tables.stock_movements.rows.values() {
+            *ledger.entry(movement.product_id).or_default() += i64::from(movement.quantity);
+        }
+        Ok(tables
+            .products
+            .rows
+            .values()
+            .map(|p| StockLevel {
+                product_id: p.id,
+                name: p.name.clone(),
+                stock: p.stock,
+                ledger: ledger.get(&p.id).copied().unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    async fn set_threshold(&self, product_id: i64, threshold: Option<i32>) -> Result<bool, DomainError> {
+        let mut tables = self.store.lock();
+        if !tables.products.rows.contains_key(&product_id) {
+            return Ok(false);
+        }
+        if let Some(threshold) = threshold
+            && threshold < 0
+        {
+            return Err(DomainError::Unexpected(
+                "error returned from database: new row for relation \"stock_thresholds\" violates check constraint \"stock_thresholds_threshold_check\"".into(),
+            ));
+        }
+        match threshold {
+            Some(threshold) => tables.stock_thresholds.insert(product_id, threshold),
+            None => tables.stock_thresholds.remove(&product_id),
+        };
+        Ok(true)
+    }
+
+    async fn threshold(&self, product_id: i64) -> Result<Option<i32>, DomainError> {
+        Ok(self.store.lock().stock_thresholds.get(&product_id).copied())
+    }
+
+    async fn low_stock(&self) -> Result<Vec<LowStockProduct>, DomainError> {
+        let tables = self.store.lock();
+        let mut low: Vec<_> = tables
+            .stock_thresholds
+            .iter()
+            .filter_map(|(product_id, &threshold)| {
+                let product = tables.products.rows.get(product_id)?;
+                let category = tables.categories.rows.get(&product.category_id)?;
+                (product.stock < threshold).then(|| LowStockProduct {
+                    product_id: product.id,
+                    name: product.name.clone(),
+                    category_id: category.id,
+                    category: category.name.clone(),
+                    stock: product.stock,
+                    threshold,
+                })
+            })
+            .collect();
+        low.sort_by(|a, b| (&a.category, a.category_id, a.product_id).cmp(&(&b.category, b.category_id, b.product_id)));
+        Ok(low)
+    }
+
+    async fn average_cost(&self, product_id: i64) -> Result<Option<f64>, DomainError> {
+        Ok(self.store.lock().product_costs.get(&product_id).copied())
+    }
+
+    async fn set_average_cost(&self, product_id: i64, cost: f64) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        if !tables.products.rows.contains_key(&product_id) {
+            return Err(foreign_key_violation("product_costs", "product_costs_product_id_fkey"));
+        }
+        tables.product_costs.insert(product_id, cost);
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::domain::job::{Job, JobQueue, NewJob};
+use crate::domain::DomainError;
+use crate::infra::propagation::current_traceparent;
+
+use super::Table;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Status {
+    Scheduled,
+    Running,
+    Completed,
+    Dead,
+}
+
+struct JobEntry {
+    job: Job,
+    status: Status,
+    locked_until: Option<DateTime<Utc>>,
+    unique_key: Option<String>,
+    finished_at: Option<DateTime<Utc>>,
+}
+
+impl JobEntry {
+    fn is_due(&self, queue: &str, now: DateTime<Utc>) -> bool {
+        self.job.queue == queue
+            && match self.status {
+                Status::Scheduled => self.job.run_at <= now,
+                Status::Running => self.locked_until.is_some_and(|t| t < now),
+                Status::Completed | Status::Dead => false,
+            }
+    }
+}
+
+/// Job queue for `DATABASE_URL=memory://`. Kept apart from the
+/// [`InMemoryStore`](super::InMemoryStore): units of work swap the store's
+/// tables wholesale and would undo claims made meanwhile.
+#[derive(Clone, Default)]
+pub struct InMemoryJobQueue {
+    jobs: Arc<Mutex<Table<JobEntry>>>,
+}
+
+impl InMemoryJobQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Table<JobEntry>> {
+        self.jobs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The job's row, if this claim still owns it.
+    fn claimed<'a>(jobs: &'a mut Table<JobEntry>, job: &Job) -> Option<&'a mut JobEntry> {
+        jobs.rows
+            .get_mut(&job.id)
+            .filter(|e| e.status == Status::Running && e.job.attempts == job.attempts)
+    }
+}
+
+#[async_trait]
+impl JobQueue for InMemoryJobQueue {
+    async fn enqueue(&self, job: NewJob) -> Result<Option<i64>, DomainError> {
+        let mut jobs = self.lock();
+        if let Some(key) = &job.unique_key
+            && jobs.rows.values().any(|e| e.unique_key.as_ref() == Some(key))
+        {
+            return Ok(None);
+        }
+        let id = jobs.next_id();
+        let entry = JobEntry {
+            job: Job {
+                id,
+                queue: job.queue,
+                kind: job.kind,
+                payload: job.payload,
+                attempts: 0,
+                max_attempts: job.max_attempts,
+                run_at: job.run_at,
+                trace_context: current_traceparent(),
+            },
+            status: Status::Scheduled,
+            locked_until: None,
+            unique_key: job.unique_key,
+            finished_at: None,
+        };
+        jobs.rows.insert(id, entry);
+        Ok(Some(id))
+    }
+
+    async fn claim(&self, queue: &str, limit: i64, lease: Duration) -> Result<Vec<Job>, DomainError> {
+        let now = Utc::now();
+        let lease = chrono::Duration::from_std(lease).map_err(|e| DomainError::Unexpected(e.to_string()))?;
+
+        let mut jobs = self.lock();
+        let mut due: Vec<_> = jobs
+            .rows
+            .values()
+            .filter(|e| e.is_due(queue, now))
+            .map(|e| (e.job.run_at, e.job.id))
+            .collect();
+        due.sort();
+        due.truncate(usize::try_from(limit).unwrap_or(0));
+
+        Ok(due
+            .into_iter()
+            .filter_map(|(_, id)| {
+                let entry = jobs.rows.get_mut(&id)?;
+                entry.status = Status::Running;
+                entry.locked_until = Some(now + lease);
+                entry.job.attempts += 1;
+                Some(entry.job.clone())
+            })
+            .collect())
+    }
+
+    async fn complete(&self, job: &Job) -> Result<(), DomainError> {
+        if let Some(entry) = Self::claimed(&mut self.lock(), job) {
+            entry.status = Status::Completed;
+            entry.locked_until = None;
+            entry.finished_at = Some(Utc::now());
+        }
+        Ok(())
+    }
+
+    async fn fail(&self, job: &Job, _error: String, retry_at: Option<DateTime<Utc>>) -> Result<(), DomainError> {
+        if let Some(entry) = Self::claimed(&mut self.lock(), job) {
+            entry.locked_until = None;
+            match retry_at {
+                Some(at) => {
+                    entry.status = Status::Scheduled;
+                    entry.job.run_at = at;
+                }
+                None => {
+                    entry.status = Status::Dead;
+                    entry.finished_at = Some(Utc::now());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn prune_finished(&self, before: DateTime<Utc>) -> Result<u64, DomainError> {
+        let mut jobs = self.lock();
+        let count = jobs.rows.len();
+        jobs.rows.retain(|_, e| e.finished_at.is_none_or(|t| t >= before));
+        Ok((count - jobs.rows.len()) as u64)
+    }
+}
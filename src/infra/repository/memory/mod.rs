@@ -0,0 +1,236 @@
+//! In-memory repositories for tests and demos (`DATABASE_URL=memory://`).
+//!
+//! All three repositories share one [`InMemoryStore`] so they behave like the
+//! Postgres tables in `migrations/`: BIGSERIAL ids starting at 1,
+//! `products.category_id` foreign key, `NUMERIC(10, 2)` prices.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::domain::cart::Cart;
+use crate::domain::category::Category;
+use crate::domain::inventory::StockMovement;
+use crate::domain::notification::Notification;
+use crate::domain::order::Order;
+use crate::domain::payment::PaymentIntent;
+use crate::domain::product::Product;
+use crate::domain::promotion::{Promotion, Redemption};
+use crate::domain::purchasing::{PurchaseOrder, Supplier, SupplierProduct};
+use crate::domain::reservation::Reservation;
+use crate::domain::user::User;
+use crate::domain::warehouse::Warehouse;
+use crate::domain::DomainError;
+
+pub mod api_key;
+pub mod cart;
+pub mod category;
+pub mod inventory;
+pub mod job;
+pub mod notification;
+pub mod order;
+pub mod outbox;
+pub mod payment;
+pub mod product;
+pub mod promotion;
+pub mod purchasing;
+pub mod reservation;
+pub mod user;
+pub mod uow;
+pub mod warehouse;
+
+pub use api_key::InMemoryApiKeyRepository;
+pub use cart::InMemoryCartRepository;
+pub use category::InMemoryCategoryRepository;
+pub use inventory::InMemoryInventoryRepository;
+pub use job::InMemoryJobQueue;
+pub use notification::InMemoryNotificationRepository;
+pub use order::InMemoryOrderRepository;
+pub use outbox::InMemoryOutboxRepository;
+pub use payment::InMemoryPaymentRepository;
+pub use product::InMemoryProductRepository;
+pub use promotion::InMemoryPromotionRepository;
+pub use purchasing::InMemoryPurchasingRepository;
+pub use reservation::InMemoryReservationRepository;
+pub use user::InMemoryUserRepository;
+pub use uow::InMemoryUnitOfWork;
+pub use warehouse::InMemoryWarehouseRepository;
+
+#[derive(Clone)]
+pub struct InMemoryStore {
+    tables: Arc<Mutex<Tables>>,
+    /// Held for the whole of a unit of work, so they run one at a time.
+    tx_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+#[derive(Clone, Default)]
+pub(super) struct Tables {
+    pub users: Table<User>,
+    pub categories: Table<Category>,
+    pub products: Table<Product>,
+    pub warehouses: Table<Warehouse>,
+    /// Stock by `(warehouse_id, product_id)`.
+    pub warehouse_stock: BTreeMap<(i64, i64), i32>,
+    pub stock_movements: Table<StockMovement>,
+    pub stock_reservations: Table<Reservation>,
+    /// Reorder threshold by product id.
+    pub stock_thresholds: BTreeMap<i64, i32>,
+    pub notifications: Table<Notification>,
+    /// Weighted average cost by product id.
+    pub product_costs: BTreeMap<i64, f64>,
+    pub suppliers: Table<Supplier>,
+    /// By `(supplier_id, product_id)`.
+    pub supplier_products: BTreeMap<(i64, i64), SupplierProduct>,
+    pub purchase_orders: Table<PurchaseOrder>,
+    pub promotions: Table<Promotion>,
+    pub promotion_redemptions: Table<Redemption>,
+    pub outbox: Table<outbox::OutboxEntry>,
+    pub api_keys: Table<api_key::ApiKeyEntry>,
+    pub carts: Table<Cart>,
+    pub orders: Table<Order>,
+    pub order_status_changes: Table<order::StatusChangeEntry>,
+    pub payment_intents: Table<PaymentIntent>,
+    pub payment_attempts: Table<payment::AttemptEntry>,
+    /// `(provider, event_id)` of the webhook events seen.
+    pub payment_webhook_events: BTreeSet<(String, String)>,
+}
+
+impl Tables {
+    /// The first warehouse by priority, where stock goes unless told
+    /// otherwise.
+    pub fn default_warehouse(&self) -> Option<i64> {
+        self.warehouses.rows.values().min_by_key(|w| (w.priority, w.id)).map(|w| w.id)
+    }
+
+    /// Delete the carts `keep` rejects, and their reservations with them
+    /// (`stock_reservations.cart_id REFERENCES carts(id) ON DELETE CASCADE`);
+    /// returns how many carts went.
+    pub fn retain_carts(&mut self, keep: impl Fn(&Cart) -> bool) -> u64 {
+        let before = self.carts.rows.len();
+        self.carts.rows.retain(|_, c| keep(c));
+        let carts = &self.carts.rows;
+        self.stock_reservations.rows.retain(|_, r| carts.contains_key(&r.cart_id));
+        (before - self.carts.rows.len()) as u64
+    }
+
+    /// Apply the rows `after` changed since `before` on top of `self`, table
+    /// by table. A row changed here meanwhile as well is a
+    /// [`DomainError::Conflict`], as a serialization failure is in Postgres;
+    /// `self` is left alone then.
+    pub fn merge(&mut self, before: &Tables, after: &Tables) -> Result<(), DomainError> {
+        let mut merged = self.clone();
+        merged.users.merge(&before.users, &after.users)?;
+        merged.categories.merge(&before.categories, &after.categories)?;
+        merged.products.merge(&before.products, &after.products)?;
+        merged.warehouses.merge(&before.warehouses, &after.warehouses)?;
+        merge_rows(&mut merged.warehouse_stock, &before.warehouse_stock, &after.warehouse_stock)?;
+        merged.stock_movements.merge(&before.stock_movements, &after.stock_movements)?;
+        merged.stock_reservations.merge(&before.stock_reservations, &after.stock_reservations)?;
+        merge_rows(&mut merged.stock_thresholds, &before.stock_thresholds, &after.stock_thresholds)?;
+        merged.notifications.merge(&before.notifications, &after.notifications)?;
+        merge_rows(&mut merged.product_costs, &before.product_costs, &after.product_costs)?;
+        merged.suppliers.merge(&before.suppliers, &after.suppliers)?;
+        merge_rows(&mut merged.supplier_products, &before.supplier_products, &after.supplier_products)?;
+        merged.purchase_orders.merge(&before.purchase_orders, &after.purchase_orders)?;
+        merged.promotions.merge(&before.promotions, &after.promotions)?;
+        merged.promotion_redemptions.merge(&before.promotion_redemptions, &after.promotion_redemptions)?;
+        merged.outbox.merge(&before.outbox, &after.outbox)?;
+        merged.api_keys.merge(&before.api_keys, &after.api_keys)?;
+        merged.carts.merge(&before.carts, &after.carts)?;
+        merged.orders.merge(&before.orders, &after.orders)?;
+        merged.order_status_changes.merge(&before.order_status_changes, &after.order_status_changes)?;
+        merged.payment_intents.merge(&before.payment_intents, &after.payment_intents)?;
+        merged.payment_attempts.merge(&before.payment_attempts, &after.payment_attempts)?;
+        // insert-only, and inserting twice is a no-op
+        let seen = after.payment_webhook_events.difference(&before.payment_webhook_events);
+        merged.payment_webhook_events.extend(seen.cloned());
+        *self = merged;
+        Ok(())
+    }
+}
+
+/// See [`Tables::merge`].
+fn merge_rows<K, V>(live: &mut BTreeMap<K, V>, before: &BTreeMap<K, V>, after: &BTreeMap<K, V>) -> Result<(), DomainError>
+where
+    K: Ord + Clone,
+    V: Clone + PartialEq,
+{
+    let keys: BTreeSet<&K> = before.keys().chain(after.keys()).collect();
+    for key in keys {
+        let (was, now) = (before.get(key), after.get(key));
+        if was == now {
+            continue;
+        }
+        let current = live.get(key);
+        if current != was && current != now {
+            return Err(DomainError::Conflict("could not serialize access due to concurrent update".into()));
+        }
+        match now {
+            Some(row) => live.insert(key.clone(), row.clone()),
+            None => live.remove(key),
+        };
+    }
+    Ok(())
+}
+
+/// Rows by id plus the BIGSERIAL sequence.
+#[derive(Clone)]
+pub(super) struct Table<T> {
+    pub rows: BTreeMap<i64, T>,
+    last_id: i64,
+}
+
+impl<T> Default for Table<T> {
+    fn default() -> Self {
+        Self { rows: BTreeMap::new(), last_id: 0 }
+    }
+}
+
+impl<T> Table<T> {
+    pub fn next_id(&mut self) -> i64 {
+        self.last_id += 1;
+        self.last_id
+    }
+}
+
+impl<T: Clone + PartialEq> Table<T> {
+    /// Like a sequence, ids handed out are never given back; two rows given
+    /// the same id here and in `after` conflict.
+    fn merge(&mut self, before: &Table<T>, after: &Table<T>) -> Result<(), DomainError> {
+        merge_rows(&mut self.rows, &before.rows, &after.rows)?;
+        self.last_id = self.last_id.max(after.last_id);
+        Ok(())
+    }
+}
+
+/// Empty but for the `main` warehouse the migrations create.
+impl Default for InMemoryStore {
+    fn default() -> Self {
+        let mut tables = Tables::default();
+        let id = tables.warehouses.next_id();
+        tables.warehouses.rows.insert(id, Warehouse { id, name: "main".into(), priority: 0, location: None });
+        Self { tables: Arc::new(Mutex::new(tables)), tx_lock: Arc::default() }
+    }
+}
+
+impl InMemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(super) fn lock(&self) -> MutexGuard<'_, Tables> {
+        // a panic in another test thread must not poison every later call
+        self.tables.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Same message sqlx gives for `fetch_one` on zero rows.
+pub(super) fn row_not_found() -> DomainError {
+    DomainError::Unexpected(sqlx::Error::RowNotFound.to_string())
+}
+
+/// Same failure Postgres reports on a foreign-key violation.
+pub(super) fn foreign_key_violation(table: &str, constraint: &str) -> DomainError {
+    DomainError::Unexpected(format!(
+        "error returned from database: insert or update on table \"{table}\" violates foreign key constraint \"{constraint}\""
+    ))
+}
@@ -0,0 +1,56 @@
+use async_trait::async_trait;
+use chrono::Utc;
+
+use crate::domain::notification::{Notification, NotificationRepository};
+use crate::domain::DomainError;
+
+use super::{foreign_key_violation, InMemoryStore};
+
+#[derive(Clone)]
+pub struct InMemoryNotificationRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryNotificationRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl NotificationRepository for InMemoryNotificationRepository {
+    async fn create(&self, notification: Notification) -> Result<Notification, DomainError> {
+        let mut tables = self.store.lock();
+        if let Some(product_id) = notification.product_id
+            && !tables.products.rows.contains_key(&product_id)
+        {
+            return Err(foreign_key_violation("notifications", "notifications_product_id_fkey"));
+        }
+        let id = tables.notifications.next_id();
+        let notification = Notification { id, created_at: Utc::now(), read_at: None, ..notification };
+        tables.notifications.rows.insert(id, notification.clone());
+        Ok(notification)
+    }
+
+    async fn list(&self, unread_only: bool) -> Result<Vec<Notification>, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .notifications
+            .rows
+            .values()
+            .rev()
+            .filter(|n| !unread_only || n.read_at.is_none())
+            .cloned()
+            .collect())
+    }
+
+    async fn mark_read(&self, id: i64) -> Result<bool, DomainError> {
+        let mut tables = self.store.lock();
+        let Some(notification) = tables.notifications.rows.get_mut(&id) else {
+            return Ok(false);
+        };
+        notification.read_at.get_or_insert_with(Utc::now);
+        Ok(true)
+    }
+}
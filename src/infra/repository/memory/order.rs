@@ -0,0 +1,97 @@
+use async_trait::async_trait;
+use chrono::Utc;
+
+use crate::domain::page::Page;
+use crate::domain::order::{Order, OrderLine, OrderRepository, StatusChange};
+use crate::domain::DomainError;
+use crate::infra::repository::numeric::{cents_to_f64, to_numeric_10_2_cents};
+
+use super::{foreign_key_violation, InMemoryStore};
+
+/// A row of `order_status_changes`.
+#[derive(Clone, PartialEq)]
+pub(crate) struct StatusChangeEntry {
+    pub order_id: i64,
+    pub change: StatusChange,
+}
+
+#[derive(Clone)]
+pub struct InMemoryOrderRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryOrderRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl OrderRepository for InMemoryOrderRepository {
+    async fn create(&self, order: Order) -> Result<i64, DomainError> {
+        let discount = cents_to_f64(to_numeric_10_2_cents(order.discount)?);
+        let shipping = cents_to_f64(to_numeric_10_2_cents(order.shipping)?);
+        let total = cents_to_f64(to_numeric_10_2_cents(order.total)?);
+        let mut lines = Vec::with_capacity(order.lines.len());
+        for line in order.lines {
+            if line.quantity <= 0 {
+                return Err(DomainError::Unexpected(
+                    "error returned from database: new row for relation \"order_items\" violates check constraint \"order_items_quantity_check\"".into(),
+                ));
+            }
+            let unit_price = cents_to_f64(to_numeric_10_2_cents(line.unit_price)?);
+            lines.push(OrderLine { unit_price, ..line });
+        }
+
+        let mut tables = self.store.lock();
+        if let Some(user_id) = order.user_id
+            && !tables.users.rows.contains_key(&user_id)
+        {
+            return Err(foreign_key_violation("orders", "orders_user_id_fkey"));
+        }
+        if lines.iter().any(|l| !tables.products.rows.contains_key(&l.product_id)) {
+            return Err(foreign_key_violation("order_items", "order_items_product_id_fkey"));
+        }
+        let id = tables.orders.next_id();
+        tables.orders.rows.insert(id, Order { id, lines, discount, shipping, total, created_at: Utc::now(), ..order });
+        Ok(id)
+    }
+
+    async fn record_status_change(&self, id: i64, change: StatusChange) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        if let Some(actor_id) = change.actor_id
+            && !tables.users.rows.contains_key(&actor_id)
+        {
+            return Err(foreign_key_violation("order_status_changes", "order_status_changes_actor_id_fkey"));
+        }
+        let Some(order) = tables.orders.rows.get_mut(&id) else {
+            return Err(foreign_key_violation("order_status_changes", "order_status_changes_order_id_fkey"));
+        };
+        order.status = change.to;
+        let entry_id = tables.order_status_changes.next_id();
+        let change = StatusChange { changed_at: Utc::now(), ..change };
+        tables.order_status_changes.rows.insert(entry_id, StatusChangeEntry { order_id: id, change });
+        Ok(())
+    }
+
+    async fn status_history(&self, id: i64) -> Result<Vec<StatusChange>, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .order_status_changes
+            .rows
+            .values()
+            .filter(|e| e.order_id == id)
+            .map(|e| e.change.clone())
+            .collect())
+    }
+
+    async fn get(&self, id: i64) -> Result<Option<Order>, DomainError> {
+        Ok(self.store.lock().orders.rows.get(&id).cloned())
+    }
+
+    async fn get_by_user_id(&self, user_id: i64, page: Page) -> Result<Vec<Order>, DomainError> {
+        let tables = self.store.lock();
+        Ok(page.apply(tables.orders.rows.values().rev().filter(|o| o.user_id == Some(user_id)).cloned()))
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::domain::event::{DomainEvent, OutboxMessage, OutboxRepository};
+use crate::domain::DomainError;
+use crate::infra::propagation::current_traceparent;
+
+use super::InMemoryStore;
+
+/// An outbox row plus whether it went out, when it may be tried again and
+/// whether it was given up on; errors are only logged by the relay.
+#[derive(Clone, PartialEq)]
+pub(crate) struct OutboxEntry {
+    pub message: OutboxMessage,
+    pub published: bool,
+    pub retry_at: Option<DateTime<Utc>>,
+    pub dead: bool,
+}
+
+impl OutboxEntry {
+    fn open(&self) -> bool {
+        !self.published && !self.dead
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryOutboxRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryOutboxRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl OutboxRepository for InMemoryOutboxRepository {
+    async fn append(&self, event: &DomainEvent) -> Result<i64, DomainError> {
+        let payload = event.payload()?;
+
+        let mut tables = self.store.lock();
+        let id = tables.outbox.next_id();
+        let message = OutboxMessage {
+            id,
+            aggregate_type: event.aggregate_type().into(),
+            aggregate_id: event.aggregate_id(),
+            event_type: event.event_type().into(),
+            payload,
+            trace_context: current_traceparent(),
+            attempts: 0,
+        };
+        tables.outbox.rows.insert(
+            id,
+            OutboxEntry { message, published: false, retry_at: None, dead: false },
+        );
+        Ok(id)
+    }
+
+    async fn lock_relay(&self) -> Result<bool, DomainError> {
+        // units of work already run one at a time
+        Ok(true)
+    }
+
+    async fn pending(&self, limit: i64) -> Result<Vec<OutboxMessage>, DomainError> {
+        let now = Utc::now();
+        // aggregates behind a message waiting for its retry
+        let mut held = HashSet::new();
+        Ok(self
+            .store
+            .lock()
+            .outbox
+            .rows
+            .values()
+            .filter(|e| e.open())
+            .filter(|e| {
+                let aggregate = (e.message.aggregate_type.clone(), e.message.aggregate_id);
+                if e.retry_at.is_some_and(|t| t > now) {
+                    held.insert(aggregate);
+                    return false;
+                }
+                !held.contains(&aggregate)
+            })
+            .take(usize::try_from(limit).unwrap_or(0))
+            .map(|e| e.message.clone())
+            .collect())
+    }
+
+    async fn hold(&self, ids: &[i64], until: Option<DateTime<Utc>>) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        for id in ids {
+            if let Some(entry) = tables.outbox.rows.get_mut(id) {
+                entry.retry_at = until;
+            }
+        }
+        Ok(())
+    }
+
+    async fn mark_published(&self, id: i64) -> Result<(), DomainError> {
+        if let Some(entry) = self.store.lock().outbox.rows.get_mut(&id) {
+            entry.published = true;
+        }
+        Ok(())
+    }
+
+    async fn mark_failed(&self, id: i64, _error: String, retry_at: Option<DateTime<Utc>>) -> Result<(), DomainError> {
+        if let Some(entry) = self.store.lock().outbox.rows.get_mut(&id) {
+            entry.message.attempts += 1;
+            entry.retry_at = retry_at;
+            entry.dead = retry_at.is_none();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+use async_trait::async_trait;
+use chrono::Utc;
+
+use crate::domain::payment::{PaymentAttempt, PaymentIntent, PaymentRepository, PaymentStatus};
+use crate::domain::DomainError;
+use crate::infra::repository::numeric::{cents_to_f64, to_numeric_10_2_cents};
+
+use super::{foreign_key_violation, InMemoryStore};
+
+/// A row of `payment_attempts`.
+#[derive(Clone, PartialEq)]
+pub(crate) struct AttemptEntry {
+    pub intent_id: i64,
+    pub attempt: PaymentAttempt,
+}
+
+#[derive(Clone)]
+pub struct InMemoryPaymentRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryPaymentRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+fn duplicate_provider_ref() -> DomainError {
+    DomainError::Unexpected(
+        "error returned from database: duplicate key value violates unique constraint \"payment_intents_provider_provider_ref_key\"".into(),
+    )
+}
+
+#[async_trait]
+impl PaymentRepository for InMemoryPaymentRepository {
+    async fn create_intent(&self, intent: PaymentIntent) -> Result<i64, DomainError> {
+        let amount = cents_to_f64(to_numeric_10_2_cents(intent.amount)?);
+        let mut tables = self.store.lock();
+        if !tables.orders.rows.contains_key(&intent.order_id) {
+            return Err(foreign_key_violation("payment_intents", "payment_intents_order_id_fkey"));
+        }
+        if intent.provider_ref.is_some()
+            && tables
+                .payment_intents
+                .rows
+                .values()
+                .any(|i| i.provider == intent.provider && i.provider_ref == intent.provider_ref)
+        {
+            return Err(duplicate_provider_ref());
+        }
+        let id = tables.payment_intents.next_id();
+        let now = Utc::now();
+        tables.payment_intents.rows.insert(id, PaymentIntent { id, amount, created_at: now, updated_at: now, ..intent });
+        Ok(id)
+    }
+
+    async fn get_intent(&self, id: i64) -> Result<Option<PaymentIntent>, DomainError> {
+        Ok(self.store.lock().payment_intents.rows.get(&id).cloned())
+    }
+
+    async fn get_intents_by_order_id(&self, order_id: i64) -> Result<Vec<PaymentIntent>, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .payment_intents
+            .rows
+            .values()
+            .rev()
+            .filter(|i| i.order_id == order_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_intent_by_provider_ref(
+        &self,
+        provider: &str,
+        provider_ref: &str,
+    ) -> Result<Option<PaymentIntent>, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .payment_intents
+            .rows
+            .values()
+            .find(|i| i.provider == provider && i.provider_ref.as_deref() == Some(provider_ref))
+            .cloned())
+    }
+
+    async fn update_intent(&self, id: i64, status: PaymentStatus, provider_ref: Option<String>) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        let Some(provider) = tables.payment_intents.rows.get(&id).map(|i| i.provider.clone()) else {
+            return Ok(());
+        };
+        if let Some(provider_ref) = &provider_ref
+            && tables
+                .payment_intents
+                .rows
+                .values()
+                .any(|i| i.id != id && i.provider == provider && i.provider_ref.as_ref() == Some(provider_ref))
+        {
+            return Err(duplicate_provider_ref());
+        }
+        if let Some(intent) = tables.payment_intents.rows.get_mut(&id) {
+            intent.status = status;
+            intent.provider_ref = provider_ref.or(intent.provider_ref.take());
+            intent.updated_at = Utc::now();
+        }
+        Ok(())
+    }
+
+    async fn record_attempt(&self, intent_id: i64, attempt: PaymentAttempt) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        if !tables.payment_intents.rows.contains_key(&intent_id) {
+            return Err(foreign_key_violation("payment_attempts", "payment_attempts_intent_id_fkey"));
+        }
+        let id = tables.payment_attempts.next_id();
+        let attempt = PaymentAttempt { created_at: Utc::now(), ..attempt };
+        tables.payment_attempts.rows.insert(id, AttemptEntry { intent_id, attempt });
+        Ok(())
+    }
+
+    async fn attempts(&self, intent_id: i64) -> Result<Vec<PaymentAttempt>, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .payment_attempts
+            .rows
+            .values()
+            .filter(|e| e.intent_id == intent_id)
+            .map(|e| e.attempt.clone())
+            .collect())
+    }
+
+    async fn record_webhook_event(&self, provider: &str, event_id: &str) -> Result<bool, DomainError> {
+        Ok(self.store.lock().payment_webhook_events.insert((provider.to_string(), event_id.to_string())))
+    }
+}
@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+
+use crate::domain::page::Page;
+use crate::domain::product::{Product, ProductRepository};
+use crate::domain::DomainError;
+use crate::infra::repository::numeric::{cents_to_f64, to_numeric_10_2_cents};
+
+use super::{foreign_key_violation, InMemoryStore};
+
+#[derive(Clone)]
+pub struct InMemoryProductRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryProductRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl ProductRepository for InMemoryProductRepository {
+    async fn create(&self, product: Product) -> Result<i64, DomainError> {
+        let price = cents_to_f64(to_numeric_10_2_cents(product.price)?);
+
+        let mut tables = self.store.lock();
+        if !tables.categories.rows.contains_key(&product.category_id) {
+            return Err(foreign_key_violation("products", "products_category_id_fkey"));
+        }
+        let id = tables.products.next_id();
+        if product.stock > 0
+            && let Some(warehouse_id) = tables.default_warehouse()
+        {
+            tables.warehouse_stock.insert((warehouse_id, id), product.stock);
+        }
+        tables.products.rows.insert(id, Product { id, price, ..product });
+        Ok(id)
+    }
+
+    async fn get_by_product_id(&self, id: i64) -> Result<Option<Product>, DomainError> {
+        Ok(self.store.lock().products.rows.get(&id).cloned())
+    }
+
+    async fn get_by_category_id(&self, category_id: i64, page: Page) -> Result<Vec<Product>, DomainError> {
+        let tables = self.store.lock();
+        Ok(page.apply(tables.products.rows.values().filter(|p| p.category_id == category_id).cloned()))
+    }
+
+    async fn get_all_products(&self, page: Page) -> Result<Vec<Product>, DomainError> {
+        Ok(page.apply(self.store.lock().products.rows.values().cloned()))
+    }
+}
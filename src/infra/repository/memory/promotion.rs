@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+use crate::domain::promotion::{Promotion, PromotionRepository, Redemption};
+use crate::domain::DomainError;
+use crate::infra::repository::numeric::{cents_to_f64, to_numeric_10_2_cents};
+
+use super::{foreign_key_violation, InMemoryStore, Tables};
+
+/// Same failure Postgres reports on a unique violation.
+fn unique_violation(constraint: &str) -> DomainError {
+    DomainError::Unexpected(format!(
+        "error returned from database: duplicate key value violates unique constraint \"{constraint}\""
+    ))
+}
+
+fn check_violation(constraint: &str) -> DomainError {
+    DomainError::Unexpected(format!(
+        "error returned from database: new row for relation \"promotions\" violates check constraint \"{constraint}\""
+    ))
+}
+
+/// The constraints on a `promotions` row, with `value` rounded to
+/// `NUMERIC(10, 2)`.
+fn checked(tables: &Tables, promotion: Promotion) -> Result<Promotion, DomainError> {
+    let value = cents_to_f64(to_numeric_10_2_cents(promotion.value)?);
+    if value < 0.0 {
+        return Err(check_violation("promotions_value_check"));
+    }
+    if promotion.buy_quantity.is_some_and(|q| q <= 0) {
+        return Err(check_violation("promotions_buy_quantity_check"));
+    }
+    if promotion.get_quantity.is_some_and(|q| q <= 0) {
+        return Err(check_violation("promotions_get_quantity_check"));
+    }
+    if promotion.max_uses.is_some_and(|n| n <= 0) {
+        return Err(check_violation("promotions_max_uses_check"));
+    }
+    if promotion.max_uses_per_user.is_some_and(|n| n <= 0) {
+        return Err(check_violation("promotions_max_uses_per_user_check"));
+    }
+    if promotion.category_id.is_some() && promotion.product_id.is_some() {
+        return Err(check_violation("promotions_check"));
+    }
+    if promotion.category_id.is_some_and(|id| !tables.categories.rows.contains_key(&id)) {
+        return Err(foreign_key_violation("promotions", "promotions_category_id_fkey"));
+    }
+    if promotion.product_id.is_some_and(|id| !tables.products.rows.contains_key(&id)) {
+        return Err(foreign_key_violation("promotions", "promotions_product_id_fkey"));
+    }
+    if promotion.code.is_some()
+        && tables.promotions.rows.values().any(|p| p.id != promotion.id && p.code == promotion.code)
+    {
+        return Err(unique_violation("promotions_code_key"));
+    }
+    Ok(Promotion { value, ..promotion })
+}
+
+#[derive(Clone)]
+pub struct InMemoryPromotionRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryPromotionRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl PromotionRepository for InMemoryPromotionRepository {
+    async fn create(&self, promotion: Promotion) -> Result<i64, DomainError> {
+        let mut tables = self.store.lock();
+        let promotion = checked(&tables, Promotion { id: 0, ..promotion })?;
+        let id = tables.promotions.next_id();
+        tables.promotions.rows.insert(id, Promotion { id, ..promotion });
+        Ok(id)
+    }
+
+    async fn get(&self, id: i64) -> Result<Option<Promotion>, DomainError> {
+        Ok(self.store.lock().promotions.rows.get(&id).cloned())
+    }
+
+    async fn by_code(&self, code: &str) -> Result<Option<Promotion>, DomainError> {
+        Ok(self.store.lock().promotions.rows.values().find(|p| p.code.as_deref() == Some(code)).cloned())
+    }
+
+    async fn list(&self) -> Result<Vec<Promotion>, DomainError> {
+        Ok(self.store.lock().promotions.rows.values().cloned().collect())
+    }
+
+    async fn update(&self, promotion: Promotion) -> Result<bool, DomainError> {
+        let mut tables = self.store.lock();
+        if !tables.promotions.rows.contains_key(&promotion.id) {
+            return Ok(false);
+        }
+        let promotion = checked(&tables, promotion)?;
+        tables.promotions.rows.insert(promotion.id, promotion);
+        Ok(true)
+    }
+
+    async fn delete(&self, id: i64) -> Result<bool, DomainError> {
+        let mut tables = self.store.lock();
+        let deleted = tables.promotions.rows.remove(&id).is_some();
+        // promotion_redemptions.promotion_id REFERENCES promotions(id) ON DELETE CASCADE
+        tables.promotion_redemptions.rows.retain(|_, r| r.promotion_id != id);
+        Ok(deleted)
+    }
+
+    async fn redeem(&self, redemption: Redemption) -> Result<(), DomainError> {
+        let discount = cents_to_f64(to_numeric_10_2_cents(redemption.discount)?);
+        let mut tables = self.store.lock();
+        if !tables.promotions.rows.contains_key(&redemption.promotion_id) {
+            return Err(foreign_key_violation("promotion_redemptions", "promotion_redemptions_promotion_id_fkey"));
+        }
+        if let Some(user_id) = redemption.user_id
+            && !tables.users.rows.contains_key(&user_id)
+        {
+            return Err(foreign_key_violation("promotion_redemptions", "promotion_redemptions_user_id_fkey"));
+        }
+        if !tables.orders.rows.contains_key(&redemption.order_id) {
+            return Err(foreign_key_violation("promotion_redemptions", "promotion_redemptions_order_id_fkey"));
+        }
+        let id = tables.promotion_redemptions.next_id();
+        tables.promotion_redemptions.rows.insert(id, Redemption { discount, ..redemption });
+        Ok(())
+    }
+
+    async fn uses(&self, promotion_id: i64, user_id: Option<i64>) -> Result<i64, DomainError> {
+        let tables = self.store.lock();
+        let uses = tables
+            .promotion_redemptions
+            .rows
+            .values()
+            .filter(|r| r.promotion_id == promotion_id && user_id.is_none_or(|id| r.user_id == Some(id)))
+            .count();
+        Ok(uses as i64)
+    }
+
+    async fn uses_by_promotion(&self, user_id: Option<i64>) -> Result<HashMap<i64, i64>, DomainError> {
+        let tables = self.store.lock();
+        let mut uses = HashMap::new();
+        for r in tables.promotion_redemptions.rows.values() {
+            if user_id.is_none_or(|id| r.user_id == Some(id)) {
+                *uses.entry(r.promotion_id).or_insert(0) += 1;
+            }
+        }
+        Ok(uses)
+    }
+}
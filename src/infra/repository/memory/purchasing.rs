@@ -0,0 +1,162 @@
+use async_trait::async_trait;
+use chrono::Utc;
+
+use crate::domain::purchasing::{PurchaseOrder, PurchaseOrderLine, PurchaseOrderStatus, PurchasingRepository, Supplier, SupplierProduct};
+use crate::domain::DomainError;
+use crate::infra::repository::numeric::{cents_to_f64, to_numeric_10_2_cents};
+
+use super::{foreign_key_violation, InMemoryStore};
+
+/// Same failure Postgres reports on a unique violation.
+fn unique_violation(constraint: &str) -> DomainError {
+    DomainError::Unexpected(format!(
+        "error returned from database: duplicate key value violates unique constraint \"{constraint}\""
+    ))
+}
+
+fn check_violation(table: &str, constraint: &str) -> DomainError {
+    DomainError::Unexpected(format!(
+        "error returned from database: new row for relation \"{table}\" violates check constraint \"{constraint}\""
+    ))
+}
+
+/// `NUMERIC(10, 2)` rounding, and the `>= 0` checks on money.
+fn money(value: f64, table: &str, constraint: &str) -> Result<f64, DomainError> {
+    let value = cents_to_f64(to_numeric_10_2_cents(value)?);
+    if value < 0.0 {
+        return Err(check_violation(table, constraint));
+    }
+    Ok(value)
+}
+
+#[derive(Clone)]
+pub struct InMemoryPurchasingRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryPurchasingRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl PurchasingRepository for InMemoryPurchasingRepository {
+    async fn create_supplier(&self, supplier: Supplier) -> Result<i64, DomainError> {
+        let mut tables = self.store.lock();
+        if tables.suppliers.rows.values().any(|s| s.name == supplier.name) {
+            return Err(unique_violation("suppliers_name_key"));
+        }
+        let id = tables.suppliers.next_id();
+        tables.suppliers.rows.insert(id, Supplier { id, ..supplier });
+        Ok(id)
+    }
+
+    async fn supplier(&self, id: i64) -> Result<Option<Supplier>, DomainError> {
+        Ok(self.store.lock().suppliers.rows.get(&id).cloned())
+    }
+
+    async fn suppliers(&self) -> Result<Vec<Supplier>, DomainError> {
+        Ok(self.store.lock().suppliers.rows.values().cloned().collect())
+    }
+
+    async fn set_supplier_product(&self, link: SupplierProduct) -> Result<SupplierProduct, DomainError> {
+        let cost_price = money(link.cost_price, "supplier_products", "supplier_products_cost_price_check")?;
+        let mut tables = self.store.lock();
+        if !tables.suppliers.rows.contains_key(&link.supplier_id) {
+            return Err(foreign_key_violation("supplier_products", "supplier_products_supplier_id_fkey"));
+        }
+        if !tables.products.rows.contains_key(&link.product_id) {
+            return Err(foreign_key_violation("supplier_products", "supplier_products_product_id_fkey"));
+        }
+        let taken = tables
+            .supplier_products
+            .values()
+            .any(|l| l.supplier_id == link.supplier_id && l.product_id != link.product_id && l.sku == link.sku);
+        if taken {
+            return Err(unique_violation("supplier_products_supplier_id_sku_key"));
+        }
+        let link = SupplierProduct { cost_price, ..link };
+        tables.supplier_products.insert((link.supplier_id, link.product_id), link.clone());
+        Ok(link)
+    }
+
+    async fn supplier_products(&self, supplier_id: i64) -> Result<Vec<SupplierProduct>, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .supplier_products
+            .range((supplier_id, i64::MIN)..=(supplier_id, i64::MAX))
+            .map(|(_, link)| link.clone())
+            .collect())
+    }
+
+    async fn create_order(&self, order: PurchaseOrder) -> Result<i64, DomainError> {
+        let mut lines = Vec::with_capacity(order.lines.len());
+        for line in order.lines {
+            if line.quantity <= 0 {
+                return Err(check_violation("purchase_order_lines", "purchase_order_lines_quantity_check"));
+            }
+            if line.received < 0 || line.received > line.quantity {
+                return Err(check_violation("purchase_order_lines", "purchase_order_lines_check"));
+            }
+            let unit_cost = money(line.unit_cost, "purchase_order_lines", "purchase_order_lines_unit_cost_check")?;
+            lines.push(PurchaseOrderLine { unit_cost, ..line });
+        }
+        lines.sort_by_key(|l| l.product_id);
+        if lines.windows(2).any(|w| w[0].product_id == w[1].product_id) {
+            return Err(unique_violation("purchase_order_lines_pkey"));
+        }
+
+        let mut tables = self.store.lock();
+        if !tables.suppliers.rows.contains_key(&order.supplier_id) {
+            return Err(foreign_key_violation("purchase_orders", "purchase_orders_supplier_id_fkey"));
+        }
+        if let Some(created_by) = order.created_by
+            && !tables.users.rows.contains_key(&created_by)
+        {
+            return Err(foreign_key_violation("purchase_orders", "purchase_orders_created_by_fkey"));
+        }
+        if lines.iter().any(|l| !tables.products.rows.contains_key(&l.product_id)) {
+            return Err(foreign_key_violation("purchase_order_lines", "purchase_order_lines_product_id_fkey"));
+        }
+        let id = tables.purchase_orders.next_id();
+        tables.purchase_orders.rows.insert(id, PurchaseOrder { id, lines, created_at: Utc::now(), ..order });
+        Ok(id)
+    }
+
+    async fn order(&self, id: i64) -> Result<Option<PurchaseOrder>, DomainError> {
+        Ok(self.store.lock().purchase_orders.rows.get(&id).cloned())
+    }
+
+    async fn orders(&self) -> Result<Vec<PurchaseOrder>, DomainError> {
+        Ok(self.store.lock().purchase_orders.rows.values().rev().cloned().collect())
+    }
+
+    async fn set_status(&self, id: i64, status: PurchaseOrderStatus) -> Result<(), DomainError> {
+        if let Some(order) = self.store.lock().purchase_orders.rows.get_mut(&id) {
+            order.status = status;
+        }
+        Ok(())
+    }
+
+    async fn receive(&self, id: i64, product_id: i64, quantity: i32) -> Result<bool, DomainError> {
+        let mut tables = self.store.lock();
+        let Some(line) = tables
+            .purchase_orders
+            .rows
+            .get_mut(&id)
+            .and_then(|o| o.lines.iter_mut().find(|l| l.product_id == product_id))
+        else {
+            return Ok(false);
+        };
+        if line.received + quantity > line.quantity {
+            return Ok(false);
+        }
+        if line.received + quantity < 0 {
+            return Err(check_violation("purchase_order_lines", "purchase_order_lines_check"));
+        }
+        line.received += quantity;
+        Ok(true)
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::domain::reservation::{Reservation, ReservationRepository};
+use crate::domain::DomainError;
+
+use super::{foreign_key_violation, InMemoryStore};
+
+#[derive(Clone)]
+pub struct InMemoryReservationRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryReservationRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl ReservationRepository for InMemoryReservationRepository {
+    async fn hold(
+        &self,
+        cart_id: i64,
+        product_id: i64,
+        quantity: i32,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Reservation, DomainError> {
+        if quantity <= 0 {
+            return Err(DomainError::Unexpected(
+                "error returned from database: new row for relation \"stock_reservations\" violates check constraint \"stock_reservations_quantity_check\"".into(),
+            ));
+        }
+        let mut tables = self.store.lock();
+        if !tables.carts.rows.contains_key(&cart_id) {
+            return Err(foreign_key_violation("stock_reservations", "stock_reservations_cart_id_fkey"));
+        }
+        if !tables.products.rows.contains_key(&product_id) {
+            return Err(foreign_key_violation("stock_reservations", "stock_reservations_product_id_fkey"));
+        }
+        let existing = tables
+            .stock_reservations
+            .rows
+            .values()
+            .find(|r| r.cart_id == cart_id && r.product_id == product_id)
+            .map(|r| r.id);
+        let id = match existing {
+            Some(id) => id,
+            None => tables.stock_reservations.next_id(),
+        };
+        let reservation = Reservation { id, cart_id, product_id, quantity, expires_at };
+        tables.stock_reservations.rows.insert(id, reservation.clone());
+        Ok(reservation)
+    }
+
+    async fn release(&self, cart_id: i64, product_id: i64) -> Result<bool, DomainError> {
+        let mut tables = self.store.lock();
+        let before = tables.stock_reservations.rows.len();
+        tables.stock_reservations.rows.retain(|_, r| r.cart_id != cart_id || r.product_id != product_id);
+        Ok(tables.stock_reservations.rows.len() < before)
+    }
+
+    async fn reserved(
+        &self,
+        product_ids: &[i64],
+        except_cart: Option<i64>,
+        now: DateTime<Utc>,
+    ) -> Result<HashMap<i64, i32>, DomainError> {
+        let mut reserved = HashMap::new();
+        for r in self.store.lock().stock_reservations.rows.values() {
+            if product_ids.contains(&r.product_id) && r.expires_at > now && Some(r.cart_id) != except_cart {
+                *reserved.entry(r.product_id).or_default() += r.quantity;
+            }
+        }
+        Ok(reserved)
+    }
+
+    async fn release_expired(&self, now: DateTime<Utc>) -> Result<u64, DomainError> {
+        let mut tables = self.store.lock();
+        let before = tables.stock_reservations.rows.len();
+        tables.stock_reservations.rows.retain(|_, r| r.expires_at > now);
+        Ok((before - tables.stock_reservations.rows.len()) as u64)
+    }
+}
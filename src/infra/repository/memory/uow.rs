@@ -0,0 +1,65 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use crate::domain::uow::{TxRepositories, TxWork, UnitOfWork};
+use crate::domain::DomainError;
+use crate::infra::repository::db::retry_on_conflict;
+
+use super::{
+    InMemoryCartRepository, InMemoryCategoryRepository, InMemoryInventoryRepository, InMemoryNotificationRepository, InMemoryOrderRepository, InMemoryOutboxRepository,
+    InMemoryPaymentRepository, InMemoryProductRepository, InMemoryPromotionRepository, InMemoryPurchasingRepository, InMemoryReservationRepository, InMemoryStore, InMemoryUserRepository,
+    InMemoryWarehouseRepository,
+};
+
+/// Runs `work` against a private copy of the tables and, on commit, applies
+/// the rows it changed to the shared ones. Units of work are serialized with
+/// each other; a plain repository write made while one is running stays, and
+/// if it touched a row the unit of work changed too, the unit of work is
+/// retried as a conflict.
+#[derive(Clone)]
+pub struct InMemoryUnitOfWork {
+    store: InMemoryStore,
+}
+
+impl InMemoryUnitOfWork {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+
+    async fn attempt(&self, work: &mut TxWork<'_>) -> Result<(), DomainError> {
+        let _serialized = self.store.tx_lock.lock().await;
+
+        let before = self.store.lock().clone();
+        let snapshot = InMemoryStore {
+            tables: Arc::new(Mutex::new(before.clone())),
+            tx_lock: self.store.tx_lock.clone(),
+        };
+        let repos = TxRepositories {
+            users: Arc::new(InMemoryUserRepository::new(snapshot.clone())),
+            categories: Arc::new(InMemoryCategoryRepository::new(snapshot.clone())),
+            products: Arc::new(InMemoryProductRepository::new(snapshot.clone())),
+            inventory: Arc::new(InMemoryInventoryRepository::new(snapshot.clone())),
+            warehouses: Arc::new(InMemoryWarehouseRepository::new(snapshot.clone())),
+            carts: Arc::new(InMemoryCartRepository::new(snapshot.clone())),
+            reservations: Arc::new(InMemoryReservationRepository::new(snapshot.clone())),
+            orders: Arc::new(InMemoryOrderRepository::new(snapshot.clone())),
+            payments: Arc::new(InMemoryPaymentRepository::new(snapshot.clone())),
+            notifications: Arc::new(InMemoryNotificationRepository::new(snapshot.clone())),
+            purchasing: Arc::new(InMemoryPurchasingRepository::new(snapshot.clone())),
+            promotions: Arc::new(InMemoryPromotionRepository::new(snapshot.clone())),
+            outbox: Arc::new(InMemoryOutboxRepository::new(snapshot.clone())),
+        };
+        work(&repos).await?;
+
+        let after = snapshot.lock();
+        self.store.lock().merge(&before, &after)
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for InMemoryUnitOfWork {
+    async fn run(&self, work: &mut TxWork<'_>) -> Result<(), DomainError> {
+        retry_on_conflict(work, |work| Box::pin(self.attempt(work))).await
+    }
+}
@@ -0,0 +1,96 @@
+use async_trait::async_trait;
+
+use crate::domain::cart::CartOwner;
+use crate::domain::page::Page;
+use crate::domain::user::{User, UserRepository};
+use crate::domain::DomainError;
+use crate::infra::crypto::hash_password;
+
+use super::{row_not_found, InMemoryStore};
+
+#[derive(Clone)]
+pub struct InMemoryUserRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryUserRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl UserRepository for InMemoryUserRepository {
+    async fn create(&self, username: String, password: String) -> Result<i64, DomainError> {
+        let hashed = hash_password(password).await?;
+
+        let mut tables = self.store.lock();
+        let id = tables.users.next_id();
+        tables.users.rows.insert(
+            id,
+            User { id, username, password: hashed.into(), active: true, admin: false },
+        );
+        Ok(id)
+    }
+
+    async fn get_by_id(&self, id: i64) -> Result<Option<User>, DomainError> {
+        Ok(self.store.lock().users.rows.get(&id).cloned())
+    }
+
+    async fn get_by_username(&self, username: String) -> Result<Option<User>, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .users
+            .rows
+            .values()
+            .find(|u| u.username == username)
+            .cloned())
+    }
+
+    async fn get_all_users(&self, page: Page) -> Result<Vec<User>, DomainError> {
+        Ok(page.apply(self.store.lock().users.rows.values().cloned()))
+    }
+
+    async fn update(&self, id: i64, username: String, password: String) -> Result<User, DomainError> {
+        let hashed = hash_password(password).await?;
+
+        let mut tables = self.store.lock();
+        let user = tables.users.rows.get_mut(&id).ok_or_else(row_not_found)?;
+        user.username = username;
+        user.password = hashed.into();
+        Ok(user.clone())
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        tables.users.rows.remove(&id);
+        // carts.user_id REFERENCES users(id) ON DELETE CASCADE
+        tables.retain_carts(|c| c.owner != CartOwner::User(id));
+        // orders.user_id REFERENCES users(id) ON DELETE SET NULL
+        for order in tables.orders.rows.values_mut().filter(|o| o.user_id == Some(id)) {
+            order.user_id = None;
+        }
+        // order_status_changes.actor_id likewise
+        for entry in tables.order_status_changes.rows.values_mut().filter(|e| e.change.actor_id == Some(id)) {
+            entry.change.actor_id = None;
+        }
+        for movement in tables.stock_movements.rows.values_mut().filter(|m| m.actor_id == Some(id)) {
+            movement.actor_id = None;
+        }
+        for order in tables.purchase_orders.rows.values_mut().filter(|o| o.created_by == Some(id)) {
+            order.created_by = None;
+        }
+        for redemption in tables.promotion_redemptions.rows.values_mut().filter(|r| r.user_id == Some(id)) {
+            redemption.user_id = None;
+        }
+        Ok(())
+    }
+
+    async fn set_admin(&self, id: i64, admin: bool) -> Result<(), DomainError> {
+        if let Some(user) = self.store.lock().users.rows.get_mut(&id) {
+            user.admin = admin;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+
+use crate::domain::warehouse::{Warehouse, WarehouseRepository, WarehouseStock};
+use crate::domain::DomainError;
+
+use super::InMemoryStore;
+
+#[derive(Clone)]
+pub struct InMemoryWarehouseRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryWarehouseRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl WarehouseRepository for InMemoryWarehouseRepository {
+    async fn create(&self, warehouse: Warehouse) -> Result<i64, DomainError> {
+        let mut tables = self.store.lock();
+        if tables.warehouses.rows.values().any(|w| w.name == warehouse.name) {
+            return Err(DomainError::Unexpected(
+                "error returned from database: duplicate key value violates unique constraint \"warehouses_name_key\"".into(),
+            ));
+        }
+        let id = tables.warehouses.next_id();
+        tables.warehouses.rows.insert(id, Warehouse { id, ..warehouse });
+        Ok(id)
+    }
+
+    async fn get(&self, id: i64) -> Result<Option<Warehouse>, DomainError> {
+        Ok(self.store.lock().warehouses.rows.get(&id).cloned())
+    }
+
+    async fn list(&self) -> Result<Vec<Warehouse>, DomainError> {
+        let mut warehouses: Vec<_> = self.store.lock().warehouses.rows.values().cloned().collect();
+        warehouses.sort_by_key(|w| (w.priority, w.id));
+        Ok(warehouses)
+    }
+
+    async fn stock(&self, product_ids: &[i64]) -> Result<Vec<WarehouseStock>, DomainError> {
+        let tables = self.store.lock();
+        let mut stock: Vec<_> = tables
+            .warehouse_stock
+            .iter()
+            .filter(|((_, product_id), stock)| product_ids.contains(product_id) && **stock != 0)
+            .filter_map(|(&(warehouse_id, product_id), &stock)| {
+                let warehouse = tables.warehouses.rows.get(&warehouse_id)?;
+                Some((warehouse.priority, WarehouseStock { warehouse_id, warehouse: warehouse.name.clone(), product_id, stock }))
+            })
+            .collect();
+        stock.sort_by_key(|(priority, s)| (s.product_id, *priority, s.warehouse_id));
+        Ok(stock.into_iter().map(|(_, s)| s).collect())
+    }
+}
@@ -1,3 +1,25 @@
 pub mod user;
+pub mod api_key;
+pub mod cart;
 pub mod category;
+pub mod inventory;
+pub mod notification;
+pub mod order;
+pub mod payment;
 pub mod product;
+pub mod promotion;
+pub mod purchasing;
+pub mod reservation;
+pub mod warehouse;
+pub mod outbox;
+pub mod job;
+pub mod memory;
+pub mod numeric;
+pub mod sqlite;
+pub mod db;
+pub mod backend;
+pub mod cache_sync;
+pub mod cached;
+pub mod uow;
+#[cfg(test)]
+mod conformance;
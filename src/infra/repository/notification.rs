@@ -0,0 +1,85 @@
+use async_trait::async_trait;
+use sqlx::{PgPool, Postgres};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::notification::{Notification, NotificationRepository};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{pg_query, pg_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+
+#[derive(Clone)]
+pub struct PostgresNotificationRepository {
+    db: DbHandle<Postgres>,
+}
+
+impl PostgresNotificationRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Postgres>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl NotificationRepository for PostgresNotificationRepository {
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "notifications", db.query.text = Empty))]
+    async fn create(&self, notification: Notification) -> Result<Notification, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query_as!(
+            Notification,
+            r#"
+            INSERT INTO notifications (kind, message, product_id)
+            VALUES ($1, $2, $3)
+            RETURNING id, kind, message, product_id, created_at, read_at
+            "#,
+            notification.kind,
+            notification.message,
+            notification.product_id
+        )
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "notifications", db.query.text = Empty))]
+    async fn list(&self, unread_only: bool) -> Result<Vec<Notification>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query_as!(
+            Notification,
+            r#"
+            SELECT id, kind, message, product_id, created_at, read_at
+            FROM notifications
+            WHERE NOT $1 OR read_at IS NULL
+            ORDER BY id DESC
+            "#,
+            unread_only
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "UPDATE", db.collection.name = "notifications", db.query.text = Empty))]
+    async fn mark_read(&self, id: i64) -> Result<bool, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = pg_query!(
+            r#"
+            UPDATE notifications
+            SET read_at = COALESCE(read_at, now())
+            WHERE id = $1
+            "#,
+            id
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
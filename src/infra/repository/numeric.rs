@@ -0,0 +1,49 @@
+//! `NUMERIC(10, 2)` emulation for backends without an exact decimal type
+//! (in-memory, SQLite), so prices round exactly as they do in Postgres.
+
+use crate::domain::DomainError;
+
+/// Largest value that fits `NUMERIC(10, 2)`, in cents.
+const MAX_CENTS: i128 = 9_999_999_999;
+
+/// Round to cents the way `$1::float8` into `NUMERIC(10, 2)` does: Postgres
+/// prints the float with 15 significant digits (DBL_DIG), then rounds half
+/// away from zero — so 1.005 becomes 1.01, not 1.00.
+pub fn to_numeric_10_2_cents(price: f64) -> Result<i64, DomainError> {
+    let overflow = || DomainError::Unexpected("error returned from database: numeric field overflow".into());
+    if !price.is_finite() {
+        return Err(overflow());
+    }
+
+    // d.dddddddddddddde<exp>
+    let text = format!("{:.14e}", price.abs());
+    let (mantissa, exp) = text.split_once('e').ok_or_else(overflow)?;
+    let exp: i32 = exp.parse().map_err(|_| overflow())?;
+    if exp >= 8 {
+        return Err(overflow());
+    }
+    let digits: Vec<i128> = mantissa
+        .bytes()
+        .filter(u8::is_ascii_digit)
+        .map(|b| (b - b'0') as i128)
+        .collect();
+
+    // digits before the rounding position form the value in cents
+    let keep = exp + 3;
+    let digit = |i: i32| usize::try_from(i).ok().and_then(|i| digits.get(i)).copied().unwrap_or(0);
+    let mut cents = (0..keep).fold(0i128, |acc, i| acc * 10 + digit(i));
+    if keep >= 0 && digit(keep) >= 5 {
+        cents += 1;
+    }
+    if cents > MAX_CENTS {
+        return Err(overflow());
+    }
+
+    let cents = if price < 0.0 { -cents } else { cents };
+    Ok(cents as i64)
+}
+
+/// `price::float8` for a value stored in cents.
+pub fn cents_to_f64(cents: i64) -> f64 {
+    cents as f64 / 100.0
+}
@@ -0,0 +1,257 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool, Postgres};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::page::Page;
+use crate::domain::order::{Order, OrderLine, OrderRepository, OrderStatus, StatusChange};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{pg_query, pg_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+
+/// A status as stored in `orders.status`.
+pub(crate) fn parse_status(status: &str) -> Result<OrderStatus, DomainError> {
+    status.parse().map_err(DomainError::Unexpected)
+}
+
+struct OrderRow {
+    id: i64,
+    user_id: Option<i64>,
+    discount: f64,
+    shipping: f64,
+    total: f64,
+    status: String,
+    created_at: DateTime<Utc>,
+}
+
+struct StatusChangeRow {
+    from_status: Option<String>,
+    to_status: String,
+    actor_id: Option<i64>,
+    reason: Option<String>,
+    changed_at: DateTime<Utc>,
+}
+
+struct OrderLineRow {
+    order_id: i64,
+    product_id: i64,
+    name: String,
+    quantity: i32,
+    unit_price: f64,
+}
+
+#[derive(Clone)]
+pub struct PostgresOrderRepository {
+    db: DbHandle<Postgres>,
+}
+
+impl PostgresOrderRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Postgres>) -> Self {
+        Self { db }
+    }
+}
+
+/// The orders of `rows` with their lines, in the order of `rows`.
+async fn with_lines(conn: &mut PgConnection, rows: Vec<OrderRow>) -> Result<Vec<Order>, DomainError> {
+    let ids: Vec<i64> = rows.iter().map(|o| o.id).collect();
+    let lines = pg_query_as!(
+        OrderLineRow,
+        r#"
+        SELECT order_id, product_id, name, quantity, unit_price::float8 as "unit_price!"
+        FROM order_items
+        WHERE order_id = ANY($1)
+        ORDER BY id
+        "#,
+        &ids
+    )
+    .fetch_all(&mut *conn)
+    .traced()
+    .await
+    .map_err(db_error)?;
+
+    rows.into_iter()
+        .map(|o| {
+            Ok(Order {
+                id: o.id,
+                user_id: o.user_id,
+                lines: lines
+                    .iter()
+                    .filter(|l| l.order_id == o.id)
+                    .map(|l| OrderLine {
+                        product_id: l.product_id,
+                        name: l.name.clone(),
+                        quantity: l.quantity,
+                        unit_price: l.unit_price,
+                    })
+                    .collect(),
+                discount: o.discount,
+                shipping: o.shipping,
+                total: o.total,
+                status: parse_status(&o.status)?,
+                created_at: o.created_at,
+            })
+        })
+        .collect()
+}
+
+#[async_trait]
+impl OrderRepository for PostgresOrderRepository {
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "orders", db.query.text = Empty))]
+    async fn create(&self, order: Order) -> Result<i64, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query!(
+            r#"
+            INSERT INTO orders (user_id, discount, shipping, total, status)
+            VALUES ($1, $2::float8, $3::float8, $4::float8, $5)
+            RETURNING id
+            "#,
+            order.user_id,
+            order.discount,
+            order.shipping,
+            order.total,
+            order.status.as_str()
+        )
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        for line in order.lines {
+            pg_query!(
+                r#"
+                INSERT INTO order_items (order_id, product_id, name, quantity, unit_price)
+                VALUES ($1, $2, $3, $4, $5::float8)
+                "#,
+                row.id,
+                line.product_id,
+                line.name,
+                line.quantity,
+                line.unit_price
+            )
+            .execute(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)?;
+        }
+
+        Ok(row.id)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "UPDATE", db.collection.name = "orders", db.query.text = Empty))]
+    async fn record_status_change(&self, id: i64, change: StatusChange) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query!(
+            r#"
+            UPDATE orders
+            SET status = $2
+            WHERE id = $1
+            "#,
+            id,
+            change.to.as_str()
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        pg_query!(
+            r#"
+            INSERT INTO order_status_changes (order_id, from_status, to_status, actor_id, reason)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            id,
+            change.from.map(OrderStatus::as_str),
+            change.to.as_str(),
+            change.actor_id,
+            change.reason
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "order_status_changes", db.query.text = Empty))]
+    async fn status_history(&self, id: i64) -> Result<Vec<StatusChange>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = pg_query_as!(
+            StatusChangeRow,
+            r#"
+            SELECT from_status, to_status, actor_id, reason, changed_at
+            FROM order_status_changes
+            WHERE order_id = $1
+            ORDER BY id
+            "#,
+            id
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(StatusChange {
+                    from: row.from_status.as_deref().map(parse_status).transpose()?,
+                    to: parse_status(&row.to_status)?,
+                    actor_id: row.actor_id,
+                    reason: row.reason,
+                    changed_at: row.changed_at,
+                })
+            })
+            .collect()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "orders", db.query.text = Empty))]
+    async fn get(&self, id: i64) -> Result<Option<Order>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let Some(row) = pg_query_as!(
+            OrderRow,
+            r#"
+            SELECT id, user_id, discount::float8 as "discount!", shipping::float8 as "shipping!", total::float8 as "total!", status, created_at
+            FROM orders
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        else {
+            return Ok(None);
+        };
+
+        Ok(with_lines(&mut conn, vec![row]).await?.pop())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "orders", db.query.text = Empty))]
+    async fn get_by_user_id(&self, user_id: i64, page: Page) -> Result<Vec<Order>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = pg_query_as!(
+            OrderRow,
+            r#"
+            SELECT id, user_id, discount::float8 as "discount!", shipping::float8 as "shipping!", total::float8 as "total!", status, created_at
+            FROM orders
+            WHERE user_id = $1
+            ORDER BY id DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            user_id,
+            page.limit,
+            page.offset
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        with_lines(&mut conn, rows).await
+    }
+}
@@ -0,0 +1,157 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::Postgres;
+use tracing::{field::Empty, instrument};
+
+use crate::domain::event::{DomainEvent, OutboxMessage, OutboxRepository};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{pg_query, pg_query_as, DbTraceExt};
+use crate::infra::propagation::current_traceparent;
+use crate::infra::repository::db::{db_error, DbHandle};
+
+/// Only used through a unit of work: `append` joins the caller's
+/// transaction and `lock_relay` holds an advisory lock until it ends.
+#[derive(Clone)]
+pub struct PostgresOutboxRepository {
+    db: DbHandle<Postgres>,
+}
+
+impl PostgresOutboxRepository {
+    pub fn with_handle(db: DbHandle<Postgres>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl OutboxRepository for PostgresOutboxRepository {
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "outbox", db.query.text = Empty))]
+    async fn append(&self, event: &DomainEvent) -> Result<i64, DomainError> {
+        let payload = event.payload()?;
+
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query!(
+            r#"
+            INSERT INTO outbox (aggregate_type, aggregate_id, event_type, payload, trace_context)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#,
+            event.aggregate_type(),
+            event.aggregate_id(),
+            event.event_type(),
+            payload,
+            current_traceparent()
+        )
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.id)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "outbox", db.query.text = Empty))]
+    async fn lock_relay(&self) -> Result<bool, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query!(
+            r#"
+            SELECT pg_try_advisory_xact_lock(hashtext('outbox_relay')) AS "locked!"
+            "#
+        )
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.locked)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "outbox", db.query.text = Empty))]
+    async fn pending(&self, limit: i64) -> Result<Vec<OutboxMessage>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = pg_query_as!(
+            OutboxMessage,
+            r#"
+            SELECT o.id, o.aggregate_type, o.aggregate_id, o.event_type, o.payload, o.trace_context, o.attempts
+            FROM outbox o
+            WHERE o.published_at IS NULL AND o.dead_at IS NULL
+              AND (o.next_attempt_at IS NULL OR o.next_attempt_at <= now())
+              AND NOT EXISTS (
+                SELECT 1
+                FROM outbox w
+                WHERE w.aggregate_type = o.aggregate_type AND w.aggregate_id = o.aggregate_id AND w.id < o.id
+                  AND w.published_at IS NULL AND w.dead_at IS NULL AND w.next_attempt_at > now()
+              )
+            ORDER BY o.id
+            LIMIT $1
+            "#,
+            limit
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(rows)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "UPDATE", db.collection.name = "outbox", db.query.text = Empty))]
+    async fn hold(&self, ids: &[i64], until: Option<DateTime<Utc>>) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query!(
+            r#"
+            UPDATE outbox
+            SET next_attempt_at = $2
+            WHERE id = ANY($1)
+            "#,
+            ids,
+            until
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "UPDATE", db.collection.name = "outbox", db.query.text = Empty))]
+    async fn mark_published(&self, id: i64) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query!(
+            r#"
+            UPDATE outbox
+            SET published_at = now(), attempts = attempts + 1, last_error = NULL
+            WHERE id = $1
+            "#,
+            id
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "UPDATE", db.collection.name = "outbox", db.query.text = Empty))]
+    async fn mark_failed(&self, id: i64, error: String, retry_at: Option<DateTime<Utc>>) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query!(
+            r#"
+            UPDATE outbox
+            SET attempts = attempts + 1, last_error = $2, next_attempt_at = $3,
+                dead_at = CASE WHEN $3::timestamptz IS NULL THEN now() END
+            WHERE id = $1
+            "#,
+            id,
+            error,
+            retry_at
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,251 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::payment::{PaymentAttempt, PaymentIntent, PaymentOperation, PaymentRepository, PaymentStatus};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{pg_query, pg_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+
+/// A status as stored in `payment_intents.status`.
+pub(crate) fn parse_payment_status(status: &str) -> Result<PaymentStatus, DomainError> {
+    status.parse().map_err(DomainError::Unexpected)
+}
+
+/// An operation as stored in `payment_attempts.operation`.
+pub(crate) fn parse_operation(operation: &str) -> Result<PaymentOperation, DomainError> {
+    operation.parse().map_err(DomainError::Unexpected)
+}
+
+struct IntentRow {
+    id: i64,
+    order_id: i64,
+    provider: String,
+    provider_ref: Option<String>,
+    amount: f64,
+    status: String,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl TryFrom<IntentRow> for PaymentIntent {
+    type Error = DomainError;
+
+    fn try_from(row: IntentRow) -> Result<Self, Self::Error> {
+        Ok(PaymentIntent {
+            id: row.id,
+            order_id: row.order_id,
+            provider: row.provider,
+            provider_ref: row.provider_ref,
+            amount: row.amount,
+            status: parse_payment_status(&row.status)?,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+struct AttemptRow {
+    operation: String,
+    succeeded: bool,
+    detail: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+pub struct PostgresPaymentRepository {
+    db: DbHandle<Postgres>,
+}
+
+impl PostgresPaymentRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Postgres>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl PaymentRepository for PostgresPaymentRepository {
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "payment_intents", db.query.text = Empty))]
+    async fn create_intent(&self, intent: PaymentIntent) -> Result<i64, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query!(
+            r#"
+            INSERT INTO payment_intents (order_id, provider, provider_ref, amount, status)
+            VALUES ($1, $2, $3, $4::float8, $5)
+            RETURNING id
+            "#,
+            intent.order_id,
+            intent.provider,
+            intent.provider_ref,
+            intent.amount,
+            intent.status.as_str()
+        )
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(row.id)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "payment_intents", db.query.text = Empty))]
+    async fn get_intent(&self, id: i64) -> Result<Option<PaymentIntent>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query_as!(
+            IntentRow,
+            r#"
+            SELECT id, order_id, provider, provider_ref, amount::float8 as "amount!", status, created_at, updated_at
+            FROM payment_intents
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        .map(PaymentIntent::try_from)
+        .transpose()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "payment_intents", db.query.text = Empty))]
+    async fn get_intents_by_order_id(&self, order_id: i64) -> Result<Vec<PaymentIntent>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query_as!(
+            IntentRow,
+            r#"
+            SELECT id, order_id, provider, provider_ref, amount::float8 as "amount!", status, created_at, updated_at
+            FROM payment_intents
+            WHERE order_id = $1
+            ORDER BY id DESC
+            "#,
+            order_id
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(PaymentIntent::try_from)
+        .collect()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "payment_intents", db.query.text = Empty))]
+    async fn get_intent_by_provider_ref(
+        &self,
+        provider: &str,
+        provider_ref: &str,
+    ) -> Result<Option<PaymentIntent>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query_as!(
+            IntentRow,
+            r#"
+            SELECT id, order_id, provider, provider_ref, amount::float8 as "amount!", status, created_at, updated_at
+            FROM payment_intents
+            WHERE provider = $1 AND provider_ref = $2
+            "#,
+            provider,
+            provider_ref
+        )
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        .map(PaymentIntent::try_from)
+        .transpose()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "UPDATE", db.collection.name = "payment_intents", db.query.text = Empty))]
+    async fn update_intent(&self, id: i64, status: PaymentStatus, provider_ref: Option<String>) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query!(
+            r#"
+            UPDATE payment_intents
+            SET status = $2, provider_ref = COALESCE($3, provider_ref), updated_at = now()
+            WHERE id = $1
+            "#,
+            id,
+            status.as_str(),
+            provider_ref
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "payment_attempts", db.query.text = Empty))]
+    async fn record_attempt(&self, intent_id: i64, attempt: PaymentAttempt) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query!(
+            r#"
+            INSERT INTO payment_attempts (intent_id, operation, succeeded, detail)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            intent_id,
+            attempt.operation.as_str(),
+            attempt.succeeded,
+            attempt.detail
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "payment_attempts", db.query.text = Empty))]
+    async fn attempts(&self, intent_id: i64) -> Result<Vec<PaymentAttempt>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = pg_query_as!(
+            AttemptRow,
+            r#"
+            SELECT operation, succeeded, detail, created_at
+            FROM payment_attempts
+            WHERE intent_id = $1
+            ORDER BY id
+            "#,
+            intent_id
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(PaymentAttempt {
+                    operation: parse_operation(&row.operation)?,
+                    succeeded: row.succeeded,
+                    detail: row.detail,
+                    created_at: row.created_at,
+                })
+            })
+            .collect()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "payment_webhook_events", db.query.text = Empty))]
+    async fn record_webhook_event(&self, provider: &str, event_id: &str) -> Result<bool, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = pg_query!(
+            r#"
+            INSERT INTO payment_webhook_events (provider, event_id)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+            "#,
+            provider,
+            event_id
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(result.rows_affected() == 1)
+    }
+}
@@ -1,30 +1,48 @@
 use async_trait::async_trait;
-use sqlx::PgPool;
-use tracing::instrument;
+use sqlx::{PgPool, Postgres};
+use tracing::{field::Empty, instrument};
 
+use crate::domain::page::Page;
 use crate::domain::product::{Product, ProductRepository};
 use crate::domain::DomainError;
+use crate::infra::db_trace::{pg_query, pg_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
 
 #[derive(Clone)]
 pub struct PostgresProductRepository {
-    pool: PgPool,
+    db: DbHandle<Postgres>,
 }
 
 impl PostgresProductRepository {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Postgres>) -> Self {
+        Self { db }
     }
 }
 
 #[async_trait]
 impl ProductRepository for PostgresProductRepository {
-    #[instrument(skip(self), err, fields(db = "postgres"))]
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "products", db.query.text = Empty))]
     async fn create(&self, product: Product) -> Result<i64, DomainError> {
-        let row = sqlx::query!(
+        let mut conn = self.db.acquire().await?;
+        // starting stock goes to the default warehouse
+        let row = pg_query!(
             r#"
-            INSERT INTO products (name, description, price, stock, category_id, active)
-            VALUES ($1, $2, $3::float8, $4, $5, $6)
-            RETURNING id
+            WITH created AS (
+                INSERT INTO products (name, description, price, stock, category_id, active)
+                VALUES ($1, $2, $3::float8, $4, $5, $6)
+                RETURNING id, stock
+            ), located AS (
+                INSERT INTO warehouse_stock (warehouse_id, product_id, stock)
+                SELECT w.id, c.id, c.stock
+                FROM created c, (SELECT id FROM warehouses ORDER BY priority, id LIMIT 1) w
+                WHERE c.stock > 0
+            )
+            SELECT id AS "id!" FROM created
             "#,
             product.name,
             product.description,
@@ -33,16 +51,18 @@ impl ProductRepository for PostgresProductRepository {
             product.category_id,
             product.active
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *conn)
+        .traced()
         .await
-        .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        .map_err(db_error)?;
 
         Ok(row.id)
     }
 
-    #[instrument(skip(self), err, fields(db = "postgres"))]
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "products", db.query.text = Empty))]
     async fn get_by_product_id(&self, id: i64) -> Result<Option<Product>, DomainError> {
-        let row = sqlx::query_as!(
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query_as!(
             Product,
             r#"
             SELECT id, name, description, price::float8 as "price!", stock, category_id, active
@@ -51,43 +71,56 @@ impl ProductRepository for PostgresProductRepository {
             "#,
             id
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *conn)
+        .traced()
         .await
-        .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        .map_err(db_error)?;
 
         Ok(row)
     }
 
-    #[instrument(skip(self), err, fields(db = "postgres"))]
-    async fn get_by_category_id(&self, category_id: i64) -> Result<Vec<Product>, DomainError> {
-        let rows = sqlx::query_as!(
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "products", db.query.text = Empty))]
+    async fn get_by_category_id(&self, category_id: i64, page: Page) -> Result<Vec<Product>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = pg_query_as!(
             Product,
             r#"
             SELECT id, name, description, price::float8 as "price!", stock, category_id, active
             FROM products
             WHERE category_id = $1
+            ORDER BY id
+            LIMIT $2 OFFSET $3
             "#,
-            category_id
+            category_id,
+            page.limit,
+            page.offset
         )
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *conn)
+        .traced()
         .await
-        .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        .map_err(db_error)?;
 
         Ok(rows)
     }
 
-    #[instrument(skip(self), err, fields(db = "postgres"))]
-    async fn get_all_products(&self) -> Result<Vec<Product>, DomainError> {
-        let rows = sqlx::query_as!(
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "products", db.query.text = Empty))]
+    async fn get_all_products(&self, page: Page) -> Result<Vec<Product>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = pg_query_as!(
             Product,
             r#"
             SELECT id, name, description, price::float8 as "price!", stock, category_id, active
             FROM products
-            "#
+            ORDER BY id
+            LIMIT $1 OFFSET $2
+            "#,
+            page.limit,
+            page.offset
         )
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *conn)
+        .traced()
         .await
-        .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        .map_err(db_error)?;
 
         Ok(rows)
     }
@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::promotion::{Promotion, PromotionKind, PromotionRepository, Redemption};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{pg_query, pg_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+
+/// A kind as stored in `promotions.kind`.
+pub(crate) fn parse_kind(kind: &str) -> Result<PromotionKind, DomainError> {
+    kind.parse().map_err(DomainError::Unexpected)
+}
+
+struct PromotionRow {
+    id: i64,
+    name: String,
+    code: Option<String>,
+    kind: String,
+    value: f64,
+    buy_quantity: Option<i32>,
+    get_quantity: Option<i32>,
+    category_id: Option<i64>,
+    product_id: Option<i64>,
+    starts_at: Option<DateTime<Utc>>,
+    ends_at: Option<DateTime<Utc>>,
+    max_uses: Option<i32>,
+    max_uses_per_user: Option<i32>,
+    active: bool,
+}
+
+impl TryFrom<PromotionRow> for Promotion {
+    type Error = DomainError;
+
+    fn try_from(row: PromotionRow) -> Result<Self, Self::Error> {
+        Ok(Promotion {
+            id: row.id,
+            name: row.name,
+            code: row.code,
+            kind: parse_kind(&row.kind)?,
+            value: row.value,
+            buy_quantity: row.buy_quantity,
+            get_quantity: row.get_quantity,
+            category_id: row.category_id,
+            product_id: row.product_id,
+            starts_at: row.starts_at,
+            ends_at: row.ends_at,
+            max_uses: row.max_uses,
+            max_uses_per_user: row.max_uses_per_user,
+            active: row.active,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct PostgresPromotionRepository {
+    db: DbHandle<Postgres>,
+}
+
+impl PostgresPromotionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Postgres>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl PromotionRepository for PostgresPromotionRepository {
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "promotions", db.query.text = Empty))]
+    async fn create(&self, promotion: Promotion) -> Result<i64, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query!(
+            r#"
+            INSERT INTO promotions (name, code, kind, value, buy_quantity, get_quantity, category_id, product_id,
+                                    starts_at, ends_at, max_uses, max_uses_per_user, active)
+            VALUES ($1, $2, $3, $4::float8, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            RETURNING id
+            "#,
+            promotion.name,
+            promotion.code,
+            promotion.kind.as_str(),
+            promotion.value,
+            promotion.buy_quantity,
+            promotion.get_quantity,
+            promotion.category_id,
+            promotion.product_id,
+            promotion.starts_at,
+            promotion.ends_at,
+            promotion.max_uses,
+            promotion.max_uses_per_user,
+            promotion.active
+        )
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.id)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "promotions", db.query.text = Empty))]
+    async fn get(&self, id: i64) -> Result<Option<Promotion>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query_as!(
+            PromotionRow,
+            r#"
+            SELECT id, name, code, kind, value::float8 as "value!", buy_quantity, get_quantity, category_id, product_id,
+                   starts_at, ends_at, max_uses, max_uses_per_user, active
+            FROM promotions
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        .map(Promotion::try_from)
+        .transpose()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "promotions", db.query.text = Empty))]
+    async fn by_code(&self, code: &str) -> Result<Option<Promotion>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query_as!(
+            PromotionRow,
+            r#"
+            SELECT id, name, code, kind, value::float8 as "value!", buy_quantity, get_quantity, category_id, product_id,
+                   starts_at, ends_at, max_uses, max_uses_per_user, active
+            FROM promotions
+            WHERE code = $1
+            "#,
+            code
+        )
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        .map(Promotion::try_from)
+        .transpose()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "promotions", db.query.text = Empty))]
+    async fn list(&self) -> Result<Vec<Promotion>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query_as!(
+            PromotionRow,
+            r#"
+            SELECT id, name, code, kind, value::float8 as "value!", buy_quantity, get_quantity, category_id, product_id,
+                   starts_at, ends_at, max_uses, max_uses_per_user, active
+            FROM promotions
+            ORDER BY id
+            "#
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(Promotion::try_from)
+        .collect()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "UPDATE", db.collection.name = "promotions", db.query.text = Empty))]
+    async fn update(&self, promotion: Promotion) -> Result<bool, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = pg_query!(
+            r#"
+            UPDATE promotions
+            SET name = $2, code = $3, kind = $4, value = $5::float8, buy_quantity = $6, get_quantity = $7,
+                category_id = $8, product_id = $9, starts_at = $10, ends_at = $11, max_uses = $12,
+                max_uses_per_user = $13, active = $14
+            WHERE id = $1
+            "#,
+            promotion.id,
+            promotion.name,
+            promotion.code,
+            promotion.kind.as_str(),
+            promotion.value,
+            promotion.buy_quantity,
+            promotion.get_quantity,
+            promotion.category_id,
+            promotion.product_id,
+            promotion.starts_at,
+            promotion.ends_at,
+            promotion.max_uses,
+            promotion.max_uses_per_user,
+            promotion.active
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "DELETE", db.collection.name = "promotions", db.query.text = Empty))]
+    async fn delete(&self, id: i64) -> Result<bool, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = pg_query!("DELETE FROM promotions WHERE id = $1", id)
+            .execute(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "promotion_redemptions", db.query.text = Empty))]
+    async fn redeem(&self, redemption: Redemption) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query!(
+            r#"
+            INSERT INTO promotion_redemptions (promotion_id, user_id, order_id, discount)
+            VALUES ($1, $2, $3, $4::float8)
+            "#,
+            redemption.promotion_id,
+            redemption.user_id,
+            redemption.order_id,
+            redemption.discount
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "promotion_redemptions", db.query.text = Empty))]
+    async fn uses(&self, promotion_id: i64, user_id: Option<i64>) -> Result<i64, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query!(
+            r#"
+            SELECT COUNT(*) as "count!"
+            FROM promotion_redemptions
+            WHERE promotion_id = $1 AND ($2::bigint IS NULL OR user_id = $2)
+            "#,
+            promotion_id,
+            user_id
+        )
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.count)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "promotion_redemptions", db.query.text = Empty))]
+    async fn uses_by_promotion(&self, user_id: Option<i64>) -> Result<HashMap<i64, i64>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = pg_query!(
+            r#"
+            SELECT promotion_id, COUNT(*) as "count!"
+            FROM promotion_redemptions
+            WHERE $1::bigint IS NULL OR user_id = $1
+            GROUP BY promotion_id
+            "#,
+            user_id
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(rows.into_iter().map(|row| (row.promotion_id, row.count)).collect())
+    }
+}
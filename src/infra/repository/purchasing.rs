@@ -0,0 +1,284 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool, Postgres};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::purchasing::{PurchaseOrder, PurchaseOrderLine, PurchaseOrderStatus, PurchasingRepository, Supplier, SupplierProduct};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{pg_query, pg_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+
+/// A status as stored in `purchase_orders.status`.
+pub(crate) fn parse_status(status: &str) -> Result<PurchaseOrderStatus, DomainError> {
+    status.parse().map_err(DomainError::Unexpected)
+}
+
+struct PurchaseOrderRow {
+    id: i64,
+    supplier_id: i64,
+    status: String,
+    created_by: Option<i64>,
+    created_at: DateTime<Utc>,
+}
+
+struct PurchaseOrderLineRow {
+    purchase_order_id: i64,
+    product_id: i64,
+    quantity: i32,
+    received: i32,
+    unit_cost: f64,
+}
+
+#[derive(Clone)]
+pub struct PostgresPurchasingRepository {
+    db: DbHandle<Postgres>,
+}
+
+impl PostgresPurchasingRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Postgres>) -> Self {
+        Self { db }
+    }
+}
+
+/// The orders of `rows` with their lines, in the order of `rows`.
+async fn with_lines(conn: &mut PgConnection, rows: Vec<PurchaseOrderRow>) -> Result<Vec<PurchaseOrder>, DomainError> {
+    let ids: Vec<i64> = rows.iter().map(|o| o.id).collect();
+    let lines = pg_query_as!(
+        PurchaseOrderLineRow,
+        r#"
+        SELECT purchase_order_id, product_id, quantity, received, unit_cost::float8 as "unit_cost!"
+        FROM purchase_order_lines
+        WHERE purchase_order_id = ANY($1)
+        ORDER BY product_id
+        "#,
+        &ids
+    )
+    .fetch_all(&mut *conn)
+    .traced()
+    .await
+    .map_err(db_error)?;
+
+    rows.into_iter()
+        .map(|o| {
+            Ok(PurchaseOrder {
+                id: o.id,
+                supplier_id: o.supplier_id,
+                status: parse_status(&o.status)?,
+                lines: lines
+                    .iter()
+                    .filter(|l| l.purchase_order_id == o.id)
+                    .map(|l| PurchaseOrderLine {
+                        product_id: l.product_id,
+                        quantity: l.quantity,
+                        received: l.received,
+                        unit_cost: l.unit_cost,
+                    })
+                    .collect(),
+                created_by: o.created_by,
+                created_at: o.created_at,
+            })
+        })
+        .collect()
+}
+
+#[async_trait]
+impl PurchasingRepository for PostgresPurchasingRepository {
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "suppliers", db.query.text = Empty))]
+    async fn create_supplier(&self, supplier: Supplier) -> Result<i64, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query!(
+            r#"
+            INSERT INTO suppliers (name, email)
+            VALUES ($1, $2)
+            RETURNING id
+            "#,
+            supplier.name,
+            supplier.email
+        )
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.id)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "suppliers", db.query.text = Empty))]
+    async fn supplier(&self, id: i64) -> Result<Option<Supplier>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query_as!(Supplier, "SELECT id, name, email FROM suppliers WHERE id = $1", id)
+            .fetch_optional(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "suppliers", db.query.text = Empty))]
+    async fn suppliers(&self) -> Result<Vec<Supplier>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query_as!(Supplier, "SELECT id, name, email FROM suppliers ORDER BY id")
+            .fetch_all(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "UPSERT", db.collection.name = "supplier_products", db.query.text = Empty))]
+    async fn set_supplier_product(&self, link: SupplierProduct) -> Result<SupplierProduct, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query_as!(
+            SupplierProduct,
+            r#"
+            INSERT INTO supplier_products (supplier_id, product_id, sku, cost_price)
+            VALUES ($1, $2, $3, $4::float8)
+            ON CONFLICT (supplier_id, product_id) DO UPDATE SET sku = EXCLUDED.sku, cost_price = EXCLUDED.cost_price
+            RETURNING supplier_id, product_id, sku, cost_price::float8 as "cost_price!"
+            "#,
+            link.supplier_id,
+            link.product_id,
+            link.sku,
+            link.cost_price
+        )
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "supplier_products", db.query.text = Empty))]
+    async fn supplier_products(&self, supplier_id: i64) -> Result<Vec<SupplierProduct>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query_as!(
+            SupplierProduct,
+            r#"
+            SELECT supplier_id, product_id, sku, cost_price::float8 as "cost_price!"
+            FROM supplier_products
+            WHERE supplier_id = $1
+            ORDER BY product_id
+            "#,
+            supplier_id
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "purchase_orders", db.query.text = Empty))]
+    async fn create_order(&self, order: PurchaseOrder) -> Result<i64, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query!(
+            r#"
+            INSERT INTO purchase_orders (supplier_id, status, created_by)
+            VALUES ($1, $2, $3)
+            RETURNING id
+            "#,
+            order.supplier_id,
+            order.status.as_str(),
+            order.created_by
+        )
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        for line in order.lines {
+            pg_query!(
+                r#"
+                INSERT INTO purchase_order_lines (purchase_order_id, product_id, quantity, received, unit_cost)
+                VALUES ($1, $2, $3, $4, $5::float8)
+                "#,
+                row.id,
+                line.product_id,
+                line.quantity,
+                line.received,
+                line.unit_cost
+            )
+            .execute(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)?;
+        }
+
+        Ok(row.id)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "purchase_orders", db.query.text = Empty))]
+    async fn order(&self, id: i64) -> Result<Option<PurchaseOrder>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let Some(row) = pg_query_as!(
+            PurchaseOrderRow,
+            r#"
+            SELECT id, supplier_id, status, created_by, created_at
+            FROM purchase_orders
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        else {
+            return Ok(None);
+        };
+
+        Ok(with_lines(&mut conn, vec![row]).await?.pop())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "purchase_orders", db.query.text = Empty))]
+    async fn orders(&self) -> Result<Vec<PurchaseOrder>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = pg_query_as!(
+            PurchaseOrderRow,
+            r#"
+            SELECT id, supplier_id, status, created_by, created_at
+            FROM purchase_orders
+            ORDER BY id DESC
+            "#
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        with_lines(&mut conn, rows).await
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "UPDATE", db.collection.name = "purchase_orders", db.query.text = Empty))]
+    async fn set_status(&self, id: i64, status: PurchaseOrderStatus) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query!("UPDATE purchase_orders SET status = $2 WHERE id = $1", id, status.as_str())
+            .execute(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "UPDATE", db.collection.name = "purchase_order_lines", db.query.text = Empty))]
+    async fn receive(&self, id: i64, product_id: i64, quantity: i32) -> Result<bool, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = pg_query!(
+            r#"
+            UPDATE purchase_order_lines
+            SET received = received + $3
+            WHERE purchase_order_id = $1 AND product_id = $2 AND received + $3 <= quantity
+            "#,
+            id,
+            product_id,
+            quantity
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::reservation::{Reservation, ReservationRepository};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{pg_query, pg_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+
+#[derive(Clone)]
+pub struct PostgresReservationRepository {
+    db: DbHandle<Postgres>,
+}
+
+impl PostgresReservationRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Postgres>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl ReservationRepository for PostgresReservationRepository {
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "stock_reservations", db.query.text = Empty))]
+    async fn hold(
+        &self,
+        cart_id: i64,
+        product_id: i64,
+        quantity: i32,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Reservation, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let reservation = pg_query_as!(
+            Reservation,
+            r#"
+            INSERT INTO stock_reservations (cart_id, product_id, quantity, expires_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (cart_id, product_id)
+            DO UPDATE SET quantity = EXCLUDED.quantity, expires_at = EXCLUDED.expires_at
+            RETURNING id, cart_id, product_id, quantity, expires_at
+            "#,
+            cart_id,
+            product_id,
+            quantity,
+            expires_at
+        )
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(reservation)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "DELETE", db.collection.name = "stock_reservations", db.query.text = Empty))]
+    async fn release(&self, cart_id: i64, product_id: i64) -> Result<bool, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = pg_query!(
+            r#"
+            DELETE FROM stock_reservations
+            WHERE cart_id = $1 AND product_id = $2
+            "#,
+            cart_id,
+            product_id
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "stock_reservations", db.query.text = Empty))]
+    async fn reserved(
+        &self,
+        product_ids: &[i64],
+        except_cart: Option<i64>,
+        now: DateTime<Utc>,
+    ) -> Result<HashMap<i64, i32>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = pg_query!(
+            r#"
+            SELECT product_id, SUM(quantity)::int4 as "reserved!"
+            FROM stock_reservations
+            WHERE product_id = ANY($1) AND expires_at > $3 AND cart_id IS DISTINCT FROM $2
+            GROUP BY product_id
+            "#,
+            product_ids,
+            except_cart,
+            now
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(rows.into_iter().map(|row| (row.product_id, row.reserved)).collect())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "DELETE", db.collection.name = "stock_reservations", db.query.text = Empty))]
+    async fn release_expired(&self, now: DateTime<Utc>) -> Result<u64, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = pg_query!(
+            r#"
+            DELETE FROM stock_reservations
+            WHERE expires_at <= $1
+            "#,
+            now
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,115 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, Sqlite, SqlitePool};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::api_key::{ApiKey, ApiKeyRepository};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{sqlite_query, sqlite_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+
+#[derive(FromRow)]
+struct ApiKeyRow {
+    id: i64,
+    name: String,
+    prefix: String,
+    created_at: DateTime<Utc>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<ApiKeyRow> for ApiKey {
+    fn from(row: ApiKeyRow) -> Self {
+        ApiKey { id: row.id, name: row.name, prefix: row.prefix, created_at: row.created_at, revoked_at: row.revoked_at }
+    }
+}
+
+#[derive(Clone)]
+pub struct SqliteApiKeyRepository {
+    db: DbHandle<Sqlite>,
+}
+
+impl SqliteApiKeyRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for SqliteApiKeyRepository {
+    #[instrument(skip(self, key_hash), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "api_keys", db.query.text = Empty))]
+    async fn create(&self, name: String, prefix: String, key_hash: String) -> Result<i64, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let id: (i64,) = sqlite_query_as!((i64,),
+            r#"
+            INSERT INTO api_keys (name, prefix, key_hash)
+            VALUES (?1, ?2, ?3)
+            RETURNING id
+            "#
+        )
+        .bind(name)
+        .bind(prefix)
+        .bind(key_hash)
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(id.0)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "api_keys", db.query.text = Empty))]
+    async fn get_all(&self) -> Result<Vec<ApiKey>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = sqlite_query_as!(ApiKeyRow,
+            r#"
+            SELECT id, name, prefix, created_at, revoked_at
+            FROM api_keys
+            ORDER BY id
+            "#
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(rows.into_iter().map(ApiKey::from).collect())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "api_keys", db.query.text = Empty))]
+    async fn active_hash(&self, prefix: &str) -> Result<Option<String>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row: Option<(String,)> = sqlite_query_as!((String,),
+            r#"
+            SELECT key_hash
+            FROM api_keys
+            WHERE prefix = ?1 AND revoked_at IS NULL
+            "#
+        )
+        .bind(prefix)
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.map(|r| r.0))
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "UPDATE", db.collection.name = "api_keys", db.query.text = Empty))]
+    async fn revoke(&self, id: i64) -> Result<bool, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = sqlite_query!(
+            r#"
+            UPDATE api_keys
+            SET revoked_at = CURRENT_TIMESTAMP
+            WHERE id = ?1 AND revoked_at IS NULL
+            "#
+        )
+        .bind(id)
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,248 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, Sqlite, SqlitePool};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::cart::{Cart, CartLine, CartOwner, CartRepository};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{sqlite_query, sqlite_query_as, DbTraceExt};
+use crate::infra::repository::cart::{owner_columns, owner_from_columns};
+use crate::infra::repository::db::{db_error, DbHandle};
+use crate::infra::repository::numeric::{cents_to_f64, to_numeric_10_2_cents};
+
+#[derive(FromRow)]
+struct CartRow {
+    id: i64,
+    user_id: Option<i64>,
+    guest_token: Option<String>,
+    coupon_code: Option<String>,
+    updated_at: i64,
+}
+
+#[derive(FromRow)]
+struct CartLineRow {
+    product_id: i64,
+    quantity: i32,
+    unit_price_cents: i64,
+}
+
+impl From<CartLineRow> for CartLine {
+    fn from(row: CartLineRow) -> Self {
+        CartLine { product_id: row.product_id, quantity: row.quantity, unit_price: cents_to_f64(row.unit_price_cents) }
+    }
+}
+
+/// Same as the Postgres repository; `updated_at` is Unix milliseconds from
+/// the application clock.
+#[derive(Clone)]
+pub struct SqliteCartRepository {
+    db: DbHandle<Sqlite>,
+}
+
+impl SqliteCartRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Sqlite>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl CartRepository for SqliteCartRepository {
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "carts", db.query.text = Empty))]
+    async fn find(&self, owner: &CartOwner) -> Result<Option<Cart>, DomainError> {
+        let (user_id, guest_token) = owner_columns(owner);
+        let mut conn = self.db.acquire().await?;
+        let Some(cart) = sqlite_query_as!(CartRow,
+            r#"
+            SELECT id, user_id, guest_token, coupon_code, updated_at
+            FROM carts
+            WHERE user_id = ?1 OR guest_token = ?2
+            "#
+        )
+        .bind(user_id)
+        .bind(guest_token)
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        else {
+            return Ok(None);
+        };
+
+        let lines = sqlite_query_as!(CartLineRow,
+            r#"
+            SELECT product_id, quantity, unit_price_cents
+            FROM cart_items
+            WHERE cart_id = ?1
+            ORDER BY id
+            "#
+        )
+        .bind(cart.id)
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(Some(Cart {
+            id: cart.id,
+            owner: owner_from_columns(cart.user_id, cart.guest_token)?,
+            lines: lines.into_iter().map(CartLine::from).collect(),
+            coupon: cart.coupon_code,
+            updated_at: DateTime::from_timestamp_millis(cart.updated_at)
+                .ok_or_else(|| DomainError::Unexpected(format!("invalid updated_at {}", cart.updated_at)))?,
+        }))
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "carts", db.query.text = Empty))]
+    async fn create(&self, owner: &CartOwner) -> Result<i64, DomainError> {
+        let (user_id, guest_token) = owner_columns(owner);
+        let mut conn = self.db.acquire().await?;
+        let id: (i64,) = sqlite_query_as!((i64,),
+            r#"
+            INSERT INTO carts (user_id, guest_token, updated_at)
+            VALUES (?1, ?2, ?3)
+            RETURNING id
+            "#
+        )
+        .bind(user_id)
+        .bind(guest_token)
+        .bind(Utc::now().timestamp_millis())
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(id.0)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "cart_items", db.query.text = Empty))]
+    async fn set_line(&self, cart_id: i64, line: CartLine) -> Result<(), DomainError> {
+        let unit_price_cents = to_numeric_10_2_cents(line.unit_price)?;
+
+        let mut conn = self.db.acquire().await?;
+        sqlite_query!(
+            r#"
+            INSERT INTO cart_items (cart_id, product_id, quantity, unit_price_cents)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT (cart_id, product_id)
+            DO UPDATE SET quantity = excluded.quantity, unit_price_cents = excluded.unit_price_cents
+            "#
+        )
+        .bind(cart_id)
+        .bind(line.product_id)
+        .bind(line.quantity)
+        .bind(unit_price_cents)
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        sqlite_query!(
+            r#"
+            UPDATE carts
+            SET updated_at = ?2
+            WHERE id = ?1
+            "#
+        )
+        .bind(cart_id)
+        .bind(Utc::now().timestamp_millis())
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "DELETE", db.collection.name = "cart_items", db.query.text = Empty))]
+    async fn remove_line(&self, cart_id: i64, product_id: i64) -> Result<bool, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = sqlite_query!(
+            r#"
+            DELETE FROM cart_items
+            WHERE cart_id = ?1 AND product_id = ?2
+            "#
+        )
+        .bind(cart_id)
+        .bind(product_id)
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlite_query!(
+            r#"
+            UPDATE carts
+            SET updated_at = ?2
+            WHERE id = ?1
+            "#
+        )
+        .bind(cart_id)
+        .bind(Utc::now().timestamp_millis())
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(true)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "UPDATE", db.collection.name = "carts", db.query.text = Empty))]
+    async fn set_coupon(&self, cart_id: i64, coupon: Option<String>) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query!(
+            r#"
+            UPDATE carts
+            SET coupon_code = ?2, updated_at = ?3
+            WHERE id = ?1
+            "#
+        )
+        .bind(cart_id)
+        .bind(coupon)
+        .bind(Utc::now().timestamp_millis())
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "DELETE", db.collection.name = "carts", db.query.text = Empty))]
+    async fn delete(&self, cart_id: i64) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query!(
+            r#"
+            DELETE FROM carts
+            WHERE id = ?1
+            "#
+        )
+        .bind(cart_id)
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "DELETE", db.collection.name = "carts", db.query.text = Empty))]
+    async fn delete_idle(&self, before: DateTime<Utc>) -> Result<u64, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = sqlite_query!(
+            r#"
+            DELETE FROM carts
+            WHERE updated_at < ?1
+            "#
+        )
+        .bind(before.timestamp_millis())
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(result.rows_affected())
+    }
+}
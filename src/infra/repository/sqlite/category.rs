@@ -0,0 +1,139 @@
+use async_trait::async_trait;
+use sqlx::{FromRow, Sqlite, SqlitePool};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::page::Page;
+use crate::domain::category::{Category, CategoryRepository};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{sqlite_query, sqlite_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+
+#[derive(FromRow)]
+struct CategoryRow {
+    id: i64,
+    name: String,
+    active: bool,
+}
+
+impl From<CategoryRow> for Category {
+    fn from(row: CategoryRow) -> Self {
+        Category { id: row.id, name: row.name, active: row.active }
+    }
+}
+
+#[derive(Clone)]
+pub struct SqliteCategoryRepository {
+    db: DbHandle<Sqlite>,
+}
+
+impl SqliteCategoryRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Sqlite>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl CategoryRepository for SqliteCategoryRepository {
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "categories", db.query.text = Empty))]
+    async fn create(&self, name: String) -> Result<i64, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let id: (i64,) = sqlite_query_as!((i64,),
+            r#"
+            INSERT INTO categories (name)
+            VALUES (?1)
+            RETURNING id
+            "#
+        )
+        .bind(name)
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(id.0)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "categories", db.query.text = Empty))]
+    async fn get_by_id(&self, id: i64) -> Result<Option<Category>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = sqlite_query_as!(CategoryRow,
+            r#"
+            SELECT id, name, active
+            FROM categories
+            WHERE id = ?1
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.map(Category::from))
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "categories", db.query.text = Empty))]
+    async fn get_all_categories(&self, page: Page) -> Result<Vec<Category>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = sqlite_query_as!(CategoryRow,
+            r#"
+            SELECT id, name, active
+            FROM categories
+            ORDER BY id
+            LIMIT ?1 OFFSET ?2
+            "#
+        )
+        .bind(page.limit.unwrap_or(-1))
+        .bind(page.offset)
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(rows.into_iter().map(Category::from).collect())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "UPDATE", db.collection.name = "categories", db.query.text = Empty))]
+    async fn update(&self, id: i64, name: String) -> Result<Category, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = sqlite_query_as!(CategoryRow,
+            r#"
+            UPDATE categories
+            SET name = ?1
+            WHERE id = ?2
+            RETURNING id, name, active
+            "#
+        )
+        .bind(name)
+        .bind(id)
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.into())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "DELETE", db.collection.name = "categories", db.query.text = Empty))]
+    async fn delete(&self, id: i64) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query!(
+            r#"
+            DELETE FROM categories
+            WHERE id = ?1
+            "#
+        )
+        .bind(id)
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,332 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{FromRow, Sqlite, SqlitePool};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::inventory::{InventoryRepository, LowStockProduct, StockLevel, StockMovement};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{sqlite_query, sqlite_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+use crate::infra::repository::inventory::parse_kind;
+use crate::infra::repository::sqlite::order::from_millis;
+
+#[derive(FromRow)]
+struct MovementRow {
+    id: i64,
+    product_id: i64,
+    warehouse_id: i64,
+    kind: String,
+    quantity: i32,
+    reason: Option<String>,
+    actor_id: Option<i64>,
+    order_id: Option<i64>,
+    created_at: i64,
+}
+
+impl TryFrom<MovementRow> for StockMovement {
+    type Error = DomainError;
+
+    fn try_from(row: MovementRow) -> Result<Self, Self::Error> {
+        Ok(StockMovement {
+            id: row.id,
+            product_id: row.product_id,
+            warehouse_id: row.warehouse_id,
+            kind: parse_kind(&row.kind)?,
+            quantity: row.quantity,
+            reason: row.reason,
+            actor_id: row.actor_id,
+            order_id: row.order_id,
+            created_at: from_millis(row.created_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct StockLevelRow {
+    product_id: i64,
+    name: String,
+    stock: i32,
+    ledger: i64,
+}
+
+#[derive(FromRow)]
+struct LowStockRow {
+    product_id: i64,
+    name: String,
+    category_id: i64,
+    category: String,
+    stock: i32,
+    threshold: i32,
+}
+
+/// Same as the Postgres repository; timestamps are Unix milliseconds from
+/// the application clock.
+#[derive(Clone)]
+pub struct SqliteInventoryRepository {
+    db: DbHandle<Sqlite>,
+}
+
+impl SqliteInventoryRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Sqlite>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl InventoryRepository for SqliteInventoryRepository {
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "stock_movements", db.query.text = Empty))]
+    async fn record(&self, movement: StockMovement) -> Result<Option<StockMovement>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        // the product's first movement in the warehouse starts from zero
+        sqlite_query!(
+            r#"
+            INSERT INTO warehouse_stock (warehouse_id, product_id, stock)
+            SELECT ?2, id, 0 FROM products WHERE id = ?1
+            ON CONFLICT DO NOTHING
+            "#
+        )
+        .bind(movement.product_id)
+        .bind(movement.warehouse_id)
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        // the ledger line first: when it is refused (no such product, too
+        // little stock in the warehouse, a foreign key) the stock is left
+        // alone
+        let Some(row) = sqlite_query_as!(MovementRow,
+            r#"
+            INSERT INTO stock_movements (product_id, warehouse_id, kind, quantity, reason, actor_id, order_id, created_at)
+            SELECT product_id, warehouse_id, ?3, ?4, ?5, ?6, ?7, ?8 FROM warehouse_stock
+            WHERE product_id = ?1 AND warehouse_id = ?2 AND stock + ?4 >= 0
+            RETURNING id, product_id, warehouse_id, kind, quantity, reason, actor_id, order_id, created_at
+            "#
+        )
+        .bind(movement.product_id)
+        .bind(movement.warehouse_id)
+        .bind(movement.kind.as_str())
+        .bind(movement.quantity)
+        .bind(movement.reason)
+        .bind(movement.actor_id)
+        .bind(movement.order_id)
+        .bind(Utc::now().timestamp_millis())
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        else {
+            return Ok(None);
+        };
+
+        sqlite_query!(
+            r#"
+            UPDATE warehouse_stock
+            SET stock = stock + ?3
+            WHERE product_id = ?1 AND warehouse_id = ?2
+            "#
+        )
+        .bind(movement.product_id)
+        .bind(movement.warehouse_id)
+        .bind(movement.quantity)
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        sqlite_query!(
+            r#"
+            UPDATE products
+            SET stock = stock + ?2
+            WHERE id = ?1
+            "#
+        )
+        .bind(movement.product_id)
+        .bind(movement.quantity)
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        StockMovement::try_from(row).map(Some)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "stock_movements", db.query.text = Empty))]
+    async fn movements(&self, product_id: i64) -> Result<Vec<StockMovement>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query_as!(MovementRow,
+            r#"
+            SELECT id, product_id, warehouse_id, kind, quantity, reason, actor_id, order_id, created_at
+            FROM stock_movements
+            WHERE product_id = ?1
+            ORDER BY id
+            "#
+        )
+        .bind(product_id)
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(StockMovement::try_from)
+        .collect()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "stock_movements", db.query.text = Empty))]
+    async fn order_movements(&self, order_id: i64) -> Result<Vec<StockMovement>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query_as!(MovementRow,
+            r#"
+            SELECT id, product_id, warehouse_id, kind, quantity, reason, actor_id, order_id, created_at
+            FROM stock_movements
+            WHERE order_id = ?1
+            ORDER BY id
+            "#
+        )
+        .bind(order_id)
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(StockMovement::try_from)
+        .collect()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "stock_movements", db.query.text = Empty))]
+    async fn stock_levels(&self) -> Result<Vec<StockLevel>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = sqlite_query_as!(StockLevelRow,
+            r#"
+            SELECT p.id AS product_id, p.name, p.stock, COALESCE(SUM(m.quantity), 0) AS ledger
+            FROM products p
+            LEFT JOIN stock_movements m ON m.product_id = p.id
+            GROUP BY p.id
+            ORDER BY p.id
+            "#
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| StockLevel { product_id: row.product_id, name: row.name, stock: row.stock, ledger: row.ledger })
+            .collect())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "UPSERT", db.collection.name = "stock_thresholds", db.query.text = Empty))]
+    async fn set_threshold(&self, product_id: i64, threshold: Option<i32>) -> Result<bool, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let exists = sqlite_query_as!((i64,), "SELECT id FROM products WHERE id = ?1")
+            .bind(product_id)
+            .fetch_optional(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)?
+            .is_some();
+        if !exists {
+            return Ok(false);
+        }
+        match threshold {
+            Some(threshold) => sqlite_query!(
+                r#"
+                INSERT INTO stock_thresholds (product_id, threshold)
+                VALUES (?1, ?2)
+                ON CONFLICT (product_id) DO UPDATE SET threshold = excluded.threshold
+                "#
+            )
+            .bind(product_id)
+            .bind(threshold)
+            .execute(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)?,
+            None => sqlite_query!("DELETE FROM stock_thresholds WHERE product_id = ?1")
+                .bind(product_id)
+                .execute(&mut *conn)
+                .traced()
+                .await
+                .map_err(db_error)?,
+        };
+        Ok(true)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "stock_thresholds", db.query.text = Empty))]
+    async fn threshold(&self, product_id: i64) -> Result<Option<i32>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row: Option<(i32,)> = sqlite_query_as!((i32,), "SELECT threshold FROM stock_thresholds WHERE product_id = ?1")
+            .bind(product_id)
+            .fetch_optional(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)?;
+        Ok(row.map(|r| r.0))
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "stock_thresholds", db.query.text = Empty))]
+    async fn low_stock(&self) -> Result<Vec<LowStockProduct>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = sqlite_query_as!(LowStockRow,
+            r#"
+            SELECT p.id AS product_id, p.name, p.category_id, c.name AS category, p.stock, t.threshold
+            FROM stock_thresholds t
+            JOIN products p ON p.id = t.product_id
+            JOIN categories c ON c.id = p.category_id
+            WHERE p.stock < t.threshold
+            ORDER BY c.name, c.id, p.id
+            "#
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| LowStockProduct {
+                product_id: row.product_id,
+                name: row.name,
+                category_id: row.category_id,
+                category: row.category,
+                stock: row.stock,
+                threshold: row.threshold,
+            })
+            .collect())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "product_costs", db.query.text = Empty))]
+    async fn average_cost(&self, product_id: i64) -> Result<Option<f64>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row: Option<(f64,)> = sqlite_query_as!((f64,), "SELECT average_cost FROM product_costs WHERE product_id = ?1")
+            .bind(product_id)
+            .fetch_optional(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)?;
+        Ok(row.map(|r| r.0))
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "UPSERT", db.collection.name = "product_costs", db.query.text = Empty))]
+    async fn set_average_cost(&self, product_id: i64, cost: f64) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query!(
+            r#"
+            INSERT INTO product_costs (product_id, average_cost)
+            VALUES (?1, ?2)
+            ON CONFLICT (product_id) DO UPDATE SET average_cost = excluded.average_cost
+            "#
+        )
+        .bind(product_id)
+        .bind(cost)
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+}
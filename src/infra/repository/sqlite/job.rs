@@ -0,0 +1,187 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, Sqlite, SqlitePool};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::job::{Job, JobQueue, NewJob};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{sqlite_query, sqlite_query_as, DbTraceExt};
+use crate::infra::propagation::current_traceparent;
+use crate::infra::repository::db::{db_error, DbHandle};
+
+#[derive(FromRow)]
+struct JobRow {
+    id: i64,
+    queue: String,
+    kind: String,
+    payload: String,
+    attempts: i32,
+    max_attempts: i32,
+    run_at: i64,
+    trace_context: Option<String>,
+}
+
+impl TryFrom<JobRow> for Job {
+    type Error = DomainError;
+
+    fn try_from(row: JobRow) -> Result<Self, Self::Error> {
+        Ok(Job {
+            id: row.id,
+            queue: row.queue,
+            kind: row.kind,
+            payload: serde_json::from_str(&row.payload).map_err(|e| DomainError::Unexpected(e.to_string()))?,
+            attempts: row.attempts,
+            max_attempts: row.max_attempts,
+            run_at: DateTime::from_timestamp_millis(row.run_at)
+                .ok_or_else(|| DomainError::Unexpected(format!("invalid run_at {}", row.run_at)))?,
+            trace_context: row.trace_context,
+        })
+    }
+}
+
+/// Same queue as the Postgres one. Times are Unix milliseconds from the
+/// application clock, since SQLite has no timestamp type.
+#[derive(Clone)]
+pub struct SqliteJobQueue {
+    db: DbHandle<Sqlite>,
+}
+
+impl SqliteJobQueue {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+}
+
+#[async_trait]
+impl JobQueue for SqliteJobQueue {
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "jobs", db.query.text = Empty))]
+    async fn enqueue(&self, job: NewJob) -> Result<Option<i64>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let id: Option<(i64,)> = sqlite_query_as!((i64,),
+            r#"
+            INSERT INTO jobs (queue, kind, payload, run_at, max_attempts, unique_key, trace_context)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            ON CONFLICT (unique_key) DO NOTHING
+            RETURNING id
+            "#
+        )
+        .bind(job.queue)
+        .bind(job.kind)
+        .bind(job.payload.to_string())
+        .bind(job.run_at.timestamp_millis())
+        .bind(job.max_attempts)
+        .bind(job.unique_key)
+        .bind(current_traceparent())
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(id.map(|id| id.0))
+    }
+
+    /// One statement, so it runs under SQLite's single write lock and two
+    /// workers never claim the same row.
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "UPDATE", db.collection.name = "jobs", db.query.text = Empty))]
+    async fn claim(&self, queue: &str, limit: i64, lease: Duration) -> Result<Vec<Job>, DomainError> {
+        let now = Utc::now().timestamp_millis();
+        let lease = i64::try_from(lease.as_millis()).unwrap_or(i64::MAX);
+
+        let mut conn = self.db.acquire().await?;
+        let rows = sqlite_query_as!(JobRow,
+            r#"
+            UPDATE jobs
+            SET status = 'running', attempts = attempts + 1, locked_until = ?3 + ?4
+            WHERE id IN (
+                SELECT id FROM jobs
+                WHERE queue = ?1
+                  AND ((status = 'scheduled' AND run_at <= ?3)
+                    OR (status = 'running' AND locked_until < ?3))
+                ORDER BY run_at, id
+                LIMIT ?2
+            )
+            RETURNING id, queue, kind, payload, attempts, max_attempts, run_at, trace_context
+            "#
+        )
+        .bind(queue)
+        .bind(limit)
+        .bind(now)
+        .bind(lease)
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        let mut jobs = rows.into_iter().map(Job::try_from).collect::<Result<Vec<_>, _>>()?;
+        jobs.sort_by_key(|j| (j.run_at, j.id));
+        Ok(jobs)
+    }
+
+    #[instrument(skip(self, job), err, fields(job.id = job.id, db.system = "sqlite", db.operation.name = "UPDATE", db.collection.name = "jobs", db.query.text = Empty))]
+    async fn complete(&self, job: &Job) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query!(
+            r#"
+            UPDATE jobs
+            SET status = 'completed', locked_until = NULL, last_error = NULL, finished_at = ?3
+            WHERE id = ?1 AND status = 'running' AND attempts = ?2
+            "#
+        )
+        .bind(job.id)
+        .bind(job.attempts)
+        .bind(Utc::now().timestamp_millis())
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self, job), err, fields(job.id = job.id, db.system = "sqlite", db.operation.name = "UPDATE", db.collection.name = "jobs", db.query.text = Empty))]
+    async fn fail(&self, job: &Job, error: String, retry_at: Option<DateTime<Utc>>) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query!(
+            r#"
+            UPDATE jobs
+            SET status = CASE WHEN ?4 IS NULL THEN 'dead' ELSE 'scheduled' END,
+                run_at = COALESCE(?4, run_at),
+                finished_at = CASE WHEN ?4 IS NULL THEN ?5 END,
+                locked_until = NULL,
+                last_error = ?3
+            WHERE id = ?1 AND status = 'running' AND attempts = ?2
+            "#
+        )
+        .bind(job.id)
+        .bind(job.attempts)
+        .bind(error)
+        .bind(retry_at.map(|t| t.timestamp_millis()))
+        .bind(Utc::now().timestamp_millis())
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "DELETE", db.collection.name = "jobs", db.query.text = Empty))]
+    async fn prune_finished(&self, before: DateTime<Utc>) -> Result<u64, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = sqlite_query!(
+            r#"
+            DELETE FROM jobs
+            WHERE finished_at < ?1
+            "#
+        )
+        .bind(before.timestamp_millis())
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected())
+    }
+}
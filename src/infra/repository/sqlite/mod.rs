@@ -0,0 +1,72 @@
+//! SQLite repositories for small deployments and demos
+//! (`DATABASE_URL=sqlite://app.db` or `sqlite::memory:`).
+//!
+//! Schema lives in `migrations/sqlite/` and mirrors the Postgres one:
+//! never-reused ids, `products.category_id` foreign key (enforced via
+//! `PRAGMA foreign_keys`), `NUMERIC(10, 2)` prices stored as integer cents.
+
+use std::str::FromStr;
+
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::SqlitePool;
+
+pub mod api_key;
+pub mod cart;
+pub mod category;
+pub mod inventory;
+pub mod job;
+pub mod notification;
+pub mod order;
+pub mod outbox;
+pub mod payment;
+pub mod product;
+pub mod promotion;
+pub mod purchasing;
+pub mod reservation;
+pub mod user;
+pub mod uow;
+pub mod warehouse;
+
+pub use api_key::SqliteApiKeyRepository;
+pub use cart::SqliteCartRepository;
+pub use category::SqliteCategoryRepository;
+pub use inventory::SqliteInventoryRepository;
+pub use job::SqliteJobQueue;
+pub use notification::SqliteNotificationRepository;
+pub use order::SqliteOrderRepository;
+pub use outbox::SqliteOutboxRepository;
+pub use payment::SqlitePaymentRepository;
+pub use product::SqliteProductRepository;
+pub use promotion::SqlitePromotionRepository;
+pub use purchasing::SqlitePurchasingRepository;
+pub use reservation::SqliteReservationRepository;
+pub use user::SqliteUserRepository;
+pub use uow::SqliteUnitOfWork;
+pub use warehouse::SqliteWarehouseRepository;
+
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
+
+/// Open (creating if missing) the database at `url` and apply migrations.
+pub async fn connect(url: &str, max_connections: u32) -> Result<SqlitePool, sqlx::Error> {
+    let pool = open(url, max_connections).await?;
+    MIGRATOR.run(&pool).await?;
+    Ok(pool)
+}
+
+/// Open (creating if missing) the database at `url`, without migrating.
+pub async fn open(url: &str, max_connections: u32) -> Result<SqlitePool, sqlx::Error> {
+    let options = SqliteConnectOptions::from_str(url)?
+        .create_if_missing(true)
+        .foreign_keys(true);
+    // an in-memory database is dropped with its last connection, so never
+    // let the pool reap them all
+    let pool = SqlitePoolOptions::new()
+        .max_connections(max_connections)
+        .min_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(options)
+        .await?;
+    Ok(pool)
+}
@@ -0,0 +1,118 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{FromRow, Sqlite, SqlitePool};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::notification::{Notification, NotificationRepository};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{sqlite_query, sqlite_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+use crate::infra::repository::sqlite::order::from_millis;
+
+#[derive(FromRow)]
+struct NotificationRow {
+    id: i64,
+    kind: String,
+    message: String,
+    product_id: Option<i64>,
+    created_at: i64,
+    read_at: Option<i64>,
+}
+
+impl TryFrom<NotificationRow> for Notification {
+    type Error = DomainError;
+
+    fn try_from(row: NotificationRow) -> Result<Self, Self::Error> {
+        Ok(Notification {
+            id: row.id,
+            kind: row.kind,
+            message: row.message,
+            product_id: row.product_id,
+            created_at: from_millis(row.created_at)?,
+            read_at: row.read_at.map(from_millis).transpose()?,
+        })
+    }
+}
+
+/// Same as the Postgres repository; timestamps are Unix milliseconds from
+/// the application clock.
+#[derive(Clone)]
+pub struct SqliteNotificationRepository {
+    db: DbHandle<Sqlite>,
+}
+
+impl SqliteNotificationRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Sqlite>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl NotificationRepository for SqliteNotificationRepository {
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "notifications", db.query.text = Empty))]
+    async fn create(&self, notification: Notification) -> Result<Notification, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query_as!(NotificationRow,
+            r#"
+            INSERT INTO notifications (kind, message, product_id, created_at)
+            VALUES (?1, ?2, ?3, ?4)
+            RETURNING id, kind, message, product_id, created_at, read_at
+            "#
+        )
+        .bind(notification.kind)
+        .bind(notification.message)
+        .bind(notification.product_id)
+        .bind(Utc::now().timestamp_millis())
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        .try_into()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "notifications", db.query.text = Empty))]
+    async fn list(&self, unread_only: bool) -> Result<Vec<Notification>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query_as!(NotificationRow,
+            r#"
+            SELECT id, kind, message, product_id, created_at, read_at
+            FROM notifications
+            WHERE NOT ?1 OR read_at IS NULL
+            ORDER BY id DESC
+            "#
+        )
+        .bind(unread_only)
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(Notification::try_from)
+        .collect()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "UPDATE", db.collection.name = "notifications", db.query.text = Empty))]
+    async fn mark_read(&self, id: i64) -> Result<bool, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = sqlite_query!(
+            r#"
+            UPDATE notifications
+            SET read_at = COALESCE(read_at, ?2)
+            WHERE id = ?1
+            "#
+        )
+        .bind(id)
+        .bind(Utc::now().timestamp_millis())
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
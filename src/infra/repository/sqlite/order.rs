@@ -0,0 +1,269 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, Sqlite, SqliteConnection, SqlitePool};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::page::Page;
+use crate::domain::order::{Order, OrderLine, OrderRepository, OrderStatus, StatusChange};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{sqlite_query, sqlite_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+use crate::infra::repository::numeric::{cents_to_f64, to_numeric_10_2_cents};
+use crate::infra::repository::order::parse_status;
+
+#[derive(FromRow)]
+struct OrderRow {
+    id: i64,
+    user_id: Option<i64>,
+    discount_cents: i64,
+    shipping_cents: i64,
+    total_cents: i64,
+    status: String,
+    created_at: i64,
+}
+
+#[derive(FromRow)]
+struct StatusChangeRow {
+    from_status: Option<String>,
+    to_status: String,
+    actor_id: Option<i64>,
+    reason: Option<String>,
+    changed_at: i64,
+}
+
+pub(crate) fn from_millis(millis: i64) -> Result<DateTime<Utc>, DomainError> {
+    DateTime::from_timestamp_millis(millis).ok_or_else(|| DomainError::Unexpected(format!("invalid timestamp {millis}")))
+}
+
+#[derive(FromRow)]
+struct OrderLineRow {
+    product_id: i64,
+    name: String,
+    quantity: i32,
+    unit_price_cents: i64,
+}
+
+impl From<OrderLineRow> for OrderLine {
+    fn from(row: OrderLineRow) -> Self {
+        OrderLine {
+            product_id: row.product_id,
+            name: row.name,
+            quantity: row.quantity,
+            unit_price: cents_to_f64(row.unit_price_cents),
+        }
+    }
+}
+
+/// Same as the Postgres repository; timestamps are Unix milliseconds from
+/// the application clock.
+#[derive(Clone)]
+pub struct SqliteOrderRepository {
+    db: DbHandle<Sqlite>,
+}
+
+impl SqliteOrderRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Sqlite>) -> Self {
+        Self { db }
+    }
+}
+
+async fn with_lines(conn: &mut SqliteConnection, row: OrderRow) -> Result<Order, DomainError> {
+    let lines = sqlite_query_as!(OrderLineRow,
+        r#"
+        SELECT product_id, name, quantity, unit_price_cents
+        FROM order_items
+        WHERE order_id = ?1
+        ORDER BY id
+        "#
+    )
+    .bind(row.id)
+    .fetch_all(&mut *conn)
+    .traced()
+    .await
+    .map_err(db_error)?;
+
+    Ok(Order {
+        id: row.id,
+        user_id: row.user_id,
+        lines: lines.into_iter().map(OrderLine::from).collect(),
+        discount: cents_to_f64(row.discount_cents),
+        shipping: cents_to_f64(row.shipping_cents),
+        total: cents_to_f64(row.total_cents),
+        status: parse_status(&row.status)?,
+        created_at: from_millis(row.created_at)?,
+    })
+}
+
+#[async_trait]
+impl OrderRepository for SqliteOrderRepository {
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "orders", db.query.text = Empty))]
+    async fn create(&self, order: Order) -> Result<i64, DomainError> {
+        let discount_cents = to_numeric_10_2_cents(order.discount)?;
+        let shipping_cents = to_numeric_10_2_cents(order.shipping)?;
+        let total_cents = to_numeric_10_2_cents(order.total)?;
+        let lines = order
+            .lines
+            .into_iter()
+            .map(|l| Ok((to_numeric_10_2_cents(l.unit_price)?, l)))
+            .collect::<Result<Vec<_>, DomainError>>()?;
+
+        let mut conn = self.db.acquire().await?;
+        let id: (i64,) = sqlite_query_as!((i64,),
+            r#"
+            INSERT INTO orders (user_id, discount_cents, shipping_cents, total_cents, status, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            RETURNING id
+            "#
+        )
+        .bind(order.user_id)
+        .bind(discount_cents)
+        .bind(shipping_cents)
+        .bind(total_cents)
+        .bind(order.status.as_str())
+        .bind(Utc::now().timestamp_millis())
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        for (unit_price_cents, line) in lines {
+            sqlite_query!(
+                r#"
+                INSERT INTO order_items (order_id, product_id, name, quantity, unit_price_cents)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                "#
+            )
+            .bind(id.0)
+            .bind(line.product_id)
+            .bind(line.name)
+            .bind(line.quantity)
+            .bind(unit_price_cents)
+            .execute(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)?;
+        }
+
+        Ok(id.0)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "UPDATE", db.collection.name = "orders", db.query.text = Empty))]
+    async fn record_status_change(&self, id: i64, change: StatusChange) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query!(
+            r#"
+            UPDATE orders
+            SET status = ?2
+            WHERE id = ?1
+            "#
+        )
+        .bind(id)
+        .bind(change.to.as_str())
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        sqlite_query!(
+            r#"
+            INSERT INTO order_status_changes (order_id, from_status, to_status, actor_id, reason, changed_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#
+        )
+        .bind(id)
+        .bind(change.from.map(OrderStatus::as_str))
+        .bind(change.to.as_str())
+        .bind(change.actor_id)
+        .bind(change.reason)
+        .bind(Utc::now().timestamp_millis())
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "order_status_changes", db.query.text = Empty))]
+    async fn status_history(&self, id: i64) -> Result<Vec<StatusChange>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = sqlite_query_as!(StatusChangeRow,
+            r#"
+            SELECT from_status, to_status, actor_id, reason, changed_at
+            FROM order_status_changes
+            WHERE order_id = ?1
+            ORDER BY id
+            "#
+        )
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(StatusChange {
+                    from: row.from_status.as_deref().map(parse_status).transpose()?,
+                    to: parse_status(&row.to_status)?,
+                    actor_id: row.actor_id,
+                    reason: row.reason,
+                    changed_at: from_millis(row.changed_at)?,
+                })
+            })
+            .collect()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "orders", db.query.text = Empty))]
+    async fn get(&self, id: i64) -> Result<Option<Order>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let Some(row) = sqlite_query_as!(OrderRow,
+            r#"
+            SELECT id, user_id, discount_cents, shipping_cents, total_cents, status, created_at
+            FROM orders
+            WHERE id = ?1
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(with_lines(&mut conn, row).await?))
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "orders", db.query.text = Empty))]
+    async fn get_by_user_id(&self, user_id: i64, page: Page) -> Result<Vec<Order>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = sqlite_query_as!(OrderRow,
+            r#"
+            SELECT id, user_id, discount_cents, shipping_cents, total_cents, status, created_at
+            FROM orders
+            WHERE user_id = ?1
+            ORDER BY id DESC
+            LIMIT ?2 OFFSET ?3
+            "#
+        )
+        .bind(user_id)
+        .bind(page.limit.unwrap_or(-1))
+        .bind(page.offset)
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        let mut orders = Vec::with_capacity(rows.len());
+        for row in rows {
+            orders.push(with_lines(&mut conn, row).await?);
+        }
+        Ok(orders)
+    }
+}
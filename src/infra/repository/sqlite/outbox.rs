@@ -0,0 +1,186 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, Sqlite};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::event::{DomainEvent, OutboxMessage, OutboxRepository};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{sqlite_query, sqlite_query_as, DbTraceExt};
+use crate::infra::propagation::current_traceparent;
+use crate::infra::repository::db::{db_error, DbHandle};
+
+#[derive(FromRow)]
+struct OutboxRow {
+    id: i64,
+    aggregate_type: String,
+    aggregate_id: i64,
+    event_type: String,
+    payload: String,
+    trace_context: Option<String>,
+    attempts: i32,
+}
+
+impl TryFrom<OutboxRow> for OutboxMessage {
+    type Error = DomainError;
+
+    fn try_from(row: OutboxRow) -> Result<Self, Self::Error> {
+        Ok(OutboxMessage {
+            id: row.id,
+            aggregate_type: row.aggregate_type,
+            aggregate_id: row.aggregate_id,
+            event_type: row.event_type,
+            payload: serde_json::from_str(&row.payload).map_err(|e| DomainError::Unexpected(e.to_string()))?,
+            trace_context: row.trace_context,
+            attempts: row.attempts,
+        })
+    }
+}
+
+/// Only used through a unit of work, like the Postgres outbox.
+#[derive(Clone)]
+pub struct SqliteOutboxRepository {
+    db: DbHandle<Sqlite>,
+}
+
+impl SqliteOutboxRepository {
+    pub fn with_handle(db: DbHandle<Sqlite>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl OutboxRepository for SqliteOutboxRepository {
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "outbox", db.query.text = Empty))]
+    async fn append(&self, event: &DomainEvent) -> Result<i64, DomainError> {
+        let payload = event.payload()?.to_string();
+
+        let mut conn = self.db.acquire().await?;
+        let id: (i64,) = sqlite_query_as!((i64,),
+            r#"
+            INSERT INTO outbox (aggregate_type, aggregate_id, event_type, payload, trace_context)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            RETURNING id
+            "#
+        )
+        .bind(event.aggregate_type())
+        .bind(event.aggregate_id())
+        .bind(event.event_type())
+        .bind(payload)
+        .bind(current_traceparent())
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(id.0)
+    }
+
+    /// SQLite has one writer at a time: taking the write lock up front makes
+    /// this relay the only one until the transaction ends.
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "UPDATE", db.collection.name = "outbox", db.query.text = Empty))]
+    async fn lock_relay(&self) -> Result<bool, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query!(
+            r#"
+            UPDATE outbox SET id = id WHERE id < 0
+            "#
+        )
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(true)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "outbox", db.query.text = Empty))]
+    async fn pending(&self, limit: i64) -> Result<Vec<OutboxMessage>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = sqlite_query_as!(OutboxRow,
+            r#"
+            SELECT o.id, o.aggregate_type, o.aggregate_id, o.event_type, o.payload, o.trace_context, o.attempts
+            FROM outbox o
+            WHERE o.published_at IS NULL AND o.dead_at IS NULL
+              AND (o.next_attempt_at IS NULL OR o.next_attempt_at <= ?2)
+              AND NOT EXISTS (
+                SELECT 1
+                FROM outbox w
+                WHERE w.aggregate_type = o.aggregate_type AND w.aggregate_id = o.aggregate_id AND w.id < o.id
+                  AND w.published_at IS NULL AND w.dead_at IS NULL AND w.next_attempt_at > ?2
+              )
+            ORDER BY o.id
+            LIMIT ?1
+            "#
+        )
+        .bind(limit)
+        .bind(Utc::now().timestamp_millis())
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        rows.into_iter().map(OutboxMessage::try_from).collect()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "UPDATE", db.collection.name = "outbox", db.query.text = Empty))]
+    async fn hold(&self, ids: &[i64], until: Option<DateTime<Utc>>) -> Result<(), DomainError> {
+        let ids = serde_json::to_string(ids).map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        let mut conn = self.db.acquire().await?;
+        sqlite_query!(
+            r#"
+            UPDATE outbox
+            SET next_attempt_at = ?2
+            WHERE id IN (SELECT value FROM json_each(?1))
+            "#
+        )
+        .bind(ids)
+        .bind(until.map(|t| t.timestamp_millis()))
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "UPDATE", db.collection.name = "outbox", db.query.text = Empty))]
+    async fn mark_published(&self, id: i64) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query!(
+            r#"
+            UPDATE outbox
+            SET published_at = CURRENT_TIMESTAMP, attempts = attempts + 1, last_error = NULL
+            WHERE id = ?1
+            "#
+        )
+        .bind(id)
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "UPDATE", db.collection.name = "outbox", db.query.text = Empty))]
+    async fn mark_failed(&self, id: i64, error: String, retry_at: Option<DateTime<Utc>>) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query!(
+            r#"
+            UPDATE outbox
+            SET attempts = attempts + 1, last_error = ?2, next_attempt_at = ?3,
+                dead_at = CASE WHEN ?3 IS NULL THEN CURRENT_TIMESTAMP END
+            WHERE id = ?1
+            "#
+        )
+        .bind(id)
+        .bind(error)
+        .bind(retry_at.map(|t| t.timestamp_millis()))
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,250 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{FromRow, Sqlite, SqlitePool};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::payment::{PaymentAttempt, PaymentIntent, PaymentRepository, PaymentStatus};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{sqlite_query, sqlite_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+use crate::infra::repository::numeric::{cents_to_f64, to_numeric_10_2_cents};
+use crate::infra::repository::payment::{parse_operation, parse_payment_status};
+use crate::infra::repository::sqlite::order::from_millis;
+
+#[derive(FromRow)]
+struct IntentRow {
+    id: i64,
+    order_id: i64,
+    provider: String,
+    provider_ref: Option<String>,
+    amount_cents: i64,
+    status: String,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl TryFrom<IntentRow> for PaymentIntent {
+    type Error = DomainError;
+
+    fn try_from(row: IntentRow) -> Result<Self, Self::Error> {
+        Ok(PaymentIntent {
+            id: row.id,
+            order_id: row.order_id,
+            provider: row.provider,
+            provider_ref: row.provider_ref,
+            amount: cents_to_f64(row.amount_cents),
+            status: parse_payment_status(&row.status)?,
+            created_at: from_millis(row.created_at)?,
+            updated_at: from_millis(row.updated_at)?,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct AttemptRow {
+    operation: String,
+    succeeded: bool,
+    detail: Option<String>,
+    created_at: i64,
+}
+
+/// Same as the Postgres repository; timestamps are Unix milliseconds from
+/// the application clock.
+#[derive(Clone)]
+pub struct SqlitePaymentRepository {
+    db: DbHandle<Sqlite>,
+}
+
+impl SqlitePaymentRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Sqlite>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl PaymentRepository for SqlitePaymentRepository {
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "payment_intents", db.query.text = Empty))]
+    async fn create_intent(&self, intent: PaymentIntent) -> Result<i64, DomainError> {
+        let amount_cents = to_numeric_10_2_cents(intent.amount)?;
+        let now = Utc::now().timestamp_millis();
+        let mut conn = self.db.acquire().await?;
+        let id: (i64,) = sqlite_query_as!((i64,),
+            r#"
+            INSERT INTO payment_intents (order_id, provider, provider_ref, amount_cents, status, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)
+            RETURNING id
+            "#
+        )
+        .bind(intent.order_id)
+        .bind(intent.provider)
+        .bind(intent.provider_ref)
+        .bind(amount_cents)
+        .bind(intent.status.as_str())
+        .bind(now)
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(id.0)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "payment_intents", db.query.text = Empty))]
+    async fn get_intent(&self, id: i64) -> Result<Option<PaymentIntent>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query_as!(IntentRow,
+            r#"
+            SELECT id, order_id, provider, provider_ref, amount_cents, status, created_at, updated_at
+            FROM payment_intents
+            WHERE id = ?1
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        .map(PaymentIntent::try_from)
+        .transpose()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "payment_intents", db.query.text = Empty))]
+    async fn get_intents_by_order_id(&self, order_id: i64) -> Result<Vec<PaymentIntent>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query_as!(IntentRow,
+            r#"
+            SELECT id, order_id, provider, provider_ref, amount_cents, status, created_at, updated_at
+            FROM payment_intents
+            WHERE order_id = ?1
+            ORDER BY id DESC
+            "#
+        )
+        .bind(order_id)
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(PaymentIntent::try_from)
+        .collect()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "payment_intents", db.query.text = Empty))]
+    async fn get_intent_by_provider_ref(
+        &self,
+        provider: &str,
+        provider_ref: &str,
+    ) -> Result<Option<PaymentIntent>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query_as!(IntentRow,
+            r#"
+            SELECT id, order_id, provider, provider_ref, amount_cents, status, created_at, updated_at
+            FROM payment_intents
+            WHERE provider = ?1 AND provider_ref = ?2
+            "#
+        )
+        .bind(provider)
+        .bind(provider_ref)
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        .map(PaymentIntent::try_from)
+        .transpose()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "UPDATE", db.collection.name = "payment_intents", db.query.text = Empty))]
+    async fn update_intent(&self, id: i64, status: PaymentStatus, provider_ref: Option<String>) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query!(
+            r#"
+            UPDATE payment_intents
+            SET status = ?2, provider_ref = COALESCE(?3, provider_ref), updated_at = ?4
+            WHERE id = ?1
+            "#
+        )
+        .bind(id)
+        .bind(status.as_str())
+        .bind(provider_ref)
+        .bind(Utc::now().timestamp_millis())
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "payment_attempts", db.query.text = Empty))]
+    async fn record_attempt(&self, intent_id: i64, attempt: PaymentAttempt) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query!(
+            r#"
+            INSERT INTO payment_attempts (intent_id, operation, succeeded, detail, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#
+        )
+        .bind(intent_id)
+        .bind(attempt.operation.as_str())
+        .bind(attempt.succeeded)
+        .bind(attempt.detail)
+        .bind(Utc::now().timestamp_millis())
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "payment_attempts", db.query.text = Empty))]
+    async fn attempts(&self, intent_id: i64) -> Result<Vec<PaymentAttempt>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = sqlite_query_as!(AttemptRow,
+            r#"
+            SELECT operation, succeeded, detail, created_at
+            FROM payment_attempts
+            WHERE intent_id = ?1
+            ORDER BY id
+            "#
+        )
+        .bind(intent_id)
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(PaymentAttempt {
+                    operation: parse_operation(&row.operation)?,
+                    succeeded: row.succeeded,
+                    detail: row.detail,
+                    created_at: from_millis(row.created_at)?,
+                })
+            })
+            .collect()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "payment_webhook_events", db.query.text = Empty))]
+    async fn record_webhook_event(&self, provider: &str, event_id: &str) -> Result<bool, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = sqlite_query!(
+            r#"
+            INSERT INTO payment_webhook_events (provider, event_id, received_at)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT DO NOTHING
+            "#
+        )
+        .bind(provider)
+        .bind(event_id)
+        .bind(Utc::now().timestamp_millis())
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(result.rows_affected() == 1)
+    }
+}
@@ -0,0 +1,160 @@
+use async_trait::async_trait;
+use sqlx::{FromRow, Sqlite, SqlitePool};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::page::Page;
+use crate::domain::product::{Product, ProductRepository};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{sqlite_query, sqlite_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+use crate::infra::repository::numeric::{cents_to_f64, to_numeric_10_2_cents};
+
+#[derive(FromRow)]
+struct ProductRow {
+    id: i64,
+    name: String,
+    description: Option<String>,
+    price_cents: i64,
+    stock: i32,
+    category_id: i64,
+    active: bool,
+}
+
+impl From<ProductRow> for Product {
+    fn from(row: ProductRow) -> Self {
+        Product {
+            id: row.id,
+            name: row.name,
+            description: row.description,
+            price: cents_to_f64(row.price_cents),
+            stock: row.stock,
+            category_id: row.category_id,
+            active: row.active,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SqliteProductRepository {
+    db: DbHandle<Sqlite>,
+}
+
+impl SqliteProductRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Sqlite>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl ProductRepository for SqliteProductRepository {
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "products", db.query.text = Empty))]
+    async fn create(&self, product: Product) -> Result<i64, DomainError> {
+        let price_cents = to_numeric_10_2_cents(product.price)?;
+
+        let mut conn = self.db.acquire().await?;
+        let id: (i64,) = sqlite_query_as!((i64,),
+            r#"
+            INSERT INTO products (name, description, price_cents, stock, category_id, active)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            RETURNING id
+            "#
+        )
+        .bind(product.name)
+        .bind(product.description)
+        .bind(price_cents)
+        .bind(product.stock)
+        .bind(product.category_id)
+        .bind(product.active)
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        // starting stock goes to the default warehouse
+        sqlite_query!(
+            r#"
+            INSERT INTO warehouse_stock (warehouse_id, product_id, stock)
+            SELECT id, ?1, ?2 FROM warehouses
+            WHERE ?2 > 0
+            ORDER BY priority, id
+            LIMIT 1
+            "#
+        )
+        .bind(id.0)
+        .bind(product.stock)
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(id.0)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "products", db.query.text = Empty))]
+    async fn get_by_product_id(&self, id: i64) -> Result<Option<Product>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = sqlite_query_as!(ProductRow,
+            r#"
+            SELECT id, name, description, price_cents, stock, category_id, active
+            FROM products
+            WHERE id = ?1
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.map(Product::from))
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "products", db.query.text = Empty))]
+    async fn get_by_category_id(&self, category_id: i64, page: Page) -> Result<Vec<Product>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = sqlite_query_as!(ProductRow,
+            r#"
+            SELECT id, name, description, price_cents, stock, category_id, active
+            FROM products
+            WHERE category_id = ?1
+            ORDER BY id
+            LIMIT ?2 OFFSET ?3
+            "#
+        )
+        .bind(category_id)
+        .bind(page.limit.unwrap_or(-1))
+        .bind(page.offset)
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(rows.into_iter().map(Product::from).collect())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "products", db.query.text = Empty))]
+    async fn get_all_products(&self, page: Page) -> Result<Vec<Product>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = sqlite_query_as!(ProductRow,
+            r#"
+            SELECT id, name, description, price_cents, stock, category_id, active
+            FROM products
+            ORDER BY id
+            LIMIT ?1 OFFSET ?2
+            "#
+        )
+        .bind(page.limit.unwrap_or(-1))
+        .bind(page.offset)
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(rows.into_iter().map(Product::from).collect())
+    }
+}
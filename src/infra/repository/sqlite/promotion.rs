@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{FromRow, Sqlite, SqlitePool};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::promotion::{Promotion, PromotionRepository, Redemption};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{sqlite_query, sqlite_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+use crate::infra::repository::numeric::{cents_to_f64, to_numeric_10_2_cents};
+use crate::infra::repository::promotion::parse_kind;
+use crate::infra::repository::sqlite::order::from_millis;
+
+#[derive(FromRow)]
+struct PromotionRow {
+    id: i64,
+    name: String,
+    code: Option<String>,
+    kind: String,
+    value_cents: i64,
+    buy_quantity: Option<i32>,
+    get_quantity: Option<i32>,
+    category_id: Option<i64>,
+    product_id: Option<i64>,
+    starts_at: Option<i64>,
+    ends_at: Option<i64>,
+    max_uses: Option<i32>,
+    max_uses_per_user: Option<i32>,
+    active: bool,
+}
+
+impl TryFrom<PromotionRow> for Promotion {
+    type Error = DomainError;
+
+    fn try_from(row: PromotionRow) -> Result<Self, Self::Error> {
+        Ok(Promotion {
+            id: row.id,
+            name: row.name,
+            code: row.code,
+            kind: parse_kind(&row.kind)?,
+            value: cents_to_f64(row.value_cents),
+            buy_quantity: row.buy_quantity,
+            get_quantity: row.get_quantity,
+            category_id: row.category_id,
+            product_id: row.product_id,
+            starts_at: row.starts_at.map(from_millis).transpose()?,
+            ends_at: row.ends_at.map(from_millis).transpose()?,
+            max_uses: row.max_uses,
+            max_uses_per_user: row.max_uses_per_user,
+            active: row.active,
+        })
+    }
+}
+
+/// Same as the Postgres repository; timestamps are Unix milliseconds from the application clock.
+#[derive(Clone)]
+pub struct SqlitePromotionRepository {
+    db: DbHandle<Sqlite>,
+}
+
+impl SqlitePromotionRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Sqlite>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl PromotionRepository for SqlitePromotionRepository {
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "promotions", db.query.text = Empty))]
+    async fn create(&self, promotion: Promotion) -> Result<i64, DomainError> {
+        let value_cents = to_numeric_10_2_cents(promotion.value)?;
+        let mut conn = self.db.acquire().await?;
+        let id: (i64,) = sqlite_query_as!((i64,),
+            r#"
+            INSERT INTO promotions (name, code, kind, value_cents, buy_quantity, get_quantity, category_id, product_id,
+                                    starts_at, ends_at, max_uses, max_uses_per_user, active)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            RETURNING id
+            "#
+        )
+        .bind(promotion.name)
+        .bind(promotion.code)
+        .bind(promotion.kind.as_str())
+        .bind(value_cents)
+        .bind(promotion.buy_quantity)
+        .bind(promotion.get_quantity)
+        .bind(promotion.category_id)
+        .bind(promotion.product_id)
+        .bind(promotion.starts_at.map(|t| t.timestamp_millis()))
+        .bind(promotion.ends_at.map(|t| t.timestamp_millis()))
+        .bind(promotion.max_uses)
+        .bind(promotion.max_uses_per_user)
+        .bind(promotion.active)
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(id.0)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "promotions", db.query.text = Empty))]
+    async fn get(&self, id: i64) -> Result<Option<Promotion>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query_as!(PromotionRow,
+            r#"
+            SELECT id, name, code, kind, value_cents, buy_quantity, get_quantity, category_id, product_id,
+                   starts_at, ends_at, max_uses, max_uses_per_user, active
+            FROM promotions
+            WHERE id = ?1
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        .map(Promotion::try_from)
+        .transpose()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "promotions", db.query.text = Empty))]
+    async fn by_code(&self, code: &str) -> Result<Option<Promotion>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query_as!(PromotionRow,
+            r#"
+            SELECT id, name, code, kind, value_cents, buy_quantity, get_quantity, category_id, product_id,
+                   starts_at, ends_at, max_uses, max_uses_per_user, active
+            FROM promotions
+            WHERE code = ?1
+            "#
+        )
+        .bind(code)
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        .map(Promotion::try_from)
+        .transpose()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "promotions", db.query.text = Empty))]
+    async fn list(&self) -> Result<Vec<Promotion>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query_as!(PromotionRow,
+            r#"
+            SELECT id, name, code, kind, value_cents, buy_quantity, get_quantity, category_id, product_id,
+                   starts_at, ends_at, max_uses, max_uses_per_user, active
+            FROM promotions
+            ORDER BY id
+            "#
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(Promotion::try_from)
+        .collect()
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "UPDATE", db.collection.name = "promotions", db.query.text = Empty))]
+    async fn update(&self, promotion: Promotion) -> Result<bool, DomainError> {
+        let value_cents = to_numeric_10_2_cents(promotion.value)?;
+        let mut conn = self.db.acquire().await?;
+        let result = sqlite_query!(
+            r#"
+            UPDATE promotions
+            SET name = ?2, code = ?3, kind = ?4, value_cents = ?5, buy_quantity = ?6, get_quantity = ?7,
+                category_id = ?8, product_id = ?9, starts_at = ?10, ends_at = ?11, max_uses = ?12,
+                max_uses_per_user = ?13, active = ?14
+            WHERE id = ?1
+            "#
+        )
+        .bind(promotion.id)
+        .bind(promotion.name)
+        .bind(promotion.code)
+        .bind(promotion.kind.as_str())
+        .bind(value_cents)
+        .bind(promotion.buy_quantity)
+        .bind(promotion.get_quantity)
+        .bind(promotion.category_id)
+        .bind(promotion.product_id)
+        .bind(promotion.starts_at.map(|t| t.timestamp_millis()))
+        .bind(promotion.ends_at.map(|t| t.timestamp_millis()))
+        .bind(promotion.max_uses)
+        .bind(promotion.max_uses_per_user)
+        .bind(promotion.active)
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "DELETE", db.collection.name = "promotions", db.query.text = Empty))]
+    async fn delete(&self, id: i64) -> Result<bool, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = sqlite_query!("DELETE FROM promotions WHERE id = ?1")
+            .bind(id)
+            .execute(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "promotion_redemptions", db.query.text = Empty))]
+    async fn redeem(&self, redemption: Redemption) -> Result<(), DomainError> {
+        let discount_cents = to_numeric_10_2_cents(redemption.discount)?;
+        let mut conn = self.db.acquire().await?;
+        sqlite_query!(
+            r#"
+            INSERT INTO promotion_redemptions (promotion_id, user_id, order_id, discount_cents, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#
+        )
+        .bind(redemption.promotion_id)
+        .bind(redemption.user_id)
+        .bind(redemption.order_id)
+        .bind(discount_cents)
+        .bind(Utc::now().timestamp_millis())
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "promotion_redemptions", db.query.text = Empty))]
+    async fn uses(&self, promotion_id: i64, user_id: Option<i64>) -> Result<i64, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let count: (i64,) = sqlite_query_as!((i64,),
+            r#"
+            SELECT COUNT(*)
+            FROM promotion_redemptions
+            WHERE promotion_id = ?1 AND (?2 IS NULL OR user_id = ?2)
+            "#
+        )
+        .bind(promotion_id)
+        .bind(user_id)
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(count.0)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "promotion_redemptions", db.query.text = Empty))]
+    async fn uses_by_promotion(&self, user_id: Option<i64>) -> Result<HashMap<i64, i64>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows: Vec<(i64, i64)> = sqlite_query_as!((i64, i64),
+            r#"
+            SELECT promotion_id, COUNT(*)
+            FROM promotion_redemptions
+            WHERE ?1 IS NULL OR user_id = ?1
+            GROUP BY promotion_id
+            "#
+        )
+        .bind(user_id)
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(rows.into_iter().collect())
+    }
+}
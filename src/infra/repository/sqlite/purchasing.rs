@@ -0,0 +1,325 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{FromRow, Sqlite, SqliteConnection, SqlitePool};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::purchasing::{PurchaseOrder, PurchaseOrderLine, PurchaseOrderStatus, PurchasingRepository, Supplier, SupplierProduct};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{sqlite_query, sqlite_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+use crate::infra::repository::numeric::{cents_to_f64, to_numeric_10_2_cents};
+use crate::infra::repository::purchasing::parse_status;
+use crate::infra::repository::sqlite::order::from_millis;
+
+#[derive(FromRow)]
+struct SupplierRow {
+    id: i64,
+    name: String,
+    email: Option<String>,
+}
+
+#[derive(FromRow)]
+struct SupplierProductRow {
+    supplier_id: i64,
+    product_id: i64,
+    sku: String,
+    cost_price_cents: i64,
+}
+
+impl From<SupplierProductRow> for SupplierProduct {
+    fn from(row: SupplierProductRow) -> Self {
+        SupplierProduct {
+            supplier_id: row.supplier_id,
+            product_id: row.product_id,
+            sku: row.sku,
+            cost_price: cents_to_f64(row.cost_price_cents),
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct PurchaseOrderRow {
+    id: i64,
+    supplier_id: i64,
+    status: String,
+    created_by: Option<i64>,
+    created_at: i64,
+}
+
+#[derive(FromRow)]
+struct PurchaseOrderLineRow {
+    purchase_order_id: i64,
+    product_id: i64,
+    quantity: i32,
+    received: i32,
+    unit_cost_cents: i64,
+}
+
+/// Same as the Postgres repository; timestamps are Unix milliseconds from the application clock.
+#[derive(Clone)]
+pub struct SqlitePurchasingRepository {
+    db: DbHandle<Sqlite>,
+}
+
+impl SqlitePurchasingRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Sqlite>) -> Self {
+        Self { db }
+    }
+}
+
+/// The orders of `rows` with their lines, in the order of `rows`.
+async fn with_lines(conn: &mut SqliteConnection, rows: Vec<PurchaseOrderRow>) -> Result<Vec<PurchaseOrder>, DomainError> {
+    let ids: Vec<i64> = rows.iter().map(|o| o.id).collect();
+    let ids = serde_json::to_string(&ids).map_err(|e| DomainError::Unexpected(e.to_string()))?;
+    let lines = sqlite_query_as!(PurchaseOrderLineRow,
+        r#"
+        SELECT purchase_order_id, product_id, quantity, received, unit_cost_cents
+        FROM purchase_order_lines
+        WHERE purchase_order_id IN (SELECT value FROM json_each(?1))
+        ORDER BY product_id
+        "#
+    )
+    .bind(ids)
+    .fetch_all(&mut *conn)
+    .traced()
+    .await
+    .map_err(db_error)?;
+
+    rows.into_iter()
+        .map(|o| {
+            Ok(PurchaseOrder {
+                id: o.id,
+                supplier_id: o.supplier_id,
+                status: parse_status(&o.status)?,
+                lines: lines
+                    .iter()
+                    .filter(|l| l.purchase_order_id == o.id)
+                    .map(|l| PurchaseOrderLine {
+                        product_id: l.product_id,
+                        quantity: l.quantity,
+                        received: l.received,
+                        unit_cost: cents_to_f64(l.unit_cost_cents),
+                    })
+                    .collect(),
+                created_by: o.created_by,
+                created_at: from_millis(o.created_at)?,
+            })
+        })
+        .collect()
+}
+
+#[async_trait]
+impl PurchasingRepository for SqlitePurchasingRepository {
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "suppliers", db.query.text = Empty))]
+    async fn create_supplier(&self, supplier: Supplier) -> Result<i64, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let id: (i64,) = sqlite_query_as!((i64,),
+            r#"
+            INSERT INTO suppliers (name, email)
+            VALUES (?1, ?2)
+            RETURNING id
+            "#
+        )
+        .bind(supplier.name)
+        .bind(supplier.email)
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(id.0)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "suppliers", db.query.text = Empty))]
+    async fn supplier(&self, id: i64) -> Result<Option<Supplier>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = sqlite_query_as!(SupplierRow, "SELECT id, name, email FROM suppliers WHERE id = ?1")
+            .bind(id)
+            .fetch_optional(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)?;
+        Ok(row.map(|r| Supplier { id: r.id, name: r.name, email: r.email }))
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "suppliers", db.query.text = Empty))]
+    async fn suppliers(&self) -> Result<Vec<Supplier>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = sqlite_query_as!(SupplierRow, "SELECT id, name, email FROM suppliers ORDER BY id")
+            .fetch_all(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)?;
+        Ok(rows.into_iter().map(|r| Supplier { id: r.id, name: r.name, email: r.email }).collect())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "UPSERT", db.collection.name = "supplier_products", db.query.text = Empty))]
+    async fn set_supplier_product(&self, link: SupplierProduct) -> Result<SupplierProduct, DomainError> {
+        let cost_price_cents = to_numeric_10_2_cents(link.cost_price)?;
+
+        let mut conn = self.db.acquire().await?;
+        let row = sqlite_query_as!(SupplierProductRow,
+            r#"
+            INSERT INTO supplier_products (supplier_id, product_id, sku, cost_price_cents)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT (supplier_id, product_id) DO UPDATE SET sku = excluded.sku, cost_price_cents = excluded.cost_price_cents
+            RETURNING supplier_id, product_id, sku, cost_price_cents
+            "#
+        )
+        .bind(link.supplier_id)
+        .bind(link.product_id)
+        .bind(link.sku)
+        .bind(cost_price_cents)
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.into())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "supplier_products", db.query.text = Empty))]
+    async fn supplier_products(&self, supplier_id: i64) -> Result<Vec<SupplierProduct>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = sqlite_query_as!(SupplierProductRow,
+            r#"
+            SELECT supplier_id, product_id, sku, cost_price_cents
+            FROM supplier_products
+            WHERE supplier_id = ?1
+            ORDER BY product_id
+            "#
+        )
+        .bind(supplier_id)
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(rows.into_iter().map(SupplierProduct::from).collect())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "purchase_orders", db.query.text = Empty))]
+    async fn create_order(&self, order: PurchaseOrder) -> Result<i64, DomainError> {
+        let lines = order
+            .lines
+            .into_iter()
+            .map(|l| Ok((to_numeric_10_2_cents(l.unit_cost)?, l)))
+            .collect::<Result<Vec<_>, DomainError>>()?;
+
+        let mut conn = self.db.acquire().await?;
+        let id: (i64,) = sqlite_query_as!((i64,),
+            r#"
+            INSERT INTO purchase_orders (supplier_id, status, created_by, created_at)
+            VALUES (?1, ?2, ?3, ?4)
+            RETURNING id
+            "#
+        )
+        .bind(order.supplier_id)
+        .bind(order.status.as_str())
+        .bind(order.created_by)
+        .bind(Utc::now().timestamp_millis())
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        for (unit_cost_cents, line) in lines {
+            sqlite_query!(
+                r#"
+                INSERT INTO purchase_order_lines (purchase_order_id, product_id, quantity, received, unit_cost_cents)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                "#
+            )
+            .bind(id.0)
+            .bind(line.product_id)
+            .bind(line.quantity)
+            .bind(line.received)
+            .bind(unit_cost_cents)
+            .execute(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)?;
+        }
+
+        Ok(id.0)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "purchase_orders", db.query.text = Empty))]
+    async fn order(&self, id: i64) -> Result<Option<PurchaseOrder>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let Some(row) = sqlite_query_as!(PurchaseOrderRow,
+            r#"
+            SELECT id, supplier_id, status, created_by, created_at
+            FROM purchase_orders
+            WHERE id = ?1
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?
+        else {
+            return Ok(None);
+        };
+
+        Ok(with_lines(&mut conn, vec![row]).await?.pop())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "purchase_orders", db.query.text = Empty))]
+    async fn orders(&self) -> Result<Vec<PurchaseOrder>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = sqlite_query_as!(PurchaseOrderRow,
+            r#"
+            SELECT id, supplier_id, status, created_by, created_at
+            FROM purchase_orders
+            ORDER BY id DESC
+            "#
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        with_lines(&mut conn, rows).await
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "UPDATE", db.collection.name = "purchase_orders", db.query.text = Empty))]
+    async fn set_status(&self, id: i64, status: PurchaseOrderStatus) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query!("UPDATE purchase_orders SET status = ?2 WHERE id = ?1")
+            .bind(id)
+            .bind(status.as_str())
+            .execute(&mut *conn)
+            .traced()
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "UPDATE", db.collection.name = "purchase_order_lines", db.query.text = Empty))]
+    async fn receive(&self, id: i64, product_id: i64, quantity: i32) -> Result<bool, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = sqlite_query!(
+            r#"
+            UPDATE purchase_order_lines
+            SET received = received + ?3
+            WHERE purchase_order_id = ?1 AND product_id = ?2 AND received + ?3 <= quantity
+            "#
+        )
+        .bind(id)
+        .bind(product_id)
+        .bind(quantity)
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
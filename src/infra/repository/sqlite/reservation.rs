@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, Sqlite, SqlitePool};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::reservation::{Reservation, ReservationRepository};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{sqlite_query, sqlite_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+use crate::infra::repository::sqlite::order::from_millis;
+
+#[derive(FromRow)]
+struct ReservationRow {
+    id: i64,
+    cart_id: i64,
+    product_id: i64,
+    quantity: i32,
+    expires_at: i64,
+}
+
+/// Same as the Postgres repository; `expires_at` is Unix milliseconds.
+#[derive(Clone)]
+pub struct SqliteReservationRepository {
+    db: DbHandle<Sqlite>,
+}
+
+impl SqliteReservationRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Sqlite>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl ReservationRepository for SqliteReservationRepository {
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "stock_reservations", db.query.text = Empty))]
+    async fn hold(
+        &self,
+        cart_id: i64,
+        product_id: i64,
+        quantity: i32,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Reservation, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = sqlite_query_as!(ReservationRow,
+            r#"
+            INSERT INTO stock_reservations (cart_id, product_id, quantity, expires_at)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT (cart_id, product_id)
+            DO UPDATE SET quantity = excluded.quantity, expires_at = excluded.expires_at
+            RETURNING id, cart_id, product_id, quantity, expires_at
+            "#
+        )
+        .bind(cart_id)
+        .bind(product_id)
+        .bind(quantity)
+        .bind(expires_at.timestamp_millis())
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(Reservation {
+            id: row.id,
+            cart_id: row.cart_id,
+            product_id: row.product_id,
+            quantity: row.quantity,
+            expires_at: from_millis(row.expires_at)?,
+        })
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "DELETE", db.collection.name = "stock_reservations", db.query.text = Empty))]
+    async fn release(&self, cart_id: i64, product_id: i64) -> Result<bool, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = sqlite_query!(
+            r#"
+            DELETE FROM stock_reservations
+            WHERE cart_id = ?1 AND product_id = ?2
+            "#
+        )
+        .bind(cart_id)
+        .bind(product_id)
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "stock_reservations", db.query.text = Empty))]
+    async fn reserved(
+        &self,
+        product_ids: &[i64],
+        except_cart: Option<i64>,
+        now: DateTime<Utc>,
+    ) -> Result<HashMap<i64, i32>, DomainError> {
+        // the ids go in as one JSON array, SQLite has no array parameters
+        let product_ids = serde_json::to_string(product_ids).map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        let mut conn = self.db.acquire().await?;
+        let rows = sqlite_query_as!((i64, i32),
+            r#"
+            SELECT product_id, SUM(quantity)
+            FROM stock_reservations
+            WHERE product_id IN (SELECT value FROM json_each(?1)) AND expires_at > ?3 AND cart_id IS NOT ?2
+            GROUP BY product_id
+            "#
+        )
+        .bind(product_ids)
+        .bind(except_cart)
+        .bind(now.timestamp_millis())
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(rows.into_iter().collect())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "DELETE", db.collection.name = "stock_reservations", db.query.text = Empty))]
+    async fn release_expired(&self, now: DateTime<Utc>) -> Result<u64, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let result = sqlite_query!(
+            r#"
+            DELETE FROM stock_reservations
+            WHERE expires_at <= ?1
+            "#
+        )
+        .bind(now.timestamp_millis())
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sqlx::{Sqlite, SqlitePool};
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+use crate::domain::uow::{TxRepositories, TxWork, UnitOfWork};
+use crate::domain::DomainError;
+use crate::infra::repository::db::{db_error, retry_on_conflict, DbHandle, SharedTx};
+
+use super::{
+    SqliteCartRepository, SqliteCategoryRepository, SqliteInventoryRepository, SqliteNotificationRepository, SqliteOrderRepository, SqliteOutboxRepository,
+    SqlitePaymentRepository, SqliteProductRepository, SqlitePromotionRepository, SqlitePurchasingRepository, SqliteReservationRepository, SqliteUserRepository,
+    SqliteWarehouseRepository,
+};
+
+/// Runs each unit of work in one transaction. SQLite transactions are
+/// always serializable; a `SQLITE_BUSY` from a competing writer is retried.
+#[derive(Clone)]
+pub struct SqliteUnitOfWork {
+    pool: SqlitePool,
+}
+
+impl SqliteUnitOfWork {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    async fn attempt(&self, work: &mut TxWork<'_>) -> Result<(), DomainError> {
+        let tx = self.pool.begin().await.map_err(db_error)?;
+
+        let shared: SharedTx<Sqlite> = Arc::new(Mutex::new(Some(tx)));
+        let db = DbHandle::Tx(shared.clone());
+        let repos = TxRepositories {
+            users: Arc::new(SqliteUserRepository::with_handle(db.clone())),
+            categories: Arc::new(SqliteCategoryRepository::with_handle(db.clone())),
+            products: Arc::new(SqliteProductRepository::with_handle(db.clone())),
+            inventory: Arc::new(SqliteInventoryRepository::with_handle(db.clone())),
+            warehouses: Arc::new(SqliteWarehouseRepository::with_handle(db.clone())),
+            carts: Arc::new(SqliteCartRepository::with_handle(db.clone())),
+            reservations: Arc::new(SqliteReservationRepository::with_handle(db.clone())),
+            orders: Arc::new(SqliteOrderRepository::with_handle(db.clone())),
+            payments: Arc::new(SqlitePaymentRepository::with_handle(db.clone())),
+            notifications: Arc::new(SqliteNotificationRepository::with_handle(db.clone())),
+            purchasing: Arc::new(SqlitePurchasingRepository::with_handle(db.clone())),
+            promotions: Arc::new(SqlitePromotionRepository::with_handle(db.clone())),
+            outbox: Arc::new(SqliteOutboxRepository::with_handle(db)),
+        };
+        let result = work(&repos).await;
+
+        let tx = shared
+            .lock()
+            .await
+            .take()
+            .ok_or_else(|| DomainError::Unexpected("transaction already finished".into()))?;
+        match result {
+            Ok(()) => tx.commit().await.map_err(db_error),
+            Err(e) => {
+                if let Err(rollback) = tx.rollback().await {
+                    tracing::warn!(error = %rollback, "rollback failed");
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for SqliteUnitOfWork {
+    #[instrument(skip_all, err, fields(db.system = "sqlite"))]
+    async fn run(&self, work: &mut TxWork<'_>) -> Result<(), DomainError> {
+        retry_on_conflict(work, |work| Box::pin(self.attempt(work))).await
+    }
+}
@@ -0,0 +1,187 @@
+use async_trait::async_trait;
+use sqlx::{FromRow, Sqlite, SqlitePool};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::DomainError;
+use crate::domain::page::Page;
+use crate::domain::user::{User, UserRepository};
+use crate::infra::crypto::hash_password;
+use crate::infra::db_trace::{sqlite_query, sqlite_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+
+#[derive(FromRow)]
+struct UserRow {
+    id: i64,
+    username: String,
+    password: String,
+    active: bool,
+    admin: bool,
+}
+
+impl From<UserRow> for User {
+    fn from(row: UserRow) -> Self {
+        User { id: row.id, username: row.username, password: row.password.into(), active: row.active, admin: row.admin }
+    }
+}
+
+#[derive(Clone)]
+pub struct SqliteUserRepository {
+    db: DbHandle<Sqlite>,
+}
+
+impl SqliteUserRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Sqlite>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl UserRepository for SqliteUserRepository {
+    #[instrument(skip(self, password), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "users", db.query.text = Empty))]
+    async fn create(&self, username: String, password: String) -> Result<i64, DomainError> {
+        let hashed = hash_password(password).await?;
+
+        let mut conn = self.db.acquire().await?;
+        let id: (i64,) = sqlite_query_as!((i64,),
+            r#"
+            INSERT INTO users (username, password, active)
+            VALUES (?1, ?2, TRUE)
+            RETURNING id
+            "#
+        )
+        .bind(username)
+        .bind(hashed)
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(id.0)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "users", db.query.text = Empty))]
+    async fn get_by_id(&self, id: i64) -> Result<Option<User>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = sqlite_query_as!(UserRow,
+            r#"
+            SELECT id, username, password, active, admin
+            FROM users
+            WHERE id = ?1
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.map(User::from))
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "users", db.query.text = Empty))]
+    async fn get_by_username(&self, username: String) -> Result<Option<User>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = sqlite_query_as!(UserRow,
+            r#"
+            SELECT id, username, password, active, admin
+            FROM users
+            WHERE username = ?1
+            "#
+        )
+        .bind(username)
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.map(User::from))
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "users", db.query.text = Empty))]
+    async fn get_all_users(&self, page: Page) -> Result<Vec<User>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = sqlite_query_as!(UserRow,
+            r#"
+            SELECT id, username, password, active, admin
+            FROM users
+            ORDER BY id
+            LIMIT ?1 OFFSET ?2
+            "#
+        )
+        .bind(page.limit.unwrap_or(-1))
+        .bind(page.offset)
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(rows.into_iter().map(User::from).collect())
+    }
+
+    #[instrument(skip(self, password), err, fields(db.system = "sqlite", db.operation.name = "UPDATE", db.collection.name = "users", db.query.text = Empty))]
+    async fn update(&self, id: i64, username: String, password: String) -> Result<User, DomainError> {
+        let hashed = hash_password(password).await?;
+
+        let mut conn = self.db.acquire().await?;
+        let row = sqlite_query_as!(UserRow,
+            r#"
+            UPDATE users
+            SET username = ?1, password = ?2
+            WHERE id = ?3
+            RETURNING id, username, password, active, admin
+            "#
+        )
+        .bind(username)
+        .bind(hashed)
+        .bind(id)
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.into())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "DELETE", db.collection.name = "users", db.query.text = Empty))]
+    async fn delete(&self, id: i64) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query!(
+            r#"
+            DELETE FROM users
+            WHERE id = ?1
+            "#
+        )
+        .bind(id)
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "UPDATE", db.collection.name = "users", db.query.text = Empty))]
+    async fn set_admin(&self, id: i64, admin: bool) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        sqlite_query!(
+            r#"
+            UPDATE users
+            SET admin = ?2
+            WHERE id = ?1
+            "#
+        )
+        .bind(id)
+        .bind(admin)
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+use async_trait::async_trait;
+use sqlx::{FromRow, Sqlite, SqlitePool};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::warehouse::{GeoPoint, Warehouse, WarehouseRepository, WarehouseStock};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{sqlite_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+
+#[derive(FromRow)]
+struct WarehouseRow {
+    id: i64,
+    name: String,
+    priority: i32,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+}
+
+impl From<WarehouseRow> for Warehouse {
+    fn from(row: WarehouseRow) -> Self {
+        let location = row.latitude.zip(row.longitude).map(|(latitude, longitude)| GeoPoint { latitude, longitude });
+        Warehouse { id: row.id, name: row.name, priority: row.priority, location }
+    }
+}
+
+#[derive(FromRow)]
+struct WarehouseStockRow {
+    warehouse_id: i64,
+    warehouse: String,
+    product_id: i64,
+    stock: i32,
+}
+
+/// Same as the Postgres repository.
+#[derive(Clone)]
+pub struct SqliteWarehouseRepository {
+    db: DbHandle<Sqlite>,
+}
+
+impl SqliteWarehouseRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Sqlite>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl WarehouseRepository for SqliteWarehouseRepository {
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "INSERT", db.collection.name = "warehouses", db.query.text = Empty))]
+    async fn create(&self, warehouse: Warehouse) -> Result<i64, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let id: (i64,) = sqlite_query_as!((i64,),
+            r#"
+            INSERT INTO warehouses (name, priority, latitude, longitude)
+            VALUES (?1, ?2, ?3, ?4)
+            RETURNING id
+            "#
+        )
+        .bind(warehouse.name)
+        .bind(warehouse.priority)
+        .bind(warehouse.location.map(|l| l.latitude))
+        .bind(warehouse.location.map(|l| l.longitude))
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(id.0)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "warehouses", db.query.text = Empty))]
+    async fn get(&self, id: i64) -> Result<Option<Warehouse>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = sqlite_query_as!(WarehouseRow,
+            r#"
+            SELECT id, name, priority, latitude, longitude
+            FROM warehouses
+            WHERE id = ?1
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.map(Warehouse::from))
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "warehouses", db.query.text = Empty))]
+    async fn list(&self) -> Result<Vec<Warehouse>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = sqlite_query_as!(WarehouseRow,
+            r#"
+            SELECT id, name, priority, latitude, longitude
+            FROM warehouses
+            ORDER BY priority, id
+            "#
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(rows.into_iter().map(Warehouse::from).collect())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "sqlite", db.operation.name = "SELECT", db.collection.name = "warehouse_stock", db.query.text = Empty))]
+    async fn stock(&self, product_ids: &[i64]) -> Result<Vec<WarehouseStock>, DomainError> {
+        // the ids go in as one JSON array, SQLite has no array parameters
+        let product_ids = serde_json::to_string(product_ids).map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        let mut conn = self.db.acquire().await?;
+        let rows = sqlite_query_as!(WarehouseStockRow,
+            r#"
+            SELECT s.warehouse_id, w.name AS warehouse, s.product_id, s.stock
+            FROM warehouse_stock s
+            JOIN warehouses w ON w.id = s.warehouse_id
+            WHERE s.product_id IN (SELECT value FROM json_each(?1)) AND s.stock <> 0
+            ORDER BY s.product_id, w.priority, w.id
+            "#
+        )
+        .bind(product_ids)
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| WarehouseStock {
+                warehouse_id: row.warehouse_id,
+                warehouse: row.warehouse,
+                product_id: row.product_id,
+                stock: row.stock,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sqlx::{Executor, PgPool, Postgres};
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+use crate::domain::uow::{TxRepositories, TxWork, UnitOfWork};
+use crate::domain::DomainError;
+use crate::infra::repository::cart::PostgresCartRepository;
+use crate::infra::repository::category::PostgresCategoryRepository;
+use crate::infra::repository::db::{db_error, retry_on_conflict, DbHandle, SharedTx};
+use crate::infra::repository::inventory::PostgresInventoryRepository;
+use crate::infra::repository::notification::PostgresNotificationRepository;
+use crate::infra::repository::order::PostgresOrderRepository;
+use crate::infra::repository::outbox::PostgresOutboxRepository;
+use crate::infra::repository::payment::PostgresPaymentRepository;
+use crate::infra::repository::product::PostgresProductRepository;
+use crate::infra::repository::promotion::PostgresPromotionRepository;
+use crate::infra::repository::purchasing::PostgresPurchasingRepository;
+use crate::infra::repository::reservation::PostgresReservationRepository;
+use crate::infra::repository::user::PostgresUserRepository;
+use crate::infra::repository::warehouse::PostgresWarehouseRepository;
+
+/// Runs each unit of work in one `SERIALIZABLE` transaction.
+#[derive(Clone)]
+pub struct PostgresUnitOfWork {
+    pool: PgPool,
+}
+
+impl PostgresUnitOfWork {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    async fn attempt(&self, work: &mut TxWork<'_>) -> Result<(), DomainError> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        tx.execute("SET TRANSACTION ISOLATION LEVEL SERIALIZABLE")
+            .await
+            .map_err(db_error)?;
+
+        let shared: SharedTx<Postgres> = Arc::new(Mutex::new(Some(tx)));
+        let db = DbHandle::Tx(shared.clone());
+        let repos = TxRepositories {
+            users: Arc::new(PostgresUserRepository::with_handle(db.clone())),
+            categories: Arc::new(PostgresCategoryRepository::with_handle(db.clone())),
+            products: Arc::new(PostgresProductRepository::with_handle(db.clone())),
+            inventory: Arc::new(PostgresInventoryRepository::with_handle(db.clone())),
+            warehouses: Arc::new(PostgresWarehouseRepository::with_handle(db.clone())),
+            carts: Arc::new(PostgresCartRepository::with_handle(db.clone())),
+            reservations: Arc::new(PostgresReservationRepository::with_handle(db.clone())),
+            orders: Arc::new(PostgresOrderRepository::with_handle(db.clone())),
+            payments: Arc::new(PostgresPaymentRepository::with_handle(db.clone())),
+            notifications: Arc::new(PostgresNotificationRepository::with_handle(db.clone())),
+            purchasing: Arc::new(PostgresPurchasingRepository::with_handle(db.clone())),
+            promotions: Arc::new(PostgresPromotionRepository::with_handle(db.clone())),
+            outbox: Arc::new(PostgresOutboxRepository::with_handle(db)),
+        };
+        let result = work(&repos).await;
+
+        let tx = shared
+            .lock()
+            .await
+            .take()
+            .ok_or_else(|| DomainError::Unexpected("transaction already finished".into()))?;
+        match result {
+            Ok(()) => tx.commit().await.map_err(db_error),
+            Err(e) => {
+                if let Err(rollback) = tx.rollback().await {
+                    tracing::warn!(error = %rollback, "rollback failed");
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for PostgresUnitOfWork {
+    #[instrument(skip_all, err, fields(db.system = "postgresql"))]
+    async fn run(&self, work: &mut TxWork<'_>) -> Result<(), DomainError> {
+        retry_on_conflict(work, |work| Box::pin(self.attempt(work))).await
+    }
+}
@@ -1,29 +1,38 @@
 use async_trait::async_trait;
-use sqlx::PgPool;
-use tracing::instrument;
+use sqlx::{PgPool, Postgres};
+use tracing::{field::Empty, instrument};
 
 use crate::domain::DomainError;
+use crate::domain::page::Page;
 use crate::domain::user::{User, UserRepository};
 use crate::infra::crypto::hash_password;
+use crate::infra::db_trace::{pg_query, pg_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
 
 #[derive(Clone)]
 pub struct PostgresUserRepository {
-    pool: PgPool,
+    db: DbHandle<Postgres>,
 }
 
 impl PostgresUserRepository {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Postgres>) -> Self {
+        Self { db }
     }
 }
 
 #[async_trait]
 impl UserRepository for PostgresUserRepository {
-    #[instrument(skip(self, password), err, fields(db = "postgres"))]
+    #[instrument(skip(self, password), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "users", db.query.text = Empty))]
     async fn create(&self, username: String, password: String) -> Result<i64, DomainError> {
         let hashed = hash_password(password).await?;
 
-        let row = sqlx::query!(
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query!(
             r#"
             INSERT INTO users (username, password, active)
             VALUES ($1, $2, TRUE)
@@ -32,96 +41,131 @@ impl UserRepository for PostgresUserRepository {
             username,
             hashed
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *conn)
+        .traced()
         .await
-        .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        .map_err(db_error)?;
 
         Ok(row.id)
     }
 
-    #[instrument(skip(self), err, fields(db = "postgres"))]
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "users", db.query.text = Empty))]
     async fn get_by_id(&self, id: i64) -> Result<Option<User>, DomainError> {
-        let row = sqlx::query_as!(User,
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query_as!(User,
             r#"
-            SELECT id, username, password, active
+            SELECT id, username, password, active, admin
             FROM users
             WHERE id = $1
             "#,
             id
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *conn)
+        .traced()
         .await
-        .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        .map_err(db_error)?;
 
         Ok(row)
     }
 
-    #[instrument(skip(self), err, fields(db = "postgres"))]
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "users", db.query.text = Empty))]
     async fn get_by_username(&self, username: String) -> Result<Option<User>, DomainError> {
-        let row = sqlx::query_as!(User,
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query_as!(User,
             r#"
-            SELECT id, username, password, active
+            SELECT id, username, password, active, admin
             FROM users
             WHERE username = $1
             "#,
             username
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *conn)
+        .traced()
         .await
-        .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        .map_err(db_error)?;
 
         Ok(row)
     }
 
-    #[instrument(skip(self), err, fields(db = "postgres"))]
-    async fn get_all_users(&self) -> Result<Vec<User>, DomainError> {
-        let rows = sqlx::query_as!(User,
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "users", db.query.text = Empty))]
+    async fn get_all_users(&self, page: Page) -> Result<Vec<User>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = pg_query_as!(User,
             r#"
-            SELECT id, username, password, active
+            SELECT id, username, password, active, admin
             FROM users
-            "#
+            ORDER BY id
+            LIMIT $1 OFFSET $2
+            "#,
+            page.limit,
+            page.offset
         )
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *conn)
+        .traced()
         .await
-        .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        .map_err(db_error)?;
 
         Ok(rows)
     }
 
-    #[instrument(skip(self, password), err, fields(db = "postgres"))]
+    #[instrument(skip(self, password), err, fields(db.system = "postgresql", db.operation.name = "UPDATE", db.collection.name = "users", db.query.text = Empty))]
     async fn update(&self, id: i64, username: String, password: String) -> Result<User, DomainError> {
         let hashed = hash_password(password).await?;
 
-        let row = sqlx::query_as!(User,
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query_as!(User,
             r#"
             UPDATE users
             SET username = $1, password = $2
             WHERE id = $3
-            RETURNING id, username, password, active
+            RETURNING id, username, password, active, admin
             "#,
             username,
             hashed,
             id
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *conn)
+        .traced()
         .await
-        .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        .map_err(db_error)?;
 
         Ok(row)
     }
 
-    #[instrument(skip(self), err, fields(db = "postgres"))]
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "DELETE", db.collection.name = "users", db.query.text = Empty))]
     async fn delete(&self, id: i64) -> Result<(), DomainError> {
-        sqlx::query!(
+        let mut conn = self.db.acquire().await?;
+        pg_query!(
             r#"
             DELETE FROM users
             WHERE id = $1
             "#,
             id
         )
-        .execute(&self.pool)
+        .execute(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "UPDATE", db.collection.name = "users", db.query.text = Empty))]
+    async fn set_admin(&self, id: i64, admin: bool) -> Result<(), DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query!(
+            r#"
+            UPDATE users
+            SET admin = $2
+            WHERE id = $1
+            "#,
+            id,
+            admin
+        )
+        .execute(&mut *conn)
+        .traced()
         .await
-        .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        .map_err(db_error)?;
 
         Ok(())
     }
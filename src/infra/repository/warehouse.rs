@@ -0,0 +1,123 @@
+use async_trait::async_trait;
+use sqlx::{PgPool, Postgres};
+use tracing::{field::Empty, instrument};
+
+use crate::domain::warehouse::{GeoPoint, Warehouse, WarehouseRepository, WarehouseStock};
+use crate::domain::DomainError;
+use crate::infra::db_trace::{pg_query, pg_query_as, DbTraceExt};
+use crate::infra::repository::db::{db_error, DbHandle};
+
+struct WarehouseRow {
+    id: i64,
+    name: String,
+    priority: i32,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+}
+
+impl From<WarehouseRow> for Warehouse {
+    fn from(row: WarehouseRow) -> Self {
+        let location = row.latitude.zip(row.longitude).map(|(latitude, longitude)| GeoPoint { latitude, longitude });
+        Warehouse { id: row.id, name: row.name, priority: row.priority, location }
+    }
+}
+
+#[derive(Clone)]
+pub struct PostgresWarehouseRepository {
+    db: DbHandle<Postgres>,
+}
+
+impl PostgresWarehouseRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { db: DbHandle::Pool(pool) }
+    }
+
+    /// Repository running on `db`, e.g. a unit of work's transaction.
+    pub fn with_handle(db: DbHandle<Postgres>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl WarehouseRepository for PostgresWarehouseRepository {
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "INSERT", db.collection.name = "warehouses", db.query.text = Empty))]
+    async fn create(&self, warehouse: Warehouse) -> Result<i64, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query!(
+            r#"
+            INSERT INTO warehouses (name, priority, latitude, longitude)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
+            "#,
+            warehouse.name,
+            warehouse.priority,
+            warehouse.location.map(|l| l.latitude),
+            warehouse.location.map(|l| l.longitude)
+        )
+        .fetch_one(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.id)
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "warehouses", db.query.text = Empty))]
+    async fn get(&self, id: i64) -> Result<Option<Warehouse>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let row = pg_query_as!(
+            WarehouseRow,
+            r#"
+            SELECT id, name, priority, latitude, longitude
+            FROM warehouses
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.map(Warehouse::from))
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "warehouses", db.query.text = Empty))]
+    async fn list(&self) -> Result<Vec<Warehouse>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        let rows = pg_query_as!(
+            WarehouseRow,
+            r#"
+            SELECT id, name, priority, latitude, longitude
+            FROM warehouses
+            ORDER BY priority, id
+            "#
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)?;
+
+        Ok(rows.into_iter().map(Warehouse::from).collect())
+    }
+
+    #[instrument(skip(self), err, fields(db.system = "postgresql", db.operation.name = "SELECT", db.collection.name = "warehouse_stock", db.query.text = Empty))]
+    async fn stock(&self, product_ids: &[i64]) -> Result<Vec<WarehouseStock>, DomainError> {
+        let mut conn = self.db.acquire().await?;
+        pg_query_as!(
+            WarehouseStock,
+            r#"
+            SELECT s.warehouse_id, w.name AS warehouse, s.product_id, s.stock
+            FROM warehouse_stock s
+            JOIN warehouses w ON w.id = s.warehouse_id
+            WHERE s.product_id = ANY($1) AND s.stock <> 0
+            ORDER BY s.product_id, w.priority, w.id
+            "#,
+            product_ids
+        )
+        .fetch_all(&mut *conn)
+        .traced()
+        .await
+        .map_err(db_error)
+    }
+}
@@ -0,0 +1,45 @@
+//! Per-request context carried in a task-local, so code without access to
+//! the HTTP request (error mapping, log processors) can still read it.
+
+use std::future::Future;
+
+use ulid::Ulid;
+
+/// Longest caller-supplied `X-Request-Id` we accept.
+const MAX_LEN: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: RequestId;
+}
+
+/// Correlation id for one request: caller-supplied or a fresh ULID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(String);
+
+impl RequestId {
+    pub fn generate() -> Self {
+        Self(Ulid::new().to_string())
+    }
+
+    /// Accept a caller-supplied id if it is short, visible ASCII.
+    pub fn parse(value: &str) -> Option<Self> {
+        let valid = !value.is_empty()
+            && value.len() <= MAX_LEN
+            && value.bytes().all(|b| b.is_ascii_graphic());
+        valid.then(|| Self(value.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Run `f` with `id` as the current request id.
+pub async fn scope<F: Future>(id: RequestId, f: F) -> F::Output {
+    REQUEST_ID.scope(id, f).await
+}
+
+/// The request id of the request being handled on this task, if any.
+pub fn current_request_id() -> Option<RequestId> {
+    REQUEST_ID.try_with(RequestId::clone).ok()
+}
@@ -7,18 +7,24 @@ use opentelemetry_otlp::{
     LogExporter,
     MetricExporter,
 };
+use opentelemetry::logs::LogRecord as _;
+use opentelemetry::InstrumentationScope;
 use opentelemetry_sdk::{
-    logs::SdkLoggerProvider,
+    error::OTelSdkResult,
+    logs::{LogProcessor, SdkLogRecord, SdkLoggerProvider},
     metrics::{PeriodicReader, SdkMeterProvider},
     propagation::TraceContextPropagator,
     trace::SdkTracerProvider,
     Resource,
 };
 use opentelemetry_semantic_conventions::attribute::SERVICE_NAME;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
 use tonic::metadata::MetadataMap;
 use std::env;
 
+use crate::infra::redact::{RedactionLayer, Redactor};
+use crate::infra::request_context::current_request_id;
+
 pub struct TelemetryProviders {
     pub tracer_provider: SdkTracerProvider,
     pub logger_provider: SdkLoggerProvider,
@@ -33,6 +39,24 @@ impl TelemetryProviders {
     }
 }
 
+/// Adds `request.id` to every log record emitted while handling a request,
+/// so logs can be found by the id returned in `X-Request-Id`.
+/// Runs before the batch processor, on the task that emitted the log.
+#[derive(Debug)]
+struct RequestIdLogProcessor;
+
+impl LogProcessor for RequestIdLogProcessor {
+    fn emit(&self, record: &mut SdkLogRecord, _scope: &InstrumentationScope) {
+        if let Some(id) = current_request_id() {
+            record.add_attribute("request.id", id.as_str().to_string());
+        }
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        Ok(())
+    }
+}
+
 fn resource() -> Resource {
     let service_name = env::var("OTEL_SERVICE_NAME")
         .unwrap_or_else(|_| "rust-just-learn".to_string());
@@ -47,6 +71,7 @@ fn endpoint() -> String {
 }
 
 /// Build gRPC metadata with Authorization header from HYPERDX_API_KEY (if set)
+#[allow(clippy::collapsible_if)]
 fn otlp_metadata() -> MetadataMap {
     let mut map = MetadataMap::new();
     if let Ok(api_key) = env::var("HYPERDX_API_KEY") {
@@ -85,6 +110,7 @@ pub fn init_telemetry() -> Result<TelemetryProviders, Box<dyn std::error::Error
         .with_metadata(metadata.clone())
         .build()?;
     let logger_provider = SdkLoggerProvider::builder()
+        .with_log_processor(RequestIdLogProcessor)
         .with_batch_exporter(log_exporter)
         .with_resource(resource.clone())
         .build();
@@ -109,11 +135,15 @@ pub fn init_telemetry() -> Result<TelemetryProviders, Box<dyn std::error::Error
     let env_filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("info"));
 
+    // Everything that leaves the process goes through the redaction layer
+    let exporters = tracing_opentelemetry::layer()
+        .with_tracer(tracer)
+        .and_then(opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge::new(&logger_provider))
+        .and_then(tracing_subscriber::fmt::layer().with_ansi(true));
+
     tracing_subscriber::registry()
         .with(env_filter)
-        .with(tracing_opentelemetry::layer().with_tracer(tracer))
-        .with(opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge::new(&logger_provider))
-        .with(tracing_subscriber::fmt::layer().with_ansi(true))
+        .with(RedactionLayer::new(exporters, Redactor::from_env()?))
         .try_init()?;
 
     Ok(TelemetryProviders { tracer_provider, logger_provider, meter_provider })
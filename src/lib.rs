@@ -0,0 +1,15 @@
+//! The rust-just-learn service as a library: the `domain` types, the
+//! `usecases` built on them, the `infra` implementations (repositories,
+//! JWT, telemetry) and the REST `adapters`. [`App::builder`] assembles them;
+//! the `rust-just-learn` server and the `just-learn-admin` CLI are thin
+//! binaries on top.
+
+pub mod adapters;
+pub mod app;
+pub mod config;
+pub mod domain;
+pub mod infra;
+pub mod usecases;
+
+pub use app::{App, AppBuilder};
+pub use config::Config;
@@ -1,75 +1,83 @@
-mod adapters;
-mod domain;
-mod infra;
-mod usecases;
-
-use std::sync::Arc;
-
-use adapters::restapi::{router, AppState};
-use infra::repository::user::PostgresUserRepository;
-use infra::repository::category::PostgresCategoryRepository;
-use infra::repository::product::PostgresProductRepository;
-use usecases::user_service::UserService;
-use usecases::category_service::CategoryService;
-use usecases::product_service::ProductService;
-use sqlx::postgres::PgPoolOptions;
+use rust_just_learn::{App, Config};
+use rust_just_learn::infra::repository::backend::Backend;
+use rust_just_learn::infra::repository::cache_sync::PgCacheSync;
+use rust_just_learn::infra::repository::cached::RepositoryCache;
+use rust_just_learn::infra::events::{sinks_from_env, InProcessBus};
+use rust_just_learn::infra::payment::provider_from_env;
 use dotenvy::dotenv;
-use std::env;
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
 
+    let config = match Config::from_env() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Invalid configuration: {e}");
+            std::process::exit(2);
+        }
+    };
+
     // Initialize OpenTelemetry — keep providers alive until shutdown
-    let otel = infra::telemetry::init_telemetry()
+    let otel = rust_just_learn::infra::telemetry::init_telemetry()
         .expect("Failed to initialize OpenTelemetry");
 
     tracing::info!("OpenTelemetry initialized");
 
-    let url_db = env::var("DATABASE_URL")
-        .expect("DATABASE_URL must be set");
-    let max_connection = env::var("MAX_CONNECTION")
-        .expect("MAX_CONNECTIONS must be set")
-        .parse::<u32>()
-        .expect("MAX_CONNECTIONS must be a number");
-    let listen_port = env::var("LISTEN_PORT")
-        .expect("LISTEN_PORT must be set");
-    let address = format!("0.0.0.0:{}", listen_port);
-
-    let pool = PgPoolOptions::new()
-        .max_connections(max_connection)
-        .connect(&url_db)
+    let address = config.listen_address();
+    let backend = Backend::connect(&config.database_url, config.max_connections)
         .await
         .expect("Failed to connect to database");
+    match &backend {
+        Backend::Memory(..) => tracing::warn!("Using in-memory repositories, data is not persisted"),
+        // the file is created on first run; Postgres is migrated with sqlx-cli or just-learn-admin
+        Backend::Sqlite(pool) => {
+            backend.migrate().await.expect("Failed to migrate SQLite database");
+            rust_just_learn::infra::db_trace::register_pool_metrics(pool);
+        }
+        Backend::Postgres(pool) => rust_just_learn::infra::db_trace::register_pool_metrics(pool),
+    }
 
-    let user_repo = Arc::new(PostgresUserRepository::new(pool.clone()));
-    let user_service = UserService::new(user_repo);
-
-    let category_repo = Arc::new(PostgresCategoryRepository::new(pool.clone()));
-    let category_service = CategoryService::new(category_repo);
-
-    let product_repo = Arc::new(PostgresProductRepository::new(pool));
-    let product_service = ProductService::new(product_repo);
+    // Outbox relay: publish domain events committed by the services
+    let bus = InProcessBus::new(1024);
+    let sinks = sinks_from_env(&bus).expect("Invalid OUTBOX_SINKS configuration");
+    let mut events = bus.subscribe();
+    tokio::spawn(async move {
+        while let Ok(event) = events.recv().await {
+            tracing::debug!(event_id = event.id, event_type = %event.event_type, aggregate_id = event.aggregate_id, "domain event");
+        }
+    });
 
-    let state = AppState { user_service, category_service, product_service };
-    let app = router(state);
+    let mut builder = config.configure(App::builder())
+        .repositories(backend.repositories())
+        .event_bus(bus)
+        .outbox_sinks(sinks)
+        .payment_provider(provider_from_env().expect("Invalid PAYMENT_PROVIDER configuration"));
+    // Read-through cache of categories and products; CACHE_TTL_SECS=0 turns it off
+    if let Some((entries, ttl)) = config.cache {
+        let cache = RepositoryCache::new(entries, ttl);
+        if let Backend::Postgres(pool) = &backend {
+            // other replicas write to the same database
+            builder = builder.cache_sync(PgCacheSync::new(pool.clone(), cache.clone()));
+        }
+        builder = builder.cache(cache);
+    }
+    let app = builder.build().expect("Invalid app configuration");
 
     let listener = tokio::net::TcpListener::bind(&address)
         .await
         .unwrap();
     tracing::info!("Listening on http://{}", address);
 
-    // Run server until Ctrl+C, then gracefully flush OTel before exit
-    tokio::select! {
-        result = axum::serve(listener, app) => {
-            if let Err(e) = result {
-                tracing::error!("Server error: {e}");
-            }
-        }
-        _ = tokio::signal::ctrl_c() => {
-            tracing::info!("Shutting down — flushing telemetry...");
-        }
+    // Run until Ctrl+C, then gracefully flush OTel before exit
+    let shutdown = async {
+        let _ = tokio::signal::ctrl_c().await;
+        tracing::info!("Shutting down — flushing telemetry...");
+    };
+    if let Err(e) = app.serve(listener, shutdown).await {
+        tracing::error!("Server error: {e}");
     }
+    backend.close().await;
 
     // Flush and shutdown OTel providers BEFORE process exits
     otel.shutdown();
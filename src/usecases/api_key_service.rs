@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use crate::domain::api_key::{ApiKey, ApiKeyRepository};
+use crate::domain::DomainError;
+use crate::infra::crypto::{random_hex, sha256_hex};
+
+/// Keys look like `jl_<prefix>_<secret>`.
+const KEY_PREFIX: &str = "jl_";
+
+/// A freshly issued key; `key` is not stored and cannot be shown again.
+#[derive(Debug, Clone)]
+pub struct IssuedApiKey {
+    pub id: i64,
+    pub name: String,
+    pub key: String,
+}
+
+#[derive(Clone)]
+pub struct ApiKeyService {
+    repo: Arc<dyn ApiKeyRepository>,
+}
+
+impl ApiKeyService {
+    pub fn new(repo: Arc<dyn ApiKeyRepository>) -> Self {
+        Self { repo }
+    }
+
+    pub async fn issue(&self, name: String) -> Result<IssuedApiKey, DomainError> {
+        if name.trim().is_empty() {
+            return Err(DomainError::Validation("name is required".into()));
+        }
+        let prefix = random_hex(4);
+        let key = format!("{KEY_PREFIX}{prefix}_{}", random_hex(24));
+        let id = self.repo.create(name.clone(), prefix, sha256_hex(&key)).await?;
+        Ok(IssuedApiKey { id, name, key })
+    }
+
+    pub async fn list(&self) -> Result<Vec<ApiKey>, DomainError> {
+        self.repo.get_all().await
+    }
+
+    pub async fn revoke(&self, id: i64) -> Result<(), DomainError> {
+        if !self.repo.revoke(id).await? {
+            return Err(DomainError::NotFound);
+        }
+        Ok(())
+    }
+
+    /// Whether `key` is an issued, unrevoked key.
+    pub async fn verify(&self, key: &str) -> Result<bool, DomainError> {
+        let Some((prefix, _)) = key.strip_prefix(KEY_PREFIX).and_then(|rest| rest.split_once('_')) else {
+            return Ok(false);
+        };
+        Ok(self.repo.active_hash(prefix).await? == Some(sha256_hex(key)))
+    }
+}
@@ -0,0 +1,359 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::domain::cart::{Cart, CartLine, CartOwner, PricedCart, PricedLine};
+use crate::domain::product::Product;
+use crate::domain::promotion::{Pricing, PricingLine};
+use crate::domain::uow::{TxRepositories, UnitOfWork};
+use crate::domain::DomainError;
+use crate::infra::crypto::random_hex;
+use crate::usecases::promotion_service::{normalize_code, price_with_promotions, unusable};
+
+/// Carts not written to for this long are treated as gone.
+pub(crate) const DEFAULT_IDLE_TIMEOUT_DAYS: i64 = 30;
+
+/// How long a cart holds its products after it was last looked at.
+pub(crate) const DEFAULT_RESERVATION_MINUTES: i64 = 15;
+
+#[derive(Clone)]
+pub struct CartService {
+    uow: Arc<dyn UnitOfWork>,
+    idle_timeout: Duration,
+    reservation_ttl: Duration,
+    shipping_fee: f64,
+}
+
+impl CartService {
+    pub fn new(uow: Arc<dyn UnitOfWork>) -> Self {
+        Self {
+            uow,
+            idle_timeout: Duration::days(DEFAULT_IDLE_TIMEOUT_DAYS),
+            reservation_ttl: Duration::minutes(DEFAULT_RESERVATION_MINUTES),
+            shipping_fee: 0.0,
+        }
+    }
+
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Every read or write of a cart reserves its available lines for this
+    /// long, so other shoppers cannot take the stock meanwhile.
+    pub fn with_reservation_ttl(mut self, reservation_ttl: Duration) -> Self {
+        self.reservation_ttl = reservation_ttl;
+        self
+    }
+
+    /// Flat fee added to every cart with something in it, unless a
+    /// promotion waives it; none by default.
+    pub fn with_shipping_fee(mut self, shipping_fee: f64) -> Self {
+        self.shipping_fee = shipping_fee;
+        self
+    }
+
+    /// The cart re-priced from the current products; empty if there is none.
+    /// Lines whose price changed keep the new price, so the change is
+    /// reported once.
+    pub async fn get(&self, owner: CartOwner) -> Result<PricedCart, DomainError> {
+        let (idle_timeout, ttl, shipping) = (self.idle_timeout, self.reservation_ttl, self.shipping_fee);
+        self.uow
+            .transaction(move |tx| {
+                let owner = owner.clone();
+                Box::pin(async move {
+                    match live_cart(tx, &owner, idle_timeout).await? {
+                        Some(cart) => price(tx, cart, true, Utc::now() + ttl, shipping).await,
+                        None => Ok(PricedCart { owner, lines: Vec::new(), coupon: None, pricing: Pricing::default() }),
+                    }
+                })
+            })
+            .await
+    }
+
+    /// Add `quantity` of a product, to the line already there if any. Without
+    /// a cart a new one is started; for a guest that means a new token, in
+    /// the returned owner, so guests cannot choose their own.
+    pub async fn add_item(
+        &self,
+        owner: Option<CartOwner>,
+        product_id: i64,
+        quantity: i32,
+    ) -> Result<PricedCart, DomainError> {
+        if quantity <= 0 {
+            return Err(DomainError::Validation("quantity must be positive".into()));
+        }
+        let (idle_timeout, ttl, shipping) = (self.idle_timeout, self.reservation_ttl, self.shipping_fee);
+        self.uow
+            .transaction(move |tx| {
+                let owner = owner.clone();
+                Box::pin(async move {
+                    let cart = match &owner {
+                        Some(owner) => live_cart(tx, owner, idle_timeout).await?,
+                        None => None,
+                    };
+                    let cart = match (cart, owner) {
+                        (Some(cart), _) => cart,
+                        (None, Some(CartOwner::User(user_id))) => new_cart(tx, CartOwner::User(user_id)).await?,
+                        (None, _) => new_cart(tx, CartOwner::Guest(random_hex(16))).await?,
+                    };
+                    let in_cart = cart.line(product_id).map_or(0, |l| l.quantity);
+                    let total = in_cart
+                        .checked_add(quantity)
+                        .ok_or_else(|| DomainError::Validation("quantity is too large".into()))?;
+                    let product = available_product(tx, product_id, total, Some(cart.id)).await?;
+                    tx.carts
+                        .set_line(cart.id, CartLine { product_id, quantity: total, unit_price: product.price })
+                        .await?;
+                    reload_and_price(tx, &cart.owner, Utc::now() + ttl, shipping).await
+                })
+            })
+            .await
+    }
+
+    /// Change the quantity of a line already in the cart.
+    pub async fn set_quantity(&self, owner: CartOwner, product_id: i64, quantity: i32) -> Result<PricedCart, DomainError> {
+        if quantity <= 0 {
+            return Err(DomainError::Validation("quantity must be positive".into()));
+        }
+        let (idle_timeout, ttl, shipping) = (self.idle_timeout, self.reservation_ttl, self.shipping_fee);
+        self.uow
+            .transaction(move |tx| {
+                let owner = owner.clone();
+                Box::pin(async move {
+                    let cart = live_cart(tx, &owner, idle_timeout).await?.ok_or(DomainError::NotFound)?;
+                    if cart.line(product_id).is_none() {
+                        return Err(DomainError::NotFound);
+                    }
+                    let product = available_product(tx, product_id, quantity, Some(cart.id)).await?;
+                    tx.carts.set_line(cart.id, CartLine { product_id, quantity, unit_price: product.price }).await?;
+                    reload_and_price(tx, &owner, Utc::now() + ttl, shipping).await
+                })
+            })
+            .await
+    }
+
+    /// Enter a coupon code, in any case, replacing the cart's earlier one.
+    /// The code must exist and be usable now; whether it takes anything off
+    /// shows in the pricing.
+    pub async fn apply_coupon(&self, owner: CartOwner, code: String) -> Result<PricedCart, DomainError> {
+        let code = normalize_code(&code);
+        if code.is_empty() {
+            return Err(DomainError::Validation("code is required".into()));
+        }
+        let (idle_timeout, ttl, shipping) = (self.idle_timeout, self.reservation_ttl, self.shipping_fee);
+        self.uow
+            .transaction(move |tx| {
+                let (owner, code) = (owner.clone(), code.clone());
+                Box::pin(async move {
+                    let cart = live_cart(tx, &owner, idle_timeout).await?.ok_or(DomainError::NotFound)?;
+                    let promotion = tx
+                        .promotions
+                        .by_code(&code)
+                        .await?
+                        .ok_or_else(|| DomainError::Validation(format!("coupon {code} does not exist")))?;
+                    if let Some(reason) = unusable(tx, &promotion, owner.user_id(), Utc::now()).await? {
+                        return Err(DomainError::Validation(format!("coupon {code} {reason}")));
+                    }
+                    tx.carts.set_coupon(cart.id, Some(code)).await?;
+                    reload_and_price(tx, &owner, Utc::now() + ttl, shipping).await
+                })
+            })
+            .await
+    }
+
+    /// Take the coupon out of the cart; a no-op when it has none.
+    pub async fn remove_coupon(&self, owner: CartOwner) -> Result<PricedCart, DomainError> {
+        let (idle_timeout, ttl, shipping) = (self.idle_timeout, self.reservation_ttl, self.shipping_fee);
+        self.uow
+            .transaction(move |tx| {
+                let owner = owner.clone();
+                Box::pin(async move {
+                    let cart = live_cart(tx, &owner, idle_timeout).await?.ok_or(DomainError::NotFound)?;
+                    if cart.coupon.is_some() {
+                        tx.carts.set_coupon(cart.id, None).await?;
+                    }
+                    reload_and_price(tx, &owner, Utc::now() + ttl, shipping).await
+                })
+            })
+            .await
+    }
+
+    pub async fn remove_item(&self, owner: CartOwner, product_id: i64) -> Result<(), DomainError> {
+        let idle_timeout = self.idle_timeout;
+        self.uow
+            .transaction(move |tx| {
+                let owner = owner.clone();
+                Box::pin(async move {
+                    let cart = live_cart(tx, &owner, idle_timeout).await?.ok_or(DomainError::NotFound)?;
+                    if !tx.carts.remove_line(cart.id, product_id).await? {
+                        return Err(DomainError::NotFound);
+                    }
+                    tx.reservations.release(cart.id, product_id).await?;
+                    Ok(())
+                })
+            })
+            .await
+    }
+
+    /// Empty the cart, releasing its reservations; a no-op when there is
+    /// none.
+    pub async fn clear(&self, owner: CartOwner) -> Result<(), DomainError> {
+        self.uow
+            .transaction(move |tx| {
+                let owner = owner.clone();
+                Box::pin(async move {
+                    if let Some(cart) = tx.carts.find(&owner).await? {
+                        tx.carts.delete(cart.id).await?;
+                    }
+                    Ok(())
+                })
+            })
+            .await
+    }
+
+    /// Move a guest's cart into the user's on login. Quantities of the same
+    /// product add up, capped at what is available; products no longer for
+    /// sale are dropped. The guest's reservations pass to the user's cart,
+    /// and so does the guest's coupon unless the user's cart has its own.
+    /// An unknown or expired guest token is ignored.
+    pub async fn merge_guest_cart(&self, guest_token: String, user_id: i64) -> Result<(), DomainError> {
+        let (idle_timeout, ttl) = (self.idle_timeout, self.reservation_ttl);
+        self.uow
+            .transaction(move |tx| {
+                let guest = CartOwner::Guest(guest_token.clone());
+                Box::pin(async move {
+                    let Some(guest_cart) = live_cart(tx, &guest, idle_timeout).await? else {
+                        return Ok(());
+                    };
+                    // first, so the guest's reservations no longer count
+                    tx.carts.delete(guest_cart.id).await?;
+                    let user = CartOwner::User(user_id);
+                    let cart = match live_cart(tx, &user, idle_timeout).await? {
+                        Some(cart) => cart,
+                        None => new_cart(tx, user).await?,
+                    };
+                    if cart.coupon.is_none() && guest_cart.coupon.is_some() {
+                        tx.carts.set_coupon(cart.id, guest_cart.coupon.clone()).await?;
+                    }
+                    let product_ids: Vec<_> = guest_cart.lines.iter().map(|l| l.product_id).collect();
+                    let reserved = tx.reservations.reserved(&product_ids, Some(cart.id), Utc::now()).await?;
+                    for line in &guest_cart.lines {
+                        let Some(product) = tx.products.get_by_product_id(line.product_id).await? else {
+                            continue;
+                        };
+                        let available = product.stock - reserved.get(&product.id).copied().unwrap_or_default();
+                        let in_cart = cart.line(line.product_id).map_or(0, |l| l.quantity);
+                        let quantity = in_cart.saturating_add(line.quantity).min(available);
+                        if !product.active || quantity <= 0 {
+                            continue;
+                        }
+                        let line = CartLine { product_id: product.id, quantity, unit_price: product.price };
+                        tx.carts.set_line(cart.id, line).await?;
+                        tx.reservations.hold(cart.id, product.id, quantity, Utc::now() + ttl).await?;
+                    }
+                    Ok(())
+                })
+            })
+            .await
+    }
+}
+
+/// The owner's cart, unless it has been idle too long; an expired cart is
+/// deleted on the spot rather than left for the prune job.
+pub(crate) async fn live_cart(tx: &TxRepositories, owner: &CartOwner, idle_timeout: Duration) -> Result<Option<Cart>, DomainError> {
+    let Some(cart) = tx.carts.find(owner).await? else {
+        return Ok(None);
+    };
+    if cart.updated_at < Utc::now() - idle_timeout {
+        tx.carts.delete(cart.id).await?;
+        return Ok(None);
+    }
+    Ok(Some(cart))
+}
+
+async fn new_cart(tx: &TxRepositories, owner: CartOwner) -> Result<Cart, DomainError> {
+    let id = tx.carts.create(&owner).await?;
+    Ok(Cart { id, owner, lines: Vec::new(), coupon: None, updated_at: Utc::now() })
+}
+
+/// The product, if it is for sale with at least `quantity` in stock that
+/// no cart but `cart_id` has reserved.
+pub(crate) async fn available_product(
+    tx: &TxRepositories,
+    product_id: i64,
+    quantity: i32,
+    cart_id: Option<i64>,
+) -> Result<Product, DomainError> {
+    let product = tx
+        .products
+        .get_by_product_id(product_id)
+        .await?
+        .ok_or_else(|| DomainError::Validation(format!("product {product_id} does not exist")))?;
+    if !product.active {
+        return Err(DomainError::Validation(format!("product {product_id} is not available")));
+    }
+    let reserved = tx.reservations.reserved(&[product_id], cart_id, Utc::now()).await?;
+    let available = product.stock - reserved.get(&product_id).copied().unwrap_or_default();
+    if available < quantity {
+        return Err(DomainError::Validation(format!("only {} of product {product_id} in stock", available.max(0))));
+    }
+    Ok(product)
+}
+
+async fn reload_and_price(
+    tx: &TxRepositories,
+    owner: &CartOwner,
+    hold_until: DateTime<Utc>,
+    shipping: f64,
+) -> Result<PricedCart, DomainError> {
+    let cart = tx.carts.find(owner).await?.ok_or(DomainError::NotFound)?;
+    price(tx, cart, false, hold_until, shipping).await
+}
+
+/// Price every line from its product; with `reprice`, lines whose price
+/// changed are stored at the new price. Available lines are reserved until
+/// `hold_until`, the others lose their reservation. The available lines
+/// are totalled with the promotions and a shipping fee of `shipping`.
+async fn price(
+    tx: &TxRepositories,
+    cart: Cart,
+    reprice: bool,
+    hold_until: DateTime<Utc>,
+    shipping: f64,
+) -> Result<PricedCart, DomainError> {
+    let product_ids: Vec<_> = cart.lines.iter().map(|l| l.product_id).collect();
+    let reserved = tx.reservations.reserved(&product_ids, Some(cart.id), Utc::now()).await?;
+    let mut lines = Vec::with_capacity(cart.lines.len());
+    let mut pricing_lines = Vec::with_capacity(cart.lines.len());
+    for line in cart.lines {
+        let product = tx.products.get_by_product_id(line.product_id).await?.ok_or(DomainError::NotFound)?;
+        let price_changed = product.price != line.unit_price;
+        if reprice && price_changed {
+            tx.carts.set_line(cart.id, CartLine { unit_price: product.price, ..line.clone() }).await?;
+        }
+        let unreserved = product.stock - reserved.get(&product.id).copied().unwrap_or_default();
+        let available = product.active && unreserved >= line.quantity;
+        if available {
+            tx.reservations.hold(cart.id, product.id, line.quantity, hold_until).await?;
+            pricing_lines.push(PricingLine {
+                product_id: product.id,
+                category_id: product.category_id,
+                quantity: line.quantity,
+                unit_price: product.price,
+            });
+        } else {
+            tx.reservations.release(cart.id, product.id).await?;
+        }
+        lines.push(PricedLine {
+            product_id: product.id,
+            name: product.name,
+            quantity: line.quantity,
+            unit_price: product.price,
+            price_changed,
+            available,
+        });
+    }
+    let pricing = price_with_promotions(tx, cart.coupon.as_deref(), cart.owner.user_id(), &pricing_lines, shipping).await?;
+    Ok(PricedCart { owner: cart.owner, lines, coupon: cart.coupon, pricing })
+}
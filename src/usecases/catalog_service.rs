@@ -0,0 +1,121 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::page::Page;
+use crate::domain::product::Product;
+use crate::domain::DomainError;
+use crate::usecases::category_service::CategoryService;
+use crate::usecases::product_service::ProductService;
+
+/// Categories with their products, keyed by name rather than id so a catalog
+/// can move between databases.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Catalog {
+    pub categories: Vec<CatalogCategory>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatalogCategory {
+    pub name: String,
+    #[serde(default)]
+    pub products: Vec<CatalogProduct>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatalogProduct {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub price: f64,
+    pub stock: i32,
+    #[serde(default = "active_by_default")]
+    pub active: bool,
+}
+
+fn active_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ImportSummary {
+    pub categories_created: usize,
+    pub products_created: usize,
+    /// Already present in their category, by name.
+    pub products_skipped: usize,
+}
+
+#[derive(Clone)]
+pub struct CatalogService {
+    categories: CategoryService,
+    products: ProductService,
+}
+
+impl CatalogService {
+    pub fn new(categories: CategoryService, products: ProductService) -> Self {
+        Self { categories, products }
+    }
+
+    pub async fn export(&self) -> Result<Catalog, DomainError> {
+        let mut products: HashMap<i64, Vec<CatalogProduct>> = HashMap::new();
+        for p in self.products.get_all_products(Page::ALL).await? {
+            products.entry(p.category_id).or_default().push(CatalogProduct {
+                name: p.name,
+                description: p.description,
+                price: p.price,
+                stock: p.stock,
+                active: p.active,
+            });
+        }
+        let categories = self
+            .categories
+            .get_all_categories(Page::ALL)
+            .await?
+            .into_iter()
+            .map(|c| CatalogCategory { products: products.remove(&c.id).unwrap_or_default(), name: c.name })
+            .collect();
+        Ok(Catalog { categories })
+    }
+
+    /// Create missing categories and products through the services, so the
+    /// usual validation and events apply. Existing ones are matched by name
+    /// and left alone, which makes a re-run after a failure safe.
+    pub async fn import(&self, catalog: Catalog) -> Result<ImportSummary, DomainError> {
+        let mut summary = ImportSummary::default();
+        let mut existing: HashMap<String, i64> =
+            self.categories.get_all_categories(Page::ALL).await?.into_iter().map(|c| (c.name, c.id)).collect();
+        let mut existing_products: HashSet<(i64, String)> =
+            self.products.get_all_products(Page::ALL).await?.into_iter().map(|p| (p.category_id, p.name)).collect();
+
+        for category in catalog.categories {
+            let category_id = match existing.get(&category.name) {
+                Some(id) => *id,
+                None => {
+                    let id = self.categories.create(category.name.clone()).await?;
+                    existing.insert(category.name, id);
+                    summary.categories_created += 1;
+                    id
+                }
+            };
+            for product in category.products {
+                if !existing_products.insert((category_id, product.name.clone())) {
+                    summary.products_skipped += 1;
+                    continue;
+                }
+                self.products
+                    .create(Product {
+                        id: 0,
+                        name: product.name,
+                        description: product.description,
+                        price: product.price,
+                        stock: product.stock,
+                        category_id,
+                        active: product.active,
+                    })
+                    .await?;
+                summary.products_created += 1;
+            }
+        }
+        Ok(summary)
+    }
+}
@@ -1,48 +1,103 @@
 use std::sync::Arc;
 
 use crate::domain::DomainError;
+use crate::domain::cache::{CacheInvalidator, Invalidation, NoCache};
 use crate::domain::category::{Category, CategoryRepository};
+use crate::domain::event::DomainEvent;
+use crate::domain::page::Page;
+use crate::domain::uow::UnitOfWork;
 
 #[derive(Clone)]
 pub struct CategoryService {
     repo: Arc<dyn CategoryRepository>,
+    uow: Arc<dyn UnitOfWork>,
+    cache: Arc<dyn CacheInvalidator>,
 }
 
 impl CategoryService {
-    pub fn new(repo: Arc<dyn CategoryRepository>) -> Self {
-        Self { repo }
+    pub fn new(repo: Arc<dyn CategoryRepository>, uow: Arc<dyn UnitOfWork>) -> Self {
+        Self { repo, uow, cache: Arc::new(NoCache) }
+    }
+
+    /// Report committed writes to `cache`, for a cached `repo`.
+    pub fn with_cache_invalidator(mut self, cache: Arc<dyn CacheInvalidator>) -> Self {
+        self.cache = cache;
+        self
     }
 
     pub async fn create(&self, name: String) -> Result<i64, DomainError> {
         if name.trim().is_empty() {
             return Err(DomainError::Validation("name is required".into()));
         }
-        self.repo.create(name).await
+        let id = self
+            .uow
+            .transaction(move |tx| {
+                let name = name.clone();
+                Box::pin(async move {
+                    let id = tx.categories.create(name.clone()).await?;
+                    tx.outbox.append(&DomainEvent::CategoryCreated { id, name }).await?;
+                    Ok(id)
+                })
+            })
+            .await?;
+        self.cache.invalidate(Invalidation::Category(id)).await;
+        Ok(id)
     }
 
     pub async fn get_by_id(&self, id: i64) -> Result<Option<Category>, DomainError> {
         self.repo.get_by_id(id).await
     }
 
-    pub async fn get_all_categories(&self) -> Result<Vec<Category>, DomainError> {
-        self.repo.get_all_categories().await
+    pub async fn get_all_categories(&self, page: Page) -> Result<Vec<Category>, DomainError> {
+        self.repo.get_all_categories(page).await
     }
 
     pub async fn update(&self, id: i64, name: String) -> Result<Category, DomainError> {
         if name.trim().is_empty() {
             return Err(DomainError::Validation("name is required".into()));
         }
-        if self.repo.get_by_id(id).await?.is_none() {
-            return Err(DomainError::NotFound);
-        }
-        self.repo.update(id, name).await
+        // existence check and write in one transaction
+        let category = self
+            .uow
+            .transaction(move |tx| {
+                let name = name.clone();
+                Box::pin(async move {
+                    let Some(old) = tx.categories.get_by_id(id).await? else {
+                        return Err(DomainError::NotFound);
+                    };
+                    let category = tx.categories.update(id, name).await?;
+                    if category.name != old.name {
+                        tx.outbox
+                            .append(&DomainEvent::CategoryRenamed {
+                                id,
+                                old_name: old.name,
+                                new_name: category.name.clone(),
+                            })
+                            .await?;
+                    }
+                    Ok(category)
+                })
+            })
+            .await?;
+        self.cache.invalidate(Invalidation::Category(id)).await;
+        Ok(category)
     }
 
     pub async fn delete(&self, id: i64) -> Result<(), DomainError> {
-        let category = self.repo.get_by_id(id).await?;
-        if category.is_none() {
-            return Err(DomainError::NotFound);
-        }
-        self.repo.delete(id).await
+        self.uow
+            .transaction(move |tx| {
+                Box::pin(async move {
+                    let category = tx.categories.get_by_id(id).await?;
+                    if category.is_none() {
+                        return Err(DomainError::NotFound);
+                    }
+                    tx.categories.delete(id).await?;
+                    tx.outbox.append(&DomainEvent::CategoryDeleted { id }).await?;
+                    Ok(())
+                })
+            })
+            .await?;
+        self.cache.invalidate(Invalidation::Category(id)).await;
+        Ok(())
     }
 }
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::cart::CartRepository;
+use crate::domain::job::{JobPayload, JobQueue};
+use crate::domain::reservation::ReservationRepository;
+use crate::domain::DomainError;
+use crate::usecases::job_worker::JobHandler;
+
+/// Delete finished (completed or dead) jobs older than `retention_days`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PruneFinishedJobs {
+    pub retention_days: i64,
+}
+
+impl JobPayload for PruneFinishedJobs {
+    const KIND: &'static str = "prune_finished_jobs";
+}
+
+pub struct PruneFinishedJobsHandler {
+    queue: Arc<dyn JobQueue>,
+}
+
+impl PruneFinishedJobsHandler {
+    pub fn new(queue: Arc<dyn JobQueue>) -> Self {
+        Self { queue }
+    }
+}
+
+#[async_trait]
+impl JobHandler for PruneFinishedJobsHandler {
+    type Job = PruneFinishedJobs;
+
+    async fn handle(&self, job: PruneFinishedJobs) -> Result<(), DomainError> {
+        let before = Utc::now() - Duration::days(job.retention_days);
+        let pruned = self.queue.prune_finished(before).await?;
+        tracing::info!(pruned, %before, "pruned finished jobs");
+        Ok(())
+    }
+}
+
+/// Delete carts nobody has written to for `idle_days`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PruneIdleCarts {
+    pub idle_days: i64,
+}
+
+impl JobPayload for PruneIdleCarts {
+    const KIND: &'static str = "prune_idle_carts";
+}
+
+pub struct PruneIdleCartsHandler {
+    carts: Arc<dyn CartRepository>,
+}
+
+impl PruneIdleCartsHandler {
+    pub fn new(carts: Arc<dyn CartRepository>) -> Self {
+        Self { carts }
+    }
+}
+
+#[async_trait]
+impl JobHandler for PruneIdleCartsHandler {
+    type Job = PruneIdleCarts;
+
+    async fn handle(&self, job: PruneIdleCarts) -> Result<(), DomainError> {
+        let before = Utc::now() - Duration::days(job.idle_days);
+        let pruned = self.carts.delete_idle(before).await?;
+        tracing::info!(pruned, %before, "pruned idle carts");
+        Ok(())
+    }
+}
+
+/// Delete stock reservations that have expired. They stop counting the
+/// moment they expire; this only clears them out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReleaseExpiredReservations {}
+
+impl JobPayload for ReleaseExpiredReservations {
+    const KIND: &'static str = "release_expired_reservations";
+}
+
+pub struct ReleaseExpiredReservationsHandler {
+    reservations: Arc<dyn ReservationRepository>,
+}
+
+impl ReleaseExpiredReservationsHandler {
+    pub fn new(reservations: Arc<dyn ReservationRepository>) -> Self {
+        Self { reservations }
+    }
+}
+
+#[async_trait]
+impl JobHandler for ReleaseExpiredReservationsHandler {
+    type Job = ReleaseExpiredReservations;
+
+    async fn handle(&self, _job: ReleaseExpiredReservations) -> Result<(), DomainError> {
+        let released = self.reservations.release_expired(Utc::now()).await?;
+        if released > 0 {
+            tracing::info!(released, "released expired stock reservations");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,314 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+
+use crate::domain::cache::{CacheInvalidator, Invalidation, NoCache};
+use crate::domain::event::DomainEvent;
+use crate::domain::inventory::{InventoryRepository, LowStockProduct, MovementKind, StockLevel, StockMovement};
+use crate::domain::notification::Notification;
+use crate::domain::product::ProductRepository;
+use crate::domain::uow::{TxRepositories, UnitOfWork};
+use crate::domain::warehouse::{GeoPoint, Warehouse, WarehouseRepository};
+use crate::domain::DomainError;
+
+/// Outcome of [`InventoryService::reconcile`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reconciliation {
+    pub products_checked: usize,
+    /// Products whose stock is not what their ledger adds up to.
+    pub discrepancies: Vec<StockLevel>,
+}
+
+/// One category's part of [`InventoryService::low_stock`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LowStockCategory {
+    pub category_id: i64,
+    pub category: String,
+    /// By product id.
+    pub products: Vec<LowStockProduct>,
+}
+
+#[derive(Clone)]
+pub struct InventoryService {
+    repo: Arc<dyn InventoryRepository>,
+    products: Arc<dyn ProductRepository>,
+    warehouses: Arc<dyn WarehouseRepository>,
+    uow: Arc<dyn UnitOfWork>,
+    cache: Arc<dyn CacheInvalidator>,
+}
+
+impl InventoryService {
+    pub fn new(
+        repo: Arc<dyn InventoryRepository>,
+        products: Arc<dyn ProductRepository>,
+        warehouses: Arc<dyn WarehouseRepository>,
+        uow: Arc<dyn UnitOfWork>,
+    ) -> Self {
+        Self { repo, products, warehouses, uow, cache: Arc::new(NoCache) }
+    }
+
+    /// Report stock changes to `cache`, for a cached product repository.
+    pub fn with_cache_invalidator(mut self, cache: Arc<dyn CacheInvalidator>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Book a receipt or an adjustment in the warehouse (the default one if
+    /// `None`) on behalf of `actor_id`. Receipts add stock; adjustments go
+    /// either way but never below zero. Sales, returns and reservations are
+    /// left to the orders.
+    pub async fn adjust(
+        &self,
+        product_id: i64,
+        warehouse_id: Option<i64>,
+        kind: MovementKind,
+        quantity: i32,
+        reason: String,
+        actor_id: i64,
+    ) -> Result<StockMovement, DomainError> {
+        if !kind.is_manual() {
+            return Err(DomainError::Validation(format!("{kind} movements are recorded by orders")));
+        }
+        if quantity == 0 || (kind == MovementKind::Receipt && quantity < 0) {
+            return Err(DomainError::Validation(format!("invalid {kind} quantity {quantity}")));
+        }
+        let reason = reason.trim().to_string();
+        if reason.is_empty() {
+            return Err(DomainError::Validation("reason is required".into()));
+        }
+
+        let movement = self
+            .uow
+            .transaction(move |tx| {
+                let reason = reason.clone();
+                Box::pin(async move {
+                    let product = tx.products.get_by_product_id(product_id).await?.ok_or(DomainError::NotFound)?;
+                    let warehouse = match warehouse_id {
+                        Some(id) => tx.warehouses.get(id).await?.ok_or_else(|| unknown_warehouse(id))?,
+                        None => default_warehouse(tx).await?,
+                    };
+                    let movement = StockMovement {
+                        id: 0,
+                        product_id,
+                        warehouse_id: warehouse.id,
+                        kind,
+                        quantity,
+                        reason: Some(reason),
+                        actor_id: Some(actor_id),
+                        order_id: None,
+                        created_at: Utc::now(),
+                    };
+                    let Some(movement) = record_movement(tx, movement).await? else {
+                        return Err(too_little(tx, product_id, &warehouse).await);
+                    };
+                    let stock = product.stock + quantity;
+                    let warehouse_id = warehouse.id;
+                    tx.outbox.append(&DomainEvent::StockAdjusted { product_id, warehouse_id, kind, quantity, stock }).await?;
+                    Ok(movement)
+                })
+            })
+            .await?;
+        self.cache.invalidate(Invalidation::Product(product_id)).await;
+        Ok(movement)
+    }
+
+    /// Move `quantity` of the product from one warehouse to another on
+    /// behalf of `actor_id`: a transfer out of `from` and one into `to`,
+    /// in that order. The product's total stock stays the same.
+    pub async fn transfer(
+        &self,
+        product_id: i64,
+        from: i64,
+        to: i64,
+        quantity: i32,
+        reason: String,
+        actor_id: i64,
+    ) -> Result<Vec<StockMovement>, DomainError> {
+        if quantity <= 0 {
+            return Err(DomainError::Validation(format!("invalid transfer quantity {quantity}")));
+        }
+        if from == to {
+            return Err(DomainError::Validation("cannot transfer to the same warehouse".into()));
+        }
+        let reason = reason.trim().to_string();
+        if reason.is_empty() {
+            return Err(DomainError::Validation("reason is required".into()));
+        }
+
+        // the total does not change, so there is nothing to invalidate
+        self.uow
+            .transaction(move |tx| {
+                let reason = reason.clone();
+                Box::pin(async move {
+                    tx.products.get_by_product_id(product_id).await?.ok_or(DomainError::NotFound)?;
+                    let source = tx.warehouses.get(from).await?.ok_or_else(|| unknown_warehouse(from))?;
+                    tx.warehouses.get(to).await?.ok_or_else(|| unknown_warehouse(to))?;
+                    let mut moved = Vec::with_capacity(2);
+                    for (warehouse_id, quantity) in [(from, -quantity), (to, quantity)] {
+                        let movement = StockMovement {
+                            id: 0,
+                            product_id,
+                            warehouse_id,
+                            kind: MovementKind::Transfer,
+                            quantity,
+                            reason: Some(reason.clone()),
+                            actor_id: Some(actor_id),
+                            order_id: None,
+                            created_at: Utc::now(),
+                        };
+                        let Some(movement) = record_movement(tx, movement).await? else {
+                            return Err(too_little(tx, product_id, &source).await);
+                        };
+                        moved.push(movement);
+                    }
+                    let transferred =
+                        DomainEvent::StockTransferred { product_id, from_warehouse_id: from, to_warehouse_id: to, quantity };
+                    tx.outbox.append(&transferred).await?;
+                    Ok(moved)
+                })
+            })
+            .await
+    }
+
+    /// Set the stock below which the product needs reordering, or clear it
+    /// with `None`. Checked whenever the stock goes down: crossing it raises
+    /// [`DomainEvent::LowStock`] and leaves a notification for the admins.
+    /// Setting a threshold above the current stock raises nothing; the
+    /// product shows up in [`low_stock`](Self::low_stock) all the same.
+    pub async fn set_threshold(&self, product_id: i64, threshold: Option<i32>) -> Result<(), DomainError> {
+        if threshold.is_some_and(|t| t < 0) {
+            return Err(DomainError::Validation("threshold cannot be negative".into()));
+        }
+        if !self.repo.set_threshold(product_id, threshold).await? {
+            return Err(DomainError::NotFound);
+        }
+        Ok(())
+    }
+
+    /// The products below their reorder threshold, by category name.
+    pub async fn low_stock(&self) -> Result<Vec<LowStockCategory>, DomainError> {
+        let mut report: Vec<LowStockCategory> = Vec::new();
+        for product in self.repo.low_stock().await? {
+            match report.last_mut() {
+                Some(group) if group.category_id == product.category_id => group.products.push(product),
+                _ => report.push(LowStockCategory {
+                    category_id: product.category_id,
+                    category: product.category.clone(),
+                    products: vec![product],
+                }),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Add a stock location. Names are unique.
+    pub async fn create_warehouse(
+        &self,
+        name: String,
+        priority: i32,
+        location: Option<GeoPoint>,
+    ) -> Result<Warehouse, DomainError> {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err(DomainError::Validation("name is required".into()));
+        }
+        if location.is_some_and(|l| GeoPoint::new(l.latitude, l.longitude).is_none()) {
+            return Err(DomainError::Validation(
+                "latitude must be within [-90, 90] and longitude within [-180, 180]".into(),
+            ));
+        }
+        self.uow
+            .transaction(move |tx| {
+                let name = name.clone();
+                Box::pin(async move {
+                    if tx.warehouses.list().await?.iter().any(|w| w.name == name) {
+                        return Err(DomainError::Validation(format!("warehouse {name:?} already exists")));
+                    }
+                    let warehouse = Warehouse { id: 0, name, priority, location };
+                    let id = tx.warehouses.create(warehouse.clone()).await?;
+                    Ok(Warehouse { id, ..warehouse })
+                })
+            })
+            .await
+    }
+
+    /// By priority; the first is the default.
+    pub async fn warehouses(&self) -> Result<Vec<Warehouse>, DomainError> {
+        self.warehouses.list().await
+    }
+
+    /// The product's ledger, oldest first.
+    pub async fn movements(&self, product_id: i64) -> Result<Vec<StockMovement>, DomainError> {
+        if self.products.get_by_product_id(product_id).await?.is_none() {
+            return Err(DomainError::NotFound);
+        }
+        self.repo.movements(product_id).await
+    }
+
+    /// Check every product's stock against its ledger.
+    pub async fn reconcile(&self) -> Result<Reconciliation, DomainError> {
+        let levels = self.repo.stock_levels().await?;
+        let products_checked = levels.len();
+        let discrepancies: Vec<_> = levels.into_iter().filter(|l| l.difference() != 0).collect();
+        if !discrepancies.is_empty() {
+            tracing::warn!(products = discrepancies.len(), "stock does not match the ledger");
+        }
+        Ok(Reconciliation { products_checked, discrepancies })
+    }
+}
+
+/// The first warehouse by priority, where stock goes unless told otherwise.
+pub(crate) async fn default_warehouse(tx: &TxRepositories) -> Result<Warehouse, DomainError> {
+    tx.warehouses
+        .list()
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| DomainError::Unexpected("no warehouse to keep stock in".into()))
+}
+
+/// Record `movement` through `tx`, like [`InventoryRepository::record`], and
+/// check the product's reorder threshold when it takes stock out: if the
+/// stock drops below it, raise [`DomainEvent::LowStock`] and notify the
+/// admins. Transfers leave the total alone and are not checked.
+pub(crate) async fn record_movement(tx: &TxRepositories, movement: StockMovement) -> Result<Option<StockMovement>, DomainError> {
+    let Some(movement) = tx.inventory.record(movement).await? else {
+        return Ok(None);
+    };
+    if movement.quantity > 0 || movement.kind == MovementKind::Transfer {
+        return Ok(Some(movement));
+    }
+    let Some(threshold) = tx.inventory.threshold(movement.product_id).await? else {
+        return Ok(Some(movement));
+    };
+    let product = tx.products.get_by_product_id(movement.product_id).await?.ok_or(DomainError::NotFound)?;
+    let before = product.stock - movement.quantity;
+    if product.stock < threshold && before >= threshold {
+        let (product_id, stock) = (product.id, product.stock);
+        tx.outbox.append(&DomainEvent::LowStock { product_id, stock, threshold }).await?;
+        let message = format!("{} is low on stock: {stock} left, reorder threshold {threshold}", product.name);
+        let notification = Notification {
+            id: 0,
+            kind: "low_stock".into(),
+            message,
+            product_id: Some(product_id),
+            created_at: Utc::now(),
+            read_at: None,
+        };
+        tx.notifications.create(notification).await?;
+    }
+    Ok(Some(movement))
+}
+
+pub(crate) fn unknown_warehouse(id: i64) -> DomainError {
+    DomainError::Validation(format!("no warehouse {id}"))
+}
+
+/// Why a movement taking stock out of `warehouse` was refused.
+async fn too_little(tx: &TxRepositories, product_id: i64, warehouse: &Warehouse) -> DomainError {
+    let held = match tx.warehouses.stock(&[product_id]).await {
+        Ok(stock) => stock.iter().find(|s| s.warehouse_id == warehouse.id).map_or(0, |s| s.stock),
+        Err(e) => return e,
+    };
+    DomainError::Validation(format!("only {held} of product {product_id} in stock at {}", warehouse.name))
+}
@@ -0,0 +1,88 @@
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+
+use crate::domain::job::{JobPayload, JobQueue, NewJob};
+use crate::domain::DomainError;
+
+#[derive(Clone)]
+struct CronJob {
+    name: String,
+    schedule: Schedule,
+    job: NewJob,
+}
+
+/// Enqueues jobs on cron schedules.
+///
+/// Every fire time gets the unique key `cron:<name>:<unix seconds>`, so
+/// several instances running the same schedule enqueue each job once. Fire
+/// times missed while no instance was running are skipped.
+#[derive(Clone)]
+pub struct JobScheduler {
+    queue: Arc<dyn JobQueue>,
+    entries: Vec<CronJob>,
+}
+
+impl JobScheduler {
+    pub fn new(queue: Arc<dyn JobQueue>) -> Self {
+        Self { queue, entries: Vec::new() }
+    }
+
+    /// Enqueue `job` at every time matching `expression`, in UTC. Expressions
+    /// start with a seconds field: `0 0 3 * * *` is daily at 03:00.
+    pub fn add<J: JobPayload>(mut self, name: &str, expression: &str, job: &J) -> Result<Self, DomainError> {
+        let schedule = Schedule::from_str(expression)
+            .map_err(|e| DomainError::Validation(format!("invalid cron expression {expression:?}: {e}")))?;
+        self.entries.push(CronJob { name: name.to_string(), schedule, job: NewJob::of(job)? });
+        Ok(self)
+    }
+
+    /// Enqueue every job due in `(after, until]`; returns how many were new.
+    pub async fn enqueue_due(&self, after: DateTime<Utc>, until: DateTime<Utc>) -> Result<usize, DomainError> {
+        let mut enqueued = 0;
+        for entry in &self.entries {
+            for at in entry.schedule.after(&after).take_while(|at| *at <= until) {
+                let job = entry
+                    .job
+                    .clone()
+                    .run_at(at)
+                    .unique_key(format!("cron:{}:{}", entry.name, at.timestamp()));
+                if self.queue.enqueue(job).await?.is_some() {
+                    tracing::info!(schedule = %entry.name, run_at = %at, "scheduled job enqueued");
+                    enqueued += 1;
+                }
+            }
+        }
+        Ok(enqueued)
+    }
+
+    /// Sleep until the next fire time and enqueue, until `shutdown` resolves.
+    pub async fn run(self, shutdown: impl Future<Output = ()>) {
+        tokio::pin!(shutdown);
+        let mut last = Utc::now();
+        loop {
+            let next = self.entries.iter().filter_map(|e| e.schedule.after(&last).next()).min();
+            let wait = next
+                .map(|at| (at - Utc::now()).to_std().unwrap_or_default())
+                // nothing scheduled, or the schedules ended
+                .unwrap_or(Duration::from_secs(3600));
+            tokio::select! {
+                _ = &mut shutdown => return,
+                _ = tokio::time::sleep(wait) => {}
+            }
+
+            let now = Utc::now();
+            match self.enqueue_due(last, now).await {
+                Ok(_) => last = now,
+                Err(e) => {
+                    tracing::warn!(error = %e, "scheduling jobs failed");
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                }
+            }
+        }
+    }
+}
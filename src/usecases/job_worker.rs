@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use tokio::sync::{watch, Semaphore};
+use tracing::Instrument;
+
+use crate::domain::job::{Job, JobPayload, JobQueue};
+use crate::domain::DomainError;
+use crate::infra::job_trace::{job_span, ActiveJob};
+
+/// Runs jobs of one [`JobPayload`] type.
+#[async_trait]
+pub trait JobHandler: Send + Sync + 'static {
+    type Job: JobPayload;
+
+    /// An `Err` (or a panic) makes the job run again later, up to
+    /// `max_attempts` times; handlers must be safe to repeat.
+    async fn handle(&self, job: Self::Job) -> Result<(), DomainError>;
+}
+
+#[async_trait]
+trait ErasedHandler: Send + Sync {
+    async fn handle(&self, payload: serde_json::Value) -> Result<(), DomainError>;
+}
+
+struct Typed<H>(H);
+
+#[async_trait]
+impl<H: JobHandler> ErasedHandler for Typed<H> {
+    async fn handle(&self, payload: serde_json::Value) -> Result<(), DomainError> {
+        let job = serde_json::from_value::<H::Job>(payload)
+            .map_err(|e| DomainError::Validation(format!("invalid {} payload: {e}", H::Job::KIND)))?;
+        self.0.handle(job).await
+    }
+}
+
+/// Delay before attempt `attempts + 1`: 5s doubling per attempt, at most 1h.
+pub fn retry_delay(attempts: i32) -> Duration {
+    let exponent = u32::try_from(attempts.saturating_sub(1)).unwrap_or(0).min(10);
+    (Duration::from_secs(5) * 2u32.pow(exponent)).min(Duration::from_secs(3600))
+}
+
+/// Parse `JOB_QUEUES`-style `queue:concurrency` pairs, e.g.
+/// `default:4,imports:1`; a bare queue name gets one slot.
+pub fn parse_queues(spec: &str) -> Result<Vec<(String, usize)>, String> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|queue| !queue.is_empty())
+        .map(|queue| {
+            let (name, concurrency) = queue.split_once(':').unwrap_or((queue, "1"));
+            match concurrency.parse::<usize>() {
+                Ok(n) if n > 0 && !name.is_empty() => Ok((name.to_string(), n)),
+                _ => Err(format!("invalid queue {queue:?}, expected name:concurrency")),
+            }
+        })
+        .collect()
+}
+
+/// Claims jobs from a [`JobQueue`] and runs them with the registered
+/// handlers.
+///
+/// Each claim is a lease: a job still running when its lease ends (say the
+/// process died) is claimed again by another worker, so delivery is
+/// at-least-once.
+#[derive(Clone)]
+pub struct JobWorker {
+    queue: Arc<dyn JobQueue>,
+    handlers: HashMap<&'static str, Arc<dyn ErasedHandler>>,
+    lease: Duration,
+}
+
+impl JobWorker {
+    pub fn new(queue: Arc<dyn JobQueue>) -> Self {
+        Self { queue, handlers: HashMap::new(), lease: Duration::from_secs(300) }
+    }
+
+    pub fn register<H: JobHandler>(mut self, handler: H) -> Self {
+        self.handlers.insert(H::Job::KIND, Arc::new(Typed(handler)));
+        self
+    }
+
+    /// Claim up to `limit` due jobs of `queue` and run them one by one;
+    /// returns how many ran.
+    #[cfg(test)]
+    pub async fn run_once(&self, queue: &str, limit: i64) -> Result<usize, DomainError> {
+        let jobs = self.queue.claim(queue, limit, self.lease).await?;
+        let count = jobs.len();
+        for job in jobs {
+            self.process(job).await;
+        }
+        Ok(count)
+    }
+
+    /// Work every `(queue, concurrency)` pair until `shutdown` resolves, then
+    /// let running jobs finish. Idle queues are polled every `interval`.
+    pub async fn run(self, queues: Vec<(String, usize)>, interval: Duration, shutdown: impl Future<Output = ()>) {
+        let (stop, stopped) = watch::channel(false);
+        let workers: Vec<_> = queues
+            .into_iter()
+            .map(|(queue, concurrency)| {
+                tokio::spawn(self.clone().run_queue(queue, concurrency, interval, stopped.clone()))
+            })
+            .collect();
+
+        shutdown.await;
+        let _ = stop.send(true);
+        for worker in workers {
+            if let Err(e) = worker.await {
+                tracing::error!("job worker task failed: {e}");
+            }
+        }
+    }
+
+    async fn run_queue(self, queue: String, concurrency: usize, interval: Duration, mut stopped: watch::Receiver<bool>) {
+        let slots = Arc::new(Semaphore::new(concurrency));
+        loop {
+            let free = slots.available_permits();
+            let mut wait = interval;
+            if free > 0 {
+                match self.queue.claim(&queue, free as i64, self.lease).await {
+                    Ok(jobs) => {
+                        if jobs.len() == free {
+                            // more may be due; come back as soon as a slot frees
+                            wait = Duration::ZERO;
+                        }
+                        for job in jobs {
+                            let Ok(slot) = slots.clone().acquire_owned().await else { return };
+                            let worker = self.clone();
+                            tokio::spawn(async move {
+                                worker.process(job).await;
+                                drop(slot);
+                            });
+                        }
+                    }
+                    Err(e) => tracing::warn!(queue = %queue, error = %e, "job claim failed"),
+                }
+            }
+
+            tokio::select! {
+                _ = stopped.changed() => break,
+                _ = tokio::time::sleep(wait), if slots.available_permits() > 0 => {}
+                _ = slots.acquire(), if slots.available_permits() == 0 => {}
+            }
+        }
+
+        // wait for running jobs
+        let _ = slots.acquire_many(concurrency as u32).await;
+    }
+
+    /// Run `job` and record the outcome in the queue.
+    async fn process(&self, job: Job) {
+        let span = job_span(&job);
+        async {
+            let active = ActiveJob::start(&job);
+            let started = Instant::now();
+
+            let result = match self.handlers.get(job.kind.as_str()) {
+                // the worker died (or hung) on the final attempt
+                _ if job.attempts > job.max_attempts => {
+                    Err(DomainError::Unexpected("lease expired on the last attempt".into()))
+                }
+                Some(handler) => {
+                    let (handler, payload) = (handler.clone(), job.payload.clone());
+                    tokio::spawn(async move { handler.handle(payload).await }.in_current_span())
+                        .await
+                        .unwrap_or_else(|e| Err(DomainError::Unexpected(format!("job panicked: {e}"))))
+                }
+                None => Err(DomainError::Unexpected(format!("no handler for job kind {}", job.kind))),
+            };
+
+            let (outcome, recorded) = match result {
+                Ok(()) => ("completed", self.queue.complete(&job).await),
+                Err(e) => {
+                    let retry_at = (job.attempts < job.max_attempts)
+                        .then(|| Utc::now() + retry_delay(job.attempts));
+                    tracing::warn!(error = %e, ?retry_at, "job failed");
+                    let outcome = if retry_at.is_some() { "retried" } else { "dead" };
+                    (outcome, self.queue.fail(&job, e.to_string(), retry_at).await)
+                }
+            };
+            if let Err(e) = recorded {
+                // the lease runs out and the job is claimed again
+                tracing::warn!(error = %e, "failed to record job outcome");
+            }
+            active.finish(&job.kind, outcome, started.elapsed());
+        }
+        .instrument(span)
+        .await
+    }
+}
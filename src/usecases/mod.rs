@@ -1,3 +1,16 @@
 pub mod user_service;
 pub mod category_service;
-pub mod product_service;
\ No newline at end of file
+pub mod product_service;
+pub mod outbox_relay;
+pub mod job_worker;
+pub mod job_scheduler;
+pub mod housekeeping;
+pub mod api_key_service;
+pub mod catalog_service;
+pub mod cart_service;
+pub mod inventory_service;
+pub mod notification_service;
+pub mod order_service;
+pub mod payment_service;
+pub mod promotion_service;
+pub mod purchasing_service;
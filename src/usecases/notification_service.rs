@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use crate::domain::notification::{Notification, NotificationRepository};
+use crate::domain::DomainError;
+
+/// The admins' in-app notification list. Notifications are left by the other
+/// services, in the same transaction as whatever they are about.
+#[derive(Clone)]
+pub struct NotificationService {
+    repo: Arc<dyn NotificationRepository>,
+}
+
+impl NotificationService {
+    pub fn new(repo: Arc<dyn NotificationRepository>) -> Self {
+        Self { repo }
+    }
+
+    /// Newest first; only the unread ones if `unread_only`.
+    pub async fn list(&self, unread_only: bool) -> Result<Vec<Notification>, DomainError> {
+        self.repo.list(unread_only).await
+    }
+
+    pub async fn mark_read(&self, id: i64) -> Result<(), DomainError> {
+        if !self.repo.mark_read(id).await? {
+            return Err(DomainError::NotFound);
+        }
+        Ok(())
+    }
+}
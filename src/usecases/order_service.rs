@@ -0,0 +1,286 @@
+use std::sync::Arc;
+
+use std::collections::HashMap;
+
+use chrono::{Duration, Utc};
+
+use crate::domain::cache::{CacheInvalidator, Invalidation, NoCache};
+use crate::domain::cart::CartOwner;
+use crate::domain::event::DomainEvent;
+use crate::domain::inventory::{MovementKind, StockMovement};
+use crate::domain::order::{Order, OrderLine, OrderRepository, OrderStatus, StatusChange};
+use crate::domain::page::Page;
+use crate::domain::promotion::{PricingLine, Redemption};
+use crate::domain::uow::{TxRepositories, UnitOfWork};
+use crate::domain::warehouse::{allocate, AllocationStrategy, GeoPoint};
+use crate::domain::DomainError;
+use crate::usecases::cart_service::{available_product, live_cart, DEFAULT_IDLE_TIMEOUT_DAYS};
+use crate::usecases::inventory_service::record_movement;
+use crate::usecases::promotion_service::price_with_promotions;
+
+#[derive(Clone)]
+pub struct OrderService {
+    repo: Arc<dyn OrderRepository>,
+    uow: Arc<dyn UnitOfWork>,
+    cache: Arc<dyn CacheInvalidator>,
+    cart_idle_timeout: Duration,
+    allocation: AllocationStrategy,
+    shipping_fee: f64,
+}
+
+impl OrderService {
+    pub fn new(repo: Arc<dyn OrderRepository>, uow: Arc<dyn UnitOfWork>) -> Self {
+        Self {
+            repo,
+            uow,
+            cache: Arc::new(NoCache),
+            cart_idle_timeout: Duration::days(DEFAULT_IDLE_TIMEOUT_DAYS),
+            allocation: AllocationStrategy::Priority,
+            shipping_fee: 0.0,
+        }
+    }
+
+    /// Report stock changes to `cache`, for a cached product repository.
+    pub fn with_cache_invalidator(mut self, cache: Arc<dyn CacheInvalidator>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Same as the cart service's, so an expired cart cannot be checked out.
+    pub fn with_cart_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.cart_idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Which warehouses checkouts take the stock from first; by priority
+    /// unless told otherwise.
+    pub fn with_allocation_strategy(mut self, allocation: AllocationStrategy) -> Self {
+        self.allocation = allocation;
+        self
+    }
+
+    /// Same as the cart service's, so orders cost what the cart said.
+    pub fn with_shipping_fee(mut self, shipping_fee: f64) -> Self {
+        self.shipping_fee = shipping_fee;
+        self
+    }
+
+    /// Turn the user's cart into an order at the current prices. Stock is
+    /// taken for every line, the order stored and the cart emptied with its
+    /// reservations, or nothing happens at all. Stock other carts reserved
+    /// is not for sale. Each line ships from the first warehouse, ranked by
+    /// the allocation strategy and `ship_to`, that holds all of it, else
+    /// from as few as it takes. The promotions that apply are redeemed; a
+    /// coupon in the cart that takes nothing off fails the checkout.
+    pub async fn checkout(&self, user_id: i64, ship_to: Option<GeoPoint>) -> Result<Order, DomainError> {
+        let (idle_timeout, allocation, shipping) = (self.cart_idle_timeout, self.allocation, self.shipping_fee);
+        let order = self
+            .uow
+            .transaction(move |tx| {
+                Box::pin(async move {
+                    let cart = live_cart(tx, &CartOwner::User(user_id), idle_timeout)
+                        .await?
+                        .filter(|c| !c.lines.is_empty())
+                        .ok_or_else(|| DomainError::Validation("cart is empty".into()))?;
+
+                    let mut lines = Vec::with_capacity(cart.lines.len());
+                    let mut pricing_lines = Vec::with_capacity(cart.lines.len());
+                    for line in &cart.lines {
+                        let product = available_product(tx, line.product_id, line.quantity, Some(cart.id)).await?;
+                        pricing_lines.push(PricingLine {
+                            product_id: product.id,
+                            category_id: product.category_id,
+                            quantity: line.quantity,
+                            unit_price: product.price,
+                        });
+                        lines.push(OrderLine {
+                            product_id: product.id,
+                            name: product.name,
+                            quantity: line.quantity,
+                            unit_price: product.price,
+                        });
+                    }
+
+                    let pricing =
+                        price_with_promotions(tx, cart.coupon.as_deref(), Some(user_id), &pricing_lines, shipping).await?;
+                    if let Some(note) = pricing.coupon_note {
+                        return Err(DomainError::Validation(note));
+                    }
+                    let total = pricing.total;
+                    let id = tx
+                        .orders
+                        .create(Order {
+                            id: 0,
+                            user_id: Some(user_id),
+                            lines,
+                            discount: pricing.discount,
+                            shipping: pricing.shipping,
+                            total,
+                            status: OrderStatus::Pending,
+                            created_at: Utc::now(),
+                        })
+                        .await?;
+                    let placed = StatusChange {
+                        from: None,
+                        to: OrderStatus::Pending,
+                        actor_id: Some(user_id),
+                        reason: None,
+                        changed_at: Utc::now(),
+                    };
+                    tx.orders.record_status_change(id, placed).await?;
+                    for applied in &pricing.promotions {
+                        let redemption = Redemption {
+                            promotion_id: applied.promotion_id,
+                            user_id: Some(user_id),
+                            order_id: id,
+                            discount: applied.discount,
+                        };
+                        tx.promotions.redeem(redemption).await?;
+                    }
+                    let ranked = allocation.rank(tx.warehouses.list().await?, ship_to);
+                    let product_ids: Vec<_> = cart.lines.iter().map(|l| l.product_id).collect();
+                    let mut held: HashMap<i64, HashMap<i64, i32>> = HashMap::new();
+                    for stock in tx.warehouses.stock(&product_ids).await? {
+                        held.entry(stock.product_id).or_default().insert(stock.warehouse_id, stock.stock);
+                    }
+                    for line in &cart.lines {
+                        // sold by a concurrent checkout since the read;
+                        // retrying re-reads the stock
+                        let changed = || DomainError::Conflict(format!("stock of product {} changed", line.product_id));
+                        let stock = held.get(&line.product_id).cloned().unwrap_or_default();
+                        let allocated = allocate(&ranked, &stock, line.quantity).ok_or_else(changed)?;
+                        for (warehouse_id, quantity) in allocated {
+                            let sale = stock_movement(
+                                line.product_id,
+                                warehouse_id,
+                                MovementKind::Sale,
+                                -quantity,
+                                id,
+                                Some(user_id),
+                            );
+                            if record_movement(tx, sale).await?.is_none() {
+                                return Err(changed());
+                            }
+                        }
+                    }
+                    tx.carts.delete(cart.id).await?;
+                    tx.outbox.append(&DomainEvent::OrderPlaced { id, user_id, total }).await?;
+                    tx.orders.get(id).await?.ok_or(DomainError::NotFound)
+                })
+            })
+            .await?;
+        for line in &order.lines {
+            self.cache.invalidate(Invalidation::Product(line.product_id)).await;
+        }
+        Ok(order)
+    }
+
+    /// Move the order to status `to` on behalf of `actor_id`. Illegal moves
+    /// fail with [`DomainError::IllegalTransition`]; cancellations put the
+    /// stock back. Paid and refunded are not set here but follow the
+    /// payment, see [`PaymentService`](crate::usecases::payment_service::PaymentService).
+    pub async fn transition(
+        &self,
+        id: i64,
+        to: OrderStatus,
+        actor_id: i64,
+        reason: Option<String>,
+    ) -> Result<Order, DomainError> {
+        match to {
+            OrderStatus::Paid => return Err(DomainError::Validation("an order becomes paid by paying for it".into())),
+            OrderStatus::Refunded => {
+                return Err(DomainError::Validation("an order is refunded by refunding its payment".into()));
+            }
+            _ => {}
+        }
+        let (order, restocked) = self
+            .uow
+            .transaction(move |tx| {
+                let reason = reason.clone();
+                Box::pin(async move { transition_order(tx, id, to, Some(actor_id), reason).await })
+            })
+            .await?;
+        if restocked {
+            for line in &order.lines {
+                self.cache.invalidate(Invalidation::Product(line.product_id)).await;
+            }
+        }
+        Ok(order)
+    }
+
+    /// Every status the order went through, oldest first.
+    pub async fn history(&self, id: i64) -> Result<Vec<StatusChange>, DomainError> {
+        if self.repo.get(id).await?.is_none() {
+            return Err(DomainError::NotFound);
+        }
+        self.repo.status_history(id).await
+    }
+
+    /// The user's order; `NotFound` for anybody else's.
+    pub async fn get(&self, user_id: i64, id: i64) -> Result<Order, DomainError> {
+        self.repo
+            .get(id)
+            .await?
+            .filter(|o| o.user_id == Some(user_id))
+            .ok_or(DomainError::NotFound)
+    }
+
+    /// Newest first.
+    pub async fn get_by_user_id(&self, user_id: i64, page: Page) -> Result<Vec<Order>, DomainError> {
+        self.repo.get_by_user_id(user_id, page).await
+    }
+}
+
+/// Move order `id` to `to` inside `tx`, putting the stock back if the move
+/// calls for it; also whether it did. The caller invalidates the cache.
+pub(crate) async fn transition_order(
+    tx: &TxRepositories,
+    id: i64,
+    to: OrderStatus,
+    actor_id: Option<i64>,
+    reason: Option<String>,
+) -> Result<(Order, bool), DomainError> {
+    let order = tx.orders.get(id).await?.ok_or(DomainError::NotFound)?;
+    let from = order.status;
+    from.transition(to)?;
+    let restocks = from.restocks(to);
+    if restocks {
+        // back to the warehouses the sales took it from
+        for sale in tx.inventory.order_movements(id).await? {
+            if sale.kind != MovementKind::Sale {
+                continue;
+            }
+            let returned = StockMovement {
+                reason: Some(format!("order {id} {to}")),
+                ..stock_movement(sale.product_id, sale.warehouse_id, MovementKind::Return, -sale.quantity, id, actor_id)
+            };
+            record_movement(tx, returned).await?;
+        }
+    }
+    let change = StatusChange { from: Some(from), to, actor_id, reason, changed_at: Utc::now() };
+    tx.orders.record_status_change(id, change).await?;
+    tx.outbox.append(&DomainEvent::OrderStatusChanged { id, from, to }).await?;
+    let order = tx.orders.get(id).await?.ok_or(DomainError::NotFound)?;
+    Ok((order, restocks))
+}
+
+fn stock_movement(
+    product_id: i64,
+    warehouse_id: i64,
+    kind: MovementKind,
+    quantity: i32,
+    order_id: i64,
+    actor_id: Option<i64>,
+) -> StockMovement {
+    StockMovement {
+        id: 0,
+        product_id,
+        warehouse_id,
+        kind,
+        quantity,
+        reason: Some(format!("order {order_id}")),
+        actor_id,
+        order_id: Some(order_id),
+        created_at: Utc::now(),
+    }
+}
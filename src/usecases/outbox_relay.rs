@@ -0,0 +1,193 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tracing::Instrument;
+
+use crate::domain::event::{EventSink, OutboxMessage};
+use crate::domain::uow::UnitOfWork;
+use crate::domain::DomainError;
+use crate::infra::propagation::set_parent_from_traceparent;
+
+/// How long a claimed batch is kept from other relays; a relay that dies
+/// while publishing leaves its batch to the next one after this.
+const CLAIM_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Publishes outbox messages to every sink, oldest first.
+///
+/// Delivery is at-least-once: a message is marked published only after all
+/// sinks accepted it, so a crash or a failing sink means it is sent again
+/// (consumers dedupe on the message id). A message that fails is tried again
+/// after a delay doubling per attempt, and given up on (dead-lettered) after
+/// `max_attempts`; until then later messages of the same aggregate wait,
+/// keeping per-aggregate order, while the rest of the outbox goes on.
+///
+/// No transaction is open while the sinks are called: a batch is claimed in
+/// one, published, and the outcomes recorded in another, so a transaction
+/// retried on conflict never sends anything twice.
+#[derive(Clone)]
+pub struct OutboxRelay {
+    uow: Arc<dyn UnitOfWork>,
+    sinks: Arc<Vec<Arc<dyn EventSink>>>,
+    batch_size: i64,
+    max_attempts: i32,
+    first_retry_delay: Duration,
+}
+
+impl OutboxRelay {
+    pub fn new(uow: Arc<dyn UnitOfWork>, sinks: Vec<Arc<dyn EventSink>>) -> Self {
+        Self {
+            uow,
+            sinks: Arc::new(sinks),
+            batch_size: 100,
+            max_attempts: 10,
+            first_retry_delay: Duration::from_secs(5),
+        }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: i64) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Give a message `max_attempts` tries, the second one `first_delay`
+    /// after the first (default 10 tries, 5s).
+    pub fn with_retries(mut self, max_attempts: i32, first_delay: Duration) -> Self {
+        self.max_attempts = max_attempts;
+        self.first_retry_delay = first_delay;
+        self
+    }
+
+    /// Delay before attempt `attempts + 1`: doubling per attempt, at most 1h.
+    fn retry_delay(&self, attempts: i32) -> Duration {
+        let exponent = u32::try_from(attempts.saturating_sub(1)).unwrap_or(0).min(20);
+        (self.first_retry_delay * 2u32.pow(exponent)).min(Duration::from_secs(3600))
+    }
+
+    /// Publish one batch; returns how many messages went out.
+    pub async fn run_once(&self) -> Result<usize, DomainError> {
+        let batch_size = self.batch_size;
+        let batch = self
+            .uow
+            .transaction(move |tx| {
+                Box::pin(async move {
+                    if !tx.outbox.lock_relay().await? {
+                        // another relay instance is on it
+                        return Ok(Vec::new());
+                    }
+                    let batch = tx.outbox.pending(batch_size).await?;
+                    let ids: Vec<_> = batch.iter().map(|m| m.id).collect();
+                    tx.outbox.hold(&ids, Some(Utc::now() + CLAIM_TIMEOUT)).await?;
+                    Ok(batch)
+                })
+            })
+            .await?;
+        if batch.is_empty() {
+            return Ok(0);
+        }
+
+        let mut blocked = HashSet::new();
+        let mut outcomes = Vec::with_capacity(batch.len());
+        for message in batch {
+            let aggregate = (message.aggregate_type.clone(), message.aggregate_id);
+            if blocked.contains(&aggregate) {
+                outcomes.push((message.id, Outcome::Skipped));
+                continue;
+            }
+            match publish(&self.sinks, &message).await {
+                Ok(()) => outcomes.push((message.id, Outcome::Published)),
+                Err(e) => {
+                    let attempts = message.attempts + 1;
+                    let retry_at = (attempts < self.max_attempts).then(|| Utc::now() + self.retry_delay(attempts));
+                    if retry_at.is_none() {
+                        tracing::error!(message.id = message.id, attempts, error = %e, "outbox message given up");
+                    }
+                    outcomes.push((message.id, Outcome::Failed(e.to_string(), retry_at)));
+                    blocked.insert(aggregate);
+                }
+            }
+        }
+
+        let published = outcomes.iter().filter(|(_, o)| matches!(o, Outcome::Published)).count();
+        self.uow
+            .transaction(move |tx| {
+                let outcomes = outcomes.clone();
+                Box::pin(async move {
+                    let mut skipped = Vec::new();
+                    for (id, outcome) in outcomes {
+                        match outcome {
+                            Outcome::Published => tx.outbox.mark_published(id).await?,
+                            Outcome::Failed(error, retry_at) => tx.outbox.mark_failed(id, error, retry_at).await?,
+                            Outcome::Skipped => skipped.push(id),
+                        }
+                    }
+                    // still behind the failed one, which holds them back
+                    tx.outbox.hold(&skipped, None).await
+                })
+            })
+            .await?;
+        Ok(published)
+    }
+
+    /// Poll every `interval` until `shutdown` resolves; a full batch is
+    /// followed by the next one right away.
+    pub async fn run(self, interval: Duration, shutdown: impl Future<Output = ()>) {
+        tokio::pin!(shutdown);
+        loop {
+            let wait = match self.run_once().await {
+                Ok(n) if n as i64 >= self.batch_size => Duration::ZERO,
+                Ok(_) => interval,
+                Err(e) => {
+                    tracing::warn!(error = %e, "outbox relay run failed");
+                    interval
+                }
+            };
+            tokio::select! {
+                _ = &mut shutdown => return,
+                _ = tokio::time::sleep(wait) => {}
+            }
+        }
+    }
+}
+
+/// What became of a claimed message.
+#[derive(Clone)]
+enum Outcome {
+    Published,
+    /// The error, and when to try again; never when `None`.
+    Failed(String, Option<DateTime<Utc>>),
+    /// Not tried, its aggregate failed earlier in the batch.
+    Skipped,
+}
+
+/// Send `message` to every sink, in a span linked to the request that
+/// raised it.
+async fn publish(sinks: &[Arc<dyn EventSink>], message: &OutboxMessage) -> Result<(), DomainError> {
+    let span = tracing::info_span!(
+        "outbox publish",
+        otel.name = format!("publish {}", message.event_type),
+        otel.kind = "producer",
+        messaging.operation.type = "send",
+        messaging.message.id = message.id,
+        event.type = %message.event_type,
+        aggregate.type = %message.aggregate_type,
+        aggregate.id = message.aggregate_id,
+    );
+    if let Some(traceparent) = &message.trace_context {
+        set_parent_from_traceparent(&span, traceparent);
+    }
+
+    async {
+        for sink in sinks {
+            if let Err(e) = sink.publish(message).await {
+                tracing::warn!(sink = sink.name(), error = %e, "event publish failed");
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+    .instrument(span)
+    .await
+}
@@ -0,0 +1,352 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+
+use crate::domain::cache::{CacheInvalidator, Invalidation, NoCache};
+use crate::domain::event::DomainEvent;
+use crate::domain::order::{IllegalTransition, Order, OrderStatus};
+use crate::domain::payment::{
+    Authorization, Payment, PaymentAttempt, PaymentIntent, PaymentOperation, PaymentProvider, PaymentRepository, PaymentStatus,
+    WebhookKind,
+};
+use crate::domain::uow::{TxRepositories, UnitOfWork};
+use crate::domain::DomainError;
+use crate::usecases::order_service::transition_order;
+
+#[derive(Clone)]
+pub struct PaymentService {
+    repo: Arc<dyn PaymentRepository>,
+    uow: Arc<dyn UnitOfWork>,
+    provider: Arc<dyn PaymentProvider>,
+    cache: Arc<dyn CacheInvalidator>,
+}
+
+impl PaymentService {
+    pub fn new(repo: Arc<dyn PaymentRepository>, uow: Arc<dyn UnitOfWork>, provider: Arc<dyn PaymentProvider>) -> Self {
+        Self { repo, uow, provider, cache: Arc::new(NoCache) }
+    }
+
+    /// Report stock put back by refunds to `cache`, for a cached product
+    /// repository.
+    pub fn with_cache_invalidator(mut self, cache: Arc<dyn CacheInvalidator>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Pay for the user's pending order: authorize its total with the
+    /// provider, capture it and mark the order paid. After a failure it is
+    /// safe to call again; an unfinished intent carries on where it stopped,
+    /// a declined one is replaced by a new one.
+    pub async fn pay(&self, user_id: i64, order_id: i64) -> Result<Payment, DomainError> {
+        let provider = self.provider.name();
+        let mut intent = self
+            .uow
+            .transaction(move |tx| {
+                Box::pin(async move {
+                    let order = tx
+                        .orders
+                        .get(order_id)
+                        .await?
+                        .filter(|o| o.user_id == Some(user_id))
+                        .ok_or(DomainError::NotFound)?;
+                    order.status.transition(OrderStatus::Paid)?;
+
+                    let open = tx.payments.get_intents_by_order_id(order_id).await?.into_iter().find(|i| {
+                        i.provider == provider && matches!(i.status, PaymentStatus::Pending | PaymentStatus::Authorized)
+                    });
+                    if let Some(intent) = open {
+                        return Ok(intent);
+                    }
+                    let id = tx
+                        .payments
+                        .create_intent(PaymentIntent {
+                            id: 0,
+                            order_id,
+                            provider: provider.to_string(),
+                            provider_ref: None,
+                            amount: order.total,
+                            status: PaymentStatus::Pending,
+                            created_at: Utc::now(),
+                            updated_at: Utc::now(),
+                        })
+                        .await?;
+                    tx.payments.get_intent(id).await?.ok_or(DomainError::NotFound)
+                })
+            })
+            .await?;
+        let amount_cents = cents(intent.amount);
+
+        if intent.status == PaymentStatus::Pending {
+            match self.provider.authorize(&intent.reference(), amount_cents).await {
+                Ok(Authorization::Authorized { provider_ref }) => {
+                    intent.status = PaymentStatus::Authorized;
+                    intent.provider_ref = Some(provider_ref.clone());
+                    let id = intent.id;
+                    self.uow
+                        .transaction(move |tx| {
+                            let provider_ref = provider_ref.clone();
+                            Box::pin(async move {
+                                tx.payments.record_attempt(id, attempt(PaymentOperation::Authorize, true, None)).await?;
+                                tx.payments.update_intent(id, PaymentStatus::Authorized, Some(provider_ref)).await
+                            })
+                        })
+                        .await?;
+                }
+                Ok(Authorization::Declined { reason }) => {
+                    let (id, declined) = (intent.id, reason.clone());
+                    self.uow
+                        .transaction(move |tx| {
+                            let reason = reason.clone();
+                            Box::pin(async move {
+                                let failed = attempt(PaymentOperation::Authorize, false, Some(reason.clone()));
+                                tx.payments.record_attempt(id, failed).await?;
+                                tx.payments.update_intent(id, PaymentStatus::Failed, None).await?;
+                                tx.outbox.append(&DomainEvent::PaymentFailed { id, order_id, reason }).await?;
+                                Ok(())
+                            })
+                        })
+                        .await?;
+                    return Err(DomainError::PaymentDeclined(declined));
+                }
+                Err(e) => return Err(self.failed(intent.id, PaymentOperation::Authorize, e).await),
+            }
+        }
+
+        let provider_ref = intent
+            .provider_ref
+            .clone()
+            .ok_or_else(|| DomainError::Unexpected(format!("payment intent {} authorized without a reference", intent.id)))?;
+        if let Err(e) = self.provider.capture(&provider_ref, amount_cents).await {
+            return Err(self.failed(intent.id, PaymentOperation::Capture, e).await);
+        }
+        let id = intent.id;
+        let stranded = self
+            .uow
+            .transaction(move |tx| {
+                Box::pin(async move {
+                    tx.payments.record_attempt(id, attempt(PaymentOperation::Capture, true, None)).await?;
+                    captured(tx, id, Some(user_id)).await
+                })
+            })
+            .await?;
+        if let Some(status) = stranded {
+            // cancelled while the payment was under way
+            self.refund_stranded(id, status).await?;
+            return Err(IllegalTransition { from: status, to: OrderStatus::Paid }.into());
+        }
+        self.payment(id).await
+    }
+
+    /// Refund the order's captured payment and mark the order refunded,
+    /// putting the stock back if it had not shipped yet.
+    pub async fn refund(&self, order_id: i64, actor_id: i64, reason: Option<String>) -> Result<Payment, DomainError> {
+        let intent = self
+            .uow
+            .transaction(move |tx| {
+                Box::pin(async move {
+                    let order = tx.orders.get(order_id).await?.ok_or(DomainError::NotFound)?;
+                    order.status.transition(OrderStatus::Refunded)?;
+                    tx.payments
+                        .get_intents_by_order_id(order_id)
+                        .await?
+                        .into_iter()
+                        .find(|i| i.status == PaymentStatus::Captured)
+                        .ok_or_else(|| DomainError::Validation(format!("order {order_id} has no captured payment")))
+                })
+            })
+            .await?;
+        if intent.provider != self.provider.name() {
+            return Err(DomainError::Validation(format!("order {order_id} was paid with {}", intent.provider)));
+        }
+        let provider_ref = intent.provider_ref.clone().unwrap_or_default();
+        if let Err(e) = self.provider.refund(&provider_ref, cents(intent.amount)).await {
+            return Err(self.failed(intent.id, PaymentOperation::Refund, e).await);
+        }
+
+        let id = intent.id;
+        let restocked = self
+            .uow
+            .transaction(move |tx| {
+                let reason = reason.clone();
+                Box::pin(async move {
+                    tx.payments.record_attempt(id, attempt(PaymentOperation::Refund, true, None)).await?;
+                    refunded(tx, id, Some(actor_id), reason).await
+                })
+            })
+            .await?;
+        self.invalidate(restocked.as_ref()).await;
+        self.payment(id).await
+    }
+
+    /// Apply a webhook from the provider. Events seen before are ignored, as
+    /// are events about payments this service does not know.
+    pub async fn handle_webhook(&self, signature: &str, body: &[u8]) -> Result<(), DomainError> {
+        let event = self.provider.verify_webhook(signature, body)?;
+        let provider = self.provider.name();
+        let (restocked, stranded) = self
+            .uow
+            .transaction(move |tx| {
+                let event = event.clone();
+                Box::pin(async move {
+                    if !tx.payments.record_webhook_event(provider, &event.id).await? {
+                        tracing::debug!(event_id = %event.id, "payment webhook seen before");
+                        return Ok((None, None));
+                    }
+                    let Some(intent) = tx.payments.get_intent_by_provider_ref(provider, &event.provider_ref).await? else {
+                        tracing::warn!(event_id = %event.id, provider_ref = %event.provider_ref, "payment webhook for unknown payment");
+                        return Ok((None, None));
+                    };
+                    let received = attempt(PaymentOperation::Webhook, true, Some(event.kind.as_str().to_string()));
+                    tx.payments.record_attempt(intent.id, received).await?;
+                    match event.kind {
+                        WebhookKind::Captured => {
+                            let stranded = captured(tx, intent.id, None).await?;
+                            Ok((None, stranded.map(|status| (intent.id, status))))
+                        }
+                        WebhookKind::Refunded => {
+                            let reason = Some("refunded by the payment provider".into());
+                            Ok((refunded(tx, intent.id, None, reason).await?, None))
+                        }
+                        WebhookKind::Failed => {
+                            if matches!(intent.status, PaymentStatus::Pending | PaymentStatus::Authorized) {
+                                tx.payments.update_intent(intent.id, PaymentStatus::Failed, None).await?;
+                                let reason = "reported by the payment provider".to_string();
+                                tx.outbox
+                                    .append(&DomainEvent::PaymentFailed { id: intent.id, order_id: intent.order_id, reason })
+                                    .await?;
+                            }
+                            Ok((None, None))
+                        }
+                    }
+                })
+            })
+            .await?;
+        self.invalidate(restocked.as_ref()).await;
+        if let Some((id, status)) = stranded {
+            self.refund_stranded(id, status).await?;
+        }
+        Ok(())
+    }
+
+    /// The order's payments, newest first.
+    pub async fn payments(&self, order_id: i64) -> Result<Vec<Payment>, DomainError> {
+        let intents = self.repo.get_intents_by_order_id(order_id).await?;
+        let mut payments = Vec::with_capacity(intents.len());
+        for intent in intents {
+            let attempts = self.repo.attempts(intent.id).await?;
+            payments.push(Payment { intent, attempts });
+        }
+        Ok(payments)
+    }
+
+    async fn payment(&self, id: i64) -> Result<Payment, DomainError> {
+        let intent = self.repo.get_intent(id).await?.ok_or(DomainError::NotFound)?;
+        let attempts = self.repo.attempts(id).await?;
+        Ok(Payment { intent, attempts })
+    }
+
+    /// Give back the capture of intent `id`, whose order went `status` while
+    /// the payment was under way and can no longer be paid. If the provider
+    /// refuses, the payment stays captured and a
+    /// [`DomainEvent::PaymentNeedsAttention`] says so.
+    async fn refund_stranded(&self, id: i64, status: OrderStatus) -> Result<(), DomainError> {
+        let intent = self.repo.get_intent(id).await?.ok_or(DomainError::NotFound)?;
+        let order_id = intent.order_id;
+        tracing::warn!(intent_id = id, order_id, status = %status, "payment captured for an order that cannot be paid, refunding it");
+        let provider_ref = intent.provider_ref.clone().unwrap_or_default();
+        let failure = match self.provider.refund(&provider_ref, cents(intent.amount)).await {
+            Ok(()) => None,
+            Err(e) => Some(self.failed(id, PaymentOperation::Refund, e).await.to_string()),
+        };
+        if let Some(error) = &failure {
+            tracing::error!(intent_id = id, order_id, error = %error, "payment for a {status} order could not be refunded");
+        }
+        self.uow
+            .transaction(move |tx| {
+                let failure = failure.clone();
+                Box::pin(async move {
+                    match failure {
+                        None => {
+                            tx.payments.record_attempt(id, attempt(PaymentOperation::Refund, true, None)).await?;
+                            refunded(tx, id, None, None).await?;
+                        }
+                        Some(error) => {
+                            let reason = format!("captured for {status} order {order_id}, refund failed: {error}");
+                            tx.outbox.append(&DomainEvent::PaymentNeedsAttention { id, order_id, reason }).await?;
+                        }
+                    }
+                    Ok(())
+                })
+            })
+            .await
+    }
+
+    /// Record the failed call and hand back its error.
+    async fn failed(&self, intent_id: i64, operation: PaymentOperation, e: DomainError) -> DomainError {
+        tracing::warn!(intent_id, operation = %operation, error = %e, "payment provider call failed");
+        if let Err(record) = self.repo.record_attempt(intent_id, attempt(operation, false, Some(e.to_string()))).await {
+            tracing::error!(intent_id, error = %record, "failed to record payment attempt");
+        }
+        e
+    }
+
+    async fn invalidate(&self, restocked: Option<&Order>) {
+        for line in restocked.map(|o| o.lines.as_slice()).unwrap_or_default() {
+            self.cache.invalidate(Invalidation::Product(line.product_id)).await;
+        }
+    }
+}
+
+fn attempt(operation: PaymentOperation, succeeded: bool, detail: Option<String>) -> PaymentAttempt {
+    PaymentAttempt { operation, succeeded, detail, created_at: Utc::now() }
+}
+
+/// Amounts are NUMERIC(10, 2), so whole cents.
+fn cents(amount: f64) -> i64 {
+    (amount * 100.0).round() as i64
+}
+
+/// Mark intent `id` captured and its order paid, unless that already
+/// happened (the capture call and the provider's webhook both report it).
+/// The order's status if it can no longer be paid, say because it was
+/// cancelled in the meantime; the caller then gives the money back.
+async fn captured(tx: &TxRepositories, id: i64, actor_id: Option<i64>) -> Result<Option<OrderStatus>, DomainError> {
+    let intent = tx.payments.get_intent(id).await?.ok_or(DomainError::NotFound)?;
+    if !matches!(intent.status, PaymentStatus::Pending | PaymentStatus::Authorized) {
+        return Ok(None);
+    }
+    tx.payments.update_intent(id, PaymentStatus::Captured, None).await?;
+    tx.outbox
+        .append(&DomainEvent::PaymentCaptured { id, order_id: intent.order_id, amount: intent.amount })
+        .await?;
+    let order = tx.orders.get(intent.order_id).await?.ok_or(DomainError::NotFound)?;
+    if !order.status.can_become(OrderStatus::Paid) {
+        return Ok(Some(order.status));
+    }
+    transition_order(tx, order.id, OrderStatus::Paid, actor_id, Some("payment captured".into())).await?;
+    Ok(None)
+}
+
+/// Mark intent `id` refunded and its order too, if it can be; the order if
+/// that put stock back.
+async fn refunded(
+    tx: &TxRepositories,
+    id: i64,
+    actor_id: Option<i64>,
+    reason: Option<String>,
+) -> Result<Option<Order>, DomainError> {
+    let intent = tx.payments.get_intent(id).await?.ok_or(DomainError::NotFound)?;
+    if intent.status != PaymentStatus::Captured {
+        return Ok(None);
+    }
+    tx.payments.update_intent(id, PaymentStatus::Refunded, None).await?;
+    tx.outbox
+        .append(&DomainEvent::PaymentRefunded { id, order_id: intent.order_id, amount: intent.amount })
+        .await?;
+    let order = tx.orders.get(intent.order_id).await?.ok_or(DomainError::NotFound)?;
+    if !order.status.can_become(OrderStatus::Refunded) {
+        return Ok(None);
+    }
+    let (order, restocked) = transition_order(tx, order.id, OrderStatus::Refunded, actor_id, reason).await?;
+    Ok(restocked.then_some(order))
+}
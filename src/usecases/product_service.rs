@@ -1,34 +1,115 @@
 use std::sync::Arc;
 
+use chrono::Utc;
+
 use crate::domain::DomainError;
+use crate::domain::cache::{CacheInvalidator, Invalidation, NoCache};
+use crate::domain::event::DomainEvent;
+use crate::domain::inventory::{MovementKind, StockMovement};
+use crate::domain::page::Page;
 use crate::domain::product::{Product, ProductRepository};
+use crate::domain::reservation::{ProductAvailability, ReservationRepository};
+use crate::domain::uow::UnitOfWork;
+use crate::domain::warehouse::WarehouseRepository;
+use crate::usecases::inventory_service::{default_warehouse, record_movement};
 
 #[derive(Clone)]
 pub struct ProductService {
     repo: Arc<dyn ProductRepository>,
+    reservations: Arc<dyn ReservationRepository>,
+    warehouses: Arc<dyn WarehouseRepository>,
+    uow: Arc<dyn UnitOfWork>,
+    cache: Arc<dyn CacheInvalidator>,
 }
 
 impl ProductService {
-    pub fn new(repo: Arc<dyn ProductRepository>) -> Self {
-        Self { repo }
+    pub fn new(
+        repo: Arc<dyn ProductRepository>,
+        reservations: Arc<dyn ReservationRepository>,
+        warehouses: Arc<dyn WarehouseRepository>,
+        uow: Arc<dyn UnitOfWork>,
+    ) -> Self {
+        Self { repo, reservations, warehouses, uow, cache: Arc::new(NoCache) }
+    }
+
+    /// Report committed writes to `cache`, for a cached `repo`.
+    pub fn with_cache_invalidator(mut self, cache: Arc<dyn CacheInvalidator>) -> Self {
+        self.cache = cache;
+        self
     }
 
+    /// Create the product; its starting stock is booked as a receipt into
+    /// the default warehouse, so the ledger accounts for it like for any
+    /// later change.
     pub async fn create(&self, product: Product) -> Result<i64, DomainError> {
         if product.name.trim().is_empty() {
             return Err(DomainError::Validation("name is required".into()));
         }
-        self.repo.create(product).await
+        if product.stock < 0 {
+            return Err(DomainError::Validation("stock cannot be negative".into()));
+        }
+        let id = self
+            .uow
+            .transaction(move |tx| {
+                let product = product.clone();
+                Box::pin(async move {
+                    let stock = product.stock;
+                    let id = tx.products.create(Product { stock: 0, ..product }).await?;
+                    if stock > 0 {
+                        let receipt = StockMovement {
+                            id: 0,
+                            product_id: id,
+                            warehouse_id: default_warehouse(tx).await?.id,
+                            kind: MovementKind::Receipt,
+                            quantity: stock,
+                            reason: Some("initial stock".into()),
+                            actor_id: None,
+                            order_id: None,
+                            created_at: Utc::now(),
+                        };
+                        record_movement(tx, receipt).await?;
+                    }
+                    // the stored row, with the price as the database rounded it
+                    let stored = tx.products.get_by_product_id(id).await?.ok_or(DomainError::NotFound)?;
+                    tx.outbox.append(&DomainEvent::product_created(&stored)).await?;
+                    Ok(id)
+                })
+            })
+            .await?;
+        self.cache.invalidate(Invalidation::Product(id)).await;
+        Ok(id)
     }
 
     pub async fn get_by_product_id(&self, id: i64) -> Result<Option<Product>, DomainError> {
         self.repo.get_by_product_id(id).await
     }
 
-    pub async fn get_by_category_id(&self, category_id: i64) -> Result<Vec<Product>, DomainError> {
-        self.repo.get_by_category_id(category_id).await
+    pub async fn get_by_category_id(&self, category_id: i64, page: Page) -> Result<Vec<Product>, DomainError> {
+        self.repo.get_by_category_id(category_id, page).await
+    }
+
+    pub async fn get_all_products(&self, page: Page) -> Result<Vec<Product>, DomainError> {
+        self.repo.get_all_products(page).await
     }
 
-    pub async fn get_all_products(&self) -> Result<Vec<Product>, DomainError> {
-        self.repo.get_all_products().await
+    /// Each product with what carts currently hold of it and, if
+    /// `locations`, which warehouses hold its stock. Neither is cached, they
+    /// change too often.
+    pub async fn with_availability(
+        &self,
+        products: Vec<Product>,
+        locations: bool,
+    ) -> Result<Vec<ProductAvailability>, DomainError> {
+        let ids: Vec<_> = products.iter().map(|p| p.id).collect();
+        let reserved = self.reservations.reserved(&ids, None, Utc::now()).await?;
+        let stock = if locations { self.warehouses.stock(&ids).await? } else { Vec::new() };
+        Ok(products
+            .into_iter()
+            .map(|product| {
+                let reserved = reserved.get(&product.id).copied().unwrap_or_default();
+                let locations = locations.then(|| stock.iter().filter(|s| s.product_id == product.id).cloned().collect());
+                ProductAvailability { product, reserved, locations }
+            })
+            .collect())
     }
 }
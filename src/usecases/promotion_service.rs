@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::promotion::{apply, Pricing, PricingLine, Promotion, PromotionRepository};
+use crate::domain::uow::{TxRepositories, UnitOfWork};
+use crate::domain::DomainError;
+
+/// A promotion and how often it was redeemed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromotionUsage {
+    pub promotion: Promotion,
+    pub uses: i64,
+}
+
+#[derive(Clone)]
+pub struct PromotionService {
+    repo: Arc<dyn PromotionRepository>,
+    uow: Arc<dyn UnitOfWork>,
+}
+
+impl PromotionService {
+    pub fn new(repo: Arc<dyn PromotionRepository>, uow: Arc<dyn UnitOfWork>) -> Self {
+        Self { repo, uow }
+    }
+
+    /// Add a promotion. Codes are unique, whatever their case.
+    pub async fn create(&self, promotion: Promotion) -> Result<PromotionUsage, DomainError> {
+        let promotion = normalized(promotion)?;
+        self.uow
+            .transaction(move |tx| {
+                let promotion = promotion.clone();
+                Box::pin(async move {
+                    check_references(tx, &promotion).await?;
+                    let id = tx.promotions.create(promotion.clone()).await?;
+                    Ok(PromotionUsage { promotion: Promotion { id, ..promotion }, uses: 0 })
+                })
+            })
+            .await
+    }
+
+    pub async fn get(&self, id: i64) -> Result<PromotionUsage, DomainError> {
+        let promotion = self.repo.get(id).await?.ok_or(DomainError::NotFound)?;
+        let uses = self.repo.uses(id, None).await?;
+        Ok(PromotionUsage { promotion, uses })
+    }
+
+    /// By id.
+    pub async fn list(&self) -> Result<Vec<PromotionUsage>, DomainError> {
+        let uses = self.repo.uses_by_promotion(None).await?;
+        let promotions = self.repo.list().await?;
+        Ok(promotions
+            .into_iter()
+            .map(|promotion| PromotionUsage { uses: count(&uses, promotion.id), promotion })
+            .collect())
+    }
+
+    /// Replace every setting of promotion `promotion.id`; its redemptions
+    /// stay and keep counting towards the limits.
+    pub async fn update(&self, promotion: Promotion) -> Result<PromotionUsage, DomainError> {
+        let promotion = normalized(promotion)?;
+        self.uow
+            .transaction(move |tx| {
+                let promotion = promotion.clone();
+                Box::pin(async move {
+                    check_references(tx, &promotion).await?;
+                    if !tx.promotions.update(promotion.clone()).await? {
+                        return Err(DomainError::NotFound);
+                    }
+                    let uses = tx.promotions.uses(promotion.id, None).await?;
+                    Ok(PromotionUsage { promotion, uses })
+                })
+            })
+            .await
+    }
+
+    /// Delete the promotion with its redemptions; carts holding its code
+    /// are told it no longer exists.
+    pub async fn delete(&self, id: i64) -> Result<(), DomainError> {
+        if !self.repo.delete(id).await? {
+            return Err(DomainError::NotFound);
+        }
+        Ok(())
+    }
+}
+
+/// Uses of promotion `id` in counts from
+/// [`PromotionRepository::uses_by_promotion`].
+fn count(uses: &HashMap<i64, i64>, id: i64) -> i64 {
+    uses.get(&id).copied().unwrap_or(0)
+}
+
+/// A coupon code as stored: trimmed, upper case.
+pub(crate) fn normalize_code(code: &str) -> String {
+    code.trim().to_uppercase()
+}
+
+fn normalized(promotion: Promotion) -> Result<Promotion, DomainError> {
+    let promotion = Promotion {
+        name: promotion.name.trim().to_string(),
+        code: promotion.code.as_deref().map(normalize_code),
+        ..promotion
+    };
+    promotion.validate().map_err(DomainError::Validation)?;
+    Ok(promotion)
+}
+
+/// The code is free and the category or product exists.
+async fn check_references(tx: &TxRepositories, promotion: &Promotion) -> Result<(), DomainError> {
+    if let Some(code) = &promotion.code
+        && tx.promotions.by_code(code).await?.is_some_and(|p| p.id != promotion.id)
+    {
+        return Err(DomainError::Validation(format!("coupon {code} already exists")));
+    }
+    if let Some(category_id) = promotion.category_id
+        && tx.categories.get_by_id(category_id).await?.is_none()
+    {
+        return Err(DomainError::Validation(format!("no category {category_id}")));
+    }
+    if let Some(product_id) = promotion.product_id
+        && tx.products.get_by_product_id(product_id).await?.is_none()
+    {
+        return Err(DomainError::Validation(format!("no product {product_id}")));
+    }
+    Ok(())
+}
+
+/// Why the promotion cannot be used at `now` by `user_id`, if anybody in
+/// particular; see [`Promotion::unusable`].
+pub(crate) async fn unusable(
+    tx: &TxRepositories,
+    promotion: &Promotion,
+    user_id: Option<i64>,
+    now: DateTime<Utc>,
+) -> Result<Option<String>, DomainError> {
+    let uses = match promotion.max_uses {
+        Some(_) => tx.promotions.uses(promotion.id, None).await?,
+        None => 0,
+    };
+    let user_uses = match (promotion.max_uses_per_user, user_id) {
+        (Some(_), Some(user_id)) => Some(tx.promotions.uses(promotion.id, Some(user_id)).await?),
+        _ => None,
+    };
+    Ok(promotion.unusable(now, uses, user_uses))
+}
+
+/// Price `lines` with every promotion without a code that can be used now,
+/// then the one `coupon` unlocks. A coupon that takes nothing off is left
+/// out, with the reason in [`Pricing::coupon_note`].
+pub(crate) async fn price_with_promotions(
+    tx: &TxRepositories,
+    coupon: Option<&str>,
+    user_id: Option<i64>,
+    lines: &[PricingLine],
+    shipping: f64,
+) -> Result<Pricing, DomainError> {
+    let now = Utc::now();
+    let codeless: Vec<_> = tx.promotions.list().await?.into_iter().filter(|p| p.code.is_none()).collect();
+    let uses = tx.promotions.uses_by_promotion(None).await?;
+    let user_uses = match user_id {
+        Some(user_id) if codeless.iter().any(|p| p.max_uses_per_user.is_some()) => {
+            Some(tx.promotions.uses_by_promotion(Some(user_id)).await?)
+        }
+        _ => None,
+    };
+    let mut candidates: Vec<_> = codeless
+        .into_iter()
+        .filter(|p| {
+            let user_uses = user_uses.as_ref().map(|u| count(u, p.id));
+            p.unusable(now, count(&uses, p.id), user_uses).is_none()
+        })
+        .collect();
+
+    let mut coupon_note = None;
+    let mut coupon_id = None;
+    if let Some(code) = coupon {
+        match tx.promotions.by_code(code).await? {
+            None => coupon_note = Some(format!("coupon {code} does not exist")),
+            Some(promotion) => match unusable(tx, &promotion, user_id, now).await? {
+                Some(reason) => coupon_note = Some(format!("coupon {code} {reason}")),
+                None => {
+                    coupon_id = Some(promotion.id);
+                    candidates.push(promotion);
+                }
+            },
+        }
+    }
+
+    let (mut pricing, skipped) = apply(&candidates, lines, shipping);
+    if let Some((_, reason)) = skipped.into_iter().find(|(id, _)| Some(*id) == coupon_id) {
+        coupon_note = Some(format!("coupon {} {reason}", coupon.unwrap_or_default()));
+    }
+    pricing.coupon_note = coupon_note;
+    Ok(pricing)
+}
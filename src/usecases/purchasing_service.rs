@@ -0,0 +1,342 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use chrono::Utc;
+
+use crate::domain::cache::{CacheInvalidator, Invalidation, NoCache};
+use crate::domain::event::DomainEvent;
+use crate::domain::inventory::{InventoryRepository, MovementKind, StockMovement};
+use crate::domain::product::ProductRepository;
+use crate::domain::purchasing::{PurchaseOrder, PurchaseOrderLine, PurchaseOrderStatus, PurchasingRepository, Supplier, SupplierProduct};
+use crate::domain::uow::{TxRepositories, UnitOfWork};
+use crate::domain::DomainError;
+use crate::usecases::inventory_service::{default_warehouse, record_movement, unknown_warehouse};
+
+/// A line of a new purchase order; without a unit cost it is ordered at the
+/// supplier's cost price.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewPurchaseOrderLine {
+    pub product_id: i64,
+    pub quantity: i32,
+    pub unit_cost: Option<f64>,
+}
+
+/// Goods that arrived against a purchase order line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReceivedLine {
+    pub product_id: i64,
+    pub quantity: i32,
+}
+
+#[derive(Clone)]
+pub struct PurchasingService {
+    repo: Arc<dyn PurchasingRepository>,
+    inventory: Arc<dyn InventoryRepository>,
+    products: Arc<dyn ProductRepository>,
+    uow: Arc<dyn UnitOfWork>,
+    cache: Arc<dyn CacheInvalidator>,
+}
+
+impl PurchasingService {
+    pub fn new(
+        repo: Arc<dyn PurchasingRepository>,
+        inventory: Arc<dyn InventoryRepository>,
+        products: Arc<dyn ProductRepository>,
+        uow: Arc<dyn UnitOfWork>,
+    ) -> Self {
+        Self { repo, inventory, products, uow, cache: Arc::new(NoCache) }
+    }
+
+    /// Report stock changes to `cache`, for a cached product repository.
+    pub fn with_cache_invalidator(mut self, cache: Arc<dyn CacheInvalidator>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Add a supplier. Names are unique.
+    pub async fn create_supplier(&self, name: String, email: Option<String>) -> Result<Supplier, DomainError> {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err(DomainError::Validation("name is required".into()));
+        }
+        let email = email.map(|e| e.trim().to_string()).filter(|e| !e.is_empty());
+        self.uow
+            .transaction(move |tx| {
+                let (name, email) = (name.clone(), email.clone());
+                Box::pin(async move {
+                    if tx.purchasing.suppliers().await?.iter().any(|s| s.name == name) {
+                        return Err(DomainError::Validation(format!("supplier {name:?} already exists")));
+                    }
+                    let supplier = Supplier { id: 0, name, email };
+                    let id = tx.purchasing.create_supplier(supplier.clone()).await?;
+                    Ok(Supplier { id, ..supplier })
+                })
+            })
+            .await
+    }
+
+    /// By id.
+    pub async fn suppliers(&self) -> Result<Vec<Supplier>, DomainError> {
+        self.repo.suppliers().await
+    }
+
+    /// Record, or replace, what the supplier sells the product as and for
+    /// how much.
+    pub async fn set_supplier_product(
+        &self,
+        supplier_id: i64,
+        product_id: i64,
+        sku: String,
+        cost_price: f64,
+    ) -> Result<SupplierProduct, DomainError> {
+        let sku = sku.trim().to_string();
+        if sku.is_empty() {
+            return Err(DomainError::Validation("sku is required".into()));
+        }
+        if !cost_price.is_finite() || cost_price < 0.0 {
+            return Err(DomainError::Validation("cost price cannot be negative".into()));
+        }
+        self.uow
+            .transaction(move |tx| {
+                let sku = sku.clone();
+                Box::pin(async move {
+                    tx.purchasing.supplier(supplier_id).await?.ok_or(DomainError::NotFound)?;
+                    if tx.products.get_by_product_id(product_id).await?.is_none() {
+                        return Err(DomainError::Validation(format!("no product {product_id}")));
+                    }
+                    let taken = tx
+                        .purchasing
+                        .supplier_products(supplier_id)
+                        .await?
+                        .iter()
+                        .any(|l| l.product_id != product_id && l.sku == sku);
+                    if taken {
+                        return Err(DomainError::Validation(format!("sku {sku:?} is already used by this supplier")));
+                    }
+                    tx.purchasing.set_supplier_product(SupplierProduct { supplier_id, product_id, sku, cost_price }).await
+                })
+            })
+            .await
+    }
+
+    /// The supplier's products, by product id.
+    pub async fn supplier_products(&self, supplier_id: i64) -> Result<Vec<SupplierProduct>, DomainError> {
+        if self.repo.supplier(supplier_id).await?.is_none() {
+            return Err(DomainError::NotFound);
+        }
+        self.repo.supplier_products(supplier_id).await
+    }
+
+    /// Draw up a purchase order on behalf of `actor_id`. Every product must
+    /// be one the supplier sells, and appear once.
+    pub async fn create_order(
+        &self,
+        supplier_id: i64,
+        lines: Vec<NewPurchaseOrderLine>,
+        actor_id: i64,
+    ) -> Result<PurchaseOrder, DomainError> {
+        if lines.is_empty() {
+            return Err(DomainError::Validation("a purchase order needs at least one line".into()));
+        }
+        let mut seen = HashSet::new();
+        for line in &lines {
+            if line.quantity <= 0 {
+                return Err(DomainError::Validation(format!("invalid quantity {} for product {}", line.quantity, line.product_id)));
+            }
+            if line.unit_cost.is_some_and(|c| !c.is_finite() || c < 0.0) {
+                return Err(DomainError::Validation("unit cost cannot be negative".into()));
+            }
+            if !seen.insert(line.product_id) {
+                return Err(DomainError::Validation(format!("product {} is on the order twice", line.product_id)));
+            }
+        }
+
+        self.uow
+            .transaction(move |tx| {
+                let lines = lines.clone();
+                Box::pin(async move {
+                    if tx.purchasing.supplier(supplier_id).await?.is_none() {
+                        return Err(DomainError::Validation(format!("no supplier {supplier_id}")));
+                    }
+                    let terms = tx.purchasing.supplier_products(supplier_id).await?;
+                    let lines = lines
+                        .into_iter()
+                        .map(|line| {
+                            let link = terms.iter().find(|l| l.product_id == line.product_id).ok_or_else(|| {
+                                DomainError::Validation(format!("supplier {supplier_id} does not sell product {}", line.product_id))
+                            })?;
+                            Ok(PurchaseOrderLine {
+                                product_id: line.product_id,
+                                quantity: line.quantity,
+                                received: 0,
+                                unit_cost: line.unit_cost.unwrap_or(link.cost_price),
+                            })
+                        })
+                        .collect::<Result<Vec<_>, DomainError>>()?;
+                    let order = PurchaseOrder {
+                        id: 0,
+                        supplier_id,
+                        status: PurchaseOrderStatus::Draft,
+                        lines,
+                        created_by: Some(actor_id),
+                        created_at: Utc::now(),
+                    };
+                    let id = tx.purchasing.create_order(order).await?;
+                    tx.purchasing.order(id).await?.ok_or(DomainError::NotFound)
+                })
+            })
+            .await
+    }
+
+    pub async fn order(&self, id: i64) -> Result<PurchaseOrder, DomainError> {
+        self.repo.order(id).await?.ok_or(DomainError::NotFound)
+    }
+
+    /// Newest first.
+    pub async fn orders(&self) -> Result<Vec<PurchaseOrder>, DomainError> {
+        self.repo.orders().await
+    }
+
+    /// Mark a draft as sent to the supplier; from then on goods can be
+    /// received against it.
+    pub async fn send(&self, id: i64) -> Result<PurchaseOrder, DomainError> {
+        self.uow
+            .transaction(move |tx| {
+                Box::pin(async move {
+                    let order = tx.purchasing.order(id).await?.ok_or(DomainError::NotFound)?;
+                    change_status(tx, &order, PurchaseOrderStatus::Sent).await?;
+                    tx.purchasing.order(id).await?.ok_or(DomainError::NotFound)
+                })
+            })
+            .await
+    }
+
+    /// Book goods that arrived against a sent order into the warehouse (the
+    /// default one if `None`) on behalf of `actor_id`: a receipt per line,
+    /// and the product's average cost weighted with the line's unit cost.
+    /// The order is received once every line is, partially received until
+    /// then. No line may take in more than is outstanding.
+    pub async fn receive(
+        &self,
+        id: i64,
+        warehouse_id: Option<i64>,
+        lines: Vec<ReceivedLine>,
+        actor_id: i64,
+    ) -> Result<PurchaseOrder, DomainError> {
+        if lines.is_empty() {
+            return Err(DomainError::Validation("nothing to receive".into()));
+        }
+        let mut seen = HashSet::new();
+        for line in &lines {
+            if line.quantity <= 0 {
+                return Err(DomainError::Validation(format!("invalid quantity {} for product {}", line.quantity, line.product_id)));
+            }
+            if !seen.insert(line.product_id) {
+                return Err(DomainError::Validation(format!("product {} is received twice", line.product_id)));
+            }
+        }
+
+        let product_ids: Vec<i64> = lines.iter().map(|l| l.product_id).collect();
+        let order = self
+            .uow
+            .transaction(move |tx| {
+                let lines = lines.clone();
+                Box::pin(async move {
+                    let order = tx.purchasing.order(id).await?.ok_or(DomainError::NotFound)?;
+                    if !order.status.is_receivable() {
+                        return Err(DomainError::Validation(format!("a {} purchase order cannot be received", order.status)));
+                    }
+                    let warehouse = match warehouse_id {
+                        Some(id) => tx.warehouses.get(id).await?.ok_or_else(|| unknown_warehouse(id))?,
+                        None => default_warehouse(tx).await?,
+                    };
+                    for received in lines {
+                        let product_id = received.product_id;
+                        let line = order
+                            .lines
+                            .iter()
+                            .find(|l| l.product_id == product_id)
+                            .ok_or_else(|| DomainError::Validation(format!("product {product_id} is not on purchase order {id}")))?;
+                        if received.quantity > line.outstanding() {
+                            return Err(DomainError::Validation(format!(
+                                "only {} of product {product_id} outstanding on purchase order {id}",
+                                line.outstanding()
+                            )));
+                        }
+
+                        let product = tx.products.get_by_product_id(product_id).await?.ok_or(DomainError::NotFound)?;
+                        let average_cost =
+                            weighted_average(tx.inventory.average_cost(product_id).await?, product.stock, line.unit_cost, received.quantity);
+                        let movement = StockMovement {
+                            id: 0,
+                            product_id,
+                            warehouse_id: warehouse.id,
+                            kind: MovementKind::Receipt,
+                            quantity: received.quantity,
+                            reason: Some(format!("purchase order {id}")),
+                            actor_id: Some(actor_id),
+                            order_id: None,
+                            created_at: Utc::now(),
+                        };
+                        record_movement(tx, movement)
+                            .await?
+                            .ok_or_else(|| DomainError::Unexpected("a receipt was refused".into()))?;
+                        if !tx.purchasing.receive(id, product_id, received.quantity).await? {
+                            return Err(DomainError::Unexpected(format!("purchase order {id} changed while receiving")));
+                        }
+                        tx.inventory.set_average_cost(product_id, average_cost).await?;
+                        let (kind, quantity, stock) = (MovementKind::Receipt, received.quantity, product.stock + received.quantity);
+                        let warehouse_id = warehouse.id;
+                        tx.outbox.append(&DomainEvent::StockAdjusted { product_id, warehouse_id, kind, quantity, stock }).await?;
+                    }
+
+                    let updated = tx.purchasing.order(id).await?.ok_or(DomainError::NotFound)?;
+                    let to = if updated.is_fully_received() {
+                        PurchaseOrderStatus::Received
+                    } else {
+                        PurchaseOrderStatus::PartiallyReceived
+                    };
+                    if to != updated.status {
+                        change_status(tx, &updated, to).await?;
+                    }
+                    tx.purchasing.order(id).await?.ok_or(DomainError::NotFound)
+                })
+            })
+            .await?;
+        for product_id in product_ids {
+            self.cache.invalidate(Invalidation::Product(product_id)).await;
+        }
+        Ok(order)
+    }
+
+    /// The product's weighted average cost, `None` before anything of it
+    /// was received.
+    pub async fn average_cost(&self, product_id: i64) -> Result<Option<f64>, DomainError> {
+        if self.products.get_by_product_id(product_id).await?.is_none() {
+            return Err(DomainError::NotFound);
+        }
+        self.inventory.average_cost(product_id).await
+    }
+}
+
+async fn change_status(tx: &TxRepositories, order: &PurchaseOrder, to: PurchaseOrderStatus) -> Result<(), DomainError> {
+    let from = order.status;
+    if !from.can_become(to) {
+        return Err(DomainError::Validation(format!("a purchase order cannot go from {from} to {to}")));
+    }
+    tx.purchasing.set_status(order.id, to).await?;
+    tx.outbox.append(&DomainEvent::PurchaseOrderStatusChanged { id: order.id, from, to }).await?;
+    Ok(())
+}
+
+/// The average cost of `stock` units at `average` and `quantity` more at
+/// `unit_cost`. Stock that cost nothing we know of (none on record, or
+/// none left) does not weigh in.
+fn weighted_average(average: Option<f64>, stock: i32, unit_cost: f64, quantity: i32) -> f64 {
+    match average {
+        Some(average) if stock > 0 => {
+            (average * f64::from(stock) + unit_cost * f64::from(quantity)) / f64::from(stock + quantity)
+        }
+        _ => unit_cost,
+    }
+}
@@ -1,17 +1,28 @@
 use std::sync::Arc;
 use crate::domain::DomainError;
+use crate::domain::event::DomainEvent;
+use crate::domain::page::Page;
+use crate::domain::uow::UnitOfWork;
 use crate::domain::user::{User, UserRepository};
 use crate::infra::crypto::verify_password;
 use crate::infra::jwt::sign_token;
 
+/// A successful login.
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub user_id: i64,
+    pub token: String,
+}
+
 #[derive(Clone)]
 pub struct UserService {
     repo: Arc<dyn UserRepository>,
+    uow: Arc<dyn UnitOfWork>,
 }
 
 impl UserService {
-    pub fn new(repo: Arc<dyn UserRepository>) -> Self {
-        Self { repo }
+    pub fn new(repo: Arc<dyn UserRepository>, uow: Arc<dyn UnitOfWork>) -> Self {
+        Self { repo, uow }
     }
 
     pub async fn create_user(&self, username: String, password: String) -> Result<i64, DomainError> {
@@ -21,10 +32,20 @@ impl UserService {
         if password.trim().is_empty() {
             return Err(DomainError::Validation("password is required".into()));
         }
-        if self.repo.get_by_username(username.clone()).await?.is_some() {
-            return Err(DomainError::Validation("username already exists".into()));
-        }
-        self.repo.create(username, password).await
+        // concurrent sign-ups with the same name serialize on the check
+        self.uow
+            .transaction(move |tx| {
+                let (username, password) = (username.clone(), password.clone());
+                Box::pin(async move {
+                    if tx.users.get_by_username(username.clone()).await?.is_some() {
+                        return Err(DomainError::Validation("username already exists".into()));
+                    }
+                    let id = tx.users.create(username.clone(), password).await?;
+                    tx.outbox.append(&DomainEvent::UserCreated { id, username }).await?;
+                    Ok(id)
+                })
+            })
+            .await
     }
 
     pub async fn get_user(&self, id: i64) -> Result<User, DomainError> {
@@ -32,8 +53,8 @@ impl UserService {
         user.ok_or(DomainError::NotFound)
     }
 
-    pub async fn get_all_users(&self) -> Result<Vec<User>, DomainError> {
-        self.repo.get_all_users().await
+    pub async fn get_all_users(&self, page: Page) -> Result<Vec<User>, DomainError> {
+        self.repo.get_all_users(page).await
     }
 
     pub async fn update_user(&self, id: i64, username: String, password: String) -> Result<User, DomainError> {
@@ -43,29 +64,97 @@ impl UserService {
         if password.trim().is_empty() {
             return Err(DomainError::Validation("password is required".into()));
         }
-        if self.repo.get_by_id(id).await?.is_none() {
-            return Err(DomainError::NotFound);
-        }
-        self.repo.update(id, username, password).await
+        self.uow
+            .transaction(move |tx| {
+                let (username, password) = (username.clone(), password.clone());
+                Box::pin(async move {
+                    if tx.users.get_by_id(id).await?.is_none() {
+                        return Err(DomainError::NotFound);
+                    }
+                    let user = tx.users.update(id, username, password).await?;
+                    tx.outbox
+                        .append(&DomainEvent::UserUpdated { id, username: user.username.clone() })
+                        .await?;
+                    Ok(user)
+                })
+            })
+            .await
     }
 
     pub async fn delete_user(&self, id: i64) -> Result<(), DomainError> {
-        let user = self.repo.get_by_id(id).await?;
-        if user.is_none() {
-            return Err(DomainError::NotFound);
+        self.uow
+            .transaction(move |tx| {
+                Box::pin(async move {
+                    let user = tx.users.get_by_id(id).await?;
+                    if user.is_none() {
+                        return Err(DomainError::NotFound);
+                    }
+                    tx.users.delete(id).await?;
+                    tx.outbox.append(&DomainEvent::UserDeleted { id }).await?;
+                    Ok(())
+                })
+            })
+            .await
+    }
+
+    /// Create the first admin; refused once any admin exists.
+    pub async fn create_admin(&self, username: String, password: String) -> Result<i64, DomainError> {
+        if username.trim().is_empty() {
+            return Err(DomainError::Validation("username is required".into()));
+        }
+        if password.trim().is_empty() {
+            return Err(DomainError::Validation("password is required".into()));
+        }
+        self.uow
+            .transaction(move |tx| {
+                let (username, password) = (username.clone(), password.clone());
+                Box::pin(async move {
+                    if tx.users.get_all_users(Page::ALL).await?.iter().any(|u| u.admin) {
+                        return Err(DomainError::Validation("an admin already exists".into()));
+                    }
+                    if tx.users.get_by_username(username.clone()).await?.is_some() {
+                        return Err(DomainError::Validation("username already exists".into()));
+                    }
+                    let id = tx.users.create(username.clone(), password).await?;
+                    tx.users.set_admin(id, true).await?;
+                    tx.outbox.append(&DomainEvent::UserCreated { id, username }).await?;
+                    Ok(id)
+                })
+            })
+            .await
+    }
+
+    pub async fn reset_password(&self, username: String, password: String) -> Result<User, DomainError> {
+        if password.trim().is_empty() {
+            return Err(DomainError::Validation("password is required".into()));
         }
-        self.repo.delete(id).await
+        self.uow
+            .transaction(move |tx| {
+                let (username, password) = (username.clone(), password.clone());
+                Box::pin(async move {
+                    let Some(user) = tx.users.get_by_username(username.clone()).await? else {
+                        return Err(DomainError::NotFound);
+                    };
+                    let user = tx.users.update(user.id, username, password).await?;
+                    tx.outbox
+                        .append(&DomainEvent::UserUpdated { id: user.id, username: user.username.clone() })
+                        .await?;
+                    Ok(user)
+                })
+            })
+            .await
     }
 
-    pub async fn login(&self, username: String, password: String) -> Result<String, DomainError> {
+    pub async fn login(&self, username: String, password: String) -> Result<Session, DomainError> {
         let user = match self.repo.get_by_username(username).await? {
             Some(u) => u,
             None => return Err(DomainError::Unauthorized),
         };
 
-        if !verify_password(password, user.password.clone()).await? {
+        if !verify_password(password, user.password.into_inner()).await? {
             return Err(DomainError::Unauthorized);
         }
-        sign_token(user.id, user.username)
+        let token = sign_token(user.id, user.username)?;
+        Ok(Session { user_id: user.id, token })
     }
 }
\ No newline at end of file